@@ -0,0 +1,111 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use syn::{parse_macro_input, LitStr};
+
+pub fn include_ui(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+
+    match collect_files(&lit.value()) {
+        Ok((source_dir, root, files)) => {
+            let source_dir = source_dir.to_string_lossy().to_string();
+            let entries = files.iter().map(|(relative, absolute)| {
+                let relative = relative.to_string_lossy().to_string();
+                let absolute = absolute.to_string_lossy().to_string();
+                // `include_str!` makes cargo rebuild when any of the files changes.
+                quote! { (#relative, include_str!(#absolute)) }
+            });
+
+            quote! {
+                ::gpui_component::json_ui::EmbeddedUiMap::from_entries(
+                    #source_dir,
+                    #root,
+                    &[#(#entries),*],
+                )
+            }
+            .into()
+        }
+        Err(err) => syn::Error::new(lit.span(), err).to_compile_error().into(),
+    }
+}
+
+/// Read the root document and every file it transitively references with `$ref`.
+///
+/// Returns the root's directory, the root's file name, and every file keyed by its
+/// path relative to that directory, as `$ref`s are resolved relative to it.
+fn collect_files(path: &str) -> Result<(PathBuf, String, BTreeMap<PathBuf, PathBuf>), String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| "CARGO_MANIFEST_DIR is not set".to_string())?;
+    let root_path = normalize(&Path::new(&manifest_dir).join(path));
+    let source_dir = root_path
+        .parent()
+        .ok_or_else(|| format!("invalid path `{}`", path))?
+        .to_path_buf();
+    let root = root_path
+        .file_name()
+        .ok_or_else(|| format!("invalid path `{}`", path))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut files = BTreeMap::new();
+    let mut pending = vec![PathBuf::from(&root)];
+    while let Some(relative) = pending.pop() {
+        if files.contains_key(&relative) {
+            continue;
+        }
+
+        let absolute = source_dir.join(&relative);
+        let content = std::fs::read_to_string(&absolute)
+            .map_err(|err| format!("failed to read {}: {}", absolute.display(), err))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|err| format!("failed to parse {}: {}", absolute.display(), err))?;
+
+        let mut refs = vec![];
+        collect_refs(&value, &mut refs);
+        pending.extend(refs.into_iter().map(|reference| normalize(Path::new(&reference))));
+        files.insert(relative, absolute);
+    }
+
+    Ok((source_dir, root, files))
+}
+
+fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(obj) => {
+            for (key, value) in obj {
+                match value {
+                    // References interpolated from `${..}` props can't be known at compile time.
+                    Value::String(reference) if key == "$ref" && !reference.contains("${") => {
+                        refs.push(reference.clone());
+                    }
+                    _ => collect_refs(value, refs),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_refs(item, refs);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolve `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
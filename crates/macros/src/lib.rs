@@ -1,8 +1,22 @@
 use proc_macro::TokenStream;
 
 mod derive_into_plot;
+mod include_ui;
 
 #[proc_macro_derive(IntoPlot)]
 pub fn derive_into_plot(input: TokenStream) -> TokenStream {
     derive_into_plot::derive_into_plot(input)
 }
+
+/// Embed a JSON UI document, and every file it references with `$ref`, into the binary.
+///
+/// The path is relative to the crate's `Cargo.toml`, and the macro evaluates to a
+/// `gpui_component::json_ui::EmbeddedUiMap`.
+///
+/// ```ignore
+/// let canvas = JsonCanvas::from_embedded(include_ui!("assets/ui/main.json"));
+/// ```
+#[proc_macro]
+pub fn include_ui(input: TokenStream) -> TokenStream {
+    include_ui::include_ui(input)
+}
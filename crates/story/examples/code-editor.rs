@@ -8,14 +8,14 @@ use gpui_component::{
     h_flex,
     highlighter::{Diagnostic, DiagnosticSeverity, Language, LanguageConfig, LanguageRegistry},
     input::{
-        self, CodeActionProvider, CompletionProvider, InputEvent, InputState, Position, Rope,
-        RopeExt, TabSize, TextInput,
+        self, CodeActionProvider, CompletionProvider, HoverProvider, InputEvent, InputState,
+        Position, Rope, RopeExt, TabSize, TextInput,
     },
     v_flex, ActiveTheme, ContextModal, IconName, IndexPath, Selectable, Sizable,
 };
 use lsp_types::{
-    CodeAction, CodeActionKind, CompletionContext, CompletionItem, CompletionResponse, TextEdit,
-    WorkspaceEdit,
+    CodeAction, CodeActionKind, CompletionContext, CompletionItem, CompletionResponse, Hover,
+    HoverContents, MarkupContent, MarkupKind, TextEdit, WorkspaceEdit,
 };
 use story::Assets;
 
@@ -173,6 +173,56 @@ impl CompletionProvider for ExampleLspStore {
     }
 }
 
+impl HoverProvider for ExampleLspStore {
+    fn hover(
+        &self,
+        rope: &Rope,
+        offset: usize,
+        _: &mut Window,
+        _: &mut Context<InputState>,
+    ) -> Task<Result<Option<Hover>>> {
+        let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+        let start = offset
+            - rope
+                .reversed_chars_at(offset)
+                .take_while(is_word)
+                .map(char::len_utf8)
+                .sum::<usize>();
+        let end = offset
+            + rope
+                .chars_at(offset)
+                .take_while(is_word)
+                .map(char::len_utf8)
+                .sum::<usize>();
+        let word = rope.slice(start..end).to_string();
+
+        // Show the documentation of the completion item with the same label.
+        let hover = self
+            .completions
+            .iter()
+            .find(|item| item.label == word)
+            .map(|item| {
+                let mut value = format!("```\n{}\n```", item.label);
+                if let Some(detail) = &item.detail {
+                    value.push_str(&format!("\n\n{}", detail));
+                }
+
+                Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value,
+                    }),
+                    range: Some(lsp_types::Range::new(
+                        rope.offset_to_position(start),
+                        rope.offset_to_position(end),
+                    )),
+                }
+            });
+
+        Task::ready(Ok(hover))
+    }
+}
+
 impl CodeActionProvider for ExampleLspStore {
     fn id(&self) -> SharedString {
         "LspStore".into()
@@ -443,6 +493,7 @@ impl Example {
             let mut editor = InputState::new(window, cx)
                 .code_editor(default_language.0.name().to_string())
                 .line_number(true)
+                .minimap(true)
                 .tab_size(TabSize {
                     tab_size: 4,
                     hard_tabs: false,
@@ -452,6 +503,7 @@ impl Example {
                 .placeholder("Enter your code here...");
 
             editor.set_completion_provider(Some(Rc::new(lsp_store.clone())), cx);
+            editor.set_hover_provider(Some(Rc::new(lsp_store.clone())), cx);
             editor.add_code_action_provider(Rc::new(lsp_store.clone()), cx);
             editor.add_code_action_provider(Rc::new(TextConvertor), cx);
 
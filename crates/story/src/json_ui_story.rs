@@ -1,7 +1,10 @@
 use gpui::*;
 
 use gpui_component::{
-    button::Button, h_flex, json_ui::JsonCanvas, v_flex, IconName,
+    button::Button,
+    h_flex,
+    json_ui::{include_ui, JsonCanvas},
+    v_flex, IconName,
 };
 
 use crate::section;
@@ -10,7 +13,7 @@ actions!(json_ui_story, [ReloadUI]);
 
 pub struct JsonUIStory {
     focus_handle: gpui::FocusHandle,
-    json_canvas: JsonCanvas,
+    json_canvas: Entity<JsonCanvas>,
 }
 
 impl JsonUIStory {
@@ -20,67 +23,19 @@ impl JsonUIStory {
 
     fn new(cx: &mut Context<Self>) -> Self {
 
-        let mut json_canvas = {
-            // Dev mode: load from absolute path using CARGO_MANIFEST_DIR
-            let json_path = format!(
-                "{}/assets/json_ui_examples/complex.json",
-                env!("CARGO_MANIFEST_DIR")
-            );
-            let mut jc = JsonCanvas::new(&json_path);
+        let json_canvas = cx.new(|cx| {
+            // Bundled into the binary; debug builds read (and hot reload) the files on disk.
+            let mut jc =
+                JsonCanvas::from_embedded(include_ui!("assets/json_ui_examples/complex.json"));
             if let Err(e) = jc.load() {
-                eprintln!("Error loading JSON UI from file: {}", e);
-                eprintln!("Tried path: {}", json_path);
-                eprintln!("Falling back to embedded JSON demo...");
-
-                // Fallback to embedded JSON demo
-                let fallback_json = r#"{
-                    "type": "column",
-                    "props": {
-                        "padding": 20,
-                        "backgroundColor": "black"
-                    },
-                    "children": [
-                        {
-                            "type": "h1",
-                            "props": {
-                                "color": "blue"
-                            },
-                            "children": ["JSON UI Demo (Fallback)"]
-                        },
-                        {
-                            "type": "text",
-                            "props": {
-                                "content": "This is a fallback JSON UI demo since the file could not be loaded."
-                            }
-                        },
-                        {
-                            "type": "row",
-                            "props": {
-                                "margin": 10
-                            },
-                            "children": [
-                                {
-                                    "type": "button",
-                                    "children": ["Demo Button"]
-                                },
-                                {
-                                    "type": "input",
-                                    "props": {
-                                        "placeholder": "Test input..."
-                                    }
-                                }
-                            ]
-                        }
-                    ]
-                }"#;
-                let _ = jc.load_from_string(fallback_json);
+                eprintln!("Error loading JSON UI: {}", e);
             }
-            jc
-        };
 
-        if let Err(e) = json_canvas.start_hot_reload() {
-            eprintln!("Error starting hot reload: {}", e);
-        }
+            if let Err(e) = jc.start_hot_reload(cx) {
+                eprintln!("Error starting hot reload: {}", e);
+            }
+            jc
+        });
 
         Self {
             focus_handle: cx.focus_handle(),
@@ -89,10 +44,12 @@ impl JsonUIStory {
     }
 
     fn reload_ui(&mut self, _action: &ReloadUI, _window: &mut Window, cx: &mut Context<Self>) {
-        if let Err(e) = self.json_canvas.reload() {
-            eprintln!("Error reloading JSON UI: {}", e);
-        }
-        cx.notify();
+        self.json_canvas.update(cx, |canvas, cx| {
+            if let Err(e) = canvas.reload() {
+                eprintln!("Error reloading JSON UI: {}", e);
+            }
+            cx.notify();
+        });
     }
 }
 
@@ -180,17 +137,9 @@ impl Render for JsonUIStory {
                                             .child("📄 File: complex.json")
                                     )
                                     .child(
-                                        if self.json_canvas.is_loaded() {
-                                            // Render the JSON UI content
-                                            use gpui_component::json_ui::UiRenderer;
-                                            if let Some(ui) = self.json_canvas.get_ui() {
-                                                UiRenderer::render_component_generic(ui, cx)
-                                            } else {
-                                                div()
-                                                    .p_4()
-                                                    .child("Loading JSON UI...")
-                                                    .into_any_element()
-                                            }
+                                        if self.json_canvas.read(cx).is_loaded() {
+                                            // The canvas reloads itself when any watched file changes.
+                                            self.json_canvas.clone().into_any_element()
                                         } else {
                                             div()
                                                 .gap_2()
@@ -212,7 +161,7 @@ impl Render for JsonUIStory {
                             .child("   • assets/json_ui_examples/header_component.json")
                             .child("   • assets/json_ui_examples/card_component.json")
                             .child("2. Change colors, text, or add components")
-                            .child("3. Save the file and click Reload to see changes")
+                            .child("3. Save the file and the preview reloads automatically")
                             .child("")
                             .child("✅ JSON-based UI definition")
                             .child("✅ Component references with $ref")
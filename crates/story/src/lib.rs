@@ -45,6 +45,7 @@ mod themes;
 mod title_bar;
 mod toggle_story;
 mod tooltip_story;
+mod tree_story;
 mod virtual_list_story;
 mod webview_story;
 mod welcome_story;
@@ -104,6 +105,7 @@ pub use textarea_story::TextareaStory;
 pub use title_bar::AppTitleBar;
 pub use toggle_story::ToggleStory;
 pub use tooltip_story::TooltipStory;
+pub use tree_story::TreeStory;
 use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _};
 pub use virtual_list_story::VirtualListStory;
 pub use webview_story::WebViewStory;
@@ -658,6 +660,7 @@ impl StoryState {
             "TableStory" => story!(TableStory),
             "LabelStory" => story!(LabelStory),
             "TooltipStory" => story!(TooltipStory),
+            "TreeStory" => story!(TreeStory),
             "WebViewStory" => story!(WebViewStory),
             "AccordionStory" => story!(AccordionStory),
             "SidebarStory" => story!(SidebarStory),
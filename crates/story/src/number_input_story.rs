@@ -160,6 +160,7 @@ impl NumberInputStory {
             }
             InputEvent::Focus => println!("Focus"),
             InputEvent::Blur => println!("Blur"),
+            InputEvent::GoToDefinition { .. } => {}
         }
     }
 
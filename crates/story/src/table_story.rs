@@ -6,9 +6,9 @@ use std::{
 
 use fake::Fake;
 use gpui::{
-    div, prelude::FluentBuilder as _, Action, AnyElement, App, AppContext, ClickEvent, Context,
-    Entity, Focusable, InteractiveElement, IntoElement, ParentElement, Render, SharedString,
-    StatefulInteractiveElement, Styled, TextAlign, Timer, Window,
+    div, prelude::FluentBuilder as _, Action, AnyElement, App, AppContext, ClickEvent,
+    ClipboardItem, Context, Entity, Focusable, InteractiveElement, IntoElement, ParentElement,
+    Render, SharedString, StatefulInteractiveElement, Styled, TextAlign, Timer, Window,
 };
 use gpui_component::{
     button::Button,
@@ -18,7 +18,10 @@ use gpui_component::{
     input::{InputEvent, InputState, TextInput},
     label::Label,
     popup_menu::{PopupMenu, PopupMenuExt},
-    table::{Column, ColumnFixed, ColumnSort, Table, TableDelegate, TableEvent},
+    table::{
+        Aggregate, CellValue, Column, ColumnFilterKind, ColumnFixed, ColumnSort, ExportFormat,
+        Table, TableDelegate, TableEvent,
+    },
     v_flex, ActiveTheme as _, Selectable, Sizable as _, Size, StyleSized as _, StyledExt,
 };
 use serde::{Deserialize, Serialize};
@@ -31,6 +34,10 @@ struct ChangeSize(Size);
 #[action(namespace = table_story, no_json)]
 struct OpenDetail(usize);
 
+#[derive(Action, Clone, PartialEq, Eq, Deserialize)]
+#[action(namespace = table_story, no_json)]
+struct ExportTable(ExportFormat);
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct Counter {
     symbol: SharedString,
@@ -180,6 +187,8 @@ struct StockTableDelegate {
     eof: bool,
     visible_rows: Range<usize>,
     visible_cols: Range<usize>,
+    /// The editor of the name cell in editing.
+    name_editor: Option<Entity<InputState>>,
 }
 
 impl StockTableDelegate {
@@ -193,23 +202,36 @@ impl StockTableDelegate {
                     .fixed(ColumnFixed::Left)
                     .resizable(false),
                 Column::new("market", "Market")
-                    .width(60.)
+                    .width(80.)
                     .fixed(ColumnFixed::Left)
-                    .resizable(false),
+                    .resizable(false)
+                    .filter(ColumnFilterKind::Values),
                 Column::new("name", "Name")
                     .width(180.)
-                    .fixed(ColumnFixed::Left),
+                    .fixed(ColumnFixed::Left)
+                    .filter(ColumnFilterKind::Text),
                 Column::new("symbol", "Symbol")
                     .width(100.)
                     .fixed(ColumnFixed::Left)
                     .sortable(),
-                Column::new("price", "Price").sortable().text_right().p_0(),
-                Column::new("change", "Chg").sortable().text_right().p_0(),
+                Column::new("price", "Price")
+                    .sortable()
+                    .text_right()
+                    .p_0()
+                    .filter(ColumnFilterKind::Number)
+                    .aggregate(Aggregate::Avg),
+                Column::new("change", "Chg")
+                    .sortable()
+                    .text_right()
+                    .p_0()
+                    .aggregate(Aggregate::Max),
                 Column::new("change_percent", "Chg%")
                     .sortable()
                     .text_right()
                     .p_0(),
-                Column::new("volume", "Volume").p_0(),
+                Column::new("volume", "Volume")
+                    .p_0()
+                    .aggregate(Aggregate::Sum),
                 Column::new("turnover", "Turnover").p_0(),
                 Column::new("market_cap", "Market Cap").p_0(),
                 Column::new("ttm", "TTM").p_0(),
@@ -259,6 +281,7 @@ impl StockTableDelegate {
             eof: false,
             visible_cols: Range::default(),
             visible_rows: Range::default(),
+            name_editor: None,
         }
     }
 
@@ -337,6 +360,24 @@ impl TableDelegate for StockTableDelegate {
         &self.columns[col_ix]
     }
 
+    fn cell_value(&self, row_ix: usize, col_ix: usize, _: &App) -> CellValue {
+        let Some(stock) = self.stocks.get(row_ix) else {
+            return CellValue::Empty;
+        };
+
+        match self.columns[col_ix].key.as_ref() {
+            "id" => CellValue::from(stock.id as i64),
+            "market" => CellValue::from(stock.counter.market.clone()),
+            "name" => CellValue::from(stock.counter.name.clone()),
+            "symbol" => CellValue::from(stock.counter.symbol.clone()),
+            "price" => CellValue::from(stock.price),
+            "change" => CellValue::from(stock.change),
+            "change_percent" => CellValue::from(stock.change_percent),
+            "volume" => CellValue::from(stock.volume),
+            _ => CellValue::Empty,
+        }
+    }
+
     fn render_th(
         &self,
         col_ix: usize,
@@ -495,6 +536,76 @@ impl TableDelegate for StockTableDelegate {
         }
     }
 
+    fn is_cell_editable(&self, _: usize, col_ix: usize, _: &App) -> bool {
+        self.columns.get(col_ix).map(|col| col.key.as_ref()) == Some("name")
+    }
+
+    fn start_cell_edit(
+        &mut self,
+        row_ix: usize,
+        _: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> bool {
+        let Some(stock) = self.stocks.get(row_ix) else {
+            return false;
+        };
+
+        let name = stock.counter.name.clone();
+        let editor = cx.new(|cx| InputState::new(window, cx).default_value(name));
+        editor.focus_handle(cx).focus(window);
+        self.name_editor = Some(editor);
+        true
+    }
+
+    fn render_cell_editor(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        match &self.name_editor {
+            Some(editor) => TextInput::new(editor).xsmall().into_any_element(),
+            None => self
+                .render_td(row_ix, col_ix, window, cx)
+                .into_any_element(),
+        }
+    }
+
+    fn commit_cell_edit(
+        &mut self,
+        row_ix: usize,
+        _: usize,
+        _: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Result<(), SharedString> {
+        let Some(editor) = self.name_editor.as_ref() else {
+            return Ok(());
+        };
+
+        let name = editor.read(cx).value().trim().to_string();
+        if name.is_empty() {
+            return Err("Name can't be empty.".into());
+        }
+
+        if let Some(stock) = self.stocks.get_mut(row_ix) {
+            stock.counter.name = name.into();
+        }
+        self.name_editor = None;
+        Ok(())
+    }
+
+    fn cancel_cell_edit(
+        &mut self,
+        _: usize,
+        _: usize,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) {
+        self.name_editor = None;
+    }
+
     fn move_column(
         &mut self,
         col_ix: usize,
@@ -598,7 +709,7 @@ impl super::Story for TableStory {
     }
 
     fn description() -> &'static str {
-        "A complex data table with selection, sorting, column moving, and loading more."
+        "A complex data table with selection, sorting, filtering, grouping, exporting, column moving, and loading more."
     }
 
     fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render + Focusable> {
@@ -693,8 +804,9 @@ impl TableStory {
                         return;
                     }
 
-                    self.table.update(cx, |table, _| {
+                    self.table.update(cx, |table, cx| {
                         table.delegate_mut().update_stocks(total_count);
+                        table.refresh(cx);
                     });
                     cx.notify();
                 }
@@ -762,6 +874,30 @@ impl TableStory {
         });
     }
 
+    fn on_export_table(&mut self, a: &ExportTable, _: &mut Window, cx: &mut Context<Self>) {
+        let mut buf = Vec::new();
+        if let Err(err) = self.table.read(cx).export(a.0, &mut buf, cx) {
+            println!("Failed to export table: {}", err);
+            return;
+        }
+
+        cx.write_to_clipboard(ClipboardItem::new_string(
+            String::from_utf8_lossy(&buf).to_string(),
+        ));
+    }
+
+    fn toggle_group_by_market(
+        &mut self,
+        checked: &bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let group_by = if *checked { vec![1] } else { vec![] };
+        self.table.update(cx, |table, cx| {
+            table.set_group_by(group_by, window, cx);
+        });
+    }
+
     fn toggle_refresh_data(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
         self.refresh_data = *checked;
         cx.notify();
@@ -781,6 +917,10 @@ impl TableStory {
             TableEvent::SelectColumn(ix) => println!("Select col: {}", ix),
             TableEvent::DoubleClickedRow(ix) => println!("Double clicked row: {}", ix),
             TableEvent::SelectRow(ix) => println!("Select row: {}", ix),
+            TableEvent::SelectionChanged(selection) => {
+                println!("Selection changed: {:?}", selection)
+            }
+            TableEvent::CellEdited(cell) => println!("Cell edited: {:?}", cell),
             TableEvent::MoveColumn(origin_idx, target_idx) => {
                 println!("Move col index: {} -> {}", origin_idx, target_idx);
             }
@@ -797,6 +937,7 @@ impl Render for TableStory {
 
         v_flex()
             .on_action(cx.listener(Self::on_change_size))
+            .on_action(cx.listener(Self::on_export_table))
             .size_full()
             .text_sm()
             .gap_4()
@@ -858,6 +999,12 @@ impl Render for TableStory {
                                 })
                             })),
                     )
+                    .child(
+                        Checkbox::new("group-by-market")
+                            .label("Group by Market")
+                            .selected(!table.group_by().is_empty())
+                            .on_click(cx.listener(Self::toggle_group_by_market)),
+                    )
                     .child(
                         Checkbox::new("refresh-data")
                             .label("Refresh Data")
@@ -896,6 +1043,21 @@ impl Render for TableStory {
                                 )
                             }),
                     )
+                    .child(
+                        Button::new("export")
+                            .outline()
+                            .small()
+                            .label("Copy as")
+                            .popup_menu(|menu, _, _| {
+                                menu.menu("CSV", Box::new(ExportTable(ExportFormat::Csv)))
+                                    .menu("TSV", Box::new(ExportTable(ExportFormat::Tsv)))
+                                    .menu(
+                                        "JSON Lines",
+                                        Box::new(ExportTable(ExportFormat::JsonLines)),
+                                    )
+                                    .menu("Markdown", Box::new(ExportTable(ExportFormat::Markdown)))
+                            }),
+                    )
                     .child(
                         Button::new("scroll-top")
                             .outline()
@@ -0,0 +1,310 @@
+use std::time::Duration;
+
+use gpui::{
+    div, App, AppContext, Context, Entity, Focusable, IntoElement, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, Timer, Window,
+};
+use gpui_component::{
+    checkbox::Checkbox,
+    h_flex,
+    label::Label,
+    tree::{DropPosition, TreeDelegate, TreeEvent, TreeView},
+    v_flex, ActiveTheme as _, Icon, IconName, Sizable as _,
+};
+
+struct FileNode {
+    name: SharedString,
+    is_dir: bool,
+    depth: usize,
+    parent: Option<usize>,
+    /// `None` if the children are not loaded yet.
+    children: Option<Vec<usize>>,
+}
+
+struct FileTreeDelegate {
+    nodes: Vec<FileNode>,
+    roots: Vec<usize>,
+    draggable: bool,
+}
+
+impl FileTreeDelegate {
+    fn new() -> Self {
+        let mut this = Self {
+            nodes: vec![],
+            roots: vec![],
+            draggable: true,
+        };
+        for name in ["src", "crates", "assets", "large (10000 files)"] {
+            let ix = this.push_node(name, true, None);
+            this.roots.push(ix);
+        }
+        for name in ["Cargo.toml", "README.md", "LICENSE"] {
+            let ix = this.push_node(name, false, None);
+            this.roots.push(ix);
+        }
+        this
+    }
+
+    fn push_node(
+        &mut self,
+        name: impl Into<SharedString>,
+        is_dir: bool,
+        parent: Option<usize>,
+    ) -> usize {
+        let depth = parent.map_or(0, |parent| self.nodes[parent].depth + 1);
+        self.nodes.push(FileNode {
+            name: name.into(),
+            is_dir,
+            depth,
+            parent,
+            children: if is_dir { None } else { Some(vec![]) },
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Generate the fake children of the directory.
+    fn generate_children(&mut self, ix: usize) {
+        if self.nodes[ix].children.is_some() {
+            return;
+        }
+
+        let (dirs, files) = match (ix, self.nodes[ix].depth) {
+            (3, _) => (0, 10000),
+            (_, depth) if depth < 3 => (3, 5),
+            _ => (0, 5),
+        };
+
+        let mut children = vec![];
+        for i in 0..dirs {
+            children.push(self.push_node(format!("folder-{}", i + 1), true, Some(ix)));
+        }
+        for i in 0..files {
+            children.push(self.push_node(format!("file-{}.rs", i + 1), false, Some(ix)));
+        }
+        self.nodes[ix].children = Some(children);
+    }
+
+    fn siblings_mut(&mut self, parent: Option<usize>) -> &mut Vec<usize> {
+        match parent {
+            Some(parent) => self.nodes[parent].children.get_or_insert_with(Vec::new),
+            None => &mut self.roots,
+        }
+    }
+
+    fn move_node(&mut self, ix: usize, parent: Option<usize>, position: usize) {
+        let old_parent = self.nodes[ix].parent;
+        self.siblings_mut(old_parent).retain(|child| *child != ix);
+
+        let siblings = self.siblings_mut(parent);
+        let position = position.min(siblings.len());
+        siblings.insert(position, ix);
+        self.nodes[ix].parent = parent;
+        self.update_depth(ix);
+    }
+
+    fn update_depth(&mut self, ix: usize) {
+        self.nodes[ix].depth = self.nodes[ix]
+            .parent
+            .map_or(0, |parent| self.nodes[parent].depth + 1);
+        for child in self.nodes[ix].children.clone().unwrap_or_default() {
+            self.update_depth(child);
+        }
+    }
+}
+
+impl TreeDelegate for FileTreeDelegate {
+    type Id = usize;
+
+    fn children(&self, parent: Option<&usize>, _: &App) -> Option<Vec<usize>> {
+        match parent {
+            Some(ix) => self.nodes[*ix].children.clone(),
+            None => Some(self.roots.clone()),
+        }
+    }
+
+    fn has_children(&self, ix: &usize, _: &App) -> bool {
+        self.nodes[*ix].is_dir
+    }
+
+    fn load_children(
+        &mut self,
+        ix: &usize,
+        _: &mut Window,
+        cx: &mut Context<TreeView<Self>>,
+    ) -> Task<()> {
+        let ix = *ix;
+        cx.spawn(async move |tree, cx| {
+            // Simulate the slow file system.
+            Timer::after(Duration::from_millis(500)).await;
+            _ = tree.update(cx, |tree, _| {
+                tree.delegate_mut().generate_children(ix);
+            });
+        })
+    }
+
+    fn label(&self, ix: &usize, _: &App) -> SharedString {
+        self.nodes[*ix].name.clone()
+    }
+
+    fn icon(&self, ix: &usize, expanded: bool, _: &App) -> Option<Icon> {
+        let icon = match (self.nodes[*ix].is_dir, expanded) {
+            (true, true) => IconName::FolderOpen,
+            (true, false) => IconName::FolderClosed,
+            (false, _) => IconName::BookOpen,
+        };
+        Some(Icon::new(icon).small())
+    }
+
+    fn can_drag(&self, _: &usize, _: &App) -> bool {
+        self.draggable
+    }
+
+    fn perform_drop(
+        &mut self,
+        ids: Vec<usize>,
+        target: usize,
+        position: DropPosition,
+        _: &mut Window,
+        _: &mut Context<TreeView<Self>>,
+    ) {
+        if position == DropPosition::Inside {
+            self.generate_children(target);
+            for ix in ids {
+                let len = self.nodes[target].children.as_ref().map_or(0, |c| c.len());
+                self.move_node(ix, Some(target), len);
+            }
+            return;
+        }
+
+        // Insert in the reverse order at the same position to keep the order.
+        let offset = if position == DropPosition::After {
+            1
+        } else {
+            0
+        };
+        for ix in ids.into_iter().rev() {
+            let parent = self.nodes[target].parent;
+            let old_parent = self.nodes[ix].parent;
+            self.siblings_mut(old_parent).retain(|child| *child != ix);
+            let target_ix = self
+                .siblings_mut(parent)
+                .iter()
+                .position(|child| *child == target)
+                .unwrap_or(0);
+            self.move_node(ix, parent, target_ix + offset);
+        }
+    }
+}
+
+pub struct TreeStory {
+    tree: Entity<TreeView<FileTreeDelegate>>,
+    message: SharedString,
+    _subscription: Subscription,
+}
+
+impl super::Story for TreeStory {
+    fn title() -> &'static str {
+        "TreeView"
+    }
+
+    fn description() -> &'static str {
+        "A virtualized tree with lazy loading, keyboard navigation, multiple selection, tri-state checkboxes and drag and drop."
+    }
+
+    fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render + Focusable> {
+        Self::view(window, cx)
+    }
+}
+
+impl TreeStory {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let tree = cx.new(|cx| {
+            TreeView::new(FileTreeDelegate::new(), window, cx)
+                .multi_select(true)
+                .checkable(true)
+        });
+
+        let _subscription = cx.subscribe(&tree, |this, tree, event: &TreeEvent<usize>, cx| {
+            let tree = tree.read(cx);
+            let name = |ix: &usize| tree.delegate().nodes[*ix].name.clone();
+            this.message = match event {
+                TreeEvent::SelectionChanged(ids) => format!("Selected {} items", ids.len()).into(),
+                TreeEvent::Confirm(ix) => format!("Confirmed: {}", name(ix)).into(),
+                TreeEvent::Expand(ix) => format!("Expanded: {}", name(ix)).into(),
+                TreeEvent::Collapse(ix) => format!("Collapsed: {}", name(ix)).into(),
+                TreeEvent::CheckedChanged => {
+                    format!("Checked {} items", tree.checked_items().len()).into()
+                }
+                TreeEvent::Drop {
+                    ids,
+                    target,
+                    position,
+                } => format!(
+                    "Dropped {} items {:?} {}",
+                    ids.len(),
+                    position,
+                    name(target)
+                )
+                .into(),
+            };
+            cx.notify();
+        });
+
+        Self {
+            tree,
+            message: SharedString::default(),
+            _subscription,
+        }
+    }
+
+    fn toggle_draggable(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.tree.update(cx, |tree, cx| {
+            tree.delegate_mut().draggable = *checked;
+            cx.notify();
+        });
+    }
+}
+
+impl Focusable for TreeStory {
+    fn focus_handle(&self, cx: &gpui::App) -> gpui::FocusHandle {
+        self.tree.focus_handle(cx)
+    }
+}
+
+impl Render for TreeStory {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let draggable = self.tree.read(cx).delegate().draggable;
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .gap_3()
+                    .child(
+                        Checkbox::new("draggable")
+                            .label("Draggable")
+                            .checked(draggable)
+                            .on_click(cx.listener(Self::toggle_draggable)),
+                    )
+                    .child(
+                        Label::new(self.message.clone())
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .overflow_hidden()
+                    .child(self.tree.clone()),
+            )
+    }
+}
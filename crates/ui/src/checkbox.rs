@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use crate::{
-    text::Text, v_flex, ActiveTheme, Disableable, IconName, Selectable, Sizable, Size,
+    text::Text, v_flex, ActiveTheme, Disableable, Icon, IconName, Selectable, Sizable, Size,
     StyledExt as _,
 };
 use gpui::{
@@ -19,6 +19,7 @@ pub struct Checkbox {
     label: Option<Text>,
     children: Vec<AnyElement>,
     checked: bool,
+    indeterminate: bool,
     disabled: bool,
     size: Size,
     on_click: Option<Box<dyn Fn(&bool, &mut Window, &mut App) + 'static>>,
@@ -33,6 +34,7 @@ impl Checkbox {
             label: None,
             children: Vec::new(),
             checked: false,
+            indeterminate: false,
             disabled: false,
             size: Size::default(),
             on_click: None,
@@ -49,6 +51,14 @@ impl Checkbox {
         self
     }
 
+    /// Set the checkbox to the indeterminate state, to show a partially checked group.
+    ///
+    /// Clicking an indeterminate checkbox checks it.
+    pub fn indeterminate(mut self, indeterminate: bool) -> Self {
+        self.indeterminate = indeterminate;
+        self
+    }
+
     pub fn on_click(mut self, handler: impl Fn(&bool, &mut Window, &mut App) + 'static) -> Self {
         self.on_click = Some(Box::new(handler));
         self
@@ -157,8 +167,8 @@ pub(crate) fn checkbox_check_icon(
 
 impl RenderOnce for Checkbox {
     fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
-        let checked = self.checked;
-        let border_color = if checked {
+        let checked = self.checked && !self.indeterminate;
+        let border_color = if checked || self.indeterminate {
             cx.theme().primary
         } else {
             cx.theme().input
@@ -204,18 +214,28 @@ impl RenderOnce for Checkbox {
                         .border_color(color)
                         .rounded(radius)
                         .when(cx.theme().shadow && !self.disabled, |this| this.shadow_xs())
-                        .map(|this| match self.checked {
+                        .map(|this| match checked || self.indeterminate {
                             false => this.bg(cx.theme().background),
                             _ => this.bg(color),
                         })
-                        .child(checkbox_check_icon(
-                            self.id,
-                            self.size,
-                            checked,
-                            self.disabled,
-                            window,
-                            cx,
-                        )),
+                        .map(|this| {
+                            if self.indeterminate {
+                                this.items_center().justify_center().child(
+                                    Icon::new(IconName::Minus)
+                                        .xsmall()
+                                        .text_color(cx.theme().primary_foreground),
+                                )
+                            } else {
+                                this.child(checkbox_check_icon(
+                                    self.id,
+                                    self.size,
+                                    checked,
+                                    self.disabled,
+                                    window,
+                                    cx,
+                                ))
+                            }
+                        }),
                 )
                 .when(self.label.is_some() || !self.children.is_empty(), |this| {
                     this.child(
@@ -247,7 +267,7 @@ impl RenderOnce for Checkbox {
                     |this, on_click| {
                         this.on_click(move |_, window, cx| {
                             cx.stop_propagation();
-                            let checked = !self.checked;
+                            let checked = !checked;
                             on_click(&checked, window, cx);
                         })
                     },
@@ -0,0 +1,511 @@
+use std::{ops::Range, rc::Rc};
+
+use gpui::{
+    div, prelude::FluentBuilder as _, px, uniform_list, App, Context, FocusHandle, Focusable,
+    HighlightStyle, Hsla, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, Styled, StyledText, UniformListScrollHandle, Window,
+};
+use rope::Rope;
+
+use crate::{
+    diff::{diff_inline, diff_lines, DiffAlgorithm, DiffHunk},
+    h_flex,
+    highlighter::SyntaxHighlighter,
+    input::RopeExt as _,
+    scroll::{Scrollbar, ScrollbarState},
+    ActiveTheme,
+};
+
+const LINE_NUMBER_WIDTH: f32 = 48.;
+const MARKER_WIDTH: f32 = 20.;
+
+/// The layout of the [`DiffView`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffViewMode {
+    /// The old and new text in two columns, the lines are aligned by the hunks.
+    #[default]
+    SideBySide,
+    /// The removed and added lines of each hunk in one column.
+    Unified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// A line of the [`DiffView`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// The row (0-based) in the old text, `None` for the added line.
+    pub old_row: Option<usize>,
+    /// The row (0-based) in the new text, `None` for the removed line.
+    pub new_row: Option<usize>,
+    /// The changed byte ranges in the line, for the intra-line highlighting.
+    pub changes: Vec<Range<usize>>,
+}
+
+impl DiffLine {
+    fn unchanged(old_row: usize, new_row: usize) -> Self {
+        Self {
+            kind: DiffLineKind::Unchanged,
+            old_row: Some(old_row),
+            new_row: Some(new_row),
+            changes: vec![],
+        }
+    }
+
+    fn removed(old_row: usize, changes: Vec<Range<usize>>) -> Self {
+        Self {
+            kind: DiffLineKind::Removed,
+            old_row: Some(old_row),
+            new_row: None,
+            changes,
+        }
+    }
+
+    fn added(new_row: usize, changes: Vec<Range<usize>>) -> Self {
+        Self {
+            kind: DiffLineKind::Added,
+            old_row: None,
+            new_row: Some(new_row),
+            changes,
+        }
+    }
+}
+
+/// A row of the side by side layout, the old line at left and the new line at right.
+type DiffRow = (Option<DiffLine>, Option<DiffLine>);
+
+/// Return the aligned rows of the side by side layout.
+///
+/// The removed and added lines of a modified hunk are paired in order, and the paired
+/// lines are diffed by words for the intra-line changes.
+fn side_by_side_rows(old_lines: &[&str], new_lines: &[&str], hunks: &[DiffHunk]) -> Vec<DiffRow> {
+    let mut rows = vec![];
+    let (mut old_row, mut new_row) = (0, 0);
+    let end = DiffHunk {
+        old_rows: old_lines.len()..old_lines.len(),
+        new_rows: new_lines.len()..new_lines.len(),
+    };
+    for hunk in hunks.iter().chain([&end]) {
+        while old_row < hunk.old_rows.start && new_row < hunk.new_rows.start {
+            let line = DiffLine::unchanged(old_row, new_row);
+            rows.push((Some(line.clone()), Some(line)));
+            old_row += 1;
+            new_row += 1;
+        }
+
+        let paired = hunk.old_rows.len().min(hunk.new_rows.len());
+        for ix in 0..hunk.old_rows.len().max(hunk.new_rows.len()) {
+            let old = hunk.old_rows.start + ix;
+            let new = hunk.new_rows.start + ix;
+            if ix < paired {
+                let (old_changes, new_changes) = diff_inline(old_lines[old], new_lines[new]);
+                rows.push((
+                    Some(DiffLine::removed(old, old_changes)),
+                    Some(DiffLine::added(new, new_changes)),
+                ));
+            } else if ix < hunk.old_rows.len() {
+                rows.push((Some(DiffLine::removed(old, vec![])), None));
+            } else {
+                rows.push((None, Some(DiffLine::added(new, vec![]))));
+            }
+        }
+        old_row = hunk.old_rows.end;
+        new_row = hunk.new_rows.end;
+    }
+
+    rows
+}
+
+/// Return the lines of the unified layout, the removed lines of a hunk are before the added.
+fn unified_lines(rows: &[DiffRow]) -> Vec<DiffLine> {
+    let mut lines = vec![];
+    let mut added = vec![];
+    for (old, new) in rows {
+        match (old, new) {
+            (Some(line), Some(_)) if line.kind == DiffLineKind::Unchanged => {
+                lines.append(&mut added);
+                lines.push(line.clone());
+            }
+            _ => {
+                lines.extend(old.clone());
+                added.extend(new.clone());
+            }
+        }
+    }
+    lines.append(&mut added);
+
+    lines
+}
+
+/// The text of one side of the [`DiffView`].
+struct DiffSide {
+    text: Rope,
+    lines: Vec<SharedString>,
+    highlighter: Option<SyntaxHighlighter>,
+}
+
+impl DiffSide {
+    fn new(text: Rope, language: Option<&SharedString>, cx: &App) -> Self {
+        let lines = text
+            .to_string()
+            .split('\n')
+            .map(|line| SharedString::from(line.to_string()))
+            .collect();
+        let highlighter = language.map(|language| {
+            let mut highlighter = SyntaxHighlighter::new(language, cx);
+            highlighter.update(None, &text);
+            highlighter
+        });
+
+        Self {
+            text,
+            lines,
+            highlighter,
+        }
+    }
+
+    /// Return the styles of the syntax highlighting in the line.
+    fn syntax_styles(&self, row: usize, cx: &App) -> Vec<(Range<usize>, HighlightStyle)> {
+        let Some(highlighter) = &self.highlighter else {
+            return vec![];
+        };
+
+        let start = self.text.line_start_offset(row);
+        let end = self.text.line_end_offset(row);
+        highlighter
+            .styles(&(start..end), cx)
+            .into_iter()
+            .filter_map(|(range, style)| {
+                let range = range.start.max(start) - start..range.end.min(end) - start;
+                (!range.is_empty()).then_some((range, style))
+            })
+            .collect()
+    }
+}
+
+/// A view to compare two texts side by side or unified.
+///
+/// The two sides are rendered in the same rows, so that they are always scrolled together.
+///
+/// ```ignore
+/// let diff_view = cx.new(|cx| {
+///     DiffView::new(window, cx)
+///         .language("rust")
+///         .mode(DiffViewMode::Unified)
+///         .texts(old_text, new_text, cx)
+/// });
+/// ```
+pub struct DiffView {
+    focus_handle: FocusHandle,
+    language: Option<SharedString>,
+    algorithm: DiffAlgorithm,
+    mode: DiffViewMode,
+    old: DiffSide,
+    new: DiffSide,
+    hunks: Vec<DiffHunk>,
+    rows: Rc<Vec<DiffRow>>,
+    unified_lines: Rc<Vec<DiffLine>>,
+    scroll_handle: UniformListScrollHandle,
+    scroll_state: ScrollbarState,
+}
+
+impl DiffView {
+    pub fn new(_: &mut Window, cx: &mut Context<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            language: None,
+            algorithm: DiffAlgorithm::default(),
+            mode: DiffViewMode::default(),
+            old: DiffSide::new(Rope::new(), None, cx),
+            new: DiffSide::new(Rope::new(), None, cx),
+            hunks: vec![],
+            rows: Rc::new(vec![]),
+            unified_lines: Rc::new(vec![]),
+            scroll_handle: UniformListScrollHandle::new(),
+            scroll_state: ScrollbarState::default(),
+        }
+    }
+
+    /// Set the language for the syntax highlighting, default is None.
+    pub fn language(mut self, language: impl Into<SharedString>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Set the algorithm to diff the lines, default is [`DiffAlgorithm::Myers`].
+    pub fn algorithm(mut self, algorithm: DiffAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the layout, default is [`DiffViewMode::SideBySide`].
+    pub fn mode(mut self, mode: DiffViewMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the old and new text to compare.
+    pub fn texts(
+        mut self,
+        old_text: impl Into<SharedString>,
+        new_text: impl Into<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        self.set_texts(old_text, new_text, cx);
+        self
+    }
+
+    /// Set the layout.
+    pub fn set_mode(&mut self, mode: DiffViewMode, cx: &mut Context<Self>) {
+        self.mode = mode;
+        cx.notify();
+    }
+
+    /// Set the old and new text to compare, and diff them.
+    pub fn set_texts(
+        &mut self,
+        old_text: impl Into<SharedString>,
+        new_text: impl Into<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let old_text = Rope::from(old_text.into().as_str());
+        let new_text = Rope::from(new_text.into().as_str());
+        self.hunks = diff_lines(&old_text, &new_text, self.algorithm);
+        self.old = DiffSide::new(old_text, self.language.as_ref(), cx);
+        self.new = DiffSide::new(new_text, self.language.as_ref(), cx);
+
+        let old_lines = self
+            .old
+            .lines
+            .iter()
+            .map(|line| line.as_str())
+            .collect::<Vec<_>>();
+        let new_lines = self
+            .new
+            .lines
+            .iter()
+            .map(|line| line.as_str())
+            .collect::<Vec<_>>();
+        let rows = side_by_side_rows(&old_lines, &new_lines, &self.hunks);
+        self.unified_lines = Rc::new(unified_lines(&rows));
+        self.rows = Rc::new(rows);
+        cx.notify();
+    }
+
+    /// Return the changed hunks between the old and new text.
+    pub fn hunks(&self) -> &[DiffHunk] {
+        &self.hunks
+    }
+
+    fn line_background(kind: DiffLineKind, cx: &App) -> Option<Hsla> {
+        match kind {
+            DiffLineKind::Unchanged => None,
+            DiffLineKind::Added => Some(cx.theme().success.opacity(0.12)),
+            DiffLineKind::Removed => Some(cx.theme().danger.opacity(0.12)),
+        }
+    }
+
+    fn render_line_number(row: Option<usize>, cx: &App) -> impl IntoElement {
+        h_flex()
+            .flex_shrink_0()
+            .justify_end()
+            .w(px(LINE_NUMBER_WIDTH))
+            .pr_2()
+            .text_color(cx.theme().muted_foreground)
+            .children(row.map(|row| (row + 1).to_string()))
+    }
+
+    /// Render the text of the line, with the syntax highlighting and the intra-line changes.
+    fn render_line_text(&self, line: &DiffLine, cx: &App) -> impl IntoElement {
+        let (side, row) = match (line.kind, line.old_row, line.new_row) {
+            (DiffLineKind::Removed, Some(row), _) => (&self.old, row),
+            (_, _, Some(row)) => (&self.new, row),
+            _ => (&self.old, 0),
+        };
+        let text = side.lines.get(row).cloned().unwrap_or_default();
+
+        let change_color = match line.kind {
+            DiffLineKind::Added => cx.theme().success.opacity(0.3),
+            _ => cx.theme().danger.opacity(0.3),
+        };
+        let changes = line.changes.iter().map(|range| {
+            (
+                range.clone(),
+                HighlightStyle {
+                    background_color: Some(change_color),
+                    ..Default::default()
+                },
+            )
+        });
+        let highlights =
+            gpui::combine_highlights(side.syntax_styles(row, cx), changes).collect::<Vec<_>>();
+
+        div()
+            .flex_1()
+            .overflow_hidden()
+            .whitespace_nowrap()
+            .child(StyledText::new(text).with_highlights(highlights))
+    }
+
+    fn render_marker(kind: DiffLineKind, cx: &App) -> impl IntoElement {
+        let (marker, color) = match kind {
+            DiffLineKind::Unchanged => ("", cx.theme().muted_foreground),
+            DiffLineKind::Added => ("+", cx.theme().success),
+            DiffLineKind::Removed => ("-", cx.theme().danger),
+        };
+
+        div()
+            .flex_shrink_0()
+            .w(px(MARKER_WIDTH))
+            .text_color(color)
+            .child(marker)
+    }
+
+    /// Render one side of a row in the side by side layout, empty for the missing line.
+    fn render_side(&self, line: Option<&DiffLine>, is_old: bool, cx: &App) -> impl IntoElement {
+        h_flex()
+            .flex_1()
+            .h_full()
+            .overflow_hidden()
+            .map(|this| match line {
+                Some(line) => this
+                    .when_some(Self::line_background(line.kind, cx), |this, bg| this.bg(bg))
+                    .child(Self::render_line_number(
+                        if is_old { line.old_row } else { line.new_row },
+                        cx,
+                    ))
+                    .child(Self::render_marker(line.kind, cx))
+                    .child(self.render_line_text(line, cx)),
+                None => this.bg(cx.theme().muted.opacity(0.5)),
+            })
+    }
+
+    fn render_row(&self, ix: usize, window: &mut Window, cx: &App) -> impl IntoElement {
+        let line_height = window.line_height();
+
+        match self.mode {
+            DiffViewMode::SideBySide => {
+                let (old, new) = &self.rows[ix];
+                h_flex()
+                    .id(ix)
+                    .w_full()
+                    .h(line_height)
+                    .child(self.render_side(old.as_ref(), true, cx))
+                    .child(div().h_full().w(px(1.)).bg(cx.theme().border))
+                    .child(self.render_side(new.as_ref(), false, cx))
+            }
+            DiffViewMode::Unified => {
+                let line = &self.unified_lines[ix];
+                h_flex()
+                    .id(ix)
+                    .w_full()
+                    .h(line_height)
+                    .when_some(Self::line_background(line.kind, cx), |this, bg| this.bg(bg))
+                    .child(Self::render_line_number(line.old_row, cx))
+                    .child(Self::render_line_number(line.new_row, cx))
+                    .child(Self::render_marker(line.kind, cx))
+                    .child(self.render_line_text(line, cx))
+            }
+        }
+    }
+}
+
+impl Focusable for DiffView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for DiffView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows_count = match self.mode {
+            DiffViewMode::SideBySide => self.rows.len(),
+            DiffViewMode::Unified => self.unified_lines.len(),
+        };
+
+        div()
+            .id("diff-view")
+            .track_focus(&self.focus_handle)
+            .relative()
+            .size_full()
+            .bg(cx.theme().background)
+            .child(
+                uniform_list(
+                    "diff-view-rows",
+                    rows_count,
+                    cx.processor(|this, visible_range: Range<usize>, window, cx| {
+                        visible_range
+                            .map(|ix| this.render_row(ix, window, cx))
+                            .collect::<Vec<_>>()
+                    }),
+                )
+                .size_full()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+            .child(Scrollbar::uniform_scroll(
+                &self.scroll_state,
+                &self.scroll_handle,
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::diff_slices;
+
+    #[test]
+    fn test_side_by_side_rows() {
+        let old_lines = ["a", "b c", "d", "e"];
+        let new_lines = ["a", "b x", "y", "e", "f"];
+        let hunks = diff_slices(&old_lines, &new_lines, DiffAlgorithm::Myers);
+        let rows = side_by_side_rows(&old_lines, &new_lines, &hunks);
+
+        assert_eq!(rows.len(), 5);
+        assert_eq!(
+            rows[0],
+            (
+                Some(DiffLine::unchanged(0, 0)),
+                Some(DiffLine::unchanged(0, 0))
+            )
+        );
+        assert_eq!(
+            rows[1],
+            (
+                Some(DiffLine::removed(1, vec![2..3])),
+                Some(DiffLine::added(1, vec![2..3]))
+            )
+        );
+        assert_eq!(
+            rows[2],
+            (
+                Some(DiffLine::removed(2, vec![0..1])),
+                Some(DiffLine::added(2, vec![0..1]))
+            )
+        );
+        assert_eq!(rows[4], (None, Some(DiffLine::added(4, vec![]))));
+
+        let lines = unified_lines(&rows);
+        let kinds = lines.iter().map(|line| line.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffLineKind::Unchanged,
+                DiffLineKind::Removed,
+                DiffLineKind::Removed,
+                DiffLineKind::Added,
+                DiffLineKind::Added,
+                DiffLineKind::Unchanged,
+                DiffLineKind::Added,
+            ]
+        );
+        assert_eq!(lines[3].new_row, Some(1));
+        assert_eq!(lines[5].old_row, Some(3));
+    }
+}
@@ -0,0 +1,501 @@
+use std::{collections::HashMap, hash::Hash, ops::Range};
+
+use rope::Rope;
+
+/// The algorithm to diff the lines of two texts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffAlgorithm {
+    /// The Myers O(ND) algorithm, find the shortest edit script.
+    #[default]
+    Myers,
+    /// The patience algorithm, match the unique lines first, which gives more readable
+    /// hunks for the moved blocks of code.
+    Patience,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffHunkKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A changed region between the old and new text, by the rows (0-based) of the lines.
+///
+/// The `new_rows` of a removed hunk is empty, at the row after the removed lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub old_rows: Range<usize>,
+    pub new_rows: Range<usize>,
+}
+
+impl DiffHunk {
+    pub fn kind(&self) -> DiffHunkKind {
+        if self.old_rows.is_empty() {
+            DiffHunkKind::Added
+        } else if self.new_rows.is_empty() {
+            DiffHunkKind::Removed
+        } else {
+            DiffHunkKind::Modified
+        }
+    }
+}
+
+/// Diff the lines of the old and new text, returns the changed hunks sorted by the rows.
+///
+/// The lines are split by `\n`, same as the rows of the [`Rope`].
+pub fn diff_lines(old: &Rope, new: &Rope, algorithm: DiffAlgorithm) -> Vec<DiffHunk> {
+    let old = old.to_string();
+    let new = new.to_string();
+    let old_lines = old.split('\n').collect::<Vec<_>>();
+    let new_lines = new.split('\n').collect::<Vec<_>>();
+
+    diff_slices(&old_lines, &new_lines, algorithm)
+}
+
+/// Diff the items of the old and new slice, returns the changed hunks sorted by the indices.
+pub fn diff_slices<T: Eq + Hash>(old: &[T], new: &[T], algorithm: DiffAlgorithm) -> Vec<DiffHunk> {
+    let mut matches = vec![];
+    match algorithm {
+        DiffAlgorithm::Myers => diff_trimmed(old, new, 0, 0, &mut matches, myers_matches),
+        DiffAlgorithm::Patience => patience_matches(old, new, 0, 0, &mut matches),
+    }
+
+    hunks_from_matches(&matches, old.len(), new.len())
+}
+
+/// Diff the words of the old and new line, returns the changed byte ranges in the old
+/// and new line, for the intra-line highlighting.
+pub fn diff_inline(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let old_words = old_tokens
+        .iter()
+        .map(|range| &old[range.clone()])
+        .collect::<Vec<_>>();
+    let new_words = new_tokens
+        .iter()
+        .map(|range| &new[range.clone()])
+        .collect::<Vec<_>>();
+
+    let mut old_changes: Vec<Range<usize>> = vec![];
+    let mut new_changes: Vec<Range<usize>> = vec![];
+    for hunk in diff_slices(&old_words, &new_words, DiffAlgorithm::Myers) {
+        if !hunk.old_rows.is_empty() {
+            old_changes
+                .push(old_tokens[hunk.old_rows.start].start..old_tokens[hunk.old_rows.end - 1].end);
+        }
+        if !hunk.new_rows.is_empty() {
+            new_changes
+                .push(new_tokens[hunk.new_rows.start].start..new_tokens[hunk.new_rows.end - 1].end);
+        }
+    }
+
+    (old_changes, new_changes)
+}
+
+/// Split the line into the words, the whitespaces and the other chars.
+fn tokenize(line: &str) -> Vec<Range<usize>> {
+    #[derive(PartialEq)]
+    enum CharKind {
+        Word,
+        Whitespace,
+        Other,
+    }
+
+    let kind = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            CharKind::Word
+        } else if c.is_whitespace() {
+            CharKind::Whitespace
+        } else {
+            CharKind::Other
+        }
+    };
+
+    let mut tokens: Vec<Range<usize>> = vec![];
+    let mut last_kind = None;
+    for (ix, c) in line.char_indices() {
+        let c_kind = kind(c);
+        let end = ix + c.len_utf8();
+        match tokens.last_mut() {
+            Some(last) if c_kind != CharKind::Other && last_kind.as_ref() == Some(&c_kind) => {
+                last.end = end;
+            }
+            _ => tokens.push(ix..end),
+        }
+        last_kind = Some(c_kind);
+    }
+
+    tokens
+}
+
+/// Convert the matched pairs of the indices to the hunks between them.
+fn hunks_from_matches(matches: &[(usize, usize)], old_len: usize, new_len: usize) -> Vec<DiffHunk> {
+    let mut hunks = vec![];
+    let (mut old_ix, mut new_ix) = (0, 0);
+    for &(old_match, new_match) in matches.iter().chain([(old_len, new_len)].iter()) {
+        if old_match > old_ix || new_match > new_ix {
+            hunks.push(DiffHunk {
+                old_rows: old_ix..old_match,
+                new_rows: new_ix..new_match,
+            });
+        }
+        old_ix = old_match + 1;
+        new_ix = new_match + 1;
+    }
+
+    hunks
+}
+
+/// Match the common prefix and suffix, and the middle by the `diff` function.
+fn diff_trimmed<T: Eq + Hash>(
+    old: &[T],
+    new: &[T],
+    old_start: usize,
+    new_start: usize,
+    matches: &mut Vec<(usize, usize)>,
+    diff: fn(&[T], &[T], usize, usize, &mut Vec<(usize, usize)>),
+) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    matches.extend((0..prefix).map(|ix| (old_start + ix, new_start + ix)));
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    if !old_middle.is_empty() && !new_middle.is_empty() {
+        diff(
+            old_middle,
+            new_middle,
+            old_start + prefix,
+            new_start + prefix,
+            matches,
+        );
+    }
+    matches.extend((0..suffix).map(|ix| {
+        (
+            old_start + old.len() - suffix + ix,
+            new_start + new.len() - suffix + ix,
+        )
+    }));
+}
+
+/// The Myers diff algorithm, push the matched pairs of the indices in order.
+///
+/// This is the linear space variant: find the middle snake of the shortest edit script,
+/// and diff the both sides of it recursively.
+///
+/// http://www.xmailserver.org/diff2.pdf
+fn myers_matches<T: Eq + Hash>(
+    old: &[T],
+    new: &[T],
+    old_start: usize,
+    new_start: usize,
+    matches: &mut Vec<(usize, usize)>,
+) {
+    let (x, y, u, v) = middle_snake(old, new);
+    diff_trimmed(
+        &old[..x],
+        &new[..y],
+        old_start,
+        new_start,
+        matches,
+        myers_matches,
+    );
+    matches.extend((x..u).map(|ix| (old_start + ix, new_start + y + ix - x)));
+    diff_trimmed(
+        &old[u..],
+        &new[v..],
+        old_start + u,
+        new_start + v,
+        matches,
+        myers_matches,
+    );
+}
+
+/// Return the start `(x, y)` and the end `(u, v)` of the middle snake, by searching the
+/// shortest edit script from both ends until the paths overlap.
+///
+/// The first and last items of the sides must differ, so the edit script has at least 2
+/// edits, and the both sides of the snake are smaller to diff.
+fn middle_snake<T: Eq>(old: &[T], new: &[T]) -> (usize, usize, usize, usize) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    // The furthest `x` of each diagonal `k = x - y`, from the start and from the end.
+    let mut forward = vec![0isize; 2 * max as usize + 3];
+    let mut backward = vec![0isize; 2 * max as usize + 3];
+    let at = |k: isize| (offset + k) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+
+            // The same diagonal counted from the end.
+            let back_k = delta - k;
+            if odd && back_k.abs() < d && x + backward[at(back_k)] >= n {
+                return (start_x as usize, start_y as usize, x as usize, y as usize);
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (end_x, end_y) = (x, y);
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+
+            let forward_k = delta - k;
+            if !odd && forward_k.abs() <= d && x + forward[at(forward_k)] >= n {
+                return (
+                    (n - x) as usize,
+                    (m - y) as usize,
+                    (n - end_x) as usize,
+                    (m - end_y) as usize,
+                );
+            }
+        }
+    }
+
+    unreachable!("the paths overlap in (n + m) / 2 rounds")
+}
+
+/// The patience diff algorithm, push the matched pairs of the indices in order.
+///
+/// The unique lines in both sides are matched by the longest increasing subsequence,
+/// and the lines between them are diffed recursively, fallback to Myers if there is no
+/// unique line.
+fn patience_matches<T: Eq + Hash>(
+    old: &[T],
+    new: &[T],
+    old_start: usize,
+    new_start: usize,
+    matches: &mut Vec<(usize, usize)>,
+) {
+    diff_trimmed(old, new, old_start, new_start, matches, patience_anchored);
+}
+
+fn patience_anchored<T: Eq + Hash>(
+    old: &[T],
+    new: &[T],
+    old_start: usize,
+    new_start: usize,
+    matches: &mut Vec<(usize, usize)>,
+) {
+    let anchors = unique_anchors(old, new);
+    if anchors.is_empty() {
+        myers_matches(old, new, old_start, new_start, matches);
+        return;
+    }
+
+    let (mut old_ix, mut new_ix) = (0, 0);
+    for (old_anchor, new_anchor) in anchors {
+        patience_matches(
+            &old[old_ix..old_anchor],
+            &new[new_ix..new_anchor],
+            old_start + old_ix,
+            new_start + new_ix,
+            matches,
+        );
+        matches.push((old_start + old_anchor, new_start + new_anchor));
+        old_ix = old_anchor + 1;
+        new_ix = new_anchor + 1;
+    }
+    patience_matches(
+        &old[old_ix..],
+        &new[new_ix..],
+        old_start + old_ix,
+        new_start + new_ix,
+        matches,
+    );
+}
+
+/// Return the pairs of the indices of the lines occurring once in both sides, in the
+/// longest increasing subsequence.
+fn unique_anchors<T: Eq + Hash>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    // The count and the index in the old and new side.
+    let mut counts: HashMap<&T, (usize, usize, usize, usize)> = HashMap::new();
+    for (ix, item) in old.iter().enumerate() {
+        let entry = counts.entry(item).or_insert((0, ix, 0, 0));
+        entry.0 += 1;
+    }
+    for (ix, item) in new.iter().enumerate() {
+        if let Some(entry) = counts.get_mut(item) {
+            entry.2 += 1;
+            entry.3 = ix;
+        }
+    }
+
+    let mut pairs = counts
+        .into_values()
+        .filter(|(old_count, _, new_count, _)| *old_count == 1 && *new_count == 1)
+        .map(|(_, old_ix, _, new_ix)| (old_ix, new_ix))
+        .collect::<Vec<_>>();
+    pairs.sort_unstable();
+
+    longest_increasing_subsequence(&pairs)
+}
+
+/// Return the longest subsequence of the pairs (sorted by the first) with increasing second.
+fn longest_increasing_subsequence(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // The index of the last pair of the subsequences by the length.
+    let mut tails: Vec<usize> = vec![];
+    let mut prev: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (ix, &(_, value)) in pairs.iter().enumerate() {
+        let pos = tails.partition_point(|&tail| pairs[tail].1 < value);
+        if pos > 0 {
+            prev[ix] = Some(tails[pos - 1]);
+        }
+        if pos == tails.len() {
+            tails.push(ix);
+        } else {
+            tails[pos] = ix;
+        }
+    }
+
+    let mut result = vec![];
+    let mut current = tails.last().copied();
+    while let Some(ix) = current {
+        result.push(pairs[ix]);
+        current = prev[ix];
+    }
+    result.reverse();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(old_rows: Range<usize>, new_rows: Range<usize>) -> DiffHunk {
+        DiffHunk { old_rows, new_rows }
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        for algorithm in [DiffAlgorithm::Myers, DiffAlgorithm::Patience] {
+            let old = Rope::from("a\nb\nc\nd\ne");
+            assert_eq!(diff_lines(&old, &old, algorithm), vec![]);
+
+            let new = Rope::from("a\nB\nc\nd\nf\ne\ng");
+            assert_eq!(
+                diff_lines(&old, &new, algorithm),
+                vec![hunk(1..2, 1..2), hunk(4..4, 4..5), hunk(5..5, 6..7)]
+            );
+
+            let new = Rope::from("a\nd\ne");
+            let hunks = diff_lines(&old, &new, algorithm);
+            assert_eq!(hunks, vec![hunk(1..3, 1..1)]);
+            assert_eq!(hunks[0].kind(), DiffHunkKind::Removed);
+
+            assert_eq!(
+                diff_lines(&Rope::new(), &Rope::from("a\nb"), algorithm),
+                vec![hunk(0..1, 0..2)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff_patience() {
+        // The function `b` is kept, and the function `a` is moved after it.
+        let old = ["fn a() {", "a", "}", "fn b() {", "b", "}"];
+        let new = ["fn b() {", "b", "}", "fn a() {", "a", "}"];
+        assert_eq!(
+            diff_slices(&old, &new, DiffAlgorithm::Patience),
+            vec![hunk(0..3, 0..0), hunk(5..5, 2..5)]
+        );
+
+        assert_eq!(
+            longest_increasing_subsequence(&[(0, 3), (1, 1), (2, 4), (3, 2), (4, 5)]),
+            vec![(1, 1), (3, 2), (4, 5)]
+        );
+    }
+
+    #[test]
+    fn test_diff_myers_minimal() {
+        // The length of the longest common subsequence.
+        fn lcs_len(old: &[u32], new: &[u32]) -> usize {
+            let mut lens = vec![vec![0; new.len() + 1]; old.len() + 1];
+            for (i, a) in old.iter().enumerate() {
+                for (j, b) in new.iter().enumerate() {
+                    lens[i + 1][j + 1] = if a == b {
+                        lens[i][j] + 1
+                    } else {
+                        lens[i][j + 1].max(lens[i + 1][j])
+                    };
+                }
+            }
+            lens[old.len()][new.len()]
+        }
+
+        let mut seed = 7u32;
+        let mut next = |len: usize| {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    (seed >> 16) % 4
+                })
+                .collect::<Vec<_>>()
+        };
+        for len in [1, 2, 5, 17, 40, 100] {
+            let (old, new) = (next(len), next(len + len / 3));
+            let hunks = diff_slices(&old, &new, DiffAlgorithm::Myers);
+
+            // Applying the hunks to the old gives the new, keeping the most items.
+            let mut patched = vec![];
+            let mut old_ix = 0;
+            for hunk in &hunks {
+                patched.extend_from_slice(&old[old_ix..hunk.old_rows.start]);
+                patched.extend_from_slice(&new[hunk.new_rows.clone()]);
+                old_ix = hunk.old_rows.end;
+            }
+            patched.extend_from_slice(&old[old_ix..]);
+            assert_eq!(patched, new);
+
+            let removed = hunks.iter().map(|hunk| hunk.old_rows.len()).sum::<usize>();
+            assert_eq!(old.len() - removed, lcs_len(&old, &new));
+        }
+    }
+
+    #[test]
+    fn test_diff_hunk_kind() {
+        assert_eq!(hunk(1..1, 1..3).kind(), DiffHunkKind::Added);
+        assert_eq!(hunk(1..3, 1..1).kind(), DiffHunkKind::Removed);
+        assert_eq!(hunk(1..2, 1..3).kind(), DiffHunkKind::Modified);
+    }
+
+    #[test]
+    fn test_diff_inline() {
+        assert_eq!(
+            diff_inline("let foo = bar(1);", "let foo = baz(1, 2);"),
+            (vec![10..13], vec![10..13, 15..18])
+        );
+        assert_eq!(diff_inline("你好 world", "你好 world"), (vec![], vec![]));
+        assert_eq!(diff_inline("", "a b"), (vec![], vec![0..3]));
+    }
+}
@@ -0,0 +1,450 @@
+use std::sync::Arc;
+
+use gpui::{
+    point, px, AnyWindowHandle, App, AppContext, Bounds, Context, DragMoveEvent, Entity,
+    InteractiveElement as _, IntoElement, MouseButton, ParentElement as _, Pixels, Point, Render,
+    Size, Styled as _, Subscription, WeakEntity, Window, WindowBounds, WindowKind, WindowOptions,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{v_flex, Root};
+
+use super::{
+    DockArea, DockEvent, DockItem, DockPlacement, DragPanel, Panel, PanelEvent, PanelState,
+    PanelView, TabPanel,
+};
+
+/// The size of the window a panel is torn off into.
+const FLOATING_WINDOW_SIZE: Size<Pixels> = Size {
+    width: px(480.),
+    height: px(360.),
+};
+
+/// Used to serialize and deserialize a [`FloatingWindow`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FloatingWindowState {
+    /// The bounds of the window, in screen coordinates.
+    pub bounds: Bounds<Pixels>,
+    /// The state of the [`TabPanel`] in the window.
+    pub panel: PanelState,
+}
+
+/// The content of a floating window to open.
+pub(super) enum FloatingContent {
+    Panel(Arc<dyn PanelView>),
+    State(PanelState),
+}
+
+/// A window hosting panels torn off from a [`DockArea`].
+///
+/// Drag a tab out of a [`TabPanel`] to open it in a floating window, and drag it
+/// back over the window of the dock area to dock it again. Closing a floating
+/// window moves its panels back into the center of the dock area.
+pub struct FloatingWindow {
+    dock_area: WeakEntity<DockArea>,
+    window: AnyWindowHandle,
+    tab_panel: Entity<TabPanel>,
+    bounds: Bounds<Pixels>,
+    /// The panel dragged outside of the window, and the screen position to drop it at.
+    tear_off: Option<(DragPanel, Point<Pixels>)>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl FloatingWindow {
+    fn new(
+        dock_area: WeakEntity<DockArea>,
+        tab_panel: Entity<TabPanel>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        tab_panel.update(cx, |tab_panel, _| tab_panel.set_floating(true));
+
+        let _subscriptions = vec![
+            cx.observe_window_bounds(window, |this, window, cx| {
+                this.bounds = window.bounds();
+                this.emit_layout_changed(cx);
+            }),
+            cx.subscribe_in(&tab_panel, window, |this, _, event, window, cx| {
+                if let PanelEvent::LayoutChanged = event {
+                    if this.tab_panel.read(cx).panels.is_empty() {
+                        this.close(window, cx);
+                    }
+                    this.emit_layout_changed(cx);
+                }
+            }),
+        ];
+
+        let view = cx.entity().downgrade();
+        window.on_window_should_close(cx, move |_, cx| {
+            _ = view.update(cx, |this, cx| this.dock_all(cx));
+            true
+        });
+
+        Self {
+            dock_area,
+            window: window.window_handle(),
+            tab_panel,
+            bounds: window.bounds(),
+            tear_off: None,
+            _subscriptions,
+        }
+    }
+
+    /// Return the window handle of the floating window.
+    pub fn window_handle(&self) -> AnyWindowHandle {
+        self.window
+    }
+
+    /// Return the bounds of the floating window, in screen coordinates.
+    pub fn bounds(&self) -> Bounds<Pixels> {
+        self.bounds
+    }
+
+    /// Return the [`TabPanel`] of the floating window.
+    pub fn tab_panel(&self) -> &Entity<TabPanel> {
+        &self.tab_panel
+    }
+
+    pub(super) fn dump(&self, cx: &App) -> FloatingWindowState {
+        FloatingWindowState {
+            bounds: self.bounds,
+            panel: self.tab_panel.read(cx).dump(cx),
+        }
+    }
+
+    /// Open a floating window for the dock area.
+    pub(super) fn open(
+        dock_area: WeakEntity<DockArea>,
+        content: FloatingContent,
+        bounds: Bounds<Pixels>,
+        cx: &mut App,
+    ) {
+        // Defer to open the window outside of the current update of the dock area.
+        cx.defer(move |cx| {
+            let options = WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                kind: WindowKind::Normal,
+                ..Default::default()
+            };
+
+            let mut panels = vec![];
+            let result = cx.open_window(options, |window, cx| {
+                let tab_panel = match content {
+                    FloatingContent::Panel(panel) => {
+                        panels.push(panel.clone());
+                        DockItem::tabs(vec![panel], None, &dock_area, window, cx)
+                    }
+                    FloatingContent::State(state) => state.to_item(dock_area.clone(), window, cx),
+                }
+                .left_top_tab_panel(cx)
+                .unwrap_or_else(|| cx.new(|cx| TabPanel::new(None, dock_area.clone(), window, cx)));
+
+                let view = cx.new(|cx| Self::new(dock_area.clone(), tab_panel, window, cx));
+                _ = dock_area.update(cx, |dock_area, cx| {
+                    dock_area.floating_windows.push(view.clone());
+                    cx.emit(DockEvent::LayoutChanged);
+                });
+
+                cx.new(|cx| Root::new(view.into(), window, cx))
+            });
+
+            if let Err(err) = result {
+                tracing::error!("failed to open floating window: {:?}", err);
+                dock_panels(&dock_area, panels, cx);
+            }
+        });
+    }
+
+    /// Close the window, without moving its panels back to the dock area.
+    fn close(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let entity = cx.entity();
+        _ = self.dock_area.update(cx, |dock_area, _| {
+            dock_area.floating_windows.retain(|view| view != &entity);
+        });
+        window.remove_window();
+    }
+
+    /// Move all panels back to the dock area, when the window is closed.
+    fn dock_all(&mut self, cx: &mut Context<Self>) {
+        let entity = cx.entity();
+        let panels = self.tab_panel.read(cx).panels.clone();
+        _ = self.dock_area.update(cx, |dock_area, cx| {
+            dock_area.floating_windows.retain(|view| view != &entity);
+            cx.emit(DockEvent::LayoutChanged);
+        });
+        dock_panels(&self.dock_area, panels, cx);
+    }
+
+    fn emit_layout_changed(&self, cx: &mut App) {
+        _ = self
+            .dock_area
+            .update(cx, |_, cx| cx.emit(DockEvent::LayoutChanged));
+    }
+
+    /// Dock the panel if it was dropped over the window of the dock area, or else
+    /// tear it off into another floating window.
+    fn on_drop_outside(
+        &mut self,
+        drag: DragPanel,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if drag.tab_panel != self.tab_panel {
+            return;
+        }
+
+        let Some(dock_window) = self
+            .dock_area
+            .upgrade()
+            .map(|dock_area| dock_area.read(cx).window_handle)
+        else {
+            return;
+        };
+
+        let over_dock_window = dock_window
+            .update(cx, |_, window, _| window.bounds().contains(&position))
+            .unwrap_or(false);
+        if !over_dock_window && self.tab_panel.read(cx).panels.len() <= 1 {
+            // Tearing off the only panel would just move the window.
+            return;
+        }
+
+        let panel = drag.panel.clone();
+        self.tab_panel.update(cx, |tab_panel, cx| {
+            tab_panel.remove_panel(panel.clone(), window, cx);
+        });
+
+        if over_dock_window {
+            dock_panels(&self.dock_area, vec![panel], cx);
+        } else {
+            FloatingWindow::open(
+                self.dock_area.clone(),
+                FloatingContent::Panel(panel),
+                tear_off_bounds(position),
+                cx,
+            );
+        }
+    }
+}
+
+impl Render for FloatingWindow {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("floating-window")
+            .size_full()
+            .on_drag_move(
+                cx.listener(|this, event: &DragMoveEvent<DragPanel>, window, cx| {
+                    this.tear_off = tear_off_position(event, window)
+                        .map(|position| (event.drag(cx).clone(), position));
+                }),
+            )
+            .on_mouse_up_out(
+                MouseButton::Left,
+                cx.listener(|this, _, window, cx| {
+                    if let Some((drag, position)) = this.tear_off.take() {
+                        this.on_drop_outside(drag, position, window, cx);
+                    }
+                }),
+            )
+            .child(self.tab_panel.clone())
+    }
+}
+
+/// Return the screen position of a panel drag, if it is outside of the window.
+pub(super) fn tear_off_position(
+    event: &DragMoveEvent<DragPanel>,
+    window: &Window,
+) -> Option<Point<Pixels>> {
+    let position = event.event.position;
+    let viewport = Bounds::new(point(px(0.), px(0.)), window.viewport_size());
+    if viewport.contains(&position) {
+        return None;
+    }
+
+    Some(window.bounds().origin + position)
+}
+
+/// The bounds of a window for a panel dropped at a screen position, with the
+/// title of the window under the mouse.
+pub(super) fn tear_off_bounds(position: Point<Pixels>) -> Bounds<Pixels> {
+    Bounds::new(position - point(px(60.), px(12.)), FLOATING_WINDOW_SIZE)
+}
+
+/// Add panels to the center of the dock area, in the window of the dock area.
+fn dock_panels(dock_area: &WeakEntity<DockArea>, panels: Vec<Arc<dyn PanelView>>, cx: &mut App) {
+    if panels.is_empty() {
+        return;
+    }
+
+    let dock_area = dock_area.clone();
+    cx.defer(move |cx| {
+        let Some(dock_window) = dock_area
+            .upgrade()
+            .map(|dock_area| dock_area.read(cx).window_handle)
+        else {
+            return;
+        };
+
+        _ = dock_window.update(cx, |_, window, cx| {
+            _ = dock_area.update(cx, |dock_area, cx| {
+                for panel in panels {
+                    dock_area.add_panel(panel, DockPlacement::Center, None, window, cx);
+                }
+            });
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{
+        div, EventEmitter, FocusHandle, Focusable, SharedString, TestAppContext, VisualTestContext,
+    };
+    use serde_json::json;
+
+    use super::*;
+    use crate::dock::{register_panel, DockAreaState, PanelInfo};
+
+    struct TestPanel {
+        name: SharedString,
+        focus_handle: FocusHandle,
+    }
+
+    impl TestPanel {
+        fn new(name: impl Into<SharedString>, cx: &mut App) -> Arc<dyn PanelView> {
+            let name = name.into();
+            Arc::new(cx.new(|cx| Self {
+                name,
+                focus_handle: cx.focus_handle(),
+            }))
+        }
+    }
+
+    impl Panel for TestPanel {
+        fn panel_name(&self) -> &'static str {
+            "TestPanel"
+        }
+
+        fn dump(&self, _: &App) -> PanelState {
+            let mut state = PanelState::new(self);
+            state.info = PanelInfo::panel(json!({ "name": self.name }));
+            state
+        }
+    }
+
+    impl EventEmitter<PanelEvent> for TestPanel {}
+
+    impl Focusable for TestPanel {
+        fn focus_handle(&self, _: &App) -> FocusHandle {
+            self.focus_handle.clone()
+        }
+    }
+
+    impl Render for TestPanel {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().child(self.name.clone())
+        }
+    }
+
+    fn init(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            crate::init(cx);
+            register_panel(cx, "TestPanel", |_, _, info, _, cx| {
+                let PanelInfo::Panel(info) = info else {
+                    unreachable!()
+                };
+                let name = info["name"].as_str().unwrap_or_default().to_string();
+                Box::new(TestPanel::new(name, cx))
+            });
+        });
+    }
+
+    /// The names of the test panels in the state, in order.
+    fn panel_names(state: &PanelState) -> Vec<String> {
+        match &state.info {
+            PanelInfo::Panel(info) => vec![info["name"].as_str().unwrap_or_default().to_string()],
+            _ => state.children.iter().flat_map(panel_names).collect(),
+        }
+    }
+
+    fn dump(dock_area: &Entity<DockArea>, cx: &mut VisualTestContext) -> DockAreaState {
+        dock_area.read_with(cx, |dock_area, cx| dock_area.dump(cx))
+    }
+
+    #[gpui::test]
+    fn test_tear_off_and_dock_back(cx: &mut TestAppContext) {
+        init(cx);
+        let (dock_area, cx) = cx.add_window_view(|window, cx| {
+            let mut dock_area = DockArea::new("test", Some(1), window, cx);
+            let weak_dock_area = cx.entity().downgrade();
+            let panels = vec![TestPanel::new("a", cx), TestPanel::new("b", cx)];
+            let item = DockItem::tabs(panels, None, &weak_dock_area, window, cx);
+            dock_area.set_center(item, window, cx);
+            dock_area
+        });
+        cx.run_until_parked();
+
+        let panel = dock_area.read_with(cx, |dock_area, cx| {
+            let tab_panel = dock_area.items.left_top_tab_panel(cx).unwrap();
+            tab_panel.read(cx).panels[1].clone()
+        });
+        let bounds = Bounds::new(point(px(900.), px(100.)), FLOATING_WINDOW_SIZE);
+        dock_area.update_in(cx, |dock_area, window, cx| {
+            dock_area.float_panel(panel.clone(), bounds, window, cx);
+        });
+        cx.run_until_parked();
+
+        let floating = dock_area.read_with(cx, |dock_area, _| dock_area.floating_windows.clone());
+        assert_eq!(floating.len(), 1);
+        let floating = floating[0].clone();
+        let tab_panel = floating.read_with(cx, |floating, _| floating.tab_panel.clone());
+        // The only panel of a floating window can be dragged back.
+        tab_panel.read_with(cx, |tab_panel, cx| {
+            assert!(tab_panel.draggable(cx));
+            assert!(tab_panel.droppable(cx));
+        });
+
+        let state = dump(&dock_area, cx);
+        assert_eq!(panel_names(&state.center), ["a"]);
+        assert_eq!(state.floating.len(), 1);
+        assert_eq!(state.floating[0].bounds, bounds);
+        assert_eq!(panel_names(&state.floating[0].panel), ["b"]);
+
+        // Restoring the state opens the floating window again.
+        dock_area.update_in(cx, |dock_area, window, cx| {
+            dock_area.load(state.clone(), window, cx).unwrap();
+        });
+        cx.run_until_parked();
+        assert_eq!(dump(&dock_area, cx), state);
+
+        // Drop the panel over the window of the dock area.
+        let floating =
+            dock_area.read_with(cx, |dock_area, _| dock_area.floating_windows[0].clone());
+        let (floating_window, tab_panel) = floating.read_with(cx, |floating, _| {
+            (floating.window_handle(), floating.tab_panel.clone())
+        });
+        let panel = tab_panel.read_with(cx, |tab_panel, _| tab_panel.panels[0].clone());
+        let position = cx.update(|window, _| window.bounds().center());
+        floating_window
+            .update(cx, |_, window, cx| {
+                floating.update(cx, |floating, cx| {
+                    let drag = DragPanel::new(panel, tab_panel.clone());
+                    floating.on_drop_outside(drag, position, window, cx);
+                });
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        let state = dump(&dock_area, cx);
+        assert!(state.floating.is_empty());
+        assert_eq!(panel_names(&state.center), ["a", "b"]);
+        assert!(floating_window.update(cx, |_, _, _| ()).is_err());
+
+        dock_area.update_in(cx, |dock_area, window, cx| {
+            dock_area.load(state.clone(), window, cx).unwrap();
+        });
+        cx.run_until_parked();
+        assert_eq!(dump(&dock_area, cx), state);
+    }
+}
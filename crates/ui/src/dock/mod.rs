@@ -1,25 +1,30 @@
 mod dock;
+mod floating;
 mod invalid_panel;
 mod panel;
 mod stack_panel;
 mod state;
 mod tab_panel;
 mod tiles;
+mod workspace;
 
 use anyhow::Result;
 use gpui::{
-    actions, canvas, div, prelude::FluentBuilder, AnyElement, AnyView, App, AppContext, Axis,
-    Bounds, Context, Edges, Entity, EntityId, EventEmitter, InteractiveElement as _, IntoElement,
-    ParentElement as _, Pixels, Render, SharedString, Styled, Subscription, WeakEntity, Window,
+    actions, canvas, div, prelude::FluentBuilder, AnyElement, AnyView, AnyWindowHandle, App,
+    AppContext, Axis, Bounds, Context, DragMoveEvent, Edges, Entity, EntityId, EventEmitter,
+    InteractiveElement as _, IntoElement, MouseButton, ParentElement as _, Pixels, Point, Render,
+    SharedString, Styled, Subscription, WeakEntity, Window,
 };
 use std::sync::Arc;
 
 pub use dock::*;
+pub use floating::*;
 pub use panel::*;
 pub use stack_panel::*;
 pub use state::*;
 pub use tab_panel::*;
 pub use tiles::*;
+pub use workspace::*;
 
 pub fn init(cx: &mut App) {
     PanelRegistry::init(cx);
@@ -68,6 +73,13 @@ pub struct DockArea {
     /// The panel style, default is [`PanelStyle::Default`](PanelStyle::Default).
     pub(crate) panel_style: PanelStyle,
 
+    /// The window of the dock area.
+    window_handle: AnyWindowHandle,
+    /// The windows of the panels torn off from the dock area.
+    floating_windows: Vec<Entity<FloatingWindow>>,
+    /// The panel dragged outside of the window, and the screen position to drop it at.
+    tear_off: Option<(DragPanel, Point<Pixels>)>,
+
     _subscriptions: Vec<Subscription>,
 }
 
@@ -464,6 +476,9 @@ impl DockArea {
             bottom_dock: None,
             locked: false,
             panel_style: PanelStyle::Default,
+            window_handle: window.window_handle(),
+            floating_windows: vec![],
+            tear_off: None,
             _subscriptions: vec![],
         };
 
@@ -816,6 +831,52 @@ impl DockArea {
         self.remove_panel(panel.clone(), DockPlacement::Bottom, window, cx);
     }
 
+    /// Return the windows of the panels torn off from the dock area.
+    pub fn floating_windows(&self) -> &[Entity<FloatingWindow>] {
+        &self.floating_windows
+    }
+
+    /// Move a panel out of the docks into a new window, with the given bounds in screen coordinates.
+    pub fn float_panel(
+        &mut self,
+        panel: Arc<dyn PanelView>,
+        bounds: Bounds<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.remove_panel_from_all_docks(panel.clone(), window, cx);
+        FloatingWindow::open(
+            cx.entity().downgrade(),
+            FloatingContent::Panel(panel),
+            bounds,
+            cx,
+        );
+    }
+
+    /// Tear off a panel dragged outside of the window.
+    fn tear_off_panel(
+        &mut self,
+        drag: DragPanel,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.locked || drag.tab_panel.read(cx).dock_area.entity_id() != cx.entity_id() {
+            return;
+        }
+
+        let panel = drag.panel.clone();
+        drag.tab_panel.update(cx, |tab_panel, cx| {
+            tab_panel.remove_panel(panel.clone(), window, cx);
+        });
+        FloatingWindow::open(
+            cx.entity().downgrade(),
+            FloatingContent::Panel(panel),
+            floating::tear_off_bounds(position),
+            cx,
+        );
+    }
+
     /// Load the state of the DockArea from the DockAreaState.
     ///
     /// See also [DockeArea::dump].
@@ -840,8 +901,23 @@ impl DockArea {
             self.bottom_dock = Some(bottom_dock_state.to_dock(weak_self.clone(), window, cx));
         }
 
-        self.items = state.center.to_item(weak_self, window, cx);
+        self.items = state.center.to_item(weak_self.clone(), window, cx);
         self.update_toggle_button_tab_panels(window, cx);
+
+        for floating_window in self.floating_windows.drain(..) {
+            let handle = floating_window.read(cx).window_handle();
+            cx.defer(move |cx| {
+                _ = handle.update(cx, |_, window, _| window.remove_window());
+            });
+        }
+        for floating_window in state.floating {
+            FloatingWindow::open(
+                weak_self.clone(),
+                FloatingContent::State(floating_window.panel),
+                floating_window.bounds,
+                cx,
+            );
+        }
         Ok(())
     }
 
@@ -865,12 +941,19 @@ impl DockArea {
             .as_ref()
             .map(|dock| DockState::new(dock.clone(), cx));
 
+        let floating = self
+            .floating_windows
+            .iter()
+            .map(|floating_window| floating_window.read(cx).dump(cx))
+            .collect();
+
         DockAreaState {
             version: self.version,
             center,
             left_dock,
             right_dock,
             bottom_dock,
+            floating,
         }
     }
 
@@ -1016,6 +1099,20 @@ impl Render for DockArea {
             .relative()
             .size_full()
             .overflow_hidden()
+            .on_drag_move(
+                cx.listener(|this, event: &DragMoveEvent<DragPanel>, window, cx| {
+                    this.tear_off = floating::tear_off_position(event, window)
+                        .map(|position| (event.drag(cx).clone(), position));
+                }),
+            )
+            .on_mouse_up_out(
+                MouseButton::Left,
+                cx.listener(|this, _, window, cx| {
+                    if let Some((drag, position)) = this.tear_off.take() {
+                        this.tear_off_panel(drag, position, window, cx);
+                    }
+                }),
+            )
             .child(
                 canvas(
                     move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds),
@@ -2,7 +2,7 @@ use gpui::{point, px, size, App, AppContext, Axis, Bounds, Entity, Pixels, WeakE
 use itertools::Itertools as _;
 use serde::{Deserialize, Serialize};
 
-use super::{Dock, DockArea, DockItem, DockPlacement, Panel, PanelRegistry};
+use super::{Dock, DockArea, DockItem, DockPlacement, FloatingWindowState, Panel, PanelRegistry};
 
 /// Used to serialize and deserialize the DockArea
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,6 +19,9 @@ pub struct DockAreaState {
     pub right_dock: Option<DockState>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bottom_dock: Option<DockState>,
+    /// The windows of the panels torn off from the dock area.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub floating: Vec<FloatingWindowState>,
 }
 
 /// Used to serialize and deserialize the Dock
@@ -42,6 +45,11 @@ impl DockState {
         }
     }
 
+    /// The state of the panel in the dock.
+    pub fn panel(&self) -> &PanelState {
+        &self.panel
+    }
+
     /// Convert the DockState to Dock
     pub fn to_dock(
         &self,
@@ -278,5 +286,29 @@ mod tests {
         assert_eq!(right_dock.panel.panel_name, "TabPanel");
         assert_eq!(right_dock.panel.children.len(), 1);
         assert_eq!(right_dock.panel.children[0].panel_name, "StoryContainer");
+        assert!(state.floating.is_empty());
+    }
+
+    #[test]
+    fn test_floating_windows_state() {
+        let json = include_str!("../../tests/fixtures/layout.json");
+        let mut state: DockAreaState = serde_json::from_str(json).unwrap();
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(!json.contains("floating"));
+
+        let panel = state.center.children.remove(1);
+        state.floating.push(FloatingWindowState {
+            bounds: Bounds {
+                origin: point(px(1920.), px(100.)),
+                size: size(px(480.), px(360.)),
+            },
+            panel,
+        });
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: DockAreaState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, state);
+        assert_eq!(restored.floating[0].bounds.origin.x, px(1920.));
+        assert_eq!(restored.floating[0].panel.panel_name, "TabPanel");
     }
 }
@@ -65,7 +65,7 @@ impl Render for DragPanel {
 
 pub struct TabPanel {
     focus_handle: FocusHandle,
-    dock_area: WeakEntity<DockArea>,
+    pub(crate) dock_area: WeakEntity<DockArea>,
     /// The stock_panel can be None, if is None, that means the panels can't be split or move
     stack_panel: Option<WeakEntity<StackPanel>>,
     pub(crate) panels: Vec<Arc<dyn PanelView>>,
@@ -83,6 +83,10 @@ pub struct TabPanel {
     will_split_placement: Option<Placement>,
     /// Is TabPanel used in Tiles.
     in_tiles: bool,
+    /// Is TabPanel the content of a [`super::FloatingWindow`].
+    ///
+    /// It has no stack panel to split into, but its panels can still be dragged out of the window.
+    floating: bool,
 }
 
 impl Panel for TabPanel {
@@ -161,6 +165,7 @@ impl TabPanel {
             collapsed: false,
             closable: true,
             in_tiles: false,
+            floating: false,
         }
     }
 
@@ -169,6 +174,11 @@ impl TabPanel {
         self.in_tiles = in_tiles;
     }
 
+    /// Mark the TabPanel as being the content of a floating window.
+    pub(super) fn set_floating(&mut self, floating: bool) {
+        self.floating = floating;
+    }
+
     pub(super) fn set_parent(&mut self, view: WeakEntity<StackPanel>) {
         self.stack_panel = Some(view);
     }
@@ -370,7 +380,7 @@ impl TabPanel {
             return true;
         }
 
-        self.stack_panel.is_none()
+        self.stack_panel.is_none() && !self.floating
     }
 
     /// Return true if self or parent only have last panel.
@@ -399,15 +409,16 @@ impl TabPanel {
 
     /// Return true if the tab panel is draggable.
     ///
-    /// E.g. if the parent and self only have one panel, it is not draggable.
-    fn draggable(&self, cx: &App) -> bool {
-        !self.is_locked(cx) && !self.is_last_panel(cx)
+    /// E.g. if the parent and self only have one panel, it is not draggable,
+    /// unless it is in a floating window, to drag it back to the dock area.
+    pub(super) fn draggable(&self, cx: &App) -> bool {
+        !self.is_locked(cx) && (self.floating || !self.is_last_panel(cx))
     }
 
     /// Return true if the tab panel is droppable.
     ///
     /// E.g. if the tab panel is locked, it is not droppable.
-    fn droppable(&self, cx: &App) -> bool {
+    pub(super) fn droppable(&self, cx: &App) -> bool {
         !self.is_locked(cx)
     }
 
@@ -871,6 +882,13 @@ impl TabPanel {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        // Without a stack panel (e.g. in a floating window), the panel can only be merged into the tabs.
+        if self.stack_panel.is_none() {
+            self.will_split_placement = None;
+            cx.notify();
+            return;
+        }
+
         let bounds = drag.bounds;
         let position = drag.event.position;
 
@@ -0,0 +1,486 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use gpui::{App, Context, Entity, SharedString, Window};
+
+use super::{DockArea, DockAreaState, PanelInfo, PanelRegistry, PanelState};
+
+/// The name of the preset used by [`DockWorkspace::reset`].
+pub const DEFAULT_PRESET: &str = "default";
+
+/// The file in the workspace directory that keeps the most recently used layouts.
+const RECENT_FILE: &str = ".recent.json";
+
+type Migration = Box<dyn Fn(serde_json::Value) -> Result<serde_json::Value>>;
+type Preset = Rc<dyn Fn(&mut DockArea, &mut Window, &mut Context<DockArea>)>;
+
+/// Saves and restores named [`DockAreaState`] layouts in a directory.
+///
+/// Each layout is stored as `<name>.json`, and the most recently used layouts are
+/// remembered across sessions.
+///
+/// ```ignore
+/// let mut workspace = DockWorkspace::new(layouts_dir, 5)
+///     .migration(4, |mut state| {
+///         // Version 5 renamed the `Logs` panel.
+///         rename_panel(&mut state, "Logs", "Console");
+///         Ok(state)
+///     })
+///     .preset(DEFAULT_PRESET, |dock_area, window, cx| {
+///         dock_area.set_center(default_center(cx.entity().downgrade(), window, cx), window, cx);
+///     });
+///
+/// // Open the last used layout, or the default preset.
+/// workspace.restore(&dock_area, window, cx);
+/// ```
+pub struct DockWorkspace {
+    dir: PathBuf,
+    version: usize,
+    migrations: BTreeMap<usize, Migration>,
+    presets: Vec<(SharedString, Preset)>,
+    recent: Vec<String>,
+    max_recent: usize,
+}
+
+impl DockWorkspace {
+    /// Create a workspace storing layouts in `dir`, for layouts of the given `version`.
+    ///
+    /// The directory is created when the first layout is saved.
+    pub fn new(dir: impl Into<PathBuf>, version: usize) -> Self {
+        let dir = dir.into();
+        let recent = fs::read_to_string(dir.join(RECENT_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            dir,
+            version,
+            migrations: BTreeMap::new(),
+            presets: Vec::new(),
+            recent,
+            max_recent: 10,
+        }
+    }
+
+    /// Set the number of layouts kept in the most recently used list, default is 10.
+    pub fn max_recent(mut self, max_recent: usize) -> Self {
+        self.max_recent = max_recent;
+        self.recent.truncate(max_recent);
+        self
+    }
+
+    /// Register a migration that upgrades a layout from version `from` to `from + 1`.
+    ///
+    /// Migrations work on the JSON of the layout, so they can handle states that no
+    /// longer deserialize as a [`DockAreaState`]. The `version` field is updated after
+    /// the migration runs. A layout without a version is version 0.
+    pub fn migration(
+        mut self,
+        from: usize,
+        migrate: impl Fn(serde_json::Value) -> Result<serde_json::Value> + 'static,
+    ) -> Self {
+        self.migrations.insert(from, Box::new(migrate));
+        self
+    }
+
+    /// Register a named layout preset, that builds the layout on a [`DockArea`].
+    ///
+    /// The preset named [`DEFAULT_PRESET`] is used by [`DockWorkspace::reset`].
+    pub fn preset(
+        mut self,
+        name: impl Into<SharedString>,
+        build: impl Fn(&mut DockArea, &mut Window, &mut Context<DockArea>) + 'static,
+    ) -> Self {
+        let name = name.into();
+        self.presets.retain(|(preset, _)| *preset != name);
+        self.presets.push((name, Rc::new(build)));
+        self
+    }
+
+    /// The directory the layouts are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The current layout version.
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// The names of the registered presets, in registration order.
+    pub fn presets(&self) -> impl Iterator<Item = &SharedString> {
+        self.presets.iter().map(|(name, _)| name)
+    }
+
+    /// The saved layouts, most recently used first.
+    pub fn recent(&self) -> &[String] {
+        &self.recent
+    }
+
+    /// The names of all saved layouts, sorted by name.
+    pub fn layouts(&self) -> Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut names = vec![];
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                    if !name.starts_with('.') {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Save the current layout of the dock area as `name`.
+    pub fn save(&mut self, name: &str, dock_area: &Entity<DockArea>, cx: &App) -> Result<()> {
+        let mut state = dock_area.read(cx).dump(cx);
+        state.version = Some(self.version);
+        self.save_state(name, &state)
+    }
+
+    /// Save a layout state as `name`, replacing any layout with that name.
+    pub fn save_state(&mut self, name: &str, state: &DockAreaState) -> Result<()> {
+        let path = self.path(name)?;
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        fs::write(&path, serde_json::to_string_pretty(state)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        self.touch(name)
+    }
+
+    /// Load the layout `name` into the dock area, migrating it if it is from an older version.
+    ///
+    /// Panels that are not registered in [`PanelRegistry`] keep their state as placeholders,
+    /// see [`DockArea::reload`].
+    pub fn load(
+        &mut self,
+        name: &str,
+        dock_area: &Entity<DockArea>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<()> {
+        let state = self.load_state(name)?;
+        dock_area.update(cx, |dock_area, cx| dock_area.load(state, window, cx))?;
+        self.touch(name)
+    }
+
+    /// Read the layout `name`, migrating it to the current version.
+    ///
+    /// A migrated layout is written back, so the migrations run only once.
+    pub fn load_state(&self, name: &str) -> Result<DockAreaState> {
+        let path = self.path(name)?;
+        let json = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&json)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        let (value, migrated) = self.migrate(value)?;
+        let state: DockAreaState = serde_json::from_value(value)
+            .with_context(|| format!("invalid layout in {}", path.display()))?;
+        if migrated {
+            fs::write(&path, serde_json::to_string_pretty(&state)?)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        Ok(state)
+    }
+
+    /// Delete the layout `name`.
+    pub fn delete(&mut self, name: &str) -> Result<()> {
+        let path = self.path(name)?;
+        fs::remove_file(&path).with_context(|| format!("failed to delete {}", path.display()))?;
+        self.recent.retain(|recent| recent != name);
+        self.save_recent()
+    }
+
+    /// Apply a registered preset to the dock area.
+    pub fn apply_preset(
+        &self,
+        name: &str,
+        dock_area: &Entity<DockArea>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<()> {
+        let (_, preset) = self
+            .presets
+            .iter()
+            .find(|(preset, _)| preset.as_ref() == name)
+            .ok_or_else(|| anyhow!("no layout preset named `{}`", name))?;
+
+        let preset = preset.clone();
+        let version = self.version;
+        dock_area.update(cx, |dock_area, cx| {
+            dock_area.set_version(version, window, cx);
+            preset(dock_area, window, cx);
+        });
+        Ok(())
+    }
+
+    /// Reset the dock area to the [`DEFAULT_PRESET`] layout.
+    pub fn reset(
+        &self,
+        dock_area: &Entity<DockArea>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<()> {
+        self.apply_preset(DEFAULT_PRESET, dock_area, window, cx)
+    }
+
+    /// Load the most recently used layout that still loads, or reset to the default preset.
+    pub fn restore(
+        &mut self,
+        dock_area: &Entity<DockArea>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<()> {
+        for name in self.recent.clone() {
+            match self.load(&name, dock_area, window, cx) {
+                Ok(()) => return Ok(()),
+                Err(err) => tracing::warn!("failed to restore layout `{}`: {:?}", name, err),
+            }
+        }
+
+        self.reset(dock_area, window, cx)
+    }
+
+    /// Upgrade the JSON of a layout to the current version.
+    ///
+    /// Returns the migrated layout and whether any migration ran.
+    pub fn migrate(&self, mut value: serde_json::Value) -> Result<(serde_json::Value, bool)> {
+        let mut version = layout_version(&value)?;
+        if version > self.version {
+            bail!(
+                "layout version {} is newer than the supported version {}",
+                version,
+                self.version
+            );
+        }
+
+        let migrated = version < self.version;
+        while version < self.version {
+            let migrate = self
+                .migrations
+                .get(&version)
+                .ok_or_else(|| anyhow!("no migration from layout version {}", version))?;
+            value = migrate(value)
+                .with_context(|| format!("failed to migrate layout from version {}", version))?;
+            version += 1;
+
+            value
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("layout must be a JSON object"))?
+                .insert("version".into(), version.into());
+        }
+
+        Ok((value, migrated))
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || name.starts_with('.')
+            || name.contains(|c: char| matches!(c, '/' | '\\' | ':') || c.is_control())
+        {
+            bail!("invalid layout name `{}`", name);
+        }
+
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+
+    fn touch(&mut self, name: &str) -> Result<()> {
+        self.recent.retain(|recent| recent != name);
+        self.recent.insert(0, name.to_string());
+        self.recent.truncate(self.max_recent);
+        self.save_recent()
+    }
+
+    fn save_recent(&self) -> Result<()> {
+        if !self.dir.exists() {
+            return Ok(());
+        }
+
+        let path = self.dir.join(RECENT_FILE);
+        fs::write(&path, serde_json::to_string(&self.recent)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+fn layout_version(value: &serde_json::Value) -> Result<usize> {
+    match value.get("version") {
+        None | Some(serde_json::Value::Null) => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .map(|version| version as usize)
+            .ok_or_else(|| anyhow!("invalid layout version `{}`", version)),
+    }
+}
+
+impl DockAreaState {
+    /// The names of the panels in this layout that are not registered in [`PanelRegistry`].
+    ///
+    /// These panels are shown as placeholders when loaded, and keep their state so
+    /// they can be restored once registered.
+    pub fn missing_panels(&self, cx: &App) -> Vec<String> {
+        let registry = cx.try_global::<PanelRegistry>();
+        let mut names = vec![];
+        for panel in self.panels() {
+            panel.visit(&mut |state| {
+                if matches!(state.info, PanelInfo::Panel(_))
+                    && !registry
+                        .is_some_and(|registry| registry.items.contains_key(&state.panel_name))
+                    && !names.contains(&state.panel_name)
+                {
+                    names.push(state.panel_name.clone());
+                }
+            });
+        }
+        names
+    }
+
+    fn panels(&self) -> impl Iterator<Item = &PanelState> {
+        std::iter::once(&self.center)
+            .chain(
+                [&self.left_dock, &self.right_dock, &self.bottom_dock]
+                    .into_iter()
+                    .flatten()
+                    .map(|dock| dock.panel()),
+            )
+            .chain(self.floating.iter().map(|floating| &floating.panel))
+    }
+}
+
+impl PanelState {
+    fn visit(&self, f: &mut impl FnMut(&PanelState)) {
+        f(self);
+        for child in &self.children {
+            child.visit(f);
+        }
+    }
+}
+
+impl DockArea {
+    /// Rebuild every panel from its dumped state.
+    ///
+    /// Panels that were not registered when the layout was loaded are shown as
+    /// placeholders that keep their state, call this after registering them to
+    /// restore them.
+    pub fn reload(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Result<()> {
+        let state = self.dump(cx);
+        self.load(state, window, cx)?;
+        cx.notify();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("dock_workspace_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn layout() -> DockAreaState {
+        serde_json::from_str(include_str!("../../tests/fixtures/layout.json")).unwrap()
+    }
+
+    #[test]
+    fn test_save_and_load_layouts() {
+        let dir = temp_dir("save");
+        let mut workspace = DockWorkspace::new(&dir, 0).max_recent(2);
+        assert_eq!(workspace.layouts().unwrap(), Vec::<String>::new());
+
+        let state = layout();
+        workspace.save_state("coding", &state).unwrap();
+        workspace.save_state("debug", &state).unwrap();
+        workspace.save_state("review", &state).unwrap();
+        workspace.save_state("debug", &state).unwrap();
+
+        assert_eq!(
+            workspace.layouts().unwrap(),
+            vec!["coding", "debug", "review"]
+        );
+        assert_eq!(workspace.recent(), &["debug", "review"]);
+        assert_eq!(workspace.load_state("coding").unwrap(), state);
+
+        // The most recently used list is kept across sessions.
+        let mut workspace = DockWorkspace::new(&dir, 0);
+        assert_eq!(workspace.recent(), &["debug", "review"]);
+
+        workspace.delete("debug").unwrap();
+        assert_eq!(workspace.layouts().unwrap(), vec!["coding", "review"]);
+        assert_eq!(workspace.recent(), &["review"]);
+        assert!(workspace.load_state("debug").is_err());
+
+        assert!(workspace.save_state("../escape", &state).is_err());
+        assert!(workspace.save_state(".recent", &state).is_err());
+        assert!(workspace.save_state("", &state).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_layouts() {
+        let dir = temp_dir("migrate");
+        let mut workspace = DockWorkspace::new(&dir, 0);
+        workspace.save_state("old", &layout()).unwrap();
+
+        let workspace = DockWorkspace::new(&dir, 2)
+            .migration(0, |mut value| {
+                // Version 1 renamed `StoryContainer` to `Story`.
+                fn rename(value: &mut serde_json::Value) {
+                    match value {
+                        serde_json::Value::Object(obj) => {
+                            if obj.get("panel_name").and_then(|name| name.as_str())
+                                == Some("StoryContainer")
+                            {
+                                obj.insert("panel_name".into(), "Story".into());
+                            }
+                            obj.values_mut().for_each(rename);
+                        }
+                        serde_json::Value::Array(items) => items.iter_mut().for_each(rename),
+                        _ => {}
+                    }
+                }
+                rename(&mut value);
+                Ok(value)
+            })
+            .migration(1, Ok);
+
+        let state = workspace.load_state("old").unwrap();
+        assert_eq!(state.version, Some(2));
+        assert_eq!(state.center.children[1].children[0].panel_name, "Story");
+
+        // The migrated layout was written back.
+        let json = fs::read_to_string(dir.join("old.json")).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], 2);
+        assert!(!workspace.migrate(value).unwrap().1);
+
+        // A missing migration or a newer layout is an error, and leaves the file alone.
+        let missing = DockWorkspace::new(&dir, 3);
+        let err = missing.load_state("old").unwrap_err();
+        assert!(format!("{:?}", err).contains("no migration from layout version 2"));
+        let newer = DockWorkspace::new(&dir, 1);
+        assert!(newer.load_state("old").is_err());
+        assert_eq!(fs::read_to_string(dir.join("old.json")).unwrap(), json);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
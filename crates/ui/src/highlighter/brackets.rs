@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+/// A pair of brackets or quotes of a language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketPair {
+    pub start: char,
+    pub end: char,
+    /// Insert the `end` when typing the `start`, and type over the `end` when it is next to the cursor.
+    pub auto_close: bool,
+}
+
+impl BracketPair {
+    pub const fn new(start: char, end: char) -> Self {
+        Self {
+            start,
+            end,
+            auto_close: true,
+        }
+    }
+
+    /// Set to not insert the `end` when typing the `start`, e.g.: `'` in Rust is used by lifetimes.
+    pub const fn no_auto_close(mut self) -> Self {
+        self.auto_close = false;
+        self
+    }
+
+    /// Return true if the pair is quotes (the start and the end are the same), e.g.: `""`.
+    #[inline]
+    pub fn is_quote(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// The default brackets of the languages: `()`, `[]`, `{}` and `""`.
+pub fn default_brackets() -> Vec<BracketPair> {
+    vec![
+        BracketPair::new('(', ')'),
+        BracketPair::new('[', ']'),
+        BracketPair::new('{', '}'),
+        BracketPair::new('"', '"'),
+    ]
+}
+
+/// Return the innermost indent range that the new line inserted at the `offset` is in.
+///
+/// The indent ranges are from the start of the `@indent` nodes to the start of the `@end`
+/// captures (or the end of the nodes), a new line is indented when the range starts
+/// before the `offset` and ends at or after it.
+pub fn indent_range_at(ranges: &[Range<usize>], offset: usize) -> Option<Range<usize>> {
+    ranges
+        .iter()
+        .filter(|range| range.start < offset && range.end >= offset)
+        .max_by_key(|range| range.start)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bracket_pair() {
+        let pair = BracketPair::new('(', ')');
+        assert!(pair.auto_close);
+        assert!(!pair.is_quote());
+        assert!(BracketPair::new('"', '"').is_quote());
+        assert!(!BracketPair::new('\'', '\'').no_auto_close().auto_close);
+    }
+
+    #[test]
+    fn test_indent_range_at() {
+        // fn a() {\n    if b {\n    }\n}
+        let ranges = vec![7..28, 19..25];
+        assert_eq!(indent_range_at(&ranges, 7), None);
+        assert_eq!(indent_range_at(&ranges, 8), Some(7..28));
+        assert_eq!(indent_range_at(&ranges, 20), Some(19..25));
+        assert_eq!(indent_range_at(&ranges, 25), Some(19..25));
+        assert_eq!(indent_range_at(&ranges, 26), Some(7..28));
+        assert_eq!(indent_range_at(&ranges, 29), None);
+    }
+}
@@ -329,7 +329,6 @@ impl DiagnosticSet {
         styles
     }
 
-    #[allow(unused)]
     pub(crate) fn iter(&self) -> impl Iterator<Item = &DiagnosticEntry> {
         self.diagnostics.iter()
     }
@@ -0,0 +1,283 @@
+use std::{cmp::Reverse, ops::Range};
+
+use rope::Rope;
+
+use crate::input::RopeExt as _;
+
+/// A foldable region of lines in the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FoldRange {
+    /// The row (0-based) of the first line, it keeps visible when folded.
+    pub start_row: usize,
+    /// The last row (0-based, inclusive) to hide when folded.
+    pub end_row: usize,
+}
+
+impl FoldRange {
+    pub fn new(start_row: usize, end_row: usize) -> Self {
+        Self { start_row, end_row }
+    }
+
+    /// The rows to hide when folded.
+    #[inline]
+    pub fn hidden_rows(&self) -> Range<usize> {
+        self.start_row + 1..self.end_row + 1
+    }
+
+    /// Return true if the row is hidden when folded.
+    #[inline]
+    pub fn hides_row(&self, row: usize) -> bool {
+        row > self.start_row && row <= self.end_row
+    }
+}
+
+/// A vertical indentation guide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndentGuide {
+    /// The rows (0-based) to draw the guide.
+    pub rows: Range<usize>,
+    /// The indent column (in spaces) to draw the guide.
+    pub column: usize,
+}
+
+/// Return the indent width of the line, `None` if the line is blank.
+fn indent_width(line: &str, tab_size: usize) -> Option<usize> {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += tab_size - width % tab_size.max(1),
+            '\r' | '\n' => return None,
+            _ => return Some(width),
+        }
+    }
+
+    None
+}
+
+/// Return true if the line starts with a closing token, e.g.: `}`, `]`, `)` or `end`.
+///
+/// A line like this is kept visible at the end of a fold, to show the folded block as `{ ... }`.
+fn starts_with_closing(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with(['}', ']', ')', '>'])
+        || line == "end"
+        || line.starts_with("end ")
+        || line.starts_with("</")
+}
+
+/// Build the fold ranges from the byte ranges of the foldable nodes.
+///
+/// - The ranges in the same line are ignored.
+/// - The closing line (e.g.: `}`) of the node is kept visible.
+/// - Only the largest range is kept when there are multiple ranges start at the same row.
+pub(crate) fn fold_ranges_for_nodes(
+    text: &Rope,
+    nodes: impl IntoIterator<Item = Range<usize>>,
+) -> Vec<FoldRange> {
+    let mut folds: Vec<FoldRange> = vec![];
+    for range in nodes {
+        let start_row = text.offset_to_point(range.start.min(text.len())).row as usize;
+        let end = text.offset_to_point(range.end.min(text.len()));
+        let mut end_row = end.row as usize;
+
+        let end_line = text.line(end_row).to_string();
+        if starts_with_closing(&end_line[..(end.column as usize).min(end_line.len())]) {
+            end_row = end_row.saturating_sub(1);
+        }
+
+        if end_row <= start_row {
+            continue;
+        }
+
+        folds.push(FoldRange::new(start_row, end_row));
+    }
+
+    folds.sort_by_key(|fold| (fold.start_row, Reverse(fold.end_row)));
+    folds.dedup_by_key(|fold| fold.start_row);
+    folds
+}
+
+/// Build the fold ranges by the indentation of lines, used as a fallback for
+/// the languages without a `folds.scm` query.
+///
+/// A line starts a fold when the following non-blank lines are more indented.
+pub fn indent_fold_ranges(text: &Rope, tab_size: usize) -> Vec<FoldRange> {
+    let indents = text
+        .lines()
+        .map(|line| indent_width(&line.to_string(), tab_size))
+        .collect::<Vec<_>>();
+
+    let mut folds = vec![];
+    for (row, indent) in indents.iter().enumerate() {
+        let Some(indent) = indent else {
+            continue;
+        };
+
+        let mut end_row = row;
+        for (next_row, next_indent) in indents.iter().enumerate().skip(row + 1) {
+            match next_indent {
+                None => continue,
+                Some(next_indent) if next_indent > indent => end_row = next_row,
+                Some(_) => break,
+            }
+        }
+
+        if end_row > row {
+            folds.push(FoldRange::new(row, end_row));
+        }
+    }
+
+    folds
+}
+
+/// Build the indentation guides of the fold ranges.
+///
+/// The guide is drawn under the first character of the fold start line, and
+/// skipped if any line in the fold is not indented deeper than it.
+pub fn indent_guides(text: &Rope, folds: &[FoldRange], tab_size: usize) -> Vec<IndentGuide> {
+    let mut guides: Vec<IndentGuide> = vec![];
+    for fold in folds {
+        let Some(column) = indent_width(&text.line(fold.start_row).to_string(), tab_size) else {
+            continue;
+        };
+
+        let is_indented = fold.hidden_rows().all(|row| {
+            indent_width(&text.line(row).to_string(), tab_size)
+                .map_or(true, |indent| indent > column)
+        });
+        if !is_indented {
+            continue;
+        }
+
+        let guide = IndentGuide {
+            rows: fold.hidden_rows(),
+            column,
+        };
+        if !guides.contains(&guide) {
+            guides.push(guide);
+        }
+    }
+
+    guides
+}
+
+/// Shift the fold ranges by an edit replacing the rows `start_row..=old_end_row` with
+/// `start_row..=new_end_row`.
+///
+/// The fold ranges after the edit are moved, and the fold ranges touched by the edit are
+/// removed, except the edit is in the first line of the fold range.
+pub(crate) fn shift_fold_ranges(
+    folds: &[FoldRange],
+    start_row: usize,
+    old_end_row: usize,
+    new_end_row: usize,
+) -> Vec<FoldRange> {
+    folds
+        .iter()
+        .filter_map(|fold| {
+            if old_end_row < fold.start_row {
+                Some(FoldRange::new(
+                    fold.start_row + new_end_row - old_end_row,
+                    fold.end_row + new_end_row - old_end_row,
+                ))
+            } else if start_row > fold.end_row
+                || (start_row == fold.start_row
+                    && old_end_row == fold.start_row
+                    && new_end_row == fold.start_row)
+            {
+                Some(*fold)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rope::Rope;
+
+    use super::*;
+
+    #[test]
+    fn test_indent_width() {
+        assert_eq!(indent_width("", 4), None);
+        assert_eq!(indent_width("    ", 4), None);
+        assert_eq!(indent_width("foo", 4), Some(0));
+        assert_eq!(indent_width("  foo", 4), Some(2));
+        assert_eq!(indent_width("\tfoo", 4), Some(4));
+        assert_eq!(indent_width("  \tfoo", 4), Some(4));
+    }
+
+    #[test]
+    fn test_fold_ranges_for_nodes() {
+        let text = Rope::from("fn main() {\n    let a = [\n        1,\n    ];\n}\nfn foo() {}");
+        let folds = fold_ranges_for_nodes(&text, vec![10..45, 24..42, 55..57]);
+        assert_eq!(folds, vec![FoldRange::new(0, 3), FoldRange::new(1, 2)]);
+
+        // Keep the largest range at the same row.
+        let folds = fold_ranges_for_nodes(&text, vec![0..45, 10..42]);
+        assert_eq!(folds, vec![FoldRange::new(0, 3)]);
+    }
+
+    #[test]
+    fn test_indent_fold_ranges() {
+        let text = Rope::from("def foo():\n    a = 1\n\n    if a:\n        b = 2\n\nc = 3\n");
+        assert_eq!(
+            indent_fold_ranges(&text, 4),
+            vec![FoldRange::new(0, 4), FoldRange::new(3, 4)]
+        );
+    }
+
+    #[test]
+    fn test_indent_guides() {
+        let text = Rope::from("fn main() {\n    if a {\n        b();\n    }\n}\n");
+        let folds = vec![FoldRange::new(0, 3), FoldRange::new(1, 2)];
+        assert_eq!(
+            indent_guides(&text, &folds, 4),
+            vec![
+                IndentGuide {
+                    rows: 1..4,
+                    column: 0
+                },
+                IndentGuide {
+                    rows: 2..3,
+                    column: 4
+                },
+            ]
+        );
+
+        // Skip the guide if a line is not indented.
+        let text = Rope::from("let a = \"\nfoo\n\";\n");
+        assert_eq!(indent_guides(&text, &[FoldRange::new(0, 1)], 4), vec![]);
+    }
+
+    #[test]
+    fn test_shift_fold_ranges() {
+        let folds = vec![FoldRange::new(2, 4), FoldRange::new(8, 10)];
+
+        // Insert 2 lines before the folds.
+        assert_eq!(
+            shift_fold_ranges(&folds, 0, 0, 2),
+            vec![FoldRange::new(4, 6), FoldRange::new(10, 12)]
+        );
+        // Delete 1 line between the folds.
+        assert_eq!(
+            shift_fold_ranges(&folds, 5, 6, 5),
+            vec![FoldRange::new(2, 4), FoldRange::new(7, 9)]
+        );
+        // Edit in the first line of the fold.
+        assert_eq!(shift_fold_ranges(&folds, 2, 2, 2), folds);
+        // Edit in the hidden lines of the fold.
+        assert_eq!(
+            shift_fold_ranges(&folds, 3, 3, 3),
+            vec![FoldRange::new(8, 10)]
+        );
+        // Insert a new line in the first line of the fold.
+        assert_eq!(
+            shift_fold_ranges(&folds, 2, 2, 3),
+            vec![FoldRange::new(9, 11)]
+        );
+    }
+}
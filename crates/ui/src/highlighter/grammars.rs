@@ -0,0 +1,386 @@
+use anyhow::{anyhow, Context as _, Result};
+use gpui::{App, SharedString};
+use notify::Watcher as _;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::highlighter::{LanguageConfig, LanguageRegistry};
+
+/// The file extensions of the grammar libraries.
+const LIBRARY_EXTENSIONS: [&str; 3] = ["so", "dylib", "dll"];
+
+/// The query files of a grammar.
+const QUERY_FILES: [&str; 5] = [
+    "highlights.scm",
+    "injections.scm",
+    "locals.scm",
+    "folds.scm",
+    "indents.scm",
+];
+
+/// The optional `config.json` in the grammar directory.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+struct GrammarConfig {
+    /// The name of the language, default: the name of the grammar directory.
+    name: Option<String>,
+    /// The file name of the grammar library, default: the first library in the directory.
+    library: Option<String>,
+    /// The symbol of the language function, default: `tree_sitter_{name}`.
+    symbol: Option<String>,
+    injection_languages: Vec<String>,
+    path_suffixes: Vec<String>,
+    shebangs: Vec<String>,
+    line_comment: Option<String>,
+    block_comment: Option<(String, String)>,
+}
+
+/// A grammar library loaded at runtime.
+///
+/// The library is never unloaded while the registry holds it, because the parsed trees
+/// keep pointers to the language.
+#[derive(Clone)]
+pub(super) struct LoadedGrammar {
+    _library: Arc<libloading::Library>,
+    language: tree_sitter::Language,
+}
+
+/// Return the default symbol of the language function, e.g.: `tree_sitter_foo_bar` for `foo-bar`.
+fn language_symbol(name: &str) -> String {
+    format!("tree_sitter_{}", name.replace(['-', '.'], "_"))
+}
+
+fn is_library(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| LIBRARY_EXTENSIONS.contains(&ext))
+}
+
+/// Read the query file in the directory, empty if the file does not exist.
+fn read_query(dir: &Path, file_name: &str) -> Result<String> {
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return Ok(String::new());
+    }
+
+    fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))
+}
+
+impl LanguageRegistry {
+    /// Load the grammars in the `grammars_dir`, and watch the directory to reload the
+    /// queries when the files change, the languages of the removed grammars are unregistered.
+    ///
+    /// Each grammar is a sub directory with a grammar library (`.so`, `.dylib` or `.dll`),
+    /// the query files (`highlights.scm`, `injections.scm`, `locals.scm`, `folds.scm`,
+    /// `indents.scm`) and an optional `config.json`:
+    ///
+    /// ```json
+    /// {
+    ///   "name": "foo",
+    ///   "path_suffixes": ["foo"],
+    ///   "shebangs": ["foo"],
+    ///   "line_comment": "#"
+    /// }
+    /// ```
+    pub fn watch_grammars_dir(grammars_dir: PathBuf, cx: &mut App) -> Result<()> {
+        Self::reload_grammars(&grammars_dir, cx);
+
+        let (tx, rx) = smol::channel::bounded(100);
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = &res {
+                    match event.kind {
+                        notify::EventKind::Create(_)
+                        | notify::EventKind::Modify(_)
+                        | notify::EventKind::Remove(_) => {
+                            if let Err(err) = tx.send_blocking(res) {
+                                tracing::error!("Failed to send grammar event: {:?}", err);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            })?;
+
+        cx.spawn(async move |cx| {
+            if let Err(err) = watcher.watch(&grammars_dir, notify::RecursiveMode::Recursive) {
+                tracing::error!("Failed to watch grammars directory: {:?}", err);
+            }
+
+            while (rx.recv().await).is_ok() {
+                // Merge the events of a save into one reload.
+                while rx.try_recv().is_ok() {}
+
+                tracing::info!("Reloading grammars...");
+                _ = cx.update(|cx| Self::reload_grammars(&grammars_dir, cx));
+            }
+        })
+        .detach();
+
+        Ok(())
+    }
+
+    fn reload_grammars(grammars_dir: &Path, cx: &mut App) {
+        let registry = Self::global_mut(cx);
+        match registry.load_grammars_dir(grammars_dir) {
+            Ok(names) => tracing::info!("Grammars loaded: {:?}", names),
+            Err(err) => tracing::error!("Failed to load grammars: {:?}", err),
+        }
+
+        let names = registry.unload_removed_grammars();
+        if !names.is_empty() {
+            tracing::info!("Grammars removed: {:?}", names);
+        }
+    }
+
+    /// Unregister the languages whose grammar directory is removed, returns their names.
+    pub fn unload_removed_grammars(&mut self) -> Vec<SharedString> {
+        let removed_dirs = self
+            .grammar_dirs
+            .keys()
+            .filter(|dir| !dir.is_dir())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut names = vec![];
+        for dir in removed_dirs {
+            if let Some(name) = self.grammar_dirs.remove(&dir) {
+                self.unregister(&name);
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Load and register the grammars in the sub directories of the `grammars_dir`.
+    ///
+    /// The invalid grammars are skipped with an error log, returns the names of the loaded languages.
+    pub fn load_grammars_dir(&mut self, grammars_dir: &Path) -> Result<Vec<SharedString>> {
+        let mut names = vec![];
+        for entry in fs::read_dir(grammars_dir)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            match self.load_grammar(&path) {
+                Ok(config) => names.push(config.name),
+                Err(err) => {
+                    tracing::error!("ignored invalid grammar: {}, {:?}", path.display(), err)
+                }
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Load and register the grammar in the `dir`, returns the language config.
+    ///
+    /// The grammar library is only loaded once, the queries are read again to reload them.
+    pub fn load_grammar(&mut self, dir: &Path) -> Result<LanguageConfig> {
+        let config_path = dir.join("config.json");
+        let grammar: GrammarConfig = if config_path.exists() {
+            serde_json::from_str(&fs::read_to_string(&config_path)?)
+                .with_context(|| format!("parse {}", config_path.display()))?
+        } else {
+            GrammarConfig::default()
+        };
+
+        let name = match &grammar.name {
+            Some(name) => name.clone(),
+            None => dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| anyhow!("invalid grammar directory: {}", dir.display()))?
+                .to_string(),
+        };
+
+        let library_path = match &grammar.library {
+            Some(library) => dir.join(library),
+            None => fs::read_dir(dir)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .find(|path| is_library(path))
+                .ok_or_else(|| anyhow!("no grammar library in {}", dir.display()))?,
+        };
+        let symbol = grammar
+            .symbol
+            .clone()
+            .unwrap_or_else(|| language_symbol(&name));
+        let language = self.load_library(&library_path, &symbol)?;
+
+        let [highlights, injections, locals, folds, indents] =
+            QUERY_FILES.map(|file_name| read_query(dir, file_name));
+        let (highlights, injections, locals, folds, indents) =
+            (highlights?, injections?, locals?, folds?, indents?);
+
+        // Check the queries to keep the previous config if any query is invalid.
+        let queries = [&highlights, &injections, &locals, &folds, &indents];
+        for (file_name, source) in QUERY_FILES.iter().zip(queries) {
+            if !source.is_empty() {
+                tree_sitter::Query::new(&language, source)
+                    .with_context(|| format!("invalid query: {}", dir.join(file_name).display()))?;
+            }
+        }
+
+        let mut config = LanguageConfig::new(
+            name.clone(),
+            language,
+            grammar
+                .injection_languages
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            &highlights,
+            &injections,
+            &locals,
+        )
+        .folds(&folds)
+        .indents(&indents)
+        .path_suffixes(grammar.path_suffixes)
+        .shebangs(grammar.shebangs);
+        if let Some(token) = grammar.line_comment {
+            config = config.line_comment(token);
+        }
+        if let Some((start, end)) = grammar.block_comment {
+            config = config.block_comment(start, end);
+        }
+
+        // Unregister the previous name of the grammar if it's changed in the `config.json`.
+        let old_name = self
+            .grammar_dirs
+            .insert(dir.to_path_buf(), config.name.clone());
+        if let Some(old_name) = old_name.filter(|old_name| *old_name != config.name) {
+            self.unregister(&old_name);
+        }
+        self.register(&name, &config);
+        Ok(config)
+    }
+
+    /// Load the language of the grammar library, or return the loaded one.
+    fn load_library(&mut self, path: &Path, symbol: &str) -> Result<tree_sitter::Language> {
+        if let Some(grammar) = self.grammars.get(path) {
+            return Ok(grammar.language.clone());
+        }
+
+        // SAFETY: The grammar library is expected to be a tree-sitter grammar built by the
+        // tree-sitter CLI, which exports the language function as `symbol`.
+        let library = unsafe { libloading::Library::new(path) }
+            .with_context(|| format!("load grammar library {}", path.display()))?;
+        let language = unsafe {
+            let language_fn: libloading::Symbol<unsafe extern "C" fn() -> *const ()> = library
+                .get(symbol.as_bytes())
+                .with_context(|| format!("find symbol {} in {}", symbol, path.display()))?;
+            tree_sitter::Language::new(tree_sitter::LanguageFn::from_raw(*language_fn))
+        };
+
+        self.grammars.insert(
+            path.to_path_buf(),
+            LoadedGrammar {
+                _library: Arc::new(library),
+                language: language.clone(),
+            },
+        );
+        Ok(language)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_symbol() {
+        assert_eq!(language_symbol("foo"), "tree_sitter_foo");
+        assert_eq!(language_symbol("foo-bar.baz"), "tree_sitter_foo_bar_baz");
+    }
+
+    #[test]
+    fn test_grammar_config() {
+        let config: GrammarConfig = serde_json::from_str(
+            r#"{ "name": "foo", "path_suffixes": ["foo"], "block_comment": ["/*", "*/"] }"#,
+        )
+        .unwrap();
+        assert_eq!(config.name.as_deref(), Some("foo"));
+        assert_eq!(config.path_suffixes, vec!["foo"]);
+        assert_eq!(
+            config.block_comment,
+            Some(("/*".to_string(), "*/".to_string()))
+        );
+        assert!(config.shebangs.is_empty());
+
+        assert!(is_library(Path::new("foo/libtree-sitter-foo.so")));
+        assert!(is_library(Path::new("foo.dylib")));
+        assert!(!is_library(Path::new("foo/highlights.scm")));
+    }
+
+    /// A new empty directory in the temp directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("gpui-component-{}-{}", name, std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_invalid_grammar() {
+        let grammars_dir = temp_dir("invalid-grammars");
+        let mut registry = LanguageRegistry::new();
+        let languages_len = registry.languages().len();
+
+        // No grammar library.
+        let dir = grammars_dir.join("foo");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("highlights.scm"), "(identifier) @variable").unwrap();
+        let err = registry.load_grammar(&dir).unwrap_err();
+        assert!(err.to_string().contains("no grammar library"), "{err}");
+
+        // Not a library.
+        fs::write(dir.join("libtree-sitter-foo.so"), "foo").unwrap();
+        assert!(registry.load_grammar(&dir).is_err());
+
+        // The library in the `config.json` is missing.
+        fs::write(dir.join("config.json"), r#"{ "library": "bar.so" }"#).unwrap();
+        assert!(registry.load_grammar(&dir).is_err());
+
+        assert_eq!(
+            registry.load_grammars_dir(&grammars_dir).unwrap(),
+            Vec::<SharedString>::new()
+        );
+        assert!(registry.language("foo").is_none());
+        assert_eq!(registry.languages().len(), languages_len);
+        assert!(registry.grammars.is_empty());
+        assert!(registry.grammar_dirs.is_empty());
+
+        fs::remove_dir_all(&grammars_dir).unwrap();
+    }
+
+    #[test]
+    fn test_unload_removed_grammars() {
+        let grammars_dir = temp_dir("removed-grammars");
+        let mut registry = LanguageRegistry::new();
+        for name in ["foo", "bar"] {
+            let config =
+                LanguageConfig::new(name, tree_sitter_json::LANGUAGE.into(), vec![], "", "", "");
+            registry.register(name, &config);
+            registry
+                .grammar_dirs
+                .insert(grammars_dir.join(name), name.into());
+        }
+        fs::create_dir_all(grammars_dir.join("foo")).unwrap();
+
+        assert_eq!(
+            registry.unload_removed_grammars(),
+            vec![SharedString::from("bar")]
+        );
+        assert!(registry.language("foo").is_some());
+        assert!(registry.language("bar").is_none());
+        assert!(registry.unload_removed_grammars().is_empty());
+
+        fs::remove_dir_all(&grammars_dir).unwrap();
+    }
+}
@@ -1,10 +1,19 @@
-use crate::{highlighter::LanguageRegistry, input::RopeExt as _, ActiveTheme};
+use crate::{
+    highlighter::{
+        fold_ranges_for_nodes, is_sticky_scope,
+        locals::{resolve_local_references, LocalName, LocalScope},
+        BracketPair, FoldRange, LanguageConfig, LanguageRegistry,
+    },
+    input::RopeExt as _,
+    ActiveTheme,
+};
 
 use anyhow::{anyhow, Context, Result};
-use gpui::{App, HighlightStyle, SharedString};
+use gpui::{App, HighlightStyle, Hsla, SharedString};
 
 use rope::Rope;
 use std::{
+    cell::RefCell,
     collections::{BTreeSet, HashMap},
     ops::Range,
     usize,
@@ -20,6 +29,13 @@ pub struct SyntaxHighlighter {
     language: SharedString,
     query: Option<Query>,
     injection_queries: HashMap<SharedString, Query>,
+    /// The query to capture the foldable nodes, `None` if the language has no folds query.
+    fold_query: Option<Query>,
+    /// The query to capture the nodes to indent, `None` if the language has no indents query.
+    indent_query: Option<Query>,
+    brackets: Vec<BracketPair>,
+    line_comment: Option<SharedString>,
+    block_comment: Option<(SharedString, SharedString)>,
 
     locals_pattern_index: usize,
     highlights_pattern_index: usize,
@@ -37,6 +53,15 @@ pub struct SyntaxHighlighter {
     parser: Parser,
     /// The last parsed tree.
     tree: Option<Tree>,
+    /// The highlight names of the local references resolved in a top level node of the
+    /// last parsed tree, by the range of the node.
+    local_highlights: RefCell<Option<(Range<usize>, HashMap<Range<usize>, SharedString>)>>,
+    /// The nesting depths of the brackets in the last parsed tree, sorted by the range of
+    /// the bracket, computed at the first paint after the parse.
+    bracket_depths: RefCell<Option<Vec<(Range<usize>, usize)>>>,
+    /// The registered configs of the language and its injection languages when the
+    /// highlighter was built, `None` for the unregistered ones.
+    configs: Vec<Option<LanguageConfig>>,
 }
 
 struct TextProvider<'a>(&'a Rope);
@@ -140,7 +165,7 @@ impl<'a> sum_tree::Dimension<'a, HighlightSummary> for Range<usize> {
 impl SyntaxHighlighter {
     /// Create a new SyntaxHighlighter for HTML.
     pub fn new(lang: &str, cx: &App) -> Self {
-        match Self::build_combined_injections_query(&lang, cx) {
+        let mut highlighter = match Self::build_combined_injections_query(&lang, cx) {
             Ok(result) => result,
             Err(err) => {
                 tracing::warn!(
@@ -149,7 +174,29 @@ impl SyntaxHighlighter {
                 );
                 Self::build_combined_injections_query("text", cx).unwrap()
             }
-        }
+        };
+        highlighter.configs = Self::registered_configs(lang, cx);
+        highlighter
+    }
+
+    /// Return the registered configs of the language and its injection languages.
+    fn registered_configs(lang: &str, cx: &App) -> Vec<Option<LanguageConfig>> {
+        let registry = LanguageRegistry::global(cx);
+        let config = registry.language(lang);
+        let injection_configs = config
+            .into_iter()
+            .flat_map(|config| config.injection_languages.iter())
+            .map(|name| registry.language(name).cloned());
+
+        std::iter::once(config.cloned())
+            .chain(injection_configs)
+            .collect()
+    }
+
+    /// Return true if the config of the language `lang` or its injection languages in the
+    /// [`LanguageRegistry`] is changed since the highlighter was built.
+    pub fn is_outdated(&self, lang: &str, cx: &App) -> bool {
+        Self::registered_configs(lang, cx) != self.configs
     }
 
     /// Build the combined injections query for the given language.
@@ -266,12 +313,45 @@ impl SyntaxHighlighter {
             }
         }
 
+        let fold_query = if config.folds.is_empty() {
+            None
+        } else {
+            match Query::new(&config.language, &config.folds) {
+                Ok(q) => Some(q),
+                Err(e) => {
+                    tracing::error!("failed to build folds query for {:?}: {:?}", config.name, e);
+                    None
+                }
+            }
+        };
+
+        let indent_query = if config.indents.is_empty() {
+            None
+        } else {
+            match Query::new(&config.language, &config.indents) {
+                Ok(q) => Some(q),
+                Err(e) => {
+                    tracing::error!(
+                        "failed to build indents query for {:?}: {:?}",
+                        config.name,
+                        e
+                    );
+                    None
+                }
+            }
+        };
+
         // let highlight_indices = vec![None; query.capture_names().len()];
 
         Ok(Self {
             language: config.name.clone(),
             query: Some(query),
             injection_queries,
+            fold_query,
+            indent_query,
+            brackets: config.brackets.clone(),
+            line_comment: config.line_comment.clone(),
+            block_comment: config.block_comment.clone(),
 
             locals_pattern_index,
             highlights_pattern_index,
@@ -285,6 +365,9 @@ impl SyntaxHighlighter {
             text: Rope::new(),
             parser,
             tree: None,
+            local_highlights: RefCell::new(None),
+            bracket_depths: RefCell::new(None),
+            configs: vec![],
         })
     }
 
@@ -331,6 +414,8 @@ impl SyntaxHighlighter {
 
         self.tree = Some(new_tree);
         self.text = text.clone();
+        self.local_highlights.take();
+        self.bracket_depths.take();
     }
 
     /// Match the visible ranges of nodes in the Tree for highlighting.
@@ -346,12 +431,25 @@ impl SyntaxHighlighter {
 
         let root_node = tree.root_node();
 
+        let local_highlights = self.local_highlights(query, root_node, &range);
+
         let source = &self.text;
         let mut cursor = QueryCursor::new();
-        cursor.set_byte_range(range);
+        cursor.set_byte_range(range.clone());
         let mut matches = cursor.matches(&query, root_node, TextProvider(&source));
 
         while let Some(query_match) = matches.next() {
+            // The locals patterns are only used to resolve the references.
+            let pattern_index = query_match.pattern_index;
+            if (self.locals_pattern_index..self.highlights_pattern_index).contains(&pattern_index) {
+                continue;
+            }
+            let is_non_local_pattern = self
+                .non_local_variable_patterns
+                .get(pattern_index)
+                .copied()
+                .unwrap_or(false);
+
             // Ref:
             // https://github.com/tree-sitter/tree-sitter/blob/460118b4c82318b083b4d527c9c750426730f9c0/highlight/src/lib.rs#L556
             if let (Some(language_name), Some(content_node), _) =
@@ -367,6 +465,10 @@ impl SyntaxHighlighter {
 
             for cap in query_match.captures {
                 let node = cap.node;
+                // Skip the patterns with `(#is-not? local)` for the local references.
+                if is_non_local_pattern && local_highlights.contains_key(&node.byte_range()) {
+                    continue;
+                }
 
                 let Some(highlight_name) = query.capture_names().get(cap.index as usize) else {
                     continue;
@@ -401,6 +503,16 @@ impl SyntaxHighlighter {
             }
         }
 
+        // Highlight the local references same as their definitions.
+        for (node_range, highlight_name) in local_highlights.iter() {
+            if node_range.start < range.end && node_range.end > range.start {
+                highlights.push(HighlightItem::new(
+                    node_range.clone(),
+                    highlight_name.clone(),
+                ));
+            }
+        }
+
         // DO NOT REMOVE THIS PRINT, it's useful for debugging
         // for item in highlights {
         //     println!("item: {:?}", item);
@@ -409,6 +521,97 @@ impl SyntaxHighlighter {
         highlights
     }
 
+    /// Return the highlight names of the local references by the locals query, each
+    /// reference has the highlight name of its definition.
+    ///
+    /// The references are resolved in the top level node at the `range`, to find the
+    /// definitions before the range, and cached until the next parse.
+    fn local_highlights(
+        &self,
+        query: &Query,
+        root_node: Node,
+        range: &Range<usize>,
+    ) -> HashMap<Range<usize>, SharedString> {
+        if self.locals_pattern_index == self.highlights_pattern_index {
+            return HashMap::new();
+        }
+
+        if let Some((cached_range, highlights)) = self.local_highlights.borrow().as_ref() {
+            if cached_range.start <= range.start && range.end <= cached_range.end {
+                return highlights.clone();
+            }
+        }
+
+        let scope_range = match root_node.first_child_for_byte(range.start) {
+            Some(node) => node.start_byte().min(range.start)..node.end_byte().max(range.end),
+            None => range.clone(),
+        };
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(scope_range.clone());
+        let mut matches = cursor.matches(query, root_node, TextProvider(&self.text));
+        let mut scopes = vec![];
+        let mut defs = vec![];
+        let mut refs = vec![];
+        let mut node_highlights: HashMap<Range<usize>, SharedString> = HashMap::new();
+        while let Some(query_match) = matches.next() {
+            let pattern_index = query_match.pattern_index;
+            if pattern_index < self.locals_pattern_index {
+                continue;
+            }
+
+            // The first highlight of the nodes, to find the highlights of the definitions.
+            if pattern_index >= self.highlights_pattern_index {
+                for cap in query_match.captures {
+                    if let Some(name) = query.capture_names().get(cap.index as usize) {
+                        node_highlights
+                            .entry(cap.node.byte_range())
+                            .or_insert_with(|| SharedString::from(name.to_string()));
+                    }
+                }
+                continue;
+            }
+
+            for cap in query_match.captures {
+                let index = Some(cap.index);
+                let node_range = cap.node.byte_range();
+                if index == self.local_scope_capture_index {
+                    let inherits = !query.property_settings(pattern_index).iter().any(|prop| {
+                        prop.key.as_ref() == "local.scope-inherits"
+                            && prop.value.as_deref() == Some("false")
+                    });
+                    scopes.push(LocalScope {
+                        range: node_range,
+                        inherits,
+                    });
+                } else if index == self.local_def_capture_index
+                    || index == self.local_ref_capture_index
+                {
+                    let name = LocalName {
+                        name: self.text.slice(node_range.clone()).to_string(),
+                        range: node_range,
+                    };
+                    if index == self.local_def_capture_index {
+                        defs.push(name);
+                    } else {
+                        refs.push(name);
+                    }
+                }
+            }
+        }
+
+        let highlights = resolve_local_references(&scopes, &defs, &refs)
+            .into_iter()
+            .filter_map(|(ref_range, def_range)| {
+                Some((ref_range, node_highlights.get(&def_range)?.clone()))
+            })
+            .collect::<HashMap<_, _>>();
+
+        self.local_highlights
+            .replace(Some((scope_range, highlights.clone())));
+        highlights
+    }
+
     /// TODO: Use incremental parsing to handle the injection.
     fn handle_injection(
         &self,
@@ -544,6 +747,293 @@ impl SyntaxHighlighter {
         (language_name, content_node, include_children)
     }
 
+    /// Return the foldable ranges of the nodes captured as `@fold` by the folds query in the last parsed tree.
+    ///
+    /// Returns `None` if the language has no folds query.
+    pub fn fold_ranges(&self) -> Option<Vec<FoldRange>> {
+        let query = self.fold_query.as_ref()?;
+        let Some(tree) = &self.tree else {
+            return Some(vec![]);
+        };
+        let Some(fold_capture_index) = query.capture_index_for_name("fold") else {
+            return Some(vec![]);
+        };
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, tree.root_node(), TextProvider(&self.text));
+        let mut ranges = vec![];
+        while let Some(query_match) = matches.next() {
+            for cap in query_match.captures {
+                if cap.index == fold_capture_index {
+                    ranges.push(cap.node.byte_range());
+                }
+            }
+        }
+
+        Some(fold_ranges_for_nodes(&self.text, ranges))
+    }
+
+    /// Return the ranges of the indents in the last parsed tree that intersect the `range`.
+    ///
+    /// The range is from the start of a node captured as `@indent` to the start of the
+    /// closing token captured as `@end` in it, or the end of the node.
+    ///
+    /// Returns `None` if the language has no indents query.
+    pub fn indent_ranges(&self, range: Range<usize>) -> Option<Vec<Range<usize>>> {
+        let query = self.indent_query.as_ref()?;
+        let Some(tree) = &self.tree else {
+            return Some(vec![]);
+        };
+        let Some(indent_capture_index) = query.capture_index_for_name("indent") else {
+            return Some(vec![]);
+        };
+        let end_capture_index = query.capture_index_for_name("end");
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(range);
+        let mut matches = cursor.matches(query, tree.root_node(), TextProvider(&self.text));
+        let mut ranges = vec![];
+        while let Some(query_match) = matches.next() {
+            let mut indent_range: Option<Range<usize>> = None;
+            let mut end: Option<usize> = None;
+            for cap in query_match.captures {
+                if cap.index == indent_capture_index {
+                    indent_range = Some(cap.node.byte_range());
+                } else if Some(cap.index) == end_capture_index {
+                    end = Some(cap.node.start_byte());
+                }
+            }
+
+            if let Some(mut indent_range) = indent_range {
+                if let Some(end) = end {
+                    indent_range.end = end;
+                }
+                ranges.push(indent_range);
+            }
+        }
+
+        Some(ranges)
+    }
+
+    /// Return the brackets and quotes of the language.
+    pub fn brackets(&self) -> &[BracketPair] {
+        &self.brackets
+    }
+
+    /// Return the line comment token of the language, e.g.: `//`.
+    pub fn line_comment(&self) -> Option<&SharedString> {
+        self.line_comment.as_ref()
+    }
+
+    /// Return the block comment tokens of the language, e.g.: `/*` and `*/`.
+    pub fn block_comment(&self) -> Option<&(SharedString, SharedString)> {
+        self.block_comment.as_ref()
+    }
+
+    /// Return the ranges of the bracket pair next to the `offset` in the last parsed tree,
+    /// the bracket after the `offset` is preferred.
+    ///
+    /// The brackets are matched within the same parent node, so the brackets in strings
+    /// or comments are ignored.
+    pub fn bracket_pair_at(&self, offset: usize) -> Option<(Range<usize>, Range<usize>)> {
+        let tree = self.tree.as_ref()?;
+
+        let mut offsets = vec![offset];
+        if let Some(c) = self.text.reversed_chars_at(offset).next() {
+            offsets.push(offset - c.len_utf8());
+        }
+
+        for offset in offsets {
+            let Some(node) = tree
+                .root_node()
+                .descendant_for_byte_range(offset, offset + 1)
+            else {
+                continue;
+            };
+            if node.child_count() > 0 || node.start_byte() != offset {
+                continue;
+            }
+
+            for pair in self.brackets.iter().filter(|pair| !pair.is_quote()) {
+                if let Some(other) = self.matching_bracket_node(node, pair) {
+                    let (start, end) = if other.start_byte() > node.start_byte() {
+                        (node, other)
+                    } else {
+                        (other, node)
+                    };
+                    return Some((start.byte_range(), end.byte_range()));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find the matching bracket of the bracket `node` in its siblings.
+    fn matching_bracket_node<'a>(&self, node: Node<'a>, pair: &BracketPair) -> Option<Node<'a>> {
+        let mut buf = [0; 4];
+        let start = pair.start.encode_utf8(&mut buf).to_string();
+        let end = pair.end.encode_utf8(&mut buf).to_string();
+
+        let forward = if node.kind() == start {
+            true
+        } else if node.kind() == end {
+            false
+        } else {
+            return None;
+        };
+        let (open, close) = if forward { (start, end) } else { (end, start) };
+
+        let mut depth = 0;
+        let mut sibling = node;
+        loop {
+            sibling = if forward {
+                sibling.next_sibling()?
+            } else {
+                sibling.prev_sibling()?
+            };
+
+            if sibling.is_missing() {
+                continue;
+            }
+            if sibling.kind() == open {
+                depth += 1;
+            } else if sibling.kind() == close {
+                if depth == 0 {
+                    return Some(sibling);
+                }
+                depth -= 1;
+            }
+        }
+    }
+
+    /// Return the styles to color the brackets in the range by their nesting depth, empty if
+    /// the `colors` is empty.
+    fn rainbow_bracket_styles(
+        &self,
+        range: &Range<usize>,
+        colors: &[Hsla],
+    ) -> Vec<(Range<usize>, HighlightStyle)> {
+        let Some(tree) = &self.tree else {
+            return vec![];
+        };
+        if colors.is_empty() {
+            return vec![];
+        }
+
+        let mut bracket_depths = self.bracket_depths.borrow_mut();
+        let bracket_depths =
+            bracket_depths.get_or_insert_with(|| self.compute_bracket_depths(tree.root_node()));
+        let start = bracket_depths.partition_point(|(bracket, _)| bracket.start < range.start);
+        bracket_depths[start..]
+            .iter()
+            .take_while(|(bracket, _)| bracket.start < range.end)
+            .map(|(bracket, depth)| {
+                (
+                    bracket.clone(),
+                    HighlightStyle {
+                        color: Some(colors[depth % colors.len()]),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Return the nesting depths of all the brackets in the tree, by one walk of it.
+    ///
+    /// The depth is the number of the ancestors above the parent having an open bracket before
+    /// the bracket. The brackets in the strings or comments are not colored, because they are
+    /// not leaf nodes.
+    fn compute_bracket_depths(&self, root: Node) -> Vec<(Range<usize>, usize)> {
+        let is_open_bracket = |node: &Node| {
+            let mut chars = node.kind().chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => self
+                    .brackets
+                    .iter()
+                    .any(|pair| !pair.is_quote() && pair.start == c),
+                _ => false,
+            }
+        };
+
+        let mut depths = vec![];
+        // Whether an open bracket is in the visited children of each node on the path to the
+        // cursor, and the count of them.
+        let mut opened: Vec<bool> = vec![];
+        let mut open_count = 0;
+        let mut cursor = root.walk();
+        loop {
+            let node = cursor.node();
+            if node.child_count() == 0 {
+                let range = node.byte_range();
+                let is_bracket = self.text.chars_at(range.start).next().is_some_and(|c| {
+                    c.len_utf8() == range.len()
+                        && self
+                            .brackets
+                            .iter()
+                            .any(|pair| !pair.is_quote() && (pair.start == c || pair.end == c))
+                });
+                if is_bracket && !node.is_missing() {
+                    let in_parent = opened.last().copied().unwrap_or(false);
+                    depths.push((range, open_count - in_parent as usize));
+                }
+
+                if is_open_bracket(&node) {
+                    if let Some(in_parent) = opened.last_mut().filter(|opened| !**opened) {
+                        *in_parent = true;
+                        open_count += 1;
+                    }
+                }
+            }
+
+            if cursor.goto_first_child() {
+                opened.push(false);
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return depths;
+                }
+                if opened.pop() == Some(true) {
+                    open_count -= 1;
+                }
+            }
+        }
+    }
+
+    /// Return the row ranges of the scopes (e.g.: functions, classes) in the last parsed tree
+    /// that start before the row and enclose it, outermost first.
+    pub fn sticky_scopes(&self, row: usize) -> Vec<Range<usize>> {
+        let Some(tree) = &self.tree else {
+            return vec![];
+        };
+        if row >= self.text.lines_len() {
+            return vec![];
+        }
+
+        let offset = self.text.line_start_offset(row);
+        let mut scopes = vec![];
+        let mut node = tree.root_node().descendant_for_byte_range(offset, offset);
+        while let Some(current) = node {
+            node = current.parent();
+            // Skip the root node, e.g.: `module` in Python.
+            if node.is_none() || !is_sticky_scope(current.kind()) {
+                continue;
+            }
+
+            let start_row = current.start_position().row;
+            let end_row = current.end_position().row;
+            if start_row < row && end_row >= row {
+                scopes.push(start_row..end_row + 1);
+            }
+        }
+
+        scopes.reverse();
+        scopes.dedup_by_key(|scope| scope.start);
+        scopes
+    }
+
     /// The argument `range` is the range of the line in the text.
     ///
     /// Returns `range` is the range in the line.
@@ -575,6 +1065,8 @@ impl SyntaxHighlighter {
         }
         // dbg!(iter_count);
 
+        styles.extend(self.rainbow_bracket_styles(range, &theme.style.rainbow_brackets));
+
         // If the matched styles is empty, return a default range.
         if styles.len() == 0 {
             return vec![(start_offset..range.end, HighlightStyle::default())];
@@ -800,4 +1292,40 @@ mod tests {
             ],
         );
     }
+
+    #[gpui::test]
+    fn test_is_outdated(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::highlighter::init(cx);
+            let config = |name: &str, injection_languages: Vec<SharedString>| {
+                LanguageConfig::new(
+                    name.to_string(),
+                    tree_sitter_json::LANGUAGE.into(),
+                    injection_languages,
+                    "",
+                    "",
+                    "",
+                )
+            };
+
+            LanguageRegistry::global_mut(cx).register("foo", &config("foo", vec!["bar".into()]));
+            let highlighter = SyntaxHighlighter::new("foo", cx);
+            assert!(!highlighter.is_outdated("foo", cx));
+
+            // Not the language or its injection languages.
+            LanguageRegistry::global_mut(cx).register("baz", &config("baz", vec![]));
+            assert!(!highlighter.is_outdated("foo", cx));
+
+            // The injection language is registered.
+            LanguageRegistry::global_mut(cx).register("bar", &config("bar", vec![]));
+            assert!(highlighter.is_outdated("foo", cx));
+
+            let highlighter = SyntaxHighlighter::new("foo", cx);
+            LanguageRegistry::global_mut(cx).register("foo", &config("foo", vec!["bar".into()]));
+            assert!(!highlighter.is_outdated("foo", cx));
+
+            LanguageRegistry::global_mut(cx).unregister("foo");
+            assert!(highlighter.is_outdated("foo", cx));
+        });
+    }
 }
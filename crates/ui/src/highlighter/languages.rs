@@ -1,6 +1,6 @@
 use gpui::SharedString;
 
-use crate::highlighter::LanguageConfig;
+use crate::highlighter::{default_brackets, BracketPair, LanguageConfig};
 
 #[cfg(not(feature = "tree-sitter-languages"))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, enum_iterator::Sequence)]
@@ -172,6 +172,174 @@ impl Language {
         .collect()
     }
 
+    /// Return the folds query for the language, empty to fold by indentation.
+    #[allow(unused)]
+    pub(super) fn folds(&self) -> &'static str {
+        #[cfg(not(feature = "tree-sitter-languages"))]
+        return include_str!("languages/json/folds.scm");
+
+        #[cfg(feature = "tree-sitter-languages")]
+        match self {
+            Self::Json => include_str!("languages/json/folds.scm"),
+            Self::Rust => include_str!("languages/rust/folds.scm"),
+            Self::Go => include_str!("languages/go/folds.scm"),
+            Self::JavaScript => include_str!("languages/javascript/folds.scm"),
+            Self::TypeScript | Self::Tsx => include_str!("languages/typescript/folds.scm"),
+            Self::Html => include_str!("languages/html/folds.scm"),
+            Self::Css => include_str!("languages/css/folds.scm"),
+            _ => "",
+        }
+    }
+
+    /// Return the indents query for the language, empty to keep the indentation of the current line.
+    #[allow(unused)]
+    pub(super) fn indents(&self) -> &'static str {
+        #[cfg(not(feature = "tree-sitter-languages"))]
+        return include_str!("languages/json/indents.scm");
+
+        #[cfg(feature = "tree-sitter-languages")]
+        match self {
+            Self::Json => include_str!("languages/json/indents.scm"),
+            Self::Rust => include_str!("languages/rust/indents.scm"),
+            Self::Go => include_str!("languages/go/indents.scm"),
+            Self::JavaScript => include_str!("languages/javascript/indents.scm"),
+            Self::TypeScript | Self::Tsx => include_str!("languages/typescript/indents.scm"),
+            Self::Html => include_str!("languages/html/indents.scm"),
+            Self::Css => include_str!("languages/css/indents.scm"),
+            _ => "",
+        }
+    }
+
+    /// Return the brackets and quotes for the language.
+    #[allow(unused)]
+    pub(super) fn brackets(&self) -> Vec<BracketPair> {
+        let mut brackets = default_brackets();
+
+        #[cfg(feature = "tree-sitter-languages")]
+        match self {
+            Self::JavaScript | Self::TypeScript | Self::Tsx => {
+                brackets.extend([BracketPair::new('\'', '\''), BracketPair::new('`', '`')])
+            }
+            Self::Python
+            | Self::Ruby
+            | Self::Bash
+            | Self::Css
+            | Self::Sql
+            | Self::Toml
+            | Self::Yaml
+            | Self::Elixir
+            | Self::Html => brackets.push(BracketPair::new('\'', '\'')),
+            Self::Rust => brackets.push(BracketPair::new('\'', '\'').no_auto_close()),
+            Self::Go => brackets.push(BracketPair::new('`', '`')),
+            _ => {}
+        }
+
+        brackets
+    }
+
+    /// Return the line comment token and the block comment tokens for the language.
+    #[allow(unused)]
+    pub(super) fn comment_tokens(
+        &self,
+    ) -> (Option<&'static str>, Option<(&'static str, &'static str)>) {
+        #[cfg(not(feature = "tree-sitter-languages"))]
+        return (None, None);
+
+        #[cfg(feature = "tree-sitter-languages")]
+        match self {
+            Self::C
+            | Self::Cpp
+            | Self::CSharp
+            | Self::Go
+            | Self::Java
+            | Self::JavaScript
+            | Self::Proto
+            | Self::Rust
+            | Self::Scala
+            | Self::Swift
+            | Self::Tsx
+            | Self::TypeScript => (Some("//"), Some(("/*", "*/"))),
+            Self::Zig => (Some("//"), None),
+            Self::Bash
+            | Self::CMake
+            | Self::Elixir
+            | Self::GraphQL
+            | Self::Make
+            | Self::Python
+            | Self::Ruby
+            | Self::Toml
+            | Self::Yaml => (Some("#"), None),
+            Self::Sql => (Some("--"), Some(("/*", "*/"))),
+            Self::Css => (None, Some(("/*", "*/"))),
+            Self::Html | Self::Markdown => (None, Some(("<!--", "-->"))),
+            Self::Ejs | Self::Erb => (None, Some(("<%#", "%>"))),
+            _ => (None, None),
+        }
+    }
+
+    /// Return the file extensions or the file names of the language, e.g.: `rs`, `Makefile`.
+    #[allow(unused)]
+    pub(super) fn path_suffixes(&self) -> &'static [&'static str] {
+        #[cfg(not(feature = "tree-sitter-languages"))]
+        return &["json", "jsonc"];
+
+        #[cfg(feature = "tree-sitter-languages")]
+        match self {
+            Self::Bash => &["sh", "bash", "zsh"],
+            Self::C => &["c", "h"],
+            Self::CMake => &["cmake", "CMakeLists.txt"],
+            Self::CSharp => &["cs"],
+            Self::Cpp => &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+            Self::Css => &["css", "scss"],
+            Self::Diff => &["diff", "patch"],
+            Self::Ejs => &["ejs"],
+            Self::Elixir => &["ex", "exs"],
+            Self::Erb => &["erb"],
+            Self::Go => &["go"],
+            Self::GraphQL => &["graphql", "gql"],
+            Self::Html => &["html", "htm"],
+            Self::Java => &["java"],
+            Self::JavaScript => &["js", "jsx", "mjs", "cjs"],
+            Self::Json => &["json", "jsonc"],
+            Self::Make => &["mk", "Makefile", "makefile", "GNUmakefile"],
+            Self::Markdown => &["md", "markdown", "mdx"],
+            Self::Proto => &["proto"],
+            Self::Python => &["py", "pyi"],
+            Self::Ruby => &["rb", "Gemfile", "Rakefile"],
+            Self::Rust => &["rs"],
+            Self::Scala => &["scala", "sc"],
+            Self::Sql => &["sql"],
+            Self::Swift => &["swift"],
+            Self::Toml => &["toml", "Cargo.lock"],
+            Self::Tsx => &["tsx"],
+            Self::TypeScript => &["ts", "mts", "cts"],
+            Self::Yaml => &["yaml", "yml"],
+            Self::Zig => &["zig"],
+            Self::Plain | Self::JsDoc | Self::MarkdownInline => &[],
+        }
+    }
+
+    /// Return the interpreters in the shebang line of the language, e.g.: `python` of `#!/usr/bin/env python3`.
+    #[allow(unused)]
+    pub(super) fn shebangs(&self) -> &'static [&'static str] {
+        #[cfg(not(feature = "tree-sitter-languages"))]
+        return &[];
+
+        #[cfg(feature = "tree-sitter-languages")]
+        match self {
+            Self::Bash => &["sh", "bash", "zsh"],
+            Self::Elixir => &["elixir"],
+            Self::JavaScript => &["node"],
+            Self::Make => &["make"],
+            Self::Python => &["python"],
+            Self::Ruby => &["ruby"],
+            Self::Scala => &["scala"],
+            Self::Swift => &["swift"],
+            Self::TypeScript => &["deno", "ts-node"],
+            _ => &[],
+        }
+    }
+
     /// Return the language info for the language.
     ///
     /// (language, query, injection, locals)
@@ -360,7 +528,7 @@ impl Language {
 
         let language = tree_sitter::Language::new(language);
 
-        LanguageConfig::new(
+        let mut config = LanguageConfig::new(
             self.name(),
             language,
             self.injection_languages(),
@@ -368,6 +536,20 @@ impl Language {
             injection,
             locals,
         )
+        .folds(self.folds())
+        .indents(self.indents())
+        .brackets(self.brackets())
+        .path_suffixes(self.path_suffixes().iter().copied())
+        .shebangs(self.shebangs().iter().copied());
+
+        let (line_comment, block_comment) = self.comment_tokens();
+        if let Some(token) = line_comment {
+            config = config.line_comment(token);
+        }
+        if let Some((start, end)) = block_comment {
+            config = config.block_comment(start, end);
+        }
+        config
     }
 }
 
@@ -0,0 +1,13 @@
+[
+  (block)
+  (literal_value)
+  (field_declaration_list)
+  (interface_type)
+  (import_spec_list)
+  (expression_switch_statement)
+  (type_switch_statement)
+  (select_statement)
+  (argument_list)
+  (parameter_list)
+  (comment)
+] @fold
@@ -0,0 +1,5 @@
+[
+  (element (end_tag) @end)
+  (script_element (end_tag) @end)
+  (style_element (end_tag) @end)
+] @indent
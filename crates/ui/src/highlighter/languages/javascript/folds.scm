@@ -0,0 +1,15 @@
+[
+  (statement_block)
+  (class_body)
+  (switch_body)
+  (object)
+  (object_pattern)
+  (array)
+  (array_pattern)
+  (arguments)
+  (formal_parameters)
+  (named_imports)
+  (template_string)
+  (jsx_element)
+  (comment)
+] @fold
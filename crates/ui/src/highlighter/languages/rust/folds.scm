@@ -0,0 +1,15 @@
+[
+  (block)
+  (declaration_list)
+  (field_declaration_list)
+  (ordered_field_declaration_list)
+  (enum_variant_list)
+  (field_initializer_list)
+  (match_block)
+  (use_list)
+  (token_tree)
+  (arguments)
+  (parameters)
+  (array_expression)
+  (block_comment)
+] @fold
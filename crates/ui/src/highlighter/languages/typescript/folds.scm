@@ -0,0 +1,16 @@
+[
+  (statement_block)
+  (class_body)
+  (switch_body)
+  (enum_body)
+  (object_type)
+  (object)
+  (object_pattern)
+  (array)
+  (array_pattern)
+  (arguments)
+  (formal_parameters)
+  (named_imports)
+  (template_string)
+  (comment)
+] @fold
@@ -0,0 +1,152 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+/// A scope captured as `@local.scope` by the locals query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LocalScope {
+    pub range: Range<usize>,
+    /// Whether the definitions of the parent scopes are visible in the scope,
+    /// `false` by `(#set! local.scope-inherits false)`.
+    pub inherits: bool,
+}
+
+/// A name captured as `@local.definition` or `@local.reference` by the locals query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LocalName {
+    pub range: Range<usize>,
+    pub name: String,
+}
+
+/// Return the index of the innermost scope containing the range.
+fn innermost_scope(scopes: &[LocalScope], range: &Range<usize>) -> Option<usize> {
+    scopes
+        .iter()
+        .enumerate()
+        .filter(|(_, scope)| scope.range.start <= range.start && range.end <= scope.range.end)
+        .max_by_key(|(_, scope)| (scope.range.start, usize::MAX - scope.range.end))
+        .map(|(ix, _)| ix)
+}
+
+/// Resolve the references to their definitions, returns the range of the definition by the
+/// range of each resolved reference.
+///
+/// Same as `tree-sitter-highlight`, a definition is visible to the references after it in
+/// its scope and the inner scopes, the lookup stops at the scope not inheriting its parent.
+/// The definitions out of any scope are in the root scope.
+pub(super) fn resolve_local_references(
+    scopes: &[LocalScope],
+    defs: &[LocalName],
+    refs: &[LocalName],
+) -> HashMap<Range<usize>, Range<usize>> {
+    // The definitions by the name and the scope, sorted by the start.
+    let mut defs_by_name: HashMap<(&str, Option<usize>), Vec<&LocalName>> = HashMap::new();
+    for def in defs {
+        let scope_ix = innermost_scope(scopes, &def.range);
+        defs_by_name
+            .entry((def.name.as_str(), scope_ix))
+            .or_default()
+            .push(def);
+    }
+    for defs in defs_by_name.values_mut() {
+        defs.sort_by_key(|def| def.range.start);
+    }
+    let def_ranges = defs.iter().map(|def| &def.range).collect::<HashSet<_>>();
+
+    let mut resolved = HashMap::new();
+    for reference in refs {
+        if def_ranges.contains(&reference.range) {
+            continue;
+        }
+
+        // The last definition of the name in the scope before the reference.
+        let find_def = |scope_ix: Option<usize>| {
+            let defs = defs_by_name.get(&(reference.name.as_str(), scope_ix))?;
+            let ix = defs.partition_point(|def| def.range.start <= reference.range.start);
+            ix.checked_sub(1).map(|ix| defs[ix])
+        };
+
+        let mut chain = scopes
+            .iter()
+            .enumerate()
+            .filter(|(_, scope)| {
+                scope.range.start <= reference.range.start && reference.range.end <= scope.range.end
+            })
+            .collect::<Vec<_>>();
+        // Innermost first.
+        chain.sort_by_key(|(_, scope)| (usize::MAX - scope.range.start, scope.range.end));
+
+        let mut def = None;
+        let mut reaches_root = true;
+        for (ix, scope) in chain {
+            def = find_def(Some(ix));
+            if def.is_some() {
+                break;
+            }
+            if !scope.inherits {
+                reaches_root = false;
+                break;
+            }
+        }
+        if def.is_none() && reaches_root {
+            def = find_def(None);
+        }
+
+        if let Some(def) = def {
+            resolved.insert(reference.range.clone(), def.range.clone());
+        }
+    }
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(range: Range<usize>, name: &str) -> LocalName {
+        LocalName {
+            range,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_local_references() {
+        // 0         1         2         3         4
+        // 01234567890123456789012345678901234567890123
+        // let a; function f(a, b) { a; b; c; } a; b;
+        let scopes = vec![LocalScope {
+            range: 7..36,
+            inherits: true,
+        }];
+        let defs = vec![name(4..5, "a"), name(18..19, "a"), name(21..22, "b")];
+        let refs = vec![
+            name(26..27, "a"),
+            name(29..30, "b"),
+            name(32..33, "c"),
+            name(37..38, "a"),
+            name(40..41, "b"),
+            // The definition is not a reference.
+            name(18..19, "a"),
+        ];
+
+        let resolved = resolve_local_references(&scopes, &defs, &refs);
+        assert_eq!(resolved.len(), 3);
+        assert_eq!(resolved.get(&(26..27)), Some(&(18..19)));
+        assert_eq!(resolved.get(&(29..30)), Some(&(21..22)));
+        assert_eq!(resolved.get(&(37..38)), Some(&(4..5)));
+
+        // The root definitions are not visible in a scope not inheriting its parent.
+        let scopes = vec![LocalScope {
+            range: 7..36,
+            inherits: false,
+        }];
+        let refs = vec![name(26..27, "a"), name(32..33, "a")];
+        let defs = vec![name(4..5, "a"), name(28..29, "a")];
+        let resolved = resolve_local_references(&scopes, &defs, &refs);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved.get(&(32..33)), Some(&(28..29)));
+    }
+}
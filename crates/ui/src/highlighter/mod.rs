@@ -1,12 +1,22 @@
+mod brackets;
 mod diagnostics;
+mod folding;
+mod grammars;
 mod highlighter;
 mod languages;
+mod locals;
 mod registry;
+mod semantic_tokens;
+mod sticky_scroll;
 
+pub use brackets::*;
 pub use diagnostics::*;
+pub use folding::*;
 pub use highlighter::*;
 pub use languages::*;
 pub use registry::*;
+pub use semantic_tokens::*;
+pub use sticky_scroll::*;
 
 use gpui::App;
 
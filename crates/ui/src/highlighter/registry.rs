@@ -2,10 +2,15 @@ use gpui::{App, FontWeight, HighlightStyle, Hsla, SharedString};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::{collections::HashMap, ops::Deref, sync::Arc};
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
-    highlighter::{languages, Language},
+    highlighter::{default_brackets, grammars::LoadedGrammar, languages, BracketPair, Language},
     ActiveTheme, ThemeMode, DEFAULT_THEME_COLORS,
 };
 
@@ -66,6 +71,21 @@ pub struct LanguageConfig {
     pub highlights: SharedString,
     pub injections: SharedString,
     pub locals: SharedString,
+    /// The query to capture the foldable nodes as `@fold`.
+    pub folds: SharedString,
+    /// The query to capture the nodes to indent their inner lines as `@indent`, and the
+    /// closing tokens to outdent as `@end`.
+    pub indents: SharedString,
+    /// The brackets and quotes to match and to auto close.
+    pub brackets: Vec<BracketPair>,
+    /// The line comment token, e.g.: `//`.
+    pub line_comment: Option<SharedString>,
+    /// The block comment start and end tokens, e.g.: `/*` and `*/`.
+    pub block_comment: Option<(SharedString, SharedString)>,
+    /// The file extensions or the file names to detect the language, e.g.: `rs`, `Makefile`.
+    pub path_suffixes: Vec<SharedString>,
+    /// The interpreters in the shebang line to detect the language, e.g.: `python`.
+    pub shebangs: Vec<SharedString>,
 }
 
 impl LanguageConfig {
@@ -84,8 +104,105 @@ impl LanguageConfig {
             highlights: SharedString::from(highlights.to_string()),
             injections: SharedString::from(injections.to_string()),
             locals: SharedString::from(locals.to_string()),
+            folds: SharedString::default(),
+            indents: SharedString::default(),
+            brackets: default_brackets(),
+            line_comment: None,
+            block_comment: None,
+            path_suffixes: vec![],
+            shebangs: vec![],
         }
     }
+
+    /// Set the folds query of the language, the nodes captured as `@fold` can be folded in the editor.
+    ///
+    /// If not set, the editor will fold by the indentation of lines.
+    pub fn folds(mut self, folds: &str) -> Self {
+        self.folds = SharedString::from(folds.to_string());
+        self
+    }
+
+    /// Set the indents query of the language, used to indent the new line in the editor.
+    ///
+    /// If not set, the new line keeps the indentation of the current line.
+    pub fn indents(mut self, indents: &str) -> Self {
+        self.indents = SharedString::from(indents.to_string());
+        self
+    }
+
+    /// Set the brackets and quotes of the language, default: `()`, `[]`, `{}` and `""`.
+    pub fn brackets(mut self, brackets: Vec<BracketPair>) -> Self {
+        self.brackets = brackets;
+        self
+    }
+
+    /// Set the line comment token of the language, e.g.: `//`.
+    pub fn line_comment(mut self, token: impl Into<SharedString>) -> Self {
+        self.line_comment = Some(token.into());
+        self
+    }
+
+    /// Set the block comment tokens of the language, e.g.: `/*` and `*/`.
+    ///
+    /// Used to toggle the comment when the language has no line comment.
+    pub fn block_comment(
+        mut self,
+        start: impl Into<SharedString>,
+        end: impl Into<SharedString>,
+    ) -> Self {
+        self.block_comment = Some((start.into(), end.into()));
+        self
+    }
+
+    /// Set the file extensions or the file names of the language, e.g.: `rs`, `Makefile`.
+    pub fn path_suffixes(
+        mut self,
+        suffixes: impl IntoIterator<Item = impl Into<SharedString>>,
+    ) -> Self {
+        self.path_suffixes = suffixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the interpreters in the shebang line of the language, e.g.: `python`.
+    ///
+    /// The version of the interpreter is ignored, e.g.: `python3` is matched by `python`.
+    pub fn shebangs(mut self, shebangs: impl IntoIterator<Item = impl Into<SharedString>>) -> Self {
+        self.shebangs = shebangs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Return the length of the longest path suffix matching the file name, `None` if not matched.
+    fn match_path(&self, file_name: &str) -> Option<usize> {
+        self.path_suffixes
+            .iter()
+            .filter(|suffix| {
+                file_name == suffix.as_ref()
+                    || file_name
+                        .strip_suffix(suffix.as_ref())
+                        .map_or(false, |name| name.ends_with('.'))
+            })
+            .map(|suffix| suffix.len())
+            .max()
+    }
+
+    fn match_shebang(&self, interpreter: &str) -> bool {
+        let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        self.shebangs
+            .iter()
+            .any(|shebang| shebang.as_ref() == interpreter || shebang.as_ref() == name)
+    }
+}
+
+/// Return the interpreter of the shebang line, e.g.: `python3` of `#!/usr/bin/env python3`.
+fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut args = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = args.next()?;
+    if program.rsplit('/').next() == Some("env") {
+        // Skip the options of `env`, e.g.: `#!/usr/bin/env -S deno run`.
+        program = args.find(|arg| !arg.starts_with('-'))?;
+    }
+
+    program.rsplit('/').next()
 }
 
 /// Theme for Tree-sitter Highlight
@@ -423,6 +540,9 @@ pub struct HighlightThemeStyle {
     pub line_number: Option<Hsla>,
     #[serde(rename = "editor.active_line_number")]
     pub active_line_number: Option<Hsla>,
+    /// The colors of the brackets by the nesting depth, the rainbow brackets are disabled if empty.
+    #[serde(rename = "editor.rainbow_brackets", default)]
+    pub rainbow_brackets: Vec<Hsla>,
     #[serde(flatten)]
     pub status: StatusColors,
     #[serde(rename = "syntax")]
@@ -464,6 +584,10 @@ impl HighlightTheme {
 #[derive(Clone)]
 pub struct LanguageRegistry {
     languages: HashMap<String, LanguageConfig>,
+    /// The grammar libraries loaded at runtime, keyed by the library path.
+    pub(super) grammars: HashMap<PathBuf, LoadedGrammar>,
+    /// The names of the languages loaded from the grammar directories, keyed by the directory.
+    pub(super) grammar_dirs: HashMap<PathBuf, SharedString>,
 }
 
 impl gpui::Global for LanguageRegistry {}
@@ -481,6 +605,8 @@ impl LanguageRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
             languages: HashMap::new(),
+            grammars: HashMap::new(),
+            grammar_dirs: HashMap::new(),
         };
 
         for language in languages::Language::all() {
@@ -494,6 +620,11 @@ impl LanguageRegistry {
         self.languages.insert(lang.to_string(), config.clone());
     }
 
+    /// Remove the registered language, returns its config.
+    pub fn unregister(&mut self, lang: &str) -> Option<LanguageConfig> {
+        self.languages.remove(lang)
+    }
+
     /// Returns a reference to the map of registered languages.
     pub fn languages(&self) -> &HashMap<String, LanguageConfig> {
         &self.languages
@@ -510,6 +641,31 @@ impl LanguageRegistry {
         let language = Language::from_str(name);
         self.languages.get(language.name())
     }
+
+    /// Returns the language configuration for the file by the extension or the file name.
+    pub fn language_for_path(&self, path: &Path) -> Option<&LanguageConfig> {
+        let file_name = path.file_name()?.to_str()?;
+        self.languages
+            .values()
+            .filter_map(|config| config.match_path(file_name).map(|len| (len, config)))
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, config)| config)
+    }
+
+    /// Returns the language configuration for the text by the shebang in the first line.
+    pub fn language_for_shebang(&self, text: &str) -> Option<&LanguageConfig> {
+        let first_line = text.lines().next()?;
+        let interpreter = shebang_interpreter(first_line)?;
+        self.languages
+            .values()
+            .find(|config| config.match_shebang(interpreter))
+    }
+
+    /// Detect the language of the file by the path, and fallback to the shebang of the text.
+    pub fn detect_language(&self, path: &Path, text: &str) -> Option<&LanguageConfig> {
+        self.language_for_path(path)
+            .or_else(|| self.language_for_shebang(text))
+    }
 }
 
 #[cfg(test)]
@@ -532,4 +688,66 @@ mod tests {
         assert!(registry.language("javascript").is_some());
         assert!(registry.language("js").is_some());
     }
+
+    #[test]
+    fn test_shebang_interpreter() {
+        use super::shebang_interpreter;
+
+        assert_eq!(shebang_interpreter("#!/bin/sh"), Some("sh"));
+        assert_eq!(
+            shebang_interpreter("#!/usr/bin/env python3"),
+            Some("python3")
+        );
+        assert_eq!(
+            shebang_interpreter("#! /usr/bin/env -S deno run"),
+            Some("deno")
+        );
+        assert_eq!(shebang_interpreter("#!/usr/bin/env"), None);
+        assert_eq!(shebang_interpreter("# comment"), None);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        use super::LanguageRegistry;
+        use std::path::Path;
+
+        let mut registry = LanguageRegistry::new();
+        registry.register(
+            "foo",
+            &LanguageConfig::new("foo", tree_sitter_json::LANGUAGE.into(), vec![], "", "", "")
+                .path_suffixes(["foo", "foo.json", "Foofile"])
+                .shebangs(["foo"]),
+        );
+
+        let name = |config: Option<&LanguageConfig>| config.map(|config| config.name.clone());
+        assert_eq!(
+            name(registry.language_for_path(Path::new("a/b.foo"))),
+            Some("foo".into())
+        );
+        assert_eq!(
+            name(registry.language_for_path(Path::new("Foofile"))),
+            Some("foo".into())
+        );
+        assert_eq!(
+            name(registry.language_for_path(Path::new("b.foo.json"))),
+            Some("foo".into())
+        );
+        assert_eq!(
+            name(registry.language_for_path(Path::new("b.json"))),
+            Some("json".into())
+        );
+        assert_eq!(name(registry.language_for_path(Path::new("bfoo"))), None);
+        assert_eq!(
+            name(registry.language_for_shebang("#!/usr/bin/env foo2.1\n")),
+            Some("foo".into())
+        );
+        assert_eq!(
+            name(registry.detect_language(Path::new("script"), "#!/usr/local/bin/foo")),
+            Some("foo".into())
+        );
+        assert_eq!(
+            name(registry.detect_language(Path::new("script"), "foo")),
+            None
+        );
+    }
 }
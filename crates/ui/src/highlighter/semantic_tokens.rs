@@ -0,0 +1,319 @@
+use std::ops::Range;
+
+use gpui::{App, HighlightStyle, SharedString};
+use lsp_types::{SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensLegend};
+use rope::Rope;
+
+use crate::{
+    input::{Position, RopeExt as _},
+    ActiveTheme,
+};
+
+/// A token of the semantic tokens, with the highlight name of the token type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: Range<usize>,
+    pub name: SharedString,
+}
+
+/// The semantic tokens of the text, from the `textDocument/semanticTokens` of LSP.
+///
+/// The semantic tokens are styled by the [`crate::highlighter::HighlightTheme`], and
+/// override the styles of the Tree-sitter highlighting.
+#[derive(Debug, Clone, Default)]
+pub struct SemanticTokenSet {
+    /// Sorted by the range, and not overlapped.
+    tokens: Vec<SemanticToken>,
+}
+
+/// Return the highlight name of the semantic token type and modifiers, `None` to keep the
+/// Tree-sitter highlighting.
+pub fn semantic_token_highlight_name(
+    token_type: &SemanticTokenType,
+    modifiers: &[SemanticTokenModifier],
+) -> Option<&'static str> {
+    let has_modifier = |modifier: SemanticTokenModifier| modifiers.contains(&modifier);
+
+    let name = match token_type.as_str() {
+        "namespace" | "type" | "class" | "struct" | "interface" | "typeParameter" => "type",
+        "enum" => "enum",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "variable" if has_modifier(SemanticTokenModifier::READONLY) => "constant",
+        "variable" => "variable",
+        "property" | "event" => "property",
+        "function" | "method" => "function",
+        "macro" => "function.macro",
+        "keyword" | "modifier" => "keyword",
+        "comment" if has_modifier(SemanticTokenModifier::DOCUMENTATION) => "comment.doc",
+        "comment" => "comment",
+        "string" => "string",
+        "number" => "number",
+        "regexp" => "string.regex",
+        "operator" => "operator",
+        "decorator" => "attribute",
+        "label" => "label",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+impl SemanticTokenSet {
+    /// Decode the semantic tokens in the relative format of LSP.
+    ///
+    /// The tokens out of the text, or having unknown types are ignored.
+    pub fn from_lsp(text: &Rope, tokens: &SemanticTokens, legend: &SemanticTokensLegend) -> Self {
+        let mut set = Self::default();
+        let mut line = 0;
+        let mut character = 0;
+        for token in &tokens.data {
+            if token.delta_line > 0 {
+                line += token.delta_line;
+                character = 0;
+            }
+            character += token.delta_start;
+
+            if line as usize >= text.lines_len() {
+                break;
+            }
+
+            let Some(token_type) = legend.token_types.get(token.token_type as usize) else {
+                continue;
+            };
+            let modifiers = legend
+                .token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| *ix < 32 && token.token_modifiers_bitset & (1 << ix) != 0)
+                .map(|(_, modifier)| modifier.clone())
+                .collect::<Vec<_>>();
+            let Some(name) = semantic_token_highlight_name(token_type, &modifiers) else {
+                continue;
+            };
+
+            let start = text.position_to_offset(&Position::new(line, character));
+            let end = text.position_to_offset(&Position::new(line, character + token.length));
+            if start >= end
+                || set
+                    .tokens
+                    .last()
+                    .map_or(false, |last| last.range.end > start)
+            {
+                continue;
+            }
+
+            set.tokens.push(SemanticToken {
+                range: start..end,
+                name: name.into(),
+            });
+        }
+
+        set
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tokens.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SemanticToken> {
+        self.tokens.iter()
+    }
+
+    /// Update the tokens for the edit replaced the `range` with a text of `new_len` bytes,
+    /// to keep the tokens until the new tokens are received.
+    ///
+    /// The tokens intersecting or touching the edit are removed, because the words of them
+    /// may be changed, and the tokens after it are shifted.
+    pub fn edit(&mut self, range: &Range<usize>, new_len: usize) {
+        if self.tokens.is_empty() {
+            return;
+        }
+
+        self.tokens.retain_mut(|token| {
+            if token.range.end < range.start {
+                true
+            } else if token.range.start > range.end {
+                token.range.start = token.range.start - range.len() + new_len;
+                token.range.end = token.range.end - range.len() + new_len;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    pub(crate) fn styles_for_range(
+        &self,
+        range: &Range<usize>,
+        cx: &App,
+    ) -> Vec<(Range<usize>, HighlightStyle)> {
+        if self.tokens.is_empty() {
+            return vec![];
+        }
+
+        let theme = &cx.theme().highlight_theme;
+        let start_ix = self
+            .tokens
+            .partition_point(|token| token.range.end <= range.start);
+
+        self.tokens[start_ix..]
+            .iter()
+            .take_while(|token| token.range.start < range.end)
+            .filter_map(|token| {
+                let style = theme.style(token.name.as_ref())?;
+                Some((token.range.clone(), style))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::SemanticToken as LspSemanticToken;
+
+    use super::*;
+
+    fn legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec![
+                SemanticTokenType::FUNCTION,
+                SemanticTokenType::PARAMETER,
+                SemanticTokenType::VARIABLE,
+            ],
+            token_modifiers: vec![
+                SemanticTokenModifier::DECLARATION,
+                SemanticTokenModifier::READONLY,
+            ],
+        }
+    }
+
+    fn token(
+        delta_line: u32,
+        delta_start: u32,
+        length: u32,
+        ty: u32,
+        mods: u32,
+    ) -> LspSemanticToken {
+        LspSemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type: ty,
+            token_modifiers_bitset: mods,
+        }
+    }
+
+    #[test]
+    fn test_semantic_token_highlight_name() {
+        assert_eq!(
+            semantic_token_highlight_name(&SemanticTokenType::PARAMETER, &[]),
+            Some("variable.parameter")
+        );
+        assert_eq!(
+            semantic_token_highlight_name(
+                &SemanticTokenType::VARIABLE,
+                &[SemanticTokenModifier::READONLY]
+            ),
+            Some("constant")
+        );
+        assert_eq!(
+            semantic_token_highlight_name(
+                &SemanticTokenType::COMMENT,
+                &[SemanticTokenModifier::DOCUMENTATION]
+            ),
+            Some("comment.doc")
+        );
+        assert_eq!(
+            semantic_token_highlight_name(&SemanticTokenType::new("unknown"), &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_from_lsp() {
+        // 0         1
+        // 0123456789012345
+        // fn foo(a: i32) {
+        //     let 你 = a;
+        // }
+        let text = Rope::from("fn foo(a: i32) {\n    let 你 = a;\n}");
+        let tokens = SemanticTokens {
+            result_id: None,
+            data: vec![
+                token(0, 3, 3, 0, 1),
+                token(0, 4, 1, 1, 1),
+                token(1, 8, 1, 2, 3),
+                token(0, 4, 1, 1, 0),
+                // Unknown token type
+                token(0, 2, 1, 9, 0),
+            ],
+        };
+
+        let set = SemanticTokenSet::from_lsp(&text, &tokens, &legend());
+        let items = set.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            vec![
+                SemanticToken {
+                    range: 3..6,
+                    name: "function".into(),
+                },
+                SemanticToken {
+                    range: 7..8,
+                    name: "variable.parameter".into(),
+                },
+                SemanticToken {
+                    range: 25..28,
+                    name: "constant".into(),
+                },
+                SemanticToken {
+                    range: 31..32,
+                    name: "variable.parameter".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_edit() {
+        let text = Rope::from("fn foo(a: i32) {\n    let 你 = a;\n}");
+        let tokens = SemanticTokens {
+            result_id: None,
+            data: vec![
+                token(0, 3, 3, 0, 0),
+                token(0, 4, 1, 1, 0),
+                token(1, 12, 1, 1, 0),
+            ],
+        };
+        let mut set = SemanticTokenSet::from_lsp(&text, &tokens, &legend());
+        assert_eq!(set.len(), 3);
+
+        // Insert a space after `a:`, the tokens after it are shifted.
+        set.edit(&(9..9), 1);
+        let ranges = set
+            .iter()
+            .map(|token| token.range.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![3..6, 7..8, 32..33]);
+
+        // Delete the `o` of `foo`, the token is removed.
+        set.edit(&(4..5), 0);
+        let ranges = set
+            .iter()
+            .map(|token| token.range.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![6..7, 31..32]);
+
+        set.clear();
+        assert!(set.is_empty());
+    }
+}
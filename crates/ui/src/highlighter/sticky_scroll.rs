@@ -0,0 +1,92 @@
+use std::ops::Range;
+
+/// The node kinds of the scopes to pin their headers in sticky scroll.
+const STICKY_SCOPE_KINDS: &[&str] = &[
+    // Rust
+    "function_item",
+    "impl_item",
+    "trait_item",
+    "struct_item",
+    "enum_item",
+    "mod_item",
+    "macro_definition",
+    // JavaScript, TypeScript, Go, Java
+    "function_declaration",
+    "generator_function_declaration",
+    "class_declaration",
+    "abstract_class_declaration",
+    "method_definition",
+    "method_declaration",
+    "constructor_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "type_declaration",
+    "internal_module",
+    "module",
+    // Python, C, C++
+    "function_definition",
+    "class_definition",
+    "class_specifier",
+    "struct_specifier",
+    "namespace_definition",
+    // Ruby
+    "class",
+    "method",
+    "singleton_method",
+    // CSS
+    "rule_set",
+];
+
+/// Return true if the node kind is a scope to pin its header in sticky scroll.
+pub(crate) fn is_sticky_scope(kind: &str) -> bool {
+    STICKY_SCOPE_KINDS.contains(&kind)
+}
+
+/// Return the header rows to pin at the top of the editor.
+///
+/// - `scopes` are the row ranges of the scopes that enclose the `top_row`, outermost first.
+/// - `top_row` is the first visible row in the viewport.
+///
+/// A scope is dropped when its last row would be covered by the pinned headers.
+pub fn sticky_header_rows(scopes: &[Range<usize>], top_row: usize, max_lines: usize) -> Vec<usize> {
+    let mut scopes = scopes
+        .iter()
+        .filter(|scope| scope.start < top_row && scope.end > top_row)
+        .take(max_lines)
+        .collect::<Vec<_>>();
+
+    while let Some(scope) = scopes.last() {
+        if scope.end > top_row + scopes.len() {
+            break;
+        }
+        scopes.pop();
+    }
+
+    scopes.into_iter().map(|scope| scope.start).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_sticky_scope() {
+        assert!(is_sticky_scope("function_item"));
+        assert!(is_sticky_scope("class_definition"));
+        assert!(!is_sticky_scope("let_declaration"));
+        assert!(!is_sticky_scope("block"));
+    }
+
+    #[test]
+    fn test_sticky_header_rows() {
+        let scopes = vec![0..20, 2..10, 4..8];
+        assert_eq!(sticky_header_rows(&scopes, 0, 5), Vec::<usize>::new());
+        assert_eq!(sticky_header_rows(&scopes, 1, 5), vec![0]);
+        assert_eq!(sticky_header_rows(&scopes, 5, 5), vec![0, 2]);
+        assert_eq!(sticky_header_rows(&scopes, 3, 5), vec![0, 2]);
+        assert_eq!(sticky_header_rows(&scopes, 3, 1), vec![0]);
+        // The last row of `2..10` is 9, which would be covered at top row 8.
+        assert_eq!(sticky_header_rows(&scopes, 8, 5), vec![0]);
+        assert_eq!(sticky_header_rows(&scopes, 7, 5), vec![0, 2]);
+    }
+}
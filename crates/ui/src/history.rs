@@ -28,6 +28,9 @@ pub struct History<I: HistoryItem> {
     max_undo: usize,
     group_interval: Option<Duration>,
     unique: bool,
+    /// The version of the changes pushed since [`History::start_group`], `Some(None)` if
+    /// nothing has been pushed yet, `None` if not grouping.
+    group: Option<Option<usize>>,
 }
 
 impl<I> History<I>
@@ -44,6 +47,7 @@ where
             max_undo: 1000,
             group_interval: None,
             unique: false,
+            group: None,
         }
     }
 
@@ -69,14 +73,34 @@ where
     /// Increment the version number if the last change was made more than `GROUP_INTERVAL` milliseconds ago.
     fn inc_version(&mut self) -> usize {
         let t = Instant::now();
+        if let Some(Some(version)) = self.group {
+            self.last_changed_at = t;
+            return version;
+        }
+
         if Some(self.last_changed_at.elapsed()) > self.group_interval {
             self.version += 1;
         }
 
+        if let Some(group) = self.group.as_mut() {
+            *group = Some(self.version);
+        }
         self.last_changed_at = t;
         self.version
     }
 
+    /// Start a group, all changes pushed until [`History::end_group`] are undone and redone together.
+    ///
+    /// For example, an edit applied at multiple cursors.
+    pub fn start_group(&mut self) {
+        self.group = Some(None);
+    }
+
+    /// End the group started by [`History::start_group`].
+    pub fn end_group(&mut self) {
+        self.group = None;
+    }
+
     /// Get the current version number.
     pub fn version(&self) -> usize {
         self.version
@@ -285,4 +309,28 @@ mod tests {
         assert_eq!(history.undos().len(), 0);
         assert_eq!(history.redos().len(), 4);
     }
+
+    #[test]
+    fn test_group_history() {
+        let mut history: History<TabIndex> = History::new();
+        history.push(0.into());
+        history.start_group();
+        history.push(1.into());
+        history.push(2.into());
+        history.end_group();
+        history.push(3.into());
+
+        assert_eq!(history.version(), 3);
+        assert_eq!(history.undo().unwrap().len(), 1);
+
+        let changes = history.undo().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].tab_index, 2);
+        assert_eq!(changes[1].tab_index, 1);
+
+        let changes = history.redo().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].tab_index, 1);
+        assert_eq!(changes[1].tab_index, 2);
+    }
 }
@@ -19,6 +19,7 @@ pub enum IconName {
     Building2,
     Calendar,
     CaseSensitive,
+    CaseUpper,
     ChartPie,
     Check,
     ChevronDown,
@@ -38,6 +39,7 @@ pub enum IconName {
     ExternalLink,
     Eye,
     EyeOff,
+    Filter,
     Folder,
     FolderClosed,
     FolderOpen,
@@ -69,6 +71,7 @@ pub enum IconName {
     PanelRightClose,
     PanelRightOpen,
     Plus,
+    Regex,
     Replace,
     ResizeCorner,
     Search,
@@ -80,10 +83,12 @@ pub enum IconName {
     Star,
     StarOff,
     Sun,
+    TextSelect,
     ThumbsDown,
     ThumbsUp,
     TriangleAlert,
     User,
+    WholeWord,
     WindowClose,
     WindowMaximize,
     WindowMinimize,
@@ -105,6 +110,7 @@ impl IconName {
             Self::Building2 => "icons/building-2.svg",
             Self::Calendar => "icons/calendar.svg",
             Self::CaseSensitive => "icons/case-sensitive.svg",
+            Self::CaseUpper => "icons/case-upper.svg",
             Self::ChartPie => "icons/chart-pie.svg",
             Self::Check => "icons/check.svg",
             Self::ChevronDown => "icons/chevron-down.svg",
@@ -124,6 +130,7 @@ impl IconName {
             Self::ExternalLink => "icons/external-link.svg",
             Self::Eye => "icons/eye.svg",
             Self::EyeOff => "icons/eye-off.svg",
+            Self::Filter => "icons/filter.svg",
             Self::Folder => "icons/folder.svg",
             Self::FolderClosed => "icons/folder-closed.svg",
             Self::FolderOpen => "icons/folder-open.svg",
@@ -155,6 +162,7 @@ impl IconName {
             Self::PanelRightClose => "icons/panel-right-close.svg",
             Self::PanelRightOpen => "icons/panel-right-open.svg",
             Self::Plus => "icons/plus.svg",
+            Self::Regex => "icons/regex.svg",
             Self::Replace => "icons/replace.svg",
             Self::ResizeCorner => "icons/resize-corner.svg",
             Self::Search => "icons/search.svg",
@@ -166,10 +174,12 @@ impl IconName {
             Self::Star => "icons/star.svg",
             Self::StarOff => "icons/star-off.svg",
             Self::Sun => "icons/sun.svg",
+            Self::TextSelect => "icons/text-select.svg",
             Self::ThumbsDown => "icons/thumbs-down.svg",
             Self::ThumbsUp => "icons/thumbs-up.svg",
             Self::TriangleAlert => "icons/triangle-alert.svg",
             Self::User => "icons/user.svg",
+            Self::WholeWord => "icons/whole-word.svg",
             Self::WindowClose => "icons/window-close.svg",
             Self::WindowMaximize => "icons/window-maximize.svg",
             Self::WindowMinimize => "icons/window-minimize.svg",
@@ -0,0 +1,295 @@
+use std::ops::Range;
+
+use gpui::{Context, EntityInputHandler as _, Window};
+
+use crate::highlighter::BracketPair;
+use crate::input::RopeExt as _;
+
+use super::{InputState, JumpToBracket};
+
+/// The edit to type a char with the auto pairs.
+#[derive(Debug, PartialEq, Eq)]
+enum AutoPairEdit {
+    /// Replace the selection with the `text`, and select the `selected_range` (relative to
+    /// the start of the text).
+    Insert {
+        text: String,
+        selected_range: Range<usize>,
+    },
+    /// Move the cursor over the closing char next to it.
+    Overtype,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Return the edit to type the char `c`, `None` to type it as usual.
+///
+/// - Type over the closing char if it is the next char.
+/// - Wrap the selection with the pair.
+/// - Insert the pair if the cursor is at the end of a word, before whitespace or a closing bracket.
+fn auto_pair_edit(
+    pairs: &[BracketPair],
+    c: char,
+    selected_text: &str,
+    prev_char: Option<char>,
+    next_char: Option<char>,
+) -> Option<AutoPairEdit> {
+    if selected_text.is_empty()
+        && next_char == Some(c)
+        && pairs.iter().any(|pair| pair.auto_close && pair.end == c)
+    {
+        return Some(AutoPairEdit::Overtype);
+    }
+
+    let pair = pairs.iter().find(|pair| pair.start == c)?;
+    if !selected_text.is_empty() {
+        let start = c.len_utf8();
+        return Some(AutoPairEdit::Insert {
+            text: format!("{}{}{}", pair.start, selected_text, pair.end),
+            selected_range: start..start + selected_text.len(),
+        });
+    }
+
+    if !pair.auto_close {
+        return None;
+    }
+    if let Some(next_char) = next_char {
+        let is_closing = pairs
+            .iter()
+            .any(|pair| !pair.is_quote() && pair.end == next_char);
+        if !next_char.is_whitespace() && !is_closing {
+            return None;
+        }
+    }
+    // Don't close the quote after a word, e.g.: `don't`.
+    if pair.is_quote() && prev_char.map_or(false, is_word_char) {
+        return None;
+    }
+
+    let start = c.len_utf8();
+    Some(AutoPairEdit::Insert {
+        text: format!("{}{}", pair.start, pair.end),
+        selected_range: start..start,
+    })
+}
+
+/// Return true if the chars are an empty auto closed pair, e.g.: `(` and `)`.
+fn is_empty_pair(pairs: &[BracketPair], prev_char: Option<char>, next_char: Option<char>) -> bool {
+    pairs
+        .iter()
+        .any(|pair| pair.auto_close && Some(pair.start) == prev_char && Some(pair.end) == next_char)
+}
+
+impl InputState {
+    /// Type the text with the auto pairs of the brackets and quotes.
+    ///
+    /// Returns true if the text has been handled.
+    pub(super) fn type_with_auto_pairs(
+        &mut self,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let mut chars = new_text.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return false;
+        };
+        let pairs = self.mode.brackets();
+        if pairs.is_empty() {
+            return false;
+        }
+
+        let range: Range<usize> = self.selected_range.into();
+        let selected_text = self.text.slice(range.clone()).to_string();
+        let prev_char = self.text.reversed_chars_at(range.start).next();
+        let next_char = self.text.chars_at(range.end).next();
+
+        match auto_pair_edit(&pairs, c, &selected_text, prev_char, next_char) {
+            Some(AutoPairEdit::Overtype) => {
+                self.move_to(range.end + c.len_utf8(), window, cx);
+            }
+            Some(AutoPairEdit::Insert {
+                text,
+                selected_range,
+            }) => {
+                self.replace_text_in_range(Some(self.range_to_utf16(&range)), &text, window, cx);
+                self.selected_range =
+                    (range.start + selected_range.start..range.start + selected_range.end).into();
+                self.selection_reversed = false;
+            }
+            None => {
+                let is_closing = pairs.iter().any(|pair| !pair.is_quote() && pair.end == c);
+                if !is_closing || !selected_text.is_empty() {
+                    return false;
+                }
+
+                self.replace_text_in_range(Some(self.range_to_utf16(&range)), new_text, window, cx);
+                self.outdent_closing_bracket(range.start, window, cx);
+            }
+        }
+
+        true
+    }
+
+    /// Outdent the closing bracket at the `offset` to the indentation of the line of the
+    /// opening bracket, if it is the first char of the line.
+    fn outdent_closing_bracket(
+        &mut self,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let row = self.text.offset_to_point(offset).row as usize;
+        let line_start = self.text.line_start_offset(row);
+        let prefix = self.text.slice(line_start..offset).to_string();
+        if !prefix.chars().all(|c| c == ' ' || c == '\t') {
+            return;
+        }
+
+        let Some((open, close)) = self.mode.bracket_pair_at(offset) else {
+            return;
+        };
+        if close.start != offset {
+            return;
+        }
+
+        let open_row = self.text.offset_to_point(open.start).row as usize;
+        let indent = self
+            .text
+            .line(open_row)
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect::<String>();
+        if open_row == row || indent == prefix {
+            return;
+        }
+
+        self.replace_text_in_range(
+            Some(self.range_to_utf16(&(line_start..offset))),
+            &indent,
+            window,
+            cx,
+        );
+        let offset = line_start + indent.len() + 1;
+        self.selected_range = (offset..offset).into();
+    }
+
+    /// Return the range to delete by backspace, to delete the empty auto pair around the cursor.
+    pub(super) fn empty_pair_range(&self) -> Option<Range<usize>> {
+        if !self.selected_range.is_empty() {
+            return None;
+        }
+
+        let offset = self.cursor();
+        let prev_char = self.text.reversed_chars_at(offset).next()?;
+        let next_char = self.text.chars_at(offset).next()?;
+        if !is_empty_pair(&self.mode.brackets(), Some(prev_char), Some(next_char)) {
+            return None;
+        }
+
+        Some(offset - prev_char.len_utf8()..offset + next_char.len_utf8())
+    }
+
+    /// Return the bracket pair next to the cursor to highlight.
+    pub(super) fn matching_brackets(&self) -> Option<(Range<usize>, Range<usize>)> {
+        if !self.mode.is_code_editor() || !self.selected_range.is_empty() {
+            return None;
+        }
+
+        self.mode.bracket_pair_at(self.cursor())
+    }
+
+    /// Move the cursor to the matching bracket of the bracket next to it.
+    pub(super) fn jump_to_bracket(
+        &mut self,
+        _: &JumpToBracket,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.cursor();
+            let Some((open, close)) = this.mode.bracket_pair_at(offset) else {
+                return;
+            };
+
+            if offset == close.start || offset == close.end {
+                this.move_to(open.start, window, cx);
+            } else {
+                this.move_to(close.start, window, cx);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highlighter::default_brackets;
+
+    #[test]
+    fn test_auto_pair_edit() {
+        let pairs = default_brackets();
+        let insert = |text: &str, selected_range: Range<usize>| {
+            Some(AutoPairEdit::Insert {
+                text: text.to_string(),
+                selected_range,
+            })
+        };
+
+        assert_eq!(
+            auto_pair_edit(&pairs, '(', "", None, None),
+            insert("()", 1..1)
+        );
+        assert_eq!(
+            auto_pair_edit(&pairs, '(', "", Some('a'), Some(' ')),
+            insert("()", 1..1)
+        );
+        assert_eq!(
+            auto_pair_edit(&pairs, '[', "", Some('('), Some(')')),
+            insert("[]", 1..1)
+        );
+        // Before a word
+        assert_eq!(auto_pair_edit(&pairs, '(', "", None, Some('a')), None);
+        // Wrap the selection
+        assert_eq!(
+            auto_pair_edit(&pairs, '{', "foo", None, Some('a')),
+            insert("{foo}", 1..4)
+        );
+        // Overtype
+        assert_eq!(
+            auto_pair_edit(&pairs, ')', "", Some('('), Some(')')),
+            Some(AutoPairEdit::Overtype)
+        );
+        assert_eq!(auto_pair_edit(&pairs, ')', "", Some('('), None), None);
+        assert_eq!(
+            auto_pair_edit(&pairs, '"', "", Some('a'), Some('"')),
+            Some(AutoPairEdit::Overtype)
+        );
+        // Quotes
+        assert_eq!(
+            auto_pair_edit(&pairs, '"', "", Some(' '), None),
+            insert("\"\"", 1..1)
+        );
+        assert_eq!(auto_pair_edit(&pairs, '"', "", Some('a'), None), None);
+        assert_eq!(auto_pair_edit(&pairs, 'a', "", None, None), None);
+
+        let pairs = vec![BracketPair::new('\'', '\'').no_auto_close()];
+        assert_eq!(auto_pair_edit(&pairs, '\'', "", None, None), None);
+        assert_eq!(auto_pair_edit(&pairs, '\'', "", None, Some('\'')), None);
+        assert_eq!(
+            auto_pair_edit(&pairs, '\'', "a", None, None),
+            insert("'a'", 1..2)
+        );
+    }
+
+    #[test]
+    fn test_is_empty_pair() {
+        let pairs = default_brackets();
+        assert!(is_empty_pair(&pairs, Some('('), Some(')')));
+        assert!(is_empty_pair(&pairs, Some('"'), Some('"')));
+        assert!(!is_empty_pair(&pairs, Some('('), Some(']')));
+        assert!(!is_empty_pair(&pairs, None, Some(')')));
+    }
+}
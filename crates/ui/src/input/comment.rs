@@ -0,0 +1,200 @@
+use std::ops::Range;
+
+use gpui::{Context, EntityInputHandler as _, Window};
+
+use crate::input::RopeExt as _;
+
+use super::{InputState, ToggleComment};
+
+/// Return true if the char is a part of the indentation.
+fn is_indent(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// Return the length in bytes of the indentation of the line.
+fn indent_len(line: &str) -> usize {
+    line.len() - line.trim_start_matches(is_indent).len()
+}
+
+/// Toggle the comments of the lines.
+///
+/// - `start` is the line comment token, or the block comment start token.
+/// - `end` is the block comment end token, `None` to use the line comment.
+///
+/// The lines are uncommented if all the non-blank lines are commented, otherwise the
+/// non-blank lines are commented at the min indentation of them.
+fn toggle_comment(lines: &[&str], start: &str, end: Option<&str>) -> Vec<String> {
+    let is_blank = |line: &str| line.trim().is_empty();
+    let is_commented = |line: &str| {
+        let line = line.trim_start_matches(is_indent).trim_end();
+        line.starts_with(start)
+            && end.map_or(true, |end| {
+                line.len() >= start.len() + end.len() && line.ends_with(end)
+            })
+    };
+
+    let mut non_blank_lines = lines.iter().filter(|line| !is_blank(line)).peekable();
+    if non_blank_lines.peek().is_none() {
+        return lines.iter().map(|line| line.to_string()).collect();
+    }
+
+    if non_blank_lines.all(|line| is_commented(line)) {
+        lines
+            .iter()
+            .map(|line| {
+                if is_blank(line) {
+                    return line.to_string();
+                }
+
+                let (indent, rest) = line.split_at(indent_len(line));
+                let Some(rest) = rest.strip_prefix(start) else {
+                    return line.to_string();
+                };
+                let mut rest = rest.strip_prefix(' ').unwrap_or(rest);
+                if let Some(end) = end {
+                    let trimmed = rest.trim_end();
+                    rest = trimmed.strip_suffix(end).unwrap_or(trimmed);
+                    rest = rest.strip_suffix(' ').unwrap_or(rest);
+                }
+                format!("{}{}", indent, rest)
+            })
+            .collect()
+    } else {
+        let min_indent = lines
+            .iter()
+            .filter(|line| !is_blank(line))
+            .map(|line| indent_len(line))
+            .min()
+            .unwrap_or(0);
+
+        lines
+            .iter()
+            .map(|line| {
+                if is_blank(line) {
+                    return line.to_string();
+                }
+
+                let (indent, rest) = line.split_at(min_indent);
+                match end {
+                    Some(end) => format!("{}{} {} {}", indent, start, rest, end),
+                    None => format!("{}{} {}", indent, start, rest),
+                }
+            })
+            .collect()
+    }
+}
+
+impl InputState {
+    /// Toggle the comments of the selected lines, by the comment tokens of the language.
+    ///
+    /// The line comment is preferred, the block comment is used to comment each line if the
+    /// language has no line comment, e.g.: HTML, CSS.
+    pub(super) fn toggle_comment(
+        &mut self,
+        _: &ToggleComment,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (start, end) = match self.mode.comment_tokens() {
+            (Some(line_comment), _) => (line_comment, None),
+            (None, Some((start, end))) => (start, Some(end)),
+            (None, None) => return,
+        };
+
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let range: Range<usize> = this.selected_range.into();
+            let start_row = this.text.offset_to_point(range.start).row as usize;
+            let end_point = this.text.offset_to_point(range.end);
+            let mut end_row = end_point.row as usize;
+            // Skip the last line if the selection ends at the start of it.
+            if end_point.column == 0 && end_row > start_row {
+                end_row -= 1;
+            }
+
+            let lines_range =
+                this.text.line_start_offset(start_row)..this.text.line_end_offset(end_row);
+            let old_text = this.text.slice(lines_range.clone()).to_string();
+            let lines = old_text.split('\n').collect::<Vec<_>>();
+            let new_text = toggle_comment(&lines, &start, end.as_deref()).join("\n");
+            if new_text == old_text {
+                return;
+            }
+
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&lines_range)),
+                &new_text,
+                window,
+                cx,
+            );
+
+            let new_end = lines_range.start + new_text.len();
+            this.selected_range = if range.is_empty() {
+                // Keep the cursor at the same position of the line.
+                let delta = new_text.len() as isize - old_text.len() as isize;
+                let offset = range
+                    .start
+                    .saturating_add_signed(delta)
+                    .clamp(lines_range.start, new_end);
+                (offset..offset).into()
+            } else {
+                (lines_range.start..new_end).into()
+            };
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_line_comment() {
+        assert_eq!(
+            toggle_comment(&["fn a() {", "    b();", "", "}"], "//", None),
+            vec!["// fn a() {", "//     b();", "", "// }"]
+        );
+        assert_eq!(
+            toggle_comment(&["    a();", "", "        b();"], "//", None),
+            vec!["    // a();", "", "    //     b();"]
+        );
+        assert_eq!(
+            toggle_comment(&["    // a();", "", "    //b();"], "//", None),
+            vec!["    a();", "", "    b();"]
+        );
+        // Not all lines are commented
+        assert_eq!(
+            toggle_comment(&["# a", "b"], "#", None),
+            vec!["# # a", "# b"]
+        );
+        assert_eq!(toggle_comment(&["", "  "], "#", None), vec!["", "  "]);
+        // Only spaces and tabs are the indentation.
+        assert_eq!(
+            toggle_comment(&["\u{3000}// x", "\x0c// x"], "//", None),
+            vec!["// \u{3000}// x", "// \x0c// x"]
+        );
+        assert_eq!(
+            toggle_comment(&["\t// \u{3000}x"], "//", None),
+            vec!["\t\u{3000}x"]
+        );
+    }
+
+    #[test]
+    fn test_toggle_block_comment() {
+        assert_eq!(
+            toggle_comment(&["  <div>", "  </div>"], "<!--", Some("-->")),
+            vec!["  <!-- <div> -->", "  <!-- </div> -->"]
+        );
+        assert_eq!(
+            toggle_comment(
+                &["  <!-- <div> -->", "  <!--</div>-->"],
+                "<!--",
+                Some("-->")
+            ),
+            vec!["  <div>", "  </div>"]
+        );
+        assert_eq!(
+            toggle_comment(&["/* a */", "b"], "/*", Some("*/")),
+            vec!["/* /* a */ */", "/* b */"]
+        );
+    }
+}
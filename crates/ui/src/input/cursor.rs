@@ -38,12 +38,108 @@ impl From<Selection> for Range<usize> {
     }
 }
 
+/// One of the cursors of multiple cursors editing, a selection with the side the cursor is on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub(crate) struct Cursor {
+    pub(crate) range: Selection,
+    /// Whether the cursor is at the start of the range.
+    pub(crate) reversed: bool,
+}
+
+impl Cursor {
+    pub(crate) fn new(range: impl Into<Selection>, reversed: bool) -> Self {
+        Self {
+            range: range.into(),
+            reversed,
+        }
+    }
+
+    /// Return the offset of the cursor.
+    pub(crate) fn offset(&self) -> usize {
+        if self.reversed {
+            self.range.start
+        } else {
+            self.range.end
+        }
+    }
+
+    /// Move the cursor by `delta` bytes.
+    pub(crate) fn shift(&mut self, delta: isize) {
+        self.range.start = self.range.start.saturating_add_signed(delta);
+        self.range.end = self.range.end.saturating_add_signed(delta);
+    }
+}
+
+/// Sort the cursors in text order and merge the overlapping ones.
+///
+/// Returns the cursors and the new index of the cursor at `primary`.
+pub(crate) fn merge_cursors(cursors: Vec<Cursor>, primary: usize) -> (Vec<Cursor>, usize) {
+    let mut cursors = cursors.into_iter().enumerate().collect::<Vec<_>>();
+    cursors.sort_by_key(|(_, cursor)| (cursor.range.start, cursor.range.end));
+
+    let mut merged: Vec<(bool, Cursor)> = Vec::with_capacity(cursors.len());
+    for (ix, cursor) in cursors {
+        let is_primary = ix == primary;
+        if let Some((last_is_primary, last)) = merged.last_mut() {
+            let touches = cursor.range.start == last.range.end
+                && (cursor.range.is_empty() || last.range.is_empty());
+            if cursor.range.start < last.range.end || touches {
+                last.range.end = last.range.end.max(cursor.range.end);
+                if is_primary {
+                    last.reversed = cursor.reversed;
+                    *last_is_primary = true;
+                }
+                continue;
+            }
+        }
+        merged.push((is_primary, cursor));
+    }
+
+    let primary = merged
+        .iter()
+        .position(|(is_primary, _)| *is_primary)
+        .unwrap_or(0);
+    (
+        merged.into_iter().map(|(_, cursor)| cursor).collect(),
+        primary,
+    )
+}
+
 pub type Position = lsp_types::Position;
 
 #[cfg(test)]
 mod tests {
+    use super::{merge_cursors, Cursor};
     use crate::input::Position;
 
+    #[test]
+    fn test_merge_cursors() {
+        let cursors = vec![
+            Cursor::new(10..10, false),
+            Cursor::new(2..5, true),
+            Cursor::new(4..8, false),
+            Cursor::new(10..10, false),
+            Cursor::new(12..14, false),
+            Cursor::new(14..16, false),
+        ];
+
+        let (merged, primary) = merge_cursors(cursors, 2);
+        assert_eq!(
+            merged,
+            vec![
+                Cursor::new(2..8, false),
+                Cursor::new(10..10, false),
+                Cursor::new(12..14, false),
+                Cursor::new(14..16, false),
+            ]
+        );
+        assert_eq!(primary, 0);
+
+        let (merged, primary) = merge_cursors(vec![Cursor::new(3..3, false)], 0);
+        assert_eq!(merged, vec![Cursor::new(3..3, false)]);
+        assert_eq!(primary, 0);
+    }
+
     #[test]
     fn test_line_column_from_to() {
         assert_eq!(
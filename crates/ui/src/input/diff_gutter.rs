@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use gpui::{Context, EntityInputHandler as _, SharedString, Task, Window};
+use rope::Rope;
+
+use crate::diff::{diff_lines, DiffAlgorithm, DiffHunk, DiffHunkKind};
+use crate::input::RopeExt as _;
+
+use super::{InputState, RevertHunk};
+
+impl InputState {
+    /// Set the base text to show the changed lines in the gutter, like a git diff.
+    ///
+    /// The added, modified and removed lines compared with the base text are marked at the left
+    /// of the line numbers, and [`RevertHunk`] restores the hunk at the cursor to the base text.
+    ///
+    /// Set `None` to remove the diff gutter.
+    pub fn set_diff_base(&mut self, base: Option<impl Into<SharedString>>, cx: &mut Context<Self>) {
+        self.diff_base = base.map(|base| Rope::from(base.into().as_str()));
+        self.update_diff(cx);
+    }
+
+    /// Return the changed hunks of the text compared with the diff base.
+    pub fn diff_hunks(&self) -> &[DiffHunk] {
+        &self.diff_hunks
+    }
+
+    /// Recompute the diff hunks on the background, if the diff base is set.
+    pub(super) fn update_diff(&mut self, cx: &mut Context<Self>) {
+        let Some(base) = self.diff_base.clone() else {
+            if !self.diff_hunks.is_empty() {
+                self.diff_hunks.clear();
+                self._diff_task = Task::ready(());
+                cx.notify();
+            }
+            return;
+        };
+
+        let text = self.text.clone();
+        let hunks = cx
+            .background_executor()
+            .spawn(async move { diff_lines(&base, &text, DiffAlgorithm::Myers) });
+        self._diff_task = cx.spawn(async move |this, cx| {
+            let hunks = hunks.await;
+            _ = this.update(cx, |this, cx| {
+                this.diff_hunks = hunks;
+                cx.notify();
+            });
+        });
+    }
+
+    /// Return the hunk of the row, or the removed hunk at the start of the row.
+    fn diff_hunk_at_row(&self, row: usize) -> Option<&DiffHunk> {
+        let ix = self.diff_hunks.partition_point(|hunk| {
+            hunk.new_rows.end <= row && !(hunk.new_rows.is_empty() && hunk.new_rows.start == row)
+        });
+
+        self.diff_hunks.get(ix).filter(|hunk| {
+            hunk.new_rows.contains(&row) || (hunk.new_rows.is_empty() && hunk.new_rows.start == row)
+        })
+    }
+
+    /// Return the diff marker to paint in the gutter of the row.
+    ///
+    /// The [`DiffHunkKind::Removed`] marker is at the top edge of the row.
+    pub(super) fn diff_marker_at_row(&self, row: usize) -> Option<DiffHunkKind> {
+        self.diff_hunk_at_row(row).map(|hunk| hunk.kind())
+    }
+
+    /// Restore the hunk at the cursor to the diff base.
+    pub(super) fn revert_hunk(
+        &mut self,
+        _: &RevertHunk,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(base) = self.diff_base.clone() else {
+            return;
+        };
+
+        let row = self.text.offset_to_point(self.cursor()).row as usize;
+        let Some(hunk) = self.diff_hunk_at_row(row).cloned().or_else(|| {
+            // The lines removed below the cursor line.
+            self.diff_hunk_at_row(row + 1)
+                .filter(|hunk| hunk.kind() == DiffHunkKind::Removed)
+                .cloned()
+        }) else {
+            return;
+        };
+
+        let (range, new_text) = revert_edit(&self.text, &base, &hunk);
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+
+        let row = hunk
+            .new_rows
+            .start
+            .min(self.text.lines_len().saturating_sub(1));
+        self.move_to(self.text.line_start_offset(row), window, cx);
+    }
+}
+
+/// Return the range of the `text` and the new text to restore the `hunk` to the `base`.
+fn revert_edit(text: &Rope, base: &Rope, hunk: &DiffHunk) -> (Range<usize>, String) {
+    let old_lines = hunk
+        .old_rows
+        .clone()
+        .map(|row| base.line(row).to_string())
+        .collect::<Vec<_>>();
+    let rows = &hunk.new_rows;
+
+    if rows.end < text.lines_len() {
+        let range = text.line_start_offset(rows.start)..text.line_start_offset(rows.end);
+        let new_text = old_lines.iter().map(|line| format!("{}\n", line)).collect();
+        (range, new_text)
+    } else if rows.start > 0 {
+        // Replace the lines at the end, with the newline of the previous line.
+        let range = text.line_end_offset(rows.start - 1)..text.len();
+        let new_text = old_lines.iter().map(|line| format!("\n{}", line)).collect();
+        (range, new_text)
+    } else {
+        (0..text.len(), old_lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revert(text: &str, base: &str, old_rows: Range<usize>, new_rows: Range<usize>) -> String {
+        let text_rope = Rope::from(text);
+        let hunk = DiffHunk { old_rows, new_rows };
+        let (range, new_text) = revert_edit(&text_rope, &Rope::from(base), &hunk);
+
+        let mut text = text.to_string();
+        text.replace_range(range, &new_text);
+        text
+    }
+
+    #[test]
+    fn test_revert_edit() {
+        // Modified line in the middle.
+        assert_eq!(revert("a\nX\nc", "a\nb\nc", 1..2, 1..2), "a\nb\nc");
+        // Added lines in the middle.
+        assert_eq!(revert("a\nX\nY\nc", "a\nc", 1..1, 1..3), "a\nc");
+        // Removed line at the start.
+        assert_eq!(revert("b\nc", "a\nb\nc", 0..1, 0..0), "a\nb\nc");
+        // Removed line at the end.
+        assert_eq!(revert("a\nb", "a\nb\nc", 2..3, 2..2), "a\nb\nc");
+        // Added lines at the end.
+        assert_eq!(revert("a\nb\nc", "a", 1..1, 1..3), "a");
+        // All lines replaced.
+        assert_eq!(revert("x", "", 0..1, 0..1), "");
+    }
+}
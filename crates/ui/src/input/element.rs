@@ -10,6 +10,7 @@ use rope::Rope;
 use smallvec::SmallVec;
 
 use crate::{
+    diff::DiffHunkKind,
     input::{blink_cursor::CURSOR_WIDTH, RopeExt as _},
     ActiveTheme as _, Colorize, Root,
 };
@@ -19,6 +20,15 @@ use super::{mode::InputMode, InputState, LastLayout};
 const BOTTOM_MARGIN_ROWS: usize = 3;
 pub(super) const RIGHT_MARGIN: Pixels = px(10.);
 pub(super) const LINE_NUMBER_RIGHT_MARGIN: Pixels = px(10.);
+/// The width of the fold chevrons area, at the right of the line numbers.
+pub(super) const FOLD_GUTTER_WIDTH: Pixels = px(14.);
+/// The width of the placeholder shown after the first line of a folded region.
+pub(super) const FOLD_PLACEHOLDER_WIDTH: Pixels = px(28.);
+const FOLD_PLACEHOLDER_GAP: Pixels = px(6.);
+/// The max number of the sticky headers pinned at the top.
+const MAX_STICKY_LINES: usize = 5;
+/// The width of the diff markers at the left of the line numbers.
+const DIFF_MARKER_WIDTH: Pixels = px(3.);
 
 pub(super) struct TextElement {
     state: Entity<InputState>,
@@ -120,6 +130,12 @@ impl TextElement {
                 break;
             }
 
+            if text_wrapper.is_hidden(ix) {
+                // +1 for the last `\n`
+                prev_lines_offset += wrap_line.len() + 1;
+                continue;
+            }
+
             let in_visible_range = ix >= visible_range.start && ix < visible_range.end;
             if let Some(line) = in_visible_range
                 .then(|| lines.get(ix.saturating_sub(visible_range.start)))
@@ -298,7 +314,13 @@ impl TextElement {
         let mut offset_y = visible_top;
         let mut line_corners = vec![];
 
-        for line in lines.iter() {
+        for (ix, line) in lines.iter().enumerate() {
+            if last_layout.is_hidden(ix) {
+                // +1 for skip the last `\n`
+                prev_lines_offset += line.len() + 1;
+                continue;
+            }
+
             let line_size = line.size(line_height);
             let line_wrap_width = line_size.width;
 
@@ -430,6 +452,23 @@ impl TextElement {
         paths
     }
 
+    /// Returns the paths of the matching brackets next to the cursor.
+    fn layout_bracket_highlights(
+        &self,
+        last_layout: &LastLayout,
+        bounds: &mut Bounds<Pixels>,
+        cx: &mut App,
+    ) -> Vec<Path<Pixels>> {
+        let Some((open, close)) = self.state.read(cx).matching_brackets() else {
+            return vec![];
+        };
+
+        [open, close]
+            .into_iter()
+            .filter_map(|range| Self::layout_match_range(range, last_layout, bounds))
+            .collect()
+    }
+
     fn layout_selections(
         &self,
         last_layout: &LastLayout,
@@ -459,6 +498,65 @@ impl TextElement {
         Self::layout_match_range(range, &last_layout, bounds)
     }
 
+    /// Returns the cursor bounds and the selection paths of the extra cursors of multiple cursors.
+    fn layout_extra_cursors(
+        &self,
+        last_layout: &LastLayout,
+        bounds: &mut Bounds<Pixels>,
+        cx: &mut App,
+    ) -> (Vec<Bounds<Pixels>>, Vec<Path<Pixels>>) {
+        let state = self.state.read(cx);
+        let visible_range_offset = &last_layout.visible_range_offset;
+
+        let mut cursor_bounds = vec![];
+        let mut selection_paths = vec![];
+        for cursor in state.cursors.iter() {
+            if let Some(bounds) = Self::layout_cursor_at(cursor.offset(), last_layout, bounds) {
+                cursor_bounds.push(bounds);
+            }
+
+            let range = cursor.range.start.max(visible_range_offset.start)
+                ..cursor.range.end.min(visible_range_offset.end);
+            if let Some(path) = Self::layout_match_range(range, last_layout, bounds) {
+                selection_paths.push(path);
+            }
+        }
+
+        (cursor_bounds, selection_paths)
+    }
+
+    /// Returns the bounds of a cursor at the offset, if it is in the visible lines.
+    fn layout_cursor_at(
+        offset: usize,
+        last_layout: &LastLayout,
+        bounds: &Bounds<Pixels>,
+    ) -> Option<Bounds<Pixels>> {
+        let line_height = last_layout.line_height;
+        let mut prev_lines_offset = last_layout.visible_range_offset.start;
+        let mut offset_y = last_layout.visible_top;
+
+        for (ix, line) in last_layout.lines.iter().enumerate() {
+            if last_layout.is_hidden(ix) {
+                prev_lines_offset += line.len() + 1;
+                continue;
+            }
+
+            if offset >= prev_lines_offset && offset <= prev_lines_offset + line.len() {
+                let pos = line.position_for_index(offset - prev_lines_offset, line_height)?;
+                return Some(Bounds::new(
+                    bounds.origin + point(last_layout.line_number_width + pos.x, offset_y + pos.y),
+                    size(CURSOR_WIDTH, line_height),
+                ));
+            }
+
+            offset_y += line.size(line_height).height;
+            // +1 for the last `\n`
+            prev_lines_offset += line.len() + 1;
+        }
+
+        None
+    }
+
     /// Calculate the visible range of lines in the viewport with virtual scrolling optimizations.
     /// Enhanced with buffer zones for smoother scrolling and better performance.
     ///
@@ -478,6 +576,7 @@ impl TextElement {
         }
 
         let total_lines = state.text_wrapper.len();
+        let total_rows = state.text_wrapper.lines.len();
         let scroll_top = state.scroll_handle.offset().y;
 
         // Virtual scrolling optimization: Calculate buffer zones for smoother scrolling
@@ -486,10 +585,10 @@ impl TextElement {
 
         // Early exit for small documents that fit entirely in viewport
         if total_lines <= viewport_lines + buffer_zone * 2 {
-            return (0..total_lines, visible_top);
+            return (0..total_rows, visible_top);
         }
 
-        let mut visible_range = 0..total_lines;
+        let mut visible_range = 0..total_rows;
         let mut line_bottom = px(0.);
         let mut first_visible_found = false;
 
@@ -507,13 +606,13 @@ impl TextElement {
 
             // Find last visible line with buffer zone and exit early
             if line_bottom + scroll_top >= input_height {
-                visible_range.end = (ix + buffer_zone + 1).min(total_lines);
+                visible_range.end = (ix + buffer_zone + 1).min(total_rows);
                 break;
             }
         }
 
         // Ensure we don't exceed total lines
-        visible_range.end = visible_range.end.min(total_lines);
+        visible_range.end = visible_range.end.min(total_rows);
 
         (visible_range, visible_top)
     }
@@ -533,6 +632,270 @@ impl TextElement {
         }
     }
 
+    /// Returns the fold state of the visible lines, and the shaped placeholder for the folded regions.
+    fn layout_fold_markers(
+        &self,
+        last_layout: &LastLayout,
+        font_size: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (Vec<Option<bool>>, Option<WrappedLine>) {
+        let state = self.state.read(cx);
+        if !state.mode.folding() {
+            return (vec![], None);
+        }
+
+        let fold_markers = (0..last_layout.lines.len())
+            .map(|ix| {
+                let row = last_layout.visible_range.start + ix;
+                state.fold_range_at_row(row).map(|_| state.is_folded(row))
+            })
+            .collect::<Vec<_>>();
+
+        let placeholder: SharedString = "⋯".into();
+        let fold_placeholder = window
+            .text_system()
+            .shape_text(
+                placeholder.clone(),
+                font_size,
+                &[TextRun {
+                    len: placeholder.len(),
+                    font: window.text_style().font(),
+                    color: cx.theme().muted_foreground,
+                    background_color: None,
+                    underline: None,
+                    strikethrough: None,
+                }],
+                None,
+                None,
+            )
+            .ok()
+            .and_then(|lines| lines.into_iter().next());
+
+        (fold_markers, fold_placeholder)
+    }
+
+    /// Returns the diff markers of the visible lines, with one more item for the row after them,
+    /// to show the lines removed at the bottom.
+    fn layout_diff_markers(&self, last_layout: &LastLayout, cx: &App) -> Vec<Option<DiffHunkKind>> {
+        let state = self.state.read(cx);
+        if state.diff_hunks.is_empty() || !state.mode.line_number() {
+            return vec![];
+        }
+
+        (0..=last_layout.lines.len())
+            .map(|ix| state.diff_marker_at_row(last_layout.visible_range.start + ix))
+            .collect()
+    }
+
+    /// Paint the diff marker at the `origin` of the row with the `height`, the removed marker is
+    /// a triangle at the top edge of the row.
+    fn paint_diff_marker(
+        kind: DiffHunkKind,
+        origin: Point<Pixels>,
+        height: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        match kind {
+            DiffHunkKind::Removed => {
+                let r = px(4.);
+                let mut builder = gpui::PathBuilder::fill();
+                builder.move_to(origin + point(px(0.), -r));
+                builder.line_to(origin + point(r, px(0.)));
+                builder.line_to(origin + point(px(0.), r));
+                builder.close();
+                if let Ok(path) = builder.build() {
+                    window.paint_path(path, cx.theme().danger);
+                }
+            }
+            DiffHunkKind::Added | DiffHunkKind::Modified => {
+                let color = if kind == DiffHunkKind::Added {
+                    cx.theme().success
+                } else {
+                    cx.theme().info
+                };
+                window.paint_quad(fill(
+                    Bounds::new(origin, size(DIFF_MARKER_WIDTH, height)),
+                    color,
+                ));
+            }
+        }
+    }
+
+    /// Returns the path of a chevron at the center, pointing right if folded, otherwise down.
+    fn layout_chevron(center: Point<Pixels>, folded: bool) -> Option<Path<Pixels>> {
+        let r = px(3.);
+        let mut builder = gpui::PathBuilder::stroke(px(1.));
+        if folded {
+            builder.move_to(center + point(-r.half(), -r));
+            builder.line_to(center + point(r.half(), px(0.)));
+            builder.line_to(center + point(-r.half(), r));
+        } else {
+            builder.move_to(center + point(-r, -r.half()));
+            builder.line_to(center + point(px(0.), r.half()));
+            builder.line_to(center + point(r, -r.half()));
+        }
+        builder.build().ok()
+    }
+
+    /// Returns the bounds of the indentation guides in the visible lines.
+    fn layout_indent_guides(
+        &self,
+        last_layout: &LastLayout,
+        bounds: &Bounds<Pixels>,
+        font_size: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<Bounds<Pixels>> {
+        let state = self.state.read(cx);
+        if state.indent_guides.is_empty() {
+            return vec![];
+        }
+
+        let line_height = last_layout.line_height;
+        let visible_range = &last_layout.visible_range;
+        let space_width = window
+            .text_system()
+            .shape_line(
+                " ".into(),
+                font_size,
+                &[TextRun {
+                    len: 1,
+                    font: window.text_style().font(),
+                    color: gpui::black(),
+                    background_color: None,
+                    underline: None,
+                    strikethrough: None,
+                }],
+                None,
+            )
+            .width;
+
+        // The top and bottom of the visible lines.
+        let mut line_tops = Vec::with_capacity(last_layout.lines.len());
+        let mut offset_y = last_layout.visible_top;
+        for (ix, line) in last_layout.lines.iter().enumerate() {
+            let height = if last_layout.is_hidden(ix) {
+                px(0.)
+            } else {
+                line.size(line_height).height
+            };
+            line_tops.push((offset_y, offset_y + height));
+            offset_y += height;
+        }
+
+        let mut guide_bounds = vec![];
+        for guide in state.indent_guides.iter() {
+            let start = guide.rows.start.max(visible_range.start);
+            let end = guide.rows.end.min(visible_range.end);
+            if start >= end {
+                continue;
+            }
+
+            let Some((top, _)) = line_tops.get(start - visible_range.start) else {
+                continue;
+            };
+            let Some((_, bottom)) = line_tops.get(end - 1 - visible_range.start) else {
+                continue;
+            };
+            if bottom <= top {
+                continue;
+            }
+
+            guide_bounds.push(Bounds::new(
+                bounds.origin
+                    + point(
+                        last_layout.line_number_width + space_width * guide.column as f32,
+                        *top,
+                    ),
+                size(px(1.), *bottom - *top),
+            ));
+        }
+
+        guide_bounds
+    }
+
+    /// Returns the rows, the line numbers and the lines of the sticky headers to pin at the top.
+    fn layout_sticky_headers(
+        &self,
+        last_layout: &LastLayout,
+        scroll_top: Pixels,
+        font_size: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<(usize, Option<WrappedLine>, WrappedLine)> {
+        let state = self.state.read(cx);
+        if !state.mode.sticky_scroll() {
+            return vec![];
+        }
+
+        // Find the first visible row in the viewport.
+        let line_height = last_layout.line_height;
+        let mut top_row = None;
+        let mut offset_y = last_layout.visible_top;
+        for (ix, line) in last_layout.lines.iter().enumerate() {
+            if last_layout.is_hidden(ix) {
+                continue;
+            }
+
+            offset_y += line.size(line_height).height;
+            if offset_y > scroll_top {
+                top_row = Some(last_layout.visible_range.start + ix);
+                break;
+            }
+        }
+        let Some(top_row) = top_row else {
+            return vec![];
+        };
+
+        let text_style = window.text_style();
+        let mut headers = vec![];
+        for row in state.mode.sticky_header_rows(top_row, MAX_STICKY_LINES) {
+            let range = state.text.line_start_offset(row)..state.text.line_end_offset(row);
+            let text: SharedString = state.text.slice(range.clone()).to_string().into();
+            let runs = match state.mode.highlight_styles(&range, cx) {
+                Some(styles) => styles
+                    .iter()
+                    .map(|(range, style)| text_style.clone().highlight(*style).to_run(range.len()))
+                    .filter(|run| run.len > 0)
+                    .collect(),
+                None => vec![text_style.to_run(text.len())],
+            };
+            let Some(line) = window
+                .text_system()
+                .shape_text(text, font_size, &runs, None, None)
+                .ok()
+                .and_then(|lines| lines.into_iter().next())
+            else {
+                continue;
+            };
+
+            let line_number = if state.mode.line_number() {
+                let line_no_text: SharedString = format!("{:>6}", row + 1).into();
+                let run = TextRun {
+                    len: line_no_text.len(),
+                    font: text_style.font(),
+                    color: cx.theme().muted_foreground,
+                    background_color: None,
+                    underline: None,
+                    strikethrough: None,
+                };
+                window
+                    .text_system()
+                    .shape_text(line_no_text, font_size, &[run], None, None)
+                    .ok()
+                    .and_then(|lines| lines.into_iter().next())
+            } else {
+                None
+            };
+
+            headers.push((row, line_number, line));
+        }
+
+        headers
+    }
+
     /// First usize is the offset of skipped.
     fn highlight_lines(
         &mut self,
@@ -544,12 +907,13 @@ impl TextElement {
         let state = self.state.read(cx);
         let text = &state.text;
 
-        let (highlighter, diagnostics) = match &state.mode {
+        let (highlighter, diagnostics, semantic_tokens) = match &state.mode {
             InputMode::CodeEditor {
                 highlighter,
                 diagnostics,
+                semantic_tokens,
                 ..
-            } => (highlighter.borrow(), diagnostics),
+            } => (highlighter.borrow(), diagnostics, semantic_tokens),
             _ => return None,
         };
         let highlighter = highlighter.as_ref()?;
@@ -571,6 +935,10 @@ impl TextElement {
             offset = range.end;
         }
 
+        // The semantic tokens override the Tree-sitter highlighting.
+        let semantic_styles = semantic_tokens.styles_for_range(&visible_byte_range, cx);
+        styles = gpui::combine_highlights(styles, semantic_styles).collect();
+
         let diagnostic_styles = diagnostics.styles_for_range(&visible_byte_range, cx);
 
         // Combine marker styles
@@ -592,7 +960,20 @@ pub(super) struct PrepaintState {
     /// row index (zero based), no wrap, same line as the cursor.
     current_row: Option<usize>,
     selection_path: Option<Path<Pixels>>,
+    /// The cursors and selections of the extra cursors of multiple cursors.
+    extra_cursor_bounds: Vec<Bounds<Pixels>>,
+    extra_selection_paths: Vec<Path<Pixels>>,
     search_match_paths: Vec<(Path<Pixels>, bool)>,
+    bracket_highlight_paths: Vec<Path<Pixels>>,
+    /// The fold state of the visible lines, `None` if the line can't be folded, otherwise is folded or not.
+    fold_markers: Vec<Option<bool>>,
+    /// The `⋯` placeholder text for the folded regions.
+    fold_placeholder: Option<WrappedLine>,
+    /// The diff markers of the visible lines, and the row after them.
+    diff_markers: Vec<Option<DiffHunkKind>>,
+    indent_guide_bounds: Vec<Bounds<Pixels>>,
+    /// The rows, line numbers and lines of the sticky headers.
+    sticky_headers: Vec<(usize, Option<WrappedLine>, WrappedLine)>,
     bounds: Bounds<Pixels>,
 }
 
@@ -740,7 +1121,12 @@ impl Element for TextElement {
             None,
         );
         let line_number_width = if state.mode.line_number() {
-            empty_line_number.width + px(6.) + LINE_NUMBER_RIGHT_MARGIN
+            let fold_gutter_width = if state.mode.folding() {
+                FOLD_GUTTER_WIDTH
+            } else {
+                px(0.)
+            };
+            empty_line_number.width + px(6.) + fold_gutter_width + LINE_NUMBER_RIGHT_MARGIN
         } else {
             px(0.)
         };
@@ -877,7 +1263,7 @@ impl Element for TextElement {
         );
 
         let mut last_layout = LastLayout {
-            visible_range,
+            visible_range: visible_range.clone(),
             visible_top,
             visible_range_offset: visible_start_offset..visible_end_offset,
             line_height,
@@ -885,6 +1271,13 @@ impl Element for TextElement {
             line_number_width,
             lines: Rc::new(lines),
             cursor_bounds: None,
+            hidden_lines: Rc::new(
+                visible_range
+                    .clone()
+                    .map(|row| state.text_wrapper.is_hidden(row))
+                    .collect(),
+            ),
+            sticky_rows: vec![],
         };
 
         // `position_for_index` for example
@@ -923,7 +1316,10 @@ impl Element for TextElement {
         last_layout.cursor_bounds = cursor_bounds;
 
         let search_match_paths = self.layout_search_matches(&last_layout, &mut bounds, cx);
+        let bracket_highlight_paths = self.layout_bracket_highlights(&last_layout, &mut bounds, cx);
         let selection_path = self.layout_selections(&last_layout, &mut bounds, cx);
+        let (extra_cursor_bounds, extra_selection_paths) =
+            self.layout_extra_cursors(&last_layout, &mut bounds, cx);
 
         let state = self.state.read(cx);
         let line_numbers = if state.mode.line_number() {
@@ -951,6 +1347,11 @@ impl Element for TextElement {
             line_numbers.reserve(last_layout.lines.len());
 
             for (ix, line) in last_layout.lines.iter().enumerate() {
+                if last_layout.is_hidden(ix) {
+                    line_numbers.push(SmallVec::new());
+                    continue;
+                }
+
                 let ix = last_layout.visible_range.start + ix;
                 let line_no = ix + 1;
 
@@ -980,6 +1381,20 @@ impl Element for TextElement {
             None
         };
 
+        let (fold_markers, fold_placeholder) =
+            self.layout_fold_markers(&last_layout, font_size, window, cx);
+        let diff_markers = self.layout_diff_markers(&last_layout, cx);
+        let indent_guide_bounds =
+            self.layout_indent_guides(&last_layout, &bounds, font_size, window, cx);
+        let sticky_headers = self.layout_sticky_headers(
+            &last_layout,
+            -cursor_scroll_offset.y,
+            font_size,
+            window,
+            cx,
+        );
+        last_layout.sticky_rows = sticky_headers.iter().map(|(row, _, _)| *row).collect();
+
         PrepaintState {
             bounds,
             last_layout,
@@ -989,7 +1404,15 @@ impl Element for TextElement {
             cursor_scroll_offset,
             current_row,
             selection_path,
+            extra_cursor_bounds,
+            extra_selection_paths,
             search_match_paths,
+            bracket_highlight_paths,
+            fold_markers,
+            fold_placeholder,
+            diff_markers,
+            indent_guide_bounds,
+            sticky_headers,
         }
     }
 
@@ -1094,19 +1517,65 @@ impl Element for TextElement {
                 }
             }
 
+            for path in prepaint.bracket_highlight_paths.drain(..) {
+                window.paint_path(path, cx.theme().border);
+            }
+
             if let Some(path) = prepaint.selection_path.take() {
                 window.paint_path(path, cx.theme().selection);
             }
+            for path in prepaint.extra_selection_paths.drain(..) {
+                window.paint_path(path, cx.theme().selection);
+            }
+        }
+
+        // Paint indentation guides
+        for guide_bounds in prepaint.indent_guide_bounds.iter() {
+            window.paint_quad(fill(*guide_bounds, cx.theme().border));
         }
 
         // Paint text
         let mut offset_y = mask_offset_y + invisible_top_padding;
-        for line in prepaint.last_layout.lines.iter() {
+        for (ix, line) in prepaint.last_layout.lines.iter().enumerate() {
+            if prepaint.last_layout.is_hidden(ix) {
+                continue;
+            }
+
             let p = point(
                 origin.x + prepaint.last_layout.line_number_width,
                 origin.y + offset_y,
             );
             _ = line.paint(p, line_height, TextAlign::Left, None, window, cx);
+
+            // Paint the placeholder after the first line of a folded region.
+            if prepaint.fold_markers.get(ix) == Some(&Some(true)) {
+                if let (Some(placeholder), Some(end)) = (
+                    prepaint.fold_placeholder.as_ref(),
+                    line.position_for_index(line.len(), line_height),
+                ) {
+                    let placeholder_bounds = Bounds::new(
+                        p + end + point(FOLD_PLACEHOLDER_GAP, px(2.)),
+                        size(FOLD_PLACEHOLDER_WIDTH, line_height - px(4.)),
+                    );
+                    window.paint_quad(
+                        fill(placeholder_bounds, cx.theme().muted)
+                            .corner_radii(Corners::all(cx.theme().radius)),
+                    );
+                    let text_origin = point(
+                        placeholder_bounds.center().x - placeholder.size(line_height).width.half(),
+                        p.y + end.y,
+                    );
+                    _ = placeholder.paint(
+                        text_origin,
+                        line_height,
+                        TextAlign::Left,
+                        None,
+                        window,
+                        cx,
+                    );
+                }
+            }
+
             offset_y += line.size(line_height).height;
         }
 
@@ -1116,6 +1585,9 @@ impl Element for TextElement {
                 cursor_bounds.origin.y += prepaint.cursor_scroll_offset.y;
                 window.paint_quad(fill(cursor_bounds, cx.theme().caret));
             }
+            for cursor_bounds in prepaint.extra_cursor_bounds.drain(..) {
+                window.paint_quad(fill(cursor_bounds, cx.theme().caret));
+            }
         }
 
         // Paint line numbers
@@ -1138,6 +1610,7 @@ impl Element for TextElement {
             // Each item is the normal lines.
             for (ix, lines) in line_numbers.iter().enumerate() {
                 let row = visible_range.start + ix;
+                let row_top = offset_y;
                 for line in lines {
                     let p = point(input_bounds.origin.x, origin.y + offset_y);
 
@@ -1160,7 +1633,100 @@ impl Element for TextElement {
                     _ = line.paint(p, line_height, TextAlign::Left, None, window, cx);
                     offset_y += line_size.height;
                 }
+
+                // Paint the fold chevron at the right of the line number.
+                if let Some(Some(folded)) =
+                    prepaint.fold_markers.get(ix).filter(|_| !lines.is_empty())
+                {
+                    let chevron_center = point(
+                        input_bounds.origin.x + prepaint.last_layout.line_number_width
+                            - LINE_NUMBER_RIGHT_MARGIN
+                            - FOLD_GUTTER_WIDTH.half(),
+                        origin.y + row_top + line_height.half(),
+                    );
+                    let color = if *folded {
+                        cx.theme().foreground
+                    } else {
+                        cx.theme().muted_foreground
+                    };
+                    if let Some(path) = Self::layout_chevron(chevron_center, *folded) {
+                        window.paint_path(path, color);
+                    }
+                }
+
+                // Paint the diff marker at the left of the line number.
+                if let Some(Some(kind)) = prepaint.diff_markers.get(ix) {
+                    Self::paint_diff_marker(
+                        *kind,
+                        point(input_bounds.origin.x, origin.y + row_top),
+                        offset_y - row_top,
+                        window,
+                        cx,
+                    );
+                }
+            }
+
+            // The lines removed after the last visible line.
+            if let Some(Some(DiffHunkKind::Removed)) = prepaint.diff_markers.get(line_numbers.len())
+            {
+                Self::paint_diff_marker(
+                    DiffHunkKind::Removed,
+                    point(input_bounds.origin.x, origin.y + offset_y),
+                    px(0.),
+                    window,
+                    cx,
+                );
+            }
+        }
+
+        // Paint the sticky headers over the text and the line numbers.
+        if !prepaint.sticky_headers.is_empty() {
+            let line_number_width = prepaint.last_layout.line_number_width;
+            for (ix, (_, line_number, line)) in prepaint.sticky_headers.iter().enumerate() {
+                let top = input_bounds.origin.y + line_height * ix as f32;
+                window.paint_quad(fill(
+                    Bounds::new(
+                        point(input_bounds.origin.x, top),
+                        size(input_bounds.size.width, line_height),
+                    ),
+                    cx.theme().background,
+                ));
+                _ = line.paint(
+                    point(origin.x + line_number_width, top),
+                    line_height,
+                    TextAlign::Left,
+                    None,
+                    window,
+                    cx,
+                );
+
+                if let Some(line_number) = line_number {
+                    window.paint_quad(fill(
+                        Bounds::new(
+                            point(input_bounds.origin.x, top),
+                            size(line_number_width - LINE_NUMBER_RIGHT_MARGIN, line_height),
+                        ),
+                        cx.theme().background,
+                    ));
+                    _ = line_number.paint(
+                        point(input_bounds.origin.x, top),
+                        line_height,
+                        TextAlign::Left,
+                        None,
+                        window,
+                        cx,
+                    );
+                }
             }
+
+            let bottom = input_bounds.origin.y + line_height * prepaint.sticky_headers.len() as f32;
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(input_bounds.origin.x, bottom),
+                    size(input_bounds.size.width, px(1.)),
+                ),
+                cx.theme().border,
+            ));
         }
 
         self.state.update(cx, |state, cx| {
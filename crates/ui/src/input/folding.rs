@@ -0,0 +1,216 @@
+use std::ops::Range;
+
+use gpui::{Context, Pixels, Point, Window};
+use rope::Rope;
+
+use crate::highlighter::{indent_guides, shift_fold_ranges, FoldRange};
+use crate::input::RopeExt as _;
+
+use super::{
+    element::{FOLD_GUTTER_WIDTH, FOLD_PLACEHOLDER_WIDTH, LINE_NUMBER_RIGHT_MARGIN},
+    Fold, FoldAll, InputState, Unfold, UnfoldAll,
+};
+
+impl InputState {
+    /// Update the foldable ranges and the indentation guides, if the text has changed.
+    ///
+    /// The folded ranges are kept if they can still be folded.
+    pub(super) fn update_fold_ranges(&mut self) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+        if let Some(text) = &self.fold_ranges_text {
+            if text.eq(&self.text) {
+                return;
+            }
+        }
+
+        self.fold_ranges_text = Some(self.text.clone());
+        self.fold_ranges = self.mode.fold_ranges(&self.text);
+        self.indent_guides = if self.mode.indent_guides() {
+            let tab_size = self.mode.tab_size().map_or(2, |tab| tab.tab_size);
+            indent_guides(&self.text, &self.fold_ranges, tab_size)
+        } else {
+            vec![]
+        };
+
+        let folded = std::mem::take(&mut self.folded);
+        self.folded = self
+            .fold_ranges
+            .iter()
+            .filter(|fold| folded.iter().any(|f| f.start_row == fold.start_row))
+            .copied()
+            .collect();
+        self.update_hidden_rows();
+    }
+
+    /// Shift the folded ranges by the edit of `range` in the `old_text`.
+    pub(super) fn shift_folds(&mut self, old_text: &Rope, range: &Range<usize>, new_text: &str) {
+        if self.folded.is_empty() {
+            return;
+        }
+
+        let start_row = old_text.offset_to_point(range.start).row as usize;
+        let old_end_row = old_text.offset_to_point(range.end).row as usize;
+        let new_end_row = start_row + new_text.matches('\n').count();
+        self.folded = shift_fold_ranges(&self.folded, start_row, old_end_row, new_end_row);
+        self.update_hidden_rows();
+    }
+
+    fn update_hidden_rows(&mut self) {
+        self.text_wrapper
+            .set_hidden_rows(self.folded.iter().map(|fold| fold.hidden_rows()).collect());
+    }
+
+    /// Return the fold range starts at the row, if the row can be folded.
+    pub(super) fn fold_range_at_row(&self, row: usize) -> Option<&FoldRange> {
+        self.fold_ranges.iter().find(|fold| fold.start_row == row)
+    }
+
+    /// Return true if the fold range starts at the row is folded.
+    pub(super) fn is_folded(&self, row: usize) -> bool {
+        self.folded.iter().any(|fold| fold.start_row == row)
+    }
+
+    /// Return the row of the fold chevron in the line number gutter, or the placeholder
+    /// of a folded region at the mouse position.
+    pub(super) fn fold_row_for_mouse_position(
+        &self,
+        position: Point<Pixels>,
+        window: &Window,
+        cx: &Context<Self>,
+    ) -> Option<usize> {
+        if !self.mode.folding() {
+            return None;
+        }
+        let last_layout = self.last_layout.as_ref()?;
+
+        let offset = self.index_for_mouse_position(position, window, cx);
+        let row = self.text.offset_to_point(offset).row as usize;
+
+        let chevron_right = last_layout.line_number_width - LINE_NUMBER_RIGHT_MARGIN;
+        let gutter_x = position.x - self.input_bounds.origin.x;
+        if self.mode.line_number()
+            && gutter_x >= chevron_right - FOLD_GUTTER_WIDTH
+            && gutter_x < chevron_right
+        {
+            return self.fold_range_at_row(row).map(|fold| fold.start_row);
+        }
+
+        if self.is_folded(row) {
+            let line_end_x = self.x_for_offset(self.text.line_end_offset(row))?;
+            let x = self.text_x_for_position(position);
+            if x > line_end_x && x < line_end_x + FOLD_PLACEHOLDER_WIDTH * 1.5 {
+                return Some(row);
+            }
+        }
+
+        None
+    }
+
+    /// Fold or unfold the fold range starts at the row.
+    pub(super) fn toggle_fold_at_row(
+        &mut self,
+        row: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.is_folded(row) {
+            self.folded.retain(|fold| fold.start_row != row);
+            self.update_hidden_rows();
+            cx.notify();
+        } else if let Some(fold) = self.fold_range_at_row(row).copied() {
+            self.fold_ranges_in(vec![fold], window, cx);
+        }
+    }
+
+    /// Unfold the folded ranges which hide the row.
+    pub(super) fn unfold_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        if !self.text_wrapper.is_hidden(row) {
+            return;
+        }
+
+        self.folded.retain(|fold| !fold.hides_row(row));
+        self.update_hidden_rows();
+        cx.notify();
+    }
+
+    fn fold_ranges_in(
+        &mut self,
+        folds: Vec<FoldRange>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        for fold in folds {
+            if !self.is_folded(fold.start_row) {
+                self.folded.push(fold);
+            }
+        }
+        self.folded.sort();
+        self.update_hidden_rows();
+
+        // Move the cursor out of the folded ranges.
+        let row = self.text.offset_to_point(self.cursor()).row as usize;
+        let start_row = self
+            .folded
+            .iter()
+            .filter(|fold| fold.hides_row(row))
+            .map(|fold| fold.start_row)
+            .min();
+        if let Some(start_row) = start_row {
+            self.move_to(self.text.line_end_offset(start_row), window, cx);
+        }
+        cx.notify();
+    }
+
+    /// Fold the innermost unfolded range contains the cursor.
+    pub(super) fn fold(&mut self, _: &Fold, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.mode.folding() {
+            return;
+        }
+
+        let row = self.text.offset_to_point(self.cursor()).row as usize;
+        let Some(fold) = self
+            .fold_ranges
+            .iter()
+            .filter(|fold| fold.start_row <= row && fold.end_row >= row)
+            .filter(|fold| !self.is_folded(fold.start_row))
+            .max_by_key(|fold| fold.start_row)
+            .copied()
+        else {
+            return;
+        };
+
+        self.fold_ranges_in(vec![fold], window, cx);
+    }
+
+    /// Unfold the folded ranges start at or contain the cursor line.
+    pub(super) fn unfold(&mut self, _: &Unfold, _: &mut Window, cx: &mut Context<Self>) {
+        let row = self.text.offset_to_point(self.cursor()).row as usize;
+        let len = self.folded.len();
+        self.folded
+            .retain(|fold| fold.start_row > row || fold.end_row < row);
+        if self.folded.len() != len {
+            self.update_hidden_rows();
+            cx.notify();
+        }
+    }
+
+    pub(super) fn fold_all(&mut self, _: &FoldAll, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.mode.folding() {
+            return;
+        }
+
+        self.fold_ranges_in(self.fold_ranges.clone(), window, cx);
+    }
+
+    pub(super) fn unfold_all(&mut self, _: &UnfoldAll, _: &mut Window, cx: &mut Context<Self>) {
+        if self.folded.is_empty() {
+            return;
+        }
+
+        self.folded.clear();
+        self.update_hidden_rows();
+        cx.notify();
+    }
+}
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+use gpui::{Context, Window};
+use rope::Rope;
+
+use crate::highlighter::{indent_range_at, BracketPair};
+use crate::input::RopeExt as _;
+
+use super::InputState;
+
+/// Return the leading whitespace of the line.
+fn line_indent(text: &Rope, row: usize) -> String {
+    text.line(row)
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+/// Return the indentation of a new line inserted at the `offset`, and the indentation of
+/// another new line to move the closing token after the cursor to.
+///
+/// - `indent_ranges` are the ranges of the indents query, `None` if the language has no
+///   indents query.
+/// - `tab` is the indentation to add.
+///
+/// Returns `None` to keep the indentation as usual.
+fn indent_for_new_line(
+    text: &Rope,
+    offset: usize,
+    indent_ranges: Option<&[Range<usize>]>,
+    pairs: &[BracketPair],
+    tab: &str,
+) -> Option<(String, Option<String>)> {
+    let row = text.offset_to_point(offset).row as usize;
+    let current_indent = line_indent(text, row);
+
+    let Some(indent_ranges) = indent_ranges else {
+        // Split the empty pair into lines, e.g.: `{|}`.
+        let prev_char = text.reversed_chars_at(offset).next();
+        let next_char = text.chars_at(offset).next();
+        let is_empty_pair = pairs.iter().any(|pair| {
+            !pair.is_quote() && Some(pair.start) == prev_char && Some(pair.end) == next_char
+        });
+        if is_empty_pair {
+            return Some((format!("{}{}", current_indent, tab), Some(current_indent)));
+        }
+
+        return None;
+    };
+
+    let Some(range) = indent_range_at(indent_ranges, offset) else {
+        return Some((current_indent, None));
+    };
+
+    let start_row = text.offset_to_point(range.start).row as usize;
+    let base_indent = line_indent(text, start_row);
+    let indent = format!("{}{}", base_indent, tab);
+
+    // The closing token is the first token after the cursor.
+    let rest = text.slice(offset..text.line_end_offset(row)).to_string();
+    let rest_start = offset + rest.len() - rest.trim_start().len();
+    if rest_start == range.end {
+        if start_row == row {
+            return Some((indent, Some(base_indent)));
+        }
+
+        return Some((base_indent, None));
+    }
+
+    Some((indent, None))
+}
+
+impl InputState {
+    /// Insert a new line at the cursor, with the indentation by the indents query of the language.
+    pub(super) fn auto_indent_new_line(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let tab = self
+            .mode
+            .tab_size()
+            .map(|tab| tab.to_string())
+            .unwrap_or_default();
+        let range: Range<usize> = self.selected_range.into();
+        let indent_ranges = self
+            .mode
+            .indent_ranges(range.start.saturating_sub(1)..range.end + 1);
+
+        let new_line = indent_for_new_line(
+            &self.text,
+            range.start,
+            indent_ranges.as_deref(),
+            &self.mode.brackets(),
+            &tab,
+        );
+        let Some((indent, closing_indent)) = new_line else {
+            let indent = self.indent_of_next_line();
+            self.replace_text_in_range(None, &format!("\n{}", indent), window, cx);
+            return;
+        };
+
+        let mut new_text = format!("\n{}", indent);
+        let cursor = range.start + new_text.len();
+        if let Some(closing_indent) = closing_indent {
+            new_text.push_str(&format!("\n{}", closing_indent));
+        }
+
+        self.replace_text_in_range(None, &new_text, window, cx);
+        self.selected_range = (cursor..cursor).into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highlighter::default_brackets;
+
+    #[test]
+    fn test_indent_for_new_line() {
+        let pairs = default_brackets();
+        let text = Rope::from("fn a() {\n    if b {}\n    c(d, e)\n}");
+        // The indent ranges of `{`..`}`, `{`..`}` and `(`..`)`.
+        let ranges = vec![7..33, 18..19, 26..31];
+        let new_line =
+            |offset: usize| indent_for_new_line(&text, offset, Some(&ranges[..]), &pairs, "    ");
+        let indent = |indent: &str, closing: Option<&str>| {
+            Some((indent.to_string(), closing.map(|s| s.to_string())))
+        };
+
+        // `fn a() {|`
+        assert_eq!(new_line(8), indent("    ", None));
+        // `    if b {|}`
+        assert_eq!(new_line(19), indent("        ", Some("    ")));
+        // `    c(d, e|)`
+        assert_eq!(new_line(31), indent("        ", Some("    ")));
+        // `    c(d, e)|`
+        assert_eq!(new_line(32), indent("    ", None));
+        // `|fn a() {`
+        assert_eq!(new_line(0), indent("", None));
+        // `|}`
+        assert_eq!(new_line(33), indent("", None));
+
+        // Without indents query
+        let new_line = |offset: usize| indent_for_new_line(&text, offset, None, &pairs, "    ");
+        assert_eq!(new_line(19), indent("        ", Some("    ")));
+        assert_eq!(new_line(8), None);
+    }
+}
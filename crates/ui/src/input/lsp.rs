@@ -1,17 +1,28 @@
-use std::{cell::RefCell, ops::Range, rc::Rc};
+use std::{cell::RefCell, ops::Range, rc::Rc, time::Duration};
 
 use anyhow::Result;
 use gpui::{App, Context, Entity, EntityInputHandler, SharedString, Task, Window};
 use lsp_types::{
-    request::Completion, CodeAction, CompletionContext, CompletionItem, CompletionResponse,
+    request::Completion, CodeAction, CompletionContext, CompletionItem, CompletionResponse, Hover,
+    Location, SemanticTokens, SemanticTokensLegend, SignatureHelp, SignatureHelpContext,
+    SignatureHelpTriggerKind,
 };
 use rope::Rope;
 
+use crate::highlighter::SemanticTokenSet;
 use crate::input::{
-    popovers::{CodeActionItem, CodeActionMenu, CompletionMenu, ContextMenu},
-    InputState, RopeExt,
+    popovers::{
+        CodeActionItem, CodeActionMenu, CompletionMenu, ContextMenu, HoverPopover,
+        SignatureHelpPopover,
+    },
+    InputEvent, InputState, RopeExt,
 };
 
+/// The delay of the mouse resting on a symbol before requesting its hover.
+const HOVER_DELAY: Duration = Duration::from_millis(300);
+/// The delay after the last change before requesting the semantic tokens.
+const SEMANTIC_TOKENS_DELAY: Duration = Duration::from_millis(300);
+
 /// A trait for providing code completions based on the current input state and context.
 pub trait CompletionProvider {
     /// Fetches completions based on the given byte offset.
@@ -68,6 +79,73 @@ pub trait CodeActionProvider {
     ) -> Task<Result<()>>;
 }
 
+/// A trait for providing the hover documentation of the symbol under the mouse.
+pub trait HoverProvider {
+    /// Fetches the hover information for the given byte offset.
+    fn hover(
+        &self,
+        text: &Rope,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Option<Hover>>>;
+}
+
+/// A trait for providing the definitions of a symbol, used by go-to-definition.
+pub trait DefinitionProvider {
+    /// Fetches the definitions of the symbol at the given byte offset.
+    fn definitions(
+        &self,
+        text: &Rope,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Vec<Location>>>;
+}
+
+/// A trait for providing the signatures of the function being called at the cursor.
+pub trait SignatureHelpProvider {
+    /// Fetches the signature help for the given byte offset.
+    ///
+    /// The [`lsp_types::ParameterLabel::LabelOffsets`] of the parameters are byte offsets in the
+    /// label of the signature.
+    fn signature_help(
+        &self,
+        text: &Rope,
+        offset: usize,
+        trigger: SignatureHelpContext,
+        window: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Option<SignatureHelp>>>;
+
+    /// Determines if the signature help should be triggered by the inserted text.
+    ///
+    /// Default is triggered by `(` and `,`.
+    fn is_signature_help_trigger(
+        &self,
+        _offset: usize,
+        new_text: &str,
+        _cx: &mut Context<InputState>,
+    ) -> bool {
+        new_text.ends_with('(') || new_text.ends_with(',')
+    }
+}
+
+/// A trait for providing the semantic tokens of the text, to override the Tree-sitter
+/// highlighting with the styles of the token types.
+pub trait SemanticTokensProvider {
+    /// The legend to decode the token types and modifiers.
+    fn legend(&self) -> SemanticTokensLegend;
+
+    /// Fetches the semantic tokens of the whole text.
+    fn semantic_tokens(
+        &self,
+        text: &Rope,
+        window: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Option<SemanticTokens>>>;
+}
+
 impl InputState {
     pub(crate) fn hide_context_menu(&mut self, cx: &mut Context<Self>) {
         self.context_menu = None;
@@ -321,4 +399,444 @@ impl InputState {
             self.replace_text_in_range(Some(range_utf16), &edit.new_text, window, cx);
         }
     }
+
+    /// Show the hover of the symbol at the offset, once the mouse rests on it.
+    pub(super) fn handle_hover_trigger(
+        &mut self,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(provider) = self.mode.hover_provider().cloned() else {
+            return;
+        };
+
+        if let Some(hover_popover) = self.hover_popover.as_ref() {
+            if hover_popover.read(cx).range.contains(&offset) {
+                hover_popover.update(cx, |this, cx| this.show(cx));
+                return;
+            }
+        }
+
+        if !self.text.char_at(offset).is_some_and(is_word_char) {
+            self._hover_task = Task::ready(Ok(()));
+            return;
+        }
+
+        self._hover_task = cx.spawn_in(window, async move |editor, cx| {
+            cx.background_executor().timer(HOVER_DELAY).await;
+
+            let task = editor.update_in(cx, |editor, window, cx| {
+                provider.hover(&editor.text, offset, window, cx)
+            })?;
+            let Some(hover) = task.await? else {
+                return Ok(());
+            };
+
+            editor.update(cx, |editor, cx| {
+                let range = match hover.range {
+                    Some(range) => {
+                        editor.text.position_to_offset(&range.start)
+                            ..editor.text.position_to_offset(&range.end)
+                    }
+                    None => editor.word_range(offset),
+                };
+
+                editor.hover_popover =
+                    Some(HoverPopover::new(range, &hover.contents, cx.entity(), cx));
+                cx.notify();
+            })
+        });
+    }
+
+    /// Request the definitions of the symbol at the offset, and emit them with
+    /// [`InputEvent::GoToDefinition`].
+    pub(super) fn handle_definition_trigger(
+        &mut self,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(provider) = self.mode.definition_provider().cloned() else {
+            return;
+        };
+
+        let task = provider.definitions(&self.text, offset, window, cx);
+        cx.spawn_in(window, async move |editor, cx| {
+            let locations = task.await?;
+            if locations.is_empty() {
+                return Ok(());
+            }
+
+            editor.update(cx, |_, cx| {
+                cx.emit(InputEvent::GoToDefinition { locations });
+            })
+        })
+        .detach();
+    }
+
+    /// Show or update the signature help after the text is changed.
+    ///
+    /// The signature help is requested when the inserted text is a trigger, and
+    /// on every change while it is open, so that the provider can close it.
+    pub(super) fn handle_signature_help_trigger(
+        &mut self,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(provider) = self.mode.signature_help_provider().cloned() else {
+            return;
+        };
+
+        let offset = self.cursor();
+        let active_signature_help = self
+            .signature_help_popover
+            .as_ref()
+            .map(|popover| popover.read(cx).help.clone());
+        let is_trigger = provider.is_signature_help_trigger(offset, new_text, cx);
+        if !is_trigger && active_signature_help.is_none() {
+            return;
+        }
+
+        let context = SignatureHelpContext {
+            trigger_kind: if is_trigger {
+                SignatureHelpTriggerKind::TRIGGER_CHARACTER
+            } else {
+                SignatureHelpTriggerKind::CONTENT_CHANGE
+            },
+            trigger_character: new_text
+                .chars()
+                .last()
+                .filter(|_| is_trigger)
+                .map(|c| c.to_string()),
+            is_retrigger: active_signature_help.is_some(),
+            active_signature_help,
+        };
+
+        let task = provider.signature_help(&self.text, offset, context, window, cx);
+        self._signature_help_task = cx.spawn_in(window, async move |editor, cx| {
+            let help = task.await?.filter(|help| !help.signatures.is_empty());
+
+            editor.update(cx, |editor, cx| {
+                match (help, editor.signature_help_popover.as_ref()) {
+                    (Some(help), Some(popover)) => popover.update(cx, |popover, cx| {
+                        popover.help = help;
+                        cx.notify();
+                    }),
+                    (Some(help), None) => {
+                        editor.signature_help_popover =
+                            Some(SignatureHelpPopover::new(help, cx.entity(), cx));
+                    }
+                    (None, _) => editor.signature_help_popover = None,
+                }
+                cx.notify();
+            })
+        });
+    }
+
+    /// Request the semantic tokens of the text after [`SEMANTIC_TOKENS_DELAY`], the pending
+    /// request is canceled.
+    ///
+    /// This is called on every change, the tokens are shifted by the edits until the new tokens
+    /// are received.
+    pub fn refresh_semantic_tokens(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(provider) = self.mode.semantic_tokens_provider().cloned() else {
+            return;
+        };
+
+        self._semantic_tokens_task = cx.spawn_in(window, async move |editor, cx| {
+            cx.background_executor().timer(SEMANTIC_TOKENS_DELAY).await;
+
+            let task = editor.update_in(cx, |editor, window, cx| {
+                provider.semantic_tokens(&editor.text, window, cx)
+            })?;
+            let Some(tokens) = task.await? else {
+                return Ok(());
+            };
+
+            editor.update(cx, |editor, cx| {
+                let set = SemanticTokenSet::from_lsp(&editor.text, &tokens, &provider.legend());
+                if let Some(semantic_tokens) = editor.mode.semantic_tokens_mut() {
+                    *semantic_tokens = set;
+                }
+                cx.notify();
+            })
+        });
+    }
+
+    /// Hide the signature help, return true if it was open.
+    pub(super) fn hide_signature_help(&mut self, cx: &mut Context<Self>) -> bool {
+        self._signature_help_task = Task::ready(Ok(()));
+        if self.signature_help_popover.take().is_none() {
+            return false;
+        }
+
+        cx.notify();
+        true
+    }
+
+    /// Return the range of the word at the offset.
+    fn word_range(&self, offset: usize) -> Range<usize> {
+        let start = offset
+            - self
+                .text
+                .reversed_chars_at(offset)
+                .take_while(|c| is_word_char(*c))
+                .map(char::len_utf8)
+                .sum::<usize>();
+        let end = offset
+            + self
+                .text
+                .chars_at(offset)
+                .take_while(|c| is_word_char(*c))
+                .map(char::len_utf8)
+                .sum::<usize>();
+
+        start..end
+    }
+}
+
+#[inline]
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{
+        point, px, AppContext as _, Focusable as _, IntoElement, Modifiers, Render, TestAppContext,
+        VisualTestContext,
+    };
+    use lsp_types::{HoverContents, MarkedString, Position, SignatureInformation};
+
+    use super::*;
+    use crate::input::TextInput;
+
+    /// A provider of the hovers, definitions and signature helps, recording the requests.
+    #[derive(Default)]
+    struct FakeProvider {
+        requests: RefCell<Vec<(&'static str, usize)>>,
+    }
+
+    impl HoverProvider for FakeProvider {
+        fn hover(
+            &self,
+            _: &Rope,
+            offset: usize,
+            _: &mut Window,
+            _: &mut Context<InputState>,
+        ) -> Task<Result<Option<Hover>>> {
+            self.requests.borrow_mut().push(("hover", offset));
+            Task::ready(Ok(Some(Hover {
+                contents: HoverContents::Scalar(MarkedString::String("fn foo()".into())),
+                range: None,
+            })))
+        }
+    }
+
+    impl DefinitionProvider for FakeProvider {
+        fn definitions(
+            &self,
+            _: &Rope,
+            offset: usize,
+            _: &mut Window,
+            _: &mut Context<InputState>,
+        ) -> Task<Result<Vec<Location>>> {
+            self.requests.borrow_mut().push(("definitions", offset));
+            let location = Location::new(
+                "file:///main.rs".parse().unwrap(),
+                lsp_types::Range::new(Position::new(0, 0), Position::new(0, 3)),
+            );
+            Task::ready(Ok(vec![location]))
+        }
+    }
+
+    impl SignatureHelpProvider for FakeProvider {
+        /// The signature help is closed after `)`.
+        fn signature_help(
+            &self,
+            text: &Rope,
+            offset: usize,
+            _: SignatureHelpContext,
+            _: &mut Window,
+            _: &mut Context<InputState>,
+        ) -> Task<Result<Option<SignatureHelp>>> {
+            self.requests.borrow_mut().push(("signature_help", offset));
+            if text.reversed_chars_at(offset).next() == Some(')') {
+                return Task::ready(Ok(None));
+            }
+
+            Task::ready(Ok(Some(SignatureHelp {
+                signatures: vec![SignatureInformation {
+                    label: "fn foo(a: i32)".into(),
+                    documentation: None,
+                    parameters: None,
+                    active_parameter: None,
+                }],
+                active_signature: None,
+                active_parameter: None,
+            })))
+        }
+    }
+
+    struct TestEditor {
+        state: Entity<InputState>,
+    }
+
+    impl Render for TestEditor {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            TextInput::new(&self.state)
+        }
+    }
+
+    fn build_editor(
+        cx: &mut TestAppContext,
+    ) -> (Entity<InputState>, Rc<FakeProvider>, &mut VisualTestContext) {
+        cx.update(crate::init);
+        let provider = Rc::new(FakeProvider::default());
+        let (editor, cx) = cx.add_window_view({
+            let provider = provider.clone();
+            move |window, cx| {
+                let state = cx.new(|cx| {
+                    let mut state = InputState::new(window, cx).code_editor("rust");
+                    state.set_value("foo(1);\nbar", window, cx);
+                    state.set_hover_provider(Some(provider.clone() as Rc<dyn HoverProvider>), cx);
+                    state.set_definition_provider(
+                        Some(provider.clone() as Rc<dyn DefinitionProvider>),
+                        cx,
+                    );
+                    state.set_signature_help_provider(
+                        Some(provider.clone() as Rc<dyn SignatureHelpProvider>),
+                        cx,
+                    );
+                    state
+                });
+                TestEditor { state }
+            }
+        });
+        let state = editor.read_with(cx, |editor, _| editor.state.clone());
+        cx.update(|window, cx| state.focus_handle(cx).focus(window));
+        cx.run_until_parked();
+        (state, provider, cx)
+    }
+
+    fn requests(provider: &FakeProvider) -> Vec<(&'static str, usize)> {
+        provider.requests.borrow_mut().drain(..).collect()
+    }
+
+    #[gpui::test]
+    fn test_hover_delay(cx: &mut TestAppContext) {
+        let (state, provider, cx) = build_editor(cx);
+
+        state.update_in(cx, |state, window, cx| {
+            state.handle_hover_trigger(1, window, cx)
+        });
+        cx.executor().advance_clock(HOVER_DELAY / 2);
+        cx.run_until_parked();
+        assert!(requests(&provider).is_empty());
+
+        // Moving the mouse restarts the delay.
+        state.update_in(cx, |state, window, cx| {
+            state.handle_hover_trigger(2, window, cx)
+        });
+        cx.executor().advance_clock(HOVER_DELAY / 2);
+        cx.run_until_parked();
+        assert!(requests(&provider).is_empty());
+
+        cx.executor().advance_clock(HOVER_DELAY / 2);
+        cx.run_until_parked();
+        assert_eq!(requests(&provider), vec![("hover", 2)]);
+        let range = state.read_with(cx, |state, cx| {
+            state
+                .hover_popover
+                .as_ref()
+                .map(|popover| popover.read(cx).range.clone())
+        });
+        assert_eq!(range, Some(0..3));
+
+        // Not on a word.
+        state.update_in(cx, |state, window, cx| {
+            state.handle_hover_trigger(3, window, cx)
+        });
+        cx.executor().advance_clock(HOVER_DELAY * 2);
+        cx.run_until_parked();
+        assert!(requests(&provider).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_go_to_definition(cx: &mut TestAppContext) {
+        let (state, provider, cx) = build_editor(cx);
+        let events = Rc::new(RefCell::new(vec![]));
+        cx.update(|_, cx| {
+            let events = events.clone();
+            cx.subscribe(&state, move |_, event: &InputEvent, _| {
+                if let InputEvent::GoToDefinition { locations } = event {
+                    events.borrow_mut().push(locations.clone());
+                }
+            })
+            .detach();
+        });
+
+        state.update_in(cx, |state, window, cx| state.move_to(9, window, cx));
+        cx.simulate_keystrokes("f12");
+        cx.run_until_parked();
+        assert_eq!(requests(&provider), vec![("definitions", 9)]);
+        assert_eq!(events.borrow().len(), 1);
+        assert_eq!(events.borrow()[0][0].range.end, Position::new(0, 3));
+
+        // Secondary-click on the first character.
+        let position = state.read_with(cx, |state, _| {
+            let bounds = state.last_bounds.expect("the editor is painted");
+            let layout = state.last_layout.as_ref().expect("the editor is painted");
+            bounds.origin + point(layout.line_number_width + px(1.), layout.line_height / 2.)
+        });
+        cx.simulate_click(position, Modifiers::secondary_key());
+        cx.run_until_parked();
+        assert_eq!(requests(&provider), vec![("definitions", 0)]);
+        assert_eq!(state.read_with(cx, |state, _| state.cursor()), 0);
+        assert_eq!(events.borrow().len(), 2);
+
+        // A click without the modifier only moves the cursor.
+        cx.simulate_click(position, Modifiers::none());
+        cx.run_until_parked();
+        assert!(requests(&provider).is_empty());
+        assert_eq!(events.borrow().len(), 2);
+    }
+
+    #[gpui::test]
+    fn test_signature_help_trigger(cx: &mut TestAppContext) {
+        let (state, provider, cx) = build_editor(cx);
+        let insert = |text: &str, cx: &mut VisualTestContext| {
+            state.update_in(cx, |state, window, cx| {
+                state.replace_text_in_range(None, text, window, cx)
+            });
+            cx.run_until_parked();
+        };
+        let is_open = |cx: &mut VisualTestContext| {
+            state.read_with(cx, |state, _| state.signature_help_popover.is_some())
+        };
+
+        state.update_in(cx, |state, window, cx| state.move_to(11, window, cx));
+        insert("x", cx);
+        assert!(requests(&provider).is_empty());
+        assert!(!is_open(cx));
+
+        insert("(", cx);
+        assert_eq!(requests(&provider), vec![("signature_help", 13)]);
+        assert!(is_open(cx));
+
+        // Updated on every change while open.
+        insert("1", cx);
+        assert_eq!(requests(&provider), vec![("signature_help", 14)]);
+        assert!(is_open(cx));
+
+        // Closed when the provider has no signature.
+        insert(")", cx);
+        assert_eq!(requests(&provider), vec![("signature_help", 15)]);
+        assert!(!is_open(cx));
+
+        insert(";", cx);
+        assert!(requests(&provider).is_empty());
+    }
 }
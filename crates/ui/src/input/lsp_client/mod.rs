@@ -0,0 +1,1234 @@
+mod rpc;
+
+pub use rpc::*;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ops::Range,
+    rc::{Rc, Weak},
+};
+
+use anyhow::Result;
+use gpui::{
+    AnyWindowHandle, App, Context, Entity, EntityId, SharedString, Subscription, Task, WeakEntity,
+    Window,
+};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Initialized,
+        PublishDiagnostics,
+    },
+    request::{
+        ApplyWorkspaceEdit, CodeActionRequest, CodeActionResolveRequest, Completion,
+        ExecuteCommand, GotoDefinition, HoverRequest, Initialize, SemanticTokensFullRequest,
+        Shutdown, SignatureHelpRequest,
+    },
+    ApplyWorkspaceEditResponse, ClientCapabilities, CodeAction, CodeActionCapabilityResolveSupport,
+    CodeActionClientCapabilities, CodeActionContext, CodeActionKindLiteralSupport,
+    CodeActionLiteralSupport, CodeActionOptions, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, CodeActionTriggerKind, CompletionClientCapabilities,
+    CompletionContext, CompletionParams, CompletionResponse, CompletionTriggerKind,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentChangeOperation, DocumentChanges, ExecuteCommandParams, GeneralClientCapabilities,
+    GotoCapability, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverClientCapabilities,
+    HoverParams, HoverProviderCapability, InitializeParams, InitializedParams, Location,
+    MarkupKind, OneOf, ParameterInformationSettings, ParameterLabel, Position,
+    PositionEncodingKind, PublishDiagnosticsClientCapabilities, PublishDiagnosticsParams,
+    SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensClientCapabilities,
+    SemanticTokensClientCapabilitiesRequests, SemanticTokensFullOptions, SemanticTokensLegend,
+    SemanticTokensParams, SemanticTokensResult, SemanticTokensServerCapabilities,
+    ServerCapabilities, SignatureHelp, SignatureHelpClientCapabilities, SignatureHelpContext,
+    SignatureHelpParams, SignatureInformationSettings, TextDocumentClientCapabilities,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncClientCapabilities,
+    TextDocumentSyncKind, TextEdit, TokenFormat, Uri, VersionedTextDocumentIdentifier,
+    WorkspaceEdit, WorkspaceFolder,
+};
+use rope::Rope;
+use sum_tree::Bias;
+
+use crate::input::{
+    CodeActionProvider, CompletionProvider, DefinitionProvider, HoverProvider, InputEvent,
+    InputState, RopeExt as _, SemanticTokensProvider, SignatureHelpProvider,
+};
+
+/// A document opened in the language server.
+struct Document {
+    uri: Uri,
+    version: i32,
+    /// The text as last sent to the server.
+    text: Rope,
+    /// The last diagnostics published by the server.
+    diagnostics: Vec<lsp_types::Diagnostic>,
+    state: WeakEntity<InputState>,
+    window: AnyWindowHandle,
+    _subscription: Subscription,
+}
+
+struct ClientState {
+    id: SharedString,
+    connection: LspConnection,
+    capabilities: ServerCapabilities,
+    /// The position encoding accepted by the server.
+    encoding: PositionEncodingKind,
+    documents: HashMap<EntityId, Document>,
+    _tasks: Vec<Task<()>>,
+}
+
+/// A language server client, speaking LSP over the stdio of a server process.
+///
+/// Open an [`InputState`] in code editor mode with [`LspClient::open`] to keep
+/// the server in sync with its text. The client registers itself as the
+/// [`CompletionProvider`], [`HoverProvider`], [`DefinitionProvider`],
+/// [`SignatureHelpProvider`], [`SemanticTokensProvider`] and a [`CodeActionProvider`]
+/// of the editor, for the capabilities of the server, and shows the diagnostics
+/// published by the server.
+///
+/// The editor counts positions in characters, so the client asks for the `utf-32`
+/// position encoding, and converts the positions from and to the `utf-16` code units
+/// (or the `utf-8` bytes) the server uses otherwise.
+///
+/// ```ignore
+/// let command = std::process::Command::new("rust-analyzer");
+/// let client = LspClient::start(command, Some(root_uri), cx).await?;
+/// client.open(&editor, uri, "rust", window, cx)?;
+/// ```
+#[derive(Clone)]
+pub struct LspClient {
+    state: Rc<RefCell<ClientState>>,
+}
+
+impl LspClient {
+    /// Spawn the language server `command` and initialize it.
+    pub fn start(
+        command: std::process::Command,
+        root_uri: Option<Uri>,
+        cx: &mut App,
+    ) -> Task<Result<Self>> {
+        let id = SharedString::from(format!("lsp:{}", command.get_program().to_string_lossy()));
+        let spawned = LspConnection::spawn(command);
+
+        cx.spawn(async move |cx| {
+            let (connection, messages, io) = spawned?;
+            let io_task = cx.background_executor().spawn(async move {
+                if let Err(err) = io.await {
+                    tracing::error!("language server connection failed: {:?}", err);
+                }
+            });
+
+            let result = connection
+                .request::<Initialize>(initialize_params(root_uri))
+                .await?;
+            connection.notify::<Initialized>(InitializedParams {})?;
+
+            // Servers that don't pick one of the offered encodings use `utf-16`.
+            let encoding = result
+                .capabilities
+                .position_encoding
+                .clone()
+                .unwrap_or(PositionEncodingKind::UTF16);
+            let state = Rc::new(RefCell::new(ClientState {
+                id,
+                connection,
+                capabilities: result.capabilities,
+                encoding,
+                documents: HashMap::new(),
+                _tasks: vec![],
+            }));
+
+            let this = Rc::downgrade(&state);
+            let message_task = cx.spawn(async move |cx| {
+                while let Ok(message) = messages.recv().await {
+                    let Some(client) = LspClient::upgrade(&this) else {
+                        break;
+                    };
+                    _ = cx.update(|cx| client.handle_message(message, cx));
+                }
+            });
+            state.borrow_mut()._tasks = vec![io_task, message_task];
+
+            Ok(Self { state })
+        })
+    }
+
+    fn upgrade(state: &Weak<RefCell<ClientState>>) -> Option<Self> {
+        state.upgrade().map(|state| Self { state })
+    }
+
+    /// Return the capabilities of the language server.
+    pub fn capabilities(&self) -> ServerCapabilities {
+        self.state.borrow().capabilities.clone()
+    }
+
+    /// Return the position encoding accepted by the language server.
+    pub fn position_encoding(&self) -> PositionEncodingKind {
+        self.state.borrow().encoding.clone()
+    }
+
+    /// Return the connection to the language server, to send other requests.
+    pub fn connection(&self) -> LspConnection {
+        self.state.borrow().connection.clone()
+    }
+
+    /// Open the text of a code editor as the document `uri` in the language server.
+    ///
+    /// The edits of the editor are sent to the server, and the client is set as
+    /// the providers of the editor.
+    pub fn open(
+        &self,
+        state: &Entity<InputState>,
+        uri: Uri,
+        language_id: impl Into<String>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<()> {
+        let text = state.read(cx).text.clone();
+        self.state
+            .borrow()
+            .connection
+            .notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: language_id.into(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            })?;
+
+        let this = Rc::downgrade(&self.state);
+        let _subscription = cx.subscribe(state, move |state, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                let Some(client) = LspClient::upgrade(&this) else {
+                    return;
+                };
+                if let Err(err) = client.sync(state.entity_id(), state.read(cx).text()) {
+                    tracing::error!("failed to sync document: {:?}", err);
+                }
+            }
+        });
+
+        self.state.borrow_mut().documents.insert(
+            state.entity_id(),
+            Document {
+                uri,
+                version: 0,
+                text,
+                diagnostics: vec![],
+                state: state.downgrade(),
+                window: window.window_handle(),
+                _subscription,
+            },
+        );
+
+        let capabilities = self.capabilities();
+        state.update(cx, |state, cx| {
+            if capabilities.completion_provider.is_some() {
+                state.set_completion_provider(Some(Rc::new(self.clone())), cx);
+            }
+            if code_actions_enabled(&capabilities) {
+                let id = self.id();
+                state.remove_code_action_provider(&id, cx);
+                state.add_code_action_provider(Rc::new(self.clone()), cx);
+            }
+            if hover_enabled(&capabilities) {
+                state.set_hover_provider(Some(Rc::new(self.clone())), cx);
+            }
+            if definition_enabled(&capabilities) {
+                state.set_definition_provider(Some(Rc::new(self.clone())), cx);
+            }
+            if capabilities.signature_help_provider.is_some() {
+                state.set_signature_help_provider(Some(Rc::new(self.clone())), cx);
+            }
+            if semantic_tokens_legend(&capabilities).is_some() {
+                state.set_semantic_tokens_provider(Some(Rc::new(self.clone())), cx);
+                state.refresh_semantic_tokens(window, cx);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Close the document of a code editor, and remove the client from its providers.
+    pub fn close(&self, state: &Entity<InputState>, cx: &mut App) -> Result<()> {
+        let Some(document) = self.state.borrow_mut().documents.remove(&state.entity_id()) else {
+            return Ok(());
+        };
+
+        let id = self.id();
+        state.update(cx, |state, cx| {
+            state.set_completion_provider(None, cx);
+            state.set_hover_provider(None, cx);
+            state.set_definition_provider(None, cx);
+            state.set_signature_help_provider(None, cx);
+            state.set_semantic_tokens_provider(None, cx);
+            state.remove_code_action_provider(&id, cx);
+            if let Some(diagnostics) = state.diagnostics_mut() {
+                diagnostics.clear();
+            }
+        });
+
+        self.state
+            .borrow()
+            .connection
+            .notify::<DidCloseTextDocument>(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier::new(document.uri),
+            })
+    }
+
+    /// Ask the language server to shut down, and then to exit.
+    pub fn shutdown(&self, cx: &App) -> Task<Result<()>> {
+        let connection = self.connection();
+        let request = connection.request::<Shutdown>(());
+        cx.background_executor().spawn(async move {
+            request.await?;
+            connection.notify::<Exit>(())
+        })
+    }
+
+    /// Send the changes of the text since the last sync to the language server.
+    fn sync(&self, entity_id: EntityId, text: &Rope) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let sync_kind = sync_kind(&state.capabilities);
+        let Some(document) = state.documents.get_mut(&entity_id) else {
+            return Ok(());
+        };
+
+        let old_text = document.text.to_string();
+        let new_text = text.to_string();
+        let (old_range, new_range) = text_diff(&old_text, &new_text);
+        if old_range.is_empty() && new_range.is_empty() {
+            return Ok(());
+        }
+
+        let change = if sync_kind == TextDocumentSyncKind::INCREMENTAL {
+            TextDocumentContentChangeEvent {
+                range: Some(lsp_types::Range::new(
+                    lsp_position(&document.text, old_range.start, &state.encoding),
+                    lsp_position(&document.text, old_range.end, &state.encoding),
+                )),
+                range_length: None,
+                text: new_text[new_range].to_string(),
+            }
+        } else {
+            TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: new_text,
+            }
+        };
+
+        document.text = text.clone();
+        document.version += 1;
+        if sync_kind == TextDocumentSyncKind::NONE {
+            return Ok(());
+        }
+
+        state
+            .connection
+            .notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(
+                    document.uri.clone(),
+                    document.version,
+                ),
+                content_changes: vec![change],
+            })
+    }
+
+    fn handle_message(&self, message: LspMessage, cx: &mut App) {
+        if let Some(params) = message.notification::<PublishDiagnostics>() {
+            self.publish_diagnostics(params, cx);
+            return;
+        }
+
+        let Some(id) = message.id.clone() else {
+            return;
+        };
+
+        let result = match message.request::<ApplyWorkspaceEdit>() {
+            Some(params) => {
+                let applied = self.apply_workspace_edit(&params.edit, cx);
+                serde_json::to_value(ApplyWorkspaceEditResponse {
+                    applied,
+                    failure_reason: None,
+                    failed_change: None,
+                })
+                .unwrap_or_default()
+            }
+            None => message.default_result(),
+        };
+
+        if let Err(err) = self.state.borrow().connection.respond(id, result) {
+            tracing::error!("failed to respond to `{}`: {:?}", message.method, err);
+        }
+    }
+
+    fn publish_diagnostics(&self, params: PublishDiagnosticsParams, cx: &mut App) {
+        let mut state = self.state.borrow_mut();
+        let Some(document) = state
+            .documents
+            .values_mut()
+            .find(|document| document.uri == params.uri)
+        else {
+            return;
+        };
+
+        // Ignore the diagnostics of an outdated version of the document.
+        if params
+            .version
+            .is_some_and(|version| version != document.version)
+        {
+            return;
+        }
+
+        document.diagnostics = params.diagnostics.clone();
+        let text = document.text.clone();
+        let Some(editor) = document.state.upgrade() else {
+            return;
+        };
+        let encoding = state.encoding.clone();
+        drop(state);
+
+        let published = params.diagnostics.into_iter().map(|mut diagnostic| {
+            diagnostic.range = editor_range(&text, diagnostic.range, &encoding);
+            diagnostic
+        });
+        editor.update(cx, |editor, cx| {
+            if editor.text.to_string() != text.to_string() {
+                return;
+            }
+
+            if let Some(diagnostics) = editor.diagnostics_mut() {
+                diagnostics.reset(&text);
+                diagnostics.extend(published.map(Into::into));
+                cx.notify();
+            }
+        });
+    }
+
+    /// Apply the edits of a [`WorkspaceEdit`] to the opened documents.
+    ///
+    /// Return false if nothing was applied.
+    fn apply_workspace_edit(&self, edit: &WorkspaceEdit, cx: &mut App) -> bool {
+        let state = self.state.borrow();
+        let targets = state
+            .documents
+            .values()
+            .filter_map(|document| {
+                let mut edits = document_edits(edit, &document.uri);
+                for edit in edits.iter_mut() {
+                    edit.range = editor_range(&document.text, edit.range, &state.encoding);
+                }
+                (!edits.is_empty()).then(|| (document.window, document.state.clone(), edits))
+            })
+            .collect::<Vec<_>>();
+        drop(state);
+
+        let mut applied = !targets.is_empty();
+        for (window, editor, edits) in targets {
+            let result = window.update(cx, |_, window, cx| {
+                editor.update(cx, |editor, cx| {
+                    editor.history.start_group();
+                    editor.apply_lsp_edits(&edits, window, cx);
+                    editor.history.end_group();
+                })
+            });
+            applied &= matches!(result, Ok(Ok(())));
+        }
+
+        applied
+    }
+}
+
+impl CompletionProvider for LspClient {
+    fn completions(
+        &self,
+        text: &Rope,
+        offset: usize,
+        trigger: CompletionContext,
+        _: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Vec<CompletionResponse>>> {
+        let entity_id = cx.entity_id();
+        if let Err(err) = self.sync(entity_id, text) {
+            return Task::ready(Err(err));
+        }
+
+        let state = self.state.borrow();
+        let Some(document) = state.documents.get(&entity_id) else {
+            return Task::ready(Ok(vec![]));
+        };
+
+        // The offset is the start of the query, complete at the end of it.
+        let query = trigger.trigger_character.unwrap_or_default();
+        let offset = text.clip_offset((offset + query.len()).min(text.len()), Bias::Left);
+        let trigger_character = query
+            .chars()
+            .last()
+            .map(|c| c.to_string())
+            .filter(|c| trigger_characters(&state.capabilities).contains(c));
+        let context = CompletionContext {
+            trigger_kind: if trigger_character.is_some() {
+                CompletionTriggerKind::TRIGGER_CHARACTER
+            } else {
+                CompletionTriggerKind::INVOKED
+            },
+            trigger_character,
+        };
+
+        let request = state.connection.request::<Completion>(CompletionParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(document.uri.clone()),
+                lsp_position(text, offset, &state.encoding),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: Some(context),
+        });
+
+        cx.background_executor()
+            .spawn(async move { Ok(request.await?.into_iter().collect()) })
+    }
+
+    fn is_completion_trigger(&self, _: usize, new_text: &str, _: &mut Context<InputState>) -> bool {
+        let state = self.state.borrow();
+        if state.capabilities.completion_provider.is_none() || new_text.is_empty() {
+            return false;
+        }
+
+        trigger_characters(&state.capabilities)
+            .iter()
+            .any(|c| new_text.ends_with(c.as_str()))
+            || new_text.chars().all(|c| c.is_alphanumeric() || c == '_')
+    }
+}
+
+impl CodeActionProvider for LspClient {
+    fn id(&self) -> SharedString {
+        self.state.borrow().id.clone()
+    }
+
+    fn code_actions(
+        &self,
+        state: Entity<InputState>,
+        range: Range<usize>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let text = state.read(cx).text.clone();
+        if let Err(err) = self.sync(state.entity_id(), &text) {
+            return Task::ready(Err(err));
+        }
+
+        let client = self.state.borrow();
+        let Some(document) = client.documents.get(&state.entity_id()) else {
+            return Task::ready(Ok(vec![]));
+        };
+
+        let range = lsp_types::Range::new(
+            lsp_position(&text, range.start, &client.encoding),
+            lsp_position(&text, range.end, &client.encoding),
+        );
+        let diagnostics = document
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| {
+                diagnostic.range.start <= range.end && diagnostic.range.end >= range.start
+            })
+            .cloned()
+            .collect();
+
+        let request = client
+            .connection
+            .request::<CodeActionRequest>(CodeActionParams {
+                text_document: TextDocumentIdentifier::new(document.uri.clone()),
+                range,
+                context: CodeActionContext {
+                    diagnostics,
+                    only: None,
+                    trigger_kind: Some(CodeActionTriggerKind::INVOKED),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            });
+
+        cx.background_executor().spawn(async move {
+            let actions = request.await?.unwrap_or_default();
+            Ok(actions
+                .into_iter()
+                .map(|action| match action {
+                    CodeActionOrCommand::CodeAction(action) => action,
+                    CodeActionOrCommand::Command(command) => CodeAction {
+                        title: command.title.clone(),
+                        command: Some(command),
+                        ..Default::default()
+                    },
+                })
+                .collect())
+        })
+    }
+
+    fn perform_code_action(
+        &self,
+        _: Entity<InputState>,
+        action: CodeAction,
+        _push_to_history: bool,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        let connection = self.connection();
+        let resolvable = matches!(
+            self.state.borrow().capabilities.code_action_provider,
+            Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                resolve_provider: Some(true),
+                ..
+            }))
+        );
+        let resolve = if resolvable && action.edit.is_none() {
+            Some(connection.request::<CodeActionResolveRequest>(action.clone()))
+        } else {
+            None
+        };
+
+        let client = self.clone();
+        cx.spawn(async move |cx| {
+            let action = match resolve {
+                Some(resolve) => resolve.await?,
+                None => action,
+            };
+
+            if let Some(edit) = action.edit.as_ref() {
+                cx.update(|cx| client.apply_workspace_edit(edit, cx))?;
+            }
+
+            if let Some(command) = action.command {
+                connection
+                    .request::<ExecuteCommand>(ExecuteCommandParams {
+                        command: command.command,
+                        arguments: command.arguments.unwrap_or_default(),
+                        work_done_progress_params: Default::default(),
+                    })
+                    .await?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl HoverProvider for LspClient {
+    fn hover(
+        &self,
+        text: &Rope,
+        offset: usize,
+        _: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Option<Hover>>> {
+        let Some(position) = self.position_params(cx.entity_id(), text, offset) else {
+            return Task::ready(Ok(None));
+        };
+
+        let request = self.connection().request::<HoverRequest>(HoverParams {
+            text_document_position_params: position,
+            work_done_progress_params: Default::default(),
+        });
+        let text = text.clone();
+        let encoding = self.position_encoding();
+        cx.background_executor().spawn(async move {
+            Ok(request.await?.map(|mut hover| {
+                hover.range = hover
+                    .range
+                    .map(|range| editor_range(&text, range, &encoding));
+                hover
+            }))
+        })
+    }
+}
+
+impl DefinitionProvider for LspClient {
+    fn definitions(
+        &self,
+        text: &Rope,
+        offset: usize,
+        _: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Vec<Location>>> {
+        let Some(position) = self.position_params(cx.entity_id(), text, offset) else {
+            return Task::ready(Ok(vec![]));
+        };
+
+        let uri = position.text_document.uri.clone();
+        let text = text.clone();
+        let encoding = self.position_encoding();
+        let request = self
+            .connection()
+            .request::<GotoDefinition>(GotoDefinitionParams {
+                text_document_position_params: position,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            });
+        cx.background_executor().spawn(async move {
+            let mut locations = match request.await? {
+                Some(GotoDefinitionResponse::Scalar(location)) => vec![location],
+                Some(GotoDefinitionResponse::Array(locations)) => locations,
+                Some(GotoDefinitionResponse::Link(links)) => links
+                    .into_iter()
+                    .map(|link| Location::new(link.target_uri, link.target_selection_range))
+                    .collect(),
+                None => vec![],
+            };
+            // Only the locations in this document can be converted, the text of others is unknown.
+            for location in locations.iter_mut().filter(|location| location.uri == uri) {
+                location.range = editor_range(&text, location.range, &encoding);
+            }
+            Ok(locations)
+        })
+    }
+}
+
+impl SignatureHelpProvider for LspClient {
+    fn signature_help(
+        &self,
+        text: &Rope,
+        offset: usize,
+        trigger: SignatureHelpContext,
+        _: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Option<SignatureHelp>>> {
+        let Some(position) = self.position_params(cx.entity_id(), text, offset) else {
+            return Task::ready(Ok(None));
+        };
+
+        let request = self
+            .connection()
+            .request::<SignatureHelpRequest>(SignatureHelpParams {
+                context: Some(trigger),
+                text_document_position_params: position,
+                work_done_progress_params: Default::default(),
+            });
+        let encoding = self.position_encoding();
+        cx.background_executor().spawn(async move {
+            Ok(request
+                .await?
+                .map(|help| editor_signature_help(help, &encoding)))
+        })
+    }
+
+    fn is_signature_help_trigger(
+        &self,
+        _: usize,
+        new_text: &str,
+        _: &mut Context<InputState>,
+    ) -> bool {
+        let state = self.state.borrow();
+        let Some(options) = state.capabilities.signature_help_provider.as_ref() else {
+            return false;
+        };
+
+        options
+            .trigger_characters
+            .iter()
+            .chain(options.retrigger_characters.iter())
+            .flatten()
+            .any(|c| new_text.ends_with(c.as_str()))
+    }
+}
+
+impl SemanticTokensProvider for LspClient {
+    fn legend(&self) -> SemanticTokensLegend {
+        semantic_tokens_legend(&self.state.borrow().capabilities).unwrap_or_default()
+    }
+
+    fn semantic_tokens(
+        &self,
+        text: &Rope,
+        _: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Option<SemanticTokens>>> {
+        let Some(text_document) = self.text_document(cx.entity_id(), text) else {
+            return Task::ready(Ok(None));
+        };
+
+        let request =
+            self.connection()
+                .request::<SemanticTokensFullRequest>(SemanticTokensParams {
+                    text_document,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                });
+        let text = text.clone();
+        let encoding = self.position_encoding();
+        cx.background_executor().spawn(async move {
+            Ok(match request.await? {
+                Some(SemanticTokensResult::Tokens(tokens)) => {
+                    Some(editor_semantic_tokens(&text, tokens, &encoding))
+                }
+                // The partial results are not requested.
+                Some(SemanticTokensResult::Partial(_)) | None => None,
+            })
+        })
+    }
+}
+
+impl LspClient {
+    /// Sync the text, and return the document of the editor.
+    fn text_document(&self, entity_id: EntityId, text: &Rope) -> Option<TextDocumentIdentifier> {
+        if let Err(err) = self.sync(entity_id, text) {
+            tracing::error!("failed to sync document: {:?}", err);
+            return None;
+        }
+
+        let state = self.state.borrow();
+        let document = state.documents.get(&entity_id)?;
+        Some(TextDocumentIdentifier::new(document.uri.clone()))
+    }
+
+    /// Sync the text, and return the position of the offset in the document of the editor.
+    fn position_params(
+        &self,
+        entity_id: EntityId,
+        text: &Rope,
+        offset: usize,
+    ) -> Option<TextDocumentPositionParams> {
+        if let Err(err) = self.sync(entity_id, text) {
+            tracing::error!("failed to sync document: {:?}", err);
+            return None;
+        }
+
+        Some(TextDocumentPositionParams::new(
+            self.text_document(entity_id, text)?,
+            lsp_position(text, offset, &self.position_encoding()),
+        ))
+    }
+}
+
+/// The semantic token types mapped to the highlight names, see
+/// [`crate::highlighter::semantic_token_highlight_name`].
+const SEMANTIC_TOKEN_TYPES: [SemanticTokenType; 23] = [
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::ENUM,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::EVENT,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::MODIFIER,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::REGEXP,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::DECORATOR,
+];
+
+fn initialize_params(root_uri: Option<Uri>) -> InitializeParams {
+    InitializeParams {
+        process_id: Some(std::process::id()),
+        workspace_folders: root_uri.map(|uri| {
+            vec![WorkspaceFolder {
+                name: uri.as_str().to_string(),
+                uri,
+            }]
+        }),
+        capabilities: ClientCapabilities {
+            general: Some(GeneralClientCapabilities {
+                position_encodings: Some(vec![PositionEncodingKind::UTF32]),
+                ..Default::default()
+            }),
+            text_document: Some(TextDocumentClientCapabilities {
+                synchronization: Some(TextDocumentSyncClientCapabilities::default()),
+                completion: Some(CompletionClientCapabilities::default()),
+                hover: Some(HoverClientCapabilities {
+                    content_format: Some(vec![MarkupKind::Markdown, MarkupKind::PlainText]),
+                    ..Default::default()
+                }),
+                definition: Some(GotoCapability {
+                    link_support: Some(true),
+                    ..Default::default()
+                }),
+                signature_help: Some(SignatureHelpClientCapabilities {
+                    signature_information: Some(SignatureInformationSettings {
+                        documentation_format: Some(vec![
+                            MarkupKind::Markdown,
+                            MarkupKind::PlainText,
+                        ]),
+                        parameter_information: Some(ParameterInformationSettings {
+                            label_offset_support: Some(true),
+                        }),
+                        active_parameter_support: Some(true),
+                    }),
+                    context_support: Some(true),
+                    ..Default::default()
+                }),
+                code_action: Some(CodeActionClientCapabilities {
+                    code_action_literal_support: Some(CodeActionLiteralSupport {
+                        code_action_kind: CodeActionKindLiteralSupport {
+                            value_set: vec!["quickfix".into(), "refactor".into(), "source".into()],
+                        },
+                    }),
+                    resolve_support: Some(CodeActionCapabilityResolveSupport {
+                        properties: vec!["edit".into()],
+                    }),
+                    data_support: Some(true),
+                    ..Default::default()
+                }),
+                publish_diagnostics: Some(PublishDiagnosticsClientCapabilities {
+                    version_support: Some(true),
+                    ..Default::default()
+                }),
+                semantic_tokens: Some(SemanticTokensClientCapabilities {
+                    requests: SemanticTokensClientCapabilitiesRequests {
+                        range: None,
+                        full: Some(SemanticTokensFullOptions::Bool(true)),
+                    },
+                    token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+                    token_modifiers: vec![
+                        SemanticTokenModifier::READONLY,
+                        SemanticTokenModifier::DOCUMENTATION,
+                    ],
+                    formats: vec![TokenFormat::RELATIVE],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn sync_kind(capabilities: &ServerCapabilities) -> TextDocumentSyncKind {
+    match &capabilities.text_document_sync {
+        Some(TextDocumentSyncCapability::Kind(kind)) => *kind,
+        Some(TextDocumentSyncCapability::Options(options)) => {
+            options.change.unwrap_or(TextDocumentSyncKind::NONE)
+        }
+        None => TextDocumentSyncKind::NONE,
+    }
+}
+
+fn trigger_characters(capabilities: &ServerCapabilities) -> Vec<String> {
+    capabilities
+        .completion_provider
+        .as_ref()
+        .and_then(|options| options.trigger_characters.clone())
+        .unwrap_or_default()
+}
+
+fn hover_enabled(capabilities: &ServerCapabilities) -> bool {
+    match &capabilities.hover_provider {
+        Some(HoverProviderCapability::Simple(enabled)) => *enabled,
+        Some(HoverProviderCapability::Options(_)) => true,
+        None => false,
+    }
+}
+
+fn definition_enabled(capabilities: &ServerCapabilities) -> bool {
+    match &capabilities.definition_provider {
+        Some(OneOf::Left(enabled)) => *enabled,
+        Some(OneOf::Right(_)) => true,
+        None => false,
+    }
+}
+
+/// Return the legend of the semantic tokens, `None` if the server does not provide the
+/// semantic tokens of the whole document.
+fn semantic_tokens_legend(capabilities: &ServerCapabilities) -> Option<SemanticTokensLegend> {
+    let options = match capabilities.semantic_tokens_provider.as_ref()? {
+        SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
+        SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            &options.semantic_tokens_options
+        }
+    };
+
+    match options.full {
+        Some(SemanticTokensFullOptions::Bool(false)) | None => None,
+        Some(_) => Some(options.legend.clone()),
+    }
+}
+
+fn code_actions_enabled(capabilities: &ServerCapabilities) -> bool {
+    match &capabilities.code_action_provider {
+        Some(CodeActionProviderCapability::Simple(enabled)) => *enabled,
+        Some(CodeActionProviderCapability::Options(_)) => true,
+        None => false,
+    }
+}
+
+/// Return the length of the character in the position encoding.
+fn encoded_len(c: char, encoding: &PositionEncodingKind) -> u32 {
+    if *encoding == PositionEncodingKind::UTF8 {
+        c.len_utf8() as u32
+    } else if *encoding == PositionEncodingKind::UTF16 {
+        c.len_utf16() as u32
+    } else {
+        1
+    }
+}
+
+/// Return the column in the position encoding of the first `chars` characters of the line.
+fn encode_column(line: &str, chars: u32, encoding: &PositionEncodingKind) -> u32 {
+    line.chars()
+        .take(chars as usize)
+        .map(|c| encoded_len(c, encoding))
+        .sum()
+}
+
+/// Return the number of characters of the line before the column in the position encoding.
+///
+/// A column in the middle of a character is moved to the start of it.
+fn decode_column(line: &str, column: u32, encoding: &PositionEncodingKind) -> u32 {
+    let mut len = 0;
+    line.chars()
+        .take_while(|c| {
+            len += encoded_len(*c, encoding);
+            len <= column
+        })
+        .count() as u32
+}
+
+/// Return the position of the byte offset in the position encoding of the server.
+fn lsp_position(text: &Rope, offset: usize, encoding: &PositionEncodingKind) -> Position {
+    let position = text.offset_to_position(offset);
+    if *encoding == PositionEncodingKind::UTF32 {
+        return position;
+    }
+
+    let line = text.line(position.line as usize).to_string();
+    Position::new(
+        position.line,
+        encode_column(&line, position.character, encoding),
+    )
+}
+
+/// Convert a range in the position encoding of the server to the characters of the editor.
+fn editor_range(
+    text: &Rope,
+    range: lsp_types::Range,
+    encoding: &PositionEncodingKind,
+) -> lsp_types::Range {
+    if *encoding == PositionEncodingKind::UTF32 {
+        return range;
+    }
+
+    let position = |position: Position| {
+        let line = text.line(position.line as usize).to_string();
+        Position::new(
+            position.line,
+            decode_column(&line, position.character, encoding),
+        )
+    };
+    lsp_types::Range::new(position(range.start), position(range.end))
+}
+
+/// Convert the label offsets of the parameters from the position encoding of the server to
+/// the byte offsets in the label of the signature.
+fn editor_signature_help(
+    mut help: SignatureHelp,
+    encoding: &PositionEncodingKind,
+) -> SignatureHelp {
+    if *encoding == PositionEncodingKind::UTF8 {
+        return help;
+    }
+
+    for signature in help.signatures.iter_mut() {
+        let label = &signature.label;
+        let byte_offset = |column: u32| {
+            let chars = decode_column(label, column, encoding) as usize;
+            label
+                .char_indices()
+                .nth(chars)
+                .map_or(label.len(), |(ix, _)| ix) as u32
+        };
+
+        for parameter in signature.parameters.iter_mut().flatten() {
+            if let ParameterLabel::LabelOffsets([start, end]) = &mut parameter.label {
+                *start = byte_offset(*start);
+                *end = byte_offset(*end);
+            }
+        }
+    }
+    help
+}
+
+/// Convert the columns of the relative semantic tokens from the position encoding of the
+/// server to the characters of the editor.
+fn editor_semantic_tokens(
+    text: &Rope,
+    mut tokens: SemanticTokens,
+    encoding: &PositionEncodingKind,
+) -> SemanticTokens {
+    if *encoding == PositionEncodingKind::UTF32 {
+        return tokens;
+    }
+
+    let mut line = 0;
+    let mut line_text = text.line(0).to_string();
+    // The start of the previous token, in the encoding of the server and in characters.
+    let mut start = 0;
+    let mut prev_start = 0;
+    for token in tokens.data.iter_mut() {
+        if token.delta_line > 0 {
+            line += token.delta_line;
+            line_text = text.line(line as usize).to_string();
+            start = 0;
+            prev_start = 0;
+        }
+        start += token.delta_start;
+
+        let token_start = decode_column(&line_text, start, encoding);
+        let token_end = decode_column(&line_text, start + token.length, encoding);
+        token.delta_start = token_start.saturating_sub(prev_start);
+        token.length = token_end.saturating_sub(token_start);
+        prev_start = token_start;
+    }
+    tokens
+}
+
+/// Return the edits of a [`WorkspaceEdit`] for the document `uri`, sorted to be
+/// applied one after another.
+fn document_edits(edit: &WorkspaceEdit, uri: &Uri) -> Vec<TextEdit> {
+    let mut edits = vec![];
+    if let Some(changes) = edit.changes.as_ref().and_then(|changes| changes.get(uri)) {
+        edits.extend(changes.iter().cloned());
+    }
+
+    let document_edits = match &edit.document_changes {
+        Some(DocumentChanges::Edits(edits)) => edits.iter().collect(),
+        Some(DocumentChanges::Operations(operations)) => operations
+            .iter()
+            .filter_map(|operation| match operation {
+                DocumentChangeOperation::Edit(edit) => Some(edit),
+                DocumentChangeOperation::Op(_) => None,
+            })
+            .collect(),
+        None => vec![],
+    };
+    for document_edit in document_edits {
+        if &document_edit.text_document.uri != uri {
+            continue;
+        }
+
+        edits.extend(document_edit.edits.iter().map(|edit| match edit {
+            OneOf::Left(edit) => edit.clone(),
+            OneOf::Right(edit) => edit.text_edit.clone(),
+        }));
+    }
+
+    // The ranges of the edits refer to the text before all of them, so apply them
+    // from the end, keeping the order of the inserts at the same position.
+    edits.reverse();
+    edits.sort_by(|a, b| b.range.start.cmp(&a.range.start));
+    edits
+}
+
+/// Return the changed byte range in the old text, and the range replacing it in the new text.
+fn text_diff(old: &str, new: &str) -> (Range<usize>, Range<usize>) {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) {
+        suffix -= 1;
+    }
+
+    (prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_encoding() {
+        let text = Rope::from("let a = \"😀中\";\nb😀c");
+        let offset = "let a = \"😀中".len();
+
+        let utf16 = PositionEncodingKind::UTF16;
+        assert_eq!(lsp_position(&text, offset, &utf16), Position::new(0, 12));
+        assert_eq!(
+            lsp_position(&text, offset, &PositionEncodingKind::UTF8),
+            Position::new(0, 16)
+        );
+        assert_eq!(
+            lsp_position(&text, offset, &PositionEncodingKind::UTF32),
+            Position::new(0, 11)
+        );
+
+        let range = lsp_types::Range::new(Position::new(0, 9), Position::new(1, 3));
+        assert_eq!(
+            editor_range(&text, range, &utf16),
+            lsp_types::Range::new(Position::new(0, 9), Position::new(1, 2))
+        );
+        // The middle of the surrogate pair moves to the start of it.
+        let range = lsp_types::Range::new(Position::new(0, 10), Position::new(1, 2));
+        assert_eq!(
+            editor_range(&text, range, &utf16),
+            lsp_types::Range::new(Position::new(0, 9), Position::new(1, 1))
+        );
+        assert_eq!(
+            editor_range(&text, range, &PositionEncodingKind::UTF32),
+            range
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_encoding() {
+        let token = |delta_line, delta_start, length| lsp_types::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        };
+        let text = Rope::from("a😀 b\n😀😀 c");
+        let tokens = SemanticTokens {
+            result_id: None,
+            data: vec![token(0, 0, 3), token(0, 4, 1), token(1, 5, 1)],
+        };
+
+        let tokens = editor_semantic_tokens(&text, tokens, &PositionEncodingKind::UTF16);
+        assert_eq!(
+            tokens.data,
+            vec![token(0, 0, 2), token(0, 3, 1), token(1, 3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_text_diff() {
+        assert_eq!(text_diff("hello", "hello"), (5..5, 5..5));
+        assert_eq!(text_diff("hello", "hello world"), (5..5, 5..11));
+        assert_eq!(text_diff("hello world", "hello"), (5..11, 5..5));
+        assert_eq!(text_diff("let a = 1;", "let ab = 1;"), (5..5, 5..6));
+        assert_eq!(text_diff("aaa", "aaaa"), (3..3, 3..4));
+        assert_eq!(text_diff("fn a()", "fn b()"), (3..4, 3..4));
+        assert_eq!(text_diff("", "abc"), (0..0, 0..3));
+
+        // Multi-byte characters sharing their first bytes.
+        assert_eq!(text_diff("中文", "中方"), (3..6, 3..6));
+        assert_eq!(text_diff("a中b", "a文b"), (1..4, 1..4));
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use futures::{
+    channel::oneshot,
+    io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader},
+    AsyncRead, AsyncWrite, FutureExt as _,
+};
+use lsp_types::{notification::Notification, request::Request};
+use serde_json::{json, Value};
+
+type PendingRequests = Arc<Mutex<HashMap<i32, oneshot::Sender<Result<Value>>>>>;
+
+/// A notification, or a request when it has an `id`, sent by the language server.
+#[derive(Debug, Clone)]
+pub struct LspMessage {
+    /// The id to [`LspConnection::respond`] with, if the message is a request.
+    pub id: Option<Value>,
+    pub method: String,
+    pub params: Value,
+}
+
+impl LspMessage {
+    /// Parse the params, if the message is the notification `N`.
+    pub fn notification<N: Notification>(&self) -> Option<N::Params> {
+        if self.id.is_some() || self.method != N::METHOD {
+            return None;
+        }
+
+        serde_json::from_value(self.params.clone()).ok()
+    }
+
+    /// Parse the params, if the message is the request `R`.
+    pub fn request<R: Request>(&self) -> Option<R::Params> {
+        if self.id.is_none() || self.method != R::METHOD {
+            return None;
+        }
+
+        serde_json::from_value(self.params.clone()).ok()
+    }
+
+    /// The result for a request nobody handled: an item per asked section for
+    /// `workspace/configuration`, and `null` for the others.
+    pub fn default_result(&self) -> Value {
+        match self.method.as_str() {
+            "workspace/configuration" => {
+                let len = self
+                    .params
+                    .get("items")
+                    .and_then(Value::as_array)
+                    .map_or(0, Vec::len);
+                Value::Array(vec![Value::Null; len])
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+/// A JSON-RPC connection to a language server, with the `Content-Length` framing
+/// of the Language Server Protocol.
+///
+/// The connection only queues messages, the IO future returned by
+/// [`LspConnection::new`] must be polled to exchange them with the server.
+#[derive(Clone)]
+pub struct LspConnection {
+    next_id: Arc<AtomicI32>,
+    outgoing: smol::channel::Sender<String>,
+    pending: PendingRequests,
+}
+
+impl LspConnection {
+    /// Create a connection reading messages from `reader` and writing to `writer`.
+    ///
+    /// Returns the connection, the receiver of the [`LspMessage`]s sent by the server,
+    /// and the IO future, which completes when the server closes its output.
+    pub fn new<R, W>(
+        reader: R,
+        writer: W,
+    ) -> (
+        Self,
+        smol::channel::Receiver<LspMessage>,
+        impl Future<Output = Result<()>> + Send + 'static,
+    )
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (outgoing, outgoing_rx) = smol::channel::unbounded();
+        let (incoming, incoming_rx) = smol::channel::unbounded();
+        let pending = PendingRequests::default();
+
+        let read = read_loop(reader, pending.clone(), incoming);
+        let write = write_loop(writer, outgoing_rx);
+        let io = {
+            let pending = pending.clone();
+            async move {
+                let result = futures::future::select(read.boxed(), write.boxed())
+                    .await
+                    .factor_first()
+                    .0;
+                // Fail the requests still waiting for a response.
+                pending.lock().unwrap().clear();
+                result
+            }
+        };
+
+        let connection = Self {
+            next_id: Arc::new(AtomicI32::new(0)),
+            outgoing,
+            pending,
+        };
+
+        (connection, incoming_rx, io)
+    }
+
+    /// Spawn a language server process and connect to its stdin and stdout.
+    ///
+    /// The process is killed when the IO future is dropped.
+    pub fn spawn(
+        command: std::process::Command,
+    ) -> Result<(
+        Self,
+        smol::channel::Receiver<LspMessage>,
+        impl Future<Output = Result<()>> + Send + 'static,
+    )> {
+        let program = command.get_program().to_string_lossy().to_string();
+        let mut child = smol::process::Command::from(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to spawn language server `{}`", program))?;
+
+        let stdin = child.stdin.take().context("missing stdin")?;
+        let stdout = child.stdout.take().context("missing stdout")?;
+        let (connection, messages, io) = Self::new(stdout, stdin);
+        let io = async move {
+            let _child = child;
+            io.await
+        };
+
+        Ok((connection, messages, io))
+    }
+
+    /// Send the request `R`, and wait for its response.
+    pub fn request<R: Request + 'static>(
+        &self,
+        params: R::Params,
+    ) -> impl Future<Output = Result<R::Result>> + Send + 'static {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let sent = self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": R::METHOD,
+            "params": params,
+        }));
+        if sent.is_err() {
+            self.pending.lock().unwrap().remove(&id);
+        }
+
+        async move {
+            sent?;
+            let result = rx.await.map_err(|_| {
+                anyhow!(
+                    "language server closed before responding to `{}`",
+                    R::METHOD
+                )
+            })??;
+            Ok(serde_json::from_value(result)?)
+        }
+    }
+
+    /// Send the notification `N`.
+    pub fn notify<N: Notification>(&self, params: N::Params) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": N::METHOD,
+            "params": params,
+        }))
+    }
+
+    /// Respond to a request of the server.
+    pub fn respond(&self, id: Value, result: Value) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": result,
+        }))
+    }
+
+    fn send(&self, message: Value) -> Result<()> {
+        self.outgoing
+            .try_send(serde_json::to_string(&message)?)
+            .map_err(|_| anyhow!("language server connection is closed"))
+    }
+}
+
+async fn read_loop<R>(
+    reader: R,
+    pending: PendingRequests,
+    incoming: smol::channel::Sender<LspMessage>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    let mut buf = vec![];
+
+    loop {
+        let mut content_len = None;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("Content-Length") {
+                    content_len = Some(value.trim().parse::<usize>()?);
+                }
+            }
+        }
+
+        let content_len = content_len.context("missing Content-Length header")?;
+        buf.resize(content_len, 0);
+        reader.read_exact(&mut buf).await?;
+
+        let mut message: Value = match serde_json::from_slice(&buf) {
+            Ok(message) => message,
+            Err(err) => {
+                tracing::error!("invalid message from language server: {}", err);
+                continue;
+            }
+        };
+
+        let id = message.get("id").cloned();
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .map(ToString::to_string);
+
+        match method {
+            Some(method) => {
+                let params = message.get_mut("params").map(Value::take);
+                _ = incoming.try_send(LspMessage {
+                    id,
+                    method,
+                    params: params.unwrap_or_default(),
+                });
+            }
+            None => {
+                let Some(id) = id.as_ref().and_then(Value::as_i64) else {
+                    continue;
+                };
+                let Some(tx) = pending.lock().unwrap().remove(&(id as i32)) else {
+                    continue;
+                };
+
+                let result = match message.get_mut("error") {
+                    Some(error) => Err(anyhow!(
+                        "{} ({})",
+                        error.get("message").and_then(Value::as_str).unwrap_or(""),
+                        error.get("code").and_then(Value::as_i64).unwrap_or(0)
+                    )),
+                    None => Ok(message
+                        .get_mut("result")
+                        .map(Value::take)
+                        .unwrap_or_default()),
+                };
+                _ = tx.send(result);
+            }
+        }
+    }
+}
+
+async fn write_loop<W>(mut writer: W, outgoing: smol::channel::Receiver<String>) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Ok(message) = outgoing.recv().await {
+        let header = format!("Content-Length: {}\r\n\r\n", message.len());
+        writer.write_all(header.as_bytes()).await?;
+        writer.write_all(message.as_bytes()).await?;
+        writer.flush().await?;
+    }
+
+    Ok(())
+}
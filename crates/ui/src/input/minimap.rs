@@ -0,0 +1,353 @@
+use std::ops::Range;
+
+use gpui::{
+    fill, point, px, relative, size, App, Bounds, ContentMask, Element, ElementId, Entity,
+    GlobalElementId, Hitbox, HitboxBehavior, Hsla, IntoElement, LayoutId, MouseButton,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Style, Window,
+};
+
+use crate::{input::RopeExt as _, ActiveTheme as _};
+
+use super::InputState;
+
+/// The width of the minimap.
+pub(super) const MINIMAP_WIDTH: Pixels = px(80.);
+/// The height of a line in the minimap.
+const MINIMAP_LINE_HEIGHT: Pixels = px(2.);
+/// The width of a character in the minimap.
+const MINIMAP_CHAR_WIDTH: Pixels = px(1.);
+
+/// A scaled-down view of the text in the code editor, click or drag it to scroll the editor.
+pub(super) struct Minimap {
+    state: Entity<InputState>,
+}
+
+impl Minimap {
+    pub(super) fn new(state: Entity<InputState>) -> Self {
+        Self { state }
+    }
+}
+
+pub(super) struct MinimapPrepaintState {
+    hitbox: Hitbox,
+    /// The bounds and colors of the words.
+    blocks: Vec<(Bounds<Pixels>, Hsla)>,
+    /// The bounds of the viewport of the editor.
+    slider_bounds: Bounds<Pixels>,
+    /// The first display row in the minimap.
+    first_display_row: usize,
+    /// The scroll distance of the editor when dragging the minimap by 1px.
+    drag_scale: f32,
+}
+
+/// Return the first display row to show in the minimap.
+///
+/// The minimap is scrolled by the same ratio as the editor, when the rows can't fit in it.
+fn minimap_first_row(total_rows: usize, minimap_rows: usize, scroll_ratio: f32) -> usize {
+    if total_rows <= minimap_rows {
+        return 0;
+    }
+
+    ((total_rows - minimap_rows) as f32 * scroll_ratio.clamp(0., 1.)).round() as usize
+}
+
+/// Split the line into words (the non-whitespace runs).
+///
+/// Returns the byte offset of the word in the line, and the columns of the word.
+fn minimap_words(line: &str, tab_size: usize) -> Vec<(usize, Range<usize>)> {
+    let mut words: Vec<(usize, Range<usize>)> = vec![];
+    let mut column = 0;
+    let mut in_word = false;
+    for (ix, c) in line.char_indices() {
+        let width = match c {
+            '\t' => tab_size - column % tab_size.max(1),
+            _ => 1,
+        };
+
+        if c.is_whitespace() {
+            in_word = false;
+        } else if in_word {
+            if let Some((_, columns)) = words.last_mut() {
+                columns.end = column + width;
+            }
+        } else {
+            in_word = true;
+            words.push((ix, column..column + width));
+        }
+        column += width;
+    }
+
+    words
+}
+
+impl IntoElement for Minimap {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for Minimap {
+    type RequestLayoutState = ();
+    type PrepaintState = MinimapPrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&gpui::InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.size.width = relative(1.).into();
+        style.size.height = relative(1.).into();
+
+        (window.request_layout(style, [], cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&gpui::InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let hitbox = window.insert_hitbox(bounds, HitboxBehavior::Normal);
+        let state = self.state.read(cx);
+        let line_height = state
+            .last_layout
+            .as_ref()
+            .map(|layout| layout.line_height)
+            .unwrap_or(window.line_height());
+        let tab_size = state.mode.tab_size().map_or(2, |tab| tab.tab_size);
+
+        let total_rows = state.text_wrapper.len();
+        let minimap_rows = (bounds.size.height / MINIMAP_LINE_HEIGHT).floor() as usize;
+        let viewport_height = state.input_bounds.size.height;
+        let scroll_top = -state.scroll_handle.offset().y;
+        let max_scroll = (state.scroll_size.height - viewport_height).max(px(0.));
+        let scroll_ratio = if max_scroll > px(0.) {
+            scroll_top / max_scroll
+        } else {
+            0.
+        };
+        let first_display_row = minimap_first_row(total_rows, minimap_rows, scroll_ratio);
+
+        let slider_bounds = Bounds::new(
+            point(
+                bounds.left(),
+                bounds.top()
+                    + (scroll_top / line_height - first_display_row as f32) * MINIMAP_LINE_HEIGHT,
+            ),
+            size(
+                bounds.size.width,
+                viewport_height / line_height * MINIMAP_LINE_HEIGHT,
+            ),
+        );
+
+        // Dragging the slider from top to bottom scrolls the editor from top to bottom.
+        let slider_range = (total_rows.min(minimap_rows) as f32 * MINIMAP_LINE_HEIGHT
+            - slider_bounds.size.height)
+            .max(px(1.));
+        let drag_scale = if total_rows > minimap_rows {
+            max_scroll / slider_range
+        } else {
+            line_height / MINIMAP_LINE_HEIGHT
+        };
+
+        // Layout the words of the lines in the minimap.
+        let first_row = state.text_wrapper.row_for_display_row(first_display_row);
+        let last_row = state
+            .text_wrapper
+            .row_for_display_row(first_display_row + minimap_rows);
+        let byte_range =
+            state.text.line_start_offset(first_row)..state.text.line_end_offset(last_row);
+        let styles = state
+            .mode
+            .highlight_styles(&byte_range, cx)
+            .unwrap_or_default();
+
+        let mut blocks = vec![];
+        let mut display_row = state.text_wrapper.display_row(first_row);
+        let mut line_start_offset = byte_range.start;
+        for (row, line) in state
+            .text_wrapper
+            .lines
+            .iter()
+            .enumerate()
+            .skip(first_row)
+            .take(last_row + 1 - first_row)
+        {
+            let line_text = state.text.line(row).to_string();
+            if !state.text_wrapper.is_hidden(row) {
+                for wrapped_range in line.wrapped_lines() {
+                    if display_row >= first_display_row + minimap_rows {
+                        break;
+                    }
+                    if display_row >= first_display_row {
+                        let y = bounds.top()
+                            + (display_row - first_display_row) as f32 * MINIMAP_LINE_HEIGHT;
+                        let wrapped_text = line_text.get(wrapped_range.clone()).unwrap_or_default();
+
+                        for (ix, columns) in minimap_words(wrapped_text, tab_size) {
+                            let x = columns.start as f32 * MINIMAP_CHAR_WIDTH;
+                            if x >= bounds.size.width {
+                                break;
+                            }
+
+                            let offset = line_start_offset + wrapped_range.start + ix;
+                            let style_ix = styles.partition_point(|(range, _)| range.end <= offset);
+                            let color = styles
+                                .get(style_ix)
+                                .and_then(|(_, style)| style.color)
+                                .unwrap_or(cx.theme().foreground);
+                            let width = (columns.len() as f32 * MINIMAP_CHAR_WIDTH)
+                                .min(bounds.size.width - x);
+
+                            blocks.push((
+                                Bounds::new(
+                                    point(bounds.left() + x, y),
+                                    size(width, MINIMAP_LINE_HEIGHT * 0.75),
+                                ),
+                                color.opacity(0.6),
+                            ));
+                        }
+                    }
+                    display_row += 1;
+                }
+            }
+
+            // +1 for the `\n`
+            line_start_offset += line_text.len() + 1;
+        }
+
+        MinimapPrepaintState {
+            hitbox,
+            blocks,
+            slider_bounds,
+            first_display_row,
+            drag_scale,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&gpui::InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            for (block_bounds, color) in prepaint.blocks.drain(..) {
+                window.paint_quad(fill(block_bounds, color));
+            }
+
+            let is_hovered = prepaint.hitbox.is_hovered(window);
+            let slider_color = if is_hovered {
+                cx.theme().scrollbar_thumb_hover.opacity(0.3)
+            } else {
+                cx.theme().scrollbar_thumb.opacity(0.2)
+            };
+            window.paint_quad(fill(prepaint.slider_bounds, slider_color));
+        });
+
+        let line_height = window.line_height();
+        let first_display_row = prepaint.first_display_row;
+        let drag_scale = prepaint.drag_scale;
+
+        window.on_mouse_event({
+            let state = self.state.clone();
+            let hitbox = prepaint.hitbox.clone();
+
+            move |event: &MouseDownEvent, phase, window, cx| {
+                if !phase.bubble()
+                    || event.button != MouseButton::Left
+                    || !hitbox.is_hovered(window)
+                {
+                    return;
+                }
+                cx.stop_propagation();
+
+                state.update(cx, |state, cx| {
+                    // Scroll to center the clicked row in the editor.
+                    let display_row = first_display_row as f32
+                        + (event.position.y - bounds.top()) / MINIMAP_LINE_HEIGHT;
+                    let scroll_top =
+                        display_row * line_height - state.input_bounds.size.height / 2.;
+                    let offset = state.scroll_handle.offset();
+                    state.update_scroll_offset(Some(point(offset.x, -scroll_top)), cx);
+                    state.minimap_drag = Some((event.position.y, -state.scroll_handle.offset().y));
+                });
+            }
+        });
+
+        window.on_mouse_event({
+            let state = self.state.clone();
+
+            move |event: &MouseMoveEvent, _, _, cx| {
+                if event.pressed_button != Some(MouseButton::Left) {
+                    return;
+                }
+
+                state.update(cx, |state, cx| {
+                    let Some((start_y, start_scroll_top)) = state.minimap_drag else {
+                        return;
+                    };
+
+                    let scroll_top = start_scroll_top + (event.position.y - start_y) * drag_scale;
+                    let offset = state.scroll_handle.offset();
+                    state.update_scroll_offset(Some(point(offset.x, -scroll_top)), cx);
+                });
+            }
+        });
+
+        window.on_mouse_event({
+            let state = self.state.clone();
+
+            move |_: &MouseUpEvent, _, _, cx| {
+                state.update(cx, |state, _| {
+                    state.minimap_drag = None;
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimap_first_row() {
+        assert_eq!(minimap_first_row(10, 100, 0.5), 0);
+        assert_eq!(minimap_first_row(300, 100, 0.), 0);
+        assert_eq!(minimap_first_row(300, 100, 0.5), 100);
+        assert_eq!(minimap_first_row(300, 100, 1.), 200);
+        assert_eq!(minimap_first_row(300, 100, 2.), 200);
+    }
+
+    #[test]
+    fn test_minimap_words() {
+        assert_eq!(minimap_words("", 4), vec![]);
+        assert_eq!(
+            minimap_words("let a = 1;", 4),
+            vec![(0, 0..3), (4, 4..5), (6, 6..7), (8, 8..10)]
+        );
+        assert_eq!(minimap_words("\tfoo", 4), vec![(1, 4..7)]);
+        assert_eq!(minimap_words("  \tfoo()", 4), vec![(3, 4..9)]);
+    }
+}
@@ -1,13 +1,21 @@
 mod blink_cursor;
+mod brackets;
 mod change;
 mod clear_button;
+mod comment;
 mod cursor;
+mod diff_gutter;
 mod element;
+mod folding;
+mod indent;
 mod lsp;
+mod lsp_client;
 mod mask_pattern;
+mod minimap;
 mod mode;
 mod number_input;
 mod otp_input;
+mod overview_ruler;
 mod popovers;
 mod rope_ext;
 mod search;
@@ -18,6 +26,7 @@ mod text_wrapper;
 pub(crate) use clear_button::*;
 pub use cursor::*;
 pub use lsp::*;
+pub use lsp_client::*;
 pub use mask_pattern::MaskPattern;
 pub use mode::TabSize;
 pub use number_input::{NumberInput, NumberInputEvent, StepAction};
@@ -1,13 +1,19 @@
 use std::rc::Rc;
 use std::{cell::RefCell, ops::Range};
 
-use gpui::{App, SharedString};
+use gpui::{App, HighlightStyle, SharedString};
 use rope::Rope;
 use tree_sitter::{InputEdit, Point};
 
-use crate::highlighter::DiagnosticSet;
 use crate::highlighter::SyntaxHighlighter;
-use crate::input::{CodeActionProvider, CompletionProvider};
+use crate::highlighter::{
+    default_brackets, indent_fold_ranges, sticky_header_rows, BracketPair, DiagnosticSet,
+    FoldRange, SemanticTokenSet,
+};
+use crate::input::{
+    CodeActionProvider, CompletionProvider, DefinitionProvider, HoverProvider,
+    SemanticTokensProvider, SignatureHelpProvider,
+};
 
 use super::text_wrapper::TextWrapper;
 
@@ -56,11 +62,26 @@ pub enum InputMode {
         rows: usize,
         /// Show line number
         line_number: bool,
+        /// Allow to fold the code, the fold chevrons are shown in the line number gutter.
+        folding: bool,
+        /// Show the vertical indentation guides.
+        indent_guides: bool,
+        /// Show a scaled-down view of the text at the right side.
+        minimap: bool,
+        /// Pin the headers of the enclosing scopes (e.g.: functions, classes) at the top.
+        sticky_scroll: bool,
+        /// Mark the diagnostics and search matches in the vertical scrollbar.
+        overview_ruler: bool,
         language: SharedString,
         highlighter: Rc<RefCell<Option<SyntaxHighlighter>>>,
         diagnostics: DiagnosticSet,
         completion_provider: Option<Rc<dyn CompletionProvider>>,
         code_action_providers: Vec<Rc<dyn CodeActionProvider>>,
+        hover_provider: Option<Rc<dyn HoverProvider>>,
+        definition_provider: Option<Rc<dyn DefinitionProvider>>,
+        signature_help_provider: Option<Rc<dyn SignatureHelpProvider>>,
+        semantic_tokens_provider: Option<Rc<dyn SemanticTokensProvider>>,
+        semantic_tokens: SemanticTokenSet,
     },
 }
 
@@ -154,6 +175,51 @@ impl InputMode {
         }
     }
 
+    /// Return false if the mode is not [`InputMode::CodeEditor`].
+    #[inline]
+    pub(super) fn folding(&self) -> bool {
+        match self {
+            InputMode::CodeEditor { folding, .. } => *folding,
+            _ => false,
+        }
+    }
+
+    /// Return false if the mode is not [`InputMode::CodeEditor`].
+    #[inline]
+    pub(super) fn indent_guides(&self) -> bool {
+        match self {
+            InputMode::CodeEditor { indent_guides, .. } => *indent_guides,
+            _ => false,
+        }
+    }
+
+    /// Return false if the mode is not [`InputMode::CodeEditor`].
+    #[inline]
+    pub(super) fn minimap(&self) -> bool {
+        match self {
+            InputMode::CodeEditor { minimap, .. } => *minimap,
+            _ => false,
+        }
+    }
+
+    /// Return false if the mode is not [`InputMode::CodeEditor`].
+    #[inline]
+    pub(super) fn sticky_scroll(&self) -> bool {
+        match self {
+            InputMode::CodeEditor { sticky_scroll, .. } => *sticky_scroll,
+            _ => false,
+        }
+    }
+
+    /// Return false if the mode is not [`InputMode::CodeEditor`].
+    #[inline]
+    pub(super) fn overview_ruler(&self) -> bool {
+        match self {
+            InputMode::CodeEditor { overview_ruler, .. } => *overview_ruler,
+            _ => false,
+        }
+    }
+
     #[inline]
     pub(super) fn tab_size(&self) -> Option<&TabSize> {
         match self {
@@ -163,6 +229,23 @@ impl InputMode {
         }
     }
 
+    /// Return true if the language of the highlighter is changed in the [`LanguageRegistry`].
+    ///
+    /// [`LanguageRegistry`]: crate::highlighter::LanguageRegistry
+    pub(super) fn is_highlighter_outdated(&self, cx: &App) -> bool {
+        match self {
+            InputMode::CodeEditor {
+                language,
+                highlighter,
+                ..
+            } => highlighter
+                .borrow()
+                .as_ref()
+                .is_some_and(|highlighter| highlighter.is_outdated(language, cx)),
+            _ => false,
+        }
+    }
+
     pub(super) fn update_highlighter(
         &mut self,
         selected_range: &Range<usize>,
@@ -227,6 +310,107 @@ impl InputMode {
         }
     }
 
+    /// Return the foldable ranges of the text.
+    ///
+    /// Use the folds query of the language if it has, otherwise fold by the indentation of lines.
+    pub(super) fn fold_ranges(&self, text: &Rope) -> Vec<FoldRange> {
+        match self {
+            InputMode::CodeEditor {
+                highlighter, tab, ..
+            } => highlighter
+                .borrow()
+                .as_ref()
+                .and_then(|highlighter| highlighter.fold_ranges())
+                .unwrap_or_else(|| indent_fold_ranges(text, tab.tab_size)),
+            _ => vec![],
+        }
+    }
+
+    /// Return the rows of the scope headers to pin at the top, when the `top_row` is the
+    /// first visible row.
+    pub(super) fn sticky_header_rows(&self, top_row: usize, max_lines: usize) -> Vec<usize> {
+        match self {
+            InputMode::CodeEditor { highlighter, .. } => {
+                let scopes = highlighter
+                    .borrow()
+                    .as_ref()
+                    .map(|highlighter| highlighter.sticky_scopes(top_row))
+                    .unwrap_or_default();
+                sticky_header_rows(&scopes, top_row, max_lines)
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Return the syntax highlight styles of the range, `None` if there is no highlighter.
+    pub(super) fn highlight_styles(
+        &self,
+        range: &Range<usize>,
+        cx: &App,
+    ) -> Option<Vec<(Range<usize>, HighlightStyle)>> {
+        match self {
+            InputMode::CodeEditor { highlighter, .. } => highlighter
+                .borrow()
+                .as_ref()
+                .map(|highlighter| highlighter.styles(range, cx)),
+            _ => None,
+        }
+    }
+
+    /// Return the brackets and quotes of the language, empty if the mode is not
+    /// [`InputMode::CodeEditor`].
+    pub(super) fn brackets(&self) -> Vec<BracketPair> {
+        match self {
+            InputMode::CodeEditor { highlighter, .. } => highlighter
+                .borrow()
+                .as_ref()
+                .map(|highlighter| highlighter.brackets().to_vec())
+                .unwrap_or_else(default_brackets),
+            _ => vec![],
+        }
+    }
+
+    /// Return the ranges of the bracket pair next to the `offset`.
+    pub(super) fn bracket_pair_at(&self, offset: usize) -> Option<(Range<usize>, Range<usize>)> {
+        match self {
+            InputMode::CodeEditor { highlighter, .. } => highlighter
+                .borrow()
+                .as_ref()
+                .and_then(|highlighter| highlighter.bracket_pair_at(offset)),
+            _ => None,
+        }
+    }
+
+    /// Return the indent ranges intersect the `range`, `None` if the language has no indents query.
+    pub(super) fn indent_ranges(&self, range: Range<usize>) -> Option<Vec<Range<usize>>> {
+        match self {
+            InputMode::CodeEditor { highlighter, .. } => highlighter
+                .borrow()
+                .as_ref()
+                .and_then(|highlighter| highlighter.indent_ranges(range)),
+            _ => None,
+        }
+    }
+
+    /// Return the line comment token and the block comment tokens of the language.
+    pub(super) fn comment_tokens(
+        &self,
+    ) -> (Option<SharedString>, Option<(SharedString, SharedString)>) {
+        match self {
+            InputMode::CodeEditor { highlighter, .. } => highlighter
+                .borrow()
+                .as_ref()
+                .map(|highlighter| {
+                    (
+                        highlighter.line_comment().cloned(),
+                        highlighter.block_comment().cloned(),
+                    )
+                })
+                .unwrap_or_default(),
+            _ => (None, None),
+        }
+    }
+
     #[allow(unused)]
     pub(super) fn diagnostics(&self) -> Option<&DiagnosticSet> {
         match self {
@@ -261,6 +445,61 @@ impl InputMode {
             _ => vec![],
         }
     }
+
+    pub(super) fn hover_provider(&self) -> Option<&Rc<dyn HoverProvider>> {
+        match self {
+            InputMode::CodeEditor { hover_provider, .. } => hover_provider.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(super) fn definition_provider(&self) -> Option<&Rc<dyn DefinitionProvider>> {
+        match self {
+            InputMode::CodeEditor {
+                definition_provider,
+                ..
+            } => definition_provider.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(super) fn signature_help_provider(&self) -> Option<&Rc<dyn SignatureHelpProvider>> {
+        match self {
+            InputMode::CodeEditor {
+                signature_help_provider,
+                ..
+            } => signature_help_provider.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(super) fn semantic_tokens_provider(&self) -> Option<&Rc<dyn SemanticTokensProvider>> {
+        match self {
+            InputMode::CodeEditor {
+                semantic_tokens_provider,
+                ..
+            } => semantic_tokens_provider.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(super) fn semantic_tokens(&self) -> Option<&SemanticTokenSet> {
+        match self {
+            InputMode::CodeEditor {
+                semantic_tokens, ..
+            } => Some(semantic_tokens),
+            _ => None,
+        }
+    }
+
+    pub(super) fn semantic_tokens_mut(&mut self) -> Option<&mut SemanticTokenSet> {
+        match self {
+            InputMode::CodeEditor {
+                semantic_tokens, ..
+            } => Some(semantic_tokens),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
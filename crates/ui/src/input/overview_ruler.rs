@@ -0,0 +1,164 @@
+use std::ops::Range;
+
+use gpui::{
+    fill, point, px, relative, size, App, Bounds, Element, ElementId, Entity, GlobalElementId,
+    Half, Hsla, IntoElement, LayoutId, Pixels, Style, Window,
+};
+
+use crate::{highlighter::DiagnosticSeverity, input::RopeExt as _, ActiveTheme as _};
+
+use super::InputState;
+
+/// The min height of a marker in the overview ruler.
+const MARKER_MIN_HEIGHT: Pixels = px(2.);
+
+/// Marks the diagnostics and the search matches of the code editor in the vertical scrollbar.
+///
+/// The search matches are marked at the left half, and the diagnostics at the right half.
+pub(super) struct OverviewRuler {
+    state: Entity<InputState>,
+}
+
+impl OverviewRuler {
+    pub(super) fn new(state: Entity<InputState>) -> Self {
+        Self { state }
+    }
+}
+
+impl IntoElement for OverviewRuler {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// Push a marker, or extend the last marker if they are overlapped with the same color.
+fn push_marker(markers: &mut Vec<(Bounds<Pixels>, Hsla)>, bounds: Bounds<Pixels>, color: Hsla) {
+    if let Some((last_bounds, last_color)) = markers.last_mut() {
+        if *last_color == color
+            && last_bounds.left() == bounds.left()
+            && last_bounds.bottom() >= bounds.top()
+        {
+            last_bounds.size.height = last_bounds
+                .size
+                .height
+                .max(bounds.bottom() - last_bounds.top());
+            return;
+        }
+    }
+
+    markers.push((bounds, color));
+}
+
+impl Element for OverviewRuler {
+    type RequestLayoutState = ();
+    type PrepaintState = Vec<(Bounds<Pixels>, Hsla)>;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&gpui::InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.size.width = relative(1.).into();
+        style.size.height = relative(1.).into();
+
+        (window.request_layout(style, [], cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&gpui::InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let state = self.state.read(cx);
+        let line_height = state
+            .last_layout
+            .as_ref()
+            .map(|layout| layout.line_height)
+            .unwrap_or(window.line_height());
+        let scroll_height = state.scroll_size.height;
+        if scroll_height <= px(0.) {
+            return vec![];
+        }
+
+        // The markers are placed at the same ratio as the scrollbar thumb.
+        let display_rows = state.text_wrapper.display_rows();
+        let y_for_offset = |offset: usize| -> Pixels {
+            let row = state.text.offset_to_point(offset).row as usize;
+            let display_row = display_rows.get(row).copied().unwrap_or_default();
+            bounds.top() + bounds.size.height * (display_row as f32 * line_height / scroll_height)
+        };
+        let marker_bounds = |x: Pixels, range: &Range<usize>| -> Bounds<Pixels> {
+            let top = y_for_offset(range.start);
+            let bottom = y_for_offset(range.end).max(top + MARKER_MIN_HEIGHT);
+            Bounds::new(point(x, top), size(bounds.size.width.half(), bottom - top))
+        };
+
+        let mut markers = vec![];
+        if let Some(matcher) = state
+            .search_panel
+            .as_ref()
+            .and_then(|panel| panel.read(cx).matcher())
+        {
+            let color = cx.theme().selection.opacity(1.);
+            for range in matcher.matched_ranges.iter() {
+                push_marker(&mut markers, marker_bounds(bounds.left(), range), color);
+            }
+        }
+
+        if let Some(diagnostics) = state.mode.diagnostics() {
+            // Paint the more severe diagnostics on top.
+            for severity in [
+                DiagnosticSeverity::Hint,
+                DiagnosticSeverity::Info,
+                DiagnosticSeverity::Warning,
+                DiagnosticSeverity::Error,
+            ] {
+                let color = severity.fg(cx);
+                for entry in diagnostics
+                    .iter()
+                    .filter(|entry| entry.diagnostic.severity == severity)
+                {
+                    push_marker(
+                        &mut markers,
+                        marker_bounds(bounds.center().x, &entry.range),
+                        color,
+                    );
+                }
+            }
+        }
+
+        markers
+    }
+
+    fn paint(
+        &mut self,
+        _: Option<&GlobalElementId>,
+        _: Option<&gpui::InspectorElementId>,
+        _: Bounds<Pixels>,
+        _: &mut Self::RequestLayoutState,
+        markers: &mut Self::PrepaintState,
+        window: &mut Window,
+        _: &mut App,
+    ) {
+        for (bounds, color) in markers.drain(..) {
+            window.paint_quad(fill(bounds, color));
+        }
+    }
+}
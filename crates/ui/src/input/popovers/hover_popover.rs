@@ -0,0 +1,204 @@
+use std::ops::Range;
+
+use gpui::{
+    canvas, deferred, div, px, rems, App, AppContext as _, Bounds, Context, Empty, Entity,
+    InteractiveElement, IntoElement, ParentElement as _, Pixels, Point, Render, SharedString,
+    Styled, Window,
+};
+use lsp_types::{HoverContents, MarkedString, MarkupKind};
+
+use crate::{
+    input::InputState,
+    text::{TextView, TextViewStyle},
+    ActiveTheme as _,
+};
+
+const MAX_POPOVER_WIDTH: Pixels = px(500.);
+const MAX_POPOVER_HEIGHT: Pixels = px(320.);
+
+/// A popover to show the hover documentation of a symbol in the code editor.
+pub struct HoverPopover {
+    state: Entity<InputState>,
+    /// The byte range of the hovered symbol.
+    pub(crate) range: Range<usize>,
+    content: SharedString,
+    bounds: Bounds<Pixels>,
+    open: bool,
+}
+
+impl HoverPopover {
+    pub fn new(
+        range: Range<usize>,
+        contents: &HoverContents,
+        state: Entity<InputState>,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        let content = hover_markdown(contents).into();
+
+        cx.new(|_| Self {
+            state,
+            range,
+            content,
+            bounds: Bounds::default(),
+            open: true,
+        })
+    }
+
+    fn origin(&self, cx: &App) -> Option<Point<Pixels>> {
+        let state = self.state.read(cx);
+        let Some(last_layout) = state.last_layout.as_ref() else {
+            return None;
+        };
+
+        let line_number_width = last_layout.line_number_width;
+        let (_, _, start_pos) = state.line_and_position_for_offset(self.range.start);
+
+        start_pos.map(|pos| pos + Point::new(line_number_width, px(0.)))
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub(crate) fn show(&mut self, cx: &mut Context<Self>) {
+        self.open = true;
+        cx.notify();
+    }
+
+    pub(crate) fn hide(&mut self, cx: &mut Context<Self>) {
+        self.open = false;
+        cx.notify();
+    }
+
+    pub(crate) fn check_to_hide(&mut self, mouse_position: Point<Pixels>, cx: &mut Context<Self>) {
+        if !self.open {
+            return;
+        }
+
+        let padding = px(5.);
+        let bounds = Bounds {
+            origin: self.bounds.origin.map(|v| v - padding),
+            size: self.bounds.size.map(|v| v + padding * 2.),
+        };
+
+        if !bounds.contains(&mouse_position) {
+            self.hide(cx);
+        }
+    }
+}
+
+/// Convert the contents of a hover response to markdown.
+pub(crate) fn hover_markdown(contents: &HoverContents) -> String {
+    fn marked_string(s: &MarkedString) -> String {
+        match s {
+            MarkedString::String(s) => s.clone(),
+            MarkedString::LanguageString(s) => format!("```{}\n{}\n```", s.language, s.value),
+        }
+    }
+
+    match contents {
+        HoverContents::Scalar(s) => marked_string(s),
+        HoverContents::Array(items) => items
+            .iter()
+            .map(marked_string)
+            .collect::<Vec<_>>()
+            .join("\n\n---\n\n"),
+        HoverContents::Markup(content) => match content.kind {
+            MarkupKind::Markdown => content.value.clone(),
+            MarkupKind::PlainText => format!("```\n{}\n```", content.value),
+        },
+    }
+}
+
+impl Render for HoverPopover {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.open || self.content.trim().is_empty() {
+            return Empty.into_any_element();
+        }
+
+        let view = cx.entity();
+        let Some(pos) = self.origin(cx) else {
+            return Empty.into_any_element();
+        };
+
+        let scroll_origin = self.state.read(cx).scroll_handle.offset();
+
+        let y = pos.y - self.bounds.size.height + scroll_origin.y;
+        let x = pos.x + scroll_origin.x;
+        let max_width = MAX_POPOVER_WIDTH.min(window.bounds().size.width - x);
+
+        deferred(
+            div()
+                .id("hover-popover")
+                .absolute()
+                .left(x)
+                .top(y)
+                .px_2()
+                .py_1()
+                .text_xs()
+                .max_w(max_width)
+                .max_h(MAX_POPOVER_HEIGHT)
+                .overflow_y_scroll()
+                .occlude()
+                .bg(cx.theme().popover)
+                .text_color(cx.theme().popover_foreground)
+                .border_1()
+                .border_color(cx.theme().border)
+                .rounded(cx.theme().radius)
+                .shadow_md()
+                .child(
+                    TextView::markdown("hover", self.content.clone(), window, cx)
+                        .style(TextViewStyle::default().paragraph_gap(rems(0.5)))
+                        .selectable(),
+                )
+                .child(
+                    canvas(
+                        move |bounds, _, cx| view.update(cx, |r, _| r.bounds = bounds),
+                        |_, _, _, _| {},
+                    )
+                    .top_0()
+                    .left_0()
+                    .absolute()
+                    .size_full(),
+                )
+                .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                    this.hide(cx);
+                })),
+        )
+        .into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{HoverContents, LanguageString, MarkedString, MarkupContent, MarkupKind};
+
+    use super::hover_markdown;
+
+    #[test]
+    fn test_hover_markdown() {
+        let contents = HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "**bold**".into(),
+        });
+        assert_eq!(hover_markdown(&contents), "**bold**");
+
+        let contents = HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: "fn main()".into(),
+        });
+        assert_eq!(hover_markdown(&contents), "```\nfn main()\n```");
+
+        let contents = HoverContents::Array(vec![
+            MarkedString::LanguageString(LanguageString {
+                language: "rust".into(),
+                value: "fn main()".into(),
+            }),
+            MarkedString::String("The entry point.".into()),
+        ]);
+        assert_eq!(
+            hover_markdown(&contents),
+            "```rust\nfn main()\n```\n\n---\n\nThe entry point."
+        );
+    }
+}
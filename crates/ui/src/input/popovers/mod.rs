@@ -1,11 +1,15 @@
 mod code_action_menu;
 mod completion_menu;
 mod diagnostic_popover;
+mod hover_popover;
+mod signature_help_popover;
 
 pub(crate) use code_action_menu::*;
 pub(crate) use completion_menu::*;
 pub(crate) use diagnostic_popover::*;
 use gpui::{App, Entity, IntoElement};
+pub(crate) use hover_popover::*;
+pub(crate) use signature_help_popover::*;
 
 pub(crate) enum ContextMenu {
     Completion(Entity<CompletionMenu>),
@@ -0,0 +1,228 @@
+use std::ops::Range;
+
+use gpui::{
+    canvas, deferred, div, prelude::FluentBuilder as _, px, rems, App, AppContext as _, Context,
+    Empty, Entity, FontWeight, HighlightStyle, InteractiveElement as _, IntoElement,
+    ParentElement as _, Pixels, Point, Render, SharedString, Styled, StyledText, Window,
+};
+use lsp_types::{Documentation, ParameterLabel, SignatureHelp, SignatureInformation};
+
+use crate::{
+    input::InputState,
+    text::{TextView, TextViewStyle},
+    v_flex, ActiveTheme as _,
+};
+
+const MAX_POPOVER_WIDTH: Pixels = px(500.);
+const POPOVER_GAP: Pixels = px(4.);
+
+/// A popover to show the signature of the called function, above the cursor.
+pub struct SignatureHelpPopover {
+    state: Entity<InputState>,
+    pub(crate) help: SignatureHelp,
+    height: Pixels,
+}
+
+impl SignatureHelpPopover {
+    pub fn new(help: SignatureHelp, state: Entity<InputState>, cx: &mut App) -> Entity<Self> {
+        cx.new(|_| Self {
+            state,
+            help,
+            height: px(0.),
+        })
+    }
+
+    fn origin(&self, cx: &App) -> Option<Point<Pixels>> {
+        let state = self.state.read(cx);
+        let last_layout = state.last_layout.as_ref()?;
+        let cursor_origin = last_layout.cursor_bounds.map(|b| b.origin)?;
+        let scroll_origin = state.scroll_handle.offset();
+
+        Some(
+            scroll_origin + cursor_origin - state.input_bounds.origin
+                + Point::new(-px(4.), -self.height - POPOVER_GAP),
+        )
+    }
+
+    fn active_signature(&self) -> Option<&SignatureInformation> {
+        let ix = self.help.active_signature.unwrap_or(0) as usize;
+        self.help
+            .signatures
+            .get(ix)
+            .or(self.help.signatures.first())
+    }
+}
+
+/// Return the byte range of the active parameter in the label of the signature.
+pub(crate) fn active_parameter_range(
+    signature: &SignatureInformation,
+    active_parameter: Option<u32>,
+) -> Option<Range<usize>> {
+    let ix = signature.active_parameter.or(active_parameter)? as usize;
+    let parameter = signature.parameters.as_ref()?.get(ix)?;
+
+    match &parameter.label {
+        ParameterLabel::Simple(label) => {
+            let start = signature.label.find(label.as_str())?;
+            Some(start..start + label.len())
+        }
+        ParameterLabel::LabelOffsets([start, end]) => {
+            let range = *start as usize..*end as usize;
+            signature.label.get(range.clone()).map(|_| range)
+        }
+    }
+}
+
+fn documentation_text(documentation: &Documentation) -> SharedString {
+    match documentation {
+        Documentation::String(s) => s.clone().into(),
+        Documentation::MarkupContent(content) => content.value.clone().into(),
+    }
+}
+
+impl Render for SignatureHelpPopover {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(signature) = self.active_signature().cloned() else {
+            return Empty.into_any_element();
+        };
+        let Some(pos) = self.origin(cx) else {
+            return Empty.into_any_element();
+        };
+
+        let view = cx.entity();
+        let highlights = active_parameter_range(&signature, self.help.active_parameter)
+            .map(|range| {
+                vec![(
+                    range,
+                    HighlightStyle {
+                        color: Some(cx.theme().blue),
+                        font_weight: Some(FontWeight::BOLD),
+                        ..Default::default()
+                    },
+                )]
+            })
+            .unwrap_or_default();
+
+        let parameter_documentation = signature
+            .active_parameter
+            .or(self.help.active_parameter)
+            .and_then(|ix| signature.parameters.as_ref()?.get(ix as usize))
+            .and_then(|parameter| parameter.documentation.as_ref())
+            .map(documentation_text);
+        let documentation = signature.documentation.as_ref().map(documentation_text);
+        let max_width = MAX_POPOVER_WIDTH.min(window.bounds().size.width - pos.x);
+
+        deferred(
+            v_flex()
+                .id("signature-help-popover")
+                .absolute()
+                .left(pos.x)
+                .top(pos.y)
+                .gap_1()
+                .px_2()
+                .py_1()
+                .text_xs()
+                .max_w(max_width)
+                .occlude()
+                .bg(cx.theme().popover)
+                .text_color(cx.theme().popover_foreground)
+                .border_1()
+                .border_color(cx.theme().border)
+                .rounded(cx.theme().radius)
+                .shadow_md()
+                .child(
+                    div().child(
+                        StyledText::new(signature.label.clone()).with_highlights(highlights),
+                    ),
+                )
+                .when_some(parameter_documentation, |this, doc| {
+                    this.child(TextView::markdown("parameter-doc", doc, window, cx))
+                })
+                .when_some(documentation, |this, doc| {
+                    this.child(
+                        div()
+                            .pt_1()
+                            .border_t_1()
+                            .border_color(cx.theme().border)
+                            .child(
+                                TextView::markdown("signature-doc", doc, window, cx)
+                                    .style(TextViewStyle::default().paragraph_gap(rems(0.5))),
+                            ),
+                    )
+                })
+                .child(
+                    canvas(
+                        move |bounds, _, cx| {
+                            view.update(cx, |this, cx| {
+                                if this.height != bounds.size.height {
+                                    this.height = bounds.size.height;
+                                    cx.notify();
+                                }
+                            })
+                        },
+                        |_, _, _, _| {},
+                    )
+                    .top_0()
+                    .left_0()
+                    .absolute()
+                    .size_full(),
+                ),
+        )
+        .into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{ParameterInformation, ParameterLabel, SignatureInformation};
+
+    use super::active_parameter_range;
+
+    fn signature(label: &str, parameters: Vec<ParameterLabel>) -> SignatureInformation {
+        SignatureInformation {
+            label: label.into(),
+            documentation: None,
+            parameters: Some(
+                parameters
+                    .into_iter()
+                    .map(|label| ParameterInformation {
+                        label,
+                        documentation: None,
+                    })
+                    .collect(),
+            ),
+            active_parameter: None,
+        }
+    }
+
+    #[test]
+    fn test_active_parameter_range() {
+        let sig = signature(
+            "fn add(a: i32, b: i32) -> i32",
+            vec![
+                ParameterLabel::Simple("a: i32".into()),
+                ParameterLabel::Simple("b: i32".into()),
+            ],
+        );
+        assert_eq!(active_parameter_range(&sig, Some(0)), Some(7..13));
+        assert_eq!(active_parameter_range(&sig, Some(1)), Some(15..21));
+        assert_eq!(active_parameter_range(&sig, Some(2)), None);
+        assert_eq!(active_parameter_range(&sig, None), None);
+
+        // Offsets are in bytes.
+        let sig = signature(
+            "fn 中文(a: i32, b: i32)",
+            vec![
+                ParameterLabel::LabelOffsets([10, 16]),
+                ParameterLabel::LabelOffsets([18, 24]),
+                ParameterLabel::LabelOffsets([4, 6]),
+                ParameterLabel::LabelOffsets([18, 30]),
+            ],
+        );
+        assert_eq!(active_parameter_range(&sig, Some(0)), Some(10..16));
+        assert_eq!(active_parameter_range(&sig, Some(1)), Some(18..24));
+        // Not on the char boundaries, or out of the label.
+        assert_eq!(active_parameter_range(&sig, Some(2)), None);
+        assert_eq!(active_parameter_range(&sig, Some(3)), None);
+    }
+}
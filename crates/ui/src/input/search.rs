@@ -1,11 +1,13 @@
 use aho_corasick::AhoCorasick;
+use regex::{Regex, RegexBuilder};
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
 use rust_i18n::t;
 use std::{ops::Range, rc::Rc};
 
 use gpui::{
     actions, div, prelude::FluentBuilder as _, App, AppContext as _, Context, Empty, Entity,
     EntityInputHandler, FocusHandle, Focusable, Half, InteractiveElement as _, IntoElement,
-    KeyBinding, ParentElement as _, Render, Styled, Subscription, Window,
+    KeyBinding, ParentElement as _, Render, Styled, Subscription, Task, Window,
 };
 use rope::Rope;
 
@@ -29,10 +31,176 @@ pub(super) fn init(cx: &mut App) {
     )]);
 }
 
+/// The size in bytes of a batch of the text to search on the background.
+const SEARCH_BATCH_SIZE: usize = 64 * 1024;
+
+/// The options of the search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Ignore the ASCII case of the query, default: false
+    pub case_insensitive: bool,
+    /// Only match the whole words, default: false
+    pub whole_word: bool,
+    /// Use the query as a regular expression, default: false
+    pub regex: bool,
+    /// Keep the case of the matched text when replacing, default: false
+    pub preserve_case: bool,
+}
+
+/// A compiled search query.
+#[derive(Debug, Clone)]
+pub enum SearchQuery {
+    Text(AhoCorasick),
+    Regex(Regex),
+}
+
+impl SearchQuery {
+    /// Build the query, returns `None` if the query is empty or is an invalid regex.
+    pub fn new(query: &str, options: SearchOptions) -> Option<Self> {
+        if query.is_empty() {
+            return None;
+        }
+
+        if options.regex {
+            RegexBuilder::new(query)
+                .case_insensitive(options.case_insensitive)
+                .multi_line(true)
+                .build()
+                .ok()
+                .map(Self::Regex)
+        } else {
+            Some(Self::Text(
+                AhoCorasick::builder()
+                    .ascii_case_insensitive(options.case_insensitive)
+                    .build(&[query.to_string()])
+                    .expect("failed to build AhoCorasick query in SearchMatcher"),
+            ))
+        }
+    }
+
+    /// Find the matches in the `range` of the text, the empty matches are skipped.
+    pub fn find_in(&self, text: &Rope, range: Range<usize>, whole_word: bool) -> Vec<Range<usize>> {
+        let offset = range.start;
+        let ranges: Vec<Range<usize>> = match self {
+            Self::Text(query) => query
+                .stream_find_iter(text.bytes_in_range(range))
+                .filter_map(|query_match| query_match.ok())
+                .map(|query_match| query_match.range())
+                .collect(),
+            Self::Regex(regex) => {
+                let haystack = text.slice(range).to_string();
+                regex.find_iter(&haystack).map(|m| m.range()).collect()
+            }
+        };
+
+        ranges
+            .into_iter()
+            .map(|range| range.start + offset..range.end + offset)
+            .filter(|range| !range.is_empty())
+            .filter(|range| !whole_word || is_whole_word(text, range))
+            .collect()
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Return true if the `range` of the text is not adjacent to a word character.
+fn is_whole_word(text: &Rope, range: &Range<usize>) -> bool {
+    let before = text.reversed_chars_at(range.start).next();
+    let after = text.chars_at(range.end).next();
+
+    !before.map_or(false, is_word_char) && !after.map_or(false, is_word_char)
+}
+
+/// Return true if the regex `pattern` may match a newline, or depends on the start or end of the text,
+/// so it can't be searched batch by batch.
+fn regex_spans_lines(pattern: &str) -> bool {
+    fn spans_lines(hir: &Hir) -> bool {
+        match hir.kind() {
+            HirKind::Empty => false,
+            HirKind::Literal(literal) => literal.0.contains(&b'\n'),
+            HirKind::Class(Class::Unicode(class)) => class
+                .ranges()
+                .iter()
+                .any(|range| range.start() <= '\n' && '\n' <= range.end()),
+            HirKind::Class(Class::Bytes(class)) => class
+                .ranges()
+                .iter()
+                .any(|range| range.start() <= b'\n' && b'\n' <= range.end()),
+            HirKind::Look(look) => matches!(look, Look::Start | Look::End),
+            HirKind::Repetition(repetition) => spans_lines(&repetition.sub),
+            HirKind::Capture(capture) => spans_lines(&capture.sub),
+            HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().any(spans_lines),
+        }
+    }
+
+    regex_syntax::ParserBuilder::new()
+        .multi_line(true)
+        .build()
+        .parse(pattern)
+        .map_or(true, |hir| spans_lines(&hir))
+}
+
+/// Split the `range` of the text into batches of about `batch_size` bytes, on the line boundaries.
+fn search_batches(text: &Rope, range: Range<usize>, batch_size: usize) -> Vec<Range<usize>> {
+    let mut batches = vec![];
+    let mut start = range.start;
+    while start < range.end {
+        let mut end = (start + batch_size.max(1)).min(range.end);
+        if end < range.end {
+            let next_row = text.offset_to_point(end).row as usize + 1;
+            end = if next_row < text.lines_len() {
+                text.line_start_offset(next_row).min(range.end)
+            } else {
+                range.end
+            };
+        }
+
+        batches.push(start..end);
+        start = end;
+    }
+
+    batches
+}
+
+/// Change the case of the `replacement` to follow the case of the `matched` text.
+///
+/// - `FOO` -> `BAR`
+/// - `foo` -> `bar`
+/// - `Foo` -> `Bar`
+fn preserve_case(matched: &str, replacement: &str) -> String {
+    let letters = matched
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect::<Vec<_>>();
+    let Some(first) = letters.first() else {
+        return replacement.to_string();
+    };
+
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        replacement.to_uppercase()
+    } else if letters.iter().all(|c| c.is_lowercase()) {
+        replacement.to_lowercase()
+    } else if first.is_uppercase() {
+        let mut chars = replacement.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    } else {
+        replacement.to_string()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SearchMatcher {
     text: Rope,
-    pub query: Option<AhoCorasick>,
+    pub query: Option<SearchQuery>,
+    pub(super) options: SearchOptions,
+    /// Only search in this range of the text, used by the find in selection.
+    pub(super) scope: Option<Range<usize>>,
 
     pub(super) matched_ranges: Rc<Vec<Range<usize>>>,
     pub(super) current_match_ix: usize,
@@ -45,6 +213,8 @@ impl SearchMatcher {
         Self {
             text: "".into(),
             query: None,
+            options: SearchOptions::default(),
+            scope: None,
             matched_ranges: Rc::new(Vec::new()),
             current_match_ix: 0,
             replacing: false,
@@ -53,46 +223,115 @@ impl SearchMatcher {
 
     /// Update source text and re-match
     pub(crate) fn update(&mut self, text: &Rope) {
+        if self.set_text(text) {
+            self.update_matches();
+        }
+    }
+
+    /// Update source text without matching, returns true if the text is changed.
+    fn set_text(&mut self, text: &Rope) -> bool {
         if self.text.eq(text) {
-            return;
+            return false;
         }
 
         self.text = text.clone();
-        self.update_matches();
+        true
     }
 
-    fn update_matches(&mut self) {
-        let mut new_ranges = Vec::new();
-        if let Some(query) = &self.query {
-            let matches = query.stream_find_iter(self.text.bytes_in_range(0..self.text.len()));
+    /// Shift the scope after the `range` of the text was replaced by a text of `new_len`.
+    fn shift_scope(&mut self, range: &Range<usize>, new_len: usize) {
+        let Some(scope) = self.scope.as_mut() else {
+            return;
+        };
 
-            for query_match in matches.into_iter() {
-                let query_match = query_match.expect("query match for select all action");
-                new_ranges.push(query_match.range());
-            }
+        let delta = new_len as isize - range.len() as isize;
+        if range.end <= scope.start {
+            scope.start = scope.start.saturating_add_signed(delta);
+            scope.end = scope.end.saturating_add_signed(delta);
+        } else if range.start < scope.end {
+            scope.end = scope.end.saturating_add_signed(delta).max(scope.start);
         }
-        self.matched_ranges = Rc::new(new_ranges);
+    }
+
+    /// The range of the text to search in.
+    fn search_range(&self) -> Range<usize> {
+        match &self.scope {
+            Some(scope) => scope.start.min(self.text.len())..scope.end.min(self.text.len()),
+            None => 0..self.text.len(),
+        }
+    }
+
+    fn update_matches(&mut self) {
+        let new_ranges = match &self.query {
+            Some(query) => query.find_in(&self.text, self.search_range(), self.options.whole_word),
+            None => vec![],
+        };
+        self.set_matches(new_ranges);
+    }
+
+    fn set_matches(&mut self, ranges: Vec<Range<usize>>) {
+        self.matched_ranges = Rc::new(ranges);
         if !self.replacing {
             self.current_match_ix = 0;
             self.replacing = false;
         }
     }
 
+    fn push_matches(&mut self, ranges: Vec<Range<usize>>) {
+        Rc::make_mut(&mut self.matched_ranges).extend(ranges);
+    }
+
+    /// Set the search query and options without matching.
+    pub fn set_query(&mut self, query: &str, options: SearchOptions) {
+        self.options = options;
+        self.query = SearchQuery::new(query, options);
+    }
+
     /// Update the search query and reset the current match index.
+    #[allow(unused)]
     pub fn update_query(&mut self, query: &str, case_insensitive: bool) {
-        if query.len() > 0 {
-            self.query = Some(
-                AhoCorasick::builder()
-                    .ascii_case_insensitive(case_insensitive)
-                    .build(&[query.to_string()])
-                    .expect("failed to build AhoCorasick query in SearchMatcher"),
-            );
-        } else {
-            self.query = None;
-        }
+        self.set_query(
+            query,
+            SearchOptions {
+                case_insensitive,
+                ..self.options
+            },
+        );
         self.update_matches();
     }
 
+    /// Returns the text to replace the matched `range` with.
+    ///
+    /// In regex mode, the capture groups like `$1` or `${name}` in the `template` are expanded.
+    pub fn replacement(&self, range: &Range<usize>, template: &str) -> String {
+        let mut new_text = template.to_string();
+        if let Some(SearchQuery::Regex(regex)) = &self.query {
+            // Match in the whole lines to keep the context of the `^` and `$`.
+            let start_row = self.text.offset_to_point(range.start).row as usize;
+            let end_row = self.text.offset_to_point(range.end).row as usize;
+            let line_start = self.text.line_start_offset(start_row);
+            let haystack = self
+                .text
+                .slice(line_start..self.text.line_end_offset(end_row))
+                .to_string();
+
+            if let Some(captures) = regex.captures_at(&haystack, range.start - line_start) {
+                let matched = captures.get(0).map(|m| m.range());
+                if matched == Some(range.start - line_start..range.end - line_start) {
+                    new_text.clear();
+                    captures.expand(template, &mut new_text);
+                }
+            }
+        }
+
+        if self.options.preserve_case {
+            let matched = self.text.slice(range.clone()).to_string();
+            new_text = preserve_case(&matched, &new_text);
+        }
+
+        new_text
+    }
+
     /// Returns the number of matches found.
     #[allow(unused)]
     #[inline]
@@ -144,24 +383,29 @@ pub(super) struct SearchPanel {
     text_state: Entity<InputState>,
     search_input: Entity<InputState>,
     replace_input: Entity<InputState>,
-    case_insensitive: bool,
     replace_mode: bool,
+    /// Is the find in selection enabled.
+    in_selection: bool,
     matcher: SearchMatcher,
 
     open: bool,
+    _search_task: Task<()>,
     _subscriptions: Vec<Subscription>,
 }
 
 impl InputState {
-    /// Update the search matcher when text changes.
-    pub(super) fn update_search(&mut self, cx: &mut App) {
+    /// Update the search matcher when the `range` of the text is replaced by a text of `new_len`.
+    pub(super) fn update_search(&mut self, range: &Range<usize>, new_len: usize, cx: &mut App) {
         let Some(search_panel) = self.search_panel.as_ref() else {
             return;
         };
 
         let text = self.text.clone();
-        search_panel.update(cx, |this, _| {
-            this.matcher.update(&text);
+        search_panel.update(cx, |this, cx| {
+            this.matcher.shift_scope(range, new_len);
+            if this.matcher.set_text(&text) {
+                this.update_matches(false, cx);
+            }
         });
     }
 
@@ -206,8 +450,8 @@ impl SearchPanel {
                     match ev {
                         InputEvent::Change => {
                             let value = search_input.read(cx).value();
-                            this.matcher
-                                .update_query(value.as_str(), this.case_insensitive);
+                            this.matcher.set_query(value.as_str(), this.matcher.options);
+                            this.update_matches(false, cx);
                         }
                         _ => {}
                     }
@@ -218,10 +462,17 @@ impl SearchPanel {
                 text_state,
                 search_input,
                 replace_input,
-                case_insensitive: true,
                 replace_mode: false,
-                matcher: SearchMatcher::new(),
+                in_selection: false,
+                matcher: SearchMatcher {
+                    options: SearchOptions {
+                        case_insensitive: true,
+                        ..Default::default()
+                    },
+                    ..SearchMatcher::new()
+                },
                 open: true,
+                _search_task: Task::ready(()),
                 _subscriptions,
             }
         })
@@ -248,9 +499,85 @@ impl SearchPanel {
 
     fn update_search(&mut self, cx: &mut Context<Self>) {
         let query = self.search_input.read(cx).value();
-        self.matcher
-            .update_query(query.as_str(), self.case_insensitive);
-        self.update_text_selection(cx);
+        self.matcher.set_query(query.as_str(), self.matcher.options);
+        self.update_matches(true, cx);
+    }
+
+    /// Match the query on the background batch by batch, the matches are published after each batch.
+    ///
+    /// If `select` is true, select the first match once it is found.
+    fn update_matches(&mut self, select: bool, cx: &mut Context<Self>) {
+        let Some(query) = self.matcher.query.clone() else {
+            self._search_task = Task::ready(());
+            self.matcher.set_matches(vec![]);
+            cx.notify();
+            return;
+        };
+
+        let text = self.matcher.text.clone();
+        let whole_word = self.matcher.options.whole_word;
+        let range = self.matcher.search_range();
+        // The matches may cross the lines, so they can't be split into batches.
+        let pattern = self.search_input.read(cx).value();
+        let multiline = if self.matcher.options.regex {
+            regex_spans_lines(&pattern)
+        } else {
+            pattern.contains('\n')
+        };
+        let batches = if multiline {
+            vec![range]
+        } else {
+            search_batches(&text, range, SEARCH_BATCH_SIZE)
+        };
+
+        self._search_task = cx.spawn(async move |this, cx| {
+            if batches.is_empty() {
+                _ = this.update(cx, |this, cx| {
+                    this.matcher.set_matches(vec![]);
+                    cx.notify();
+                });
+                return;
+            }
+
+            for (ix, batch) in batches.into_iter().enumerate() {
+                let ranges = cx
+                    .background_executor()
+                    .spawn({
+                        let text = text.clone();
+                        let query = query.clone();
+                        async move { query.find_in(&text, batch, whole_word) }
+                    })
+                    .await;
+
+                let result = this.update(cx, |this, cx| {
+                    let has_matches = ix > 0 && !this.matcher.matched_ranges.is_empty();
+                    if ix == 0 {
+                        this.matcher.set_matches(ranges);
+                    } else {
+                        this.matcher.push_matches(ranges);
+                    }
+                    if select && !has_matches {
+                        this.update_text_selection(cx);
+                    }
+                    cx.notify();
+                });
+                if result.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Toggle the find in selection, the current selection of the text is used as the scope.
+    fn toggle_in_selection(&mut self, cx: &mut Context<Self>) {
+        self.in_selection = !self.in_selection;
+        self.matcher.scope = if self.in_selection {
+            let range: Range<usize> = self.text_state.read(cx).selected_range.into();
+            Some(range).filter(|range| !range.is_empty())
+        } else {
+            None
+        };
+        self.update_search(cx);
     }
 
     pub(super) fn hide(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -320,7 +647,7 @@ impl SearchPanel {
     }
 
     fn replace_next(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        let new_text = self.replace_input.read(cx).value();
+        let template = self.replace_input.read(cx).value();
         self.matcher.replacing = true;
         if let Some(range) = self
             .matcher
@@ -329,6 +656,7 @@ impl SearchPanel {
             .cloned()
         {
             let text_state = self.text_state.clone();
+            let new_text = self.matcher.replacement(&range, &template);
 
             let next_range = self.matcher.peek().unwrap_or(range.clone());
             cx.spawn_in(window, async move |_, cx| {
@@ -350,12 +678,16 @@ impl SearchPanel {
     }
 
     fn replace_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        let new_text = self.replace_input.read(cx).value();
+        let template = self.replace_input.read(cx).value();
         self.matcher.replacing = true;
         let ranges = self.matcher.matched_ranges.clone();
         if ranges.is_empty() {
             return;
         }
+        let new_texts = ranges
+            .iter()
+            .map(|range| self.matcher.replacement(range, &template))
+            .collect::<Vec<_>>();
 
         let text_state = self.text_state.clone();
         cx.spawn_in(window, async move |_, cx| {
@@ -363,8 +695,8 @@ impl SearchPanel {
                 text_state.update(cx, |state, cx| {
                     // Replace from the end to avoid messing up the ranges.
                     let mut rope = state.text.clone();
-                    for range in ranges.iter().rev() {
-                        rope.replace(range.clone(), new_text.as_str());
+                    for (range, new_text) in ranges.iter().zip(new_texts.iter()).rev() {
+                        rope.replace(range.clone(), new_text);
                     }
                     state.replace_text_in_range(
                         Some(0..state.text.len()),
@@ -420,17 +752,66 @@ impl Render for SearchPanel {
                             TextInput::new(&self.search_input)
                                 .focus_bordered(false)
                                 .suffix(
-                                    Button::new("case-insensitive")
-                                        .selected(!self.case_insensitive)
-                                        .xsmall()
-                                        .compact()
-                                        .ghost()
-                                        .icon(IconName::CaseSensitive)
-                                        .on_click(cx.listener(|this, _, _, cx| {
-                                            this.case_insensitive = !this.case_insensitive;
-                                            this.update_search(cx);
-                                            cx.notify();
-                                        })),
+                                    h_flex()
+                                        .gap_0p5()
+                                        .child(
+                                            Button::new("case-insensitive")
+                                                .selected(!self.matcher.options.case_insensitive)
+                                                .xsmall()
+                                                .compact()
+                                                .ghost()
+                                                .icon(IconName::CaseSensitive)
+                                                .tooltip(t!("Input.Match Case"))
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    this.matcher.options.case_insensitive =
+                                                        !this.matcher.options.case_insensitive;
+                                                    this.update_search(cx);
+                                                    cx.notify();
+                                                })),
+                                        )
+                                        .child(
+                                            Button::new("whole-word")
+                                                .selected(self.matcher.options.whole_word)
+                                                .xsmall()
+                                                .compact()
+                                                .ghost()
+                                                .icon(IconName::WholeWord)
+                                                .tooltip(t!("Input.Match Whole Word"))
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    this.matcher.options.whole_word =
+                                                        !this.matcher.options.whole_word;
+                                                    this.update_search(cx);
+                                                    cx.notify();
+                                                })),
+                                        )
+                                        .child(
+                                            Button::new("regex")
+                                                .selected(self.matcher.options.regex)
+                                                .xsmall()
+                                                .compact()
+                                                .ghost()
+                                                .icon(IconName::Regex)
+                                                .tooltip(t!("Input.Use Regular Expression"))
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    this.matcher.options.regex =
+                                                        !this.matcher.options.regex;
+                                                    this.update_search(cx);
+                                                    cx.notify();
+                                                })),
+                                        )
+                                        .child(
+                                            Button::new("in-selection")
+                                                .selected(self.in_selection)
+                                                .xsmall()
+                                                .compact()
+                                                .ghost()
+                                                .icon(IconName::TextSelect)
+                                                .tooltip(t!("Input.Find in Selection"))
+                                                .on_click(cx.listener(|this, _, _, cx| {
+                                                    this.toggle_in_selection(cx);
+                                                    cx.notify();
+                                                })),
+                                        ),
                                 )
                                 .small()
                                 .w_full()
@@ -487,6 +868,20 @@ impl Render for SearchPanel {
                         .child(
                             TextInput::new(&self.replace_input)
                                 .focus_bordered(false)
+                                .suffix(
+                                    Button::new("preserve-case")
+                                        .selected(self.matcher.options.preserve_case)
+                                        .xsmall()
+                                        .compact()
+                                        .ghost()
+                                        .icon(IconName::CaseUpper)
+                                        .tooltip(t!("Input.Preserve Case"))
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.matcher.options.preserve_case =
+                                                !this.matcher.options.preserve_case;
+                                            cx.notify();
+                                        })),
+                                )
                                 .small()
                                 .w_full()
                                 .shadow_none(),
@@ -544,4 +939,142 @@ mod tests {
         assert_eq!(search.next(), None);
         assert_eq!(search.next_back(), None);
     }
+
+    #[test]
+    fn test_search_options() {
+        let mut search = SearchMatcher::new();
+        search.update(&Rope::from("foo food foo_bar\nbar foo"));
+        search.set_query(
+            "foo",
+            SearchOptions {
+                whole_word: true,
+                ..Default::default()
+            },
+        );
+        search.update_matches();
+        assert_eq!(search.matched_ranges.as_ref(), &vec![0..3, 21..24]);
+
+        search.set_query(
+            r"fo+\b",
+            SearchOptions {
+                regex: true,
+                ..Default::default()
+            },
+        );
+        search.update_matches();
+        assert_eq!(search.matched_ranges.as_ref(), &vec![0..3, 21..24]);
+
+        search.set_query(
+            "^bar",
+            SearchOptions {
+                regex: true,
+                ..Default::default()
+            },
+        );
+        search.update_matches();
+        assert_eq!(search.matched_ranges.as_ref(), &vec![17..20]);
+
+        // Invalid regex
+        search.set_query(
+            "foo(",
+            SearchOptions {
+                regex: true,
+                ..Default::default()
+            },
+        );
+        search.update_matches();
+        assert!(search.query.is_none());
+        assert_eq!(search.len(), 0);
+
+        search.scope = Some(4..20);
+        search.update_query("foo", false);
+        assert_eq!(search.matched_ranges.as_ref(), &vec![4..7, 9..12]);
+
+        // Insert before the scope
+        search.shift_scope(&(0..0), 2);
+        assert_eq!(search.scope, Some(6..22));
+        // Delete in the scope
+        search.shift_scope(&(8..10), 0);
+        assert_eq!(search.scope, Some(6..20));
+        // Insert after the scope
+        search.shift_scope(&(25..25), 2);
+        assert_eq!(search.scope, Some(6..20));
+    }
+
+    #[test]
+    fn test_search_batches() {
+        let text = Rope::from("aaaa\nbbbb\ncccc\ndddd");
+        assert_eq!(search_batches(&text, 0..text.len(), 100), vec![0..19]);
+        assert_eq!(
+            search_batches(&text, 0..text.len(), 3),
+            vec![0..5, 5..10, 10..15, 15..19]
+        );
+        assert_eq!(search_batches(&text, 0..text.len(), 6), vec![0..10, 10..19]);
+        assert_eq!(search_batches(&text, 2..12, 6), vec![2..10, 10..12]);
+        assert_eq!(search_batches(&text, 3..3, 6), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn test_regex_spans_lines() {
+        for pattern in [
+            "foo",
+            "fo+\\w*",
+            "^foo$",
+            "\\bfoo\\b",
+            "[^\\n]+",
+            "(?-s).*",
+            "\\d+|[a-z]+",
+        ] {
+            assert!(!regex_spans_lines(pattern), "{pattern}");
+        }
+        for pattern in [
+            "foo\\n", "foo\nbar", "\\s+", "[^a]", "(?s).*", "\\W", "\\Afoo", "foo\\z", "(",
+        ] {
+            assert!(regex_spans_lines(pattern), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut search = SearchMatcher::new();
+        search.update(&Rope::from("let foo_bar = 1;\nlet Foo_baz = 2;"));
+        search.set_query(
+            r"(\w+)_(\w+)",
+            SearchOptions {
+                regex: true,
+                ..Default::default()
+            },
+        );
+        search.update_matches();
+        assert_eq!(search.matched_ranges.as_ref(), &vec![4..11, 21..28]);
+        assert_eq!(search.replacement(&(4..11), "${2}_$1"), "bar_foo");
+        assert_eq!(search.replacement(&(21..28), "${2}$1"), "bazFoo");
+        // Not a match, keep the template as it is.
+        assert_eq!(search.replacement(&(4..8), "${2}_$1"), "${2}_$1");
+
+        search.options.preserve_case = true;
+        assert_eq!(search.replacement(&(21..28), "${2}"), "Baz");
+
+        search.set_query(
+            "foo",
+            SearchOptions {
+                case_insensitive: true,
+                preserve_case: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(search.replacement(&(4..7), "$1"), "$1");
+        assert_eq!(search.replacement(&(21..24), "qux"), "Qux");
+    }
+
+    #[test]
+    fn test_preserve_case() {
+        assert_eq!(preserve_case("FOO", "bar"), "BAR");
+        assert_eq!(preserve_case("foo", "Bar"), "bar");
+        assert_eq!(preserve_case("Foo", "bar"), "Bar");
+        assert_eq!(preserve_case("F", "bar"), "Bar");
+        assert_eq!(preserve_case("fOO", "bar"), "bar");
+        assert_eq!(preserve_case("123", "Bar"), "Bar");
+        assert_eq!(preserve_case("Foo", ""), "");
+    }
 }
@@ -29,14 +29,17 @@ use super::{
     number_input,
     text_wrapper::TextWrapper,
 };
+use crate::input::{merge_cursors, Cursor, RopeExt as _, Selection};
 use crate::input::{
-    popovers::{ContextMenu, DiagnosticPopover},
+    popovers::{ContextMenu, DiagnosticPopover, HoverPopover, SignatureHelpPopover},
     search::{self, SearchPanel},
     Position,
 };
-use crate::input::{RopeExt as _, Selection};
-use crate::{highlighter::DiagnosticSet, input::text_wrapper::LineItem};
-use crate::{history::History, scroll::ScrollbarState, Root};
+use crate::{diff::DiffHunk, history::History, scroll::ScrollbarState, Root};
+use crate::{
+    highlighter::{DiagnosticSet, FoldRange, IndentGuide, LanguageRegistry, SemanticTokenSet},
+    input::text_wrapper::LineItem,
+};
 
 #[derive(Action, Clone, PartialEq, Eq, Deserialize)]
 #[action(namespace = input, no_json)]
@@ -92,15 +95,34 @@ actions!(
         Escape,
         ToggleCodeActions,
         Search,
+        AddCursorAbove,
+        AddCursorBelow,
+        SelectNextOccurrence,
+        SelectAllOccurrences,
+        GoToDefinition,
+        Fold,
+        Unfold,
+        FoldAll,
+        UnfoldAll,
+        JumpToBracket,
+        ToggleComment,
+        RevertHunk,
     ]
 );
 
 #[derive(Clone)]
 pub enum InputEvent {
     Change,
-    PressEnter { secondary: bool },
+    PressEnter {
+        secondary: bool,
+    },
     Focus,
     Blur,
+    /// Go to the definitions of a symbol, requested with F12 or secondary-click in the
+    /// code editor, the host can open the file of the location.
+    GoToDefinition {
+        locations: Vec<lsp_types::Location>,
+    },
 }
 
 pub(super) const CONTEXT: &str = "Input";
@@ -222,6 +244,51 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("cmd-f", Search, Some(CONTEXT)),
         #[cfg(not(target_os = "macos"))]
         KeyBinding::new("ctrl-f", Search, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-up", AddCursorAbove, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-alt-up", AddCursorAbove, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-down", AddCursorBelow, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-alt-down", AddCursorBelow, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-d", SelectNextOccurrence, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-d", SelectNextOccurrence, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-l", SelectAllOccurrences, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-l", SelectAllOccurrences, Some(CONTEXT)),
+        KeyBinding::new("f12", GoToDefinition, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-[", Fold, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-[", Fold, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-]", Unfold, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-]", Unfold, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-k cmd-0", FoldAll, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-k ctrl-0", FoldAll, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-k cmd-j", UnfoldAll, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-k ctrl-j", UnfoldAll, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-\\", JumpToBracket, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-\\", JumpToBracket, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-/", ToggleComment, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-/", ToggleComment, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-z", RevertHunk, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-alt-z", RevertHunk, Some(CONTEXT)),
     ]);
 
     search::init(cx);
@@ -246,6 +313,18 @@ pub(super) struct LastLayout {
     pub(super) line_number_width: Pixels,
     /// The cursor position (top, left) in pixels.
     pub(super) cursor_bounds: Option<Bounds<Pixels>>,
+    /// Whether the lines (same index as `lines`) are hidden in a folded region.
+    pub(super) hidden_lines: Rc<Vec<bool>>,
+    /// The rows of the sticky headers pinned at the top, from top to bottom.
+    pub(super) sticky_rows: Vec<usize>,
+}
+
+impl LastLayout {
+    /// Return true if the line at the index of `lines` is hidden in a folded region.
+    #[inline]
+    pub(super) fn is_hidden(&self, ix: usize) -> bool {
+        self.hidden_lines.get(ix).copied().unwrap_or(false)
+    }
 }
 
 /// InputState to keep editing state of the [`super::TextInput`].
@@ -267,6 +346,21 @@ pub struct InputState {
     /// Range for save the selected word, use to keep word range when drag move.
     pub(super) selected_word_range: Option<Selection>,
     pub(super) selection_reversed: bool,
+    /// The extra cursors of multiple cursors editing, the `selected_range` is the primary cursor.
+    pub(super) cursors: Vec<Cursor>,
+    /// The row and the x position (relative to the text) where an alt-drag column selection started.
+    column_selection: Option<(usize, Pixels)>,
+    /// The foldable ranges of the text in [`InputMode::CodeEditor`] mode.
+    pub(super) fold_ranges: Vec<FoldRange>,
+    /// The folded ranges, sorted by start row.
+    pub(super) folded: Vec<FoldRange>,
+    /// The text of the last updated `fold_ranges`.
+    pub(super) fold_ranges_text: Option<Rope>,
+    pub(super) indent_guides: Vec<IndentGuide>,
+    /// The base text to compare with for the diff gutter, see [`InputState::set_diff_base`].
+    pub(super) diff_base: Option<Rope>,
+    /// The changed hunks of the text compared with the `diff_base`, sorted by rows.
+    pub(super) diff_hunks: Vec<DiffHunk>,
     /// The marked range is the temporary insert text on IME typing.
     pub(super) ime_marked_range: Option<Selection>,
     pub(super) last_layout: Option<LastLayout>,
@@ -277,6 +371,8 @@ pub struct InputState {
     pub(super) last_bounds: Option<Bounds<Pixels>>,
     pub(super) last_selected_range: Option<Selection>,
     pub(super) selecting: bool,
+    /// The mouse y position and the scroll top when start dragging the minimap.
+    pub(super) minimap_drag: Option<(Pixels, Pixels)>,
     pub(super) disabled: bool,
     pub(super) masked: bool,
     pub(super) clean_on_escape: bool,
@@ -294,6 +390,8 @@ pub struct InputState {
 
     /// Popover
     diagnostic_popover: Option<Entity<DiagnosticPopover>>,
+    pub(super) hover_popover: Option<Entity<HoverPopover>>,
+    pub(super) signature_help_popover: Option<Entity<SignatureHelpPopover>>,
     /// Completion/CodeAction context menu
     pub(super) context_menu: Option<ContextMenu>,
     /// A flag to indicate if we are currently inserting a completion item.
@@ -307,6 +405,10 @@ pub struct InputState {
     _subscriptions: Vec<Subscription>,
 
     pub(super) _context_menu_task: Task<Result<()>>,
+    pub(super) _hover_task: Task<Result<()>>,
+    pub(super) _signature_help_task: Task<Result<()>>,
+    pub(super) _semantic_tokens_task: Task<Result<()>>,
+    pub(super) _diff_task: Task<()>,
 }
 
 impl EventEmitter<InputEvent> for InputState {}
@@ -336,6 +438,12 @@ impl InputState {
             }),
             cx.on_focus(&focus_handle, window, Self::on_focus),
             cx.on_blur(&focus_handle, window, Self::on_blur),
+            // Rebuild the highlighter when its language is registered, reloaded or removed.
+            cx.observe_global::<LanguageRegistry>(|input, cx| {
+                if input.mode.is_highlighter_outdated(cx) {
+                    input.reset_highlighter(cx);
+                }
+            }),
         ];
 
         let text_style = window.text_style();
@@ -355,9 +463,18 @@ impl InputState {
             searchable: false,
             selected_word_range: None,
             selection_reversed: false,
+            cursors: vec![],
+            column_selection: None,
+            fold_ranges: vec![],
+            folded: vec![],
+            fold_ranges_text: None,
+            indent_guides: vec![],
+            diff_base: None,
+            diff_hunks: vec![],
             ime_marked_range: None,
             input_bounds: Bounds::default(),
             selecting: false,
+            minimap_drag: None,
             disabled: false,
             masked: false,
             clean_on_escape: false,
@@ -377,10 +494,16 @@ impl InputState {
             placeholder: SharedString::default(),
             mask_pattern: MaskPattern::default(),
             diagnostic_popover: None,
+            hover_popover: None,
+            signature_help_popover: None,
             context_menu: None,
             completion_inserting: false,
             _subscriptions,
             _context_menu_task: Task::ready(Ok(())),
+            _hover_task: Task::ready(Ok(())),
+            _signature_help_task: Task::ready(Ok(())),
+            _semantic_tokens_task: Task::ready(Ok(())),
+            _diff_task: Task::ready(()),
         }
     }
 
@@ -410,6 +533,11 @@ impl InputState {
     /// Default options:
     ///
     /// - line_number: true
+    /// - folding: true
+    /// - indent_guides: true
+    /// - minimap: false
+    /// - sticky_scroll: true
+    /// - overview_ruler: true
     /// - tab_size: 2
     /// - hard_tabs: false
     /// - height: full
@@ -432,9 +560,19 @@ impl InputState {
             language,
             highlighter: Rc::new(RefCell::new(None)),
             line_number: true,
+            folding: true,
+            indent_guides: true,
+            minimap: false,
+            sticky_scroll: true,
+            overview_ruler: true,
             diagnostics: DiagnosticSet::default(),
             code_action_providers: vec![],
             completion_provider: None,
+            hover_provider: None,
+            definition_provider: None,
+            signature_help_provider: None,
+            semantic_tokens_provider: None,
+            semantic_tokens: SemanticTokenSet::default(),
         };
         self.searchable = true;
         self
@@ -506,6 +644,83 @@ impl InputState {
         }
     }
 
+    /// Set the hover provider for the code editor mode.
+    ///
+    /// Only for `InputMode::CodeEditor`.
+    pub fn set_hover_provider(
+        &mut self,
+        provider: Option<Rc<dyn super::HoverProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputMode::CodeEditor { hover_provider, .. } = &mut self.mode {
+            *hover_provider = provider;
+            self.hover_popover = None;
+            cx.notify();
+        }
+    }
+
+    /// Set the definition provider for the code editor mode.
+    ///
+    /// The definitions are emitted with [`InputEvent::GoToDefinition`].
+    ///
+    /// Only for `InputMode::CodeEditor`.
+    pub fn set_definition_provider(
+        &mut self,
+        provider: Option<Rc<dyn super::DefinitionProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputMode::CodeEditor {
+            definition_provider,
+            ..
+        } = &mut self.mode
+        {
+            *definition_provider = provider;
+            cx.notify();
+        }
+    }
+
+    /// Set the signature help provider for the code editor mode.
+    ///
+    /// Only for `InputMode::CodeEditor`.
+    pub fn set_signature_help_provider(
+        &mut self,
+        provider: Option<Rc<dyn super::SignatureHelpProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputMode::CodeEditor {
+            signature_help_provider,
+            ..
+        } = &mut self.mode
+        {
+            *signature_help_provider = provider;
+            self.hide_signature_help(cx);
+            cx.notify();
+        }
+    }
+
+    /// Set the semantic tokens provider for the code editor mode, the tokens are cleared.
+    ///
+    /// Call [`InputState::refresh_semantic_tokens`] to request the tokens.
+    ///
+    /// Only for `InputMode::CodeEditor`.
+    pub fn set_semantic_tokens_provider(
+        &mut self,
+        provider: Option<Rc<dyn super::SemanticTokensProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputMode::CodeEditor {
+            semantic_tokens_provider,
+            semantic_tokens,
+            ..
+        } = &mut self.mode
+        {
+            *semantic_tokens_provider = provider;
+            semantic_tokens.clear();
+            self._semantic_tokens_task = Task::ready(Ok(()));
+            cx.notify();
+        }
+    }
+
     /// Set placeholder
     pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
         self.placeholder = placeholder.into();
@@ -528,6 +743,61 @@ impl InputState {
         cx.notify();
     }
 
+    /// Set true to allow folding the code, only for [`InputMode::CodeEditor`] mode.
+    ///
+    /// The foldable ranges are from the folds query of the language, or by the indentation of lines.
+    pub fn folding(mut self, folding: bool) -> Self {
+        if let InputMode::CodeEditor { folding: f, .. } = &mut self.mode {
+            *f = folding;
+        }
+        self
+    }
+
+    /// Set true to show the vertical indentation guides, only for [`InputMode::CodeEditor`] mode.
+    pub fn indent_guides(mut self, indent_guides: bool) -> Self {
+        if let InputMode::CodeEditor {
+            indent_guides: i, ..
+        } = &mut self.mode
+        {
+            *i = indent_guides;
+        }
+        self
+    }
+
+    /// Set true to show the minimap at the right side, only for [`InputMode::CodeEditor`] mode.
+    ///
+    /// The minimap can be clicked and dragged to scroll the editor.
+    pub fn minimap(mut self, minimap: bool) -> Self {
+        if let InputMode::CodeEditor { minimap: m, .. } = &mut self.mode {
+            *m = minimap;
+        }
+        self
+    }
+
+    /// Set true to pin the headers of the enclosing functions, classes, etc. at the top of the editor,
+    /// only for [`InputMode::CodeEditor`] mode.
+    pub fn sticky_scroll(mut self, sticky_scroll: bool) -> Self {
+        if let InputMode::CodeEditor {
+            sticky_scroll: s, ..
+        } = &mut self.mode
+        {
+            *s = sticky_scroll;
+        }
+        self
+    }
+
+    /// Set true to mark the diagnostics and search matches in the vertical scrollbar,
+    /// only for [`InputMode::CodeEditor`] mode.
+    pub fn overview_ruler(mut self, overview_ruler: bool) -> Self {
+        if let InputMode::CodeEditor {
+            overview_ruler: o, ..
+        } = &mut self.mode
+        {
+            *o = overview_ruler;
+        }
+        self
+    }
+
     /// Set the tab size for the input.
     ///
     /// Only for [`InputMode::MultiLine`] and [`InputMode::CodeEditor`] mode.
@@ -601,6 +871,11 @@ impl InputState {
         self.mode.diagnostics_mut()
     }
 
+    #[inline]
+    pub fn semantic_tokens(&self) -> Option<&SemanticTokenSet> {
+        self.mode.semantic_tokens()
+    }
+
     /// Set placeholder
     pub fn set_placeholder(
         &mut self,
@@ -614,25 +889,45 @@ impl InputState {
 
     /// Called after moving the cursor. Updates preferred_column if we know where the cursor now is.
     fn update_preferred_column(&mut self) {
-        let Some(last_layout) = &self.last_layout else {
-            self.preferred_column = None;
-            return;
-        };
+        let offset = self.cursor();
+        let column = self.text.offset_to_point(offset).column as usize;
+        self.preferred_column = self.x_for_offset(offset).map(|x| (x, column));
+    }
 
-        let point = self.text.offset_to_point(self.cursor());
-        let row = (point.row as usize).saturating_sub(last_layout.visible_range.start);
-        let Some(line) = last_layout.lines.get(row) else {
-            self.preferred_column = None;
-            return;
-        };
+    /// Return the x position (relative to the text) of the offset, if its line is visible.
+    pub(super) fn x_for_offset(&self, offset: usize) -> Option<Pixels> {
+        let last_layout = self.last_layout.as_ref()?;
+        let point = self.text.offset_to_point(offset);
+        let row = (point.row as usize).checked_sub(last_layout.visible_range.start)?;
+        let line = last_layout.lines.get(row)?;
+
+        line.position_for_index(point.column as usize, last_layout.line_height)
+            .map(|pos| pos.x)
+    }
+
+    /// Return the offset in the row closest to the x position (relative to the text), if the row is visible.
+    fn offset_for_x(&self, row: usize, x: Pixels) -> Option<usize> {
+        let last_layout = self.last_layout.as_ref()?;
+        let line = last_layout
+            .lines
+            .get(row.checked_sub(last_layout.visible_range.start)?)?;
+        let column = line
+            .closest_index_for_position(point(x, px(0.)), last_layout.line_height)
+            .unwrap_or_else(|ix| ix);
+
+        let line_start = self.text.line_start_offset(row);
+        Some((line_start + column).min(self.text.line_end_offset(row)))
+    }
 
-        let Some(pos) = line.position_for_index(point.column as usize, last_layout.line_height)
+    /// Return the x position relative to the text of a mouse position.
+    pub(super) fn text_x_for_position(&self, position: Point<Pixels>) -> Pixels {
+        let (Some(bounds), Some(last_layout)) =
+            (self.last_bounds.as_ref(), self.last_layout.as_ref())
         else {
-            self.preferred_column = None;
-            return;
+            return px(0.);
         };
 
-        self.preferred_column = Some((pos.x, point.column as usize));
+        position.x - bounds.origin.x - last_layout.line_number_width
     }
 
     /// Find which line and sub-line the given offset belongs to, along with the position within that sub-line.
@@ -655,6 +950,11 @@ impl InputState {
         let mut prev_lines_offset = last_layout.visible_range_offset.start;
         let mut y_offset = last_layout.visible_top;
         for (line_index, line) in last_layout.lines.iter().enumerate() {
+            if last_layout.is_hidden(line_index) {
+                prev_lines_offset += line.len() + 1;
+                continue;
+            }
+
             let local_offset = offset.saturating_sub(prev_lines_offset);
             if let Some(pos) = line.position_for_index(local_offset, line_height) {
                 let sub_line_index = (pos.y.0 / line_height.0) as usize;
@@ -675,6 +975,11 @@ impl InputState {
         if self.mode.is_single_line() {
             return;
         }
+        if self.preferred_column.is_none() {
+            // E.g. the extra cursors of multiple cursors, keep the column of the cursor.
+            self.update_preferred_column();
+        }
+
         let Some(last_layout) = &self.last_layout else {
             return;
         };
@@ -683,7 +988,15 @@ impl InputState {
         let was_preferred_column = self.preferred_column;
 
         let row = self.text.offset_to_point(offset).row;
-        let new_row = row.saturating_add_signed(move_lines as i32);
+        let mut new_row = row.saturating_add_signed(move_lines as i32);
+        // Skip the lines hidden in the folded regions.
+        while self.text_wrapper.is_hidden(new_row as usize) {
+            if move_lines > 0 {
+                new_row += 1;
+            } else {
+                new_row -= 1;
+            }
+        }
         let line_start_offset = self.text.point_to_offset(rope::Point::new(new_row, 0));
 
         let mut new_offset = line_start_offset;
@@ -730,6 +1043,7 @@ impl InputState {
         self.replace_text(value, window, cx);
         self.disabled = was_disabled;
         self.history.ignore = false;
+        self.cursors.clear();
         // Ensure cursor to start when set text
         if self.mode.is_single_line() {
             self.selected_range = (self.text.len()..self.text.len()).into();
@@ -933,20 +1247,24 @@ impl InputState {
 
     pub(super) fn left(&mut self, _: &MoveLeft, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        if self.selected_range.is_empty() {
-            self.move_to(self.previous_boundary(self.cursor()), window, cx);
-        } else {
-            self.move_to(self.selected_range.start, window, cx)
-        }
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            if this.selected_range.is_empty() {
+                this.move_to(this.previous_boundary(this.cursor()), window, cx);
+            } else {
+                this.move_to(this.selected_range.start, window, cx)
+            }
+        });
     }
 
     pub(super) fn right(&mut self, _: &MoveRight, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        if self.selected_range.is_empty() {
-            self.move_to(self.next_boundary(self.selected_range.end), window, cx);
-        } else {
-            self.move_to(self.selected_range.end, window, cx)
-        }
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            if this.selected_range.is_empty() {
+                this.move_to(this.next_boundary(this.selected_range.end), window, cx);
+            } else {
+                this.move_to(this.selected_range.end, window, cx)
+            }
+        });
     }
 
     pub(super) fn up(&mut self, action: &MoveUp, window: &mut Window, cx: &mut Context<Self>) {
@@ -958,15 +1276,17 @@ impl InputState {
             return;
         }
 
-        if !self.selected_range.is_empty() {
-            self.move_to(
-                self.previous_boundary(self.selected_range.start.saturating_sub(1)),
-                window,
-                cx,
-            );
-        }
         self.pause_blink_cursor(cx);
-        self.move_vertical(-1, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            if !this.selected_range.is_empty() {
+                this.move_to(
+                    this.previous_boundary(this.selected_range.start.saturating_sub(1)),
+                    window,
+                    cx,
+                );
+            }
+            this.move_vertical(-1, window, cx);
+        });
     }
 
     pub(super) fn down(&mut self, action: &MoveDown, window: &mut Window, cx: &mut Context<Self>) {
@@ -978,16 +1298,17 @@ impl InputState {
             return;
         }
 
-        if !self.selected_range.is_empty() {
-            self.move_to(
-                self.next_boundary(self.selected_range.end.saturating_sub(1)),
-                window,
-                cx,
-            );
-        }
-
         self.pause_blink_cursor(cx);
-        self.move_vertical(1, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            if !this.selected_range.is_empty() {
+                this.move_to(
+                    this.next_boundary(this.selected_range.end.saturating_sub(1)),
+                    window,
+                    cx,
+                );
+            }
+            this.move_vertical(1, window, cx);
+        });
     }
 
     pub(super) fn page_up(&mut self, _: &MovePageUp, window: &mut Window, cx: &mut Context<Self>) {
@@ -1000,7 +1321,9 @@ impl InputState {
         };
 
         let display_lines = (self.input_bounds.size.height / last_layout.line_height) as isize;
-        self.move_vertical(-display_lines, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            this.move_vertical(-display_lines, window, cx)
+        });
     }
 
     pub(super) fn page_down(
@@ -1018,7 +1341,9 @@ impl InputState {
         };
 
         let display_lines = (self.input_bounds.size.height / last_layout.line_height) as isize;
-        self.move_vertical(display_lines, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            this.move_vertical(display_lines, window, cx)
+        });
     }
 
     pub(super) fn select_left(
@@ -1027,7 +1352,9 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.select_to(self.previous_boundary(self.cursor()), window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            this.select_to(this.previous_boundary(this.cursor()), window, cx)
+        });
     }
 
     pub(super) fn select_right(
@@ -1036,15 +1363,19 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.select_to(self.next_boundary(self.cursor()), window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            this.select_to(this.next_boundary(this.cursor()), window, cx)
+        });
     }
 
     pub(super) fn select_up(&mut self, _: &SelectUp, window: &mut Window, cx: &mut Context<Self>) {
         if self.mode.is_single_line() {
             return;
         }
-        let offset = self.start_of_line().saturating_sub(1);
-        self.select_to(self.previous_boundary(offset), window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.start_of_line().saturating_sub(1);
+            this.select_to(this.previous_boundary(offset), window, cx);
+        });
     }
 
     pub(super) fn select_down(
@@ -1056,8 +1387,10 @@ impl InputState {
         if self.mode.is_single_line() {
             return;
         }
-        let offset = (self.end_of_line() + 1).min(self.text.len());
-        self.select_to(self.next_boundary(offset), window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = (this.end_of_line() + 1).min(this.text.len());
+            this.select_to(this.next_boundary(offset), window, cx);
+        });
     }
 
     pub(super) fn select_all(
@@ -1066,20 +1399,25 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.cursors.clear();
         self.move_to(0, window, cx);
         self.select_to(self.text.len(), window, cx);
     }
 
     pub(super) fn home(&mut self, _: &MoveHome, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        let offset = self.start_of_line();
-        self.move_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.start_of_line();
+            this.move_to(offset, window, cx);
+        });
     }
 
     pub(super) fn end(&mut self, _: &MoveEnd, window: &mut Window, cx: &mut Context<Self>) {
         self.pause_blink_cursor(cx);
-        let offset = self.end_of_line();
-        self.move_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.end_of_line();
+            this.move_to(offset, window, cx);
+        });
     }
 
     pub(super) fn move_to_start(
@@ -1088,6 +1426,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.cursors.clear();
         self.move_to(0, window, cx);
     }
 
@@ -1097,6 +1436,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.cursors.clear();
         self.move_to(self.text.len(), window, cx);
     }
 
@@ -1106,8 +1446,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.previous_start_of_word();
-        self.move_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.previous_start_of_word();
+            this.move_to(offset, window, cx);
+        });
     }
 
     pub(super) fn move_to_next_word(
@@ -1116,8 +1458,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.next_end_of_word();
-        self.move_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.next_end_of_word();
+            this.move_to(offset, window, cx);
+        });
     }
 
     pub(super) fn select_to_start(
@@ -1126,6 +1470,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.cursors.clear();
         self.select_to(0, window, cx);
     }
 
@@ -1135,6 +1480,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.cursors.clear();
         let end = self.text.len();
         self.select_to(end, window, cx);
     }
@@ -1145,8 +1491,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.start_of_line();
-        self.select_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.start_of_line();
+            this.select_to(offset, window, cx);
+        });
     }
 
     pub(super) fn select_to_end_of_line(
@@ -1155,8 +1503,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.end_of_line();
-        self.select_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.end_of_line();
+            this.select_to(offset, window, cx);
+        });
     }
 
     pub(super) fn select_to_previous_word(
@@ -1165,8 +1515,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.previous_start_of_word();
-        self.select_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.previous_start_of_word();
+            this.select_to(offset, window, cx);
+        });
     }
 
     pub(super) fn select_to_next_word(
@@ -1175,8 +1527,10 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.next_end_of_word();
-        self.select_to(offset, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.next_end_of_word();
+            this.select_to(offset, window, cx);
+        });
     }
 
     /// Return the start offset of the previous word.
@@ -1286,18 +1640,24 @@ impl InputState {
     }
 
     pub(super) fn backspace(&mut self, _: &Backspace, window: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
-            self.select_to(self.previous_boundary(self.cursor()), window, cx)
-        }
-        self.replace_text_in_range(None, "", window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            if let Some(range) = this.empty_pair_range() {
+                this.selected_range = range.into();
+            } else if this.selected_range.is_empty() {
+                this.select_to(this.previous_boundary(this.cursor()), window, cx)
+            }
+            this.replace_text_in_range(None, "", window, cx);
+        });
         self.pause_blink_cursor(cx);
     }
 
     pub(super) fn delete(&mut self, _: &Delete, window: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
-            self.select_to(self.next_boundary(self.cursor()), window, cx)
-        }
-        self.replace_text_in_range(None, "", window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            if this.selected_range.is_empty() {
+                this.select_to(this.next_boundary(this.cursor()), window, cx)
+            }
+            this.replace_text_in_range(None, "", window, cx);
+        });
         self.pause_blink_cursor(cx);
     }
 
@@ -1307,16 +1667,18 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut offset = self.start_of_line();
-        if offset == self.cursor() {
-            offset = offset.saturating_sub(1);
-        }
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(offset..self.cursor()))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let mut offset = this.start_of_line();
+            if offset == this.cursor() {
+                offset = offset.saturating_sub(1);
+            }
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(offset..this.cursor()))),
+                "",
+                window,
+                cx,
+            );
+        });
 
         self.pause_blink_cursor(cx);
     }
@@ -1327,16 +1689,18 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut offset = self.end_of_line();
-        if offset == self.cursor() {
-            offset = (offset + 1).clamp(0, self.text.len());
-        }
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(self.cursor()..offset))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let mut offset = this.end_of_line();
+            if offset == this.cursor() {
+                offset = (offset + 1).clamp(0, this.text.len());
+            }
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(this.cursor()..offset))),
+                "",
+                window,
+                cx,
+            );
+        });
         self.pause_blink_cursor(cx);
     }
 
@@ -1346,13 +1710,15 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.previous_start_of_word();
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(offset..self.cursor()))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.previous_start_of_word();
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(offset..this.cursor()))),
+                "",
+                window,
+                cx,
+            );
+        });
         self.pause_blink_cursor(cx);
     }
 
@@ -1362,13 +1728,15 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let offset = self.next_end_of_word();
-        self.replace_text_in_range(
-            Some(self.range_to_utf16(&(self.cursor()..offset))),
-            "",
-            window,
-            cx,
-        );
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            let offset = this.next_end_of_word();
+            this.replace_text_in_range(
+                Some(this.range_to_utf16(&(this.cursor()..offset))),
+                "",
+                window,
+                cx,
+            );
+        });
         self.pause_blink_cursor(cx);
     }
 
@@ -1378,16 +1746,13 @@ impl InputState {
         }
 
         if self.mode.is_multi_line() {
-            // Get current line indent
-            let indent = if self.mode.is_code_editor() {
-                self.indent_of_next_line()
-            } else {
-                "".to_string()
-            };
-
-            // Add newline and indent
-            let new_line_text = format!("\n{}", indent);
-            self.replace_text_in_range(None, &new_line_text, window, cx);
+            self.for_each_cursor(window, cx, |this, window, cx| {
+                if this.mode.is_code_editor() {
+                    this.auto_indent_new_line(window, cx);
+                } else {
+                    this.replace_text_in_range(None, "\n", window, cx);
+                }
+            });
         } else {
             // Single line input, just emit the event (e.g.: In a modal dialog to confirm).
             cx.propagate();
@@ -1404,11 +1769,13 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.indent(false, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            this.indent(false, window, cx)
+        });
     }
 
     pub(super) fn indent_block(&mut self, _: &Indent, window: &mut Window, cx: &mut Context<Self>) {
-        self.indent(true, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| this.indent(true, window, cx));
     }
 
     pub(super) fn outdent_inline(
@@ -1417,7 +1784,9 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.outdent(false, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            this.outdent(false, window, cx)
+        });
     }
 
     pub(super) fn outdent_block(
@@ -1426,7 +1795,9 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.outdent(true, window, cx);
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            this.outdent(true, window, cx)
+        });
     }
 
     pub(super) fn indent(&mut self, block: bool, window: &mut Window, cx: &mut Context<Self>) {
@@ -1571,6 +1942,16 @@ impl InputState {
             self.unmark_text(window, cx);
         }
 
+        if self.hide_signature_help(cx) {
+            return;
+        }
+
+        if !self.cursors.is_empty() {
+            self.cursors.clear();
+            cx.notify();
+            return;
+        }
+
         if self.clean_on_escape {
             return self.clean(window, cx);
         }
@@ -1587,6 +1968,148 @@ impl InputState {
         self.handle_code_action_trigger(window, cx)
     }
 
+    pub(super) fn go_to_definition(
+        &mut self,
+        _: &GoToDefinition,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.handle_definition_trigger(self.cursor(), window, cx)
+    }
+
+    pub(super) fn add_cursor_above(
+        &mut self,
+        _: &AddCursorAbove,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_cursor_vertical(-1, cx);
+    }
+
+    pub(super) fn add_cursor_below(
+        &mut self,
+        _: &AddCursorBelow,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.add_cursor_vertical(1, cx);
+    }
+
+    /// Add a cursor on the line above (negative `direction`) the first cursor, or below the last cursor.
+    fn add_cursor_vertical(&mut self, direction: isize, cx: &mut Context<Self>) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+
+        let (mut cursors, _) = self.all_cursors();
+        let offsets = cursors.iter().map(|cursor| cursor.offset());
+        let Some(offset) = (if direction < 0 {
+            offsets.min()
+        } else {
+            offsets.max()
+        }) else {
+            return;
+        };
+
+        let point = self.text.offset_to_point(offset);
+        let Some(row) = (point.row as usize)
+            .checked_add_signed(direction)
+            .filter(|row| *row < self.text.lines_len())
+        else {
+            return;
+        };
+
+        // Keep the x position of the cursor, or the column if the line is not visible.
+        let new_offset = self
+            .x_for_offset(offset)
+            .and_then(|x| self.offset_for_x(row, x))
+            .unwrap_or_else(|| {
+                (self.text.line_start_offset(row) + point.column as usize)
+                    .min(self.text.line_end_offset(row))
+            });
+
+        cursors.push(Cursor::new(new_offset..new_offset, false));
+        let primary = cursors.len() - 1;
+        self.set_cursors(cursors, primary, cx);
+        self.scroll_to(new_offset, cx);
+    }
+
+    /// Select the word at the cursor, or add a cursor at the next occurrence of the selected text.
+    pub(super) fn select_next_occurrence(
+        &mut self,
+        _: &SelectNextOccurrence,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+
+        if self.selected_range.is_empty() {
+            self.select_word(self.cursor(), window, cx);
+            self.selected_word_range = None;
+            return;
+        }
+
+        let query = self.selected_text().to_string();
+        let text = self.text.to_string();
+        let (mut cursors, _) = self.all_cursors();
+
+        // Search from the primary cursor, and wrap around to the start.
+        let start = self.selected_range.end;
+        let Some(offset) = text[start..]
+            .match_indices(&query)
+            .map(|(ix, _)| start + ix)
+            .chain(text[..start].match_indices(&query).map(|(ix, _)| ix))
+            .find(|offset| !cursors.iter().any(|cursor| cursor.range.start == *offset))
+        else {
+            return;
+        };
+
+        cursors.push(Cursor::new(offset..offset + query.len(), false));
+        let primary = cursors.len() - 1;
+        self.set_cursors(cursors, primary, cx);
+        self.scroll_to(offset, cx);
+    }
+
+    /// Add a cursor at every occurrence of the selected text, or of the word at the cursor.
+    pub(super) fn select_all_occurrences(
+        &mut self,
+        _: &SelectAllOccurrences,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode.is_code_editor() {
+            return;
+        }
+
+        if self.selected_range.is_empty() {
+            self.select_word(self.cursor(), window, cx);
+            self.selected_word_range = None;
+            if self.selected_range.is_empty() {
+                return;
+            }
+        }
+
+        let query = self.selected_text().to_string();
+        let current = self.selected_range.start;
+        let cursors = self
+            .text
+            .to_string()
+            .match_indices(&query)
+            .map(|(ix, _)| Cursor::new(ix..ix + query.len(), false))
+            .collect::<Vec<_>>();
+        if cursors.is_empty() {
+            return;
+        }
+
+        let primary = cursors
+            .iter()
+            .position(|cursor| cursor.range.start == current)
+            .unwrap_or(0);
+        self.set_cursors(cursors, primary, cx);
+    }
+
     pub(super) fn on_mouse_down(
         &mut self,
         event: &MouseDownEvent,
@@ -1601,8 +2124,45 @@ impl InputState {
             }
         }
 
+        // Click the fold chevron or the placeholder of a folded region to toggle the fold.
+        if event.button == MouseButton::Left {
+            if let Some(row) = self.fold_row_for_mouse_position(event.position, window, cx) {
+                self.toggle_fold_at_row(row, window, cx);
+                return;
+            }
+        }
+
+        // Click the sticky header to scroll to it.
+        if event.button == MouseButton::Left {
+            if let Some(ix) = self.sticky_header_for_mouse_position(event.position) {
+                self.scroll_to_sticky_header(ix, window, cx);
+                return;
+            }
+        }
+
         self.selecting = true;
+        self.cursors.clear();
+        self.column_selection = None;
         let offset = self.index_for_mouse_position(event.position, window, cx);
+
+        // Secondary-click to go to definition
+        if event.button == MouseButton::Left
+            && event.modifiers.secondary()
+            && self.mode.is_code_editor()
+        {
+            self.move_to(offset, window, cx);
+            self.handle_definition_trigger(offset, window, cx);
+            return;
+        }
+
+        // Alt-drag to select a column
+        if event.modifiers.alt && self.mode.is_code_editor() {
+            let row = self.text.offset_to_point(offset).row as usize;
+            self.column_selection = Some((row, self.text_x_for_position(event.position)));
+            self.move_to(offset, window, cx);
+            return;
+        }
+
         // Double click to select word
         if event.button == MouseButton::Left && event.click_count == 2 {
             self.select_word(offset, window, cx);
@@ -1624,6 +2184,7 @@ impl InputState {
     ) {
         self.selecting = false;
         self.selected_word_range = None;
+        self.column_selection = None;
     }
 
     pub(super) fn on_mouse_move(
@@ -1651,6 +2212,8 @@ impl InputState {
                 }
 
                 self.diagnostic_popover = Some(DiagnosticPopover::new(diagnostic, cx.entity(), cx));
+                self.hover_popover = None;
+                self._hover_task = Task::ready(Ok(()));
                 cx.notify();
             } else {
                 if let Some(diagnostic_popover) = self.diagnostic_popover.as_mut() {
@@ -1658,6 +2221,19 @@ impl InputState {
                         this.check_to_hide(event.position, cx);
                     })
                 }
+
+                // Keep the hover open while the mouse is over it.
+                if let Some(hover_popover) = self.hover_popover.as_ref() {
+                    let open = hover_popover.update(cx, |this, cx| {
+                        this.check_to_hide(event.position, cx);
+                        this.is_open()
+                    });
+                    if open {
+                        return;
+                    }
+                }
+
+                self.handle_hover_trigger(offset, window, cx);
             }
         }
     }
@@ -1676,9 +2252,14 @@ impl InputState {
         let delta = event.delta.pixel_delta(line_height);
         self.update_scroll_offset(Some(self.scroll_handle.offset() + delta), cx);
         self.diagnostic_popover = None;
+        self.hover_popover = None;
     }
 
-    fn update_scroll_offset(&mut self, offset: Option<Point<Pixels>>, cx: &mut Context<Self>) {
+    pub(super) fn update_scroll_offset(
+        &mut self,
+        offset: Option<Point<Pixels>>,
+        cx: &mut Context<Self>,
+    ) {
         let mut offset = offset.unwrap_or(self.scroll_handle.offset());
 
         let safe_y_range =
@@ -1705,15 +2286,7 @@ impl InputState {
 
         let point = self.text.offset_to_point(offset);
         let row = point.row as usize;
-
-        let mut row_offset_y = px(0.);
-        for (ix, wrap_line) in self.text_wrapper.lines.iter().enumerate() {
-            if ix == row {
-                break;
-            }
-
-            row_offset_y += wrap_line.height(line_height);
-        }
+        let row_offset_y = self.text_wrapper.display_row(row) as f32 * line_height;
 
         // Check if row_offset_y is out of the viewport
         // If row offset is not in the viewport, scroll to make it visible
@@ -1727,6 +2300,35 @@ impl InputState {
         self.update_scroll_offset(Some(scroll_offset), cx);
     }
 
+    /// Return the index of the sticky header at the mouse position.
+    fn sticky_header_for_mouse_position(&self, position: Point<Pixels>) -> Option<usize> {
+        let last_layout = self.last_layout.as_ref()?;
+        let y = position.y - self.input_bounds.origin.y;
+        if y < px(0.) || position.x < self.input_bounds.origin.x {
+            return None;
+        }
+
+        let ix = (y / last_layout.line_height).floor() as usize;
+        (ix < last_layout.sticky_rows.len()).then_some(ix)
+    }
+
+    /// Scroll to show the row of the sticky header at the same position, and move the cursor to it.
+    fn scroll_to_sticky_header(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(last_layout) = self.last_layout.as_ref() else {
+            return;
+        };
+        let Some(row) = last_layout.sticky_rows.get(ix).copied() else {
+            return;
+        };
+
+        // Keep the outer headers above it.
+        let display_row = self.text_wrapper.display_row(row).saturating_sub(ix);
+        let scroll_offset = self.scroll_handle.offset();
+        let scroll_top = display_row as f32 * last_layout.line_height;
+        self.update_scroll_offset(Some(point(scroll_offset.x, -scroll_top)), cx);
+        self.move_to(self.text.line_start_offset(row), window, cx);
+    }
+
     pub(super) fn show_character_palette(
         &mut self,
         _: &ShowCharacterPalette,
@@ -1737,22 +2339,26 @@ impl InputState {
     }
 
     pub(super) fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
+        let selected_texts = self.selected_texts();
+        if selected_texts.is_empty() {
             return;
         }
 
-        let selected_text = self.text.slice(self.selected_range.into()).to_string();
-        cx.write_to_clipboard(ClipboardItem::new_string(selected_text));
+        cx.write_to_clipboard(ClipboardItem::new_string(selected_texts.join("\n")));
     }
 
     pub(super) fn cut(&mut self, _: &Cut, window: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
+        let selected_texts = self.selected_texts();
+        if selected_texts.is_empty() {
             return;
         }
 
-        let selected_text = self.text.slice(self.selected_range.into()).to_string();
-        cx.write_to_clipboard(ClipboardItem::new_string(selected_text));
-        self.replace_text_in_range(None, "", window, cx);
+        cx.write_to_clipboard(ClipboardItem::new_string(selected_texts.join("\n")));
+        self.for_each_cursor(window, cx, |this, window, cx| {
+            if !this.selected_range.is_empty() {
+                this.replace_text_in_range(None, "", window, cx);
+            }
+        });
     }
 
     pub(super) fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
@@ -1762,7 +2368,20 @@ impl InputState {
                 new_text = new_text.replace('\n', "");
             }
 
-            self.replace_text_in_range(None, &new_text, window, cx);
+            // Paste a line at each cursor, if there are as many lines as cursors.
+            let mut lines = vec![];
+            if !self.cursors.is_empty() {
+                let (cursors, _) = self.all_cursors();
+                if new_text.split('\n').count() == cursors.len() {
+                    lines = new_text.split('\n').map(String::from).collect::<Vec<_>>();
+                }
+            }
+
+            // The cursors are visited from the last one, so pop the lines from the end.
+            self.for_each_cursor(window, cx, |this, window, cx| {
+                let text = lines.pop().unwrap_or_else(|| new_text.clone());
+                this.replace_text_in_range(None, &text, window, cx);
+            });
         }
     }
 
@@ -1780,6 +2399,7 @@ impl InputState {
     }
 
     pub(super) fn undo(&mut self, _: &Undo, window: &mut Window, cx: &mut Context<Self>) {
+        self.cursors.clear();
         self.history.ignore = true;
         if let Some(changes) = self.history.undo() {
             for change in changes {
@@ -1791,6 +2411,7 @@ impl InputState {
     }
 
     pub(super) fn redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
+        self.cursors.clear();
         self.history.ignore = true;
         if let Some(changes) = self.history.redo() {
             for change in changes {
@@ -1806,13 +2427,15 @@ impl InputState {
     /// The offset is the UTF-8 offset.
     ///
     /// Ensure the offset use self.next_boundary or self.previous_boundary to get the correct offset.
-    fn move_to(&mut self, offset: usize, _: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn move_to(&mut self, offset: usize, _: &mut Window, cx: &mut Context<Self>) {
         let offset = offset.clamp(0, self.text.len());
         self.selected_range = (offset..offset).into();
+        self.unfold_row(self.text.offset_to_point(offset).row as usize, cx);
         self.scroll_to(offset, cx);
         self.pause_blink_cursor(cx);
         self.update_preferred_column();
         self.hide_context_menu(cx);
+        self.hide_signature_help(cx);
         cx.notify()
     }
 
@@ -1831,7 +2454,121 @@ impl InputState {
         }
     }
 
-    fn index_for_mouse_position(
+    /// Return the selected ranges of all cursors in text order, the ranges are empty for cursors
+    /// without selection.
+    ///
+    /// There are multiple cursors after adding cursors above or below, selecting the
+    /// occurrences of the selected text or alt-dragging a column in [`InputMode::CodeEditor`] mode.
+    pub fn selections(&self) -> Vec<Range<usize>> {
+        let (cursors, _) = self.all_cursors();
+        cursors
+            .into_iter()
+            .map(|cursor| cursor.range.into())
+            .collect()
+    }
+
+    /// Remove the extra cursors, only keep the primary cursor.
+    pub fn clear_cursors(&mut self, cx: &mut Context<Self>) {
+        self.cursors.clear();
+        cx.notify();
+    }
+
+    /// Return all cursors in text order, and the index of the primary cursor.
+    fn all_cursors(&self) -> (Vec<Cursor>, usize) {
+        let mut cursors = self.cursors.clone();
+        cursors.push(Cursor::new(self.selected_range, self.selection_reversed));
+        let primary = cursors.len() - 1;
+        merge_cursors(cursors, primary)
+    }
+
+    /// Set the cursors, the cursor at `primary` becomes the `selected_range`.
+    fn set_cursors(&mut self, cursors: Vec<Cursor>, primary: usize, cx: &mut Context<Self>) {
+        let (mut cursors, primary) = merge_cursors(cursors, primary);
+        let cursor = cursors.remove(primary);
+        self.selected_range = cursor.range;
+        self.selection_reversed = cursor.reversed;
+        self.cursors = cursors;
+        self.update_preferred_column();
+        cx.notify();
+    }
+
+    /// Run `f` with each cursor as the `selected_range`, from the last cursor to the first one, so
+    /// the edits made by `f` do not move the cursors that are not visited yet.
+    ///
+    /// The changes made at all cursors are undone as a single step.
+    pub(super) fn for_each_cursor(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        mut f: impl FnMut(&mut Self, &mut Window, &mut Context<Self>),
+    ) {
+        if self.cursors.is_empty() {
+            f(self, window, cx);
+            return;
+        }
+
+        let (cursors, primary) = self.all_cursors();
+        self.cursors.clear();
+        let ime_marked_range = self.ime_marked_range.take();
+        let preferred_column = self.preferred_column.take();
+        let mut primary_state = (None, None);
+
+        self.history.start_group();
+        let mut visited: Vec<Cursor> = Vec::with_capacity(cursors.len());
+        for (ix, cursor) in cursors.into_iter().enumerate().rev() {
+            // The IME marked text and the preferred column belong to the primary cursor.
+            let is_primary = ix == primary;
+            self.selected_range = cursor.range;
+            self.selection_reversed = cursor.reversed;
+            self.ime_marked_range = if is_primary { ime_marked_range } else { None };
+            self.preferred_column = if is_primary { preferred_column } else { None };
+
+            let len = self.text.len();
+            f(self, window, cx);
+
+            // The visited cursors are after this one, move them by the length changed.
+            let delta = self.text.len() as isize - len as isize;
+            for cursor in visited.iter_mut() {
+                cursor.shift(delta);
+            }
+            if is_primary {
+                primary_state = (self.ime_marked_range, self.preferred_column);
+            }
+            visited.push(Cursor::new(self.selected_range, self.selection_reversed));
+        }
+        self.history.end_group();
+
+        visited.reverse();
+        self.set_cursors(visited, primary, cx);
+        (self.ime_marked_range, self.preferred_column) = primary_state;
+        self.scroll_to(self.cursor(), cx);
+    }
+
+    /// Move the extra cursors after `range` was replaced with a text of `new_len` bytes.
+    fn shift_cursors(&mut self, range: &Range<usize>, new_len: usize) {
+        let delta = new_len as isize - range.len() as isize;
+        for cursor in self.cursors.iter_mut() {
+            if cursor.range.start >= range.end {
+                cursor.shift(delta);
+            } else if cursor.range.end > range.start {
+                // The cursor was in the replaced text.
+                let offset = range.start + new_len;
+                *cursor = Cursor::new(offset..offset, false);
+            }
+        }
+    }
+
+    /// Return the selected texts of all cursors in text order, skipping the empty selections.
+    fn selected_texts(&self) -> Vec<String> {
+        let (cursors, _) = self.all_cursors();
+        cursors
+            .into_iter()
+            .filter(|cursor| !cursor.range.is_empty())
+            .map(|cursor| self.text.slice(cursor.range.into()).to_string())
+            .collect()
+    }
+
+    pub(super) fn index_for_mouse_position(
         &self,
         position: Point<Pixels>,
         _window: &Window,
@@ -1885,6 +2622,12 @@ impl InputState {
                 continue;
             };
 
+            if last_layout.is_hidden(ix) {
+                // +1 for revert `lines` split `\n`
+                index += rendered_line.len() + 1;
+                continue;
+            }
+
             // Return offset by use closest_index_for_x if is single line mode.
             if self.mode.is_single_line() {
                 return rendered_line.unwrapped_layout.closest_index_for_x(pos.x);
@@ -2163,10 +2906,49 @@ impl InputState {
             return;
         }
 
+        if let Some((row, x)) = self.column_selection {
+            self.select_column(row, x, event.position, window, cx);
+            return;
+        }
+
         let offset = self.index_for_mouse_position(event.position, window, cx);
         self.select_to(offset, window, cx);
     }
 
+    /// Select a rectangular column from the row and x position an alt-drag started at, to the
+    /// mouse position, with a cursor on each row.
+    fn select_column(
+        &mut self,
+        anchor_row: usize,
+        anchor_x: Pixels,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let offset = self.index_for_mouse_position(position, window, cx);
+        let row = self.text.offset_to_point(offset).row as usize;
+        let x = self.text_x_for_position(position);
+
+        let rows = if row >= anchor_row {
+            (anchor_row..=row).collect::<Vec<_>>()
+        } else {
+            (row..=anchor_row).rev().collect()
+        };
+        let cursors = rows
+            .into_iter()
+            .map(|row| {
+                let line_start = self.text.line_start_offset(row);
+                let start = self.offset_for_x(row, anchor_x).unwrap_or(line_start);
+                let end = self.offset_for_x(row, x).unwrap_or(line_start);
+                Cursor::new(start.min(end)..start.max(end), end < start)
+            })
+            .collect::<Vec<_>>();
+
+        // The cursor on the row of the mouse is the primary cursor.
+        let primary = cursors.len() - 1;
+        self.set_cursors(cursors, primary, cx);
+    }
+
     fn is_valid_input(&self, new_text: &str, cx: &mut Context<Self>) -> bool {
         if new_text.is_empty() {
             return true;
@@ -2298,6 +3080,21 @@ impl EntityInputHandler for InputState {
             return;
         }
 
+        // Typing with multiple cursors, insert the text at every cursor.
+        if range_utf16.is_none() && !self.cursors.is_empty() {
+            self.for_each_cursor(window, cx, |this, window, cx| {
+                this.replace_text_in_range(None, new_text, window, cx)
+            });
+            return;
+        }
+
+        if range_utf16.is_none()
+            && self.ime_marked_range.is_none()
+            && self.type_with_auto_pairs(new_text, window, cx)
+        {
+            return;
+        }
+
         self.pause_blink_cursor(cx);
 
         let range = range_utf16
@@ -2326,9 +3123,15 @@ impl EntityInputHandler for InputState {
         }
 
         self.push_history(&old_text, &range, &new_text);
+        self.shift_cursors(&range, new_text.len());
+        self.shift_folds(&old_text, &range, &new_text);
         if let Some(diagnostics) = self.mode.diagnostics_mut() {
             diagnostics.reset(&self.text)
         }
+        if let Some(semantic_tokens) = self.mode.semantic_tokens_mut() {
+            semantic_tokens.edit(&range, new_text.len());
+        }
+        self.update_diff(cx);
         self.text_wrapper.update(&self.text, false, cx);
         self.mode
             .update_highlighter(&range, &self.text, &new_text, true, cx);
@@ -2336,9 +3139,11 @@ impl EntityInputHandler for InputState {
         self.ime_marked_range.take();
         self.update_preferred_column();
         self.update_scroll_offset(None, cx);
-        self.update_search(cx);
+        self.update_search(&range, new_text.len(), cx);
         self.mode.update_auto_grow(&self.text_wrapper);
         self.handle_completion_trigger(&range, &new_text, window, cx);
+        self.handle_signature_help_trigger(&new_text, window, cx);
+        self.refresh_semantic_tokens(window, cx);
         cx.emit(InputEvent::Change);
         cx.notify();
     }
@@ -2372,9 +3177,15 @@ impl EntityInputHandler for InputState {
         }
 
         self.push_history(&old_text, &range, new_text);
+        self.shift_cursors(&range, new_text.len());
+        self.shift_folds(&old_text, &range, new_text);
         if let Some(diagnostics) = self.mode.diagnostics_mut() {
             diagnostics.reset(&self.text)
         }
+        if let Some(semantic_tokens) = self.mode.semantic_tokens_mut() {
+            semantic_tokens.edit(&range, new_text.len());
+        }
+        self.update_diff(cx);
         self.text_wrapper.update(&self.text, false, cx);
         self.mode
             .update_highlighter(&range, &self.text, &new_text, true, cx);
@@ -2415,10 +3226,14 @@ impl EntityInputHandler for InputState {
         let mut y_offset = last_layout.visible_top;
         let mut index_offset = last_layout.visible_range_offset.start;
 
-        for line in last_layout.lines.iter() {
+        for (ix, line) in last_layout.lines.iter().enumerate() {
             if start_origin.is_some() && end_origin.is_some() {
                 break;
             }
+            if last_layout.is_hidden(ix) {
+                index_offset += line.len() + 1;
+                continue;
+            }
 
             if start_origin.is_none() {
                 if let Some(p) =
@@ -2484,6 +3299,7 @@ impl Render for InputState {
         self.text_wrapper.update(&self.text, false, cx);
         self.mode
             .update_highlighter(&(0..0), &self.text, "", false, cx);
+        self.update_fold_ranges();
 
         div()
             .id("input-state")
@@ -2493,6 +3309,8 @@ impl Render for InputState {
             .overflow_x_hidden()
             .child(TextElement::new(cx.entity().clone()).placeholder(self.placeholder.clone()))
             .children(self.diagnostic_popover.clone())
+            .children(self.hover_popover.clone())
+            .children(self.signature_help_popover.clone())
             .children(self.context_menu.as_ref().map(|menu| menu.render()))
     }
 }
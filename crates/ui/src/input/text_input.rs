@@ -9,8 +9,10 @@ use crate::button::{Button, ButtonVariants as _};
 use crate::indicator::Indicator;
 use crate::input::clear_button;
 use crate::input::element::{LINE_NUMBER_RIGHT_MARGIN, RIGHT_MARGIN};
+use crate::input::minimap::{Minimap, MINIMAP_WIDTH};
+use crate::input::overview_ruler::OverviewRuler;
 use std::cmp::{max, min};
-use crate::scroll::Scrollbar;
+use crate::scroll::{self, Scrollbar};
 use crate::{h_flex, StyledExt};
 use crate::{v_flex, ActiveTheme};
 use crate::{IconName, Size};
@@ -200,15 +202,46 @@ impl TextInput {
                         Scrollbar::vertical(&state.scroll_state, &state.scroll_handle)
                     };
 
-                    this.relative().child(
+                    // Show the overview ruler under the vertical scrollbar.
+                    let overview_ruler = state.mode.overview_ruler().then(|| {
+                        div()
+                            .absolute()
+                            .top_0()
+                            .right_0()
+                            .bottom(if state.soft_wrap {
+                                px(0.)
+                            } else {
+                                scroll::WIDTH
+                            })
+                            .w(scroll::WIDTH)
+                            .child(OverviewRuler::new(input_state.clone()))
+                    });
+
+                    let minimap = state.mode.minimap().then(|| {
                         div()
                             .absolute()
                             .top(-paddings.top + MIN_SCROLL_PADDING)
-                            .left(left)
-                            .right(-paddings.right + MIN_SCROLL_PADDING)
+                            .right(-paddings.right + MIN_SCROLL_PADDING + scroll::WIDTH)
                             .bottom(-paddings.bottom + MIN_SCROLL_PADDING)
-                            .child(scrollbar.scroll_size(scroll_size)),
-                    )
+                            .w(MINIMAP_WIDTH)
+                            .child(Minimap::new(input_state.clone()))
+                    });
+
+                    this.relative()
+                        .when(state.mode.minimap(), |this| {
+                            this.pr(MINIMAP_WIDTH + scroll::WIDTH)
+                        })
+                        .children(minimap)
+                        .child(
+                            div()
+                                .absolute()
+                                .top(-paddings.top + MIN_SCROLL_PADDING)
+                                .left(left)
+                                .right(-paddings.right + MIN_SCROLL_PADDING)
+                                .bottom(-paddings.bottom + MIN_SCROLL_PADDING)
+                                .children(overview_ruler)
+                                .child(scrollbar.scroll_size(scroll_size)),
+                        )
                 } else {
                     this
                 }
@@ -321,6 +354,10 @@ impl RenderOnce for TextInput {
                             .on_action(window.listener_for(&self.state, InputState::outdent_block))
                     })
                     .on_action(window.listener_for(&self.state, InputState::toggle_code_actions))
+                    .when(state.mode.is_code_editor(), |this| {
+                        this.on_action(window.listener_for(&self.state, InputState::toggle_comment))
+                            .on_action(window.listener_for(&self.state, InputState::revert_hunk))
+                    })
             })
             .on_action(window.listener_for(&self.state, InputState::left))
             .on_action(window.listener_for(&self.state, InputState::right))
@@ -334,6 +371,22 @@ impl RenderOnce for TextInput {
                     .on_action(window.listener_for(&self.state, InputState::page_up))
                     .on_action(window.listener_for(&self.state, InputState::page_down))
             })
+            .when(state.mode.is_code_editor(), |this| {
+                this.on_action(window.listener_for(&self.state, InputState::add_cursor_above))
+                    .on_action(window.listener_for(&self.state, InputState::add_cursor_below))
+                    .on_action(
+                        window.listener_for(&self.state, InputState::select_next_occurrence),
+                    )
+                    .on_action(
+                        window.listener_for(&self.state, InputState::select_all_occurrences),
+                    )
+                    .on_action(window.listener_for(&self.state, InputState::go_to_definition))
+                    .on_action(window.listener_for(&self.state, InputState::fold))
+                    .on_action(window.listener_for(&self.state, InputState::unfold))
+                    .on_action(window.listener_for(&self.state, InputState::fold_all))
+                    .on_action(window.listener_for(&self.state, InputState::unfold_all))
+                    .on_action(window.listener_for(&self.state, InputState::jump_to_bracket))
+            })
             .on_action(window.listener_for(&self.state, InputState::select_all))
             .on_action(window.listener_for(&self.state, InputState::select_to_start_of_line))
             .on_action(window.listener_for(&self.state, InputState::select_to_end_of_line))
@@ -16,6 +16,8 @@ pub(super) struct LineItem {
     /// like the `window.text_system().shape_text`. So, this value may not equal
     /// the actual rendered lines.
     wrapped_lines: Vec<Range<usize>>,
+    /// Whether this line is hidden in a folded region.
+    hidden: bool,
 }
 
 impl LineItem {
@@ -25,15 +27,31 @@ impl LineItem {
         self.line.len()
     }
 
+    /// Get the soft wrapped lines relative byte range of this line (include the first line).
+    #[inline]
+    pub(super) fn wrapped_lines(&self) -> &[Range<usize>] {
+        &self.wrapped_lines
+    }
+
     /// Get number of soft wrapped lines of this line (include the first line).
     #[inline]
     pub(super) fn lines_len(&self) -> usize {
         self.wrapped_lines.len()
     }
 
+    /// Get number of display lines of this line, 0 if the line is hidden in a folded region.
+    #[inline]
+    pub(super) fn display_lines_len(&self) -> usize {
+        if self.hidden {
+            0
+        } else {
+            self.lines_len()
+        }
+    }
+
     /// Get the height of this line item with given line height.
     pub(super) fn height(&self, line_height: Pixels) -> Pixels {
-        self.lines_len() as f32 * line_height
+        self.display_lines_len() as f32 * line_height
     }
 }
 
@@ -50,6 +68,8 @@ pub(super) struct TextWrapper {
     wrap_width: Option<Pixels>,
     /// The lines by split \n
     pub(super) lines: Vec<LineItem>,
+    /// The rows hidden in the folded regions, sorted by start.
+    hidden_rows: Vec<Range<usize>>,
 }
 
 #[allow(unused)]
@@ -62,6 +82,7 @@ impl TextWrapper {
             wrap_width,
             soft_lines: 0,
             lines: Vec::new(),
+            hidden_rows: Vec::new(),
         }
     }
 
@@ -70,7 +91,7 @@ impl TextWrapper {
         self.text = text.clone();
     }
 
-    /// Get the total number of lines including wrapped lines.
+    /// Get the total number of display lines including wrapped lines, excluding the hidden lines.
     #[inline]
     pub(super) fn len(&self) -> usize {
         self.soft_lines
@@ -138,10 +159,140 @@ impl TextWrapper {
             self.lines.push(LineItem {
                 line: line.clone(),
                 wrapped_lines,
+                hidden: false,
             });
         }
 
         self.text = text.clone();
-        self.soft_lines = self.lines.iter().map(|l| l.lines_len()).sum();
+        self.update_hidden_lines();
+    }
+
+    /// Set the rows hidden in the folded regions.
+    pub(super) fn set_hidden_rows(&mut self, mut hidden_rows: Vec<Range<usize>>) {
+        hidden_rows.sort_by_key(|rows| rows.start);
+        if self.hidden_rows == hidden_rows {
+            return;
+        }
+
+        self.hidden_rows = hidden_rows;
+        self.update_hidden_lines();
+    }
+
+    fn update_hidden_lines(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.hidden = false;
+        }
+        for rows in self.hidden_rows.iter() {
+            let rows = rows.start.min(self.lines.len())..rows.end.min(self.lines.len());
+            for line in self.lines[rows].iter_mut() {
+                line.hidden = true;
+            }
+        }
+
+        self.soft_lines = self.lines.iter().map(|l| l.display_lines_len()).sum();
+    }
+
+    /// Return true if the row (0-based) is hidden in a folded region.
+    #[inline]
+    pub(super) fn is_hidden(&self, row: usize) -> bool {
+        self.lines.get(row).map_or(false, |line| line.hidden)
+    }
+
+    /// Get the display row (0-based, including wrapped lines) of the first line of the row.
+    ///
+    /// The hidden row is mapped to the display row after its folded region.
+    pub(super) fn display_row(&self, row: usize) -> usize {
+        self.lines
+            .iter()
+            .take(row)
+            .map(|line| line.display_lines_len())
+            .sum()
+    }
+
+    /// Get the display rows (0-based, including wrapped lines) of the first line of all rows.
+    ///
+    /// Same as [`Self::display_row`] for every row, but in one pass.
+    pub(super) fn display_rows(&self) -> Vec<usize> {
+        let mut display_row = 0;
+        self.lines
+            .iter()
+            .map(|line| {
+                let row = display_row;
+                display_row += line.display_lines_len();
+                row
+            })
+            .collect()
+    }
+
+    /// Get the row (0-based) of the line displayed at the display row (0-based, including wrapped lines).
+    ///
+    /// Returns the last visible row if the display row is out of bounds.
+    pub(super) fn row_for_display_row(&self, display_row: usize) -> usize {
+        let mut last_visible_row = 0;
+        let mut display_rows = 0;
+        for (row, line) in self.lines.iter().enumerate() {
+            if line.hidden {
+                continue;
+            }
+
+            display_rows += line.display_lines_len();
+            last_visible_row = row;
+            if display_rows > display_row {
+                break;
+            }
+        }
+
+        last_visible_row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{font, px};
+
+    use super::*;
+
+    fn wrapper(lines: &[usize]) -> TextWrapper {
+        let mut wrapper = TextWrapper::new(font("Menlo"), px(14.), None);
+        wrapper.lines = lines
+            .iter()
+            .map(|wrapped| LineItem {
+                line: Rope::from("foo"),
+                wrapped_lines: (0..*wrapped).map(|_| 0..3).collect(),
+                hidden: false,
+            })
+            .collect();
+        wrapper.update_hidden_lines();
+        wrapper
+    }
+
+    #[test]
+    fn test_display_rows_with_hidden_rows() {
+        // The 2nd line is wrapped to 2 lines.
+        let mut wrapper = wrapper(&[1, 2, 1, 1, 1]);
+        assert_eq!(wrapper.len(), 6);
+        assert_eq!(wrapper.display_row(2), 3);
+        assert_eq!(wrapper.row_for_display_row(2), 1);
+        assert_eq!(wrapper.row_for_display_row(3), 2);
+        assert_eq!(wrapper.display_rows(), vec![0, 1, 3, 4, 5]);
+
+        wrapper.set_hidden_rows(vec![1..3]);
+        assert_eq!(wrapper.len(), 3);
+        assert!(!wrapper.is_hidden(0));
+        assert!(wrapper.is_hidden(1));
+        assert!(wrapper.is_hidden(2));
+        assert!(!wrapper.is_hidden(3));
+        assert_eq!(wrapper.display_row(1), 1);
+        assert_eq!(wrapper.display_row(3), 1);
+        assert_eq!(wrapper.display_row(4), 2);
+        assert_eq!(wrapper.row_for_display_row(0), 0);
+        assert_eq!(wrapper.row_for_display_row(1), 3);
+        assert_eq!(wrapper.row_for_display_row(2), 4);
+        assert_eq!(wrapper.row_for_display_row(10), 4);
+        assert_eq!(wrapper.display_rows(), vec![0, 1, 1, 1, 2]);
+        assert_eq!(wrapper.line(1).unwrap().height(px(20.)), px(0.));
+
+        wrapper.set_hidden_rows(vec![]);
+        assert_eq!(wrapper.len(), 6);
     }
 }
@@ -0,0 +1,51 @@
+use crate::json_ui::binding::json_from_ui_value;
+use crate::json_ui::schema::*;
+use crate::json_ui::JsonCanvas;
+use gpui::{Context, SharedString, Window};
+use serde_json::Value;
+use std::rc::Rc;
+
+/// A named action referenced by an `on_click` or `on_change` prop.
+///
+/// Either a plain name, `"on_click": "save"`, or an object with arguments,
+/// `"on_click": { "action": "delete", "args": { "id": "{{todo.id}}" } }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiAction {
+    pub name: SharedString,
+    pub args: Value,
+}
+
+impl UiAction {
+    pub fn from_value(value: &UiValue) -> Option<Self> {
+        match value {
+            UiValue::String(name) if !name.is_empty() => Some(Self {
+                name: name.clone().into(),
+                args: Value::Null,
+            }),
+            UiValue::Object(obj) => {
+                let name = obj.get("action").and_then(|v| v.as_string())?;
+                Some(Self {
+                    name: name.to_string().into(),
+                    args: obj.get("args").map(json_from_ui_value).unwrap_or(Value::Null),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Emitted by [`JsonCanvas`] whenever an action is dispatched.
+#[derive(Debug, Clone)]
+pub struct UiActionEvent {
+    /// The action name.
+    pub action: SharedString,
+    /// The (bound) `args` of the action, or `null`.
+    pub args: Value,
+    /// The new value for `on_change` actions.
+    pub value: Option<Value>,
+    /// The `id` of the component, or its position in the tree.
+    pub source: SharedString,
+}
+
+pub(crate) type UiActionHandler =
+    Rc<dyn Fn(&mut JsonCanvas, &UiActionEvent, &mut Window, &mut Context<JsonCanvas>)>;
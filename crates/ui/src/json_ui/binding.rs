@@ -0,0 +1,490 @@
+use crate::json_ui::schema::*;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+/// A local variable introduced by a `for` directive.
+#[derive(Debug, Clone)]
+enum Local {
+    /// An alias for a path in the data model, e.g. `item` => `todos.3`.
+    Alias(String),
+    /// A plain value, e.g. the loop index.
+    Value(Value),
+}
+
+/// The locals of a [`DataScope`], which can be captured and restored later
+/// (e.g. to render the content of a modal opened from inside a `for` loop).
+#[derive(Debug, Clone, Default)]
+pub struct DataLocals(Vec<(String, Local)>);
+
+/// A parsed `for` directive: `"item in items"` or `"item, index in items"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForDirective {
+    pub item: String,
+    pub index: Option<String>,
+    pub source: String,
+}
+
+impl ForDirective {
+    pub fn parse(expr: &str) -> Option<Self> {
+        let expr = strip_braces(expr);
+        let (vars, source) = expr.split_once(" in ")?;
+        let source = source.trim();
+        let mut vars = vars.trim().trim_matches(|c| c == '(' || c == ')').split(',');
+        let item = vars.next()?.trim();
+        let index = vars.next().map(|s| s.trim().to_string());
+        if item.is_empty() || source.is_empty() || vars.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            item: item.to_string(),
+            index,
+            source: source.to_string(),
+        })
+    }
+}
+
+/// The data model a JSON UI document is rendered against, plus the locals of
+/// the enclosing `for` directives.
+///
+/// Paths are dot separated, with optional `[n]` indexes: `user.name`, `todos[0].title`.
+pub struct DataScope<'a> {
+    root: &'a Value,
+    locals: DataLocals,
+}
+
+impl<'a> DataScope<'a> {
+    pub fn new(root: &'a Value) -> Self {
+        Self {
+            root,
+            locals: DataLocals::default(),
+        }
+    }
+
+    pub fn with_locals(root: &'a Value, locals: DataLocals) -> Self {
+        Self { root, locals }
+    }
+
+    pub fn locals(&self) -> &DataLocals {
+        &self.locals
+    }
+
+    /// Returns the number of items the `for` directive iterates over.
+    pub fn for_len(&self, directive: &ForDirective) -> usize {
+        match self.lookup(&directive.source) {
+            Some(Value::Array(items)) => items.len(),
+            _ => 0,
+        }
+    }
+
+    /// Bring the `ix`th item of a `for` directive into scope, see [`DataScope::pop_for`].
+    pub fn push_for(&mut self, directive: &ForDirective, ix: usize) {
+        let item = match self.resolve_path(&directive.source) {
+            Some(path) => Local::Alias(join_path(&path, &ix.to_string())),
+            None => Local::Value(
+                self.lookup(&directive.source)
+                    .and_then(|items| items.get(ix).cloned())
+                    .unwrap_or(Value::Null),
+            ),
+        };
+        self.locals.0.push((directive.item.clone(), item));
+        if let Some(index) = &directive.index {
+            self.locals.0.push((index.clone(), Local::Value(Value::from(ix))));
+        }
+    }
+
+    pub fn pop_for(&mut self, directive: &ForDirective) {
+        self.locals.0.pop();
+        if directive.index.is_some() {
+            self.locals.0.pop();
+        }
+    }
+
+    /// Resolve a path expression to an absolute path in the data model.
+    ///
+    /// Returns `None` if the path goes through a local that is not an alias
+    /// (such as a loop index), since it cannot be written back.
+    pub fn resolve_path(&self, expr: &str) -> Option<String> {
+        let segments = split_path(strip_braces(expr));
+        let (first, rest) = segments.split_first()?;
+
+        let base = match self.local(first) {
+            Some(Local::Alias(path)) => path.clone(),
+            Some(Local::Value(_)) => return None,
+            None => first.clone(),
+        };
+        Some(rest.iter().fold(base, |path, segment| join_path(&path, segment)))
+    }
+
+    /// Look up the value of a path expression.
+    pub fn lookup(&self, expr: &str) -> Option<Value> {
+        let segments = split_path(strip_braces(expr));
+        let (first, rest) = segments.split_first()?;
+
+        match self.local(first) {
+            Some(Local::Alias(path)) => {
+                let mut segments = split_path(path);
+                segments.extend(rest.iter().cloned());
+                get_segments(self.root, &segments).cloned()
+            }
+            Some(Local::Value(value)) => get_segments(value, rest).cloned(),
+            None => get_segments(self.root, &segments).cloned(),
+        }
+    }
+
+    fn local(&self, name: &str) -> Option<&Local> {
+        self.locals
+            .0
+            .iter()
+            .rev()
+            .find(|(local, _)| local == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Evaluate an expression.
+    ///
+    /// Supports paths, literals (`'text'`, `"text"`, numbers, `true`, `false`, `null`),
+    /// `!`, `==`, `!=`, `<`, `<=`, `>`, `>=`, `&&` and `||`.
+    pub fn eval(&self, expr: &str) -> Value {
+        let expr = strip_braces(expr);
+
+        if let Some((lhs, rhs)) = split_operator(expr, "||") {
+            let lhs = self.eval(lhs);
+            return if is_truthy(&lhs) { lhs } else { self.eval(rhs) };
+        }
+        if let Some((lhs, rhs)) = split_operator(expr, "&&") {
+            let lhs = self.eval(lhs);
+            return if is_truthy(&lhs) { self.eval(rhs) } else { lhs };
+        }
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if let Some((lhs, rhs)) = split_operator(expr, op) {
+                let (lhs, rhs) = (self.eval(lhs), self.eval(rhs));
+                return Value::Bool(compare(&lhs, &rhs, op));
+            }
+        }
+        if let Some(expr) = expr.strip_prefix('!') {
+            return Value::Bool(!is_truthy(&self.eval(expr)));
+        }
+
+        match expr {
+            "true" => return Value::Bool(true),
+            "false" => return Value::Bool(false),
+            "null" => return Value::Null,
+            _ => {}
+        }
+        if expr.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+            if let Ok(n) = expr.parse::<f64>() {
+                return Number::from_f64(n).map(Value::Number).unwrap_or(Value::Null);
+            }
+        }
+        for quote in ['\'', '"'] {
+            if expr.len() >= 2 && expr.starts_with(quote) && expr.ends_with(quote) {
+                return Value::String(expr[1..expr.len() - 1].to_string());
+            }
+        }
+
+        self.lookup(expr).unwrap_or(Value::Null)
+    }
+
+    /// Evaluate an `if` directive.
+    pub fn eval_condition(&self, expr: &str) -> bool {
+        is_truthy(&self.eval(expr))
+    }
+
+    /// Replace every `{{expr}}` in the string.
+    ///
+    /// If the whole string is a single expression the value keeps its type,
+    /// so `"{{count}}"` binds to a number rather than a string.
+    pub fn interpolate(&self, text: &str) -> UiValue {
+        let trimmed = text.trim();
+        if trimmed.starts_with("{{")
+            && trimmed.ends_with("}}")
+            && trimmed[2..trimmed.len() - 2].find("{{").is_none()
+        {
+            return ui_value_from_json(&self.eval(trimmed));
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            out.push_str(&rest[..start]);
+            out.push_str(&value_to_text(&self.eval(&rest[start + 2..start + end])));
+            rest = &rest[start + end + 2..];
+        }
+        out.push_str(rest);
+        UiValue::String(out)
+    }
+
+    pub fn bind_value(&self, value: &UiValue) -> UiValue {
+        match value {
+            UiValue::String(s) if s.contains("{{") => self.interpolate(s),
+            UiValue::Array(items) => UiValue::Array(items.iter().map(|v| self.bind_value(v)).collect()),
+            UiValue::Object(obj) => UiValue::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), self.bind_value(v)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+
+    /// Bind the props and text children of a component.
+    ///
+    /// Component children are left as they are, they are bound when rendered,
+    /// since they may introduce their own `for` locals.
+    pub fn bind_component(&self, component: &UiComponent) -> UiComponent {
+        let mut bound = component.clone();
+        bound.props = component
+            .props
+            .iter()
+            .map(|(key, value)| {
+                // The bind path is written back to, so keep it as an expression.
+                if key == "bind" {
+                    (key.clone(), value.clone())
+                } else {
+                    (key.clone(), self.bind_value(value))
+                }
+            })
+            .collect::<HashMap<_, _>>();
+        for child in bound.children.iter_mut() {
+            if let UiChild::Text(text) = child {
+                if text.contains("{{") {
+                    *text = value_to_text(&json_from_ui_value(&self.interpolate(text)));
+                }
+            }
+        }
+        bound
+    }
+}
+
+/// Set the value at `path` in `root`, creating objects along the way.
+pub fn set_path(root: &mut Value, path: &str, value: Value) {
+    let mut current = root;
+    for segment in split_path(path) {
+        if let Value::Array(items) = current {
+            match segment.parse::<usize>() {
+                Ok(ix) if ix < items.len() => {
+                    current = &mut items[ix];
+                    continue;
+                }
+                _ => return,
+            }
+        }
+
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .expect("just made an object")
+            .entry(segment)
+            .or_insert(Value::Null);
+    }
+    *current = value;
+}
+
+pub fn ui_value_from_json(value: &Value) -> UiValue {
+    match value {
+        Value::Null => UiValue::String(String::new()),
+        Value::Bool(b) => UiValue::Boolean(*b),
+        Value::Number(n) => UiValue::Number(n.as_f64().unwrap_or_default()),
+        Value::String(s) => UiValue::String(s.clone()),
+        Value::Array(items) => UiValue::Array(items.iter().map(ui_value_from_json).collect()),
+        Value::Object(obj) => UiValue::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), ui_value_from_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+pub fn json_from_ui_value(value: &UiValue) -> Value {
+    match value {
+        UiValue::String(s) => Value::String(s.clone()),
+        UiValue::Number(n) => Number::from_f64(*n).map(Value::Number).unwrap_or(Value::Null),
+        UiValue::Boolean(b) => Value::Bool(*b),
+        UiValue::Array(items) => Value::Array(items.iter().map(json_from_ui_value).collect()),
+        UiValue::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), json_from_ui_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().map(|n| n != 0.).unwrap_or(false),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+/// The text form of a value, as shown when interpolated into a string.
+pub fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0. && f.abs() < 1e15 => format!("{}", f as i64),
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+fn compare(lhs: &Value, rhs: &Value, op: &str) -> bool {
+    if let (Some(a), Some(b)) = (lhs.as_f64(), rhs.as_f64()) {
+        return match op {
+            "==" => a == b,
+            "!=" => a != b,
+            "<" => a < b,
+            "<=" => a <= b,
+            ">" => a > b,
+            ">=" => a >= b,
+            _ => false,
+        };
+    }
+
+    match op {
+        "==" => lhs == rhs,
+        "!=" => lhs != rhs,
+        _ => {
+            let (a, b) = (value_to_text(lhs), value_to_text(rhs));
+            match op {
+                "<" => a < b,
+                "<=" => a <= b,
+                ">" => a > b,
+                ">=" => a >= b,
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Split at the first occurrence of `op` outside of quotes.
+fn split_operator<'e>(expr: &'e str, op: &str) -> Option<(&'e str, &'e str)> {
+    let mut quote = None;
+    for (ix, c) in expr.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if expr[ix..].starts_with(op) => {
+                // Don't split `<=` at `<`, or `!=` at `=`.
+                let next = expr[ix + op.len()..].chars().next();
+                if (op == "<" || op == ">") && next == Some('=') {
+                    continue;
+                }
+                return Some((expr[..ix].trim(), expr[ix + op.len()..].trim()));
+            }
+            None => {}
+        }
+    }
+    None
+}
+
+fn strip_braces(expr: &str) -> &str {
+    let expr = expr.trim();
+    expr.strip_prefix("{{")
+        .and_then(|e| e.strip_suffix("}}"))
+        .unwrap_or(expr)
+        .trim()
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn join_path(base: &str, segment: &str) -> String {
+    if base.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", base, segment)
+    }
+}
+
+fn get_segments<'v>(value: &'v Value, segments: &[String]) -> Option<&'v Value> {
+    segments.iter().try_fold(value, |value, segment| match value {
+        Value::Object(obj) => obj.get(segment),
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_interpolate() {
+        let data = json!({ "user": { "name": "Jason", "age": 30 }, "todos": [{ "title": "A" }] });
+        let scope = DataScope::new(&data);
+
+        assert_eq!(scope.interpolate("{{user.name}}").as_string(), Some("Jason"));
+        assert_eq!(scope.interpolate("{{ user.age }}").as_number(), Some(30.));
+        assert_eq!(
+            scope.interpolate("Hi {{user.name}}, {{todos[0].title}}!").as_string(),
+            Some("Hi Jason, A!")
+        );
+        assert_eq!(scope.interpolate("{{missing}}").as_string(), Some(""));
+        assert_eq!(scope.interpolate("no bindings").as_string(), Some("no bindings"));
+    }
+
+    #[test]
+    fn test_eval() {
+        let data = json!({ "count": 3, "name": "a", "empty": [], "flag": true });
+        let scope = DataScope::new(&data);
+
+        assert!(scope.eval_condition("count > 2"));
+        assert!(scope.eval_condition("count >= 3 && flag"));
+        assert!(!scope.eval_condition("count != 3"));
+        assert!(scope.eval_condition("name == 'a'"));
+        assert!(scope.eval_condition("!empty"));
+        assert!(scope.eval_condition("missing || flag"));
+        assert!(!scope.eval_condition("{{ missing }}"));
+    }
+
+    #[test]
+    fn test_for_scope() {
+        let data = json!({ "todos": [{ "title": "A" }, { "title": "B" }] });
+        let directive = ForDirective::parse("todo, ix in todos").unwrap();
+        assert_eq!(directive.item, "todo");
+        assert_eq!(directive.index.as_deref(), Some("ix"));
+        assert_eq!(ForDirective::parse("todos"), None);
+
+        let mut scope = DataScope::new(&data);
+        assert_eq!(scope.for_len(&directive), 2);
+        scope.push_for(&directive, 1);
+        assert_eq!(scope.interpolate("{{ix}}: {{todo.title}}").as_string(), Some("1: B"));
+        assert_eq!(scope.resolve_path("todo.title").as_deref(), Some("todos.1.title"));
+        assert_eq!(scope.resolve_path("ix"), None);
+        scope.pop_for(&directive);
+        assert_eq!(scope.lookup("todo"), None);
+    }
+
+    #[test]
+    fn test_set_path() {
+        let mut data = json!({ "todos": [{ "done": false }] });
+        set_path(&mut data, "todos.0.done", json!(true));
+        set_path(&mut data, "form.user.name", json!("Jason"));
+        set_path(&mut data, "todos.5.done", json!(true));
+        assert_eq!(
+            data,
+            json!({ "todos": [{ "done": true }], "form": { "user": { "name": "Jason" } } })
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// JSON UI files bundled into the binary, usually created by [`crate::json_ui::include_ui!`].
+///
+/// Files are keyed by their path relative to the root document's directory,
+/// the same directory `$ref`s are resolved against.
+#[derive(Debug, Clone)]
+pub struct EmbeddedUiMap {
+    source_dir: PathBuf,
+    root: PathBuf,
+    files: HashMap<PathBuf, &'static str>,
+}
+
+impl EmbeddedUiMap {
+    /// Create an empty map for the root document `root`, found in `source_dir` at build time.
+    pub fn new(source_dir: impl Into<PathBuf>, root: impl AsRef<Path>) -> Self {
+        Self {
+            source_dir: source_dir.into(),
+            root: normalize_lexically(root.as_ref()),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Used by the `include_ui!` expansion.
+    pub fn from_entries(
+        source_dir: &'static str,
+        root: &'static str,
+        entries: &[(&'static str, &'static str)],
+    ) -> Self {
+        let mut map = Self::new(source_dir, root);
+        for (path, content) in entries {
+            map.insert(path, content);
+        }
+        map
+    }
+
+    pub fn insert(&mut self, path: impl AsRef<Path>, content: &'static str) {
+        self.files.insert(normalize_lexically(path.as_ref()), content);
+    }
+
+    /// The directory the files were embedded from.
+    pub fn source_dir(&self) -> &Path {
+        &self.source_dir
+    }
+
+    /// The root document, relative to [`EmbeddedUiMap::source_dir`].
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The root document's path on disk at build time.
+    pub fn root_path(&self) -> PathBuf {
+        self.source_dir.join(&self.root)
+    }
+
+    /// Look up a file by its path relative to the source directory, or by its absolute path.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&'static str> {
+        let path = normalize_lexically(path.as_ref());
+        let relative = path.strip_prefix(&self.source_dir).unwrap_or(&path);
+        self.files.get(relative).copied()
+    }
+
+    /// Returns true if the root document still exists on disk, e.g. when running from the source tree.
+    pub fn is_on_disk(&self) -> bool {
+        self.root_path().is_file()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Resolve `.` and `..` components without touching the file system.
+///
+/// Embedded paths may not exist at runtime, so they can't be canonicalized.
+pub(crate) fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let map = EmbeddedUiMap::from_entries(
+            "/build/assets/ui",
+            "./main.json",
+            &[
+                ("main.json", r#"{ "type": "div" }"#),
+                ("components/card.json", r#"{ "type": "text" }"#),
+            ],
+        );
+
+        assert_eq!(map.root(), Path::new("main.json"));
+        assert_eq!(map.root_path(), Path::new("/build/assets/ui/main.json"));
+        assert_eq!(map.get("main.json"), Some(r#"{ "type": "div" }"#));
+        assert_eq!(map.get("./components/../main.json"), Some(r#"{ "type": "div" }"#));
+        assert_eq!(
+            map.get("/build/assets/ui/components/card.json"),
+            Some(r#"{ "type": "text" }"#)
+        );
+        assert_eq!(map.get("/other/main.json"), None);
+        assert_eq!(map.get("missing.json"), None);
+        assert_eq!(map.len(), 2);
+    }
+}
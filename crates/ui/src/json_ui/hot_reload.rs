@@ -1,37 +1,161 @@
-use std::path::{Path, PathBuf};
+use notify::Watcher as _;
 use std::collections::HashSet;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long to wait for a burst of writes to settle before reloading.
+///
+/// Editors often save a file with several events (truncate, write, rename),
+/// so we collect everything that arrives within this window into one reload.
+pub const HOT_RELOAD_DEBOUNCE: Duration = Duration::from_millis(120);
+
+/// Normalize a path so that it can be compared with the paths reported by `notify`.
+pub(crate) fn normalize_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
 
-// Simplified hot reload manager without external file watching for now
+/// Wait for the next change, then keep collecting changes until none arrives within one
+/// `timer` period, so that a burst of events ends in a single reload.
+///
+/// Returns `None` once the watcher is dropped.
+pub(crate) async fn debounce_changes<F: Future<Output = ()>>(
+    changes: &smol::channel::Receiver<PathBuf>,
+    mut timer: impl FnMut() -> F,
+) -> Option<Vec<PathBuf>> {
+    let mut changed = vec![changes.recv().await.ok()?];
+    loop {
+        timer().await;
+        let mut received = false;
+        while let Ok(path) = changes.try_recv() {
+            if !changed.contains(&path) {
+                changed.push(path);
+            }
+            received = true;
+        }
+        if !received {
+            return Some(changed);
+        }
+    }
+}
+
+/// A file watcher that reports changes to a set of JSON UI files.
+///
+/// The parent directories of the files are watched (not the files themselves),
+/// because many editors save by writing a temp file and renaming it over the
+/// original, which would drop a watch placed on the file.
 pub struct HotReloadWatcher {
-    watched_files: HashSet<PathBuf>,
+    watcher: notify::RecommendedWatcher,
+    watched_dirs: HashSet<PathBuf>,
+    watched_files: Arc<Mutex<HashSet<PathBuf>>>,
+    rx: smol::channel::Receiver<PathBuf>,
 }
 
 impl HotReloadWatcher {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let (tx, rx) = smol::channel::unbounded();
+        let watched_files = Arc::new(Mutex::new(HashSet::<PathBuf>::new()));
+
+        let watcher = notify::recommended_watcher({
+            let watched_files = watched_files.clone();
+            move |res: notify::Result<notify::Event>| {
+                let event = match res {
+                    Ok(event) => event,
+                    Err(err) => {
+                        tracing::error!("JSON UI watcher error: {:?}", err);
+                        return;
+                    }
+                };
+
+                match event.kind {
+                    notify::EventKind::Create(_)
+                    | notify::EventKind::Modify(_)
+                    | notify::EventKind::Remove(_) => {}
+                    _ => return,
+                }
+
+                let Ok(watched_files) = watched_files.lock() else {
+                    return;
+                };
+                for path in event.paths {
+                    let path = normalize_path(path);
+                    if watched_files.contains(&path) {
+                        _ = tx.send_blocking(path);
+                    }
+                }
+            }
+        })?;
+
         Ok(Self {
-            watched_files: HashSet::new(),
+            watcher,
+            watched_dirs: HashSet::new(),
+            watched_files,
+            rx,
         })
     }
 
-    pub fn watch_directory(&mut self, _path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
-        // For now, just return Ok - we'll implement proper watching later
+    pub fn watch_directory(&mut self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let dir = normalize_path(path);
+        if self.watched_dirs.contains(&dir) {
+            return Ok(());
+        }
+
+        self.watcher.watch(&dir, notify::RecursiveMode::NonRecursive)?;
+        self.watched_dirs.insert(dir);
         Ok(())
     }
 
     pub fn add_file(&mut self, path: PathBuf) {
-        self.watched_files.insert(path);
+        let path = normalize_path(path);
+        if let Some(dir) = path.parent() {
+            if let Err(err) = self.watch_directory(dir) {
+                tracing::error!("Failed to watch {}: {}", dir.display(), err);
+            }
+        }
+
+        if let Ok(mut watched_files) = self.watched_files.lock() {
+            watched_files.insert(path);
+        }
     }
 
     pub fn remove_file(&mut self, path: &Path) {
-        self.watched_files.remove(path);
+        let path = normalize_path(path);
+        if let Ok(mut watched_files) = self.watched_files.lock() {
+            watched_files.remove(&path);
+        }
     }
 
+    /// Returns true if the given file is currently being watched.
+    pub fn is_watching(&self, path: &Path) -> bool {
+        let path = normalize_path(path);
+        self.watched_files
+            .lock()
+            .map(|files| files.contains(&path))
+            .unwrap_or(false)
+    }
+
+    /// Drain all pending change notifications, without blocking.
     pub fn poll_changes(&self) -> Vec<PathBuf> {
-        // For now, return empty - we'll implement proper polling later
-        Vec::new()
+        let mut changes = Vec::new();
+        while let Ok(path) = self.rx.try_recv() {
+            if !changes.contains(&path) {
+                changes.push(path);
+            }
+        }
+        changes
+    }
+
+    /// Returns a receiver of changed file paths, for use in an async task.
+    pub fn changes(&self) -> smol::channel::Receiver<PathBuf> {
+        self.rx.clone()
     }
 }
 
+/// Keeps a [`HotReloadWatcher`] in sync with the files a [`crate::json_ui::UiParser`]
+/// has loaded, including every file pulled in through `$ref`.
 pub struct HotReloadManager {
     watcher: Option<HotReloadWatcher>,
     dependencies: HashSet<PathBuf>,
@@ -45,18 +169,30 @@ impl HotReloadManager {
         }
     }
 
+    pub fn is_watching(&self) -> bool {
+        self.watcher.is_some()
+    }
+
     pub fn start_watching(&mut self, root_path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
         let mut watcher = HotReloadWatcher::new()?;
 
-        let parent_dir = root_path.as_ref().parent().unwrap_or(Path::new("."));
-        watcher.watch_directory(parent_dir)?;
-        watcher.add_file(root_path.as_ref().to_path_buf());
+        let root_path = normalize_path(root_path);
+        watcher.add_file(root_path.clone());
+        for dep in &self.dependencies {
+            watcher.add_file(dep.clone());
+        }
+        self.dependencies.insert(root_path);
 
         self.watcher = Some(watcher);
         Ok(())
     }
 
+    pub fn stop_watching(&mut self) {
+        self.watcher = None;
+    }
+
     pub fn add_dependency(&mut self, path: PathBuf) {
+        let path = normalize_path(path);
         self.dependencies.insert(path.clone());
         if let Some(ref mut watcher) = self.watcher {
             watcher.add_file(path);
@@ -64,12 +200,36 @@ impl HotReloadManager {
     }
 
     pub fn remove_dependency(&mut self, path: &Path) {
-        self.dependencies.remove(path);
+        let path = normalize_path(path);
+        self.dependencies.remove(&path);
         if let Some(ref mut watcher) = self.watcher {
-            watcher.remove_file(path);
+            watcher.remove_file(&path);
+        }
+    }
+
+    /// Replace the watched set with `paths`, watching new files and dropping stale ones.
+    pub fn set_dependencies(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        let paths: HashSet<PathBuf> = paths.into_iter().map(normalize_path).collect();
+
+        let stale = self
+            .dependencies
+            .difference(&paths)
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in stale {
+            self.remove_dependency(&path);
+        }
+        for path in paths {
+            if !self.dependencies.contains(&path) {
+                self.add_dependency(path);
+            }
         }
     }
 
+    pub fn dependencies(&self) -> &HashSet<PathBuf> {
+        &self.dependencies
+    }
+
     pub fn check_for_changes(&self) -> Vec<PathBuf> {
         if let Some(ref watcher) = self.watcher {
             watcher.poll_changes()
@@ -78,12 +238,128 @@ impl HotReloadManager {
         }
     }
 
+    /// Returns a receiver of changed paths, or `None` if not watching.
+    pub fn changes(&self) -> Option<smol::channel::Receiver<PathBuf>> {
+        self.watcher.as_ref().map(|watcher| watcher.changes())
+    }
+
     pub fn clear_dependencies(&mut self) {
-        self.dependencies.clear();
         if let Some(ref mut watcher) = self.watcher {
             for dep in &self.dependencies {
                 watcher.remove_file(dep);
             }
         }
+        self.dependencies.clear();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("json_ui_hot_reload_{}_{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("parts")).unwrap();
+        dir
+    }
+
+    /// Poll the manager until `path` is reported, or give up after a few seconds.
+    fn wait_for_change(manager: &HotReloadManager, path: &Path) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if manager.check_for_changes().iter().any(|changed| changed == path) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        let dir = temp_dir("normalize");
+        fs::write(dir.join("main.json"), "{}").unwrap();
+
+        let canonical = normalize_path(dir.join("main.json"));
+        assert!(canonical.is_absolute());
+        assert_eq!(normalize_path(dir.join("parts/../main.json")), canonical);
+        assert_eq!(normalize_path(dir.join("./main.json")), canonical);
+        // Missing files are kept as they are.
+        assert_eq!(normalize_path(dir.join("missing.json")), dir.join("missing.json"));
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_set_dependencies() {
+        let dir = temp_dir("dependencies");
+        for file in ["main.json", "parts/a.json", "parts/b.json"] {
+            fs::write(dir.join(file), "{}").unwrap();
+        }
+
+        let mut manager = HotReloadManager::new();
+        manager.start_watching(dir.join("parts/../main.json")).unwrap();
+        let main = normalize_path(dir.join("main.json"));
+        assert!(manager.dependencies().contains(&main));
+
+        manager.set_dependencies([
+            main.clone(),
+            dir.join("parts/a.json"),
+            dir.join("parts/./b.json"),
+        ]);
+        let watcher = manager.watcher.as_ref().unwrap();
+        assert_eq!(manager.dependencies().len(), 3);
+        assert!(watcher.is_watching(&dir.join("parts/b.json")));
+
+        // Files no longer referenced stop being watched.
+        manager.set_dependencies([main.clone(), dir.join("parts/b.json")]);
+        let watcher = manager.watcher.as_ref().unwrap();
+        assert_eq!(manager.dependencies().len(), 2);
+        assert!(!watcher.is_watching(&dir.join("parts/a.json")));
+        assert!(watcher.is_watching(&dir.join("main.json")));
+
+        // Only changes to the watched files are reported, by their canonical path.
+        fs::write(dir.join("parts/a.json"), r#"{ "type": "div" }"#).unwrap();
+        fs::write(dir.join("parts/b.json"), r#"{ "type": "div" }"#).unwrap();
+        assert!(wait_for_change(&manager, &normalize_path(dir.join("parts/b.json"))));
+        std::thread::sleep(Duration::from_millis(100));
+        let a = normalize_path(dir.join("parts/a.json"));
+        assert!(!manager.check_for_changes().contains(&a));
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_debounce_changes() {
+        let (tx, rx) = smol::channel::unbounded();
+        let a = PathBuf::from("/ui/a.json");
+        let b = PathBuf::from("/ui/b.json");
+        let c = PathBuf::from("/ui/c.json");
+
+        tx.send_blocking(a.clone()).unwrap();
+        tx.send_blocking(b.clone()).unwrap();
+        tx.send_blocking(a.clone()).unwrap();
+
+        // Events that arrive while waiting are merged into the same reload.
+        let mut burst = vec![c.clone(), a.clone()];
+        let mut ticks = 0;
+        let changed = smol::block_on(debounce_changes(&rx, || {
+            ticks += 1;
+            if let Some(path) = burst.pop() {
+                tx.send_blocking(path).unwrap();
+            }
+            async {}
+        }));
+        assert_eq!(changed, Some(vec![a.clone(), b.clone(), c.clone()]));
+        assert_eq!(ticks, 3);
+
+        // The next event starts a new reload.
+        tx.send_blocking(b.clone()).unwrap();
+        let changed = smol::block_on(debounce_changes(&rx, || async {}));
+        assert_eq!(changed, Some(vec![b]));
+
+        drop(tx);
+        assert_eq!(smol::block_on(debounce_changes(&rx, || async {})), None);
+    }
+}
@@ -0,0 +1,19 @@
+/// Embed a JSON UI document, and every file it references with `$ref`, into the binary.
+///
+/// The path is relative to the calling crate's `Cargo.toml`. The files are embedded with
+/// `include_str!`, so editing any of them rebuilds the crate.
+///
+/// ```ignore
+/// use gpui_component::json_ui::{include_ui, JsonCanvas};
+///
+/// let canvas = cx.new(|cx| {
+///     let mut canvas = JsonCanvas::from_embedded(include_ui!("assets/ui/main.json"));
+///     canvas.load().ok();
+///     canvas.start_hot_reload(cx).ok();
+///     canvas
+/// });
+/// ```
+///
+/// Release builds always render the embedded files. Debug builds read the files from
+/// disk while they exist, so hot reload keeps working during development.
+pub use gpui_component_macros::include_ui;
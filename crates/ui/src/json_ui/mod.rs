@@ -1,23 +1,50 @@
+use crate::v_flex;
 use gpui::*;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 pub mod schema;
 pub mod parser;
 pub mod renderer;
 pub mod hot_reload;
+pub mod binding;
+pub mod action;
+pub mod validation;
+pub mod serializer;
+pub mod style;
+mod embedded_map;
+mod include_ui;
 
 pub use schema::*;
 pub use parser::*;
 pub use renderer::*;
 pub use hot_reload::*;
+pub use binding::*;
+pub use action::*;
+pub use validation::*;
+pub use serializer::*;
+pub use style::*;
+pub use embedded_map::*;
+pub use include_ui::*;
 
 pub struct JsonCanvas {
     root_path: PathBuf,
     current_ui: Option<UiComponent>,
+    /// The error of the last failed load, shown above the last good UI.
+    load_error: Option<SharedString>,
     hot_reload_manager: HotReloadManager,
     parser: UiParser,
+    pub(crate) widget_states: UiWidgetStates,
+    /// The data model that `{{path}}` expressions, `if`/`for` directives and `bind` props refer to.
+    data: Value,
+    action_handlers: HashMap<SharedString, UiActionHandler>,
+    _reload_task: Option<Task<()>>,
 }
 
+impl EventEmitter<UiActionEvent> for JsonCanvas {}
+
 impl JsonCanvas {
     pub fn new(root_path: impl AsRef<Path>) -> Self {
         let root_path = root_path.as_ref().to_path_buf();
@@ -26,27 +53,192 @@ impl JsonCanvas {
         Self {
             root_path: root_path.clone(),
             current_ui: None,
+            load_error: None,
             hot_reload_manager: HotReloadManager::new(),
             parser: UiParser::new(base_path),
+            widget_states: UiWidgetStates::default(),
+            data: Value::Null,
+            action_handlers: HashMap::new(),
+            _reload_task: None,
         }
     }
 
-    pub fn load(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let ui = self.parser.parse_document(&self.root_path)?;
-        self.current_ui = Some(ui);
-        Ok(())
+    /// Create a canvas for files bundled with [`include_ui!`].
+    ///
+    /// Debug builds read the files from disk instead while they exist, so that
+    /// [`JsonCanvas::start_hot_reload`] keeps working during development.
+    pub fn from_embedded(map: EmbeddedUiMap) -> Self {
+        if cfg!(debug_assertions) && map.is_on_disk() {
+            return Self::new(map.root_path());
+        }
+
+        let mut canvas = Self::new(map.root_path());
+        canvas.parser = UiParser::with_embedded(map);
+        canvas
+    }
+
+    /// Set the initial data model.
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn data(&self) -> &Value {
+        &self.data
+    }
+
+    pub fn set_data(&mut self, data: Value, cx: &mut Context<Self>) {
+        self.data = data;
+        cx.notify();
+    }
+
+    /// Set the value at a path of the data model, e.g. `user.name` or `todos[0].done`.
+    pub fn set_data_path(&mut self, path: &str, value: Value, cx: &mut Context<Self>) {
+        set_path(&mut self.data, path, value);
+        cx.notify();
+    }
+
+    /// Register a handler for the action `name`, referenced by `on_click`/`on_change` props.
+    ///
+    /// Every dispatched action is also emitted as a [`UiActionEvent`].
+    pub fn on_action(
+        &mut self,
+        name: impl Into<SharedString>,
+        handler: impl Fn(&mut Self, &UiActionEvent, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.action_handlers.insert(name.into(), Rc::new(handler));
+    }
+
+    pub(crate) fn dispatch_action(
+        &mut self,
+        action: &UiAction,
+        source: SharedString,
+        value: Option<Value>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let event = UiActionEvent {
+            action: action.name.clone(),
+            args: action.args.clone(),
+            value,
+            source,
+        };
+
+        match self.action_handlers.get(&action.name).cloned() {
+            Some(handler) => handler(self, &event, window, cx),
+            None => tracing::warn!("JSON UI: no handler registered for action `{}`", action.name),
+        }
+        cx.emit(event);
+        cx.notify();
+    }
+
+    /// Write a widget's new value to its `bind` path, and dispatch its `on_change` action.
+    pub(crate) fn commit_change(
+        &mut self,
+        key: &SharedString,
+        value: Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let binding = self.widget_states.bindings.get(key).cloned().unwrap_or_default();
+        if let Some(path) = &binding.path {
+            // Ignore echoes of a value we just pushed into the widget from the model.
+            if DataScope::new(&self.data).lookup(path).as_ref() == Some(&value) {
+                return;
+            }
+            set_path(&mut self.data, path, value.clone());
+        }
+        if let Some(action) = &binding.on_change {
+            self.dispatch_action(action, key.clone(), Some(value), window, cx);
+        }
+        cx.notify();
     }
 
-    pub fn start_hot_reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load(&mut self) -> Result<(), UiError> {
+        self.parser.clear_dependencies();
+        let result = self.parser.parse_document(&self.root_path);
+
+        // Keep watching whatever was read, even if a `$ref` failed to parse,
+        // so that fixing the broken file triggers a reload.
+        if self.hot_reload_manager.is_watching() {
+            let mut deps = self.parser.dependencies().clone();
+            deps.insert(self.root_path.clone());
+            self.hot_reload_manager.set_dependencies(deps);
+        }
+
+        match result {
+            Ok(ui) => {
+                self.current_ui = Some(ui);
+                self.load_error = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.load_error = Some(err.to_string().into());
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns the validation warnings of the loaded files.
+    pub fn diagnostics(&self) -> impl Iterator<Item = &UiDiagnostic> {
+        self.parser.diagnostics()
+    }
+
+    /// Start watching the root document and every file it references.
+    ///
+    /// Changes are debounced by [`HOT_RELOAD_DEBOUNCE`], then the affected cache
+    /// entries are invalidated and the canvas is reloaded and notified.
+    ///
+    /// Embedded files never change, so this does nothing for them.
+    pub fn start_hot_reload(&mut self, cx: &mut Context<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        if self.parser.is_embedded() {
+            return Ok(());
+        }
+
         self.hot_reload_manager.start_watching(&self.root_path)?;
+        let mut deps = self.parser.dependencies().clone();
+        deps.insert(self.root_path.clone());
+        self.hot_reload_manager.set_dependencies(deps);
+
+        let Some(changes) = self.hot_reload_manager.changes() else {
+            return Ok(());
+        };
+
+        self._reload_task = Some(cx.spawn(async move |this, cx| {
+            let executor = cx.background_executor().clone();
+            while let Some(changed) =
+                debounce_changes(&changes, || executor.timer(HOT_RELOAD_DEBOUNCE)).await
+            {
+                let result = this.update(cx, |this, cx| this.apply_changes(&changed, cx));
+                if result.is_err() {
+                    break;
+                }
+            }
+        }));
+
         Ok(())
     }
 
-    pub fn check_and_reload(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+    pub fn stop_hot_reload(&mut self) {
+        self._reload_task = None;
+        self.hot_reload_manager.stop_watching();
+    }
+
+    fn apply_changes(&mut self, changed: &[PathBuf], cx: &mut Context<Self>) {
+        self.parser.invalidate(changed);
+        tracing::info!("Reloading JSON UI, changed: {:?}", changed);
+        if let Err(err) = self.load() {
+            tracing::error!("Failed to reload JSON UI: {}", err);
+        }
+        cx.notify();
+    }
+
+    /// Synchronously apply pending file changes, for canvases not driven by [`JsonCanvas::start_hot_reload`].
+    pub fn check_and_reload(&mut self) -> Result<bool, UiError> {
         let changes = self.hot_reload_manager.check_for_changes();
 
         if !changes.is_empty() {
-            self.parser.clear_cache();
+            self.parser.invalidate(&changes);
             self.load()?;
             return Ok(true);
         }
@@ -54,8 +246,10 @@ impl JsonCanvas {
         Ok(false)
     }
 
-    pub fn reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Reload every file from scratch, resetting the state of the widgets.
+    pub fn reload(&mut self) -> Result<(), UiError> {
         self.parser.clear_cache();
+        self.widget_states.clear();
         self.load()
     }
 
@@ -67,23 +261,35 @@ impl JsonCanvas {
         self.current_ui.as_ref()
     }
 
-    pub fn load_from_string(&mut self, json_content: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load_from_string(&mut self, json_content: &str) -> Result<(), UiError> {
         let ui = self.parser.parse_from_string(json_content)?;
         self.current_ui = Some(ui);
+        self.load_error = None;
         Ok(())
     }
 }
 
 impl Render for JsonCanvas {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        if let Ok(reloaded) = self.check_and_reload() {
-            if reloaded {
-                cx.notify();
-            }
-        }
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let error = self.load_error.clone().map(|error| {
+            v_flex()
+                .gap_1()
+                .children(error.lines().map(|line| UiRenderer::render_error(line.to_string(), cx)))
+        });
 
+        self.widget_states.retain_rendered();
         if let Some(ref ui) = self.current_ui {
-            UiRenderer::render_component(ui, cx)
+            let mut rcx = UiRenderContext {
+                states: &mut self.widget_states,
+                scope: DataScope::new(&self.data),
+            };
+            let element = UiRenderer::render_component(ui, &mut rcx, window, cx);
+            match error {
+                Some(error) => v_flex().gap_2().child(error).child(element).into_any_element(),
+                None => element,
+            }
+        } else if let Some(error) = error {
+            div().p_4().child(error).into_any_element()
         } else {
             div()
                 .p_4()
@@ -94,12 +300,12 @@ impl Render for JsonCanvas {
 }
 
 pub fn create_json_canvas_view(root_path: impl AsRef<Path>, cx: &mut Context<impl Render>) -> Entity<JsonCanvas> {
-    cx.new(|_cx| {
+    cx.new(|cx| {
         let mut canvas = JsonCanvas::new(root_path);
         if let Err(e) = canvas.load() {
             eprintln!("Error loading JSON UI: {}", e);
         }
-        if let Err(e) = canvas.start_hot_reload() {
+        if let Err(e) = canvas.start_hot_reload(cx) {
             eprintln!("Error starting hot reload: {}", e);
         }
         canvas
@@ -1,11 +1,53 @@
+use crate::json_ui::binding::json_from_ui_value;
+use crate::json_ui::embedded_map::{normalize_lexically, EmbeddedUiMap};
+use crate::json_ui::hot_reload::normalize_path;
 use crate::json_ui::schema::*;
-use std::collections::HashMap;
+use crate::json_ui::validation::{locate, suggest, validate_source, Severity, UiDiagnostic, UiError};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::fs;
 
+/// A parsed file, kept until it changes.
+struct CachedDocument {
+    /// The unresolved document.
+    document: UiDocument,
+    source: Arc<str>,
+    /// Validation warnings.
+    warnings: Vec<UiDiagnostic>,
+}
+
+/// What the components of a file are resolved against.
+struct ResolveScope {
+    path: Option<PathBuf>,
+    source: Option<Arc<str>>,
+    /// Values for `${name}` interpolation.
+    props: HashMap<String, UiValue>,
+    styles: HashMap<String, HashMap<String, UiValue>>,
+    /// The (resolved) children passed at the use site, by slot name.
+    slots: HashMap<String, Vec<UiChild>>,
+}
+
+/// Where a component file is used, for reporting missing or mistyped props.
+struct UseSite<'a> {
+    scope: &'a ResolveScope,
+    pointer: &'a str,
+    props: &'a HashMap<String, UiValue>,
+}
+
 pub struct UiParser {
     base_path: PathBuf,
-    cache: HashMap<PathBuf, UiDocument>,
+    cache: HashMap<PathBuf, CachedDocument>,
+    /// Every file read while resolving the current document, including `$ref` targets.
+    dependencies: HashSet<PathBuf>,
+    /// The files currently being resolved, to detect cyclic `$ref`s.
+    resolving: Vec<PathBuf>,
+    /// Errors found while resolving, such as missing required props.
+    errors: Vec<UiDiagnostic>,
+    /// Warnings found while resolving, such as unknown style presets.
+    warnings: Vec<UiDiagnostic>,
+    /// Read documents from memory instead of the file system.
+    embedded: Option<EmbeddedUiMap>,
 }
 
 impl UiParser {
@@ -13,78 +55,457 @@ impl UiParser {
         Self {
             base_path: base_path.as_ref().to_path_buf(),
             cache: HashMap::new(),
+            dependencies: HashSet::new(),
+            resolving: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            embedded: None,
         }
     }
 
-    pub fn parse_file(path: impl AsRef<Path>) -> Result<UiComponent, Box<dyn std::error::Error>> {
+    /// Create a parser that reads documents from an [`EmbeddedUiMap`] instead of the file system.
+    pub fn with_embedded(map: EmbeddedUiMap) -> Self {
+        let mut parser = Self::new(map.source_dir());
+        parser.embedded = Some(map);
+        parser
+    }
+
+    pub fn is_embedded(&self) -> bool {
+        self.embedded.is_some()
+    }
+
+    pub fn parse_file(path: impl AsRef<Path>) -> Result<UiComponent, UiError> {
         let mut parser = Self::new(path.as_ref().parent().unwrap_or(Path::new(".")));
         parser.parse_document(path.as_ref())
     }
 
-    pub fn parse_document(&mut self, path: &Path) -> Result<UiComponent, Box<dyn std::error::Error>> {
-        let absolute_path = if path.is_absolute() {
+    pub fn parse_document(&mut self, path: &Path) -> Result<UiComponent, UiError> {
+        let path = self.document_path(path);
+        let result = self.resolve_document(&path, HashMap::new(), HashMap::new(), None);
+        self.finish(result)
+    }
+
+    pub fn parse_from_string(&mut self, content: &str) -> Result<UiComponent, UiError> {
+        let (document, _) = Self::parse_source(content, None)?;
+        let mut scope = ResolveScope {
+            path: None,
+            source: Some(content.into()),
+            props: HashMap::new(),
+            styles: document.styles,
+            slots: HashMap::new(),
+        };
+        if let Some(definition) = &document.definition {
+            self.apply_definition(definition, &mut scope, None);
+        }
+        let result = self.resolve_component(&document.root, &scope, "");
+        self.finish(result)
+    }
+
+    /// Turn the errors collected while resolving into the result.
+    fn finish(&mut self, result: Result<UiComponent, UiError>) -> Result<UiComponent, UiError> {
+        let errors = std::mem::take(&mut self.errors);
+        let component = result?;
+        if !errors.is_empty() {
+            return Err(UiError::Invalid(errors));
+        }
+        Ok(component)
+    }
+
+    fn document_path(&self, path: &Path) -> PathBuf {
+        let path = if path.is_absolute() {
             path.to_path_buf()
         } else {
             self.base_path.join(path)
         };
+        // Embedded paths are from the build machine, so they can't be canonicalized.
+        match self.embedded {
+            Some(_) => normalize_lexically(&path),
+            None => normalize_path(path),
+        }
+    }
 
-        if let Some(cached) = self.cache.get(&absolute_path) {
-            return Ok(cached.root.clone());
+    /// Return the unresolved document at `path` and its source, reading it unless cached.
+    fn load(&mut self, path: &Path) -> Result<(UiDocument, Arc<str>), UiError> {
+        if let Some(cached) = self.cache.get(path) {
+            return Ok((cached.document.clone(), cached.source.clone()));
         }
 
-        let content = fs::read_to_string(&absolute_path)?;
-        let document: UiDocument = serde_json::from_str(&content)?;
+        let content = self.read_document(path)?;
+        let (document, warnings) = Self::parse_source(&content, Some(path))?;
+        let source: Arc<str> = content.into();
+        self.cache.insert(
+            path.to_path_buf(),
+            CachedDocument {
+                document: document.clone(),
+                source: source.clone(),
+                warnings,
+            },
+        );
+        Ok((document, source))
+    }
 
-        let resolved = self.resolve_component(&document.root)?;
-        self.cache.insert(absolute_path, document);
+    fn read_document(&self, path: &Path) -> Result<String, UiError> {
+        let result = match &self.embedded {
+            Some(map) => map.get(path).map(str::to_string).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, "file is not embedded")
+            }),
+            None => fs::read_to_string(path),
+        };
+        result.map_err(|source| UiError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Validate and deserialize a document, returning it with its warnings.
+    pub(crate) fn parse_source(content: &str, path: Option<&Path>) -> Result<(UiDocument, Vec<UiDiagnostic>), UiError> {
+        let (value, warnings) = validate_source(content, path)?;
+        for warning in &warnings {
+            tracing::warn!("JSON UI: {}", warning);
+        }
 
-        Ok(resolved)
+        let document = serde_json::from_value(value).map_err(|err| {
+            UiError::Invalid(vec![UiDiagnostic {
+                severity: Severity::Error,
+                path: path.map(Path::to_path_buf),
+                pointer: String::new(),
+                line: 0,
+                column: 0,
+                message: err.to_string(),
+                suggestion: None,
+            }])
+        })?;
+        Ok((document, warnings))
     }
 
-    fn resolve_component(&mut self, component: &UiComponent) -> Result<UiComponent, Box<dyn std::error::Error>> {
-        self.resolve_component_with_props(component, &HashMap::new())
+    /// Resolve the document at `path` with the props and slot content passed to it.
+    fn resolve_document(
+        &mut self,
+        path: &Path,
+        props: HashMap<String, UiValue>,
+        slots: HashMap<String, Vec<UiChild>>,
+        use_site: Option<UseSite>,
+    ) -> Result<UiComponent, UiError> {
+        self.dependencies.insert(path.to_path_buf());
+
+        if let Some(ix) = self.resolving.iter().position(|p| p == path) {
+            let mut chain = self.resolving[ix..].to_vec();
+            chain.push(path.to_path_buf());
+            return Err(UiError::CyclicReference(chain));
+        }
+
+        let (document, source) = self.load(path)?;
+        let mut scope = ResolveScope {
+            path: Some(path.to_path_buf()),
+            source: Some(source),
+            props,
+            styles: document.styles,
+            slots,
+        };
+        if let Some(definition) = &document.definition {
+            self.apply_definition(definition, &mut scope, use_site);
+        }
+
+        self.resolving.push(path.to_path_buf());
+        let result = self.resolve_component(&document.root, &scope, "");
+        self.resolving.pop();
+        result
     }
 
-    fn resolve_component_with_props(&mut self, component: &UiComponent, inherited_props: &HashMap<String, UiValue>) -> Result<UiComponent, Box<dyn std::error::Error>> {
-        if let Some(ref_path) = &component.reference {
-            let referenced_path = self.base_path.join(ref_path);
-            let referenced_component = self.parse_document(&referenced_path)?;
+    /// Fill in prop defaults, and report missing, mistyped or unknown props and slots.
+    fn apply_definition(
+        &mut self,
+        definition: &UiComponentDefinition,
+        scope: &mut ResolveScope,
+        use_site: Option<UseSite>,
+    ) {
+        let name = scope
+            .path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "component".into());
 
-            let mut merged_props = inherited_props.clone();
-            for (key, value) in &component.props {
-                merged_props.insert(key.clone(), value.clone());
+        for (prop_name, prop) in &definition.props {
+            match scope.props.get(prop_name) {
+                None => match &prop.default {
+                    Some(default) => {
+                        scope.props.insert(prop_name.clone(), default.clone());
+                    }
+                    None if prop.required => {
+                        let message = format!("missing required prop `{}` of `{}`", prop_name, name);
+                        let diagnostic = match &use_site {
+                            Some(site) => self.diagnostic(Severity::Error, site.scope, site.pointer, message),
+                            None => {
+                                let pointer = format!("/component/props/{}", prop_name);
+                                self.diagnostic(Severity::Error, scope, &pointer, message)
+                            }
+                        };
+                        self.error(diagnostic);
+                    }
+                    None => {}
+                },
+                Some(value) => {
+                    let kind = prop.prop_type.kind();
+                    if kind.accepts(&json_from_ui_value(value)) {
+                        continue;
+                    }
+                    // Values inherited from further up are not checked, only those passed here.
+                    let Some(site) = use_site.as_ref().filter(|site| site.props.contains_key(prop_name)) else {
+                        continue;
+                    };
+                    let message = format!(
+                        "prop `{}` of `{}` expects {}",
+                        prop_name,
+                        name,
+                        kind.describe()
+                    );
+                    let pointer = format!("{}/props/{}", site.pointer, prop_name);
+                    let diagnostic = self.diagnostic(Severity::Error, site.scope, &pointer, message);
+                    self.error(diagnostic);
+                }
             }
+        }
 
-            let mut resolved = referenced_component;
-            resolved.props = self.interpolate_props(&resolved.props, &merged_props);
-            resolved.children = self.resolve_children_with_props(&resolved.children, &merged_props)?;
+        let Some(site) = use_site else {
+            return;
+        };
+        for prop_name in site.props.keys() {
+            if definition.props.contains_key(prop_name) {
+                continue;
+            }
+            let pointer = format!("{}/props/{}", site.pointer, prop_name);
+            let mut diagnostic = self.diagnostic(
+                Severity::Warning,
+                site.scope,
+                &pointer,
+                format!("unknown prop `{}` of `{}`", prop_name, name),
+            );
+            diagnostic.suggestion =
+                suggest(prop_name, definition.props.keys().map(String::as_str)).map(str::to_string);
+            self.warn(diagnostic);
+        }
+
+        if !definition.slots.is_empty() {
+            for slot in scope.slots.keys() {
+                if slot != "default" && !definition.slots.contains(slot) {
+                    let mut diagnostic = self.diagnostic(
+                        Severity::Warning,
+                        site.scope,
+                        site.pointer,
+                        format!("unknown slot `{}` of `{}`", slot, name),
+                    );
+                    diagnostic.suggestion =
+                        suggest(slot, definition.slots.iter().map(String::as_str)).map(str::to_string);
+                    self.warn(diagnostic);
+                }
+            }
+        }
+    }
+
+    fn diagnostic(&self, severity: Severity, scope: &ResolveScope, pointer: &str, message: String) -> UiDiagnostic {
+        let (line, column) = scope
+            .source
+            .as_ref()
+            .map(|source| locate(source, pointer))
+            .unwrap_or((0, 0));
+        UiDiagnostic {
+            severity,
+            path: scope.path.clone(),
+            pointer: pointer.to_string(),
+            line,
+            column,
+            message,
+            suggestion: None,
+        }
+    }
+
+    /// Components used more than once would report the same problem for every use.
+    fn error(&mut self, diagnostic: UiDiagnostic) {
+        if !self.errors.contains(&diagnostic) {
+            self.errors.push(diagnostic);
+        }
+    }
+
+    fn warn(&mut self, diagnostic: UiDiagnostic) {
+        if !self.warnings.contains(&diagnostic) {
+            tracing::warn!("JSON UI: {}", diagnostic);
+            self.warnings.push(diagnostic);
+        }
+    }
+
+    fn resolve_component(&mut self, component: &UiComponent, scope: &ResolveScope, pointer: &str) -> Result<UiComponent, UiError> {
+        if let Some(reference) = &component.reference {
+            let mut resolved = self.resolve_reference(reference, &component.props, &component.children, scope, pointer)?;
+            // Directives at the use site apply to the referenced component as a whole.
+            if component.condition.is_some() {
+                resolved.condition = component.condition.clone();
+            }
+            if component.repeat.is_some() {
+                resolved.repeat = component.repeat.clone();
+            }
+            resolved.slot = component.slot.clone();
+            resolved.origin = Some(Box::new(UiChild::Component(component.clone())));
             return Ok(resolved);
         }
 
+        // A slot at the root of a file has no list to expand into.
+        if component.component_type == "slot" {
+            let children = self.resolve_slot(component, scope, pointer)?;
+            return Ok(UiComponent {
+                component_type: "div".into(),
+                props: HashMap::new(),
+                children,
+                reference: None,
+                condition: None,
+                repeat: None,
+                slot: None,
+                origin: None,
+            });
+        }
+
         let mut resolved_children = Vec::new();
-        for child in &component.children {
-            resolved_children.push(self.resolve_child_with_props(child, inherited_props)?);
+        for (ix, child) in component.children.iter().enumerate() {
+            let child_pointer = format!("{}/children/{}", pointer, ix);
+            resolved_children.extend(self.resolve_child(child, scope, &child_pointer)?);
         }
 
         Ok(UiComponent {
             component_type: component.component_type.clone(),
-            props: self.interpolate_props(&component.props, inherited_props),
+            props: self.resolve_props(&component.props, scope, pointer),
             children: resolved_children,
             reference: None,
+            condition: component.condition.clone(),
+            repeat: component.repeat.clone(),
+            slot: component.slot.clone(),
+            origin: None,
         })
     }
 
-    fn interpolate_props(&self, props: &HashMap<String, UiValue>, context: &HashMap<String, UiValue>) -> HashMap<String, UiValue> {
+    /// Resolve a child, a `slot` expands to the children passed for it.
+    fn resolve_child(&mut self, child: &UiChild, scope: &ResolveScope, pointer: &str) -> Result<Vec<UiChild>, UiError> {
+        match child {
+            UiChild::Component(component) if component.component_type == "slot" && component.reference.is_none() => {
+                self.resolve_slot(component, scope, pointer)
+            }
+            UiChild::Component(component) => {
+                Ok(vec![UiChild::Component(self.resolve_component(component, scope, pointer)?)])
+            }
+            UiChild::Text(text) => Ok(vec![UiChild::Text(text.clone())]),
+            UiChild::Reference { reference, props, condition, repeat, children } => {
+                let mut resolved = self.resolve_reference(reference, props, children, scope, pointer)?;
+                if condition.is_some() {
+                    resolved.condition = condition.clone();
+                }
+                if repeat.is_some() {
+                    resolved.repeat = repeat.clone();
+                }
+                resolved.origin = Some(Box::new(child.clone()));
+                Ok(vec![UiChild::Component(resolved)])
+            }
+        }
+    }
+
+    /// Resolve a `$ref` use site, passing it the props and children given there.
+    fn resolve_reference(
+        &mut self,
+        reference: &str,
+        props: &HashMap<String, UiValue>,
+        children: &[UiChild],
+        scope: &ResolveScope,
+        pointer: &str,
+    ) -> Result<UiComponent, UiError> {
+        let path = self.document_path(&self.base_path.join(reference));
+
+        // Props and children passed here belong to the caller, so resolve them in its scope.
+        let mut merged_props = scope.props.clone();
+        for (key, value) in props {
+            merged_props.insert(key.clone(), Self::interpolate_value(value, &scope.props));
+        }
+
+        let mut slots: HashMap<String, Vec<UiChild>> = HashMap::new();
+        for (ix, child) in children.iter().enumerate() {
+            let slot = match child {
+                UiChild::Component(component) => component.slot.clone(),
+                _ => None,
+            };
+            let child_pointer = format!("{}/children/{}", pointer, ix);
+            let resolved = self.resolve_child(child, scope, &child_pointer)?;
+            slots
+                .entry(slot.unwrap_or_else(|| "default".into()))
+                .or_default()
+                .extend(resolved);
+        }
+
+        let use_site = UseSite {
+            scope,
+            pointer,
+            props,
+        };
+        self.resolve_document(&path, merged_props, slots, Some(use_site))
+    }
+
+    /// The children passed for a slot, or the slot's own children as the fallback.
+    fn resolve_slot(&mut self, slot: &UiComponent, scope: &ResolveScope, pointer: &str) -> Result<Vec<UiChild>, UiError> {
+        let name = slot
+            .props
+            .get("name")
+            .and_then(|name| name.as_string())
+            .unwrap_or("default");
+        if let Some(content) = scope.slots.get(name) {
+            return Ok(content.clone());
+        }
+
+        let mut fallback = Vec::new();
+        for (ix, child) in slot.children.iter().enumerate() {
+            let child_pointer = format!("{}/children/{}", pointer, ix);
+            fallback.extend(self.resolve_child(child, scope, &child_pointer)?);
+        }
+        Ok(fallback)
+    }
+
+    /// Interpolate `${name}` props and apply the style presets named by `class`.
+    ///
+    /// Later classes override earlier ones, and props set on the component override both.
+    fn resolve_props(&mut self, props: &HashMap<String, UiValue>, scope: &ResolveScope, pointer: &str) -> HashMap<String, UiValue> {
+        let mut resolved = Self::interpolate_props(props, &scope.props);
+
+        let Some(UiValue::String(class)) = resolved.remove("class") else {
+            return resolved;
+        };
+        for name in class.split_whitespace().rev() {
+            let Some(preset) = scope.styles.get(name) else {
+                let mut diagnostic = self.diagnostic(
+                    Severity::Warning,
+                    scope,
+                    &format!("{}/props/class", pointer),
+                    format!("unknown style `{}`", name),
+                );
+                diagnostic.suggestion =
+                    suggest(name, scope.styles.keys().map(String::as_str)).map(str::to_string);
+                self.warn(diagnostic);
+                continue;
+            };
+            for (key, value) in preset {
+                if !resolved.contains_key(key) {
+                    resolved.insert(key.clone(), Self::interpolate_value(value, &scope.props));
+                }
+            }
+        }
+        resolved
+    }
+
+    fn interpolate_props(props: &HashMap<String, UiValue>, context: &HashMap<String, UiValue>) -> HashMap<String, UiValue> {
         let mut interpolated = HashMap::new();
 
         for (key, value) in props {
-            interpolated.insert(key.clone(), self.interpolate_value(value, context));
+            interpolated.insert(key.clone(), Self::interpolate_value(value, context));
         }
 
         interpolated
     }
 
-    fn interpolate_value(&self, value: &UiValue, context: &HashMap<String, UiValue>) -> UiValue {
+    fn interpolate_value(value: &UiValue, context: &HashMap<String, UiValue>) -> UiValue {
         match value {
             UiValue::String(s) => {
                 if s.starts_with("${") && s.ends_with("}") {
@@ -95,12 +516,12 @@ impl UiParser {
                 }
             }
             UiValue::Array(arr) => {
-                UiValue::Array(arr.iter().map(|v| self.interpolate_value(v, context)).collect())
+                UiValue::Array(arr.iter().map(|v| Self::interpolate_value(v, context)).collect())
             }
             UiValue::Object(obj) => {
                 let mut interpolated_obj = HashMap::new();
                 for (k, v) in obj {
-                    interpolated_obj.insert(k.clone(), self.interpolate_value(v, context));
+                    interpolated_obj.insert(k.clone(), Self::interpolate_value(v, context));
                 }
                 UiValue::Object(interpolated_obj)
             }
@@ -108,53 +529,296 @@ impl UiParser {
         }
     }
 
-    fn resolve_child(&mut self, child: &UiChild) -> Result<UiChild, Box<dyn std::error::Error>> {
-        self.resolve_child_with_props(child, &HashMap::new())
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
     }
 
-    fn resolve_child_with_props(&mut self, child: &UiChild, inherited_props: &HashMap<String, UiValue>) -> Result<UiChild, Box<dyn std::error::Error>> {
-        match child {
-            UiChild::Component(component) => {
-                Ok(UiChild::Component(self.resolve_component_with_props(component, inherited_props)?))
-            }
-            UiChild::Text(text) => Ok(UiChild::Text(text.clone())),
-            UiChild::Reference { reference, props } => {
-                let referenced_path = self.base_path.join(reference);
-                let mut referenced_component = self.parse_document(&referenced_path)?;
-
-                let mut merged_props = inherited_props.clone();
-                for (key, value) in props {
-                    merged_props.insert(key.clone(), value.clone());
-                }
+    /// Returns the files read since the last [`UiParser::clear_dependencies`].
+    pub fn dependencies(&self) -> &HashSet<PathBuf> {
+        &self.dependencies
+    }
 
-                referenced_component.props = self.interpolate_props(&referenced_component.props, &merged_props);
-                referenced_component.children = self.resolve_children_with_props(&referenced_component.children, &merged_props)?;
+    pub fn clear_dependencies(&mut self) {
+        self.dependencies.clear();
+        self.warnings.clear();
+    }
 
-                Ok(UiChild::Component(referenced_component))
-            }
+    /// Returns the warnings for the files read since the last [`UiParser::clear_dependencies`].
+    ///
+    /// Errors are returned from [`UiParser::parse_document`] instead.
+    pub fn diagnostics(&self) -> impl Iterator<Item = &UiDiagnostic> {
+        self.dependencies
+            .iter()
+            .filter_map(|path| self.cache.get(path))
+            .flat_map(|cached| &cached.warnings)
+            .chain(&self.warnings)
+    }
+
+    /// Drop the cache entries for the given changed files.
+    ///
+    /// Returns true if any of the files was part of the cache.
+    pub fn invalidate(&mut self, paths: &[PathBuf]) -> bool {
+        let mut invalidated = false;
+        for path in paths {
+            invalidated |= self.cache.remove(&normalize_path(path)).is_some();
         }
+        invalidated
     }
 
-    fn resolve_children(&mut self, children: &[UiChild]) -> Result<Vec<UiChild>, Box<dyn std::error::Error>> {
-        self.resolve_children_with_props(children, &HashMap::new())
+    pub fn remove_from_cache(&mut self, path: &Path) {
+        self.cache.remove(&normalize_path(path));
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn resolve_children_with_props(&mut self, children: &[UiChild], inherited_props: &HashMap<String, UiValue>) -> Result<Vec<UiChild>, Box<dyn std::error::Error>> {
-        children.iter()
-            .map(|child| self.resolve_child_with_props(child, inherited_props))
-            .collect()
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("json_ui_parser_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        dir
     }
 
-    pub fn clear_cache(&mut self) {
-        self.cache.clear();
+    #[test]
+    fn test_cyclic_reference() {
+        let dir = write_files(
+            "cyclic",
+            &[
+                ("a.json", r#"{ "type": "div", "children": [{ "$ref": "b.json" }] }"#),
+                ("b.json", r#"{ "type": "div", "children": [{ "$ref": "a.json" }] }"#),
+            ],
+        );
+
+        let mut parser = UiParser::new(&dir);
+        let Err(UiError::CyclicReference(chain)) = parser.parse_document(Path::new("a.json")) else {
+            panic!("expected a cyclic reference error");
+        };
+        let names = chain
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a.json", "b.json", "a.json"]);
+
+        // Referencing the same file twice side by side is not a cycle.
+        fs::write(
+            dir.join("a.json"),
+            r#"{ "type": "div", "children": [{ "$ref": "c.json" }, { "$ref": "c.json" }] }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("c.json"), r#"{ "type": "text", "props": { "content": "c" } }"#).unwrap();
+        parser.clear_cache();
+        assert!(parser.parse_document(Path::new("a.json")).is_ok());
+
+        fs::remove_dir_all(dir).ok();
     }
 
-    pub fn parse_from_string(&mut self, content: &str) -> Result<UiComponent, Box<dyn std::error::Error>> {
-        let document: UiDocument = serde_json::from_str(content)?;
-        self.resolve_component(&document.root)
+    #[test]
+    fn test_invalid_document() {
+        let dir = write_files(
+            "invalid",
+            &[("a.json", "{\n  \"type\": \"buton\",\n  \"props\": { \"lable\": \"Hi\" }\n}")],
+        );
+
+        let mut parser = UiParser::new(&dir);
+        let err = parser.parse_document(Path::new("a.json")).unwrap_err();
+        let diagnostics = err.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pointer, "/type");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 11));
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some("button"));
+        assert!(diagnostics[0].path.as_ref().unwrap().ends_with("a.json"));
+
+        fs::write(dir.join("a.json"), r#"{ "type": "button", "props": { "lable": "Hi" } }"#).unwrap();
+        parser.clear_dependencies();
+        parser.invalidate(&[dir.join("a.json")]);
+        assert!(parser.parse_document(Path::new("a.json")).is_ok());
+        let warnings = parser.diagnostics().collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].suggestion.as_deref(), Some("label"));
+
+        fs::remove_dir_all(dir).ok();
     }
 
-    pub fn remove_from_cache(&mut self, path: &Path) {
-        self.cache.remove(path);
+    #[test]
+    fn test_invalidate() {
+        let text = |content: &str| format!(r#"{{ "type": "text", "props": {{ "content": "{}" }} }}"#, content);
+        let dir = write_files(
+            "invalidate",
+            &[
+                ("main.json", r#"{ "type": "div", "children": [{ "$ref": "a.json" }, { "$ref": "b.json" }] }"#),
+                ("a.json", &text("a1")),
+                ("b.json", &text("b1")),
+            ],
+        );
+        let contents = |parser: &mut UiParser| {
+            let ui = parser.parse_document(Path::new("main.json")).unwrap();
+            ui.children
+                .iter()
+                .map(|child| component(child).props["content"].as_string().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut parser = UiParser::new(&dir);
+        assert_eq!(contents(&mut parser), ["a1", "b1"]);
+
+        fs::write(dir.join("a.json"), text("a2")).unwrap();
+        fs::write(dir.join("b.json"), text("b2")).unwrap();
+        // Only the changed entry is evicted, the other one is still served from the cache.
+        assert!(parser.invalidate(&[dir.join("./a.json")]));
+        assert_eq!(contents(&mut parser), ["a2", "b1"]);
+
+        assert!(!parser.invalidate(&[dir.join("missing.json")]));
+        assert!(parser.invalidate(&[dir.join("b.json")]));
+        assert_eq!(contents(&mut parser), ["a2", "b2"]);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_embedded() {
+        let map = EmbeddedUiMap::from_entries(
+            "/not/on/disk",
+            "main.json",
+            &[
+                ("main.json", r#"{ "type": "div", "children": [{ "$ref": "parts/card.json", "props": { "title": "Hi" } }] }"#),
+                ("parts/card.json", r#"{ "type": "text", "props": { "content": "${title}" }, "children": [{ "$ref": "./parts/../footer.json" }] }"#),
+                ("footer.json", r#"{ "type": "divider" }"#),
+            ],
+        );
+
+        let mut parser = UiParser::with_embedded(map);
+        let ui = parser.parse_document(Path::new("main.json")).unwrap();
+        let UiChild::Component(card) = &ui.children[0] else {
+            panic!("expected the card");
+        };
+        assert_eq!(card.props["content"].as_string(), Some("Hi"));
+        assert_eq!(parser.dependencies().len(), 3);
+        assert!(parser.dependencies().contains(Path::new("/not/on/disk/footer.json")));
+
+        let mut parser = UiParser::with_embedded(EmbeddedUiMap::new("/not/on/disk", "main.json"));
+        assert!(matches!(
+            parser.parse_document(Path::new("main.json")),
+            Err(UiError::Io { .. })
+        ));
     }
-}
\ No newline at end of file
+
+    fn component(child: &UiChild) -> &UiComponent {
+        match child {
+            UiChild::Component(component) => component,
+            _ => panic!("expected a component, got {:?}", child),
+        }
+    }
+
+    #[test]
+    fn test_component_definition() {
+        let card = r#"{
+            "component": {
+                "props": {
+                    "title": { "type": "string", "required": true },
+                    "tone": { "type": "color", "default": "primary" }
+                },
+                "slots": ["footer"]
+            },
+            "styles": {
+                "card": { "padding": 16, "bg": "${tone}" },
+                "compact": { "padding": 8 }
+            },
+            "type": "column",
+            "props": { "class": "card compact" },
+            "children": [
+                { "type": "h2", "props": { "content": "${title}", "padding": 2, "class": "card" } },
+                { "type": "slot" },
+                { "type": "slot", "props": { "name": "footer" }, "children": ["No footer"] }
+            ]
+        }"#;
+        let main = r#"{
+            "styles": { "note": { "color": "muted-foreground" } },
+            "type": "div",
+            "children": [
+                {
+                    "$ref": "card.json",
+                    "props": { "title": "Hello" },
+                    "children": [
+                        "Body",
+                        { "type": "text", "props": { "class": "note" } },
+                        { "type": "button", "slot": "footer" }
+                    ]
+                },
+                { "$ref": "card.json", "props": { "title": "Empty", "tone": "danger" } }
+            ]
+        }"#;
+        let map = EmbeddedUiMap::from_entries("/ui", "main.json", &[("main.json", main), ("card.json", card)]);
+
+        let mut parser = UiParser::with_embedded(map);
+        let ui = parser.parse_document(Path::new("main.json")).unwrap();
+
+        let first = component(&ui.children[0]);
+        // Later classes win, props set on the component win over classes.
+        assert_eq!(first.props["padding"].as_number(), Some(8.));
+        assert_eq!(first.props["bg"].as_string(), Some("primary"));
+        assert!(!first.props.contains_key("class"));
+        let heading = component(&first.children[0]);
+        assert_eq!(heading.props["content"].as_string(), Some("Hello"));
+        assert_eq!(heading.props["padding"].as_number(), Some(2.));
+
+        // The default slot gets the unnamed children, resolved with the caller's styles.
+        assert!(matches!(&first.children[1], UiChild::Text(text) if text == "Body"));
+        let note = component(&first.children[2]);
+        assert_eq!(note.props["color"].as_string(), Some("muted-foreground"));
+        assert_eq!(component(&first.children[3]).component_type, "button");
+        assert_eq!(first.children.len(), 4);
+
+        let second = component(&ui.children[1]);
+        assert_eq!(second.props["bg"].as_string(), Some("danger"));
+        assert!(matches!(&second.children[1], UiChild::Text(text) if text == "No footer"));
+        assert_eq!(parser.diagnostics().count(), 0);
+    }
+
+    #[test]
+    fn test_component_definition_errors() {
+        let card = r#"{
+            "component": {
+                "props": { "title": { "type": "string", "required": true }, "count": { "type": "number" } },
+                "slots": ["footer"]
+            },
+            "type": "div",
+            "props": { "class": "missing" }
+        }"#;
+        let main = "{\n  \"type\": \"div\",\n  \"children\": [\n    { \"$ref\": \"card.json\", \"props\": { \"count\": true, \"titel\": \"x\" },\n      \"children\": [{ \"type\": \"text\", \"slot\": \"header\" }] }\n  ]\n}";
+        let map = EmbeddedUiMap::from_entries("/ui", "main.json", &[("main.json", main), ("card.json", card)]);
+
+        let mut parser = UiParser::with_embedded(map);
+        let err = parser.parse_document(Path::new("main.json")).unwrap_err();
+        let mut errors = err
+            .diagnostics()
+            .iter()
+            .map(|d| (d.pointer.as_str(), d.line, d.message.as_str()))
+            .collect::<Vec<_>>();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                ("/children/0", 4, "missing required prop `title` of `card.json`"),
+                ("/children/0/props/count", 4, "prop `count` of `card.json` expects a number"),
+            ]
+        );
+
+        let mut warnings = parser
+            .diagnostics()
+            .map(|d| (d.message.as_str(), d.suggestion.as_deref()))
+            .collect::<Vec<_>>();
+        warnings.sort();
+        assert_eq!(
+            warnings,
+            vec![
+                ("unknown prop `titel` of `card.json`", Some("title")),
+                ("unknown slot `header` of `card.json`", None),
+                ("unknown style `missing`", None),
+            ]
+        );
+    }
+}
@@ -1,388 +1,1859 @@
+use crate::accordion::Accordion;
+use crate::alert::Alert;
+use crate::avatar::Avatar;
+use crate::badge::Badge;
+use crate::button::{Button, ButtonVariant, ButtonVariants as _};
+use crate::checkbox::Checkbox;
+use crate::divider::Divider;
+use crate::dropdown::{Dropdown, DropdownEvent, DropdownState};
+use crate::input::{InputEvent, InputState, TextInput};
+use crate::json_ui::action::UiAction;
+use crate::json_ui::binding::{value_to_text, DataScope, ForDirective};
 use crate::json_ui::schema::*;
-use crate::button::Button;
-use crate::{StyledExt, gray};
+use crate::json_ui::style::{parse_font_weight, responsive_props, UiLength, UiShadow};
+use crate::json_ui::JsonCanvas;
+use crate::list::ListItem;
+use crate::progress::Progress;
+use crate::radio::{Radio, RadioGroup};
+use crate::scroll::ScrollbarAxis;
+use crate::slider::{Slider, SliderEvent, SliderState};
+use crate::switch::Switch;
+use crate::tab::{Tab, TabBar};
+use crate::table::{Column, ColumnSort, Table, TableDelegate};
+use crate::tag::Tag;
+use crate::tooltip::Tooltip;
+use crate::{
+    box_shadow, h_flex, v_flex, ActiveTheme as _, ColorName, Colorize as _, ContextModal as _,
+    Disableable as _, Icon, IndexPath, Placement, Sizable as _, Size, StyledExt,
+};
 use gpui::*;
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// State for the stateful widgets of a [`JsonCanvas`].
+///
+/// Entries are keyed by the node's `id` prop, or by its position in the tree
+/// when no `id` is given, so they survive re-renders and hot reloads.
+///
+/// Entries of nodes that are no longer rendered, such as removed `for` items or
+/// the widgets of an inactive tab, are dropped on the next render.
+#[derive(Default)]
+pub struct UiWidgetStates {
+    toggles: HashMap<SharedString, bool>,
+    selections: HashMap<SharedString, usize>,
+    open_items: HashMap<SharedString, Vec<usize>>,
+    sliders: HashMap<SharedString, UiWidget<SliderState>>,
+    dropdowns: HashMap<SharedString, UiWidget<DropdownState<Vec<SharedString>>>>,
+    tables: HashMap<SharedString, UiWidget<Table<UiTableDelegate>>>,
+    inputs: HashMap<SharedString, UiWidget<InputState>>,
+    /// The `bind` path and `on_change` action of each widget, refreshed on every render.
+    pub(crate) bindings: HashMap<SharedString, UiBinding>,
+    /// The keys of the nodes rendered since the last [`UiWidgetStates::retain_rendered`].
+    rendered: HashSet<SharedString>,
+}
+
+impl UiWidgetStates {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Drop the entries of the nodes not rendered since the last call, and start over.
+    ///
+    /// Called before each render of the canvas, so that the content of an open modal or
+    /// drawer, rendered outside of the canvas, is kept as well.
+    pub(crate) fn retain_rendered(&mut self) {
+        let rendered = std::mem::take(&mut self.rendered);
+        self.toggles.retain(|key, _| rendered.contains(key));
+        self.selections.retain(|key, _| rendered.contains(key));
+        self.open_items.retain(|key, _| rendered.contains(key));
+        self.sliders.retain(|key, _| rendered.contains(key));
+        self.dropdowns.retain(|key, _| rendered.contains(key));
+        self.tables.retain(|key, _| rendered.contains(key));
+        self.inputs.retain(|key, _| rendered.contains(key));
+        self.bindings.retain(|key, _| rendered.contains(key));
+    }
+}
+
+/// The state of a stateful widget, and the props it was built from.
+struct UiWidget<T: 'static> {
+    state: Entity<T>,
+    /// The values of the props that the state depends on, to sync the state when they change.
+    props: Vec<Option<UiValue>>,
+    _subscription: Option<Subscription>,
+}
+
+/// Where a widget writes its value, and what it dispatches, when it changes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UiBinding {
+    /// The absolute path in the data model, from the `bind` prop.
+    pub(crate) path: Option<String>,
+    pub(crate) on_change: Option<UiAction>,
+}
+
+/// Everything a render pass needs besides the window and context.
+pub struct UiRenderContext<'a> {
+    pub states: &'a mut UiWidgetStates,
+    pub scope: DataScope<'a>,
+}
+
+/// A [`TableDelegate`] for the `table` component, backed by the `columns` and `rows` props.
+pub struct UiTableDelegate {
+    columns: Vec<Column>,
+    rows: Vec<Vec<SharedString>>,
+    /// The rows in their original order, to restore [`ColumnSort::Default`].
+    original_rows: Vec<Vec<SharedString>>,
+}
+
+impl UiTableDelegate {
+    fn from_props(props: &HashMap<String, UiValue>) -> Self {
+        let columns = props
+            .get("columns")
+            .and_then(|v| v.as_array())
+            .map(|columns| {
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, column)| match column {
+                        UiValue::Object(obj) => {
+                            let key = obj
+                                .get("key")
+                                .and_then(|v| v.as_string())
+                                .map(|s| s.to_string())
+                                .unwrap_or_else(|| ix.to_string());
+                            let name = obj
+                                .get("name")
+                                .and_then(|v| v.as_string())
+                                .map(|s| s.to_string())
+                                .unwrap_or_else(|| key.clone());
+                            let mut col = Column::new(key, name);
+                            if let Some(width) = obj.get("width").and_then(|v| v.as_number()) {
+                                col = col.width(px(width as f32));
+                            }
+                            if obj.get("sortable").and_then(|v| v.as_bool()).unwrap_or(false) {
+                                col = col.sortable();
+                            }
+                            col
+                        }
+                        other => {
+                            let name = UiRenderer::value_to_string(other);
+                            Column::new(name.clone(), name)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let rows = props
+            .get("rows")
+            .and_then(|v| v.as_array())
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| match row {
+                        UiValue::Array(cells) => cells
+                            .iter()
+                            .map(|cell| UiRenderer::value_to_string(cell).into())
+                            .collect(),
+                        UiValue::Object(obj) => columns
+                            .iter()
+                            .map(|col| {
+                                obj.get(col.key.as_ref())
+                                    .map(|cell| UiRenderer::value_to_string(cell).into())
+                                    .unwrap_or_default()
+                            })
+                            .collect(),
+                        other => vec![UiRenderer::value_to_string(other).into()],
+                    })
+                    .collect::<Vec<Vec<SharedString>>>()
+            })
+            .unwrap_or_default();
+
+        Self {
+            columns,
+            original_rows: rows.clone(),
+            rows,
+        }
+    }
+}
+
+impl TableDelegate for UiTableDelegate {
+    fn columns_count(&self, _: &App) -> usize {
+        self.columns.len()
+    }
+
+    fn rows_count(&self, _: &App) -> usize {
+        self.rows.len()
+    }
+
+    fn column(&self, col_ix: usize, _: &App) -> &Column {
+        &self.columns[col_ix]
+    }
+
+    fn perform_sort(
+        &mut self,
+        col_ix: usize,
+        sort: ColumnSort,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) {
+        let cell = |row: &Vec<SharedString>| row.get(col_ix).cloned().unwrap_or_default();
+        match sort {
+            ColumnSort::Default => self.rows = self.original_rows.clone(),
+            ColumnSort::Ascending => self.rows.sort_by(|a, b| compare_cells(&cell(a), &cell(b))),
+            ColumnSort::Descending => self.rows.sort_by(|a, b| compare_cells(&cell(b), &cell(a))),
+        }
+    }
+
+    fn render_td(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        self.rows
+            .get(row_ix)
+            .and_then(|row| row.get(col_ix))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Compare two cells numerically when both parse as numbers, otherwise as text.
+fn compare_cells(a: &str, b: &str) -> std::cmp::Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
 
 pub struct UiRenderer;
 
 impl UiRenderer {
-    pub fn render_component(component: &UiComponent, cx: &mut Context<crate::json_ui::JsonCanvas>) -> AnyElement {
-        Self::render_component_internal(component, &component.props, cx)
+    pub fn render_component(
+        component: &UiComponent,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let mut elements = Self::expand_node(component, "root".into(), rcx, window, cx);
+        if elements.len() == 1 {
+            elements.pop().expect("checked length")
+        } else {
+            v_flex().children(elements).into_any_element()
+        }
     }
 
-    pub fn render_component_generic<T>(component: &UiComponent, _cx: &mut Context<T>) -> AnyElement {
-        Self::render_component_standalone(component, &component.props)
+    /// Render a node, applying its `for` and `if` directives.
+    fn expand_node(
+        component: &UiComponent,
+        path: SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> Vec<AnyElement> {
+        let Some(repeat) = &component.repeat else {
+            if Self::is_visible(component, rcx) {
+                return vec![Self::render_node(component, path, rcx, window, cx)];
+            }
+            return vec![];
+        };
+
+        let Some(directive) = ForDirective::parse(repeat) else {
+            return vec![Self::render_error(format!("Invalid `for` directive `{}`", repeat), cx)
+                .into_any_element()];
+        };
+
+        let mut elements = vec![];
+        for ix in 0..rcx.scope.for_len(&directive) {
+            rcx.scope.push_for(&directive, ix);
+            if Self::is_visible(component, rcx) {
+                let item_path = format!("{}.{}", path, ix).into();
+                elements.push(Self::render_node(component, item_path, rcx, window, cx));
+            }
+            rcx.scope.pop_for(&directive);
+        }
+        elements
+    }
+
+    fn is_visible(component: &UiComponent, rcx: &UiRenderContext) -> bool {
+        component
+            .condition
+            .as_ref()
+            .map_or(true, |condition| rcx.scope.eval_condition(condition))
     }
 
-    fn render_component_internal(
+    fn render_node(
         component: &UiComponent,
-        props: &HashMap<String, UiValue>,
-        cx: &mut Context<crate::json_ui::JsonCanvas>
+        path: SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
     ) -> AnyElement {
+        let component = &match responsive_props(&component.props, window.viewport_size().width.0) {
+            Some(props) => rcx.scope.bind_component(&UiComponent {
+                props,
+                ..component.clone()
+            }),
+            None => rcx.scope.bind_component(component),
+        };
+        let props = &component.props;
+        let key: SharedString = props
+            .get("id")
+            .and_then(|v| v.as_string())
+            .map(|id| SharedString::from(id.to_string()))
+            .unwrap_or_else(|| path.clone());
+        rcx.states.rendered.insert(key.clone());
+
+        let element = Self::render_widget(component, key.clone(), path, rcx, window, cx);
+
+        // Widgets with their own click handling consume `on_click` themselves.
+        let handles_click = matches!(
+            component.component_type.as_str(),
+            "button" | "list" | "modal_trigger" | "drawer_trigger"
+        );
+        match props.get("on_click").and_then(UiAction::from_value) {
+            Some(action) if !handles_click => div()
+                .id(SharedString::from(format!("{}-click", key)))
+                .cursor_pointer()
+                .on_click(cx.listener(move |this, _: &ClickEvent, window, cx| {
+                    this.dispatch_action(&action, key.clone(), None, window, cx);
+                }))
+                .child(element)
+                .into_any_element(),
+            _ => element,
+        }
+    }
+
+    fn render_widget(
+        component: &UiComponent,
+        key: SharedString,
+        path: SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let props = &component.props;
         match component.component_type.as_str() {
-            "div" => Self::render_div(component, props, cx).into_any_element(),
-            "h1" => Self::render_h1(component, props, cx).into_any_element(),
-            "h2" => Self::render_h2(component, props, cx).into_any_element(),
-            "h3" => Self::render_h3(component, props, cx).into_any_element(),
-            "button" => Self::render_button(component, props, cx).into_any_element(),
-            "input" => Self::render_input(component, props, cx).into_any_element(),
-            "text" => Self::render_text(component, props, cx).into_any_element(),
-            "flex" => Self::render_flex(component, props, cx).into_any_element(),
-            "column" => Self::render_column(component, props, cx).into_any_element(),
-            "row" => Self::render_row(component, props, cx).into_any_element(),
-            _ => Self::render_unknown(component, props, cx).into_any_element(),
+            "div" => Self::render_container(div(), component, key, &path, rcx, window, cx),
+            "h1" => Self::render_heading(div().text_xl(), component, &path, rcx, window, cx),
+            "h2" => Self::render_heading(div().text_lg(), component, &path, rcx, window, cx),
+            "h3" => Self::render_heading(div().text_base(), component, &path, rcx, window, cx),
+            "text" => Self::render_text(component, cx).into_any_element(),
+            "flex" => {
+                let element = match Self::str_prop(props, "direction") {
+                    Some("column") => div().flex().flex_col(),
+                    _ => div().flex().flex_row(),
+                };
+                Self::render_container(element, component, key, &path, rcx, window, cx)
+            }
+            "column" => Self::render_container(v_flex(), component, key, &path, rcx, window, cx),
+            "row" => Self::render_container(h_flex(), component, key, &path, rcx, window, cx),
+            "input" => Self::render_input(component, key, rcx, window, cx).into_any_element(),
+            "button" => Self::render_button(component, key, cx).into_any_element(),
+            "switch" => Self::render_switch(component, key, rcx, cx).into_any_element(),
+            "checkbox" => Self::render_checkbox(component, key, rcx, cx).into_any_element(),
+            "radio" => Self::render_radio(component, key, rcx, cx).into_any_element(),
+            "radio_group" => Self::render_radio_group(component, key, rcx, cx).into_any_element(),
+            "slider" => Self::render_slider(component, key, rcx, window, cx).into_any_element(),
+            "dropdown" => Self::render_dropdown(component, key, rcx, window, cx).into_any_element(),
+            "tabs" => Self::render_tabs(component, key, &path, rcx, window, cx),
+            "table" => Self::render_table(component, key, rcx, window, cx).into_any_element(),
+            "list" => Self::render_list(component, key, rcx, cx).into_any_element(),
+            "accordion" => Self::render_accordion(component, key, &path, rcx, window, cx),
+            "alert" => Self::render_alert(component, key).into_any_element(),
+            "badge" => Self::render_badge(component, &path, rcx, window, cx),
+            "tag" => Self::render_tag(component).into_any_element(),
+            "avatar" => Self::render_avatar(component, cx).into_any_element(),
+            "progress" => Self::render_progress(component, cx).into_any_element(),
+            "tooltip" => Self::render_tooltip(component, key, &path, rcx, window, cx),
+            "divider" => Self::render_divider(component, cx).into_any_element(),
+            "icon" => Self::render_icon(component, cx).into_any_element(),
+            "modal_trigger" => Self::render_overlay_trigger(component, key, false, rcx, cx).into_any_element(),
+            "drawer_trigger" => Self::render_overlay_trigger(component, key, true, rcx, cx).into_any_element(),
+            _ => Self::render_error(
+                format!("Unknown component type `{}`", component.component_type),
+                cx,
+            )
+            .into_any_element(),
         }
     }
 
-    fn render_div(component: &UiComponent, props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div();
+    /// Render a layout component, wrapped in a scroll view if its `overflow` is `scroll`.
+    fn render_container(
+        base: Div,
+        component: &UiComponent,
+        key: SharedString,
+        path: &SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let props = &component.props;
+        let element = Self::apply_common_props(base, props, cx);
+        let element = Self::apply_children(element, component, path, rcx, window, cx);
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children(element, &component.children, cx);
+        let scroll = |name: &str| {
+            Self::str_prop(props, name).or(Self::str_prop(props, "overflow")) == Some("scroll")
+        };
+        let axis = match (scroll("overflow_x"), scroll("overflow_y")) {
+            (true, true) => ScrollbarAxis::Both,
+            (true, false) => ScrollbarAxis::Horizontal,
+            (false, true) => ScrollbarAxis::Vertical,
+            (false, false) => return element.into_any_element(),
+        };
+        // The id keeps the scroll position across renders.
+        element.id(key).scrollable(axis).into_any_element()
+    }
 
-        element
+    fn render_heading(
+        base: Div,
+        component: &UiComponent,
+        path: &SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let element = Self::apply_common_props(base.font_bold(), &component.props, cx);
+        if let Some(content) = Self::str_prop(&component.props, "content") {
+            return element.child(content.to_string()).into_any_element();
+        }
+        Self::apply_children(element, component, path, rcx, window, cx).into_any_element()
     }
 
-    fn render_h1(component: &UiComponent, props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div().text_xl().font_bold();
+    fn render_text(component: &UiComponent, cx: &mut Context<JsonCanvas>) -> impl IntoElement {
+        let content = Self::str_prop(&component.props, "content")
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| Self::text_content(component));
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children(element, &component.children, cx);
+        Self::apply_common_props(div().child(content), &component.props, cx)
+    }
 
-        element
+    fn render_input(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        Self::register_binding(&key, props, rcx);
+        let bound = Self::bound_value(props, rcx).map(|value| value_to_text(&value));
+
+        let state = match rcx.states.inputs.get(&key) {
+            Some(widget) => widget.state.clone(),
+            None => {
+                let placeholder = Self::str_prop(props, "placeholder").unwrap_or("").to_string();
+                let value = bound
+                    .clone()
+                    .or_else(|| Self::str_prop(props, "value").map(|s| s.to_string()))
+                    .unwrap_or_default();
+                let state = cx.new(|cx| {
+                    InputState::new(window, cx)
+                        .placeholder(placeholder)
+                        .default_value(value)
+                });
+
+                let subscription_key = key.clone();
+                let subscription = cx.subscribe_in(
+                    &state,
+                    window,
+                    move |this, state, event: &InputEvent, window, cx| {
+                        if let InputEvent::Change = event {
+                            let value = Value::String(state.read(cx).value().to_string());
+                            this.commit_change(&subscription_key, value, window, cx);
+                        }
+                    },
+                );
+                rcx.states.inputs.insert(
+                    key.clone(),
+                    UiWidget {
+                        state: state.clone(),
+                        props: vec![],
+                        _subscription: Some(subscription),
+                    },
+                );
+                state
+            }
+        };
+
+        // Follow changes made to the model from elsewhere.
+        if let Some(bound) = bound {
+            if state.read(cx).value().as_ref() != bound {
+                state.update(cx, |state, cx| state.set_value(bound, window, cx));
+            }
+        }
+
+        let mut input = TextInput::new(&state);
+        if let Some(size) = Self::size_prop(props) {
+            input = input.with_size(size);
+        }
+        Self::apply_common_props(input, props, cx)
     }
 
-    fn render_h2(component: &UiComponent, props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div().text_lg().font_bold();
+    fn render_button(
+        component: &UiComponent,
+        key: SharedString,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        let label = Self::str_prop(props, "label")
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| Self::text_content(component));
+
+        let mut button = Button::new(key.clone()).label(label);
+        if let Some(variant) = Self::str_prop(props, "variant").and_then(Self::button_variant) {
+            button = button.with_variant(variant);
+        }
+        if Self::bool_prop(props, "outline").unwrap_or(false) {
+            button = button.outline();
+        }
+        if let Some(size) = Self::size_prop(props) {
+            button = button.with_size(size);
+        }
+        if let Some(icon) = Self::str_prop(props, "icon") {
+            button = button.icon(Self::icon(icon));
+        }
+        if let Some(tooltip) = Self::str_prop(props, "tooltip") {
+            button = button.tooltip(tooltip.to_string());
+        }
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children(element, &component.children, cx);
+        button = button
+            .disabled(Self::bool_prop(props, "disabled").unwrap_or(false))
+            .loading(Self::bool_prop(props, "loading").unwrap_or(false));
+        if let Some(action) = props.get("on_click").and_then(UiAction::from_value) {
+            button = button.on_click(cx.listener(move |this, _: &ClickEvent, window, cx| {
+                this.dispatch_action(&action, key.clone(), None, window, cx);
+            }));
+        }
 
-        element
+        Self::apply_common_props(button, props, cx)
+    }
+
+    fn render_switch(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        let checked = Self::toggle_value(&key, props, rcx);
+
+        let mut switch = Switch::new(key.clone())
+            .checked(checked)
+            .disabled(Self::bool_prop(props, "disabled").unwrap_or(false))
+            .on_click(cx.listener(move |this, checked: &bool, window, cx| {
+                this.widget_states.toggles.insert(key.clone(), *checked);
+                this.commit_change(&key, Value::Bool(*checked), window, cx);
+            }));
+        if let Some(label) = Self::str_prop(props, "label") {
+            switch = switch.label(label.to_string());
+        }
+        if let Some(size) = Self::size_prop(props) {
+            switch = switch.with_size(size);
+        }
+        switch
     }
 
-    fn render_h3(component: &UiComponent, props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div().text_base().font_bold();
+    fn render_checkbox(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        let checked = Self::toggle_value(&key, props, rcx);
+
+        let mut checkbox = Checkbox::new(key.clone())
+            .checked(checked)
+            .disabled(Self::bool_prop(props, "disabled").unwrap_or(false))
+            .on_click(cx.listener(move |this, checked: &bool, window, cx| {
+                this.widget_states.toggles.insert(key.clone(), *checked);
+                this.commit_change(&key, Value::Bool(*checked), window, cx);
+            }));
+        if let Some(label) = Self::str_prop(props, "label") {
+            checkbox = checkbox.label(label.to_string());
+        }
+        if let Some(size) = Self::size_prop(props) {
+            checkbox = checkbox.with_size(size);
+        }
+        checkbox
+    }
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children(element, &component.children, cx);
+    fn render_radio(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        let checked = Self::toggle_value(&key, props, rcx);
 
-        element
+        let mut radio = Radio::new(key.clone())
+            .checked(checked)
+            .disabled(Self::bool_prop(props, "disabled").unwrap_or(false))
+            .on_click(cx.listener(move |this, checked: &bool, window, cx| {
+                this.widget_states.toggles.insert(key.clone(), *checked);
+                this.commit_change(&key, Value::Bool(*checked), window, cx);
+            }));
+        if let Some(label) = Self::str_prop(props, "label") {
+            radio = radio.label(label.to_string());
+        }
+        radio
     }
 
-    fn render_button(component: &UiComponent, _props: &HashMap<String, UiValue>, _cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let text = component.children.iter()
-            .filter_map(|child| match child {
-                UiChild::Text(text) => Some(text.as_str()),
-                _ => None,
-            })
-            .collect::<Vec<_>>()
-            .join(" ");
+    /// The checked state of a switch, checkbox or radio: the bound value if
+    /// there is a `bind` prop, otherwise the local state seeded by `checked`.
+    fn toggle_value(key: &SharedString, props: &HashMap<String, UiValue>, rcx: &mut UiRenderContext) -> bool {
+        Self::register_binding(key, props, rcx);
+        if let Some(value) = Self::bound_value(props, rcx) {
+            return crate::json_ui::binding::is_truthy(&value);
+        }
 
-        Button::new("json_button").label(text)
+        *rcx.states
+            .toggles
+            .entry(key.clone())
+            .or_insert_with(|| Self::bool_prop(props, "checked").unwrap_or(false))
     }
 
-    fn render_input(_component: &UiComponent, props: &HashMap<String, UiValue>, _cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let placeholder = props.get("placeholder")
-            .and_then(|v| v.as_string())
-            .unwrap_or("")
-            .to_string();
+    fn render_radio_group(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        let options = Self::string_list(props, "options");
+        Self::register_binding(&key, props, rcx);
 
-        div()
-            .border_1()
-            .border_color(gpui::blue())
-            .px_2()
-            .py_1()
-            .rounded_md()
-            .child(placeholder)
+        // A bound value may be the option itself or its index.
+        let bound = Self::bound_value(props, rcx);
+        let bind_index = matches!(bound, Some(Value::Number(_)));
+        let selected = match bound {
+            Some(Value::Number(ix)) => ix.as_u64().map(|ix| ix as usize),
+            Some(value) => {
+                let value = value_to_text(&value);
+                options.iter().position(|option| option.as_ref() == value)
+            }
+            None => rcx.states.selections.get(&key).copied().or_else(|| {
+                Self::num_prop(props, "selected").map(|ix| ix as usize)
+            }),
+        };
+
+        let group = match Self::str_prop(props, "direction") {
+            Some("horizontal") => RadioGroup::horizontal(key.clone()),
+            _ => RadioGroup::vertical(key.clone()),
+        };
+
+        group
+            .children(options.clone())
+            .selected_index(selected)
+            .disabled(Self::bool_prop(props, "disabled").unwrap_or(false))
+            .on_change(cx.listener(move |this, ix: &usize, window, cx| {
+                this.widget_states.selections.insert(key.clone(), *ix);
+                let value = if bind_index {
+                    Value::from(*ix)
+                } else {
+                    Value::String(options.get(*ix).map(|s| s.to_string()).unwrap_or_default())
+                };
+                this.commit_change(&key, value, window, cx);
+            }))
     }
 
-    fn render_text(component: &UiComponent, props: &HashMap<String, UiValue>, _cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let content = props.get("content")
-            .and_then(|v| v.as_string())
-            .map(|s| s.to_string())
-            .or_else(|| {
-                component.children.iter()
-                    .filter_map(|child| match child {
-                        UiChild::Text(text) => Some(text.clone()),
-                        _ => None,
-                    })
-                    .next()
-            })
-            .unwrap_or_else(|| "".to_string());
+    fn render_slider(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        Self::register_binding(&key, props, rcx);
+        let bound = Self::bound_value(props, rcx).and_then(|value| value.as_f64());
+        let synced = Self::prop_values(props, &["min", "max", "step"]);
 
-        let mut element = div().child(content);
-        element = Self::apply_common_props(element, props);
-        element
+        let state = match rcx.states.sliders.get(&key).filter(|widget| widget.props == synced) {
+            Some(widget) => widget.state.clone(),
+            None => {
+                let min = Self::num_prop(props, "min").unwrap_or(0.) as f32;
+                let max = Self::num_prop(props, "max").unwrap_or(100.) as f32;
+                let step = Self::num_prop(props, "step").unwrap_or(1.) as f32;
+                // Rebuilt with the new range, keep the value of the previous state.
+                let previous = rcx
+                    .states
+                    .sliders
+                    .get(&key)
+                    .map(|widget| widget.state.read(cx).value().start() as f64);
+                let value = bound
+                    .or(previous)
+                    .or_else(|| Self::num_prop(props, "value"))
+                    .map(|v| v as f32)
+                    .unwrap_or(min);
+                let state = cx.new(|_| {
+                    SliderState::new()
+                        .min(min)
+                        .max(max)
+                        .step(step)
+                        .default_value(value)
+                });
+
+                let subscription_key = key.clone();
+                let subscription = cx.subscribe_in(
+                    &state,
+                    window,
+                    move |this, _, event: &SliderEvent, window, cx| {
+                        let SliderEvent::Change(value) = event;
+                        this.commit_change(&subscription_key, Value::from(value.start() as f64), window, cx);
+                    },
+                );
+                rcx.states.sliders.insert(
+                    key.clone(),
+                    UiWidget {
+                        state: state.clone(),
+                        props: synced,
+                        _subscription: Some(subscription),
+                    },
+                );
+                state
+            }
+        };
+
+        if let Some(bound) = bound {
+            if state.read(cx).value().start() != bound as f32 {
+                state.update(cx, |state, cx| state.set_value(bound as f32, window, cx));
+            }
+        }
+
+        let slider = Slider::new(&state).disabled(Self::bool_prop(props, "disabled").unwrap_or(false));
+        match Self::str_prop(props, "direction") {
+            Some("vertical") => slider.vertical(),
+            _ => slider.horizontal(),
+        }
     }
 
-    fn render_flex(component: &UiComponent, props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div().flex();
+    fn render_dropdown(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        Self::register_binding(&key, props, rcx);
+        let bound: Option<SharedString> = Self::bound_value(props, rcx)
+            .filter(|value| !value.is_null())
+            .map(|value| value_to_text(&value).into());
 
-        let direction = props.get("direction")
-            .and_then(|v| v.as_string())
-            .unwrap_or("row");
+        let options = Self::string_list(props, "options");
+        let synced = Self::prop_values(props, &["options"]);
 
-        element = match direction {
-            "column" => element.flex_col(),
-            _ => element.flex_row(),
+        let state = match rcx.states.dropdowns.get_mut(&key) {
+            Some(widget) => {
+                if widget.props != synced {
+                    widget.props = synced;
+                    // Keep the selection if it is still one of the options.
+                    widget.state.update(cx, |state, cx| {
+                        let selected = state.selected_value().cloned();
+                        state.set_items(options, window, cx);
+                        match selected {
+                            Some(selected) => state.set_selected_value(&selected, window, cx),
+                            None => state.set_selected_index(None, window, cx),
+                        }
+                    });
+                }
+                widget.state.clone()
+            }
+            None => {
+                let selected = match &bound {
+                    Some(bound) => options.iter().position(|option| option == bound),
+                    None => Self::num_prop(props, "selected").map(|ix| ix as usize),
+                };
+                let state = cx.new(|cx| {
+                    DropdownState::new(options, selected.map(IndexPath::new), window, cx)
+                });
+
+                let subscription_key = key.clone();
+                let subscription = cx.subscribe_in(
+                    &state,
+                    window,
+                    move |this, _, event: &DropdownEvent<Vec<SharedString>>, window, cx| {
+                        let DropdownEvent::Confirm(value) = event;
+                        let value = value
+                            .as_ref()
+                            .map(|value| Value::String(value.to_string()))
+                            .unwrap_or(Value::Null);
+                        this.commit_change(&subscription_key, value, window, cx);
+                    },
+                );
+                rcx.states.dropdowns.insert(
+                    key.clone(),
+                    UiWidget {
+                        state: state.clone(),
+                        props: synced,
+                        _subscription: Some(subscription),
+                    },
+                );
+                state
+            }
         };
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children(element, &component.children, cx);
+        if let Some(bound) = bound {
+            if state.read(cx).selected_value() != Some(&bound) {
+                state.update(cx, |state, cx| state.set_selected_value(&bound, window, cx));
+            }
+        }
 
-        element
+        let mut dropdown = Dropdown::new(&state)
+            .disabled(Self::bool_prop(props, "disabled").unwrap_or(false));
+        if let Some(placeholder) = Self::str_prop(props, "placeholder") {
+            dropdown = dropdown.placeholder(placeholder.to_string());
+        }
+        if Self::bool_prop(props, "cleanable").unwrap_or(false) {
+            dropdown = dropdown.cleanable();
+        }
+        if let Some(size) = Self::size_prop(props) {
+            dropdown = dropdown.with_size(size);
+        }
+        Self::apply_common_props(dropdown, props, cx)
     }
 
-    fn render_column(component: &UiComponent, props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div().flex().flex_col();
+    /// Each child is a tab panel, titled by its `title` prop.
+    ///
+    /// Panels honor `if`, but not `for`.
+    fn render_tabs(
+        component: &UiComponent,
+        key: SharedString,
+        path: &SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let props = &component.props;
+        Self::register_binding(&key, props, rcx);
+        let panels = Self::visible_children(component, rcx);
+        let selected = Self::bound_value(props, rcx)
+            .and_then(|value| value.as_u64())
+            .map(|ix| ix as usize)
+            .or_else(|| rcx.states.selections.get(&key).copied())
+            .or_else(|| Self::num_prop(props, "selected").map(|ix| ix as usize))
+            .unwrap_or(0)
+            .min(panels.len().saturating_sub(1));
+
+        let mut tab_bar = TabBar::new(key.clone())
+            .children(panels.iter().enumerate().map(|(ix, panel)| {
+                let title = rcx.scope.bind_value(
+                    panel
+                        .props
+                        .get("title")
+                        .unwrap_or(&UiValue::String(format!("Tab {}", ix + 1))),
+                );
+                Tab::new(Self::value_to_string(&title))
+            }))
+            .selected_index(selected)
+            .on_click(cx.listener(move |this, ix: &usize, window, cx| {
+                this.widget_states.selections.insert(key.clone(), *ix);
+                this.commit_change(&key, Value::from(*ix), window, cx);
+            }));
+        tab_bar = match Self::str_prop(props, "variant") {
+            Some("pill") => tab_bar.pill(),
+            Some("segmented") => tab_bar.segmented(),
+            Some("underline") => tab_bar.underline(),
+            _ => tab_bar,
+        };
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children(element, &component.children, cx);
+        let content = panels.get(selected).map(|panel| {
+            Self::render_node(panel, format!("{}/{}", path, selected).into(), rcx, window, cx)
+        });
 
-        element
+        let element = v_flex().gap_2().child(tab_bar).children(content);
+        Self::apply_common_props(element, props, cx).into_any_element()
     }
 
-    fn render_row(component: &UiComponent, props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div().flex().flex_row();
+    fn render_table(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        let stripe = Self::bool_prop(props, "stripe").unwrap_or(false);
+        let synced = Self::prop_values(props, &["columns", "rows", "stripe"]);
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children(element, &component.children, cx);
+        let table = match rcx.states.tables.get_mut(&key) {
+            Some(widget) => {
+                if widget.props != synced {
+                    widget.props = synced;
+                    widget.state.update(cx, |table, cx| {
+                        *table.delegate_mut() = UiTableDelegate::from_props(props);
+                        table.set_stripe(stripe, cx);
+                        table.refresh(cx);
+                    });
+                }
+                widget.state.clone()
+            }
+            None => {
+                let table = cx.new(|cx| Table::new(UiTableDelegate::from_props(props), window, cx).stripe(stripe));
+                rcx.states.tables.insert(
+                    key,
+                    UiWidget {
+                        state: table.clone(),
+                        props: synced,
+                        _subscription: None,
+                    },
+                );
+                table
+            }
+        };
 
-        element
+        Self::apply_common_props(div().size_full().min_h(px(120.)), props, cx).child(table)
     }
 
-    fn render_unknown(component: &UiComponent, _props: &HashMap<String, UiValue>, cx: &mut Context<crate::json_ui::JsonCanvas>) -> impl IntoElement {
-        let mut element = div()
-            .border_1()
-            .border_color(gpui::red())
-            .p_2()
-            .child(format!("Unknown component: {}", component.component_type));
+    /// `on_click` is dispatched with the clicked item as its value, `on_change` with the selection.
+    fn render_list(
+        component: &UiComponent,
+        key: SharedString,
+        rcx: &mut UiRenderContext,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        Self::register_binding(&key, props, rcx);
+        let items = Self::string_list(props, "items");
+        let selected = match Self::bound_value(props, rcx) {
+            Some(value) => {
+                let value = value_to_text(&value);
+                items.iter().position(|item| item.as_ref() == value)
+            }
+            None => rcx.states.selections.get(&key).copied(),
+        };
+        let on_click = props.get("on_click").and_then(UiAction::from_value);
 
-        element = Self::apply_children(element, &component.children, cx);
-        element
+        let items = items
+            .into_iter()
+            .enumerate()
+            .map(|(ix, item)| {
+                let key = key.clone();
+                let on_click = on_click.clone();
+                let value = item.clone();
+                ListItem::new(SharedString::from(format!("{}-{}", key, ix)))
+                    .selected(selected == Some(ix))
+                    .on_click(cx.listener(move |this, _: &ClickEvent, window, cx| {
+                        let value = Value::String(value.to_string());
+                        if let Some(action) = &on_click {
+                            this.dispatch_action(action, key.clone(), Some(value.clone()), window, cx);
+                        }
+                        this.widget_states.selections.insert(key.clone(), ix);
+                        this.commit_change(&key, value, window, cx);
+                    }))
+                    .child(item)
+            })
+            .collect::<Vec<_>>();
+
+        Self::apply_common_props(v_flex(), props, cx).children(items)
+    }
+
+    /// Each child is an accordion item, titled by its `title` prop, with its children as content.
+    ///
+    /// Items honor `if`, but not `for`.
+    fn render_accordion(
+        component: &UiComponent,
+        key: SharedString,
+        path: &SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let props = &component.props;
+        let items = Self::visible_children(component, rcx);
+        let open_items = rcx
+            .states
+            .open_items
+            .entry(key.clone())
+            .or_insert_with(|| {
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| Self::bool_prop(&item.props, "open").unwrap_or(false))
+                    .map(|(ix, _)| ix)
+                    .collect()
+            })
+            .clone();
+
+        let canvas = cx.entity().downgrade();
+        let mut accordion = Accordion::new(key.clone())
+            .multiple(Self::bool_prop(props, "multiple").unwrap_or(false))
+            .bordered(Self::bool_prop(props, "bordered").unwrap_or(true))
+            .on_toggle_click(move |open: &[usize], _, cx| {
+                let key = key.clone();
+                let open = open.to_vec();
+                _ = canvas.update(cx, |this, cx| {
+                    this.widget_states.open_items.insert(key, open);
+                    cx.notify();
+                });
+            });
+
+        for (ix, item) in items.into_iter().enumerate() {
+            let item = &rcx.scope.bind_component(item);
+            let title = Self::str_prop(&item.props, "title").unwrap_or("").to_string();
+            let item_path: SharedString = format!("{}/{}", path, ix).into();
+            let content = Self::apply_children(v_flex().gap_2(), item, &item_path, rcx, window, cx);
+            accordion = accordion.item(|this| {
+                this.title(title)
+                    .open(open_items.contains(&ix))
+                    .content(content)
+            });
+        }
+
+        accordion.into_any_element()
+    }
+
+    fn render_alert(component: &UiComponent, key: SharedString) -> impl IntoElement {
+        let props = &component.props;
+        let message = Self::str_prop(props, "message")
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| Self::text_content(component));
+
+        let mut alert = match Self::str_prop(props, "variant") {
+            Some("success") => Alert::success(key, message),
+            Some("warning") => Alert::warning(key, message),
+            Some("error") | Some("danger") => Alert::error(key, message),
+            _ => Alert::info(key, message),
+        };
+        if let Some(title) = Self::str_prop(props, "title") {
+            alert = alert.title(title.to_string());
+        }
+        if Self::bool_prop(props, "banner").unwrap_or(false) {
+            alert = alert.banner();
+        }
+        alert
+    }
+
+    fn render_badge(
+        component: &UiComponent,
+        path: &SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let props = &component.props;
+        let mut badge = Badge::new();
+        if Self::bool_prop(props, "dot").unwrap_or(false) {
+            badge = badge.dot();
+        }
+        if let Some(count) = Self::num_prop(props, "count") {
+            badge = badge.count(count as usize);
+        }
+        if let Some(max) = Self::num_prop(props, "max") {
+            badge = badge.max(max as usize);
+        }
+        if let Some(color) = Self::str_prop(props, "color").and_then(|c| Self::resolve_color(c, cx)) {
+            badge = badge.color(color);
+        }
+        Self::apply_children(badge, component, path, rcx, window, cx).into_any_element()
     }
 
-    fn apply_common_props<E: Styled>(mut element: E, props: &HashMap<String, UiValue>) -> E {
-        if let Some(width) = props.get("width").and_then(|v| v.as_number()) {
-            element = element.w(px(width as f32));
+    fn render_tag(component: &UiComponent) -> impl IntoElement {
+        let props = &component.props;
+        let mut tag = match Self::str_prop(props, "variant") {
+            Some("primary") => Tag::primary(),
+            Some("danger") => Tag::danger(),
+            Some("success") => Tag::success(),
+            Some("warning") => Tag::warning(),
+            Some("info") => Tag::info(),
+            Some("secondary") | None => Tag::secondary(),
+            Some(color) => Tag::color(color),
+        };
+        if Self::bool_prop(props, "outline").unwrap_or(false) {
+            tag = tag.outline();
         }
+        if Self::bool_prop(props, "rounded").unwrap_or(false) {
+            tag = tag.rounded_full();
+        }
+        if let Some(size) = Self::size_prop(props) {
+            tag = tag.with_size(size);
+        }
+        let label = Self::str_prop(props, "label")
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| Self::text_content(component));
+        tag.child(label)
+    }
 
-        if let Some(height) = props.get("height").and_then(|v| v.as_number()) {
-            element = element.h(px(height as f32));
+    fn render_avatar(component: &UiComponent, cx: &mut Context<JsonCanvas>) -> impl IntoElement {
+        let props = &component.props;
+        let mut avatar = Avatar::new();
+        if let Some(src) = Self::str_prop(props, "src") {
+            avatar = avatar.src(src.to_string());
+        }
+        if let Some(name) = Self::str_prop(props, "name") {
+            avatar = avatar.name(name.to_string());
+        }
+        if let Some(size) = Self::size_prop(props) {
+            avatar = avatar.with_size(size);
         }
+        Self::apply_common_props(avatar, props, cx)
+    }
+
+    fn render_progress(component: &UiComponent, cx: &mut Context<JsonCanvas>) -> impl IntoElement {
+        let value = Self::num_prop(&component.props, "value").unwrap_or(0.) as f32;
+        Self::apply_common_props(div().w_full(), &component.props, cx).child(Progress::new().value(value))
+    }
 
-        if let Some(padding) = props.get("padding").and_then(|v| v.as_number()) {
-            element = element.p(px(padding as f32));
+    fn render_tooltip(
+        component: &UiComponent,
+        key: SharedString,
+        path: &SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
+    ) -> AnyElement {
+        let text = Self::str_prop(&component.props, "text").unwrap_or("").to_string();
+        let element = Self::apply_common_props(div().id(key), &component.props, cx)
+            .tooltip(move |window, cx| Tooltip::new(text.clone()).build(window, cx));
+        Self::apply_children(element, component, path, rcx, window, cx).into_any_element()
+    }
+
+    fn render_divider(component: &UiComponent, cx: &mut Context<JsonCanvas>) -> impl IntoElement {
+        let props = &component.props;
+        let mut divider = match Self::str_prop(props, "direction") {
+            Some("vertical") => Divider::vertical(),
+            _ => Divider::horizontal(),
+        };
+        if let Some(label) = Self::str_prop(props, "label") {
+            divider = divider.label(label.to_string());
+        }
+        if let Some(color) = Self::str_prop(props, "color").and_then(|c| Self::resolve_color(c, cx)) {
+            divider = divider.color(color);
         }
+        divider
+    }
 
-        if let Some(margin) = props.get("margin").and_then(|v| v.as_number()) {
-            element = element.m(px(margin as f32));
+    fn render_icon(component: &UiComponent, cx: &mut Context<JsonCanvas>) -> impl IntoElement {
+        let props = &component.props;
+        let mut icon = Self::icon(Self::str_prop(props, "name").unwrap_or(""));
+        if let Some(size) = Self::size_prop(props) {
+            icon = icon.with_size(size);
+        }
+        if let Some(color) = Self::str_prop(props, "color").and_then(|c| Self::resolve_color(c, cx)) {
+            icon = icon.text_color(color);
         }
+        icon
+    }
 
-        if let Some(bg_color) = props.get("backgroundColor").and_then(|v| v.as_string()) {
-            element = match bg_color {
-                "red" => element.bg(gpui::red()),
-                "blue" => element.bg(gpui::blue()),
-                "green" => element.bg(gpui::green()),
-                "yellow" => element.bg(gpui::yellow()),
-                "gray" => element.bg(gray(500)),
-                "white" => element.bg(gpui::white()),
-                "black" => element.bg(gpui::black()),
-                _ => element,
-            };
+    /// A button that opens a modal (or drawer) rendering this node's children.
+    fn render_overlay_trigger(
+        component: &UiComponent,
+        key: SharedString,
+        drawer: bool,
+        rcx: &mut UiRenderContext,
+        cx: &mut Context<JsonCanvas>,
+    ) -> impl IntoElement {
+        let props = &component.props;
+        let label = Self::str_prop(props, "label").unwrap_or("Open").to_string();
+        let title = Self::str_prop(props, "title").map(|s| s.to_string());
+        let placement = match Self::str_prop(props, "placement") {
+            Some("left") => Placement::Left,
+            Some("top") => Placement::Top,
+            Some("bottom") => Placement::Bottom,
+            _ => Placement::Right,
+        };
+        let content = component.clone();
+        let content_path: SharedString = format!("{}/content", key).into();
+        // The content is rendered later, so keep the `for` locals it is nested in.
+        let locals = rcx.scope.locals().clone();
+
+        let mut button = Button::new(key).label(label);
+        if let Some(variant) = Self::str_prop(props, "variant").and_then(Self::button_variant) {
+            button = button.with_variant(variant);
         }
 
-        if let Some(text_color) = props.get("color").and_then(|v| v.as_string()) {
-            element = match text_color {
-                "red" => element.text_color(gpui::red()),
-                "blue" => element.text_color(gpui::blue()),
-                "green" => element.text_color(gpui::green()),
-                "yellow" => element.text_color(gpui::yellow()),
-                "gray" => element.text_color(gray(500)),
-                "white" => element.text_color(gpui::white()),
-                "black" => element.text_color(gpui::black()),
-                _ => element,
+        button.on_click(cx.listener(move |_, _: &ClickEvent, window, cx| {
+            let canvas = cx.entity();
+            let title = title.clone();
+            let content = content.clone();
+            let content_path = content_path.clone();
+            let locals = locals.clone();
+            let build = move |window: &mut Window, cx: &mut App| {
+                canvas.update(cx, |this, cx| {
+                    let mut rcx = UiRenderContext {
+                        states: &mut this.widget_states,
+                        scope: DataScope::with_locals(&this.data, locals.clone()),
+                    };
+                    let element = Self::apply_children(
+                        v_flex().gap_3(),
+                        &content,
+                        &content_path,
+                        &mut rcx,
+                        window,
+                        cx,
+                    );
+                    element.into_any_element()
+                })
             };
+
+            if drawer {
+                window.open_drawer_at(placement, cx, move |drawer, window, cx| {
+                    let drawer = match &title {
+                        Some(title) => drawer.title(title.clone()),
+                        None => drawer,
+                    };
+                    drawer.child(build(window, cx))
+                });
+            } else {
+                window.open_modal(cx, move |modal, window, cx| {
+                    let modal = match &title {
+                        Some(title) => modal.title(title.clone()),
+                        None => modal,
+                    };
+                    modal.child(build(window, cx))
+                });
+            }
+        }))
+    }
+
+    /// A visible placeholder for a node that could not be rendered.
+    pub(crate) fn render_error(message: String, cx: &App) -> impl IntoElement {
+        h_flex()
+            .gap_2()
+            .p_2()
+            .border_1()
+            .border_color(cx.theme().danger)
+            .bg(cx.theme().danger.opacity(0.1))
+            .text_color(cx.theme().danger)
+            .rounded(cx.theme().radius)
+            .child(Self::icon("triangle-alert"))
+            .child(message)
+    }
+
+    /// Apply the style props of [`COMMON_PROPS`].
+    ///
+    /// Props with invalid values are skipped, validation reports them when the file is loaded.
+    fn apply_common_props<E: Styled>(
+        mut element: E,
+        props: &HashMap<String, UiValue>,
+        cx: &App,
+    ) -> E {
+        let length = |name: &str| props.get(name).and_then(UiLength::parse);
+        let edges = |name: &str| props.get(name).and_then(UiLength::parse_edges);
+
+        let sizes: [(&str, fn(E, Length) -> E); 6] = [
+            ("width", |e, l| e.w(l)),
+            ("height", |e, l| e.h(l)),
+            ("min_width", |e, l| e.min_w(l)),
+            ("max_width", |e, l| e.max_w(l)),
+            ("min_height", |e, l| e.min_h(l)),
+            ("max_height", |e, l| e.max_h(l)),
+        ];
+        for (name, apply) in sizes {
+            if let Some(value) = length(name) {
+                element = apply(element, Self::length(value));
+            }
+        }
+
+        // Shorthands first, so the more specific props override them.
+        let spacings: [(&str, fn(E, DefiniteLength) -> E); 9] = [
+            ("padding_x", |e, l| e.pl(l).pr(l)),
+            ("padding_y", |e, l| e.pt(l).pb(l)),
+            ("padding_top", |e, l| e.pt(l)),
+            ("padding_right", |e, l| e.pr(l)),
+            ("padding_bottom", |e, l| e.pb(l)),
+            ("padding_left", |e, l| e.pl(l)),
+            ("gap", |e, l| e.gap(l)),
+            ("gap_x", |e, l| e.gap_x(l)),
+            ("gap_y", |e, l| e.gap_y(l)),
+        ];
+        if let Some(padding) = edges("padding") {
+            let sides: [fn(E, DefiniteLength) -> E; 4] =
+                [|e, l| e.pt(l), |e, l| e.pr(l), |e, l| e.pb(l), |e, l| e.pl(l)];
+            for (value, apply) in padding.into_iter().zip(sides) {
+                if let Some(value) = Self::definite_length(value) {
+                    element = apply(element, value);
+                }
+            }
+        }
+        for (name, apply) in spacings {
+            if let Some(value) = length(name).and_then(Self::definite_length) {
+                element = apply(element, value);
+            }
+        }
+
+        let margins: [(&str, fn(E, Length) -> E); 6] = [
+            ("margin_x", |e, l| e.ml(l).mr(l)),
+            ("margin_y", |e, l| e.mt(l).mb(l)),
+            ("margin_top", |e, l| e.mt(l)),
+            ("margin_right", |e, l| e.mr(l)),
+            ("margin_bottom", |e, l| e.mb(l)),
+            ("margin_left", |e, l| e.ml(l)),
+        ];
+        if let Some([top, right, bottom, left]) = edges("margin").map(|e| e.map(Self::length)) {
+            element = element.mt(top).mr(right).mb(bottom).ml(left);
+        }
+        for (name, apply) in margins {
+            if let Some(value) = length(name) {
+                element = apply(element, Self::length(value));
+            }
+        }
+
+        match Self::str_prop(props, "align") {
+            Some("start") => element = element.items_start(),
+            Some("center") => element = element.items_center(),
+            Some("end") => element = element.items_end(),
+            Some("baseline") => element = element.items_baseline(),
+            Some("stretch") => element.style().align_items = Some(AlignItems::Stretch),
+            _ => {}
+        }
+        match Self::str_prop(props, "justify") {
+            Some("start") => element = element.justify_start(),
+            Some("center") => element = element.justify_center(),
+            Some("end") => element = element.justify_end(),
+            Some("between") => element = element.justify_between(),
+            Some("around") => element = element.justify_around(),
+            _ => {}
+        }
+        if Self::bool_prop(props, "wrap") == Some(true) {
+            element = element.flex_wrap();
+        }
+        if let Some(grow) = Self::num_prop(props, "grow") {
+            element.style().flex_grow = Some(grow as f32);
+        }
+        if let Some(shrink) = Self::num_prop(props, "shrink") {
+            element.style().flex_shrink = Some(shrink as f32);
+        }
+
+        if let Some(color) = Self::color_prop(props, &["bg", "backgroundColor"], cx) {
+            element = element.bg(color);
+        }
+
+        if let Some(color) = Self::color_prop(props, &["color"], cx) {
+            element = element.text_color(color);
+        }
+
+        let border_width = edges("border_width");
+        if let Some(border_width) = border_width {
+            let sides: [fn(E, AbsoluteLength) -> E; 4] =
+                [|e, w| e.border_t(w), |e, w| e.border_r(w), |e, w| e.border_b(w), |e, w| e.border_l(w)];
+            for (value, apply) in border_width.into_iter().zip(sides) {
+                if let Some(value) = Self::absolute_length(value) {
+                    element = apply(element, value);
+                }
+            }
+        }
+        if let Some(color) = Self::color_prop(props, &["borderColor"], cx) {
+            if border_width.is_none() {
+                element = element.border_1();
+            }
+            element = element.border_color(color);
+        }
+
+        if let Some(corners) = edges("border_radius") {
+            if matches!(corners[0], UiLength::Fraction(_)) {
+                element = element.rounded_full();
+            } else if let [Some(top_left), Some(top_right), Some(bottom_right), Some(bottom_left)] =
+                corners.map(Self::absolute_length)
+            {
+                element = element
+                    .rounded_tl(top_left)
+                    .rounded_tr(top_right)
+                    .rounded_br(bottom_right)
+                    .rounded_bl(bottom_left);
+            }
+        }
+
+        match props.get("shadow").and_then(UiShadow::parse) {
+            Some(UiShadow::Named(name)) => {
+                element = match name.as_str() {
+                    "xs" => element.shadow_xs(),
+                    "sm" => element.shadow_sm(),
+                    "md" => element.shadow_md(),
+                    "lg" => element.shadow_lg(),
+                    "xl" => element.shadow_xl(),
+                    "2xl" => element.shadow_2xl(),
+                    _ => element.shadow_none(),
+                }
+            }
+            Some(UiShadow::Custom(shadows)) => {
+                let shadows = shadows
+                    .into_iter()
+                    .map(|shadow| {
+                        let color = shadow
+                            .color
+                            .and_then(|color| Self::resolve_color(&color, cx))
+                            .unwrap_or(hsla(0., 0., 0., 0.1));
+                        box_shadow(px(shadow.x), px(shadow.y), px(shadow.blur), px(shadow.spread), color)
+                    })
+                    .collect::<Vec<_>>();
+                element = element.shadow(shadows);
+            }
+            None => {}
+        }
+
+        if let Some(opacity) = Self::num_prop(props, "opacity") {
+            element = element.opacity(opacity.clamp(0., 1.) as f32);
+        }
+
+        // `scroll` needs a stateful element, see `render_container`.
+        let hidden = |name: &str| {
+            Self::str_prop(props, name).or(Self::str_prop(props, "overflow")) == Some("hidden")
+        };
+        if hidden("overflow_x") {
+            element = element.overflow_x_hidden();
+        }
+        if hidden("overflow_y") {
+            element = element.overflow_y_hidden();
+        }
+
+        match Self::str_prop(props, "position") {
+            Some("absolute") => element = element.absolute(),
+            Some("relative") => element = element.relative(),
+            _ => {}
+        }
+        let offsets: [(&str, fn(E, Length) -> E); 4] = [
+            ("top", |e, l| e.top(l)),
+            ("right", |e, l| e.right(l)),
+            ("bottom", |e, l| e.bottom(l)),
+            ("left", |e, l| e.left(l)),
+        ];
+        for (name, apply) in offsets {
+            if let Some(value) = length(name) {
+                element = apply(element, Self::length(value));
+            }
+        }
+
+        match Self::str_prop(props, "font_size") {
+            Some("xs") => element = element.text_xs(),
+            Some("sm") => element = element.text_sm(),
+            Some("base") => element = element.text_base(),
+            Some("lg") => element = element.text_lg(),
+            Some("xl") => element = element.text_xl(),
+            Some("2xl") => element = element.text_2xl(),
+            Some("3xl") => element = element.text_3xl(),
+            _ => {
+                if let Some(size) = length("font_size").and_then(Self::absolute_length) {
+                    element = element.text_size(size);
+                }
+            }
+        }
+        if let Some(weight) = props.get("font_weight").and_then(parse_font_weight) {
+            element = element.font_weight(FontWeight(weight));
+        }
+        if let Some(line_height) = length("line_height").and_then(Self::definite_length) {
+            element = element.line_height(line_height);
+        }
+        match Self::str_prop(props, "text_align") {
+            Some("left") => element = element.text_left(),
+            Some("center") => element = element.text_center(),
+            Some("right") => element = element.text_right(),
+            _ => {}
+        }
+        if Self::bool_prop(props, "truncate") == Some(true) {
+            element = element.truncate();
         }
 
         element
     }
 
+    fn length(length: UiLength) -> Length {
+        Self::definite_length(length).map_or(Length::Auto, Length::Definite)
+    }
+
+    /// `None` for `auto`.
+    fn definite_length(length: UiLength) -> Option<DefiniteLength> {
+        match length {
+            UiLength::Px(value) => Some(px(value).into()),
+            UiLength::Rems(value) => Some(rems(value).into()),
+            UiLength::Fraction(value) => Some(relative(value)),
+            UiLength::Auto => None,
+        }
+    }
+
+    /// `None` for `auto` and fractions.
+    fn absolute_length(length: UiLength) -> Option<AbsoluteLength> {
+        match length {
+            UiLength::Px(value) => Some(px(value).into()),
+            UiLength::Rems(value) => Some(rems(value).into()),
+            UiLength::Fraction(_) | UiLength::Auto => None,
+        }
+    }
+
     fn apply_children<E: ParentElement>(
         mut element: E,
-        children: &[UiChild],
-        cx: &mut Context<crate::json_ui::JsonCanvas>
+        component: &UiComponent,
+        path: &SharedString,
+        rcx: &mut UiRenderContext,
+        window: &mut Window,
+        cx: &mut Context<JsonCanvas>,
     ) -> E {
-        for child in children {
+        for (ix, child) in component.children.iter().enumerate() {
             match child {
-                UiChild::Component(component) => {
-                    element = element.child(Self::render_component_internal(component, &component.props, cx));
+                UiChild::Component(child) => {
+                    let child_path = format!("{}/{}", path, ix).into();
+                    element = element.children(Self::expand_node(child, child_path, rcx, window, cx));
                 }
                 UiChild::Text(text) => {
                     element = element.child(text.clone());
                 }
-                UiChild::Reference { .. } => {
+                UiChild::Reference { reference, .. } => {
                     // References should have been resolved by the parser
+                    element = element.child(Self::render_error(
+                        format!("Unresolved reference `{}`", reference),
+                        cx,
+                    ));
                 }
             }
         }
         element
     }
 
-    fn render_component_standalone(
-        component: &UiComponent,
-        props: &HashMap<String, UiValue>
-    ) -> AnyElement {
-        match component.component_type.as_str() {
-            "div" => Self::render_div_standalone(component, props).into_any_element(),
-            "h1" => Self::render_h1_standalone(component, props).into_any_element(),
-            "h2" => Self::render_h2_standalone(component, props).into_any_element(),
-            "h3" => Self::render_h3_standalone(component, props).into_any_element(),
-            "button" => Self::render_button_standalone(component, props).into_any_element(),
-            "input" => Self::render_input_standalone(component, props).into_any_element(),
-            "text" => Self::render_text_standalone(component, props).into_any_element(),
-            "flex" => Self::render_flex_standalone(component, props).into_any_element(),
-            "column" => Self::render_column_standalone(component, props).into_any_element(),
-            "row" => Self::render_row_standalone(component, props).into_any_element(),
-            _ => Self::render_unknown_standalone(component, props).into_any_element(),
-        }
-    }
-
-    fn render_div_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div();
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children_standalone(element, &component.children);
-        element
+    /// The component children whose `if` directive (if any) holds.
+    fn visible_children<'c>(component: &'c UiComponent, rcx: &UiRenderContext) -> Vec<&'c UiComponent> {
+        component
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                UiChild::Component(c) if Self::is_visible(c, rcx) => Some(c),
+                _ => None,
+            })
+            .collect()
     }
 
-    fn render_h1_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div().text_xl().font_bold();
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children_standalone(element, &component.children);
-        element
+    /// Record the `bind` path and `on_change` action of a widget for [`JsonCanvas::commit_change`].
+    fn register_binding(key: &SharedString, props: &HashMap<String, UiValue>, rcx: &mut UiRenderContext) {
+        let binding = UiBinding {
+            path: Self::str_prop(props, "bind").and_then(|bind| rcx.scope.resolve_path(bind)),
+            on_change: props.get("on_change").and_then(UiAction::from_value),
+        };
+        rcx.states.bindings.insert(key.clone(), binding);
     }
 
-    fn render_h2_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div().text_lg().font_bold();
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children_standalone(element, &component.children);
-        element
+    /// The current value of the `bind` path in the data model.
+    fn bound_value(props: &HashMap<String, UiValue>, rcx: &UiRenderContext) -> Option<Value> {
+        let bind = Self::str_prop(props, "bind")?;
+        Some(rcx.scope.lookup(bind).unwrap_or(Value::Null))
     }
 
-    fn render_h3_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div().text_base().font_bold();
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children_standalone(element, &component.children);
-        element
+    /// Resolve a color value.
+    ///
+    /// Accepts theme tokens (`primary`, `muted-foreground`), palette scales
+    /// (`blue-500`, `blue`), `white`/`black` and hex colors (`#RRGGBB[AA]`).
+    pub fn resolve_color(value: &str, cx: &App) -> Option<Hsla> {
+        if value.starts_with('#') {
+            return Hsla::parse_hex(value).ok();
+        }
+        if let Some(color) = cx.theme().colors.get(value) {
+            return Some(color);
+        }
+
+        match value {
+            "white" => return Some(crate::white()),
+            "black" => return Some(crate::black()),
+            "transparent" => return Some(gpui::transparent_black()),
+            _ => {}
+        }
+
+        let (name, scale) = match value.rsplit_once('-') {
+            Some((name, scale)) => (name, scale.parse::<usize>().ok()?),
+            None => (value, 500),
+        };
+        ColorName::all()
+            .into_iter()
+            .find(|color| color.to_string().eq_ignore_ascii_case(name))
+            .map(|color| color.scale(scale))
+    }
+
+    fn color_prop(props: &HashMap<String, UiValue>, names: &[&str], cx: &App) -> Option<Hsla> {
+        let value = names.iter().find_map(|name| Self::str_prop(props, name))?;
+        let color = Self::resolve_color(value, cx);
+        if color.is_none() {
+            tracing::warn!("JSON UI: unknown color `{}`", value);
+        }
+        color
     }
 
-    fn render_button_standalone(component: &UiComponent, _props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let text = component.children.iter()
+    fn button_variant(variant: &str) -> Option<ButtonVariant> {
+        Some(match variant {
+            "primary" => ButtonVariant::Primary,
+            "secondary" => ButtonVariant::Secondary,
+            "danger" => ButtonVariant::Danger,
+            "info" => ButtonVariant::Info,
+            "success" => ButtonVariant::Success,
+            "warning" => ButtonVariant::Warning,
+            "ghost" => ButtonVariant::Ghost,
+            "link" => ButtonVariant::Link,
+            "text" => ButtonVariant::Text,
+            _ => return None,
+        })
+    }
+
+    /// Icons are referenced by their asset file name, e.g. `"arrow-left"`.
+    fn icon(name: &str) -> Icon {
+        Icon::empty().path(format!("icons/{}.svg", name))
+    }
+
+    fn size_prop(props: &HashMap<String, UiValue>) -> Option<Size> {
+        match Self::str_prop(props, "size")? {
+            "xsmall" => Some(Size::XSmall),
+            "small" => Some(Size::Small),
+            "medium" => Some(Size::Medium),
+            "large" => Some(Size::Large),
+            _ => None,
+        }
+    }
+
+    /// The values of the given props, to tell when a widget has to be synced with them.
+    fn prop_values(props: &HashMap<String, UiValue>, names: &[&str]) -> Vec<Option<UiValue>> {
+        names.iter().map(|name| props.get(*name).cloned()).collect()
+    }
+
+    fn str_prop<'a>(props: &'a HashMap<String, UiValue>, name: &str) -> Option<&'a str> {
+        props.get(name).and_then(|v| v.as_string())
+    }
+
+    fn num_prop(props: &HashMap<String, UiValue>, name: &str) -> Option<f64> {
+        props.get(name).and_then(|v| v.as_number())
+    }
+
+    fn bool_prop(props: &HashMap<String, UiValue>, name: &str) -> Option<bool> {
+        props.get(name).and_then(|v| v.as_bool())
+    }
+
+    fn string_list(props: &HashMap<String, UiValue>, name: &str) -> Vec<SharedString> {
+        props
+            .get(name)
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|item| Self::value_to_string(item).into())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn value_to_string(value: &UiValue) -> String {
+        match value {
+            UiValue::String(s) => s.clone(),
+            UiValue::Number(n) => n.to_string(),
+            UiValue::Boolean(b) => b.to_string(),
+            UiValue::Array(_) | UiValue::Object(_) => String::new(),
+        }
+    }
+
+    /// The text children of a component, joined by spaces.
+    fn text_content(component: &UiComponent) -> String {
+        component
+            .children
+            .iter()
             .filter_map(|child| match child {
                 UiChild::Text(text) => Some(text.as_str()),
                 _ => None,
             })
             .collect::<Vec<_>>()
-            .join(" ");
-
-        Button::new("json_button").label(text)
+            .join(" ")
     }
+}
 
-    fn render_input_standalone(_component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let placeholder = props.get("placeholder")
-            .and_then(|v| v.as_string())
-            .unwrap_or("")
-            .to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ActiveTheme as _;
 
-        div()
-            .border_1()
-            .border_color(gpui::blue())
-            .px_2()
-            .py_1()
-            .rounded_md()
-            .child(placeholder)
+    fn props(json: &str) -> HashMap<String, UiValue> {
+        serde_json::from_str(json).unwrap()
     }
 
-    fn render_text_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let content = props.get("content")
-            .and_then(|v| v.as_string())
-            .map(|s| s.to_string())
-            .or_else(|| {
-                component.children.iter()
-                    .filter_map(|child| match child {
-                        UiChild::Text(text) => Some(text.clone()),
-                        _ => None,
-                    })
-                    .next()
-            })
-            .unwrap_or_else(|| "".to_string());
+    #[test]
+    fn test_table_delegate_from_props() {
+        let delegate = UiTableDelegate::from_props(&props(
+            r#"{
+                "columns": [
+                    { "key": "name", "name": "Name", "width": 240, "sortable": true },
+                    { "key": "age" },
+                    "Note"
+                ],
+                "rows": [
+                    { "name": "Ann", "age": 32, "extra": "ignored" },
+                    ["Bob", 7, true],
+                    "Solo"
+                ]
+            }"#,
+        ));
 
-        let mut element = div().child(content);
-        element = Self::apply_common_props(element, props);
-        element
-    }
+        let columns = delegate
+            .columns
+            .iter()
+            .map(|col| (col.key.to_string(), col.name.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            [
+                ("name".to_string(), "Name".to_string()),
+                ("age".to_string(), "age".to_string()),
+                ("Note".to_string(), "Note".to_string()),
+            ]
+        );
+        assert_eq!(delegate.columns[0].width, px(240.));
+        assert_eq!(delegate.columns[0].sort, Some(ColumnSort::Default));
+        assert_eq!(delegate.columns[1].sort, None);
 
-    fn render_flex_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div().flex();
+        let rows = delegate
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                vec!["Ann", "32", ""],
+                vec!["Bob", "7", "true"],
+                vec!["Solo"],
+            ]
+        );
+        assert_eq!(delegate.original_rows, delegate.rows);
 
-        let direction = props.get("direction")
-            .and_then(|v| v.as_string())
-            .unwrap_or("row");
+        let empty = UiTableDelegate::from_props(&props("{}"));
+        assert!(empty.columns.is_empty() && empty.rows.is_empty());
+    }
 
-        element = match direction {
-            "column" => element.flex_col(),
-            _ => element.flex_row(),
-        };
+    #[test]
+    fn test_compare_cells() {
+        use std::cmp::Ordering;
 
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children_standalone(element, &component.children);
-        element
+        assert_eq!(compare_cells("9", "10"), Ordering::Less);
+        assert_eq!(compare_cells("-1.5", "-2"), Ordering::Greater);
+        assert_eq!(compare_cells("9", "10a"), Ordering::Greater);
+        assert_eq!(compare_cells("b", "a"), Ordering::Greater);
     }
 
-    fn render_column_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div().flex().flex_col();
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children_standalone(element, &component.children);
-        element
-    }
+    #[test]
+    fn test_props() {
+        let props = props(
+            r#"{
+                "count": 3,
+                "label": "Hi",
+                "open": true,
+                "size": "small",
+                "options": ["One", 2, false, ["nested"]]
+            }"#,
+        );
 
-    fn render_row_standalone(component: &UiComponent, props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div().flex().flex_row();
-        element = Self::apply_common_props(element, props);
-        element = Self::apply_children_standalone(element, &component.children);
-        element
+        assert_eq!(UiRenderer::num_prop(&props, "count"), Some(3.));
+        assert_eq!(UiRenderer::num_prop(&props, "label"), None);
+        assert_eq!(UiRenderer::str_prop(&props, "label"), Some("Hi"));
+        assert_eq!(UiRenderer::bool_prop(&props, "open"), Some(true));
+        assert_eq!(UiRenderer::bool_prop(&props, "missing"), None);
+        assert_eq!(UiRenderer::size_prop(&props), Some(Size::Small));
+        assert_eq!(
+            UiRenderer::string_list(&props, "options"),
+            [
+                SharedString::from("One"),
+                SharedString::from("2"),
+                SharedString::from("false"),
+                SharedString::from(""),
+            ]
+        );
+        assert!(UiRenderer::string_list(&props, "label").is_empty());
+        assert_eq!(
+            UiRenderer::prop_values(&props, &["count", "missing"]),
+            [Some(UiValue::Number(3.)), None]
+        );
     }
 
-    fn render_unknown_standalone(component: &UiComponent, _props: &HashMap<String, UiValue>) -> impl IntoElement {
-        let mut element = div()
-            .border_1()
-            .border_color(gpui::red())
-            .p_2()
-            .child(format!("Unknown component: {}", component.component_type));
+    #[gpui::test]
+    fn test_resolve_color(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            crate::theme::init(cx);
 
-        element = Self::apply_children_standalone(element, &component.children);
-        element
+            let theme = cx.theme().clone();
+            assert_eq!(UiRenderer::resolve_color("primary", cx), Some(theme.primary));
+            assert_eq!(
+                UiRenderer::resolve_color("muted-foreground", cx),
+                Some(theme.muted_foreground)
+            );
+            assert_eq!(
+                UiRenderer::resolve_color("#ff0000", cx),
+                Hsla::parse_hex("#ff0000").ok()
+            );
+            assert_eq!(UiRenderer::resolve_color("white", cx), Some(crate::white()));
+            assert_eq!(
+                UiRenderer::resolve_color("blue-300", cx),
+                Some(ColorName::Blue.scale(300))
+            );
+            assert_eq!(
+                UiRenderer::resolve_color("Blue", cx),
+                Some(ColorName::Blue.scale(500))
+            );
+            assert_eq!(UiRenderer::resolve_color("blue-dark", cx), None);
+            assert_eq!(UiRenderer::resolve_color("nope", cx), None);
+            assert_eq!(UiRenderer::resolve_color("#zz", cx), None);
+        });
     }
 
-    fn apply_children_standalone<E: ParentElement>(
-        mut element: E,
-        children: &[UiChild]
-    ) -> E {
-        for child in children {
-            match child {
-                UiChild::Component(component) => {
-                    element = element.child(Self::render_component_standalone(component, &component.props));
-                }
-                UiChild::Text(text) => {
-                    element = element.child(text.clone());
-                }
-                UiChild::Reference { .. } => {
-                    // References should have been resolved by the parser
-                }
-            }
-        }
-        element
+    #[gpui::test]
+    fn test_widgets_follow_data(cx: &mut TestAppContext) {
+        cx.update(crate::init);
+        let (canvas, cx) = cx.add_window_view(|_, _| {
+            let mut canvas = JsonCanvas::new("main.json").with_data(serde_json::json!({
+                "people": [["Ann", 32], ["Bob", 7]],
+                "colors": ["red", "green"],
+                "color": "green",
+                "limit": 10
+            }));
+            canvas
+                .load_from_string(
+                    r#"{
+                        "type": "column",
+                        "children": [
+                            { "type": "table", "props": { "id": "people", "columns": ["Name", "Age"], "rows": "{{people}}" } },
+                            { "type": "dropdown", "props": { "id": "color", "options": "{{colors}}", "bind": "color" } },
+                            { "type": "slider", "props": { "id": "volume", "max": "{{limit}}", "value": 8 } },
+                            { "type": "input", "for": "person in people", "props": { "value": "{{person[0]}}" } }
+                        ]
+                    }"#,
+                )
+                .unwrap();
+            canvas
+        });
+
+        let rows = |cx: &mut VisualTestContext| {
+            canvas.read_with(cx, |canvas, cx| {
+                let table = &canvas.widget_states.tables["people"].state;
+                table.read(cx).delegate().rows.clone()
+            })
+        };
+        let color = |cx: &mut VisualTestContext| {
+            canvas.read_with(cx, |canvas, cx| {
+                let dropdown = &canvas.widget_states.dropdowns["color"].state;
+                dropdown.read(cx).selected_value().cloned()
+            })
+        };
+        let slider = |cx: &mut VisualTestContext| {
+            canvas.read_with(cx, |canvas, cx| {
+                let slider = &canvas.widget_states.sliders["volume"];
+                (slider.props[1].clone(), slider.state.read(cx).value().start())
+            })
+        };
+
+        assert_eq!(rows(cx).len(), 2);
+        assert_eq!(color(cx), Some("green".into()));
+        assert_eq!(slider(cx), (Some(UiValue::Number(10.)), 8.));
+        assert_eq!(canvas.read_with(cx, |canvas, _| canvas.widget_states.inputs.len()), 2);
+
+        canvas.update(cx, |canvas, cx| {
+            canvas.set_data(
+                serde_json::json!({
+                    "people": [["Cid", 51]],
+                    "colors": ["blue"],
+                    "color": "blue",
+                    "limit": 20
+                }),
+                cx,
+            )
+        });
+        // Render once more, for the state of the removed `for` item to be dropped.
+        cx.update(|window, _| window.refresh());
+
+        assert_eq!(rows(cx), [vec![SharedString::from("Cid"), SharedString::from("51")]]);
+        // "blue" is only selectable once the options are updated.
+        assert_eq!(color(cx), Some("blue".into()));
+        assert_eq!(slider(cx), (Some(UiValue::Number(20.)), 8.));
+        assert_eq!(canvas.read_with(cx, |canvas, _| canvas.widget_states.inputs.len()), 1);
     }
-}
\ No newline at end of file
+}
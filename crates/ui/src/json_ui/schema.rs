@@ -1,7 +1,12 @@
+use crate::json_ui::style::*;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum UiValue {
     String(String),
@@ -11,7 +16,7 @@ pub enum UiValue {
     Object(HashMap<String, UiValue>),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UiComponent {
     #[serde(rename = "type")]
     pub component_type: String,
@@ -24,9 +29,28 @@ pub struct UiComponent {
 
     #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
     pub reference: Option<String>,
+
+    /// Only render this component if the expression is truthy, e.g. `"user.admin"`.
+    #[serde(rename = "if", default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+
+    /// Render this component once per item, e.g. `"todo in todos"` or `"todo, ix in todos"`.
+    #[serde(rename = "for", default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<String>,
+
+    /// The named slot this child fills, when passed as a child of a `$ref` component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<String>,
+
+    /// The `$ref` use site this component was resolved from, as written.
+    ///
+    /// Set by [`crate::json_ui::UiParser`], so that [`crate::json_ui::UiSerializer`]
+    /// can write the reference back instead of the referenced file's content.
+    #[serde(skip)]
+    pub origin: Option<Box<UiChild>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum UiChild {
     Component(UiComponent),
@@ -36,18 +60,122 @@ pub enum UiChild {
         reference: String,
         #[serde(default)]
         props: HashMap<String, UiValue>,
+        #[serde(rename = "if", default, skip_serializing_if = "Option::is_none")]
+        condition: Option<String>,
+        #[serde(rename = "for", default, skip_serializing_if = "Option::is_none")]
+        repeat: Option<String>,
+        /// Passed to the slots of the referenced component.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        children: Vec<UiChild>,
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UiDocument {
     #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
     pub schema: Option<String>,
 
+    /// Declares the props and slots of a reusable component file.
+    #[serde(rename = "component", default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<UiComponentDefinition>,
+
+    /// Named style presets, applied to components of this file by their `class` prop.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub styles: HashMap<String, HashMap<String, UiValue>>,
+
     #[serde(flatten)]
     pub root: UiComponent,
 }
 
+/// The `component` block of a reusable component file.
+///
+/// ```json
+/// "component": {
+///     "props": {
+///         "title": { "type": "string", "required": true },
+///         "tone": { "type": "color", "default": "primary" }
+///     },
+///     "slots": ["default", "footer"]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UiComponentDefinition {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub props: HashMap<String, UiPropDefinition>,
+
+    /// The slot names the component places with `{ "type": "slot", "props": { "name": .. } }`.
+    ///
+    /// Children passed without a `slot` fill the `default` slot.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slots: Vec<String>,
+}
+
+/// A declared prop of a reusable component.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UiPropDefinition {
+    #[serde(rename = "type", default)]
+    pub prop_type: UiPropType,
+
+    /// Used when the prop is not passed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<UiValue>,
+
+    /// Report an error when the prop is not passed and has no default.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UiPropType {
+    #[default]
+    Any,
+    String,
+    Number,
+    Boolean,
+    Color,
+    Array,
+    Object,
+    Action,
+}
+
+impl UiPropType {
+    pub const NAMES: &'static [&'static str] = &[
+        "any", "string", "number", "boolean", "color", "array", "object", "action",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "any" => Self::Any,
+            "string" => Self::String,
+            "number" => Self::Number,
+            "boolean" => Self::Boolean,
+            "color" => Self::Color,
+            "array" => Self::Array,
+            "object" => Self::Object,
+            "action" => Self::Action,
+            _ => return None,
+        })
+    }
+
+    /// The [`PropKind`] values of this type are checked against.
+    pub fn kind(&self) -> PropKind {
+        match self {
+            Self::Any => PropKind::Any,
+            Self::String => PropKind::String,
+            Self::Number => PropKind::Number,
+            Self::Boolean => PropKind::Boolean,
+            Self::Color => PropKind::Color,
+            Self::Array => PropKind::Array,
+            Self::Object => PropKind::Object,
+            Self::Action => PropKind::Action,
+        }
+    }
+}
+
 impl UiValue {
     pub fn as_string(&self) -> Option<&str> {
         match self {
@@ -83,4 +211,644 @@ impl UiValue {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+/// The kind of value a prop accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropKind {
+    String,
+    Number,
+    Boolean,
+    /// A theme token, palette color or hex color, see [`crate::json_ui::UiRenderer::resolve_color`].
+    Color,
+    /// One of the given strings.
+    Enum(&'static [&'static str]),
+    /// An array of values.
+    Array,
+    /// An object.
+    Object,
+    /// Any value.
+    Any,
+    /// A data model path, e.g. `"form.name"`.
+    Path,
+    /// An action name, or `{ "action": .., "args": .. }`.
+    Action,
+    /// A length, see [`UiLength`].
+    Length,
+    /// One to four lengths for the edges or corners of a box, see [`UiLength::parse_edges`].
+    Edges,
+    /// A named shadow, or custom shadows, see [`UiShadow`].
+    Shadow,
+    /// A named font size, or a length in `px` or `rem`.
+    FontSize,
+    /// A named font weight, or a weight from 1 to 1000.
+    FontWeight,
+    /// Props by window width breakpoint, see [`responsive_props`].
+    Breakpoints,
+}
+
+impl PropKind {
+    /// Describe the kind for diagnostics, e.g. "a number".
+    pub fn describe(&self) -> String {
+        match self {
+            PropKind::String => "a string".into(),
+            PropKind::Number => "a number".into(),
+            PropKind::Boolean => "a boolean".into(),
+            PropKind::Color => "a color string".into(),
+            PropKind::Enum(values) => format!("one of {}", values.join(", ")),
+            PropKind::Array => "an array".into(),
+            PropKind::Object => "an object".into(),
+            PropKind::Any => "any value".into(),
+            PropKind::Path => "a data path string".into(),
+            PropKind::Action => "an action name or action object".into(),
+            PropKind::Length => "a length, e.g. 12, \"1.5rem\" or \"50%\"".into(),
+            PropKind::Edges => "a length or an array of 2 or 4 lengths".into(),
+            PropKind::Shadow => format!("one of {}, or a shadow object", SHADOWS.join(", ")),
+            PropKind::FontSize => format!("one of {}, or a length", FONT_SIZES.join(", ")),
+            PropKind::FontWeight => "a font weight name or a number from 1 to 1000".into(),
+            PropKind::Breakpoints => "an object of props by breakpoint".into(),
+        }
+    }
+
+    /// Returns true if `value` is acceptable for this kind.
+    ///
+    /// Strings with `{{..}}` bindings or `${..}` parameters are accepted for
+    /// every kind, since their type is only known once they are resolved.
+    pub fn accepts(&self, value: &Value) -> bool {
+        if let Value::String(s) = value {
+            if s.contains("{{") || s.contains("${") {
+                return true;
+            }
+        }
+
+        match self {
+            PropKind::String | PropKind::Color | PropKind::Path => value.is_string(),
+            PropKind::Number => value.is_number(),
+            PropKind::Boolean => value.is_boolean(),
+            PropKind::Enum(values) => value.as_str().is_some_and(|s| values.contains(&s)),
+            PropKind::Array => value.is_array(),
+            PropKind::Object => value.is_object(),
+            PropKind::Any => true,
+            PropKind::Action => match value {
+                Value::String(_) => true,
+                Value::Object(obj) => obj.get("action").is_some_and(Value::is_string),
+                _ => false,
+            },
+            PropKind::Length => Self::parse(value, UiLength::parse).is_some(),
+            PropKind::Edges => Self::parse(value, UiLength::parse_edges).is_some(),
+            PropKind::Shadow => Self::parse(value, UiShadow::parse).is_some(),
+            PropKind::FontSize => {
+                value.as_str().is_some_and(|s| FONT_SIZES.contains(&s))
+                    || matches!(
+                        Self::parse(value, UiLength::parse),
+                        Some(UiLength::Px(_) | UiLength::Rems(_))
+                    )
+            }
+            PropKind::FontWeight => Self::parse(value, parse_font_weight).is_some(),
+            PropKind::Breakpoints => value.as_object().is_some_and(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .all(|(name, props)| breakpoint_width(name).is_some() && props.is_object())
+            }),
+        }
+    }
+
+    /// Parse a JSON value with a style parser from [`crate::json_ui::style`].
+    fn parse<T>(value: &Value, parse: impl Fn(&UiValue) -> Option<T>) -> Option<T> {
+        serde_json::from_value(value.clone()).ok().and_then(|value| parse(&value))
+    }
+
+    fn json_schema(&self) -> Value {
+        let schema = match self {
+            PropKind::String | PropKind::Color | PropKind::Path => return json!({ "type": "string" }),
+            PropKind::Number => json!({ "type": "number" }),
+            PropKind::Boolean => json!({ "type": "boolean" }),
+            PropKind::Enum(values) => json!({ "enum": values }),
+            PropKind::Array => json!({ "type": "array" }),
+            PropKind::Object => json!({ "type": "object" }),
+            PropKind::Length => length_schema(),
+            PropKind::Edges => json!({
+                "anyOf": [
+                    length_schema(),
+                    { "type": "array", "items": length_schema(), "minItems": 1, "maxItems": 4 }
+                ]
+            }),
+            PropKind::Shadow => {
+                let shadow = json!({
+                    "type": "object",
+                    "properties": {
+                        "x": { "type": "number" },
+                        "y": { "type": "number" },
+                        "blur": { "type": "number" },
+                        "spread": { "type": "number" },
+                        "color": { "type": "string" }
+                    },
+                    "additionalProperties": false
+                });
+                json!({ "anyOf": [{ "enum": SHADOWS }, shadow, { "type": "array", "items": shadow }] })
+            }
+            PropKind::FontSize => json!({ "anyOf": [{ "enum": FONT_SIZES }, length_schema()] }),
+            PropKind::FontWeight => {
+                let names: Vec<_> = FONT_WEIGHTS.iter().map(|(name, _)| *name).collect();
+                json!({ "anyOf": [{ "enum": names }, { "type": "number", "minimum": 1, "maximum": 1000 }] })
+            }
+            PropKind::Breakpoints => {
+                let names: Vec<_> = BREAKPOINTS.iter().map(|(name, _)| *name).collect();
+                json!({
+                    "type": "object",
+                    "propertyNames": { "anyOf": [{ "enum": names }, { "pattern": "^[0-9.]+(px)?$" }] },
+                    "additionalProperties": { "type": "object" }
+                })
+            }
+            PropKind::Any => return json!({}),
+            PropKind::Action => {
+                return json!({
+                    "anyOf": [
+                        { "type": "string" },
+                        {
+                            "type": "object",
+                            "properties": { "action": { "type": "string" }, "args": {} },
+                            "required": ["action"]
+                        }
+                    ]
+                })
+            }
+        };
+
+        // Allow a binding in place of the typed value.
+        json!({ "anyOf": [schema, { "type": "string", "pattern": "\\{\\{.*\\}\\}|\\$\\{.*\\}" }] })
+    }
+}
+
+fn length_schema() -> Value {
+    json!({
+        "anyOf": [
+            { "type": "number" },
+            { "type": "string", "pattern": "^\\s*(auto|full|-?[0-9.]+(px|rem|%)?)\\s*$" }
+        ]
+    })
+}
+
+/// A prop accepted by a component.
+#[derive(Debug, Clone, Copy)]
+pub struct PropSpec {
+    pub name: &'static str,
+    pub kind: PropKind,
+    pub description: &'static str,
+}
+
+/// A component type known to the renderer, and the props it reads.
+#[derive(Debug, Clone, Copy)]
+pub struct ComponentSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub props: &'static [PropSpec],
+}
+
+const fn prop(name: &'static str, kind: PropKind, description: &'static str) -> PropSpec {
+    PropSpec {
+        name,
+        kind,
+        description,
+    }
+}
+
+const SIZES: &[&str] = &["xsmall", "small", "medium", "large"];
+const BUTTON_VARIANTS: &[&str] = &[
+    "primary", "secondary", "danger", "info", "success", "warning", "ghost", "link", "text",
+];
+const ORIENTATIONS: &[&str] = &["horizontal", "vertical"];
+
+const ALIGNMENTS: &[&str] = &["start", "center", "end", "stretch", "baseline"];
+const JUSTIFICATIONS: &[&str] = &["start", "center", "end", "between", "around"];
+const OVERFLOWS: &[&str] = &["visible", "hidden", "scroll"];
+
+/// Props read from every component.
+///
+/// Lengths are pixels when given as numbers, see [`UiLength`] for the units.
+pub const COMMON_PROPS: &[PropSpec] = &[
+    prop("id", PropKind::String, "Identifies the component's state and is the `source` of its actions."),
+    prop("width", PropKind::Length, "Width."),
+    prop("height", PropKind::Length, "Height."),
+    prop("min_width", PropKind::Length, "Minimum width."),
+    prop("max_width", PropKind::Length, "Maximum width."),
+    prop("min_height", PropKind::Length, "Minimum height."),
+    prop("max_height", PropKind::Length, "Maximum height."),
+    prop("padding", PropKind::Edges, "Padding of every edge, or `[vertical, horizontal]`, or `[top, right, bottom, left]`."),
+    prop("padding_x", PropKind::Length, "Left and right padding."),
+    prop("padding_y", PropKind::Length, "Top and bottom padding."),
+    prop("padding_top", PropKind::Length, "Top padding."),
+    prop("padding_right", PropKind::Length, "Right padding."),
+    prop("padding_bottom", PropKind::Length, "Bottom padding."),
+    prop("padding_left", PropKind::Length, "Left padding."),
+    prop("margin", PropKind::Edges, "Margin of every edge, or `[vertical, horizontal]`, or `[top, right, bottom, left]`."),
+    prop("margin_x", PropKind::Length, "Left and right margin."),
+    prop("margin_y", PropKind::Length, "Top and bottom margin."),
+    prop("margin_top", PropKind::Length, "Top margin."),
+    prop("margin_right", PropKind::Length, "Right margin."),
+    prop("margin_bottom", PropKind::Length, "Bottom margin."),
+    prop("margin_left", PropKind::Length, "Left margin."),
+    prop("gap", PropKind::Length, "Space between children."),
+    prop("gap_x", PropKind::Length, "Space between columns."),
+    prop("gap_y", PropKind::Length, "Space between rows."),
+    prop("align", PropKind::Enum(ALIGNMENTS), "Cross axis alignment of the children."),
+    prop("justify", PropKind::Enum(JUSTIFICATIONS), "Main axis alignment of the children."),
+    prop("wrap", PropKind::Boolean, "Wrap children onto multiple lines."),
+    prop("grow", PropKind::Number, "Flex grow factor."),
+    prop("shrink", PropKind::Number, "Flex shrink factor."),
+    prop("bg", PropKind::Color, "Background color."),
+    prop("backgroundColor", PropKind::Color, "Background color, same as `bg`."),
+    prop("color", PropKind::Color, "Text color."),
+    prop("borderColor", PropKind::Color, "Border color, draws a 1px border unless `border_width` is set."),
+    prop("border_width", PropKind::Edges, "Border width of every edge, or per edge."),
+    prop("border_radius", PropKind::Edges, "Corner radius, `\"full\"` for a pill, or `[top_left, top_right, bottom_right, bottom_left]`."),
+    prop("shadow", PropKind::Shadow, "Box shadow."),
+    prop("opacity", PropKind::Number, "Opacity from 0 to 1."),
+    prop("overflow", PropKind::Enum(OVERFLOWS), "Clip or scroll the content on both axes."),
+    prop("overflow_x", PropKind::Enum(OVERFLOWS), "Clip or scroll the content horizontally."),
+    prop("overflow_y", PropKind::Enum(OVERFLOWS), "Clip or scroll the content vertically."),
+    prop("position", PropKind::Enum(&["relative", "absolute"]), "Positioning, `absolute` is placed by `top`, `right`, `bottom` and `left` within the nearest positioned parent."),
+    prop("top", PropKind::Length, "Top offset."),
+    prop("right", PropKind::Length, "Right offset."),
+    prop("bottom", PropKind::Length, "Bottom offset."),
+    prop("left", PropKind::Length, "Left offset."),
+    prop("font_size", PropKind::FontSize, "Font size."),
+    prop("font_weight", PropKind::FontWeight, "Font weight."),
+    prop("line_height", PropKind::Length, "Line height."),
+    prop("text_align", PropKind::Enum(&["left", "center", "right"]), "Text alignment."),
+    prop("truncate", PropKind::Boolean, "Cut overflowing text with an ellipsis."),
+    prop("responsive", PropKind::Breakpoints, "Props that override the others from a window width, by breakpoint (`sm`, `md`, `lg`, `xl`, `2xl` or a width like `\"900px\"`)."),
+    prop("on_click", PropKind::Action, "Action dispatched when the component is clicked."),
+    prop("title", PropKind::String, "Title of a `tabs` panel or `accordion` item."),
+    prop("open", PropKind::Boolean, "Whether an `accordion` item starts open."),
+    prop("class", PropKind::String, "Space separated names of style presets from the file's `styles`."),
+];
+
+const BIND: PropSpec = prop("bind", PropKind::Path, "Data model path to read the value from and write changes to.");
+const ON_CHANGE: PropSpec = prop("on_change", PropKind::Action, "Action dispatched with the new value.");
+const DISABLED: PropSpec = prop("disabled", PropKind::Boolean, "Disable the widget.");
+const LABEL: PropSpec = prop("label", PropKind::String, "Label text.");
+const SIZE: PropSpec = prop("size", PropKind::Enum(SIZES), "Widget size.");
+const OPTIONS: PropSpec = prop("options", PropKind::Array, "The options to choose from.");
+const SELECTED: PropSpec = prop("selected", PropKind::Number, "Index of the initially selected item.");
+const CONTENT: PropSpec = prop("content", PropKind::String, "The text, used instead of the children.");
+const CHECKED: PropSpec = prop("checked", PropKind::Boolean, "Initial checked state when not bound.");
+
+/// Every component type the renderer knows about.
+pub const COMPONENTS: &[ComponentSpec] = &[
+    ComponentSpec { name: "div", description: "A plain container.", props: &[] },
+    ComponentSpec {
+        name: "slot",
+        description: "In a component file, where the children passed at the use site go. Its own children are the fallback content.",
+        props: &[prop("name", PropKind::String, "The slot name, `default` if not set.")],
+    },
+    ComponentSpec { name: "h1", description: "A large heading.", props: &[CONTENT] },
+    ComponentSpec { name: "h2", description: "A medium heading.", props: &[CONTENT] },
+    ComponentSpec { name: "h3", description: "A small heading.", props: &[CONTENT] },
+    ComponentSpec {
+        name: "text",
+        description: "A text label, from `content` or its text children.",
+        props: &[CONTENT],
+    },
+    ComponentSpec {
+        name: "flex",
+        description: "A flex container.",
+        props: &[prop("direction", PropKind::Enum(&["row", "column"]), "Main axis direction.")],
+    },
+    ComponentSpec { name: "column", description: "A vertical flex container.", props: &[] },
+    ComponentSpec { name: "row", description: "A horizontal flex container.", props: &[] },
+    ComponentSpec {
+        name: "input",
+        description: "A single line text input.",
+        props: &[
+            prop("placeholder", PropKind::String, "Placeholder text."),
+            prop("value", PropKind::String, "Initial value when not bound."),
+            SIZE,
+            BIND,
+            ON_CHANGE,
+        ],
+    },
+    ComponentSpec {
+        name: "button",
+        description: "A button, labelled by `label` or its text children.",
+        props: &[
+            LABEL,
+            prop("variant", PropKind::Enum(BUTTON_VARIANTS), "Button style."),
+            prop("outline", PropKind::Boolean, "Use the outline style."),
+            SIZE,
+            prop("icon", PropKind::String, "Icon asset name, e.g. `arrow-left`."),
+            prop("tooltip", PropKind::String, "Tooltip text."),
+            DISABLED,
+            prop("loading", PropKind::Boolean, "Show a loading indicator."),
+        ],
+    },
+    ComponentSpec {
+        name: "switch",
+        description: "An on/off switch.",
+        props: &[LABEL, CHECKED, DISABLED, SIZE, BIND, ON_CHANGE],
+    },
+    ComponentSpec {
+        name: "checkbox",
+        description: "A checkbox.",
+        props: &[LABEL, CHECKED, DISABLED, SIZE, BIND, ON_CHANGE],
+    },
+    ComponentSpec {
+        name: "radio",
+        description: "A single radio button.",
+        props: &[LABEL, CHECKED, DISABLED, BIND, ON_CHANGE],
+    },
+    ComponentSpec {
+        name: "radio_group",
+        description: "A group of radio buttons, one per option.",
+        props: &[
+            OPTIONS,
+            SELECTED,
+            prop("direction", PropKind::Enum(ORIENTATIONS), "Layout direction."),
+            DISABLED,
+            BIND,
+            ON_CHANGE,
+        ],
+    },
+    ComponentSpec {
+        name: "slider",
+        description: "A numeric slider.",
+        props: &[
+            prop("min", PropKind::Number, "Minimum value, 0 by default."),
+            prop("max", PropKind::Number, "Maximum value, 100 by default."),
+            prop("step", PropKind::Number, "Step size, 1 by default."),
+            prop("value", PropKind::Number, "Initial value when not bound."),
+            prop("direction", PropKind::Enum(ORIENTATIONS), "Slider orientation."),
+            DISABLED,
+            BIND,
+            ON_CHANGE,
+        ],
+    },
+    ComponentSpec {
+        name: "dropdown",
+        description: "A dropdown to pick one of the options.",
+        props: &[
+            OPTIONS,
+            SELECTED,
+            prop("placeholder", PropKind::String, "Placeholder text."),
+            prop("cleanable", PropKind::Boolean, "Show a button to clear the selection."),
+            DISABLED,
+            SIZE,
+            BIND,
+            ON_CHANGE,
+        ],
+    },
+    ComponentSpec {
+        name: "tabs",
+        description: "Tabs, one per child panel, titled by the panel's `title` prop.",
+        props: &[
+            SELECTED,
+            prop("variant", PropKind::Enum(&["pill", "segmented", "underline"]), "Tab bar style."),
+            BIND,
+            ON_CHANGE,
+        ],
+    },
+    ComponentSpec {
+        name: "table",
+        description: "A sortable table.",
+        props: &[
+            prop("columns", PropKind::Array, "Column keys, or `{ key, name, width, sortable }` objects."),
+            prop("rows", PropKind::Array, "Rows, as arrays or objects keyed by column."),
+            prop("stripe", PropKind::Boolean, "Stripe alternate rows."),
+        ],
+    },
+    ComponentSpec {
+        name: "list",
+        description: "A list of selectable items.",
+        props: &[
+            prop("items", PropKind::Array, "The item labels."),
+            BIND,
+            ON_CHANGE,
+        ],
+    },
+    ComponentSpec {
+        name: "accordion",
+        description: "Collapsible items, one per child, titled by the child's `title` prop.",
+        props: &[
+            prop("multiple", PropKind::Boolean, "Allow several items to be open."),
+            prop("bordered", PropKind::Boolean, "Draw a border around the items."),
+        ],
+    },
+    ComponentSpec {
+        name: "alert",
+        description: "An alert message.",
+        props: &[
+            prop("message", PropKind::String, "The message, or use text children."),
+            prop(
+                "variant",
+                PropKind::Enum(&["info", "success", "warning", "error", "danger"]),
+                "Alert style.",
+            ),
+            prop("title", PropKind::String, "Alert title."),
+            prop("banner", PropKind::Boolean, "Use the banner style."),
+        ],
+    },
+    ComponentSpec {
+        name: "badge",
+        description: "A badge over its children.",
+        props: &[
+            prop("dot", PropKind::Boolean, "Show a dot instead of a count."),
+            prop("count", PropKind::Number, "The count to show."),
+            prop("max", PropKind::Number, "Show `max+` for larger counts."),
+        ],
+    },
+    ComponentSpec {
+        name: "tag",
+        description: "A small tag.",
+        props: &[
+            LABEL,
+            prop("variant", PropKind::String, "A tag style, e.g. `primary`, or a color name."),
+            prop("outline", PropKind::Boolean, "Use the outline style."),
+            prop("rounded", PropKind::Boolean, "Fully rounded corners."),
+            SIZE,
+        ],
+    },
+    ComponentSpec {
+        name: "avatar",
+        description: "A user avatar.",
+        props: &[
+            prop("src", PropKind::String, "Image URL."),
+            prop("name", PropKind::String, "Name to derive initials from."),
+            SIZE,
+        ],
+    },
+    ComponentSpec {
+        name: "progress",
+        description: "A progress bar.",
+        props: &[prop("value", PropKind::Number, "Progress percentage, 0 to 100.")],
+    },
+    ComponentSpec {
+        name: "tooltip",
+        description: "Shows a tooltip when hovering its children.",
+        props: &[prop("text", PropKind::String, "Tooltip text.")],
+    },
+    ComponentSpec {
+        name: "divider",
+        description: "A divider line.",
+        props: &[
+            prop("direction", PropKind::Enum(ORIENTATIONS), "Divider orientation."),
+            LABEL,
+        ],
+    },
+    ComponentSpec {
+        name: "icon",
+        description: "An icon.",
+        props: &[prop("name", PropKind::String, "Icon asset name, e.g. `arrow-left`."), SIZE],
+    },
+    ComponentSpec {
+        name: "modal_trigger",
+        description: "A button that opens its children in a modal.",
+        props: &[
+            LABEL,
+            prop("title", PropKind::String, "Modal title."),
+            prop("variant", PropKind::Enum(BUTTON_VARIANTS), "Button style."),
+        ],
+    },
+    ComponentSpec {
+        name: "drawer_trigger",
+        description: "A button that opens its children in a drawer.",
+        props: &[
+            LABEL,
+            prop("title", PropKind::String, "Drawer title."),
+            prop("variant", PropKind::Enum(BUTTON_VARIANTS), "Button style."),
+            prop("placement", PropKind::Enum(&["left", "right", "top", "bottom"]), "Drawer placement."),
+        ],
+    },
+];
+
+impl ComponentSpec {
+    /// Find the spec for a component type.
+    pub fn get(name: &str) -> Option<&'static ComponentSpec> {
+        COMPONENTS.iter().find(|spec| spec.name == name)
+    }
+
+    /// Find a prop of this component, falling back to the [`COMMON_PROPS`].
+    pub fn prop(&self, name: &str) -> Option<&'static PropSpec> {
+        self.props
+            .iter()
+            .chain(COMMON_PROPS)
+            .find(|prop| prop.name == name)
+    }
+
+    /// The names of every prop this component accepts.
+    pub fn prop_names(&self) -> impl Iterator<Item = &'static str> {
+        self.props.iter().chain(COMMON_PROPS).map(|prop| prop.name)
+    }
+}
+
+impl JsonSchema for UiComponent {
+    fn schema_name() -> Cow<'static, str> {
+        "UiComponent".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let value = generator.subschema_for::<UiValue>().to_value();
+        let child = generator.subschema_for::<UiChild>().to_value();
+
+        let prop_schemas = |props: &[PropSpec]| {
+            props
+                .iter()
+                .map(|prop| {
+                    let mut schema = prop.kind.json_schema();
+                    schema["description"] = prop.description.into();
+                    (prop.name.to_string(), schema)
+                })
+                .collect::<Map<_, _>>()
+        };
+
+        // One `if`/`then` per component type, describing the props it reads.
+        let by_type = COMPONENTS
+            .iter()
+            .filter(|spec| !spec.props.is_empty())
+            .map(|spec| {
+                json!({
+                    "if": { "properties": { "type": { "const": spec.name } } },
+                    "then": { "properties": { "props": { "properties": prop_schemas(spec.props) } } }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json_schema!({
+            "type": "object",
+            "properties": {
+                "type": {
+                    "description": "The component type.",
+                    "enum": COMPONENTS.iter().map(|spec| spec.name).collect::<Vec<_>>()
+                },
+                "props": {
+                    "type": "object",
+                    "properties": prop_schemas(COMMON_PROPS),
+                    "additionalProperties": value
+                },
+                "children": {
+                    "type": "array",
+                    "items": child
+                },
+                "$ref": {
+                    "description": "Path of a JSON UI file to render in place of this component.",
+                    "type": "string"
+                },
+                "if": {
+                    "description": "Only render this component if the expression is truthy.",
+                    "type": "string"
+                },
+                "for": {
+                    "description": "Render this component once per item, e.g. `todo, ix in todos`.",
+                    "type": "string"
+                },
+                "slot": {
+                    "description": "The named slot of the parent `$ref` component this child fills.",
+                    "type": "string"
+                }
+            },
+            "required": ["type"],
+            "allOf": by_type
+        })
+    }
+}
+
+impl JsonSchema for UiDocument {
+    fn schema_name() -> Cow<'static, str> {
+        "UiDocument".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let definition = generator.subschema_for::<UiComponentDefinition>().to_value();
+        let value = generator.subschema_for::<UiValue>().to_value();
+        let mut schema = UiComponent::json_schema(generator);
+        if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+            properties.insert(
+                "$schema".into(),
+                json!({ "description": "Path or URL of this JSON Schema.", "type": "string" }),
+            );
+            properties.insert("component".into(), definition);
+            properties.insert(
+                "styles".into(),
+                json!({
+                    "description": "Named style presets, applied by the `class` prop.",
+                    "type": "object",
+                    "additionalProperties": { "type": "object", "additionalProperties": value }
+                }),
+            );
+        }
+        schema
+    }
+}
+
+/// Generate the JSON Schema of a JSON UI document, covering every component type and its props.
+pub fn ui_json_schema() -> Schema {
+    schemars::generate::SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<UiDocument>()
+}
+
+/// Write the [`ui_json_schema`] to `path`, for editor autocompletion.
+///
+/// Point a document's `"$schema"` at the written file to use it.
+pub fn write_ui_json_schema(path: impl AsRef<Path>) -> std::io::Result<()> {
+    let content = serde_json::to_string_pretty(&ui_json_schema())?;
+    std::fs::write(path, content)
+}
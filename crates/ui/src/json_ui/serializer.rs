@@ -0,0 +1,655 @@
+use crate::json_ui::parser::UiParser;
+use crate::json_ui::schema::*;
+use crate::json_ui::validation::{locate, Severity, UiDiagnostic, UiError};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Writes component trees back as JSON UI documents.
+///
+/// The output is minimal, empty props, children and directives are left out, and
+/// formatted like the hand-written examples: keys in a fixed order, 2-space indents,
+/// and arrays or objects kept on one line when they fit.
+///
+/// Components resolved from a `$ref` are written as the `$ref` use site they came from,
+/// so a document parsed by [`UiParser`] serializes back to the file it was read from.
+///
+/// ```ignore
+/// let ui = UiParser::parse_file("assets/ui/main.json")?;
+/// std::fs::write("main.json", UiSerializer::new().component_to_string(&ui))?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct UiSerializer {
+    preserve_refs: bool,
+    line_width: usize,
+}
+
+impl Default for UiSerializer {
+    fn default() -> Self {
+        Self {
+            preserve_refs: true,
+            line_width: 80,
+        }
+    }
+}
+
+impl UiSerializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write resolved `$ref`s back as references, default is true.
+    ///
+    /// When false, referenced components are inlined into a single document.
+    pub fn preserve_refs(mut self, preserve_refs: bool) -> Self {
+        self.preserve_refs = preserve_refs;
+        self
+    }
+
+    /// The width arrays and objects are kept on one line within, default is 80.
+    pub fn line_width(mut self, line_width: usize) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    pub fn component_to_string(&self, component: &UiComponent) -> String {
+        self.write(&self.component_node(component))
+    }
+
+    pub fn document_to_string(&self, document: &UiDocument) -> String {
+        self.write(&self.document_node(document))
+    }
+
+    /// Write the document to `path`.
+    pub fn write_document(&self, document: &UiDocument, path: impl AsRef<Path>) -> Result<(), UiError> {
+        let path = path.as_ref();
+        fs::write(path, self.document_to_string(document)).map_err(|source| UiError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    fn write(&self, node: &Node) -> String {
+        let mut out = String::new();
+        node.write(&mut out, 0, 0, self.line_width);
+        out.push('\n');
+        out
+    }
+
+    fn document_node(&self, document: &UiDocument) -> Node {
+        let mut entries = vec![];
+        if let Some(schema) = &document.schema {
+            entries.push(("$schema".into(), Node::string(schema)));
+        }
+        if let Some(definition) = &document.definition {
+            entries.push(("component".into(), definition_node(definition)));
+        }
+        if !document.styles.is_empty() {
+            let styles = sorted(&document.styles)
+                .map(|(name, preset)| (name.clone(), props_node(preset)))
+                .collect();
+            entries.push(("styles".into(), Node::Object(styles)));
+        }
+
+        let Node::Object(root) = self.component_node(&document.root) else {
+            unreachable!("components are written as objects");
+        };
+        entries.extend(root);
+        Node::Object(entries)
+    }
+
+    fn component_node(&self, component: &UiComponent) -> Node {
+        if self.preserve_refs {
+            if let Some(origin) = &component.origin {
+                return self.child_node(origin);
+            }
+        }
+
+        let mut entries = vec![("type".into(), Node::string(&component.component_type))];
+        if let Some(reference) = &component.reference {
+            entries.push(("$ref".into(), Node::string(reference)));
+        }
+        self.push_common(
+            &mut entries,
+            component.slot.as_deref(),
+            component.condition.as_deref(),
+            component.repeat.as_deref(),
+            &component.props,
+            &component.children,
+        );
+        Node::Object(entries)
+    }
+
+    fn child_node(&self, child: &UiChild) -> Node {
+        match child {
+            UiChild::Component(component) => self.component_node(component),
+            UiChild::Text(text) => Node::string(text),
+            UiChild::Reference {
+                reference,
+                props,
+                condition,
+                repeat,
+                children,
+            } => {
+                let mut entries = vec![("$ref".into(), Node::string(reference))];
+                self.push_common(&mut entries, None, condition.as_deref(), repeat.as_deref(), props, children);
+                Node::Object(entries)
+            }
+        }
+    }
+
+    fn push_common(
+        &self,
+        entries: &mut Vec<(String, Node)>,
+        slot: Option<&str>,
+        condition: Option<&str>,
+        repeat: Option<&str>,
+        props: &HashMap<String, UiValue>,
+        children: &[UiChild],
+    ) {
+        for (key, value) in [("slot", slot), ("if", condition), ("for", repeat)] {
+            if let Some(value) = value {
+                entries.push((key.into(), Node::string(value)));
+            }
+        }
+        if !props.is_empty() {
+            entries.push(("props".into(), props_node(props)));
+        }
+        if !children.is_empty() {
+            let children = children.iter().map(|child| self.child_node(child)).collect();
+            entries.push(("children".into(), Node::Array(children)));
+        }
+    }
+}
+
+/// Replace the component at `pointer` in the document at `document_path` with a `$ref`
+/// to a new file at `component_path`, and write both files.
+///
+/// `base_path` is the directory `$ref`s are resolved against, the root document's
+/// directory, and relative paths are taken relative to it. `pointer` addresses the
+/// component like diagnostics do, e.g. `/children/1/children/0`.
+///
+/// The `if`, `for` and `slot` keys of the component move to the use site. Style presets
+/// the subtree uses are copied into the new file, and `${name}` props are passed through.
+/// Returns the written `$ref`.
+pub fn extract_component(
+    base_path: impl AsRef<Path>,
+    document_path: impl AsRef<Path>,
+    pointer: &str,
+    component_path: impl AsRef<Path>,
+) -> Result<String, UiError> {
+    let base_path = base_path.as_ref();
+    let document_path = base_path.join(document_path);
+    let component_path = base_path.join(component_path);
+    let reference = relative_reference(base_path, &component_path);
+
+    if component_path.exists() {
+        return Err(UiError::Io {
+            path: component_path,
+            source: std::io::Error::new(std::io::ErrorKind::AlreadyExists, "file already exists"),
+        });
+    }
+
+    let source = fs::read_to_string(&document_path).map_err(|source| UiError::Io {
+        path: document_path.clone(),
+        source,
+    })?;
+    let (mut document, _) = UiParser::parse_source(&source, Some(&document_path))?;
+    let invalid = |message: &str| {
+        let (line, column) = locate(&source, pointer);
+        UiError::Invalid(vec![UiDiagnostic {
+            severity: Severity::Error,
+            path: Some(document_path.clone()),
+            pointer: pointer.to_string(),
+            line,
+            column,
+            message: message.to_string(),
+            suggestion: None,
+        }])
+    };
+
+    let Some(child) = child_at(&mut document.root, pointer) else {
+        return Err(invalid("expected the pointer to a child component, e.g. `/children/0`"));
+    };
+    let UiChild::Component(component) = &mut *child else {
+        return Err(invalid("only components can be extracted"));
+    };
+    if component.reference.is_some() {
+        return Err(invalid("the component is already a `$ref`"));
+    }
+    if contains_slot(component) {
+        return Err(invalid("components containing slots can't be extracted"));
+    }
+
+    let mut extracted = component.clone();
+    let mut classes = BTreeSet::new();
+    let mut names = BTreeSet::new();
+    collect_component(&extracted, &mut classes, &mut names);
+
+    // `${name}` props refer to the props of this file, pass them on to the new one.
+    let props: HashMap<String, UiValue> = names
+        .iter()
+        .map(|name| (name.clone(), UiValue::String(format!("${{{}}}", name))))
+        .collect();
+    let definition = document.definition.as_ref().map(|definition| UiComponentDefinition {
+        props: names
+            .iter()
+            .map(|name| (name.clone(), definition.props.get(name).cloned().unwrap_or_default()))
+            .collect(),
+        slots: vec![],
+    });
+    let styles = document
+        .styles
+        .iter()
+        .filter(|(name, _)| classes.contains(*name))
+        .map(|(name, preset)| (name.clone(), preset.clone()))
+        .collect();
+
+    let condition = extracted.condition.take();
+    let repeat = extracted.repeat.take();
+    *child = match extracted.slot.take() {
+        // Only the component form of a `$ref` can fill a slot.
+        Some(slot) => UiChild::Component(UiComponent {
+            component_type: "div".into(),
+            props,
+            children: vec![],
+            reference: Some(reference.clone()),
+            condition,
+            repeat,
+            slot: Some(slot),
+            origin: None,
+        }),
+        None => UiChild::Reference {
+            reference: reference.clone(),
+            props,
+            condition,
+            repeat,
+            children: vec![],
+        },
+    };
+
+    let new_document = UiDocument {
+        schema: None,
+        definition: definition.filter(|definition| !definition.props.is_empty()),
+        styles,
+        root: extracted,
+    };
+
+    let serializer = UiSerializer::new();
+    if let Some(parent) = component_path.parent() {
+        fs::create_dir_all(parent).map_err(|source| UiError::Io {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
+    serializer.write_document(&new_document, &component_path)?;
+    serializer.write_document(&document, &document_path)?;
+    Ok(reference)
+}
+
+/// The child at a pointer made of `/children/<index>` steps.
+fn child_at<'a>(root: &'a mut UiComponent, pointer: &str) -> Option<&'a mut UiChild> {
+    let mut segments = pointer.strip_prefix('/')?.split('/');
+    let mut component = root;
+    loop {
+        if segments.next()? != "children" {
+            return None;
+        }
+        let ix: usize = segments.next()?.parse().ok()?;
+        let child = component.children.get_mut(ix)?;
+        if segments.clone().next().is_none() {
+            return Some(child);
+        }
+        component = match child {
+            UiChild::Component(component) => component,
+            _ => return None,
+        };
+    }
+}
+
+fn contains_slot(component: &UiComponent) -> bool {
+    (component.component_type == "slot" && component.reference.is_none())
+        || component.children.iter().any(|child| match child {
+            UiChild::Component(component) => contains_slot(component),
+            UiChild::Reference { children, .. } => children.iter().any(|child| match child {
+                UiChild::Component(component) => contains_slot(component),
+                _ => false,
+            }),
+            UiChild::Text(_) => false,
+        })
+}
+
+/// Collect the `class` names and `${name}` props used by a subtree.
+///
+/// Children passed to a `$ref` are resolved in this file too, so they are included.
+fn collect_component(component: &UiComponent, classes: &mut BTreeSet<String>, names: &mut BTreeSet<String>) {
+    collect_props(&component.props, classes, names);
+    collect_children(&component.children, classes, names);
+}
+
+fn collect_children(children: &[UiChild], classes: &mut BTreeSet<String>, names: &mut BTreeSet<String>) {
+    for child in children {
+        match child {
+            UiChild::Component(component) => collect_component(component, classes, names),
+            UiChild::Reference { props, children, .. } => {
+                collect_props(props, classes, names);
+                collect_children(children, classes, names);
+            }
+            UiChild::Text(_) => {}
+        }
+    }
+}
+
+fn collect_props(props: &HashMap<String, UiValue>, classes: &mut BTreeSet<String>, names: &mut BTreeSet<String>) {
+    if let Some(UiValue::String(class)) = props.get("class") {
+        classes.extend(class.split_whitespace().map(str::to_string));
+    }
+    for value in props.values() {
+        collect_names(value, names);
+    }
+}
+
+fn collect_names(value: &UiValue, names: &mut BTreeSet<String>) {
+    match value {
+        UiValue::String(s) => {
+            if let Some(name) = s.strip_prefix("${").and_then(|s| s.strip_suffix('}')) {
+                names.insert(name.to_string());
+            }
+        }
+        UiValue::Array(items) => items.iter().for_each(|item| collect_names(item, names)),
+        UiValue::Object(obj) => obj.values().for_each(|value| collect_names(value, names)),
+        _ => {}
+    }
+}
+
+/// The `$ref` for `path`, relative to `base_path` and with `/` separators.
+fn relative_reference(base_path: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(base_path).unwrap_or(path);
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn definition_node(definition: &UiComponentDefinition) -> Node {
+    let mut entries = vec![];
+    if !definition.props.is_empty() {
+        let props = sorted(&definition.props)
+            .map(|(name, prop)| {
+                let mut entries = vec![];
+                if prop.prop_type != UiPropType::Any {
+                    let prop_type = serde_json::to_value(prop.prop_type).unwrap_or(Value::Null);
+                    entries.push(("type".into(), Node::Scalar(prop_type.to_string())));
+                }
+                if let Some(default) = &prop.default {
+                    entries.push(("default".into(), value_node(default)));
+                }
+                if prop.required {
+                    entries.push(("required".into(), Node::Scalar("true".into())));
+                }
+                if let Some(description) = &prop.description {
+                    entries.push(("description".into(), Node::string(description)));
+                }
+                (name.clone(), Node::Object(entries))
+            })
+            .collect();
+        entries.push(("props".into(), Node::Object(props)));
+    }
+    if !definition.slots.is_empty() {
+        let slots = definition.slots.iter().map(|slot| Node::string(slot)).collect();
+        entries.push(("slots".into(), Node::Array(slots)));
+    }
+    Node::Object(entries)
+}
+
+fn props_node(props: &HashMap<String, UiValue>) -> Node {
+    Node::Object(
+        sorted(props)
+            .map(|(key, value)| (key.clone(), value_node(value)))
+            .collect(),
+    )
+}
+
+fn value_node(value: &UiValue) -> Node {
+    match value {
+        UiValue::String(s) => Node::string(s),
+        // Whole numbers are parsed as `f64`, write them back without the `.0`.
+        UiValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => Node::Scalar((*n as i64).to_string()),
+        UiValue::Number(n) => Node::Scalar(Value::from(*n).to_string()),
+        UiValue::Boolean(b) => Node::Scalar(b.to_string()),
+        UiValue::Array(items) => Node::Array(items.iter().map(value_node).collect()),
+        UiValue::Object(obj) => props_node(obj),
+    }
+}
+
+/// Map entries sorted by key, so the output doesn't depend on hash order.
+fn sorted<V>(map: &HashMap<String, V>) -> impl Iterator<Item = (&String, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries.into_iter()
+}
+
+/// A JSON value with its object keys in output order.
+enum Node {
+    /// An encoded string, number or boolean.
+    Scalar(String),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn string(s: &str) -> Self {
+        Node::Scalar(Value::from(s).to_string())
+    }
+
+    fn compact(&self) -> String {
+        match self {
+            Node::Scalar(s) => s.clone(),
+            Node::Array(items) if items.is_empty() => "[]".into(),
+            Node::Object(entries) if entries.is_empty() => "{}".into(),
+            Node::Array(items) => {
+                let items: Vec<_> = items.iter().map(Node::compact).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Node::Object(entries) => {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", Value::from(key.as_str()), value.compact()))
+                    .collect();
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+    }
+
+    /// Write the node at `indent`, after `prefix` characters already on the line.
+    fn write(&self, out: &mut String, indent: usize, prefix: usize, width: usize) {
+        let compact = self.compact();
+        if matches!(self, Node::Scalar(_)) || indent + prefix + compact.len() <= width {
+            out.push_str(&compact);
+            return;
+        }
+
+        let pad = " ".repeat(indent + 2);
+        match self {
+            Node::Array(items) => {
+                out.push_str("[\n");
+                for (ix, item) in items.iter().enumerate() {
+                    out.push_str(&pad);
+                    item.write(out, indent + 2, 0, width);
+                    out.push_str(if ix + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&" ".repeat(indent));
+                out.push(']');
+            }
+            Node::Object(entries) => {
+                out.push_str("{\n");
+                for (ix, (key, value)) in entries.iter().enumerate() {
+                    let key = format!("{}: ", Value::from(key.as_str()));
+                    out.push_str(&pad);
+                    out.push_str(&key);
+                    value.write(out, indent + 2, key.len(), width);
+                    out.push_str(if ix + 1 < entries.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&" ".repeat(indent));
+                out.push('}');
+            }
+            Node::Scalar(_) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_ui::EmbeddedUiMap;
+
+    const MAIN: &str = r##"{
+  "$schema": "./schema.json",
+  "styles": { "panel": { "bg": "#f5f5f5", "padding": 12 } },
+  "type": "column",
+  "props": { "class": "panel", "id": "root" },
+  "children": [
+    { "type": "h1", "children": ["Settings"] },
+    {
+      "$ref": "card.json",
+      "if": "user.admin",
+      "props": { "scale": 1.5, "title": "Admin" },
+      "children": [
+        "Only admins see this.",
+        { "type": "button", "slot": "actions", "props": { "label": "Save" } }
+      ]
+    },
+    { "type": "text", "for": "item in items", "children": ["{{item}}"] }
+  ]
+}
+"##;
+
+    const CARD: &str = r#"{
+  "component": {
+    "props": { "title": { "type": "string", "required": true } },
+    "slots": ["default", "actions"]
+  },
+  "type": "div",
+  "children": [
+    { "type": "h2", "props": { "content": "${title}" } },
+    { "type": "slot" },
+    {
+      "type": "row",
+      "children": [{ "type": "slot", "props": { "name": "actions" } }]
+    }
+  ]
+}
+"#;
+
+    fn parser() -> UiParser {
+        UiParser::with_embedded(EmbeddedUiMap::from_entries(
+            "/ui",
+            "main.json",
+            &[("main.json", MAIN), ("card.json", CARD)],
+        ))
+    }
+
+    fn document(source: &str) -> UiDocument {
+        UiParser::parse_source(source, None).unwrap().0
+    }
+
+    #[test]
+    fn test_document_round_trip() {
+        let serializer = UiSerializer::new();
+        // The examples are written the way the serializer formats them.
+        assert_eq!(serializer.document_to_string(&document(MAIN)), MAIN);
+        assert_eq!(serializer.document_to_string(&document(CARD)), CARD);
+
+        let serialized = serializer.document_to_string(&document(MAIN));
+        assert_eq!(document(&serialized), document(MAIN));
+    }
+
+    #[test]
+    fn test_resolved_round_trip() {
+        let resolved = parser().parse_document(Path::new("main.json")).unwrap();
+
+        // References are written back as they were used.
+        let serialized = UiSerializer::new().component_to_string(&resolved);
+        assert!(serialized.contains(r#""$ref": "card.json""#));
+        let mut parser = parser();
+        assert_eq!(parser.parse_from_string(&serialized).unwrap(), resolved);
+
+        // Inlined, the output resolves to the same tree without the referenced files.
+        let inlined = UiSerializer::new().preserve_refs(false).component_to_string(&resolved);
+        assert!(!inlined.contains("$ref"));
+        let reparsed = UiParser::new(".").parse_from_string(&inlined).unwrap();
+        let strip = |component: &UiComponent| serde_json::to_value(component).unwrap();
+        assert_eq!(strip(&reparsed), strip(&resolved));
+        assert_eq!(
+            UiSerializer::new().component_to_string(&reparsed),
+            inlined,
+        );
+    }
+
+    #[test]
+    fn test_minimal_output() {
+        let component: UiComponent = serde_json::from_str(
+            r#"{ "type": "div", "props": {}, "children": [{ "type": "divider" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            UiSerializer::new().component_to_string(&component),
+            "{ \"type\": \"div\", \"children\": [{ \"type\": \"divider\" }] }\n"
+        );
+        assert_eq!(
+            UiSerializer::new().line_width(40).component_to_string(&component),
+            "{\n  \"type\": \"div\",\n  \"children\": [{ \"type\": \"divider\" }]\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_extract_component() {
+        let dir = std::env::temp_dir().join(format!("json_ui_serializer_extract_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let source = r##"{
+  "component": { "props": { "name": { "type": "string", "default": "Ada" } } },
+  "styles": { "muted": { "color": "#888" }, "unused": { "color": "red" } },
+  "type": "column",
+  "children": [
+    {
+      "type": "row",
+      "if": "visible",
+      "props": { "class": "muted" },
+      "children": [{ "type": "text", "props": { "content": "${name}" } }]
+    }
+  ]
+}"##;
+        fs::write(dir.join("main.json"), source).unwrap();
+        let before = UiParser::new(&dir).parse_document(Path::new("main.json")).unwrap();
+
+        let reference = extract_component(&dir, "main.json", "/children/0", "parts/greeting.json").unwrap();
+        assert_eq!(reference, "parts/greeting.json");
+
+        let main = document(&fs::read_to_string(dir.join("main.json")).unwrap());
+        let UiChild::Reference { reference, condition, props, .. } = &main.root.children[0] else {
+            panic!("expected a reference");
+        };
+        assert_eq!(reference, "parts/greeting.json");
+        assert_eq!(condition.as_deref(), Some("visible"));
+        assert_eq!(props["name"], UiValue::String("${name}".into()));
+
+        let part = document(&fs::read_to_string(dir.join("parts/greeting.json")).unwrap());
+        assert_eq!(part.root.component_type, "row");
+        assert!(part.styles.contains_key("muted"));
+        assert!(!part.styles.contains_key("unused"));
+        assert!(part.definition.unwrap().props.contains_key("name"));
+
+        // The extracted document renders the same tree.
+        let after = UiParser::new(&dir).parse_document(Path::new("main.json")).unwrap();
+        let strip = |component: &UiComponent| serde_json::to_value(component).unwrap();
+        assert_eq!(strip(&after), strip(&before));
+
+        assert!(extract_component(&dir, "main.json", "/children/0", "parts/greeting.json").is_err());
+        let err = extract_component(&dir, "main.json", "/children/5", "other.json").unwrap_err();
+        assert_eq!(err.diagnostics()[0].pointer, "/children/5");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,282 @@
+use crate::json_ui::schema::UiValue;
+use std::collections::HashMap;
+
+/// Window width breakpoints for `responsive` props, by name, as minimum widths in pixels.
+///
+/// A breakpoint can also be given as a width, e.g. `"900"` or `"900px"`.
+pub const BREAKPOINTS: &[(&str, f32)] = &[
+    ("sm", 640.),
+    ("md", 768.),
+    ("lg", 1024.),
+    ("xl", 1280.),
+    ("2xl", 1536.),
+];
+
+/// Named shadows, matching `shadow_xs()` .. `shadow_2xl()`.
+pub const SHADOWS: &[&str] = &["none", "xs", "sm", "md", "lg", "xl", "2xl"];
+
+/// Named font sizes, matching `text_xs()` .. `text_3xl()`.
+pub const FONT_SIZES: &[&str] = &["xs", "sm", "base", "lg", "xl", "2xl", "3xl"];
+
+/// Named font weights, matching `font_thin()` .. `font_black()`.
+pub const FONT_WEIGHTS: &[(&str, f32)] = &[
+    ("thin", 100.),
+    ("extralight", 200.),
+    ("light", 300.),
+    ("normal", 400.),
+    ("medium", 500.),
+    ("semibold", 600.),
+    ("bold", 700.),
+    ("extrabold", 800.),
+    ("black", 900.),
+];
+
+/// A length prop value.
+///
+/// Numbers are pixels, strings may be `"12px"`, `"1.5rem"`, `"50%"`, `"full"` or `"auto"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiLength {
+    Px(f32),
+    Rems(f32),
+    /// A fraction of the parent's size, `1.0` for `"100%"`.
+    Fraction(f32),
+    Auto,
+}
+
+impl UiLength {
+    pub fn parse(value: &UiValue) -> Option<Self> {
+        match value {
+            UiValue::Number(n) => Some(UiLength::Px(*n as f32)),
+            UiValue::String(s) => Self::parse_str(s),
+            _ => None,
+        }
+    }
+
+    pub fn parse_str(s: &str) -> Option<Self> {
+        let s = s.trim();
+        match s {
+            "auto" => return Some(UiLength::Auto),
+            "full" => return Some(UiLength::Fraction(1.)),
+            _ => {}
+        }
+
+        let number = |s: &str| s.trim().parse::<f32>().ok().filter(|n| n.is_finite());
+        if let Some(rems) = s.strip_suffix("rem") {
+            number(rems).map(UiLength::Rems)
+        } else if let Some(percent) = s.strip_suffix('%') {
+            number(percent).map(|n| UiLength::Fraction(n / 100.))
+        } else {
+            number(s.strip_suffix("px").unwrap_or(s)).map(UiLength::Px)
+        }
+    }
+
+    /// Parse one to four lengths, in CSS order.
+    ///
+    /// A single length applies to every edge, two are the vertical and horizontal
+    /// edges, and four are top, right, bottom and left. For corners, four are
+    /// top left, top right, bottom right and bottom left.
+    pub fn parse_edges(value: &UiValue) -> Option<[UiLength; 4]> {
+        let lengths = match value {
+            UiValue::Array(items) => items.iter().map(Self::parse).collect::<Option<Vec<_>>>()?,
+            value => vec![Self::parse(value)?],
+        };
+        match lengths.as_slice() {
+            [all] => Some([*all; 4]),
+            [vertical, horizontal] => Some([*vertical, *horizontal, *vertical, *horizontal]),
+            [top, right, bottom, left] => Some([*top, *right, *bottom, *left]),
+            _ => None,
+        }
+    }
+}
+
+/// A `shadow` prop value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiShadow {
+    /// One of [`SHADOWS`].
+    Named(String),
+    Custom(Vec<UiBoxShadow>),
+}
+
+/// A shadow given as `{ "x": 0, "y": 4, "blur": 12, "spread": 0, "color": "#0000001a" }`.
+///
+/// Every field is optional, offsets and sizes are in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiBoxShadow {
+    pub x: f32,
+    pub y: f32,
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Option<String>,
+}
+
+impl UiShadow {
+    pub fn parse(value: &UiValue) -> Option<Self> {
+        match value {
+            UiValue::String(name) if SHADOWS.contains(&name.as_str()) => Some(UiShadow::Named(name.clone())),
+            UiValue::Object(_) => Some(UiShadow::Custom(vec![UiBoxShadow::parse(value)?])),
+            UiValue::Array(items) => items
+                .iter()
+                .map(UiBoxShadow::parse)
+                .collect::<Option<Vec<_>>>()
+                .map(UiShadow::Custom),
+            _ => None,
+        }
+    }
+}
+
+impl UiBoxShadow {
+    fn parse(value: &UiValue) -> Option<Self> {
+        let obj = value.as_object()?;
+        let number = |name: &str| match obj.get(name) {
+            None => Some(0.),
+            Some(value) => value.as_number().map(|n| n as f32),
+        };
+        let color = match obj.get("color") {
+            None => None,
+            Some(color) => Some(color.as_string()?.to_string()),
+        };
+        Some(Self {
+            x: number("x")?,
+            y: number("y")?,
+            blur: number("blur")?,
+            spread: number("spread")?,
+            color,
+        })
+    }
+}
+
+/// Parse a font weight, a number from 1 to 1000 or one of [`FONT_WEIGHTS`].
+pub fn parse_font_weight(value: &UiValue) -> Option<f32> {
+    match value {
+        UiValue::Number(n) if (1. ..=1000.).contains(n) => Some(*n as f32),
+        UiValue::String(name) => FONT_WEIGHTS
+            .iter()
+            .find(|(weight, _)| weight == name)
+            .map(|(_, weight)| *weight),
+        _ => None,
+    }
+}
+
+/// The minimum window width of a breakpoint name from [`BREAKPOINTS`], or of a width like `"900px"`.
+pub fn breakpoint_width(name: &str) -> Option<f32> {
+    if let Some((_, width)) = BREAKPOINTS.iter().find(|(breakpoint, _)| *breakpoint == name) {
+        return Some(*width);
+    }
+    match UiLength::parse_str(name)? {
+        UiLength::Px(width) if width >= 0. => Some(width),
+        _ => None,
+    }
+}
+
+/// Merge the overrides of the `responsive` prop for the breakpoints `width` reaches.
+///
+/// ```json
+/// "props": {
+///     "padding": 8,
+///     "responsive": { "md": { "padding": 16 }, "xl": { "padding": 32, "gap": 24 } }
+/// }
+/// ```
+///
+/// Wider breakpoints are applied last, so they win. Returns `None` if there is no
+/// `responsive` prop.
+pub fn responsive_props(props: &HashMap<String, UiValue>, width: f32) -> Option<HashMap<String, UiValue>> {
+    let responsive = props.get("responsive")?;
+
+    let mut breakpoints = responsive
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, overrides)| Some((breakpoint_width(name)?, overrides.as_object()?)))
+        .filter(|(min_width, _)| *min_width <= width)
+        .collect::<Vec<_>>();
+    breakpoints.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut resolved = props.clone();
+    resolved.remove("responsive");
+    for (_, overrides) in breakpoints {
+        for (key, value) in overrides {
+            resolved.insert(key.clone(), value.clone());
+        }
+    }
+    Some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_length() {
+        assert_eq!(UiLength::parse(&UiValue::Number(12.)), Some(UiLength::Px(12.)));
+        assert_eq!(UiLength::parse_str("12px"), Some(UiLength::Px(12.)));
+        assert_eq!(UiLength::parse_str("1.5rem"), Some(UiLength::Rems(1.5)));
+        assert_eq!(UiLength::parse_str("50%"), Some(UiLength::Fraction(0.5)));
+        assert_eq!(UiLength::parse_str("full"), Some(UiLength::Fraction(1.)));
+        assert_eq!(UiLength::parse_str("auto"), Some(UiLength::Auto));
+        assert_eq!(UiLength::parse_str("12em"), None);
+        assert_eq!(UiLength::parse_str("wide"), None);
+
+        let edges = |json: &str| UiLength::parse_edges(&serde_json::from_str(json).unwrap());
+        assert_eq!(edges("4"), Some([UiLength::Px(4.); 4]));
+        assert_eq!(
+            edges(r#"[4, "1rem"]"#),
+            Some([UiLength::Px(4.), UiLength::Rems(1.), UiLength::Px(4.), UiLength::Rems(1.)])
+        );
+        assert_eq!(
+            edges("[1, 2, 3, 4]"),
+            Some([UiLength::Px(1.), UiLength::Px(2.), UiLength::Px(3.), UiLength::Px(4.)])
+        );
+        assert_eq!(edges("[1, 2, 3]"), None);
+    }
+
+    #[test]
+    fn test_parse_shadow_and_font_weight() {
+        let parse = |json: &str| UiShadow::parse(&serde_json::from_str(json).unwrap());
+        assert_eq!(parse(r#""lg""#), Some(UiShadow::Named("lg".into())));
+        assert_eq!(parse(r#""huge""#), None);
+        assert_eq!(
+            parse(r#"{ "y": 4, "blur": 12, "color": "black" }"#),
+            Some(UiShadow::Custom(vec![UiBoxShadow {
+                x: 0.,
+                y: 4.,
+                blur: 12.,
+                spread: 0.,
+                color: Some("black".into()),
+            }]))
+        );
+        assert_eq!(parse(r#"[{ "x": "1" }]"#), None);
+
+        assert_eq!(parse_font_weight(&UiValue::String("semibold".into())), Some(600.));
+        assert_eq!(parse_font_weight(&UiValue::Number(450.)), Some(450.));
+        assert_eq!(parse_font_weight(&UiValue::Number(0.)), None);
+    }
+
+    #[test]
+    fn test_responsive_props() {
+        let props: HashMap<String, UiValue> = serde_json::from_str(
+            r#"{
+                "padding": 8,
+                "gap": 4,
+                "responsive": {
+                    "xl": { "padding": 32 },
+                    "md": { "padding": 16, "direction": "row" },
+                    "900px": { "gap": 12 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let at = |width: f32| responsive_props(&props, width).unwrap();
+        assert_eq!(at(320.)["padding"], UiValue::Number(8.));
+        assert!(!at(320.).contains_key("responsive"));
+        assert_eq!(at(800.)["padding"], UiValue::Number(16.));
+        assert_eq!(at(800.)["direction"], UiValue::String("row".into()));
+        assert_eq!(at(800.)["gap"], UiValue::Number(4.));
+        assert_eq!(at(1000.)["gap"], UiValue::Number(12.));
+        assert_eq!(at(1400.)["padding"], UiValue::Number(32.));
+
+        assert_eq!(responsive_props(&HashMap::new(), 800.), None);
+        assert_eq!(breakpoint_width("lg"), Some(1024.));
+        assert_eq!(breakpoint_width("huge"), None);
+    }
+}
@@ -0,0 +1,890 @@
+use crate::json_ui::binding::ForDirective;
+use crate::json_ui::schema::{ComponentSpec, PropKind, UiPropType, COMPONENTS};
+use crate::json_ui::style::{breakpoint_width, BREAKPOINTS};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The keys a component object may have.
+const COMPONENT_KEYS: &[&str] = &["type", "props", "children", "$ref", "if", "for", "slot"];
+/// The keys only the root component of a file may have.
+const DOCUMENT_KEYS: &[&str] = &["$schema", "component", "styles"];
+const DEFINITION_KEYS: &[&str] = &["props", "slots"];
+const PROP_DEFINITION_KEYS: &[&str] = &["type", "default", "required", "description"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a JSON UI document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiDiagnostic {
+    pub severity: Severity,
+    /// The file the problem is in, `None` for documents parsed from a string.
+    pub path: Option<PathBuf>,
+    /// JSON pointer to the offending value, e.g. `/children/0/props/label`.
+    pub pointer: String,
+    /// 1-based line, or 0 if unknown.
+    pub line: usize,
+    /// 1-based column, or 0 if unknown.
+    pub column: usize,
+    pub message: String,
+    /// A likely intended name, for misspelled prop names and component types.
+    pub suggestion: Option<String>,
+}
+
+impl UiDiagnostic {
+    fn new(severity: Severity, pointer: String, message: String) -> Self {
+        Self {
+            severity,
+            path: None,
+            pointer,
+            line: 0,
+            column: 0,
+            message,
+            suggestion: None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for UiDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}", path.display())?,
+            None => write!(f, "<string>")?,
+        }
+        if self.line > 0 {
+            write!(f, ":{}:{}", self.line, self.column)?;
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, ": {}: {}", severity, self.message)?;
+        if !self.pointer.is_empty() {
+            write!(f, " (at {})", self.pointer)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean `{}`?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error loading a JSON UI document.
+#[derive(Debug)]
+pub enum UiError {
+    Io { path: PathBuf, source: std::io::Error },
+    /// The document is not valid JSON, or does not describe a valid component tree.
+    Invalid(Vec<UiDiagnostic>),
+    /// A `$ref` includes a file that is already being resolved, the chain ends with the repeated file.
+    CyclicReference(Vec<PathBuf>),
+}
+
+impl UiError {
+    /// The diagnostics of an [`UiError::Invalid`] error.
+    pub fn diagnostics(&self) -> &[UiDiagnostic] {
+        match self {
+            UiError::Invalid(diagnostics) => diagnostics,
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for UiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UiError::Io { path, source } => write!(f, "failed to read {}: {}", path.display(), source),
+            UiError::Invalid(diagnostics) => {
+                for (ix, diagnostic) in diagnostics.iter().enumerate() {
+                    if ix > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", diagnostic)?;
+                }
+                Ok(())
+            }
+            UiError::CyclicReference(chain) => {
+                write!(f, "cyclic `$ref`: ")?;
+                for (ix, path) in chain.iter().enumerate() {
+                    if ix > 0 {
+                        write!(f, " -> ")?;
+                    }
+                    write!(f, "{}", path.display())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for UiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UiError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Parse and validate the source of a JSON UI document.
+///
+/// Returns the parsed JSON along with any warnings, or an [`UiError::Invalid`]
+/// with every diagnostic if there was at least one error.
+pub fn validate_source(source: &str, path: Option<&Path>) -> Result<(Value, Vec<UiDiagnostic>), UiError> {
+    let value: Value = match serde_json::from_str(source) {
+        Ok(value) => value,
+        Err(err) => {
+            // The position is reported separately.
+            let message = err.to_string();
+            let message = match message.rsplit_once(" at line ") {
+                Some((message, _)) => message.to_string(),
+                None => message,
+            };
+            let mut diagnostic = UiDiagnostic::new(Severity::Error, String::new(), message);
+            diagnostic.path = path.map(Path::to_path_buf);
+            diagnostic.line = err.line();
+            diagnostic.column = err.column();
+            return Err(UiError::Invalid(vec![diagnostic]));
+        }
+    };
+
+    let positions = SourcePositions::new(source);
+    let mut validator = Validator::default();
+    validator.validate_node(&value, String::new(), true);
+
+    let mut diagnostics = validator
+        .findings
+        .into_iter()
+        .map(|finding| {
+            let mut diagnostic = finding.diagnostic;
+            let offset = if finding.at_key {
+                positions.keys.get(&diagnostic.pointer)
+            } else {
+                positions.values.get(&diagnostic.pointer)
+            };
+            if let Some(&offset) = offset {
+                (diagnostic.line, diagnostic.column) = positions.line_column(offset);
+            }
+            diagnostic.path = path.map(Path::to_path_buf);
+            diagnostic
+        })
+        .collect::<Vec<_>>();
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+    if diagnostics.iter().any(UiDiagnostic::is_error) {
+        return Err(UiError::Invalid(diagnostics));
+    }
+    Ok((value, diagnostics))
+}
+
+/// The 1-based line and column of the value at `pointer` in `source`, or `(0, 0)` if not found.
+pub(crate) fn locate(source: &str, pointer: &str) -> (usize, usize) {
+    let positions = SourcePositions::new(source);
+    positions
+        .values
+        .get(pointer)
+        .map(|offset| positions.line_column(*offset))
+        .unwrap_or((0, 0))
+}
+
+/// Validate a parsed JSON UI document, without source positions.
+pub fn validate_value(value: &Value) -> Vec<UiDiagnostic> {
+    let mut validator = Validator::default();
+    validator.validate_node(value, String::new(), true);
+    validator
+        .findings
+        .into_iter()
+        .map(|finding| finding.diagnostic)
+        .collect()
+}
+
+/// Returns the candidate closest to `name`, if it is close enough to be a likely typo.
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    let normalized = normalize(name);
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            // Differences in case or separators only, e.g. `onClick`, are always suggested.
+            let distance = if normalize(candidate) == normalized {
+                0
+            } else {
+                edit_distance(name, candidate)
+            };
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The optimal string alignment distance, a Levenshtein distance that counts
+/// swapping two adjacent characters as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Append a reference token to a JSON pointer, escaping `~` and `/`.
+fn push_pointer(pointer: &str, token: &str) -> String {
+    format!("{}/{}", pointer, token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn describe_value(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+struct Finding {
+    diagnostic: UiDiagnostic,
+    /// Locate the diagnostic at the member's key rather than its value.
+    at_key: bool,
+}
+
+#[derive(Default)]
+struct Validator {
+    findings: Vec<Finding>,
+}
+
+impl Validator {
+    fn error(&mut self, pointer: String, message: String) -> &mut UiDiagnostic {
+        self.push(Severity::Error, pointer, message, false)
+    }
+
+    fn push(&mut self, severity: Severity, pointer: String, message: String, at_key: bool) -> &mut UiDiagnostic {
+        self.findings.push(Finding {
+            diagnostic: UiDiagnostic::new(severity, pointer, message),
+            at_key,
+        });
+        &mut self.findings.last_mut().expect("just pushed").diagnostic
+    }
+
+    fn validate_node(&mut self, node: &Value, pointer: String, is_root: bool) {
+        let Some(obj) = node.as_object() else {
+            self.error(
+                pointer,
+                format!("expected a component object, found {}", describe_value(node)),
+            );
+            return;
+        };
+
+        let keys = COMPONENT_KEYS
+            .iter()
+            .chain(if is_root { DOCUMENT_KEYS } else { &[] })
+            .copied()
+            .collect::<Vec<_>>();
+        self.unknown_keys(obj, &keys, &pointer);
+
+        if is_root {
+            self.validate_document_blocks(obj, &pointer);
+        }
+
+        for key in ["$ref", "if", "slot", "$schema"] {
+            if let Some(value) = obj.get(key).filter(|value| !value.is_string()) {
+                self.error(
+                    push_pointer(&pointer, key),
+                    format!("`{}` expects a string, found {}", key, describe_value(value)),
+                );
+            }
+        }
+        match obj.get("for") {
+            Some(Value::String(repeat)) if ForDirective::parse(repeat).is_none() => {
+                self.error(
+                    push_pointer(&pointer, "for"),
+                    format!("invalid `for` directive `{}`, expected `item in items` or `item, ix in items`", repeat),
+                );
+            }
+            Some(value) if !value.is_string() => {
+                self.error(
+                    push_pointer(&pointer, "for"),
+                    format!("`for` expects a string, found {}", describe_value(value)),
+                );
+            }
+            _ => {}
+        }
+
+        // The props of a `$ref` are parameters of the referenced file, not component props.
+        let is_reference = obj.contains_key("$ref");
+        let spec = match obj.get("type") {
+            Some(Value::String(component_type)) => {
+                let spec = ComponentSpec::get(component_type);
+                if spec.is_none() && !is_reference && !component_type.contains("${") {
+                    let suggestion = suggest(component_type, COMPONENTS.iter().map(|spec| spec.name));
+                    self.error(
+                        push_pointer(&pointer, "type"),
+                        format!("unknown component type `{}`", component_type),
+                    )
+                    .suggestion = suggestion.map(str::to_string);
+                }
+                spec
+            }
+            Some(value) => {
+                self.error(
+                    push_pointer(&pointer, "type"),
+                    format!("`type` expects a string, found {}", describe_value(value)),
+                );
+                None
+            }
+            None => {
+                if is_root || !is_reference {
+                    self.error(pointer.clone(), "missing required key `type`".into());
+                }
+                None
+            }
+        };
+
+        match obj.get("props") {
+            Some(Value::Object(props)) => {
+                if let Some(spec) = spec.filter(|_| !is_reference) {
+                    self.validate_props(spec, props, &push_pointer(&pointer, "props"));
+                }
+            }
+            Some(value) => {
+                self.error(
+                    push_pointer(&pointer, "props"),
+                    format!("`props` expects an object, found {}", describe_value(value)),
+                );
+            }
+            None => {}
+        }
+
+        match obj.get("children") {
+            Some(Value::Array(children)) => {
+                let children_pointer = push_pointer(&pointer, "children");
+                for (ix, child) in children.iter().enumerate() {
+                    let child_pointer = push_pointer(&children_pointer, &ix.to_string());
+                    match child {
+                        Value::String(_) => {}
+                        Value::Object(_) => self.validate_node(child, child_pointer, false),
+                        _ => {
+                            self.error(
+                                child_pointer,
+                                format!(
+                                    "expected a component, `$ref` or text child, found {}",
+                                    describe_value(child)
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+            Some(value) => {
+                self.error(
+                    push_pointer(&pointer, "children"),
+                    format!("`children` expects an array, found {}", describe_value(value)),
+                );
+            }
+            None => {}
+        }
+    }
+
+    /// Validate the `component` and `styles` blocks of a document.
+    fn validate_document_blocks(&mut self, obj: &serde_json::Map<String, Value>, pointer: &str) {
+        match obj.get("styles") {
+            Some(Value::Object(styles)) => {
+                let styles_pointer = push_pointer(pointer, "styles");
+                for (name, preset) in styles {
+                    if !preset.is_object() {
+                        self.error(
+                            push_pointer(&styles_pointer, name),
+                            format!("style `{}` expects an object of props, found {}", name, describe_value(preset)),
+                        );
+                    }
+                }
+            }
+            Some(value) => {
+                self.error(
+                    push_pointer(pointer, "styles"),
+                    format!("`styles` expects an object, found {}", describe_value(value)),
+                );
+            }
+            None => {}
+        }
+
+        let Some(definition) = obj.get("component") else {
+            return;
+        };
+        let definition_pointer = push_pointer(pointer, "component");
+        let Some(definition) = definition.as_object() else {
+            self.error(
+                definition_pointer,
+                format!("`component` expects an object, found {}", describe_value(definition)),
+            );
+            return;
+        };
+        self.unknown_keys(definition, DEFINITION_KEYS, &definition_pointer);
+
+        match definition.get("slots") {
+            Some(Value::Array(slots)) if slots.iter().all(Value::is_string) => {}
+            Some(value) => {
+                self.error(
+                    push_pointer(&definition_pointer, "slots"),
+                    format!("`slots` expects an array of names, found {}", describe_value(value)),
+                );
+            }
+            None => {}
+        }
+
+        let props_pointer = push_pointer(&definition_pointer, "props");
+        let props = match definition.get("props") {
+            Some(Value::Object(props)) => props,
+            Some(value) => {
+                self.error(
+                    props_pointer,
+                    format!("`props` expects an object, found {}", describe_value(value)),
+                );
+                return;
+            }
+            None => return,
+        };
+        for (name, prop) in props {
+            let prop_pointer = push_pointer(&props_pointer, name);
+            let Some(prop) = prop.as_object() else {
+                self.error(
+                    prop_pointer,
+                    format!("prop `{}` expects an object, found {}", name, describe_value(prop)),
+                );
+                continue;
+            };
+            self.unknown_keys(prop, PROP_DEFINITION_KEYS, &prop_pointer);
+
+            let prop_type = match prop.get("type") {
+                None => UiPropType::Any,
+                Some(Value::String(type_name)) => match UiPropType::from_name(type_name) {
+                    Some(prop_type) => prop_type,
+                    None => {
+                        let suggestion = suggest(type_name, UiPropType::NAMES.iter().copied());
+                        self.error(
+                            push_pointer(&prop_pointer, "type"),
+                            format!("unknown prop type `{}`", type_name),
+                        )
+                        .suggestion = suggestion.map(str::to_string);
+                        continue;
+                    }
+                },
+                Some(value) => {
+                    self.error(
+                        push_pointer(&prop_pointer, "type"),
+                        format!("`type` expects a string, found {}", describe_value(value)),
+                    );
+                    continue;
+                }
+            };
+
+            if let Some(value) = prop.get("required").filter(|value| !value.is_boolean()) {
+                self.error(
+                    push_pointer(&prop_pointer, "required"),
+                    format!("`required` expects a boolean, found {}", describe_value(value)),
+                );
+            }
+            if let Some(default) = prop.get("default") {
+                if !prop_type.kind().accepts(default) {
+                    self.error(
+                        push_pointer(&prop_pointer, "default"),
+                        format!(
+                            "default of `{}` expects {}, found {}",
+                            name,
+                            prop_type.kind().describe(),
+                            describe_value(default)
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn unknown_keys(&mut self, obj: &serde_json::Map<String, Value>, keys: &[&str], pointer: &str) {
+        for key in obj.keys() {
+            if keys.contains(&key.as_str()) {
+                continue;
+            }
+            let suggestion = suggest(key, keys.iter().copied()).map(str::to_string);
+            self.push(
+                Severity::Warning,
+                push_pointer(pointer, key),
+                format!("unknown key `{}`", key),
+                true,
+            )
+            .suggestion = suggestion;
+        }
+    }
+
+    fn validate_props(&mut self, spec: &ComponentSpec, props: &serde_json::Map<String, Value>, pointer: &str) {
+        for (name, value) in props {
+            let prop_pointer = push_pointer(pointer, name);
+            let Some(prop) = spec.prop(name) else {
+                let suggestion = suggest(name, spec.prop_names()).map(str::to_string);
+                self.push(
+                    Severity::Warning,
+                    prop_pointer,
+                    format!("unknown prop `{}` for `{}`", name, spec.name),
+                    true,
+                )
+                .suggestion = suggestion;
+                continue;
+            };
+
+            if let (PropKind::Breakpoints, Value::Object(breakpoints)) = (prop.kind, value) {
+                self.validate_breakpoints(spec, breakpoints, &prop_pointer);
+                continue;
+            }
+            if prop.kind.accepts(value) {
+                continue;
+            }
+
+            let found = match value {
+                Value::String(s) => format!("`{}`", s),
+                _ => describe_value(value).to_string(),
+            };
+            let suggestion = match (prop.kind, value) {
+                (PropKind::Enum(values), Value::String(s)) => {
+                    suggest(s, values.iter().copied()).map(str::to_string)
+                }
+                _ => None,
+            };
+            self.error(
+                prop_pointer,
+                format!("`{}` expects {}, found {}", name, prop.kind.describe(), found),
+            )
+            .suggestion = suggestion;
+        }
+    }
+
+    /// Validate the props of a `responsive` block as props of the component itself.
+    fn validate_breakpoints(&mut self, spec: &ComponentSpec, breakpoints: &serde_json::Map<String, Value>, pointer: &str) {
+        for (name, props) in breakpoints {
+            let breakpoint_pointer = push_pointer(pointer, name);
+            if breakpoint_width(name).is_none() {
+                let suggestion = suggest(name, BREAKPOINTS.iter().map(|(name, _)| *name)).map(str::to_string);
+                self.push(
+                    Severity::Error,
+                    breakpoint_pointer.clone(),
+                    format!("unknown breakpoint `{}`, expected a name or a width like `900px`", name),
+                    true,
+                )
+                .suggestion = suggestion;
+            }
+
+            match props {
+                Value::Object(props) => self.validate_props(spec, props, &breakpoint_pointer),
+                _ => {
+                    self.error(
+                        breakpoint_pointer,
+                        format!("breakpoint `{}` expects an object of props, found {}", name, describe_value(props)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Byte offsets of every key and value in a JSON source, by JSON pointer.
+///
+/// Only used on sources that `serde_json` already parsed, so it does not
+/// need to report syntax errors.
+struct SourcePositions<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+    keys: HashMap<String, usize>,
+    values: HashMap<String, usize>,
+}
+
+impl<'a> SourcePositions<'a> {
+    fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(ix, _)| ix + 1))
+            .collect();
+        let mut this = Self {
+            source,
+            line_starts,
+            keys: HashMap::new(),
+            values: HashMap::new(),
+        };
+        this.scan_value(0, String::new());
+        this
+    }
+
+    /// The 1-based line and column (in characters) of a byte offset.
+    fn line_column(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.source[line_start..offset].chars().count() + 1;
+        (line, column)
+    }
+
+    fn skip_whitespace(&self, mut pos: usize) -> usize {
+        let bytes = self.source.as_bytes();
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Scan the value starting at (or after whitespace at) `pos`, returning the offset after it.
+    fn scan_value(&mut self, pos: usize, pointer: String) -> usize {
+        let bytes = self.source.as_bytes();
+        let mut pos = self.skip_whitespace(pos);
+        self.values.insert(pointer.clone(), pos);
+
+        match bytes.get(pos) {
+            Some(b'{') => {
+                pos += 1;
+                loop {
+                    pos = self.skip_whitespace(pos);
+                    match bytes.get(pos) {
+                        Some(b'"') => {
+                            let key_start = pos;
+                            let (key, end) = self.scan_string(pos);
+                            let member = push_pointer(&pointer, &key);
+                            self.keys.insert(member.clone(), key_start);
+                            // Skip the `:`.
+                            pos = self.skip_whitespace(end) + 1;
+                            pos = self.scan_value(pos, member);
+                        }
+                        Some(b',') => pos += 1,
+                        Some(b'}') => return pos + 1,
+                        _ => return pos,
+                    }
+                }
+            }
+            Some(b'[') => {
+                pos += 1;
+                let mut ix = 0;
+                loop {
+                    pos = self.skip_whitespace(pos);
+                    match bytes.get(pos) {
+                        Some(b',') => pos += 1,
+                        Some(b']') => return pos + 1,
+                        Some(_) => {
+                            pos = self.scan_value(pos, push_pointer(&pointer, &ix.to_string()));
+                            ix += 1;
+                        }
+                        None => return pos,
+                    }
+                }
+            }
+            Some(b'"') => self.scan_string(pos).1,
+            _ => {
+                while pos < bytes.len() && !matches!(bytes[pos], b',' | b']' | b'}') && !bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                pos
+            }
+        }
+    }
+
+    /// Scan the string starting at `pos`, returning its unescaped content and the offset after it.
+    fn scan_string(&self, pos: usize) -> (String, usize) {
+        let bytes = self.source.as_bytes();
+        let mut end = pos + 1;
+        while end < bytes.len() && bytes[end] != b'"' {
+            if bytes[end] == b'\\' {
+                end += 1;
+            }
+            end += 1;
+        }
+        let raw = &self.source[pos..(end + 1).min(bytes.len())];
+        let content = serde_json::from_str::<String>(raw).unwrap_or_else(|_| raw.trim_matches('"').to_string());
+        (content, end + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest() {
+        assert_eq!(suggest("lable", ["label", "variant"]), Some("label"));
+        assert_eq!(suggest("buton", COMPONENTS.iter().map(|spec| spec.name)), Some("button"));
+        assert_eq!(suggest("onClick", ["on_click", "on_change"]), Some("on_click"));
+        assert_eq!(suggest("width", ["label", "variant"]), None);
+    }
+
+    #[test]
+    fn test_validate_source() {
+        let source = r#"{
+  "type": "column",
+  "children": [
+    { "type": "buton", "props": { "label": "Save" } },
+    {
+      "type": "button",
+      "props": { "lable": "Save", "disabled": "yes", "variant": "primery" }
+    },
+    { "$ref": "card.json", "props": { "anything": 1 } },
+    "Some text"
+  ]
+}"#;
+        let Err(UiError::Invalid(diagnostics)) = validate_source(source, Some(Path::new("ui.json"))) else {
+            panic!("expected errors");
+        };
+
+        let summary = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.pointer.as_str(), d.line, d.column, d.suggestion.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Error, "/children/0/type", 4, 15, Some("button")),
+                (Severity::Warning, "/children/1/props/lable", 7, 18, Some("label")),
+                (Severity::Error, "/children/1/props/disabled", 7, 47, None),
+                (Severity::Error, "/children/1/props/variant", 7, 65, Some("primary")),
+            ]
+        );
+        assert_eq!(
+            diagnostics[2].to_string(),
+            "ui.json:7:47: error: `disabled` expects a boolean, found `yes` (at /children/1/props/disabled)"
+        );
+    }
+
+    #[test]
+    fn test_validate_source_warnings_only() {
+        let source = r#"{ "type": "text", "props": { "content": "{{user.name}}", "size": 12 } }"#;
+        let (_, diagnostics) = validate_source(source, None).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].pointer, "/props/size");
+
+        // Bindings are only type checked once resolved.
+        let source = r#"{ "type": "switch", "props": { "checked": "{{done}}" } }"#;
+        assert!(validate_source(source, None).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_syntax_error_position() {
+        let source = "{\n  \"type\": \"div\",\n}";
+        let err = validate_source(source, None).unwrap_err();
+        let diagnostic = &err.diagnostics()[0];
+        assert_eq!((diagnostic.line, diagnostic.column), (3, 1));
+        assert!(!diagnostic.message.contains("at line"));
+    }
+
+    #[test]
+    fn test_missing_type_and_bad_children() {
+        let diagnostics = validate_value(&serde_json::json!({
+            "props": [],
+            "children": [{ "props": {} }, 1, { "$ref": "a.json" }],
+            "for": "todos"
+        }));
+        let pointers = diagnostics.iter().map(|d| d.pointer.as_str()).collect::<Vec<_>>();
+        assert_eq!(pointers, vec!["/for", "", "/props", "/children/0", "/children/1"]);
+    }
+
+    #[test]
+    fn test_style_props() {
+        let diagnostics = validate_value(&serde_json::json!({
+            "type": "column",
+            "props": {
+                "width": "50%",
+                "padding": [8, "1rem"],
+                "margin": "wide",
+                "shadow": { "y": 4, "blur": 12 },
+                "font_weight": "bold",
+                "responsive": {
+                    "md": { "padding": 16, "gap": "2em" },
+                    "tablet": { "padding": 24 },
+                    "1200px": { "direction": "row" }
+                }
+            }
+        }));
+        let mut summary = diagnostics
+            .iter()
+            .map(|d| (d.pointer.as_str(), d.severity))
+            .collect::<Vec<_>>();
+        summary.sort_by_key(|(pointer, _)| *pointer);
+        assert_eq!(
+            summary,
+            vec![
+                ("/props/margin", Severity::Error),
+                ("/props/responsive/1200px/direction", Severity::Warning),
+                ("/props/responsive/md/gap", Severity::Error),
+                ("/props/responsive/tablet", Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_blocks() {
+        let diagnostics = validate_value(&serde_json::json!({
+            "component": {
+                "props": {
+                    "title": { "type": "strng", "required": "yes" },
+                    "count": { "type": "number", "default": "zero" },
+                    "tone": { "type": "color", "defualt": "primary" }
+                },
+                "slots": "footer"
+            },
+            "styles": { "card": { "padding": 8 }, "bad": 1 },
+            "type": "div",
+            "children": [{ "type": "text", "slot": "footer", "component": {} }]
+        }));
+        let mut summary = diagnostics
+            .iter()
+            .map(|d| (d.pointer.as_str(), d.suggestion.as_deref()))
+            .collect::<Vec<_>>();
+        summary.sort_by_key(|(pointer, _)| *pointer);
+        assert_eq!(
+            summary,
+            vec![
+                ("/children/0/component", None),
+                ("/component/props/count/default", None),
+                ("/component/props/title/type", Some("string")),
+                ("/component/props/tone/defualt", Some("default")),
+                ("/component/slots", None),
+                ("/styles/bad", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_source_positions_escaped_keys() {
+        let source = r#"{"a/b": {"c~d": [1, "x"]}}"#;
+        let positions = SourcePositions::new(source);
+        assert_eq!(positions.keys["/a~1b"], 1);
+        assert_eq!(positions.values["/a~1b/c~0d/1"], 20);
+    }
+}
@@ -29,6 +29,7 @@ pub mod checkbox;
 pub mod clipboard;
 pub mod color_picker;
 pub mod description_list;
+pub mod diff;
 pub mod divider;
 pub mod dock;
 pub mod drawer;
@@ -61,6 +62,7 @@ pub mod tag;
 pub mod text;
 pub mod theme;
 pub mod tooltip;
+pub mod tree;
 #[cfg(feature = "webview")]
 pub mod webview;
 
@@ -112,6 +114,7 @@ pub fn init(cx: &mut App) {
     menu::init(cx);
     table::init(cx);
     text::init(cx);
+    tree::init(cx);
 }
 
 #[inline]
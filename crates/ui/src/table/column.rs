@@ -3,7 +3,10 @@ use gpui::{
     ParentElement as _, Pixels, Render, SharedString, Styled as _, TextAlign, Window,
 };
 
-use crate::ActiveTheme as _;
+use crate::{
+    table::{Aggregate, ColumnFilterKind},
+    ActiveTheme as _,
+};
 
 /// Represents a column in a table, used for initializing table columns.
 #[derive(Debug, Clone)]
@@ -18,6 +21,8 @@ pub struct Column {
     pub resizable: bool,
     pub movable: bool,
     pub selectable: bool,
+    pub filter: Option<ColumnFilterKind>,
+    pub aggregate: Option<Aggregate>,
 }
 
 impl Default for Column {
@@ -33,6 +38,8 @@ impl Default for Column {
             resizable: true,
             movable: true,
             selectable: true,
+            filter: None,
+            aggregate: None,
         }
     }
 }
@@ -129,6 +136,18 @@ impl Column {
         self.selectable = selectable;
         self
     }
+
+    /// Set the filter of the column to show the filter button in the header, default is None.
+    pub fn filter(mut self, filter: ColumnFilterKind) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Set the aggregate of the column to show in the group rows, default is None.
+    pub fn aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregate = Some(aggregate);
+        self
+    }
 }
 
 impl FluentBuilder for Column {}
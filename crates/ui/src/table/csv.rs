@@ -0,0 +1,398 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use gpui::{
+    div, prelude::FluentBuilder as _, App, Context, IntoElement, ParentElement as _, Styled as _,
+    Task, TextAlign, Window,
+};
+
+use crate::{
+    h_flex,
+    table::{CellValue, Column, ColumnSort, Table, TableDelegate},
+};
+
+/// The delimiters to sniff, in the order of preference.
+const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+/// The number of the lines to sniff the delimiter.
+const SNIFF_LINES: usize = 20;
+
+/// The options to parse the CSV text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The delimiter of the fields, default is `,`.
+    pub delimiter: char,
+    /// Whether the first record is the header, default is true.
+    pub has_header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Sniff the delimiter and the header from the first lines of the text.
+    ///
+    /// The delimiter is the one of `,`, `\t`, `;` and `|` that splits the most lines into the same
+    /// number of fields, and the first record is the header if all of it are texts and not the
+    /// same type as the values under it.
+    pub fn sniff(text: &str) -> Self {
+        let end = text
+            .match_indices('\n')
+            .nth(SNIFF_LINES - 1)
+            .map_or(text.len(), |(ix, _)| ix);
+        let sample = &text[..end];
+
+        let delimiter = DELIMITERS
+            .into_iter()
+            .filter_map(|delimiter| {
+                let records = parse_records(sample, delimiter);
+                let fields_count = records.first()?.len();
+                if fields_count < 2 {
+                    return None;
+                }
+
+                let consistent = records
+                    .iter()
+                    .filter(|record| record.len() == fields_count)
+                    .count();
+                Some(((consistent, fields_count), delimiter))
+            })
+            // Prefer the former delimiter with the same score.
+            .rev()
+            .max_by_key(|(score, _)| *score)
+            .map_or(',', |(_, delimiter)| delimiter);
+
+        let records = parse_records(sample, delimiter);
+        Self {
+            delimiter,
+            has_header: sniff_header(&records),
+        }
+    }
+}
+
+/// Returns true if the first record looks like a header.
+fn sniff_header(records: &[Vec<String>]) -> bool {
+    let Some((first, body)) = records.split_first() else {
+        return false;
+    };
+    if first
+        .iter()
+        .any(|field| field.trim().is_empty() || is_number(field))
+    {
+        return false;
+    }
+    if body.is_empty() {
+        return true;
+    }
+
+    // The header of a numeric column, or a name not in the column.
+    (0..first.len()).any(|col_ix| {
+        let mut values = body
+            .iter()
+            .filter_map(|record| record.get(col_ix))
+            .filter(|field| !field.trim().is_empty())
+            .peekable();
+        values.peek().is_some() && values.all(|field| is_number(field))
+    }) || (0..first.len()).all(|col_ix| {
+        body.iter()
+            .all(|record| record.get(col_ix) != first.get(col_ix))
+    })
+}
+
+fn is_number(text: &str) -> bool {
+    text.trim().parse::<f64>().is_ok()
+}
+
+/// Parse the CSV text into the records of the fields, the quoted fields can contain the delimiter,
+/// newlines and the escaped quotes (`""`).
+pub(crate) fn parse_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    // Whether the record has any char, to skip the empty lines.
+    let mut dirty = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                dirty = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                if dirty {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                dirty = false;
+            }
+            c if c == delimiter => {
+                record.push(std::mem::take(&mut field));
+                dirty = true;
+            }
+            _ => {
+                field.push(c);
+                dirty = true;
+            }
+        }
+    }
+
+    if dirty {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// A [`TableDelegate`] of the records of a CSV file, the columns are sortable and the numeric
+/// columns are aligned to the right.
+///
+/// ```ignore
+/// let delegate = CsvTableDelegate::load("data.csv", cx).await?;
+/// let table = cx.new(|cx| Table::new(delegate, window, cx));
+/// ```
+pub struct CsvTableDelegate {
+    columns: Vec<Column>,
+    rows: Vec<Vec<CellValue>>,
+    /// The order of the rows, sorted by the `perform_sort`.
+    order: Vec<usize>,
+}
+
+impl CsvTableDelegate {
+    /// Parse the CSV text, the delimiter, the header and the type of the columns are sniffed.
+    pub fn parse(text: &str) -> Self {
+        Self::parse_with_options(text, CsvOptions::sniff(text))
+    }
+
+    /// Parse the CSV text with the options, the type of the columns are sniffed.
+    ///
+    /// A column is numeric if all the non-empty values of it are numbers.
+    pub fn parse_with_options(text: &str, options: CsvOptions) -> Self {
+        let mut records = parse_records(text, options.delimiter);
+        let header = if options.has_header && !records.is_empty() {
+            records.remove(0)
+        } else {
+            vec![]
+        };
+
+        let columns_count = records
+            .iter()
+            .chain(Some(&header))
+            .map(|record| record.len())
+            .max()
+            .unwrap_or(0);
+        let numeric = (0..columns_count)
+            .map(|col_ix| {
+                let mut values = records
+                    .iter()
+                    .filter_map(|record| record.get(col_ix))
+                    .filter(|field| !field.trim().is_empty())
+                    .peekable();
+                values.peek().is_some() && values.all(|field| is_number(field))
+            })
+            .collect::<Vec<_>>();
+
+        let mut keys = HashSet::new();
+        let columns = (0..columns_count)
+            .map(|col_ix| {
+                let name = header
+                    .get(col_ix)
+                    .map(|name| name.trim())
+                    .filter(|name| !name.is_empty())
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("Column {}", col_ix + 1));
+                // The column key must be unique, for the filters and the export.
+                let mut key = name.clone();
+                if !keys.insert(key.clone()) {
+                    key = format!("{}_{}", name, col_ix + 1);
+                    keys.insert(key.clone());
+                }
+
+                Column::new(key, name)
+                    .sortable()
+                    .when(numeric[col_ix], |this| this.text_right())
+            })
+            .collect();
+
+        let rows = records
+            .into_iter()
+            .map(|record| {
+                (0..columns_count)
+                    .map(|col_ix| {
+                        let field = record.get(col_ix).map_or("", |field| field.as_str());
+                        match numeric[col_ix] {
+                            true => field
+                                .trim()
+                                .parse::<f64>()
+                                .map_or(CellValue::Empty, CellValue::Number),
+                            false => CellValue::from(field),
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+
+        Self {
+            columns,
+            order: (0..rows.len()).collect(),
+            rows,
+        }
+    }
+
+    /// Read and parse the CSV file on the background.
+    pub fn load(path: impl Into<PathBuf>, cx: &App) -> Task<anyhow::Result<Self>> {
+        let path = path.into();
+        cx.background_executor().spawn(async move {
+            let text = std::fs::read_to_string(&path)?;
+            Ok(Self::parse(&text))
+        })
+    }
+
+    /// Returns the value of the cell, by the displayed row.
+    pub fn value(&self, row_ix: usize, col_ix: usize) -> Option<&CellValue> {
+        self.rows.get(*self.order.get(row_ix)?)?.get(col_ix)
+    }
+}
+
+impl TableDelegate for CsvTableDelegate {
+    fn columns_count(&self, _: &App) -> usize {
+        self.columns.len()
+    }
+
+    fn rows_count(&self, _: &App) -> usize {
+        self.rows.len()
+    }
+
+    fn column(&self, col_ix: usize, _: &App) -> &Column {
+        &self.columns[col_ix]
+    }
+
+    fn perform_sort(
+        &mut self,
+        col_ix: usize,
+        sort: ColumnSort,
+        _: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+        self.order = (0..self.rows.len()).collect();
+        let rows = &self.rows;
+        let value = |row_ix: &usize| rows[*row_ix].get(col_ix).unwrap_or(&CellValue::Empty);
+        match sort {
+            ColumnSort::Ascending => self.order.sort_by(|a, b| value(a).total_cmp(value(b))),
+            ColumnSort::Descending => self.order.sort_by(|a, b| value(b).total_cmp(value(a))),
+            ColumnSort::Default => {}
+        }
+        cx.notify();
+    }
+
+    fn render_th(
+        &self,
+        col_ix: usize,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        let column = &self.columns[col_ix];
+        h_flex()
+            .size_full()
+            .when(column.align == TextAlign::Right, |this| this.justify_end())
+            .child(column.name.clone())
+    }
+
+    fn render_td(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        let right = self.columns[col_ix].align == TextAlign::Right;
+        let text = self
+            .value(row_ix, col_ix)
+            .map(|value| value.to_text())
+            .unwrap_or_default();
+
+        h_flex()
+            .size_full()
+            .when(right, |this| this.justify_end())
+            .child(div().truncate().child(text))
+    }
+
+    fn cell_text(&self, row_ix: usize, col_ix: usize, _: &App) -> String {
+        self.value(row_ix, col_ix)
+            .map(|value| value.to_string())
+            .unwrap_or_default()
+    }
+
+    fn cell_value(&self, row_ix: usize, col_ix: usize, _: &App) -> CellValue {
+        self.value(row_ix, col_ix).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_records() {
+        let text = "a,\"b,c\",d\r\n\n\"say \"\"hi\"\"\",\"x\ny\",\n1,,3";
+        assert_eq!(
+            parse_records(text, ','),
+            vec![
+                vec!["a", "b,c", "d"],
+                vec!["say \"hi\"", "x\ny", ""],
+                vec!["1", "", "3"],
+            ]
+        );
+        assert_eq!(parse_records("a\tb\n", '\t'), vec![vec!["a", "b"]]);
+        assert!(parse_records("", ',').is_empty());
+    }
+
+    #[test]
+    fn test_sniff() {
+        let options = CsvOptions::sniff("name;price;note\nApple;3.5;a, b\nPear;2;c, d\n");
+        assert_eq!(options.delimiter, ';');
+        assert!(options.has_header);
+
+        let options = CsvOptions::sniff("Apple\t3.5\nPear\t2\n");
+        assert_eq!(options.delimiter, '\t');
+        assert!(!options.has_header);
+
+        // The names not in the columns.
+        assert!(CsvOptions::sniff("name,city\nJohn,Paris\nJane,Rome").has_header);
+        assert!(!CsvOptions::sniff("John,Paris\nJane,Paris\nJohn,Rome").has_header);
+    }
+
+    #[test]
+    fn test_csv_table_delegate() {
+        let delegate = CsvTableDelegate::parse("name,price,name\nApple,3.5,x\nPear,,y\n");
+        let keys = delegate
+            .columns
+            .iter()
+            .map(|column| column.key.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["name", "price", "name_3"]);
+        assert_eq!(delegate.columns[1].align, TextAlign::Right);
+        assert_eq!(delegate.rows.len(), 2);
+        assert_eq!(delegate.value(0, 1), Some(&CellValue::Number(3.5)));
+        assert_eq!(delegate.value(1, 1), Some(&CellValue::Empty));
+        assert_eq!(delegate.value(1, 2), Some(&CellValue::from("y")));
+    }
+}
@@ -1,14 +1,14 @@
 use std::ops::Range;
 
 use gpui::{
-    div, App, Context, Div, InteractiveElement as _, IntoElement, ParentElement as _, Stateful,
-    Styled as _, Window,
+    div, App, Context, Div, InteractiveElement as _, IntoElement, ParentElement as _, SharedString,
+    Stateful, Styled as _, Window,
 };
 
 use crate::{
     h_flex,
     popup_menu::PopupMenu,
-    table::{loading::Loading, Column, ColumnSort, Table},
+    table::{loading::Loading, CellValue, Column, ColumnSort, Table, TableSelection},
     ActiveTheme as _, Icon, IconName, Size,
 };
 
@@ -70,6 +70,95 @@ pub trait TableDelegate: Sized + 'static {
         cx: &mut Context<Table<Self>>,
     ) -> impl IntoElement;
 
+    /// Return the text of the cell at the given row and column, used to copy the selection as TSV.
+    ///
+    /// Default to empty.
+    fn cell_text(&self, row_ix: usize, col_ix: usize, cx: &App) -> String {
+        String::new()
+    }
+
+    /// Return the value of the cell at the given row and column, used to filter, sort and group
+    /// the rows, and compute the aggregates of the groups.
+    ///
+    /// This is called for all the rows of the filtered, sorted, grouped or aggregated columns,
+    /// so make sure it is fast.
+    ///
+    /// Default to the text of the `cell_text`.
+    fn cell_value(&self, row_ix: usize, col_ix: usize, cx: &App) -> CellValue {
+        CellValue::from(self.cell_text(row_ix, col_ix, cx))
+    }
+
+    /// Called before the selection of the table changed, by the mouse, keyboard or the methods of the table.
+    ///
+    /// Return `None` to veto the change, or return a modified selection to customize it.
+    ///
+    /// Default to accept the selection.
+    fn will_change_selection(
+        &mut self,
+        selection: TableSelection,
+        cx: &mut Context<Table<Self>>,
+    ) -> Option<TableSelection> {
+        Some(selection)
+    }
+
+    /// Return true if the cell at the given row and column can be edited inline.
+    ///
+    /// Default: false
+    fn is_cell_editable(&self, row_ix: usize, col_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Called when start editing the cell, by double-click, Enter or Tab from the previous cell.
+    ///
+    /// Create the editor here (e.g. the `InputState` of a text input or `NumberInput`, the
+    /// `DropdownState`, or the `DatePickerState` with the value of the cell), and focus it.
+    ///
+    /// Return false to not start editing.
+    fn start_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> bool {
+        false
+    }
+
+    /// Render the editor of the cell in editing, default to the `render_td`.
+    fn render_cell_editor(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        self.render_td(row_ix, col_ix, window, cx)
+    }
+
+    /// Commit the value of the editor to the cell, by Enter, Tab or clicking another cell.
+    ///
+    /// Return an error message to reject the value, the message is shown under the cell and
+    /// the cell keeps editing.
+    fn commit_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Result<(), SharedString> {
+        Ok(())
+    }
+
+    /// Called when the editing is canceled by Escape, the value of the editor should be discarded.
+    fn cancel_cell_edit(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) {
+    }
+
     /// Move the column at the given `col_ix` to insert before the column at the given `to_ix`.
     fn move_column(
         &mut self,
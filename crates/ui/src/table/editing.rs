@@ -0,0 +1,500 @@
+use gpui::{
+    anchored, deferred, div, prelude::FluentBuilder as _, px, relative, Context, IntoElement,
+    ParentElement as _, Styled as _, Window,
+};
+
+use crate::{
+    actions::{Confirm, SelectNext},
+    ActiveTheme as _,
+};
+
+use super::{EditNextCell, EditPrevCell, Table, TableCell, TableDelegate, TableEvent};
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Returns the cell in editing, `None` if the row of it is not displayed.
+    pub fn editing_cell(&self) -> Option<TableCell> {
+        let cell = self.editing_cell?;
+        Some(TableCell::new(self.display_row(cell.row)?, cell.col))
+    }
+
+    /// Returns the cell of the delegate of the displayed cell, `None` for the group row.
+    fn source_cell(&self, cell: TableCell) -> Option<TableCell> {
+        Some(TableCell::new(self.source_row(cell.row)?, cell.col))
+    }
+
+    /// Returns true if the displayed cell is in editing.
+    pub(super) fn is_editing_cell(&self, cell: TableCell) -> bool {
+        self.editing_cell.is_some() && self.editing_cell == self.source_cell(cell)
+    }
+
+    /// Returns true if the displayed cell can be edited.
+    fn is_cell_editable(&self, cell: TableCell, cx: &Context<Self>) -> bool {
+        self.source_cell(cell).map_or(false, |source| {
+            self.delegate.is_cell_editable(source.row, source.col, cx)
+        })
+    }
+
+    /// Start editing the cell, the editor is created by [`TableDelegate::start_cell_edit`].
+    ///
+    /// The cell in editing is committed first, returns false if it is rejected,
+    /// or the cell can't be edited.
+    pub fn edit_cell(
+        &mut self,
+        cell: TableCell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.is_editing_cell(cell) {
+            return true;
+        }
+        if !self.commit_edit(window, cx) {
+            return false;
+        }
+        if !self.is_cell_editable(cell, cx) {
+            return false;
+        }
+        let Some(source) = self.source_cell(cell) else {
+            return false;
+        };
+        if !self
+            .delegate
+            .start_cell_edit(source.row, source.col, window, cx)
+        {
+            return false;
+        }
+
+        self.editing_cell = Some(source);
+        self.edit_error = None;
+        cx.notify();
+        true
+    }
+
+    /// Commit the value of the cell in editing by [`TableDelegate::commit_cell_edit`].
+    ///
+    /// Returns false if the value is rejected, the validation message is shown under the cell,
+    /// and the cell keeps editing.
+    pub fn commit_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let Some(cell) = self.editing_cell else {
+            return true;
+        };
+
+        match self
+            .delegate
+            .commit_cell_edit(cell.row, cell.col, window, cx)
+        {
+            Ok(()) => {
+                let display_cell = self.editing_cell();
+                self.editing_cell = None;
+                self.edit_error = None;
+                self.focus_handle.focus(window);
+                if let Some(display_cell) = display_cell {
+                    cx.emit(TableEvent::CellEdited(display_cell));
+                }
+                // The value may change the filtered, sorted and grouped rows.
+                self.update_snapshot_row(cell.row, cx);
+                self.update_view(cx);
+                cx.notify();
+                true
+            }
+            Err(message) => {
+                self.edit_error = Some(message);
+                cx.notify();
+                false
+            }
+        }
+    }
+
+    /// Cancel the editing of the cell, the value of the editor is discarded.
+    pub fn cancel_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(cell) = self.editing_cell.take() else {
+            return;
+        };
+
+        self.edit_error = None;
+        self.delegate
+            .cancel_cell_edit(cell.row, cell.col, window, cx);
+        self.focus_handle.focus(window);
+        cx.notify();
+    }
+
+    /// Returns the cell to start editing by Enter, the head of the selected cells,
+    /// or the first editable cell of the selected row.
+    fn active_cell(&self, cx: &Context<Self>) -> Option<TableCell> {
+        if let Some(cells) = self.selection.cells() {
+            return Some(cells.head);
+        }
+
+        let row_ix = self.selection.active_row()?;
+        if !self.selection.contains_row(row_ix) {
+            return None;
+        }
+        (0..self.delegate.columns_count(cx))
+            .map(|col_ix| TableCell::new(row_ix, col_ix))
+            .find(|cell| self.is_cell_editable(*cell, cx))
+    }
+
+    /// Enter to start editing the active cell, or commit the editing and move down.
+    pub(super) fn action_confirm(
+        &mut self,
+        _: &Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.editing_cell.is_none() {
+            match self.active_cell(cx) {
+                Some(cell) => {
+                    self.edit_cell(cell, window, cx);
+                }
+                None => cx.propagate(),
+            }
+            return;
+        }
+
+        if !self.commit_edit(window, cx) {
+            return;
+        }
+        if self.cell_selectable {
+            self.move_selected_cell(1, 0, false, cx);
+        } else {
+            self.action_select_next(&SelectNext, window, cx);
+        }
+    }
+
+    pub(super) fn action_edit_next_cell(
+        &mut self,
+        _: &EditNextCell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.edit_adjacent_cell(false, window, cx);
+    }
+
+    pub(super) fn action_edit_prev_cell(
+        &mut self,
+        _: &EditPrevCell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.edit_adjacent_cell(true, window, cx);
+    }
+
+    /// Commit the editing, and start editing the next (or previous if `backward`) editable cell.
+    fn edit_adjacent_cell(&mut self, backward: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(mut cell) = self.editing_cell() else {
+            cx.propagate();
+            return;
+        };
+        if !self.commit_edit(window, cx) {
+            return;
+        }
+
+        let rows_count = self.rows_count(cx);
+        let columns_count = self.delegate.columns_count(cx);
+        loop {
+            let Some(next) = step_cell(cell, rows_count, columns_count, backward) else {
+                return;
+            };
+            cell = next;
+            if self.is_cell_editable(cell, cx) {
+                break;
+            }
+        }
+
+        if self.cell_selectable {
+            self.set_selected_cell(cell, cx);
+        } else if self.selected_row() != Some(cell.row) {
+            self.set_selected_row(cell.row, cx);
+        }
+        self.edit_cell(cell, window, cx);
+    }
+
+    /// Render the editor of the cell in editing by the row of the delegate, with the validation
+    /// message under it.
+    pub(super) fn render_cell_editor(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        div()
+            .size_full()
+            .relative()
+            .child(self.delegate.render_cell_editor(row_ix, col_ix, window, cx))
+            .when_some(self.edit_error.clone(), |this, message| {
+                this.child(
+                    div().absolute().left_0().top(relative(1.)).child(deferred(
+                        anchored().snap_to_window_with_margin(px(8.)).child(
+                            div()
+                                .occlude()
+                                .mt_1()
+                                .px_2()
+                                .py_1()
+                                .text_xs()
+                                .whitespace_normal()
+                                .max_w(px(320.))
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().danger)
+                                .text_color(cx.theme().danger_foreground)
+                                .shadow_md()
+                                .child(message),
+                        ),
+                    )),
+                )
+            })
+    }
+}
+
+/// Returns the next (or previous if `backward`) cell in the row-major order.
+fn step_cell(
+    cell: TableCell,
+    rows_count: usize,
+    columns_count: usize,
+    backward: bool,
+) -> Option<TableCell> {
+    if columns_count == 0 || cell.row >= rows_count {
+        return None;
+    }
+
+    let ix = cell.row * columns_count + cell.col.min(columns_count - 1);
+    let next_ix = if backward { ix.checked_sub(1)? } else { ix + 1 };
+    if next_ix >= rows_count * columns_count {
+        return None;
+    }
+
+    Some(TableCell::new(
+        next_ix / columns_count,
+        next_ix % columns_count,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{App, Entity, Focusable as _, SharedString, TestAppContext, VisualTestContext};
+
+    use super::*;
+    use crate::table::Column;
+
+    /// A delegate of 3 rows and 3 columns, the middle column is read-only, and an empty
+    /// value is rejected.
+    struct EditableDelegate {
+        columns: Vec<Column>,
+        values: Vec<Vec<String>>,
+        /// The value of the editor of the cell in editing.
+        editor_value: Option<String>,
+        canceled: usize,
+    }
+
+    impl EditableDelegate {
+        fn new() -> Self {
+            Self {
+                columns: ["a", "b", "c"]
+                    .into_iter()
+                    .map(|key| Column::new(key, key))
+                    .collect(),
+                values: (0..3)
+                    .map(|row| (0..3).map(|col| format!("{}{}", row, col)).collect())
+                    .collect(),
+                editor_value: None,
+                canceled: 0,
+            }
+        }
+    }
+
+    impl TableDelegate for EditableDelegate {
+        fn columns_count(&self, _: &App) -> usize {
+            self.columns.len()
+        }
+
+        fn rows_count(&self, _: &App) -> usize {
+            self.values.len()
+        }
+
+        fn column(&self, col_ix: usize, _: &App) -> &Column {
+            &self.columns[col_ix]
+        }
+
+        fn render_td(
+            &self,
+            row_ix: usize,
+            col_ix: usize,
+            _: &mut Window,
+            _: &mut Context<Table<Self>>,
+        ) -> impl IntoElement {
+            self.values[row_ix][col_ix].clone()
+        }
+
+        fn is_cell_editable(&self, _: usize, col_ix: usize, _: &App) -> bool {
+            col_ix != 1
+        }
+
+        fn start_cell_edit(
+            &mut self,
+            row_ix: usize,
+            col_ix: usize,
+            _: &mut Window,
+            _: &mut Context<Table<Self>>,
+        ) -> bool {
+            self.editor_value = Some(self.values[row_ix][col_ix].clone());
+            true
+        }
+
+        fn commit_cell_edit(
+            &mut self,
+            row_ix: usize,
+            col_ix: usize,
+            _: &mut Window,
+            _: &mut Context<Table<Self>>,
+        ) -> Result<(), SharedString> {
+            let value = self.editor_value.clone().unwrap_or_default();
+            if value.is_empty() {
+                return Err("The value is required".into());
+            }
+            self.values[row_ix][col_ix] = value;
+            self.editor_value = None;
+            Ok(())
+        }
+
+        fn cancel_cell_edit(
+            &mut self,
+            _: usize,
+            _: usize,
+            _: &mut Window,
+            _: &mut Context<Table<Self>>,
+        ) {
+            self.editor_value = None;
+            self.canceled += 1;
+        }
+    }
+
+    fn build_table(
+        cx: &mut TestAppContext,
+    ) -> (Entity<Table<EditableDelegate>>, &mut VisualTestContext) {
+        cx.update(crate::init);
+        let (table, cx) = cx.add_window_view(|window, cx| {
+            Table::new(EditableDelegate::new(), window, cx).cell_selectable(true)
+        });
+        cx.update(|window, cx| table.focus_handle(cx).focus(window));
+        cx.run_until_parked();
+        (table, cx)
+    }
+
+    fn set_editor_value(
+        table: &Entity<Table<EditableDelegate>>,
+        value: &str,
+        cx: &mut VisualTestContext,
+    ) {
+        table.update(cx, |table, _| {
+            table.delegate_mut().editor_value = Some(value.to_string());
+        });
+    }
+
+    #[gpui::test]
+    fn test_commit_rejected(cx: &mut TestAppContext) {
+        let (table, cx) = build_table(cx);
+        table.update_in(cx, |table, window, cx| {
+            assert!(table.edit_cell(TableCell::new(0, 0), window, cx));
+        });
+        set_editor_value(&table, "", cx);
+
+        table.update_in(cx, |table, window, cx| {
+            assert!(!table.commit_edit(window, cx));
+            assert_eq!(table.editing_cell(), Some(TableCell::new(0, 0)));
+            assert_eq!(table.edit_error, Some("The value is required".into()));
+            // Another cell can't be edited until the value is valid.
+            assert!(!table.edit_cell(TableCell::new(2, 2), window, cx));
+        });
+
+        // Tab keeps editing the rejected cell.
+        cx.simulate_keystrokes("tab");
+        table.read_with(cx, |table, _| {
+            assert_eq!(table.editing_cell(), Some(TableCell::new(0, 0)));
+            assert_eq!(table.delegate().values[0][0], "00");
+        });
+
+        set_editor_value(&table, "new", cx);
+        table.update_in(cx, |table, window, cx| {
+            assert!(table.commit_edit(window, cx));
+            assert_eq!(table.editing_cell(), None);
+            assert_eq!(table.edit_error, None);
+            assert_eq!(table.delegate().values[0][0], "new");
+        });
+    }
+
+    #[gpui::test]
+    fn test_escape_cancels(cx: &mut TestAppContext) {
+        let (table, cx) = build_table(cx);
+        table.update_in(cx, |table, window, cx| {
+            table.edit_cell(TableCell::new(1, 2), window, cx);
+        });
+        set_editor_value(&table, "", cx);
+
+        cx.simulate_keystrokes("escape");
+        table.read_with(cx, |table, _| {
+            assert_eq!(table.editing_cell(), None);
+            assert_eq!(table.edit_error, None);
+            assert_eq!(table.delegate().canceled, 1);
+            assert_eq!(table.delegate().values[1][2], "12");
+        });
+    }
+
+    #[gpui::test]
+    fn test_tab_and_enter(cx: &mut TestAppContext) {
+        let (table, cx) = build_table(cx);
+        table.update_in(cx, |table, window, cx| {
+            table.edit_cell(TableCell::new(0, 0), window, cx);
+        });
+
+        // Tab commits and edits the next editable cell, skipping the read-only column.
+        set_editor_value(&table, "a", cx);
+        cx.simulate_keystrokes("tab");
+        table.read_with(cx, |table, _| {
+            assert_eq!(table.delegate().values[0][0], "a");
+            assert_eq!(table.editing_cell(), Some(TableCell::new(0, 2)));
+            assert_eq!(
+                table.selection().cells().map(|cells| cells.head),
+                Some(TableCell::new(0, 2))
+            );
+        });
+
+        // Tab at the end of the row moves to the next row.
+        cx.simulate_keystrokes("tab");
+        table.read_with(cx, |table, _| {
+            assert_eq!(table.editing_cell(), Some(TableCell::new(1, 0)));
+        });
+        cx.simulate_keystrokes("shift-tab shift-tab");
+        table.read_with(cx, |table, _| {
+            assert_eq!(table.editing_cell(), Some(TableCell::new(0, 0)));
+        });
+
+        // Enter commits and moves down, and then starts editing the cell.
+        set_editor_value(&table, "b", cx);
+        cx.simulate_keystrokes("enter");
+        table.read_with(cx, |table, _| {
+            assert_eq!(table.delegate().values[0][0], "b");
+            assert_eq!(table.editing_cell(), None);
+            assert_eq!(
+                table.selection().cells().map(|cells| cells.head),
+                Some(TableCell::new(1, 0))
+            );
+        });
+        cx.simulate_keystrokes("enter");
+        table.read_with(cx, |table, _| {
+            assert_eq!(table.editing_cell(), Some(TableCell::new(1, 0)));
+        });
+    }
+
+    #[test]
+    fn test_step_cell() {
+        let cell = TableCell::new;
+        assert_eq!(step_cell(cell(0, 0), 3, 2, false), Some(cell(0, 1)));
+        assert_eq!(step_cell(cell(0, 1), 3, 2, false), Some(cell(1, 0)));
+        assert_eq!(step_cell(cell(2, 1), 3, 2, false), None);
+        assert_eq!(step_cell(cell(1, 0), 3, 2, true), Some(cell(0, 1)));
+        assert_eq!(step_cell(cell(0, 0), 3, 2, true), None);
+        assert_eq!(step_cell(cell(0, 0), 3, 0, false), None);
+    }
+}
@@ -0,0 +1,260 @@
+use std::io::{self, Write};
+
+use gpui::{App, SharedString, TextAlign};
+use serde::{Deserialize, Serialize};
+
+use crate::table::{CellValue, Table, TableDelegate};
+
+/// The format to export the rows of the [`Table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ExportFormat {
+    /// Comma-separated values, with the header row.
+    Csv,
+    /// Tab-separated values, with the header row.
+    Tsv,
+    /// A JSON object per line, by the column keys.
+    JsonLines,
+    /// A Markdown table.
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Returns the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::JsonLines => "jsonl",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// A column of the exported rows.
+pub(crate) struct ExportColumn {
+    pub(crate) key: SharedString,
+    pub(crate) name: SharedString,
+    pub(crate) align: TextAlign,
+}
+
+/// Write the rows in the format one by one, only the current row is kept in memory.
+pub(crate) struct RowWriter<W: Write> {
+    writer: W,
+    format: ExportFormat,
+    columns: Vec<ExportColumn>,
+}
+
+impl<W: Write> RowWriter<W> {
+    /// Create the writer and write the header of the format.
+    pub(crate) fn new(
+        writer: W,
+        format: ExportFormat,
+        columns: Vec<ExportColumn>,
+    ) -> io::Result<Self> {
+        let mut this = Self {
+            writer,
+            format,
+            columns,
+        };
+
+        match format {
+            ExportFormat::Csv | ExportFormat::Tsv => {
+                let names = this
+                    .columns
+                    .iter()
+                    .map(|column| column.name.to_string())
+                    .collect::<Vec<_>>();
+                this.write_delimited(names.iter().map(|name| name.as_str()))?;
+            }
+            ExportFormat::Markdown => {
+                let names = this
+                    .columns
+                    .iter()
+                    .map(|column| column.name.to_string())
+                    .collect::<Vec<_>>();
+                this.write_markdown_row(names.iter().map(|name| name.as_str()))?;
+
+                let aligns = this
+                    .columns
+                    .iter()
+                    .map(|column| match column.align {
+                        TextAlign::Right => "---:",
+                        TextAlign::Center => ":---:",
+                        TextAlign::Left => "---",
+                    })
+                    .collect::<Vec<_>>();
+                this.write_markdown_row(aligns.into_iter())?;
+            }
+            ExportFormat::JsonLines => {}
+        }
+
+        Ok(this)
+    }
+
+    /// Write a row of the values by the columns.
+    pub(crate) fn write_row(&mut self, values: &[CellValue]) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv | ExportFormat::Tsv | ExportFormat::Markdown => {
+                let texts = values
+                    .iter()
+                    .map(|value| value.to_text())
+                    .collect::<Vec<_>>();
+                let texts = texts.iter().map(|text| text.as_ref());
+                if self.format == ExportFormat::Markdown {
+                    self.write_markdown_row(texts)
+                } else {
+                    self.write_delimited(texts)
+                }
+            }
+            ExportFormat::JsonLines => {
+                self.writer.write_all(b"{")?;
+                for (ix, (column, value)) in self.columns.iter().zip(values).enumerate() {
+                    if ix > 0 {
+                        self.writer.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut self.writer, &column.key)?;
+                    self.writer.write_all(b":")?;
+                    match value {
+                        CellValue::Empty => self.writer.write_all(b"null")?,
+                        // Write the integers without the fraction.
+                        CellValue::Number(number)
+                            if number.fract() == 0. && number.abs() < (1u64 << 53) as f64 =>
+                        {
+                            write!(self.writer, "{}", *number as i64)?
+                        }
+                        CellValue::Number(number) => {
+                            serde_json::to_writer(&mut self.writer, number)?
+                        }
+                        CellValue::Text(text) => serde_json::to_writer(&mut self.writer, text)?,
+                    }
+                }
+                self.writer.write_all(b"}\n")
+            }
+        }
+    }
+
+    /// Flush and return the writer.
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_delimited<'a>(&mut self, texts: impl Iterator<Item = &'a str>) -> io::Result<()> {
+        let delimiter = if self.format == ExportFormat::Tsv {
+            '\t'
+        } else {
+            ','
+        };
+
+        for (ix, text) in texts.enumerate() {
+            if ix > 0 {
+                write!(self.writer, "{}", delimiter)?;
+            }
+            if text.contains([delimiter, '"', '\n', '\r']) {
+                write!(self.writer, "\"{}\"", text.replace('"', "\"\""))?;
+            } else {
+                self.writer.write_all(text.as_bytes())?;
+            }
+        }
+        self.writer.write_all(b"\n")
+    }
+
+    fn write_markdown_row<'a>(&mut self, texts: impl Iterator<Item = &'a str>) -> io::Result<()> {
+        self.writer.write_all(b"|")?;
+        for text in texts {
+            let text = text
+                .replace('|', "\\|")
+                .replace("\r\n", "<br>")
+                .replace('\n', "<br>");
+            write!(self.writer, " {} |", text)?;
+        }
+        self.writer.write_all(b"\n")
+    }
+}
+
+impl<D> Table<D>
+where
+    D: TableDelegate,
+{
+    /// Export the displayed rows to the `writer` in the format, with the current filters, sorts
+    /// and the order of the columns, the group rows are skipped.
+    ///
+    /// The values are from [`TableDelegate::cell_value`], and the rows are written one by one,
+    /// so wrap the file in a [`std::io::BufWriter`].
+    pub fn export(&self, format: ExportFormat, writer: impl Write, cx: &App) -> io::Result<()> {
+        let columns = self
+            .col_groups
+            .iter()
+            .map(|col_group| ExportColumn {
+                key: col_group.column.key.clone(),
+                name: col_group.column.name.clone(),
+                align: col_group.column.align,
+            })
+            .collect::<Vec<_>>();
+        let columns_count = columns.len();
+
+        let mut writer = RowWriter::new(writer, format, columns)?;
+        let mut values = Vec::with_capacity(columns_count);
+        for source_ix in self.source_rows(0..self.rows_count(cx)) {
+            values.clear();
+            values.extend(
+                (0..columns_count).map(|col_ix| self.delegate.cell_value(source_ix, col_ix, cx)),
+            );
+            writer.write_row(&values)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(format: ExportFormat) -> String {
+        let columns = vec![
+            ExportColumn {
+                key: "name".into(),
+                name: "Name".into(),
+                align: TextAlign::Left,
+            },
+            ExportColumn {
+                key: "price".into(),
+                name: "Price".into(),
+                align: TextAlign::Right,
+            },
+        ];
+
+        let mut writer = RowWriter::new(Vec::new(), format, columns).unwrap();
+        writer
+            .write_row(&[CellValue::from("Apple, Inc."), CellValue::from(3.)])
+            .unwrap();
+        writer
+            .write_row(&[CellValue::from("a|\"b\""), CellValue::from(0.5)])
+            .unwrap();
+        writer
+            .write_row(&[CellValue::Empty, CellValue::Empty])
+            .unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_export() {
+        assert_eq!(
+            export(ExportFormat::Csv),
+            "Name,Price\n\"Apple, Inc.\",3\n\"a|\"\"b\"\"\",0.5\n,\n"
+        );
+        assert_eq!(
+            export(ExportFormat::Tsv),
+            "Name\tPrice\nApple, Inc.\t3\n\"a|\"\"b\"\"\"\t0.5\n\t\n"
+        );
+        assert_eq!(
+            export(ExportFormat::JsonLines),
+            "{\"name\":\"Apple, Inc.\",\"price\":3}\n{\"name\":\"a|\\\"b\\\"\",\"price\":0.5}\n{\"name\":null,\"price\":null}\n"
+        );
+        assert_eq!(
+            export(ExportFormat::Markdown),
+            "| Name | Price |\n| --- | ---: |\n| Apple, Inc. | 3 |\n| a\\|\"b\" | 0.5 |\n|  |  |\n"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashSet, rc::Rc};
+
+use gpui::{
+    div, prelude::FluentBuilder as _, px, App, AppContext as _, Context, DismissEvent, Entity,
+    EventEmitter, FocusHandle, Focusable, InteractiveElement as _, IntoElement, ParentElement as _,
+    Render, SharedString, StatefulInteractiveElement as _, Styled as _, Subscription, Task, Window,
+};
+use rust_i18n::t;
+
+use crate::{
+    actions::{Cancel, Confirm},
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    h_flex,
+    input::{InputEvent, InputState, TextInput},
+    table::CellValue,
+    v_flex, ActiveTheme as _, Sizable as _,
+};
+
+/// The kind of the filter of a column, to show the filter button in the column header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnFilterKind {
+    /// Filter the rows by the text contains, case-insensitive.
+    Text,
+    /// Filter the rows by a range of numbers.
+    Number,
+    /// Filter the rows by a checklist of the distinct values of the column.
+    Values,
+}
+
+/// The filter of a column, the rows are kept if the value of the column matches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnFilter {
+    /// The text of the value contains the text, case-insensitive.
+    Contains(SharedString),
+    /// The number of the value in the range, inclusive.
+    Range { min: Option<f64>, max: Option<f64> },
+    /// The text of the value is one of the values.
+    Values(HashSet<SharedString>),
+}
+
+impl ColumnFilter {
+    /// Returns true if the value matches the filter.
+    pub fn matches(&self, value: &CellValue) -> bool {
+        match self {
+            Self::Contains(text) => value
+                .to_text()
+                .to_lowercase()
+                .contains(&text.to_lowercase()),
+            Self::Range { min, max } => value.as_number().map_or(false, |number| {
+                min.map_or(true, |min| number >= min) && max.map_or(true, |max| number <= max)
+            }),
+            Self::Values(values) => values.contains(&value.to_text()),
+        }
+    }
+}
+
+/// The content of the filter popover of a column.
+pub(crate) struct ColumnFilterPanel {
+    focus_handle: FocusHandle,
+    kind: ColumnFilterKind,
+    text_input: Entity<InputState>,
+    min_input: Entity<InputState>,
+    max_input: Entity<InputState>,
+    /// The distinct values of the column, `None` while loading.
+    values: Option<Vec<SharedString>>,
+    checked: HashSet<SharedString>,
+    on_change: Rc<dyn Fn(Option<ColumnFilter>, &mut Window, &mut App)>,
+    _subscriptions: Vec<Subscription>,
+    _load_task: Task<()>,
+}
+
+impl ColumnFilterPanel {
+    /// Create the panel with the current filter of the column.
+    ///
+    /// The `values` are the distinct values of the column for the [`ColumnFilterKind::Values`],
+    /// and the `on_change` is called with the new filter when the inputs or the checklist changed.
+    pub(crate) fn new(
+        kind: ColumnFilterKind,
+        filter: Option<ColumnFilter>,
+        values: Task<Vec<SharedString>>,
+        on_change: impl Fn(Option<ColumnFilter>, &mut Window, &mut App) + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let (text, min, max, checked) = match filter {
+            Some(ColumnFilter::Contains(text)) => (text, None, None, None),
+            Some(ColumnFilter::Range { min, max }) => (SharedString::default(), min, max, None),
+            Some(ColumnFilter::Values(values)) => {
+                (SharedString::default(), None, None, Some(values))
+            }
+            None => (SharedString::default(), None, None, None),
+        };
+        let number_text = |number: Option<f64>| number.map(|n| n.to_string()).unwrap_or_default();
+
+        let text_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("Table.Contains"))
+                .default_value(text)
+        });
+        let min_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("Table.Min"))
+                .default_value(number_text(min))
+        });
+        let max_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(t!("Table.Max"))
+                .default_value(number_text(max))
+        });
+
+        let _subscriptions = [&text_input, &min_input, &max_input]
+            .into_iter()
+            .map(|input| {
+                cx.subscribe_in(input, window, |this, _, event: &InputEvent, window, cx| {
+                    if let InputEvent::Change = event {
+                        this.apply(window, cx);
+                    }
+                })
+            })
+            .collect();
+
+        let _load_task = cx.spawn(async move |this, cx| {
+            let values = values.await;
+            _ = this.update(cx, |this, cx| {
+                this.checked = checked.unwrap_or_else(|| values.iter().cloned().collect());
+                this.values = Some(values);
+                cx.notify();
+            });
+        });
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            kind,
+            text_input,
+            min_input,
+            max_input,
+            values: None,
+            checked: HashSet::default(),
+            on_change: Rc::new(on_change),
+            _subscriptions,
+            _load_task,
+        }
+    }
+
+    /// Returns the filter of the inputs or the checklist, `None` if nothing to filter.
+    fn filter(&self, cx: &App) -> Option<ColumnFilter> {
+        match self.kind {
+            ColumnFilterKind::Text => {
+                let text = self.text_input.read(cx).value();
+                let text = text.trim();
+                (!text.is_empty()).then(|| ColumnFilter::Contains(text.to_string().into()))
+            }
+            ColumnFilterKind::Number => {
+                let min = self.min_input.read(cx).value().trim().parse().ok();
+                let max = self.max_input.read(cx).value().trim().parse().ok();
+                (min.is_some() || max.is_some()).then_some(ColumnFilter::Range { min, max })
+            }
+            ColumnFilterKind::Values => {
+                let values = self.values.as_ref()?;
+                (self.checked.len() < values.len())
+                    .then(|| ColumnFilter::Values(self.checked.clone()))
+            }
+        }
+    }
+
+    fn apply(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let filter = self.filter(cx);
+        (self.on_change)(filter, window, cx);
+        cx.notify();
+    }
+
+    fn toggle_value(
+        &mut self,
+        value: &SharedString,
+        checked: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if checked {
+            self.checked.insert(value.clone());
+        } else {
+            self.checked.remove(value);
+        }
+        self.apply(window, cx);
+    }
+
+    fn toggle_all(&mut self, checked: bool, window: &mut Window, cx: &mut Context<Self>) {
+        self.checked = match (&self.values, checked) {
+            (Some(values), true) => values.iter().cloned().collect(),
+            _ => HashSet::default(),
+        };
+        self.apply(window, cx);
+    }
+
+    fn clear(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        (self.on_change)(None, window, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn render_values(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(values) = &self.values else {
+            return div()
+                .py_1()
+                .text_color(cx.theme().muted_foreground)
+                .child(t!("Table.Loading").to_string())
+                .into_any_element();
+        };
+
+        let all_checked = self.checked.len() == values.len();
+        v_flex()
+            .gap_1()
+            .child(
+                Checkbox::new("select-all")
+                    .small()
+                    .label(t!("Table.Select All").to_string())
+                    .checked(all_checked)
+                    .on_click(cx.listener(|this, checked: &bool, window, cx| {
+                        this.toggle_all(*checked, window, cx);
+                    })),
+            )
+            .child(
+                v_flex()
+                    .id("values")
+                    .gap_1()
+                    .max_h(px(240.))
+                    .overflow_y_scroll()
+                    .children(values.iter().enumerate().map(|(ix, value)| {
+                        let label = if value.is_empty() {
+                            t!("Table.Empty").to_string()
+                        } else {
+                            value.to_string()
+                        };
+                        let value = value.clone();
+
+                        Checkbox::new(("value", ix))
+                            .small()
+                            .label(label)
+                            .checked(self.checked.contains(&value))
+                            .on_click(cx.listener(move |this, checked: &bool, window, cx| {
+                                this.toggle_value(&value, *checked, window, cx);
+                            }))
+                    })),
+            )
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<DismissEvent> for ColumnFilterPanel {}
+
+impl Focusable for ColumnFilterPanel {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        match self.kind {
+            ColumnFilterKind::Text => self.text_input.focus_handle(cx),
+            ColumnFilterKind::Number => self.min_input.focus_handle(cx),
+            ColumnFilterKind::Values => self.focus_handle.clone(),
+        }
+    }
+}
+
+impl Render for ColumnFilterPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle)
+            // The panel is in the table, handle the keys before the table.
+            .on_action(cx.listener(|_, _: &Cancel, _, cx| cx.emit(DismissEvent)))
+            .on_action(cx.listener(|_, _: &Confirm, _, cx| cx.emit(DismissEvent)))
+            .w(px(220.))
+            .p_2()
+            .gap_2()
+            .text_sm()
+            .map(|this| match self.kind {
+                ColumnFilterKind::Text => this.child(TextInput::new(&self.text_input).small()),
+                ColumnFilterKind::Number => this.child(
+                    h_flex()
+                        .gap_2()
+                        .child(TextInput::new(&self.min_input).small())
+                        .child(TextInput::new(&self.max_input).small()),
+                ),
+                ColumnFilterKind::Values => this.child(self.render_values(cx)),
+            })
+            .child(
+                h_flex().justify_end().child(
+                    Button::new("clear")
+                        .small()
+                        .ghost()
+                        .label(t!("Table.Clear"))
+                        .on_click(cx.listener(|this, _, window, cx| this.clear(window, cx))),
+                ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_filter_matches() {
+        let filter = ColumnFilter::Contains("pl".into());
+        assert!(filter.matches(&CellValue::from("Apple")));
+        assert!(filter.matches(&CellValue::from("PLUS")));
+        assert!(!filter.matches(&CellValue::Empty));
+
+        let filter = ColumnFilter::Range {
+            min: Some(1.),
+            max: None,
+        };
+        assert!(filter.matches(&CellValue::from(1.)));
+        assert!(filter.matches(&CellValue::from("20")));
+        assert!(!filter.matches(&CellValue::from(0.5)));
+        assert!(!filter.matches(&CellValue::from("abc")));
+
+        let filter = ColumnFilter::Values(HashSet::from_iter(["US".into(), "".into()]));
+        assert!(filter.matches(&CellValue::from("US")));
+        assert!(filter.matches(&CellValue::Empty));
+        assert!(!filter.matches(&CellValue::from("HK")));
+    }
+}
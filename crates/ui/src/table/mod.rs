@@ -1,30 +1,66 @@
-use std::{ops::Range, rc::Rc, time::Duration};
+use std::{collections::HashSet, ops::Range, rc::Rc, sync::Arc, time::Duration};
 
 use crate::{
-    actions::{Cancel, SelectNext, SelectPrev},
+    actions::{Cancel, Confirm, SelectNext, SelectPrev},
+    button::{Button, ButtonVariants as _},
     context_menu::ContextMenuExt,
     h_flex,
+    popover::Popover,
     popup_menu::PopupMenu,
     scroll::{self, ScrollableMask, Scrollbar, ScrollbarState},
-    v_flex, ActiveTheme, Icon, IconName, Sizable, Size, StyleSized as _, StyledExt,
-    VirtualListScrollHandle,
+    v_flex, ActiveTheme, Icon, IconName, Selectable as _, Sizable, Size, StyleSized as _,
+    StyledExt, VirtualListScrollHandle,
 };
 use gpui::{
-    actions, canvas, div, prelude::FluentBuilder, px, uniform_list, App, AppContext, Axis, Bounds,
-    Context, Div, DragMoveEvent, Edges, EventEmitter, FocusHandle, Focusable, InteractiveElement,
-    IntoElement, KeyBinding, ListSizingBehavior, MouseButton, MouseDownEvent, ParentElement,
-    Pixels, Point, Render, ScrollStrategy, ScrollWheelEvent, SharedString,
-    StatefulInteractiveElement as _, Styled, Task, UniformListScrollHandle, Window,
+    actions, canvas, div, prelude::FluentBuilder, px, uniform_list, AnyElement, App, AppContext,
+    Axis, Bounds, ClickEvent, ClipboardItem, Context, Corner, Div, DragMoveEvent, Edges,
+    EventEmitter, FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding,
+    ListSizingBehavior, MouseButton, MouseDownEvent, ParentElement, Pixels, Point, Render,
+    ScrollStrategy, ScrollWheelEvent, SharedString, StatefulInteractiveElement as _, Styled, Task,
+    UniformListScrollHandle, Window,
 };
 
 mod column;
+mod csv;
 mod delegate;
+mod editing;
+mod export;
+mod filter;
 mod loading;
+mod selection;
+mod view;
 
 pub use column::*;
+pub use csv::{CsvOptions, CsvTableDelegate};
 pub use delegate::*;
-
-actions!(table, [SelectPrevColumn, SelectNextColumn]);
+pub use export::ExportFormat;
+pub use filter::{ColumnFilter, ColumnFilterKind};
+pub use selection::*;
+pub use view::{Aggregate, CellValue, TableGroup, TableRow};
+
+use filter::ColumnFilterPanel;
+use view::{build_rows, display_rows, distinct_values, TableQuery, TableSnapshot};
+
+/// The max number of the distinct values in the values checklist of the filter.
+const FILTER_VALUES_LIMIT: usize = 1000;
+/// The number of the values collected from the delegate between the yields to the event loop.
+const SNAPSHOT_CHUNK_SIZE: usize = 10_000;
+
+actions!(
+    table,
+    [
+        SelectPrevColumn,
+        SelectNextColumn,
+        SelectUp,
+        SelectDown,
+        SelectLeft,
+        SelectRight,
+        SelectAll,
+        Copy,
+        EditNextCell,
+        EditPrevCell
+    ]
+);
 
 pub fn init(cx: &mut App) {
     let context = Some("Table");
@@ -34,15 +70,24 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("down", SelectNext, context),
         KeyBinding::new("left", SelectPrevColumn, context),
         KeyBinding::new("right", SelectNextColumn, context),
+        KeyBinding::new("shift-up", SelectUp, context),
+        KeyBinding::new("shift-down", SelectDown, context),
+        KeyBinding::new("shift-left", SelectLeft, context),
+        KeyBinding::new("shift-right", SelectRight, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-a", SelectAll, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-a", SelectAll, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-c", Copy, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-c", Copy, context),
+        KeyBinding::new("enter", Confirm { secondary: false }, context),
+        KeyBinding::new("tab", EditNextCell, context),
+        KeyBinding::new("shift-tab", EditPrevCell, context),
     ]);
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum SelectionState {
-    Column,
-    Row,
-}
-
 #[derive(Clone)]
 pub enum TableEvent {
     /// Single click or move to selected row.
@@ -50,6 +95,10 @@ pub enum TableEvent {
     /// Double click on the row.
     DoubleClickedRow(usize),
     SelectColumn(usize),
+    /// The selection has changed, with the full selection of the rows, cells or column.
+    SelectionChanged(TableSelection),
+    /// The value of the cell has been committed by the inline editing.
+    CellEdited(TableCell),
     ColumnWidthsChanged(Vec<Pixels>),
     MoveColumn(usize, usize),
 }
@@ -93,6 +142,10 @@ pub struct Table<D: TableDelegate> {
     pub col_selectable: bool,
     /// Whether the table can select row.
     pub row_selectable: bool,
+    /// Whether the table selects the cells instead of the rows, default is false.
+    ///
+    /// Click or arrow keys to select a cell, shift-click or shift-arrow keys to select a range of cells.
+    pub cell_selectable: bool,
     /// Whether the table can sort.
     pub sortable: bool,
     /// Whether the table can resize columns.
@@ -108,10 +161,12 @@ pub struct Table<D: TableDelegate> {
     pub horizontal_scroll_state: ScrollbarState,
 
     scrollbar_visible: Edges<bool>,
-    selected_row: Option<usize>,
-    selection_state: SelectionState,
+    selection: TableSelection,
     right_clicked_row: Option<usize>,
-    selected_col: Option<usize>,
+    /// The cell in editing by the row of the delegate, see [`TableDelegate::start_cell_edit`].
+    editing_cell: Option<TableCell>,
+    /// The validation message of the last rejected commit of the editing cell.
+    edit_error: Option<SharedString>,
 
     /// The column index that is being resized.
     resizing_col: Option<usize>,
@@ -125,8 +180,24 @@ pub struct Table<D: TableDelegate> {
     /// The visible range of the rows and columns.
     visible_range: VisibleRangeState,
 
+    /// The filters of the columns, by the column key.
+    filters: Vec<(SharedString, ColumnFilter)>,
+    /// The sorts of the columns by shift-click the sort icon, by the column key in priority order.
+    sorts: Vec<(SharedString, ColumnSort)>,
+    /// The columns to group the rows, by the column key.
+    group_by: Vec<SharedString>,
+    /// The paths of the collapsed groups, see [`TableGroup::path`].
+    collapsed_groups: HashSet<Vec<SharedString>>,
+    /// The filtered, sorted and grouped rows, `None` to display the rows of the delegate.
+    view_rows: Option<Vec<TableRow>>,
+    /// The index of the displayed row by the row of the delegate, the reverse of the `view_rows`.
+    display_rows: Vec<Option<usize>>,
+    /// The values of the columns to compute the `view_rows`.
+    snapshot: Option<Arc<TableSnapshot>>,
+
     _measure: Vec<Duration>,
     _load_more_task: Task<()>,
+    _view_task: Task<()>,
 }
 
 impl<D> Table<D>
@@ -142,10 +213,10 @@ where
             vertical_scroll_handle: UniformListScrollHandle::new(),
             vertical_scroll_state: ScrollbarState::default(),
             horizontal_scroll_state: ScrollbarState::default(),
-            selection_state: SelectionState::Row,
-            selected_row: None,
+            selection: TableSelection::default(),
             right_clicked_row: None,
-            selected_col: None,
+            editing_cell: None,
+            edit_error: None,
             resizing_col: None,
             bounds: Bounds::default(),
             fixed_head_cols_bounds: Bounds::default(),
@@ -157,11 +228,20 @@ where
             loop_selection: true,
             col_selectable: true,
             row_selectable: true,
+            cell_selectable: false,
             sortable: true,
             col_movable: true,
             col_resizable: true,
             col_fixed: true,
+            filters: Vec::new(),
+            sorts: Vec::new(),
+            group_by: Vec::new(),
+            collapsed_groups: HashSet::default(),
+            view_rows: None,
+            display_rows: vec![],
+            snapshot: None,
             _load_more_task: Task::ready(()),
+            _view_task: Task::ready(()),
             _measure: Vec::new(),
         };
 
@@ -230,6 +310,12 @@ where
         self
     }
 
+    /// Set to select the cells instead of the rows, default false
+    pub fn cell_selectable(mut self, cell_selectable: bool) -> Self {
+        self.cell_selectable = cell_selectable;
+        self
+    }
+
     /// Set the size to the table.
     pub fn set_size(&mut self, size: Size, cx: &mut Context<Self>) {
         self.size = size;
@@ -252,8 +338,312 @@ where
     }
 
     /// When we update columns or rows, we need to refresh the table.
+    ///
+    /// The rows are filtered, sorted and grouped again with the new data.
     pub fn refresh(&mut self, cx: &mut Context<Self>) {
         self.prepare_col_groups(cx);
+        self.snapshot = None;
+        self.update_view(cx);
+    }
+
+    /// Returns the number of the displayed rows, including the group rows.
+    ///
+    /// The row index of the table (the selection, events and the methods of the table) is the
+    /// index of the displayed rows, use [`Table::source_row`] to get the row of the delegate.
+    pub fn rows_count(&self, cx: &App) -> usize {
+        match &self.view_rows {
+            Some(rows) => rows.len(),
+            None => self.delegate.rows_count(cx),
+        }
+    }
+
+    /// Returns the row index of the delegate of the displayed row, `None` for the group row.
+    ///
+    /// They are the same unless the rows are filtered, grouped or sorted by multiple columns.
+    pub fn source_row(&self, row_ix: usize) -> Option<usize> {
+        match &self.view_rows {
+            Some(rows) => match rows.get(row_ix)? {
+                TableRow::Row(source_ix) => Some(*source_ix),
+                TableRow::Group(_) => None,
+            },
+            None => Some(row_ix),
+        }
+    }
+
+    /// Returns the displayed row index of the row of the delegate, `None` if it is filtered out
+    /// or in a collapsed group.
+    pub fn display_row(&self, source_ix: usize) -> Option<usize> {
+        match &self.view_rows {
+            Some(_) => self.display_rows.get(source_ix).copied().flatten(),
+            None => Some(source_ix),
+        }
+    }
+
+    /// Returns the rows of the delegate of the displayed rows, the group rows are skipped.
+    fn source_rows<'a>(
+        &'a self,
+        rows: impl Iterator<Item = usize> + 'a,
+    ) -> impl Iterator<Item = usize> + 'a {
+        rows.filter_map(|row_ix| self.source_row(row_ix))
+    }
+
+    /// Returns the group of the displayed row, `None` if it is not a group row.
+    pub fn group(&self, row_ix: usize) -> Option<&TableGroup> {
+        match self.view_rows.as_ref()?.get(row_ix)? {
+            TableRow::Group(group) => Some(group),
+            TableRow::Row(_) => None,
+        }
+    }
+
+    /// Returns the filter of the column.
+    pub fn filter(&self, col_ix: usize) -> Option<&ColumnFilter> {
+        let key = &self.col_groups.get(col_ix)?.column.key;
+        self.filters
+            .iter()
+            .find(|(filter_key, _)| filter_key == key)
+            .map(|(_, filter)| filter)
+    }
+
+    /// Set the filter of the column, or `None` to remove it.
+    ///
+    /// The rows are filtered on the background, and the selection is cleared.
+    pub fn set_filter(
+        &mut self,
+        col_ix: usize,
+        filter: Option<ColumnFilter>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(col_group) = self.col_groups.get(col_ix) else {
+            return;
+        };
+        let key = col_group.column.key.clone();
+
+        self.filters.retain(|(filter_key, _)| *filter_key != key);
+        if let Some(filter) = filter {
+            self.filters.push((key, filter));
+        }
+        self.cancel_edit(window, cx);
+        self.update_view(cx);
+    }
+
+    /// Returns the columns to group the rows.
+    pub fn group_by(&self) -> Vec<usize> {
+        self.group_by
+            .iter()
+            .filter_map(|key| self.col_ix_of(key))
+            .collect()
+    }
+
+    /// Group the rows by the columns, the first column is the top level group.
+    ///
+    /// The group rows show the value of the column, the number of the rows and the aggregates
+    /// of the columns set by [`Column::aggregate`], click to collapse or expand the group.
+    ///
+    /// Set an empty list to ungroup the rows.
+    pub fn set_group_by(
+        &mut self,
+        cols: impl IntoIterator<Item = usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.group_by = cols
+            .into_iter()
+            .filter_map(|col_ix| Some(self.col_groups.get(col_ix)?.column.key.clone()))
+            .collect();
+        self.collapsed_groups.clear();
+        self.cancel_edit(window, cx);
+        self.update_view(cx);
+    }
+
+    /// Collapse or expand the group of the group row.
+    pub fn toggle_group(&mut self, row_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(path) = self.group(row_ix).map(|group| group.path.clone()) else {
+            return;
+        };
+
+        if !self.collapsed_groups.remove(&path) {
+            self.collapsed_groups.insert(path);
+        }
+        self.cancel_edit(window, cx);
+        self.update_view(cx);
+    }
+
+    fn col_ix_of(&self, key: &SharedString) -> Option<usize> {
+        self.col_groups
+            .iter()
+            .position(|col_group| col_group.column.key == *key)
+    }
+
+    fn query(&self) -> TableQuery {
+        let group_by = self.group_by();
+        let aggregates = if group_by.is_empty() {
+            vec![]
+        } else {
+            self.col_groups
+                .iter()
+                .enumerate()
+                .filter_map(|(col_ix, col_group)| Some((col_ix, col_group.column.aggregate?)))
+                .collect()
+        };
+
+        TableQuery {
+            filters: self
+                .filters
+                .iter()
+                .filter_map(|(key, filter)| Some((self.col_ix_of(key)?, filter.clone())))
+                .collect(),
+            sorts: self
+                .sorts
+                .iter()
+                .filter_map(|(key, sort)| Some((self.col_ix_of(key)?, *sort)))
+                .collect(),
+            group_by,
+            aggregates,
+            collapsed: self.collapsed_groups.clone(),
+        }
+    }
+
+    /// Returns the task to collect the values of the columns to the snapshot, the missing
+    /// values are collected from [`TableDelegate::cell_value`] in chunks, yielding to the event
+    /// loop between them to keep the UI responsive for the large delegates.
+    ///
+    /// Returns `None` if the table is released.
+    fn collect_snapshot(
+        &mut self,
+        columns: Vec<usize>,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Arc<TableSnapshot>>> {
+        cx.spawn(async move |this, cx| loop {
+            let snapshot = this
+                .update(cx, |this, cx| this.collect_snapshot_chunk(&columns, cx))
+                .ok()?;
+            if snapshot.is_some() {
+                return snapshot;
+            }
+            smol::future::yield_now().await;
+        })
+    }
+
+    /// Collect a chunk of the missing values of the columns, returns the snapshot if all the
+    /// values are collected.
+    fn collect_snapshot_chunk(
+        &mut self,
+        columns: &[usize],
+        cx: &App,
+    ) -> Option<Arc<TableSnapshot>> {
+        let rows_count = self.delegate.rows_count(cx);
+        if self
+            .snapshot
+            .as_ref()
+            .map_or(true, |snapshot| snapshot.rows_count != rows_count)
+        {
+            self.snapshot = Some(Arc::new(TableSnapshot {
+                rows_count,
+                ..Default::default()
+            }));
+        }
+        let snapshot = self.snapshot.as_mut()?;
+
+        let Some(col_ix) = columns.iter().copied().find(|col_ix| {
+            snapshot
+                .columns
+                .get(col_ix)
+                .map_or(true, |values| values.len() < rows_count)
+        }) else {
+            return Some(snapshot.clone());
+        };
+
+        let values = Arc::make_mut(snapshot).columns.entry(col_ix).or_default();
+        let start = values.len();
+        let end = (start + SNAPSHOT_CHUNK_SIZE).min(rows_count);
+        values.extend((start..end).map(|row_ix| self.delegate.cell_value(row_ix, col_ix, cx)));
+        None
+    }
+
+    /// Collect the values of the row of the delegate to the snapshot again, after the row is
+    /// edited, instead of collecting all the rows.
+    ///
+    /// The rows not collected yet are collected later by [`Table::collect_snapshot`].
+    fn update_snapshot_row(&mut self, row_ix: usize, cx: &App) {
+        let Some(snapshot) = self.snapshot.as_mut() else {
+            return;
+        };
+        if snapshot.rows_count != self.delegate.rows_count(cx) {
+            self.snapshot = None;
+            return;
+        }
+
+        for (col_ix, values) in Arc::make_mut(snapshot).columns.iter_mut() {
+            if let Some(value) = values.get_mut(row_ix) {
+                *value = self.delegate.cell_value(row_ix, *col_ix, cx);
+            }
+        }
+    }
+
+    /// Filter, sort and group the rows on the background.
+    ///
+    /// The rows of the delegate are displayed directly if there are no filters, groups and
+    /// multi-column sorts.
+    fn update_view(&mut self, cx: &mut Context<Self>) {
+        let query = self.query();
+        if query.is_empty() {
+            self.snapshot = None;
+            self._view_task = Task::ready(());
+            self.display_rows.clear();
+            if self.view_rows.take().is_some() {
+                self.right_clicked_row = None;
+                self.clear_selection(cx);
+            }
+            cx.notify();
+            return;
+        }
+
+        let snapshot = self.collect_snapshot(query.columns(), cx);
+        self._view_task = cx.spawn(async move |this, cx| {
+            let Some(snapshot) = snapshot.await else {
+                return;
+            };
+            let (rows, display_rows) = cx
+                .background_executor()
+                .spawn(async move {
+                    let rows = build_rows(&snapshot, &query);
+                    let display_rows = display_rows(&rows, snapshot.rows_count);
+                    (rows, display_rows)
+                })
+                .await;
+            _ = this.update(cx, |this, cx| {
+                if this.view_rows.as_ref() == Some(&rows) {
+                    return;
+                }
+
+                this.view_rows = Some(rows);
+                this.display_rows = display_rows;
+                this.right_clicked_row = None;
+                this.clear_selection(cx);
+                cx.notify();
+            });
+        });
+    }
+
+    /// Returns the distinct values of the column for the values checklist of the filter.
+    fn column_values(&mut self, col_ix: usize, cx: &mut Context<Self>) -> Task<Vec<SharedString>> {
+        let snapshot = self.collect_snapshot(vec![col_ix], cx);
+        cx.spawn(async move |_, cx| {
+            let Some(snapshot) = snapshot.await else {
+                return vec![];
+            };
+            cx.background_executor()
+                .spawn(async move {
+                    let values = snapshot
+                        .columns
+                        .get(&col_ix)
+                        .map(|values| values.as_slice())
+                        .unwrap_or_default();
+                    distinct_values(values, FILTER_VALUES_LIMIT)
+                })
+                .await
+        })
     }
 
     fn prepare_col_groups(&mut self, cx: &mut Context<Self>) {
@@ -297,46 +687,136 @@ where
         cx.notify();
     }
 
-    /// Returns the selected row index.
+    /// Returns the selected row index, the row of the last click or keyboard move.
     pub fn selected_row(&self) -> Option<usize> {
-        self.selected_row
+        self.selection.active_row()
     }
 
     /// Sets the selected row to the given index.
     pub fn set_selected_row(&mut self, row_ix: usize, cx: &mut Context<Self>) {
-        self.selection_state = SelectionState::Row;
         self.right_clicked_row = None;
-        self.selected_row = Some(row_ix);
-        if let Some(row_ix) = self.selected_row {
-            self.vertical_scroll_handle
-                .scroll_to_item(row_ix, ScrollStrategy::Top);
+        if !self.update_selection(|selection| selection.select_row(row_ix), cx) {
+            return;
         }
+
+        self.vertical_scroll_handle
+            .scroll_to_item(row_ix, ScrollStrategy::Top);
         cx.emit(TableEvent::SelectRow(row_ix));
-        cx.notify();
     }
 
-    /// Returns the selected column index.
+    /// Returns the selected column index, or the column of the selected cell.
     pub fn selected_col(&self) -> Option<usize> {
-        self.selected_col
+        self.selection
+            .col()
+            .or_else(|| self.selection.cells().map(|cells| cells.head.col))
     }
 
     /// Sets the selected col to the given index.
     pub fn set_selected_col(&mut self, col_ix: usize, cx: &mut Context<Self>) {
-        self.selection_state = SelectionState::Column;
-        self.selected_col = Some(col_ix);
-        if let Some(col_ix) = self.selected_col {
-            self.scroll_to_col(col_ix, cx);
+        if !self.update_selection(|selection| selection.select_col(col_ix), cx) {
+            return;
         }
+
+        self.scroll_to_col(col_ix, cx);
         cx.emit(TableEvent::SelectColumn(col_ix));
-        cx.notify();
+    }
+
+    /// Sets the selected cell, the selection of the rows and column is cleared.
+    pub fn set_selected_cell(&mut self, cell: TableCell, cx: &mut Context<Self>) {
+        if !self.update_selection(|selection| selection.select_cell(cell), cx) {
+            return;
+        }
+
+        self.vertical_scroll_handle
+            .scroll_to_item(cell.row, ScrollStrategy::Top);
+        self.scroll_to_col(cell.col, cx);
+    }
+
+    /// Returns the selection of the table.
+    pub fn selection(&self) -> &TableSelection {
+        &self.selection
+    }
+
+    /// Sets the selection of the table.
+    pub fn set_selection(&mut self, selection: TableSelection, cx: &mut Context<Self>) {
+        self.update_selection(|current| *current = selection, cx);
+    }
+
+    /// Select all the rows, or all the cells if `cell_selectable` is true.
+    pub fn select_all(&mut self, cx: &mut Context<Self>) {
+        let rows_count = self.rows_count(cx);
+        let columns_count = self.delegate.columns_count(cx);
+        if rows_count == 0 {
+            return;
+        }
+
+        if self.cell_selectable {
+            if columns_count == 0 {
+                return;
+            }
+
+            self.update_selection(
+                |selection| {
+                    selection.select_cell(TableCell::new(0, 0));
+                    selection.extend_cells_to(TableCell::new(rows_count - 1, columns_count - 1));
+                },
+                cx,
+            );
+        } else if self.row_selectable {
+            self.update_selection(|selection| selection.select_all_rows(rows_count), cx);
+        }
     }
 
     /// Clear the selection of the table.
     pub fn clear_selection(&mut self, cx: &mut Context<Self>) {
-        self.selection_state = SelectionState::Row;
-        self.selected_row = None;
-        self.selected_col = None;
+        self.update_selection(|selection| selection.clear(), cx);
+    }
+
+    /// Returns the text of the selection in TSV format, by [`TableDelegate::cell_text`].
+    ///
+    /// The selected rows have all the columns, and the selected column has all the rows,
+    /// the group rows are skipped.
+    pub fn selection_text(&self, cx: &App) -> String {
+        let cell_text = |source_ix, col_ix| self.delegate.cell_text(source_ix, col_ix, cx);
+
+        if let Some(cells) = self.selection.cells() {
+            let cols = cells.cols().collect::<Vec<_>>();
+            to_tsv(self.source_rows(cells.rows()), &cols, cell_text)
+        } else if self.selection.rows_count() > 0 {
+            let cols = (0..self.delegate.columns_count(cx)).collect::<Vec<_>>();
+            to_tsv(self.source_rows(self.selection.rows()), &cols, cell_text)
+        } else if let Some(col_ix) = self.selection.col() {
+            to_tsv(
+                self.source_rows(0..self.rows_count(cx)),
+                &[col_ix],
+                cell_text,
+            )
+        } else {
+            String::new()
+        }
+    }
+
+    /// Apply the change to a copy of the selection, then let the delegate veto or customize it.
+    ///
+    /// Returns false if the change is vetoed.
+    fn update_selection(
+        &mut self,
+        f: impl FnOnce(&mut TableSelection),
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let mut selection = self.selection.clone();
+        f(&mut selection);
+
+        let Some(selection) = self.delegate.will_change_selection(selection, cx) else {
+            return false;
+        };
+
+        if selection != self.selection {
+            self.selection = selection;
+            cx.emit(TableEvent::SelectionChanged(self.selection.clone()));
+        }
         cx.notify();
+        true
     }
 
     /// Returns the visible range of the rows and columns.
@@ -348,13 +828,34 @@ where
         &mut self,
         ev: &MouseDownEvent,
         row_ix: usize,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        // The editing cell is committed by `on_cell_click` first, keep the selection if rejected.
+        if self.editing_cell.is_some() {
+            return;
+        }
+
+        if ev.button == MouseButton::Left && self.group(row_ix).is_some() {
+            self.toggle_group(row_ix, window, cx);
+            return;
+        }
+
         if ev.button == MouseButton::Right {
             self.right_clicked_row = Some(row_ix);
         } else {
-            self.set_selected_row(row_ix, cx);
+            // The cells are selected by `on_cell_click`.
+            if !self.cell_selectable {
+                if ev.modifiers.shift {
+                    let keep = ev.modifiers.secondary();
+                    self.update_selection(|selection| selection.extend_rows_to(row_ix, keep), cx);
+                } else if ev.modifiers.secondary() {
+                    self.right_clicked_row = None;
+                    self.update_selection(|selection| selection.toggle_row(row_ix), cx);
+                } else {
+                    self.set_selected_row(row_ix, cx);
+                }
+            }
 
             if ev.click_count == 2 {
                 cx.emit(TableEvent::DoubleClickedRow(row_ix));
@@ -362,6 +863,35 @@ where
         }
     }
 
+    fn on_cell_click(
+        &mut self,
+        ev: &MouseDownEvent,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cell = TableCell::new(row_ix, col_ix);
+        if self.is_editing_cell(cell) || !self.commit_edit(window, cx) {
+            return;
+        }
+        if self.group(row_ix).is_some() {
+            return;
+        }
+
+        if self.cell_selectable {
+            if ev.modifiers.shift {
+                self.update_selection(|selection| selection.extend_cells_to(cell), cx);
+            } else {
+                self.update_selection(|selection| selection.select_cell(cell), cx);
+            }
+        }
+
+        if ev.click_count == 2 {
+            self.edit_cell(cell, window, cx);
+        }
+    }
+
     fn on_col_head_click(&mut self, col_ix: usize, _: &mut Window, cx: &mut Context<Self>) {
         if !self.col_selectable {
             return;
@@ -379,10 +909,14 @@ where
     }
 
     fn has_selection(&self) -> bool {
-        self.selected_row.is_some() || self.selected_col.is_some()
+        !self.selection.is_empty()
     }
 
-    fn action_cancel(&mut self, _: &Cancel, _: &mut Window, cx: &mut Context<Self>) {
+    fn action_cancel(&mut self, _: &Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        if self.editing_cell.is_some() {
+            self.cancel_edit(window, cx);
+            return;
+        }
         if self.has_selection() {
             self.clear_selection(cx);
             return;
@@ -391,12 +925,17 @@ where
     }
 
     fn action_select_prev(&mut self, _: &SelectPrev, _: &mut Window, cx: &mut Context<Self>) {
-        let rows_count = self.delegate.rows_count(cx);
+        if self.cell_selectable {
+            self.move_selected_cell(-1, 0, false, cx);
+            return;
+        }
+
+        let rows_count = self.rows_count(cx);
         if rows_count < 1 {
             return;
         }
 
-        let mut selected_row = self.selected_row.unwrap_or(0);
+        let mut selected_row = self.selected_row().unwrap_or(0);
         if selected_row > 0 {
             selected_row = selected_row.saturating_sub(1);
         } else {
@@ -409,12 +948,17 @@ where
     }
 
     fn action_select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
-        let rows_count = self.delegate.rows_count(cx);
+        if self.cell_selectable {
+            self.move_selected_cell(1, 0, false, cx);
+            return;
+        }
+
+        let rows_count = self.rows_count(cx);
         if rows_count < 1 {
             return;
         }
 
-        let selected_row = match self.selected_row {
+        let selected_row = match self.selected_row() {
             Some(selected_row) if selected_row < rows_count.saturating_sub(1) => selected_row + 1,
             Some(selected_row) => {
                 if self.loop_selection {
@@ -435,7 +979,12 @@ where
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut selected_col = self.selected_col.unwrap_or(0);
+        if self.cell_selectable {
+            self.move_selected_cell(0, -1, false, cx);
+            return;
+        }
+
+        let mut selected_col = self.selected_col().unwrap_or(0);
         let columns_count = self.delegate.columns_count(cx);
         if selected_col > 0 {
             selected_col = selected_col.saturating_sub(1);
@@ -453,7 +1002,12 @@ where
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut selected_col = self.selected_col.unwrap_or(0);
+        if self.cell_selectable {
+            self.move_selected_cell(0, 1, false, cx);
+            return;
+        }
+
+        let mut selected_col = self.selected_col().unwrap_or(0);
         if selected_col < self.delegate.columns_count(cx).saturating_sub(1) {
             selected_col += 1;
         } else {
@@ -465,6 +1019,122 @@ where
         self.set_selected_col(selected_col, cx);
     }
 
+    fn action_select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cell_selectable {
+            self.move_selected_cell(-1, 0, true, cx);
+        } else {
+            self.extend_selected_rows(false, cx);
+        }
+    }
+
+    fn action_select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cell_selectable {
+            self.move_selected_cell(1, 0, true, cx);
+        } else {
+            self.extend_selected_rows(true, cx);
+        }
+    }
+
+    fn action_select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cell_selectable {
+            self.move_selected_cell(0, -1, true, cx);
+        } else {
+            cx.propagate();
+        }
+    }
+
+    fn action_select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cell_selectable {
+            self.move_selected_cell(0, 1, true, cx);
+        } else {
+            cx.propagate();
+        }
+    }
+
+    fn action_select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_all(cx);
+    }
+
+    fn action_copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.has_selection() {
+            cx.propagate();
+            return;
+        }
+
+        let text = self.selection_text(cx);
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    /// Move the head of the selected cells by the given rows and columns,
+    /// or extend the range of cells if `extend` is true.
+    fn move_selected_cell(
+        &mut self,
+        row_delta: isize,
+        col_delta: isize,
+        extend: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let rows_count = self.rows_count(cx);
+        let columns_count = self.delegate.columns_count(cx);
+        if rows_count == 0 || columns_count == 0 {
+            return;
+        }
+
+        let head = self.selection.cells().map(|cells| cells.head);
+        let from = head.unwrap_or(TableCell::new(self.selected_row().unwrap_or(0), 0));
+        let cell = if head.is_none() {
+            from
+        } else {
+            TableCell::new(
+                from.row
+                    .saturating_add_signed(row_delta)
+                    .min(rows_count - 1),
+                from.col
+                    .saturating_add_signed(col_delta)
+                    .min(columns_count - 1),
+            )
+        };
+
+        let changed = self.update_selection(
+            |selection| {
+                if extend {
+                    if selection.cells().is_none() {
+                        selection.select_cell(from);
+                    }
+                    selection.extend_cells_to(cell);
+                } else {
+                    selection.select_cell(cell);
+                }
+            },
+            cx,
+        );
+
+        if changed {
+            self.vertical_scroll_handle
+                .scroll_to_item(cell.row, ScrollStrategy::Top);
+            self.scroll_to_col(cell.col, cx);
+        }
+    }
+
+    /// Extend the selected rows from the anchor row to the previous or next row of the selected row.
+    fn extend_selected_rows(&mut self, down: bool, cx: &mut Context<Self>) {
+        let rows_count = self.rows_count(cx);
+        if rows_count == 0 || !self.row_selectable {
+            return;
+        }
+
+        let row_ix = match self.selected_row() {
+            Some(row_ix) if down => (row_ix + 1).min(rows_count - 1),
+            Some(row_ix) => row_ix.saturating_sub(1),
+            None => 0,
+        };
+
+        if self.update_selection(|selection| selection.extend_rows_to(row_ix, false), cx) {
+            self.vertical_scroll_handle
+                .scroll_to_item(row_ix, ScrollStrategy::Top);
+        }
+    }
+
     /// Scroll table when mouse position is near the edge of the table bounds.
     fn scroll_table_by_col_resizing(
         &mut self,
@@ -554,9 +1224,60 @@ where
 
         self.delegate_mut().perform_sort(col_ix, sort, window, cx);
 
+        // The rows of the delegate are sorted, filter and group them again.
+        self.sorts.clear();
+        self.snapshot = None;
+        self.update_view(cx);
         cx.notify();
     }
 
+    /// Add the column to the sorts or change the sort of it by shift-click the sort icon,
+    /// the rows are sorted by the columns in the order they were added, on the background.
+    fn perform_multi_sort(&mut self, col_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.sortable {
+            return;
+        }
+
+        let Some(column) = self.col_groups.get(col_ix).map(|g| g.column.clone()) else {
+            return;
+        };
+        let Some(sort) = column.sort else {
+            return;
+        };
+
+        // Start with the column sorted by the delegate.
+        if self.sorts.is_empty() {
+            self.sorts = self
+                .col_groups
+                .iter()
+                .filter_map(|col_group| match col_group.column.sort {
+                    Some(ColumnSort::Default) | None => None,
+                    Some(sort) => Some((col_group.column.key.clone(), sort)),
+                })
+                .collect();
+        }
+
+        let sort = match sort {
+            ColumnSort::Ascending => ColumnSort::Default,
+            ColumnSort::Descending => ColumnSort::Ascending,
+            ColumnSort::Default => ColumnSort::Descending,
+        };
+        self.col_groups[col_ix].column.sort = Some(sort);
+
+        let ix = self.sorts.iter().position(|(key, _)| *key == column.key);
+        match (ix, sort) {
+            (Some(ix), ColumnSort::Default) => {
+                self.sorts.remove(ix);
+            }
+            (Some(ix), sort) => self.sorts[ix].1 = sort,
+            (None, ColumnSort::Default) => {}
+            (None, sort) => self.sorts.push((column.key, sort)),
+        }
+
+        self.cancel_edit(window, cx);
+        self.update_view(cx);
+    }
+
     fn move_column(
         &mut self,
         col_ix: usize,
@@ -572,6 +1293,11 @@ where
         let col_group = self.col_groups.remove(col_ix);
         self.col_groups.insert(to_ix, col_group);
 
+        // The values of the snapshot are by the column index.
+        if self.snapshot.take().is_some() {
+            self.update_view(cx);
+        }
+
         cx.emit(TableEvent::MoveColumn(col_ix, to_ix));
         cx.notify();
     }
@@ -654,8 +1380,15 @@ where
             })
     }
 
-    /// Show Column selection style, when the column is selected and the selection state is Column.
-    fn render_col_wrap(&self, col_ix: usize, _: &mut Window, cx: &mut Context<Self>) -> Div {
+    /// Show Column selection style when the column is selected,
+    /// and the cell selection style when the cell is in the selected cells.
+    fn render_col_wrap(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Div {
         let el = h_flex().h_full();
         let selectable = self.col_selectable
             && self
@@ -663,15 +1396,31 @@ where
                 .get(col_ix)
                 .map(|col_group| col_group.column.selectable)
                 .unwrap_or(false);
-
-        if selectable
-            && self.selected_col == Some(col_ix)
-            && self.selection_state == SelectionState::Column
-        {
-            el.bg(cx.theme().table_active)
-        } else {
-            el
-        }
+        let is_head_cell = self
+            .selection
+            .cells()
+            .map_or(false, |cells| cells.head == TableCell::new(row_ix, col_ix));
+
+        el.when(
+            (selectable && self.selection.col() == Some(col_ix))
+                || self.selection.contains_cell(row_ix, col_ix),
+            |this| this.bg(cx.theme().table_active),
+        )
+        .when(is_head_cell, |this| {
+            this.relative().child(
+                div()
+                    .absolute()
+                    .inset_0()
+                    .border_1()
+                    .border_color(cx.theme().table_active_border),
+            )
+        })
+        .on_mouse_down(
+            MouseButton::Left,
+            cx.listener(move |this, ev, window, cx| {
+                this.on_cell_click(ev, row_ix, col_ix, window, cx);
+            }),
+        )
     }
 
     fn render_vertical_scrollbar(
@@ -834,9 +1583,17 @@ where
             ColumnSort::Descending => (IconName::SortDescending, true),
             ColumnSort::Default => (IconName::ChevronsUpDown, false),
         };
+        // Show the priority of the multi-column sorts.
+        let priority = if self.sorts.len() > 1 {
+            self.sorts
+                .iter()
+                .position(|(key, _)| *key == col_group.column.key)
+        } else {
+            None
+        };
 
         Some(
-            div()
+            h_flex()
                 .id(("icon-sort", col_ix))
                 .p(px(2.))
                 .rounded(cx.theme().radius / 2.)
@@ -846,14 +1603,74 @@ where
                 })
                 .hover(|this| this.bg(cx.theme().secondary).opacity(7.))
                 .active(|this| this.bg(cx.theme().secondary_active).opacity(1.))
-                .on_click(
-                    cx.listener(move |table, _, window, cx| table.perform_sort(col_ix, window, cx)),
-                )
+                .on_click(cx.listener(move |table, ev: &ClickEvent, window, cx| {
+                    if ev.modifiers().shift {
+                        table.perform_multi_sort(col_ix, window, cx)
+                    } else {
+                        table.perform_sort(col_ix, window, cx)
+                    }
+                }))
                 .child(
                     Icon::new(icon)
                         .size_3()
                         .text_color(cx.theme().secondary_foreground),
-                ),
+                )
+                .when_some(priority, |this, ix| {
+                    this.child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().secondary_foreground)
+                            .child((ix + 1).to_string()),
+                    )
+                }),
+        )
+    }
+
+    fn render_filter_button(
+        &self,
+        col_ix: usize,
+        col_group: &ColGroup,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let kind = col_group.column.filter?;
+        let filter = self.filter(col_ix).cloned();
+        let view = cx.entity().clone();
+
+        Some(
+            Popover::new(("filter-popover", col_ix))
+                .anchor(Corner::TopRight)
+                .trigger(
+                    Button::new(("filter", col_ix))
+                        .ghost()
+                        .xsmall()
+                        .icon(IconName::Filter)
+                        .selected(filter.is_some()),
+                )
+                .content(move |window, cx| {
+                    let values = match kind {
+                        ColumnFilterKind::Values => {
+                            view.update(cx, |table, cx| table.column_values(col_ix, cx))
+                        }
+                        _ => Task::ready(vec![]),
+                    };
+                    let view = view.clone();
+
+                    cx.new(|cx| {
+                        ColumnFilterPanel::new(
+                            kind,
+                            filter.clone(),
+                            values,
+                            move |filter, window, cx| {
+                                view.update(cx, |table, cx| {
+                                    table.set_filter(col_ix, filter, window, cx);
+                                })
+                            },
+                            window,
+                            cx,
+                        )
+                    })
+                }),
         )
     }
 
@@ -897,7 +1714,16 @@ where
                                     self.size.table_cell_padding().right - paddings.right;
                                 this.pr(offset_pr.max(px(0.)))
                             })
-                            .children(self.render_sort_icon(col_ix, &col_group, window, cx)),
+                            .child(
+                                h_flex()
+                                    .gap_0p5()
+                                    .children(
+                                        self.render_filter_button(col_ix, &col_group, window, cx),
+                                    )
+                                    .children(
+                                        self.render_sort_icon(col_ix, &col_group, window, cx),
+                                    ),
+                            ),
                     )
                     .when(movable, |this| {
                         this.on_drag(
@@ -1046,7 +1872,7 @@ where
     ) -> impl IntoElement {
         let horizontal_scroll_handle = self.horizontal_scroll_handle.clone();
         let is_stripe_row = self.stripe && row_ix % 2 != 0;
-        let is_selected = self.selected_row == Some(row_ix);
+        let is_selected = self.selection.contains_row(row_ix);
         let view = cx.entity().clone();
 
         if row_ix < rows_count {
@@ -1064,7 +1890,13 @@ where
                 true
             };
 
-            let mut tr = self.delegate.render_tr(row_ix, window, cx);
+            let mut tr = match self.source_row(row_ix) {
+                Some(source_ix) => self.delegate.render_tr(source_ix, window, cx),
+                None => h_flex()
+                    .id(("group-row", row_ix))
+                    .bg(cx.theme().table_head)
+                    .font_medium(),
+            };
             let style = tr.style().clone();
 
             tr.h_flex()
@@ -1092,11 +1924,13 @@ where
                                 let mut items = Vec::with_capacity(left_columns_count);
 
                                 (0..left_columns_count).for_each(|col_ix| {
-                                    items.push(self.render_col_wrap(col_ix, window, cx).child(
-                                        self.render_cell(col_ix, window, cx).child(
-                                            self.measure_render_td(row_ix, col_ix, window, cx),
+                                    items.push(
+                                        self.render_col_wrap(row_ix, col_ix, window, cx).child(
+                                            self.render_cell(col_ix, window, cx).child(
+                                                self.measure_render_td(row_ix, col_ix, window, cx),
+                                            ),
                                         ),
-                                    ));
+                                    );
                                 });
 
                                 items
@@ -1142,8 +1976,9 @@ where
 
                                         visible_range.for_each(|col_ix| {
                                             let col_ix = col_ix + left_columns_count;
-                                            let el =
-                                                table.render_col_wrap(col_ix, window, cx).child(
+                                            let el = table
+                                                .render_col_wrap(row_ix, col_ix, window, cx)
+                                                .child(
                                                     table.render_cell(col_ix, window, cx).child(
                                                         table.measure_render_td(
                                                             row_ix, col_ix, window, cx,
@@ -1163,22 +1998,17 @@ where
                         .child(self.delegate.render_last_empty_col(window, cx)),
                 )
                 // Row selected style
-                .when_some(self.selected_row, |this, _| {
-                    this.when(
-                        is_selected && self.selection_state == SelectionState::Row,
-                        |this| {
-                            this.border_color(gpui::transparent_white()).child(
-                                div()
-                                    .top(if row_ix == 0 { px(0.) } else { px(-1.) })
-                                    .left(px(0.))
-                                    .right(px(0.))
-                                    .bottom(px(-1.))
-                                    .absolute()
-                                    .bg(cx.theme().table_active)
-                                    .border_1()
-                                    .border_color(cx.theme().table_active_border),
-                            )
-                        },
+                .when(is_selected, |this| {
+                    this.border_color(gpui::transparent_white()).child(
+                        div()
+                            .top(if row_ix == 0 { px(0.) } else { px(-1.) })
+                            .left(px(0.))
+                            .right(px(0.))
+                            .bottom(px(-1.))
+                            .absolute()
+                            .bg(cx.theme().table_active)
+                            .border_1()
+                            .border_color(cx.theme().table_active_border),
                     )
                 })
                 // Row right click row style
@@ -1257,19 +2087,80 @@ where
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
+        let Some(source_ix) = self.source_row(row_ix) else {
+            return self.render_group_td(row_ix, col_ix, cx);
+        };
+
+        if self.editing_cell == Some(TableCell::new(source_ix, col_ix)) {
+            return self
+                .render_cell_editor(source_ix, col_ix, window, cx)
+                .into_any_element();
+        }
+
         if !crate::measure_enable() {
             return self
                 .delegate
-                .render_td(row_ix, col_ix, window, cx)
+                .render_td(source_ix, col_ix, window, cx)
                 .into_any_element();
         }
 
         let start = std::time::Instant::now();
-        let el = self.delegate.render_td(row_ix, col_ix, window, cx);
+        let el = self.delegate.render_td(source_ix, col_ix, window, cx);
         self._measure.push(start.elapsed());
         el.into_any_element()
     }
 
+    /// Render the cell of the group row, the first column shows the value of the group and
+    /// the number of the rows, and the other columns show the aggregates.
+    fn render_group_td(&self, row_ix: usize, col_ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let Some(group) = self.group(row_ix) else {
+            return div().into_any_element();
+        };
+
+        if col_ix == 0 {
+            let name = self
+                .col_groups
+                .get(group.col_ix)
+                .map(|col_group| col_group.column.name.clone())
+                .unwrap_or_default();
+            let icon = if group.collapsed {
+                IconName::ChevronRight
+            } else {
+                IconName::ChevronDown
+            };
+
+            return h_flex()
+                .size_full()
+                .gap_1()
+                .pl(px(16.) * group.depth() as f32)
+                .child(Icon::new(icon).size_3())
+                .child(format!("{}: {} ({})", name, group.value, group.rows_count))
+                .into_any_element();
+        }
+
+        let aggregate = self
+            .col_groups
+            .get(col_ix)
+            .and_then(|col_group| col_group.column.aggregate);
+        let value = group
+            .aggregates
+            .iter()
+            .find(|(agg_col_ix, _)| *agg_col_ix == col_ix)
+            .map(|(_, value)| value);
+        let (Some(aggregate), Some(value)) = (aggregate, value) else {
+            return div().into_any_element();
+        };
+
+        let value = match value {
+            CellValue::Number(number) if number.fract() != 0. => format!("{:.2}", number),
+            value => value.to_string(),
+        };
+        div()
+            .size_full()
+            .child(format!("{}: {}", aggregate.label(), value))
+            .into_any_element()
+    }
+
     fn measure(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
         if !crate::measure_enable() {
             return;
@@ -1328,7 +2219,7 @@ where
             .iter()
             .filter(|col| self.col_fixed && col.column.fixed == Some(ColumnFixed::Left))
             .count();
-        let rows_count = self.delegate.rows_count(cx);
+        let rows_count = self.rows_count(cx);
         let loading = self.delegate.loading(cx);
         let extra_rows_count = self.calculate_extra_rows_needed(rows_count);
         let render_rows_count = if self.stripe {
@@ -1342,20 +2233,34 @@ where
             .id("table")
             .track_focus(&self.focus_handle)
             .on_action(cx.listener(Self::action_cancel))
-            .on_action(cx.listener(Self::action_select_next))
-            .on_action(cx.listener(Self::action_select_prev))
-            .on_action(cx.listener(Self::action_select_next_col))
-            .on_action(cx.listener(Self::action_select_prev_col))
+            .on_action(cx.listener(Self::action_confirm))
+            .on_action(cx.listener(Self::action_edit_next_cell))
+            .on_action(cx.listener(Self::action_edit_prev_cell))
+            // The keys are handled by the editor in editing.
+            .when(self.editing_cell.is_none(), |this| {
+                this.on_action(cx.listener(Self::action_select_next))
+                    .on_action(cx.listener(Self::action_select_prev))
+                    .on_action(cx.listener(Self::action_select_next_col))
+                    .on_action(cx.listener(Self::action_select_prev_col))
+                    .on_action(cx.listener(Self::action_select_up))
+                    .on_action(cx.listener(Self::action_select_down))
+                    .on_action(cx.listener(Self::action_select_left))
+                    .on_action(cx.listener(Self::action_select_right))
+                    .on_action(cx.listener(Self::action_select_all))
+                    .on_action(cx.listener(Self::action_copy))
+            })
             .size_full()
             .overflow_hidden()
             .child(self.render_table_head(left_columns_count, window, cx))
             .context_menu({
                 let view = view.clone();
                 move |this, window: &mut Window, cx: &mut Context<PopupMenu>| {
-                    if let Some(row_ix) = view.read(cx).right_clicked_row {
-                        view.read(cx)
-                            .delegate
-                            .context_menu(row_ix, this, window, cx)
+                    let table = view.read(cx);
+                    let source_ix = table
+                        .right_clicked_row
+                        .and_then(|row_ix| table.source_row(row_ix));
+                    if let Some(source_ix) = source_ix {
+                        table.delegate.context_menu(source_ix, this, window, cx)
                     } else {
                         this
                     }
@@ -1489,3 +2394,90 @@ where
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use gpui::TestAppContext;
+
+    use super::*;
+
+    /// A delegate of many rows, the value of the cell is the row index.
+    struct LargeDelegate {
+        column: Column,
+        rows_count: usize,
+        /// The number of the calls of `cell_value`.
+        reads: Cell<usize>,
+    }
+
+    impl TableDelegate for LargeDelegate {
+        fn columns_count(&self, _: &App) -> usize {
+            1
+        }
+
+        fn rows_count(&self, _: &App) -> usize {
+            self.rows_count
+        }
+
+        fn column(&self, _: usize, _: &App) -> &Column {
+            &self.column
+        }
+
+        fn render_td(
+            &self,
+            row_ix: usize,
+            _: usize,
+            _: &mut Window,
+            _: &mut Context<Table<Self>>,
+        ) -> impl IntoElement {
+            row_ix.to_string()
+        }
+
+        fn cell_value(&self, row_ix: usize, _: usize, _: &App) -> CellValue {
+            self.reads.set(self.reads.get() + 1);
+            CellValue::from(row_ix as f64)
+        }
+    }
+
+    #[gpui::test]
+    fn test_filter_large_delegate(cx: &mut TestAppContext) {
+        cx.update(crate::init);
+        let rows_count = SNAPSHOT_CHUNK_SIZE * 5;
+        let (table, cx) = cx.add_window_view(|window, cx| {
+            let delegate = LargeDelegate {
+                column: Column::new("number", "Number"),
+                rows_count,
+                reads: Cell::new(0),
+            };
+            Table::new(delegate, window, cx)
+        });
+
+        let filter = ColumnFilter::Range {
+            min: Some((rows_count - 10) as f64),
+            max: None,
+        };
+        table.update_in(cx, |table, window, cx| {
+            table.set_filter(0, Some(filter), window, cx);
+            // Nothing is collected until the event loop runs the task.
+            assert_eq!(table.delegate().reads.get(), 0);
+            assert_eq!(table.rows_count(cx), rows_count);
+        });
+
+        // The values are collected in chunks, a chunk at most in a tick of the event loop.
+        cx.executor().tick();
+        table.read_with(cx, |table, cx| {
+            assert!(table.delegate().reads.get() <= SNAPSHOT_CHUNK_SIZE);
+            assert_eq!(table.rows_count(cx), rows_count);
+        });
+
+        cx.run_until_parked();
+        table.read_with(cx, |table, cx| {
+            assert_eq!(table.delegate().reads.get(), rows_count);
+            assert_eq!(table.rows_count(cx), 10);
+            assert_eq!(table.source_row(0), Some(rows_count - 10));
+            assert_eq!(table.display_row(rows_count - 1), Some(9));
+            assert_eq!(table.display_row(0), None);
+        });
+    }
+}
@@ -0,0 +1,305 @@
+use std::ops::Range;
+
+/// The position of a cell in the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TableCell {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl TableCell {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+}
+
+/// A rectangular range of cells, from the `anchor` cell (where the selection started) to the
+/// `head` cell (where the selection moved to).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRange {
+    pub anchor: TableCell,
+    pub head: TableCell,
+}
+
+impl CellRange {
+    /// Returns the rows of the range.
+    pub fn rows(&self) -> Range<usize> {
+        self.anchor.row.min(self.head.row)..self.anchor.row.max(self.head.row) + 1
+    }
+
+    /// Returns the columns of the range.
+    pub fn cols(&self) -> Range<usize> {
+        self.anchor.col.min(self.head.col)..self.anchor.col.max(self.head.col) + 1
+    }
+
+    /// Returns true if the cell is in the range.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.rows().contains(&row) && self.cols().contains(&col)
+    }
+}
+
+/// The selection of the [`crate::table::Table`].
+///
+/// The selection is one of the selected rows, a rectangular range of cells or a column.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableSelection {
+    /// The selected rows, sorted and not overlapped.
+    rows: Vec<Range<usize>>,
+    /// The row to start the range selection, for shift-click and shift-arrow.
+    anchor_row: Option<usize>,
+    /// The row of the last click or keyboard move.
+    active_row: Option<usize>,
+    cells: Option<CellRange>,
+    col: Option<usize>,
+}
+
+impl TableSelection {
+    /// Returns true if nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && self.cells.is_none() && self.col.is_none()
+    }
+
+    /// Returns the ranges of the selected rows, sorted and not overlapped.
+    pub fn row_ranges(&self) -> &[Range<usize>] {
+        &self.rows
+    }
+
+    /// Returns the selected rows in order.
+    pub fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rows.iter().flat_map(|range| range.clone())
+    }
+
+    /// Returns the number of the selected rows.
+    pub fn rows_count(&self) -> usize {
+        self.rows.iter().map(|range| range.len()).sum()
+    }
+
+    /// Returns true if the row is selected.
+    pub fn contains_row(&self, row: usize) -> bool {
+        let ix = self.rows.partition_point(|range| range.end <= row);
+        self.rows
+            .get(ix)
+            .map_or(false, |range| range.contains(&row))
+    }
+
+    /// Returns the row of the last click or keyboard move.
+    pub fn active_row(&self) -> Option<usize> {
+        self.active_row
+    }
+
+    /// Returns the selected range of cells.
+    pub fn cells(&self) -> Option<CellRange> {
+        self.cells
+    }
+
+    /// Returns true if the cell is in the selected range of cells.
+    pub fn contains_cell(&self, row: usize, col: usize) -> bool {
+        self.cells.map_or(false, |cells| cells.contains(row, col))
+    }
+
+    /// Returns the selected column.
+    pub fn col(&self) -> Option<usize> {
+        self.col
+    }
+
+    /// Clear the selection.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Select only the row.
+    pub fn select_row(&mut self, row: usize) {
+        self.clear();
+        self.rows = vec![row..row + 1];
+        self.anchor_row = Some(row);
+        self.active_row = Some(row);
+    }
+
+    /// Add the row to the selection if it is not selected, otherwise remove it.
+    pub fn toggle_row(&mut self, row: usize) {
+        self.cells = None;
+        self.col = None;
+        if self.contains_row(row) {
+            remove_row(&mut self.rows, row);
+        } else {
+            insert_rows(&mut self.rows, row..row + 1);
+        }
+        self.anchor_row = Some(row);
+        self.active_row = Some(row);
+    }
+
+    /// Select the rows from the anchor row to the row, keep the anchor row.
+    ///
+    /// If `keep` is true, the rows selected before are kept, like the shift-click with ctrl.
+    pub fn extend_rows_to(&mut self, row: usize, keep: bool) {
+        let anchor = self.anchor_row.unwrap_or(row);
+        let rows = std::mem::take(&mut self.rows);
+        self.clear();
+        if keep {
+            self.rows = rows;
+        }
+        insert_rows(&mut self.rows, anchor.min(row)..anchor.max(row) + 1);
+        self.anchor_row = Some(anchor);
+        self.active_row = Some(row);
+    }
+
+    /// Select all the rows.
+    pub fn select_all_rows(&mut self, rows_count: usize) {
+        let active_row = self.active_row;
+        self.clear();
+        if rows_count > 0 {
+            self.rows = vec![0..rows_count];
+            self.anchor_row = Some(0);
+            self.active_row = active_row.filter(|row| *row < rows_count).or(Some(0));
+        }
+    }
+
+    /// Select only the cell.
+    pub fn select_cell(&mut self, cell: TableCell) {
+        self.clear();
+        self.cells = Some(CellRange {
+            anchor: cell,
+            head: cell,
+        });
+        self.active_row = Some(cell.row);
+    }
+
+    /// Select the range of cells from the anchor cell to the cell.
+    pub fn extend_cells_to(&mut self, cell: TableCell) {
+        let anchor = self.cells.map_or(cell, |cells| cells.anchor);
+        self.clear();
+        self.cells = Some(CellRange { anchor, head: cell });
+        self.active_row = Some(cell.row);
+    }
+
+    /// Select only the column, the active row is kept for the keyboard navigation.
+    pub fn select_col(&mut self, col: usize) {
+        let active_row = self.active_row;
+        self.clear();
+        self.col = Some(col);
+        self.active_row = active_row;
+    }
+}
+
+/// Insert the range to the sorted and not overlapped ranges, merge the overlapped or adjacent ranges.
+fn insert_rows(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    let start_ix = ranges.partition_point(|r| r.end < range.start);
+    let end_ix = ranges.partition_point(|r| r.start <= range.end);
+
+    let mut merged = range;
+    if start_ix < end_ix {
+        merged.start = merged.start.min(ranges[start_ix].start);
+        merged.end = merged.end.max(ranges[end_ix - 1].end);
+    }
+    ranges.splice(start_ix..end_ix, [merged]);
+}
+
+/// Remove the row from the sorted and not overlapped ranges, split the range if needed.
+fn remove_row(ranges: &mut Vec<Range<usize>>, row: usize) {
+    let ix = ranges.partition_point(|r| r.end <= row);
+    let Some(range) = ranges.get(ix).cloned() else {
+        return;
+    };
+    if !range.contains(&row) {
+        return;
+    }
+
+    let parts = [range.start..row, row + 1..range.end]
+        .into_iter()
+        .filter(|r| !r.is_empty());
+    ranges.splice(ix..ix + 1, parts);
+}
+
+/// Format the cells to the TSV text, the value contains tab, newline or quote is quoted.
+pub(crate) fn to_tsv(
+    rows: impl Iterator<Item = usize>,
+    cols: &[usize],
+    mut cell_text: impl FnMut(usize, usize) -> String,
+) -> String {
+    let mut tsv = String::new();
+    for (ix, row) in rows.enumerate() {
+        if ix > 0 {
+            tsv.push('\n');
+        }
+        for (col_ix, col) in cols.iter().enumerate() {
+            if col_ix > 0 {
+                tsv.push('\t');
+            }
+
+            let text = cell_text(row, *col);
+            if text.contains(['\t', '\n', '\r', '"']) {
+                tsv.push('"');
+                tsv.push_str(&text.replace('"', "\"\""));
+                tsv.push('"');
+            } else {
+                tsv.push_str(&text);
+            }
+        }
+    }
+    tsv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_rows() {
+        let mut selection = TableSelection::default();
+        assert!(selection.is_empty());
+
+        selection.select_row(2);
+        selection.toggle_row(5);
+        selection.toggle_row(4);
+        assert_eq!(selection.row_ranges(), &[2..3, 4..6]);
+        selection.toggle_row(3);
+        assert_eq!(selection.row_ranges(), &[2..6]);
+        assert_eq!(selection.rows_count(), 4);
+
+        selection.toggle_row(4);
+        assert_eq!(selection.row_ranges(), &[2..4, 5..6]);
+        assert!(selection.contains_row(5));
+        assert!(!selection.contains_row(4));
+        assert_eq!(selection.active_row(), Some(4));
+
+        // Shift-click from the anchor row 4.
+        selection.extend_rows_to(1, false);
+        assert_eq!(selection.row_ranges(), &[1..5]);
+        selection.extend_rows_to(8, true);
+        assert_eq!(selection.row_ranges(), &[1..9]);
+        assert_eq!(selection.rows().count(), 8);
+
+        selection.select_all_rows(10);
+        assert_eq!(selection.row_ranges(), &[0..10]);
+        assert_eq!(selection.active_row(), Some(8));
+    }
+
+    #[test]
+    fn test_select_cells() {
+        let mut selection = TableSelection::default();
+        selection.select_cell(TableCell::new(3, 2));
+        selection.extend_cells_to(TableCell::new(1, 4));
+
+        let cells = selection.cells().unwrap();
+        assert_eq!(cells.rows(), 1..4);
+        assert_eq!(cells.cols(), 2..5);
+        assert!(selection.contains_cell(2, 3));
+        assert!(!selection.contains_cell(0, 3));
+        assert!(!selection.contains_row(2));
+
+        selection.select_col(1);
+        assert_eq!(selection.cells(), None);
+        assert_eq!(selection.col(), Some(1));
+        assert_eq!(selection.active_row(), Some(1));
+    }
+
+    #[test]
+    fn test_to_tsv() {
+        let tsv = to_tsv([0, 2].into_iter(), &[1, 0], |row, col| match (row, col) {
+            (0, 1) => "a\tb".into(),
+            (2, 0) => "say \"hi\"".into(),
+            _ => format!("{}-{}", row, col),
+        });
+        assert_eq!(tsv, "\"a\tb\"\t0-0\n2-1\t\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,517 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+use gpui::SharedString;
+
+use crate::table::{ColumnFilter, ColumnSort};
+
+/// The value of a cell, used by the table to filter, sort, group and aggregate the rows.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CellValue {
+    #[default]
+    Empty,
+    Number(f64),
+    Text(SharedString),
+}
+
+impl CellValue {
+    /// Returns the number of the value, the text is parsed as a number.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Empty => None,
+            Self::Number(number) => Some(*number),
+            Self::Text(text) => text.trim().parse().ok(),
+        }
+    }
+
+    /// Returns the text of the value, the same as the `to_string`.
+    pub fn to_text(&self) -> SharedString {
+        match self {
+            Self::Text(text) => text.clone(),
+            _ => self.to_string().into(),
+        }
+    }
+
+    /// Compare the values, the empty value is the smallest, and the numbers are before the texts.
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Empty, Self::Empty) => Ordering::Equal,
+            (Self::Empty, _) => Ordering::Less,
+            (_, Self::Empty) => Ordering::Greater,
+            (Self::Number(a), Self::Number(b)) => a.total_cmp(b),
+            (Self::Number(_), Self::Text(_)) => Ordering::Less,
+            (Self::Text(_), Self::Number(_)) => Ordering::Greater,
+            (Self::Text(a), Self::Text(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => Ok(()),
+            Self::Number(number) => write!(f, "{}", number),
+            Self::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl From<f64> for CellValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i64> for CellValue {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<&str> for CellValue {
+    fn from(value: &str) -> Self {
+        Self::from(SharedString::from(value.to_string()))
+    }
+}
+
+impl From<String> for CellValue {
+    fn from(value: String) -> Self {
+        Self::from(SharedString::from(value))
+    }
+}
+
+impl From<SharedString> for CellValue {
+    fn from(value: SharedString) -> Self {
+        if value.is_empty() {
+            Self::Empty
+        } else {
+            Self::Text(value)
+        }
+    }
+}
+
+/// The aggregate function of a column, shown in the group header rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// The number of the non-empty values.
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Count => "Count",
+            Self::Sum => "Sum",
+            Self::Avg => "Avg",
+            Self::Min => "Min",
+            Self::Max => "Max",
+        }
+    }
+
+    /// Compute the aggregate of the values, returns [`CellValue::Empty`] if there is no number.
+    pub fn compute<'a>(&self, values: impl Iterator<Item = &'a CellValue>) -> CellValue {
+        if *self == Self::Count {
+            let count = values.filter(|value| **value != CellValue::Empty).count();
+            return CellValue::Number(count as f64);
+        }
+
+        let mut count = 0;
+        let mut result = 0.;
+        for number in values.filter_map(|value| value.as_number()) {
+            result = match self {
+                _ if count == 0 => number,
+                Self::Sum | Self::Avg => result + number,
+                Self::Min => result.min(number),
+                Self::Max => result.max(number),
+                Self::Count => unreachable!(),
+            };
+            count += 1;
+        }
+
+        match self {
+            _ if count == 0 => CellValue::Empty,
+            Self::Avg => CellValue::Number(result / count as f64),
+            _ => CellValue::Number(result),
+        }
+    }
+}
+
+/// A group header row of the grouped rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableGroup {
+    /// The values of the group columns from the top level group to this group,
+    /// used to identify the group.
+    pub path: Vec<SharedString>,
+    /// The column of the group.
+    pub col_ix: usize,
+    pub value: CellValue,
+    /// The number of the rows in the group.
+    pub rows_count: usize,
+    /// The aggregates of the columns in the group, by the column index.
+    pub aggregates: Vec<(usize, CellValue)>,
+    pub collapsed: bool,
+}
+
+impl TableGroup {
+    /// The nesting depth of the group, 0 for the top level group.
+    pub fn depth(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+}
+
+/// A row of the filtered, sorted and grouped rows of the table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableRow {
+    /// A row of the delegate, by the row index of the delegate.
+    Row(usize),
+    Group(Arc<TableGroup>),
+}
+
+/// The values of the columns of all rows, collected from [`crate::table::TableDelegate::cell_value`]
+/// to compute the rows in the background.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TableSnapshot {
+    pub(crate) rows_count: usize,
+    pub(crate) columns: HashMap<usize, Vec<CellValue>>,
+}
+
+static EMPTY_VALUE: CellValue = CellValue::Empty;
+
+impl TableSnapshot {
+    fn value(&self, col_ix: usize, row_ix: usize) -> &CellValue {
+        self.columns
+            .get(&col_ix)
+            .and_then(|values| values.get(row_ix))
+            .unwrap_or(&EMPTY_VALUE)
+    }
+}
+
+/// The filters, sorts and groups to compute the rows, by the column index.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TableQuery {
+    pub(crate) filters: Vec<(usize, ColumnFilter)>,
+    /// The sort columns in priority order.
+    pub(crate) sorts: Vec<(usize, ColumnSort)>,
+    pub(crate) group_by: Vec<usize>,
+    pub(crate) aggregates: Vec<(usize, Aggregate)>,
+    /// The paths of the collapsed groups.
+    pub(crate) collapsed: HashSet<Vec<SharedString>>,
+}
+
+impl TableQuery {
+    /// Returns true if the rows are the same as the delegate.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.filters.is_empty() && self.sorts.is_empty() && self.group_by.is_empty()
+    }
+
+    /// Returns the columns need to be collected to the snapshot.
+    pub(crate) fn columns(&self) -> Vec<usize> {
+        let mut columns = self
+            .filters
+            .iter()
+            .map(|(col_ix, _)| *col_ix)
+            .chain(self.sorts.iter().map(|(col_ix, _)| *col_ix))
+            .chain(self.group_by.iter().copied())
+            .chain(self.aggregates.iter().map(|(col_ix, _)| *col_ix))
+            .collect::<Vec<_>>();
+        columns.sort();
+        columns.dedup();
+        columns
+    }
+}
+
+/// Returns the index of the displayed row by the row index of the delegate, `None` for the rows
+/// filtered out or in the collapsed groups.
+pub(crate) fn display_rows(rows: &[TableRow], rows_count: usize) -> Vec<Option<usize>> {
+    let mut display_rows = vec![None; rows_count];
+    for (ix, row) in rows.iter().enumerate() {
+        if let TableRow::Row(source_ix) = row {
+            if let Some(display_row) = display_rows.get_mut(*source_ix) {
+                *display_row = Some(ix);
+            }
+        }
+    }
+    display_rows
+}
+
+/// Filter, sort and group the rows of the snapshot.
+///
+/// The rows are sorted by the group columns first, then the sort columns, the sort is stable.
+pub(crate) fn build_rows(snapshot: &TableSnapshot, query: &TableQuery) -> Vec<TableRow> {
+    let mut rows = (0..snapshot.rows_count)
+        .filter(|row_ix| {
+            query
+                .filters
+                .iter()
+                .all(|(col_ix, filter)| filter.matches(snapshot.value(*col_ix, *row_ix)))
+        })
+        .collect::<Vec<_>>();
+
+    let sort_keys = query
+        .group_by
+        .iter()
+        .map(|col_ix| (*col_ix, false))
+        .chain(query.sorts.iter().filter_map(|(col_ix, sort)| match sort {
+            ColumnSort::Ascending => Some((*col_ix, false)),
+            ColumnSort::Descending => Some((*col_ix, true)),
+            ColumnSort::Default => None,
+        }))
+        .collect::<Vec<_>>();
+    if !sort_keys.is_empty() {
+        rows.sort_by(|a, b| {
+            for (col_ix, descending) in &sort_keys {
+                let ordering = snapshot
+                    .value(*col_ix, *a)
+                    .total_cmp(snapshot.value(*col_ix, *b));
+                let ordering = if *descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+    }
+
+    if query.group_by.is_empty() {
+        return rows.into_iter().map(TableRow::Row).collect();
+    }
+
+    let mut items = Vec::with_capacity(rows.len());
+    push_groups(snapshot, query, &rows, 0, &mut vec![], &mut items);
+    items
+}
+
+/// Push the groups of the sorted `rows` by the group column at `level`, and the rows of the expanded groups.
+fn push_groups(
+    snapshot: &TableSnapshot,
+    query: &TableQuery,
+    rows: &[usize],
+    level: usize,
+    path: &mut Vec<SharedString>,
+    items: &mut Vec<TableRow>,
+) {
+    let col_ix = query.group_by[level];
+    let mut start = 0;
+    while start < rows.len() {
+        let value = snapshot.value(col_ix, rows[start]);
+        let end = start
+            + rows[start..]
+                .iter()
+                .take_while(|row_ix| {
+                    snapshot.value(col_ix, **row_ix).total_cmp(value) == Ordering::Equal
+                })
+                .count();
+        let group_rows = &rows[start..end];
+
+        path.push(value.to_text());
+        let collapsed = query.collapsed.contains(path);
+        let aggregates = query
+            .aggregates
+            .iter()
+            .map(|(agg_col_ix, aggregate)| {
+                let values = group_rows
+                    .iter()
+                    .map(|row_ix| snapshot.value(*agg_col_ix, *row_ix));
+                (*agg_col_ix, aggregate.compute(values))
+            })
+            .collect();
+        items.push(TableRow::Group(Arc::new(TableGroup {
+            path: path.clone(),
+            col_ix,
+            value: value.clone(),
+            rows_count: group_rows.len(),
+            aggregates,
+            collapsed,
+        })));
+
+        if !collapsed {
+            if level + 1 < query.group_by.len() {
+                push_groups(snapshot, query, group_rows, level + 1, path, items);
+            } else {
+                items.extend(group_rows.iter().map(|row_ix| TableRow::Row(*row_ix)));
+            }
+        }
+
+        path.pop();
+        start = end;
+    }
+}
+
+/// Returns the distinct texts of the values in order, at most `limit` values.
+pub(crate) fn distinct_values(values: &[CellValue], limit: usize) -> Vec<SharedString> {
+    let mut values = values.iter().collect::<Vec<_>>();
+    values.sort_by(|a, b| a.total_cmp(b));
+
+    let mut texts: Vec<SharedString> = vec![];
+    for value in values {
+        let text = value.to_text();
+        if texts.last() == Some(&text) {
+            continue;
+        }
+        if texts.len() >= limit {
+            break;
+        }
+        texts.push(text);
+    }
+    texts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> TableSnapshot {
+        // | name  | market | price |
+        // | AAPL  | US     | 3     |
+        // | 0700  | HK     | 1     |
+        // | MSFT  | US     | 2     |
+        // | 9988  | HK     |       |
+        // | TSLA  | US     | 5     |
+        let columns = HashMap::from_iter([
+            (
+                0,
+                ["AAPL", "0700", "MSFT", "9988", "TSLA"]
+                    .map(CellValue::from)
+                    .to_vec(),
+            ),
+            (
+                1,
+                ["US", "HK", "US", "HK", "US"].map(CellValue::from).to_vec(),
+            ),
+            (
+                2,
+                vec![
+                    CellValue::from(3.),
+                    CellValue::from(1.),
+                    CellValue::from(2.),
+                    CellValue::Empty,
+                    CellValue::from(5.),
+                ],
+            ),
+        ]);
+
+        TableSnapshot {
+            rows_count: 5,
+            columns,
+        }
+    }
+
+    fn source_rows(rows: &[TableRow]) -> Vec<Option<usize>> {
+        rows.iter()
+            .map(|row| match row {
+                TableRow::Row(row_ix) => Some(*row_ix),
+                TableRow::Group(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cell_value() {
+        assert_eq!(
+            CellValue::from("").total_cmp(&CellValue::from(1.)),
+            Ordering::Less
+        );
+        assert_eq!(
+            CellValue::from(2.).total_cmp(&CellValue::from("a")),
+            Ordering::Less
+        );
+        assert_eq!(CellValue::from(" 2.5 ").as_number(), Some(2.5));
+        assert_eq!(CellValue::from(3.).to_text(), SharedString::from("3"));
+
+        let values = [
+            CellValue::from(3.),
+            CellValue::from("x"),
+            CellValue::from(1.),
+        ];
+        assert_eq!(Aggregate::Count.compute(values.iter()), CellValue::from(3.));
+        assert_eq!(Aggregate::Sum.compute(values.iter()), CellValue::from(4.));
+        assert_eq!(Aggregate::Avg.compute(values.iter()), CellValue::from(2.));
+        assert_eq!(Aggregate::Min.compute(values.iter()), CellValue::from(1.));
+        assert_eq!(Aggregate::Max.compute(values.iter()), CellValue::from(3.));
+        assert_eq!(Aggregate::Max.compute([].iter()), CellValue::Empty);
+    }
+
+    #[test]
+    fn test_build_rows_filter_and_sort() {
+        let snapshot = snapshot();
+        let query = TableQuery {
+            filters: vec![(
+                2,
+                ColumnFilter::Range {
+                    min: Some(2.),
+                    max: None,
+                },
+            )],
+            sorts: vec![(2, ColumnSort::Descending)],
+            ..Default::default()
+        };
+        let rows = build_rows(&snapshot, &query);
+        assert_eq!(source_rows(&rows), vec![Some(4), Some(0), Some(2)]);
+
+        // Sort by market, then by name.
+        let query = TableQuery {
+            sorts: vec![(1, ColumnSort::Ascending), (0, ColumnSort::Descending)],
+            ..Default::default()
+        };
+        let rows = build_rows(&snapshot, &query);
+        assert_eq!(
+            source_rows(&rows),
+            vec![Some(3), Some(1), Some(4), Some(2), Some(0)]
+        );
+    }
+
+    #[test]
+    fn test_build_rows_group_by() {
+        let snapshot = snapshot();
+        let mut query = TableQuery {
+            group_by: vec![1],
+            sorts: vec![(2, ColumnSort::Ascending)],
+            aggregates: vec![(2, Aggregate::Sum)],
+            ..Default::default()
+        };
+        let rows = build_rows(&snapshot, &query);
+        assert_eq!(
+            source_rows(&rows),
+            vec![None, Some(3), Some(1), None, Some(2), Some(0), Some(4)]
+        );
+
+        let TableRow::Group(group) = &rows[3] else {
+            panic!("expected a group row");
+        };
+        assert_eq!(group.path, vec![SharedString::from("US")]);
+        assert_eq!(group.rows_count, 3);
+        assert_eq!(group.aggregates, vec![(2, CellValue::from(10.))]);
+
+        query.collapsed.insert(vec!["HK".into()]);
+        let rows = build_rows(&snapshot, &query);
+        assert_eq!(
+            source_rows(&rows),
+            vec![None, None, Some(2), Some(0), Some(4)]
+        );
+        assert_eq!(
+            display_rows(&rows, 5),
+            vec![Some(3), None, Some(2), None, Some(4)]
+        );
+    }
+
+    #[test]
+    fn test_distinct_values() {
+        let values = ["b", "a", "b", "", "c"].map(CellValue::from);
+        let texts =
+            |values: Vec<SharedString>| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(texts(distinct_values(&values, 10)), vec!["", "a", "b", "c"]);
+        assert_eq!(texts(distinct_values(&values, 2)), vec!["", "a"]);
+    }
+}
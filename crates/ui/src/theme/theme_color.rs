@@ -221,4 +221,42 @@ impl ThemeColor {
     pub fn dark() -> Arc<Self> {
         DEFAULT_THEME_COLORS[&ThemeMode::Dark].0.clone()
     }
+
+    /// Look up a color by its field name, e.g. `primary` or `muted_foreground`.
+    ///
+    /// Kebab-case names such as `primary-foreground` are accepted too.
+    pub fn get(&self, name: &str) -> Option<Hsla> {
+        macro_rules! lookup {
+            ($($field:ident),* $(,)?) => {
+                match name.replace('-', "_").as_str() {
+                    $(stringify!($field) => Some(self.$field),)*
+                    _ => None,
+                }
+            };
+        }
+
+        lookup!(
+            accent, accent_foreground, accordion, accordion_hover, background, border,
+            group_box, group_box_foreground, caret, chart_1, chart_2, chart_3, chart_4,
+            chart_5, danger, danger_active, danger_foreground, danger_hover,
+            description_list_label, description_list_label_foreground, drag_border,
+            drop_target, foreground, info, info_active, info_foreground, info_hover,
+            input, link, link_active, link_hover, list, list_active, list_active_border,
+            list_even, list_head, list_hover, muted, muted_foreground, popover,
+            popover_foreground, primary, primary_active, primary_foreground,
+            primary_hover, progress_bar, ring, scrollbar, scrollbar_thumb,
+            scrollbar_thumb_hover, secondary, secondary_active, secondary_foreground,
+            secondary_hover, selection, sidebar, sidebar_accent,
+            sidebar_accent_foreground, sidebar_border, sidebar_foreground,
+            sidebar_primary, sidebar_primary_foreground, skeleton, slider_bar,
+            slider_thumb, success, success_foreground, success_hover, success_active,
+            switch, tab, tab_active, tab_active_foreground, tab_bar, tab_bar_segmented,
+            tab_foreground, table, table_active, table_active_border, table_even,
+            table_head, table_head_foreground, table_hover, table_row_border, title_bar,
+            title_bar_border, tiles, warning, warning_active, warning_hover,
+            warning_foreground, overlay, window_border, red, red_light, green,
+            green_light, blue, blue_light, yellow, yellow_light, magenta, magenta_light,
+            cyan, cyan_light
+        )
+    }
 }
@@ -0,0 +1,90 @@
+use std::hash::Hash;
+
+use gpui::{App, Context, IntoElement, SharedString, Task, Window};
+
+use crate::{
+    tree::{DropPosition, TreeView},
+    Icon,
+};
+
+/// A delegate for the [`TreeView`], to provide the items of the tree.
+///
+/// The items are identified by the `Id`, and the children of an item can be loaded lazily
+/// when it is expanded the first time.
+#[allow(unused)]
+pub trait TreeDelegate: Sized + 'static {
+    /// The identifier of the items, must be unique in the tree.
+    type Id: Clone + Eq + Hash + 'static;
+
+    /// Returns the children of the item, or the root items for `None`.
+    ///
+    /// Returns `None` if the children are not loaded yet, then [`TreeDelegate::load_children`]
+    /// will be called when the item is expanded.
+    fn children(&self, parent: Option<&Self::Id>, cx: &App) -> Option<Vec<Self::Id>>;
+
+    /// Returns true if the item can have children, to show the expand toggle.
+    fn has_children(&self, id: &Self::Id, cx: &App) -> bool;
+
+    /// Load the children of the item, the [`TreeView`] shows the loading indicator until the
+    /// task is done, and then reads the children by [`TreeDelegate::children`].
+    fn load_children(
+        &mut self,
+        id: &Self::Id,
+        window: &mut Window,
+        cx: &mut Context<TreeView<Self>>,
+    ) -> Task<()> {
+        Task::ready(())
+    }
+
+    /// Returns the label of the item, used by the default [`TreeDelegate::render_item`] and
+    /// the type-ahead search.
+    fn label(&self, id: &Self::Id, cx: &App) -> SharedString;
+
+    /// Returns the icon of the item, default is None.
+    fn icon(&self, id: &Self::Id, expanded: bool, cx: &App) -> Option<Icon> {
+        None
+    }
+
+    /// Render the content of the item, after the indent, expand toggle, checkbox and icon.
+    ///
+    /// NOTE: Every item should have same height.
+    fn render_item(
+        &self,
+        id: &Self::Id,
+        window: &mut Window,
+        cx: &mut Context<TreeView<Self>>,
+    ) -> impl IntoElement {
+        self.label(id, cx)
+    }
+
+    /// Returns true if the item can be dragged, default is false.
+    fn can_drag(&self, id: &Self::Id, cx: &App) -> bool {
+        false
+    }
+
+    /// Returns true if the dragged items can be dropped at the position of the target.
+    ///
+    /// The target is never one of the dragged items or the descendants of them.
+    ///
+    /// Default only allows to drop inside the items that can have children.
+    fn can_drop(
+        &self,
+        ids: &[Self::Id],
+        target: &Self::Id,
+        position: DropPosition,
+        cx: &App,
+    ) -> bool {
+        position != DropPosition::Inside || self.has_children(target, cx)
+    }
+
+    /// Move the dragged items to the position of the target, the tree will be refreshed after.
+    fn perform_drop(
+        &mut self,
+        ids: Vec<Self::Id>,
+        target: Self::Id,
+        position: DropPosition,
+        window: &mut Window,
+        cx: &mut Context<TreeView<Self>>,
+    ) {
+    }
+}
@@ -0,0 +1,7 @@
+mod delegate;
+mod rows;
+mod tree_view;
+
+pub use delegate::*;
+pub use rows::{CheckState, DropPosition};
+pub use tree_view::*;
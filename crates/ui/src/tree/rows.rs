@@ -0,0 +1,306 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// The check state of an item in the checkable [`TreeView`](crate::tree::TreeView).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckState {
+    #[default]
+    Unchecked,
+    Checked,
+    /// Some of the children are checked.
+    Indeterminate,
+}
+
+/// The position to drop the dragged items, relative to the target item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropPosition {
+    /// Insert before the target, as a sibling.
+    Before,
+    /// Move into the target, as the last child.
+    Inside,
+    /// Insert after the target, as a sibling.
+    After,
+}
+
+impl DropPosition {
+    /// Returns the drop position by the ratio of the mouse position in the height of the row.
+    ///
+    /// The middle half of the row is [`DropPosition::Inside`] if the target can have children.
+    pub(crate) fn from_ratio(ratio: f32, can_inside: bool) -> Self {
+        match (can_inside, ratio) {
+            (true, ratio) if ratio < 0.25 => Self::Before,
+            (true, ratio) if ratio > 0.75 => Self::After,
+            (true, _) => Self::Inside,
+            (false, ratio) if ratio < 0.5 => Self::Before,
+            (false, _) => Self::After,
+        }
+    }
+}
+
+/// A displayed row of the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TreeRow<Id> {
+    pub(crate) id: Id,
+    pub(crate) depth: usize,
+    /// The row index of the parent item, `None` for the root items.
+    pub(crate) parent: Option<usize>,
+    /// The item can have children, to show the expand toggle.
+    pub(crate) expandable: bool,
+    pub(crate) expanded: bool,
+}
+
+/// Flatten the expanded items of the tree into the displayed rows, in the depth-first order.
+///
+/// The `children` returns the children of the item or the root items for `None`, and `None` if
+/// the children are not loaded yet.
+pub(crate) fn build_rows<Id>(
+    expanded: &HashSet<Id>,
+    children: impl Fn(Option<&Id>) -> Option<Vec<Id>>,
+    has_children: impl Fn(&Id) -> bool,
+) -> Vec<TreeRow<Id>>
+where
+    Id: Clone + Eq + Hash,
+{
+    let mut rows = vec![];
+    // The stack of the items to visit with the parent row, in the reverse order.
+    let mut stack = children(None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|id| (id, None, 0))
+        .collect::<Vec<_>>();
+
+    while let Some((id, parent, depth)) = stack.pop() {
+        let expandable = has_children(&id);
+        let is_expanded = expandable && expanded.contains(&id);
+        let row_ix = rows.len();
+
+        if is_expanded {
+            if let Some(items) = children(Some(&id)) {
+                stack.extend(
+                    items
+                        .into_iter()
+                        .rev()
+                        .map(|child| (child, Some(row_ix), depth + 1)),
+                );
+            }
+        }
+
+        rows.push(TreeRow {
+            id,
+            depth,
+            parent,
+            expandable,
+            expanded: is_expanded,
+        });
+    }
+
+    rows
+}
+
+/// Returns true if the row is the item or a descendant of any of the items.
+pub(crate) fn is_within<Id: PartialEq>(rows: &[TreeRow<Id>], row_ix: usize, ids: &[Id]) -> bool {
+    let mut current = Some(row_ix);
+    while let Some(ix) = current {
+        let Some(row) = rows.get(ix) else {
+            return false;
+        };
+        if ids.contains(&row.id) {
+            return true;
+        }
+        current = row.parent;
+    }
+    false
+}
+
+/// Check or uncheck the item and all of the loaded descendants of it.
+pub(crate) fn set_checked<Id>(
+    checked: &mut HashSet<Id>,
+    id: &Id,
+    value: bool,
+    children: &impl Fn(Option<&Id>) -> Option<Vec<Id>>,
+) where
+    Id: Clone + Eq + Hash,
+{
+    let mut stack = vec![id.clone()];
+    while let Some(id) = stack.pop() {
+        stack.extend(children(Some(&id)).unwrap_or_default());
+        if value {
+            checked.insert(id);
+        } else {
+            checked.remove(&id);
+        }
+    }
+}
+
+/// Returns the check states of the loaded items.
+///
+/// The item with the loaded children is checked if all of the children are checked, and
+/// indeterminate if some of them are, others are checked if they are in the `checked`.
+pub(crate) fn check_states<Id>(
+    checked: &HashSet<Id>,
+    children: &impl Fn(Option<&Id>) -> Option<Vec<Id>>,
+) -> HashMap<Id, CheckState>
+where
+    Id: Clone + Eq + Hash,
+{
+    fn visit<Id: Clone + Eq + Hash>(
+        id: &Id,
+        checked: &HashSet<Id>,
+        children: &impl Fn(Option<&Id>) -> Option<Vec<Id>>,
+        states: &mut HashMap<Id, CheckState>,
+    ) -> CheckState {
+        let items = children(Some(id)).unwrap_or_default();
+        let state = if items.is_empty() {
+            if checked.contains(id) {
+                CheckState::Checked
+            } else {
+                CheckState::Unchecked
+            }
+        } else {
+            let child_states = items
+                .iter()
+                .map(|child| visit(child, checked, children, states))
+                .collect::<Vec<_>>();
+            if child_states
+                .iter()
+                .all(|state| *state == CheckState::Checked)
+            {
+                CheckState::Checked
+            } else if child_states
+                .iter()
+                .all(|state| *state == CheckState::Unchecked)
+            {
+                CheckState::Unchecked
+            } else {
+                CheckState::Indeterminate
+            }
+        };
+
+        states.insert(id.clone(), state);
+        state
+    }
+
+    let mut states = HashMap::new();
+    for id in children(None).unwrap_or_default() {
+        visit(&id, checked, children, &mut states);
+    }
+    states
+}
+
+/// Returns the index of the first label starts with the query (case-insensitive), searching
+/// from the `start` and wrapping around.
+pub(crate) fn type_ahead_match(
+    labels: &[impl AsRef<str>],
+    query: &str,
+    start: usize,
+) -> Option<usize> {
+    if query.is_empty() || labels.is_empty() {
+        return None;
+    }
+
+    let query = query.to_lowercase();
+    let len = labels.len();
+    (0..len)
+        .map(|offset| (start + offset) % len)
+        .find(|ix| labels[*ix].as_ref().to_lowercase().starts_with(&query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ```text
+    /// 1
+    /// ├─ 2
+    /// │  └─ 4
+    /// └─ 3
+    /// 5 (not loaded)
+    /// ```
+    fn children(parent: Option<&u32>) -> Option<Vec<u32>> {
+        match parent {
+            None => Some(vec![1, 5]),
+            Some(1) => Some(vec![2, 3]),
+            Some(2) => Some(vec![4]),
+            Some(5) => None,
+            Some(_) => Some(vec![]),
+        }
+    }
+
+    fn has_children(id: &u32) -> bool {
+        matches!(id, 1 | 2 | 5)
+    }
+
+    #[test]
+    fn test_build_rows() {
+        let rows = build_rows(&HashSet::new(), children, has_children);
+        assert_eq!(rows.iter().map(|row| row.id).collect::<Vec<_>>(), [1, 5]);
+        assert!(rows[0].expandable && !rows[0].expanded);
+
+        let expanded = HashSet::from([1, 2, 5]);
+        let rows = build_rows(&expanded, children, has_children);
+        let rows = rows
+            .iter()
+            .map(|row| (row.id, row.depth, row.parent))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                (1, 0, None),
+                (2, 1, Some(0)),
+                (4, 2, Some(1)),
+                (3, 1, Some(0)),
+                (5, 0, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_within() {
+        let rows = build_rows(&HashSet::from([1, 2]), children, has_children);
+        assert!(is_within(&rows, 2, &[1]));
+        assert!(is_within(&rows, 2, &[4]));
+        assert!(!is_within(&rows, 3, &[2]));
+        assert!(!is_within(&rows, 4, &[1]));
+    }
+
+    #[test]
+    fn test_check_states() {
+        let mut checked = HashSet::new();
+        set_checked(&mut checked, &2, true, &children);
+        assert_eq!(checked, HashSet::from([2, 4]));
+
+        let states = check_states(&checked, &children);
+        assert_eq!(states[&1], CheckState::Indeterminate);
+        assert_eq!(states[&2], CheckState::Checked);
+        assert_eq!(states[&3], CheckState::Unchecked);
+        assert_eq!(states[&5], CheckState::Unchecked);
+
+        set_checked(&mut checked, &3, true, &children);
+        assert_eq!(check_states(&checked, &children)[&1], CheckState::Checked);
+
+        set_checked(&mut checked, &1, false, &children);
+        assert!(checked.is_empty());
+    }
+
+    #[test]
+    fn test_drop_position() {
+        assert_eq!(DropPosition::from_ratio(0.1, true), DropPosition::Before);
+        assert_eq!(DropPosition::from_ratio(0.5, true), DropPosition::Inside);
+        assert_eq!(DropPosition::from_ratio(0.9, true), DropPosition::After);
+        assert_eq!(DropPosition::from_ratio(0.4, false), DropPosition::Before);
+        assert_eq!(DropPosition::from_ratio(0.6, false), DropPosition::After);
+    }
+
+    #[test]
+    fn test_type_ahead_match() {
+        let labels = ["src", "Cargo.toml", "crates", "README.md"];
+        assert_eq!(type_ahead_match(&labels, "c", 0), Some(1));
+        assert_eq!(type_ahead_match(&labels, "c", 2), Some(2));
+        assert_eq!(type_ahead_match(&labels, "cr", 0), Some(2));
+        assert_eq!(type_ahead_match(&labels, "s", 1), Some(0));
+        assert_eq!(type_ahead_match(&labels, "x", 0), None);
+    }
+}
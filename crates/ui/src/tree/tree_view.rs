@@ -0,0 +1,930 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use gpui::{
+    actions, div, prelude::FluentBuilder as _, px, size, App, AppContext as _, ClickEvent, Context,
+    DragMoveEvent, EntityId, EventEmitter, FocusHandle, Focusable, InteractiveElement, IntoElement,
+    KeyBinding, KeyDownEvent, ParentElement, Pixels, Render, ScrollStrategy, SharedString,
+    StatefulInteractiveElement as _, Styled, Window,
+};
+
+use crate::{
+    actions::{Confirm, SelectNext, SelectPrev},
+    checkbox::Checkbox,
+    h_flex,
+    indicator::Indicator,
+    scroll::{Scrollbar, ScrollbarState},
+    tree::{
+        rows::{build_rows, check_states, is_within, set_checked, type_ahead_match, TreeRow},
+        CheckState, DropPosition, TreeDelegate,
+    },
+    v_flex, v_virtual_list, ActiveTheme, Icon, IconName, Sizable as _, Size, StyleSized as _,
+    VirtualListScrollHandle,
+};
+
+/// The type-ahead search is reset after this duration without typing.
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+actions!(
+    tree,
+    [
+        SelectUp,
+        SelectDown,
+        SelectFirst,
+        SelectLast,
+        SelectAll,
+        Expand,
+        Collapse,
+        ToggleCheck
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    let context = Some("TreeView");
+    cx.bind_keys([
+        KeyBinding::new("up", SelectPrev, context),
+        KeyBinding::new("down", SelectNext, context),
+        KeyBinding::new("shift-up", SelectUp, context),
+        KeyBinding::new("shift-down", SelectDown, context),
+        KeyBinding::new("home", SelectFirst, context),
+        KeyBinding::new("end", SelectLast, context),
+        KeyBinding::new("left", Collapse, context),
+        KeyBinding::new("right", Expand, context),
+        KeyBinding::new("space", ToggleCheck, context),
+        KeyBinding::new("enter", Confirm { secondary: false }, context),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-a", SelectAll, context),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-a", SelectAll, context),
+    ]);
+}
+
+#[derive(Clone)]
+pub enum TreeEvent<Id> {
+    /// The selected items have changed, with the selected items in the displayed order.
+    SelectionChanged(Vec<Id>),
+    /// Double click on the item or pressed Enter.
+    Confirm(Id),
+    Expand(Id),
+    Collapse(Id),
+    /// The checked items have changed, use [`TreeView::checked_items`] to get them.
+    CheckedChanged,
+    /// The items have been dropped, after the [`TreeDelegate::perform_drop`].
+    Drop {
+        ids: Vec<Id>,
+        target: Id,
+        position: DropPosition,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectMode {
+    /// Select only the item.
+    Single,
+    /// Toggle the item in the selection, e.g.: Cmd/Ctrl click.
+    Toggle,
+    /// Select the items from the anchor to the item, e.g.: Shift click.
+    Range,
+}
+
+/// The dragged items of the [`TreeView`].
+#[derive(Clone)]
+pub(crate) struct DragTreeItems<Id> {
+    entity_id: EntityId,
+    ids: Vec<Id>,
+    label: SharedString,
+}
+
+impl<Id: 'static> Render for DragTreeItems<Id> {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .px_3()
+            .py_1()
+            .text_sm()
+            .bg(cx.theme().popover)
+            .text_color(cx.theme().popover_foreground)
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded(cx.theme().radius)
+            .shadow_md()
+            .opacity(0.9)
+            .child(self.label.clone())
+    }
+}
+
+/// A virtualized tree, the items are provided by the [`TreeDelegate`].
+///
+/// - The children of an item are loaded lazily when it is expanded the first time.
+/// - Keyboard navigation: up/down to move, left/right to collapse/expand or move to the
+///   parent/first child, and type the label to jump to the item.
+/// - Multiple selection with Shift/Cmd click by [`TreeView::multi_select`].
+/// - Tri-state checkboxes by [`TreeView::checkable`].
+/// - Drag and drop to reorder or reparent the items by [`TreeDelegate::can_drag`].
+pub struct TreeView<D: TreeDelegate> {
+    focus_handle: FocusHandle,
+    delegate: D,
+    size: Size,
+    indent: Pixels,
+    multi_select: bool,
+    checkable: bool,
+
+    rows: Vec<TreeRow<D::Id>>,
+    item_sizes: Rc<Vec<gpui::Size<Pixels>>>,
+    expanded: HashSet<D::Id>,
+    /// The items of which the children are loading.
+    loading: HashSet<D::Id>,
+
+    selected: HashSet<D::Id>,
+    /// The item of the keyboard cursor.
+    active: Option<D::Id>,
+    /// The start item of the range selection.
+    anchor: Option<D::Id>,
+
+    /// The explicitly checked items, the check states of the parents are computed by the children.
+    checked: HashSet<D::Id>,
+    check_states: HashMap<D::Id, CheckState>,
+
+    drop_target: Option<(D::Id, DropPosition)>,
+    type_ahead: String,
+    type_ahead_at: Option<Instant>,
+
+    scroll_handle: VirtualListScrollHandle,
+    scroll_state: ScrollbarState,
+}
+
+impl<D> TreeView<D>
+where
+    D: TreeDelegate,
+{
+    pub fn new(delegate: D, _: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            focus_handle: cx.focus_handle(),
+            delegate,
+            size: Size::default(),
+            indent: px(16.),
+            multi_select: false,
+            checkable: false,
+            rows: vec![],
+            item_sizes: Rc::new(vec![]),
+            expanded: HashSet::default(),
+            loading: HashSet::default(),
+            selected: HashSet::default(),
+            active: None,
+            anchor: None,
+            checked: HashSet::default(),
+            check_states: HashMap::default(),
+            drop_target: None,
+            type_ahead: String::new(),
+            type_ahead_at: None,
+            scroll_handle: VirtualListScrollHandle::new(),
+            scroll_state: ScrollbarState::default(),
+        };
+        this.refresh(cx);
+        this
+    }
+
+    /// Set to allow selecting multiple items by Shift/Cmd click, default is false.
+    pub fn multi_select(mut self, multi_select: bool) -> Self {
+        self.multi_select = multi_select;
+        self
+    }
+
+    /// Set to show the tri-state checkboxes of the items, default is false.
+    pub fn checkable(mut self, checkable: bool) -> Self {
+        self.checkable = checkable;
+        self
+    }
+
+    /// Set the indent width of each level, default is 16px.
+    pub fn indent(mut self, indent: impl Into<Pixels>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    pub fn set_size(&mut self, size: Size, cx: &mut Context<Self>) {
+        self.size = size;
+        self.refresh(cx);
+    }
+
+    pub fn delegate(&self) -> &D {
+        &self.delegate
+    }
+
+    pub fn delegate_mut(&mut self) -> &mut D {
+        &mut self.delegate
+    }
+
+    /// Rebuild the rows from the delegate, call this after the items of the delegate changed.
+    ///
+    /// The selected items that are no longer displayed are deselected.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        let delegate = &self.delegate;
+        let app: &App = cx;
+        self.rows = build_rows(
+            &self.expanded,
+            |parent| delegate.children(parent, app),
+            |id| delegate.has_children(id, app),
+        );
+        self.item_sizes = Rc::new(vec![
+            size(px(0.), self.size.table_row_height());
+            self.rows.len()
+        ]);
+        self.update_check_states(cx);
+
+        let visible = self.rows.iter().map(|row| &row.id).collect::<HashSet<_>>();
+        let selected_count = self.selected.len();
+        self.selected.retain(|id| visible.contains(id));
+        if self
+            .active
+            .as_ref()
+            .map_or(false, |id| !visible.contains(id))
+        {
+            self.active = None;
+        }
+        if self
+            .anchor
+            .as_ref()
+            .map_or(false, |id| !visible.contains(id))
+        {
+            self.anchor = None;
+        }
+
+        if self.selected.len() != selected_count {
+            cx.emit(TreeEvent::SelectionChanged(self.selected_items()));
+        }
+        cx.notify();
+    }
+
+    fn row_ix(&self, id: &D::Id) -> Option<usize> {
+        self.rows.iter().position(|row| row.id == *id)
+    }
+
+    fn active_row(&self) -> Option<usize> {
+        self.row_ix(self.active.as_ref()?)
+    }
+
+    /// Returns true if the item is expanded.
+    pub fn is_expanded(&self, id: &D::Id) -> bool {
+        self.expanded.contains(id)
+    }
+
+    /// Expand or collapse the item, the children are loaded if they are not loaded yet.
+    pub fn set_expanded(
+        &mut self,
+        id: &D::Id,
+        expanded: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if expanded == self.expanded.contains(id) {
+            return;
+        }
+
+        if expanded {
+            self.expanded.insert(id.clone());
+            if self.delegate.children(Some(id), cx).is_none() {
+                self.load_children(id, window, cx);
+            }
+            self.refresh(cx);
+            cx.emit(TreeEvent::Expand(id.clone()));
+        } else {
+            // Move the cursor to the item if it will be hidden.
+            let active_hidden = self.active_row().map_or(false, |ix| {
+                self.rows[ix].id != *id && is_within(&self.rows, ix, std::slice::from_ref(id))
+            });
+
+            self.expanded.remove(id);
+            self.refresh(cx);
+            if active_hidden {
+                if let Some(row_ix) = self.row_ix(id) {
+                    self.select_row(row_ix, SelectMode::Single, cx);
+                }
+            }
+            cx.emit(TreeEvent::Collapse(id.clone()));
+        }
+    }
+
+    /// Toggle the expanded state of the item.
+    pub fn toggle_expanded(&mut self, id: &D::Id, window: &mut Window, cx: &mut Context<Self>) {
+        let expanded = self.is_expanded(id);
+        self.set_expanded(id, !expanded, window, cx);
+    }
+
+    fn load_children(&mut self, id: &D::Id, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.loading.insert(id.clone()) {
+            return;
+        }
+
+        let task = self.delegate.load_children(id, window, cx);
+        let id = id.clone();
+        cx.spawn(async move |this, cx| {
+            task.await;
+            _ = this.update(cx, |this, cx| {
+                this.loading.remove(&id);
+                // The loaded children inherit the check of the parent.
+                if this.checked.contains(&id) {
+                    this.set_checked_with_children(&id, true, cx);
+                }
+                this.refresh(cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Returns the selected items, in the displayed order.
+    pub fn selected_items(&self) -> Vec<D::Id> {
+        self.rows
+            .iter()
+            .filter(|row| self.selected.contains(&row.id))
+            .map(|row| row.id.clone())
+            .collect()
+    }
+
+    /// Set the selected items, the items that are not displayed are ignored.
+    pub fn set_selected_items(
+        &mut self,
+        ids: impl IntoIterator<Item = D::Id>,
+        cx: &mut Context<Self>,
+    ) {
+        self.selected = ids
+            .into_iter()
+            .filter(|id| self.row_ix(id).is_some())
+            .collect();
+        self.active = self.selected_items().into_iter().next();
+        self.anchor = self.active.clone();
+        if let Some(row_ix) = self.active_row() {
+            self.scroll_handle
+                .scroll_to_item(row_ix, ScrollStrategy::Top);
+        }
+        cx.emit(TreeEvent::SelectionChanged(self.selected_items()));
+        cx.notify();
+    }
+
+    fn select_row(&mut self, row_ix: usize, mode: SelectMode, cx: &mut Context<Self>) {
+        let Some(row) = self.rows.get(row_ix) else {
+            return;
+        };
+        let id = row.id.clone();
+        let mode = if self.multi_select {
+            mode
+        } else {
+            SelectMode::Single
+        };
+
+        match mode {
+            SelectMode::Single => {
+                self.selected = HashSet::from_iter([id.clone()]);
+                self.anchor = Some(id.clone());
+            }
+            SelectMode::Toggle => {
+                if !self.selected.remove(&id) {
+                    self.selected.insert(id.clone());
+                }
+                self.anchor = Some(id.clone());
+            }
+            SelectMode::Range => {
+                let anchor_ix = self
+                    .anchor
+                    .as_ref()
+                    .and_then(|anchor| self.row_ix(anchor))
+                    .unwrap_or(row_ix);
+                self.selected = self.rows[anchor_ix.min(row_ix)..=anchor_ix.max(row_ix)]
+                    .iter()
+                    .map(|row| row.id.clone())
+                    .collect();
+            }
+        }
+
+        self.active = Some(id);
+        self.scroll_handle
+            .scroll_to_item(row_ix, ScrollStrategy::Top);
+        cx.emit(TreeEvent::SelectionChanged(self.selected_items()));
+        cx.notify();
+    }
+
+    /// Returns the check state of the item.
+    pub fn check_state(&self, id: &D::Id) -> CheckState {
+        match self.check_states.get(id) {
+            Some(state) => *state,
+            None if self.checked.contains(id) => CheckState::Checked,
+            None => CheckState::Unchecked,
+        }
+    }
+
+    /// Returns the checked items in no particular order, including the parents of which all
+    /// children are checked.
+    pub fn checked_items(&self) -> Vec<D::Id> {
+        let mut items = self
+            .check_states
+            .iter()
+            .filter(|(_, state)| **state == CheckState::Checked)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        // The checked items of which the parents are not loaded.
+        items.extend(
+            self.checked
+                .iter()
+                .filter(|id| !self.check_states.contains_key(id))
+                .cloned(),
+        );
+        items
+    }
+
+    /// Check or uncheck the item and all of the loaded descendants of it.
+    pub fn set_checked(&mut self, id: &D::Id, checked: bool, cx: &mut Context<Self>) {
+        self.set_checked_with_children(id, checked, cx);
+        self.update_check_states(cx);
+        cx.emit(TreeEvent::CheckedChanged);
+        cx.notify();
+    }
+
+    fn set_checked_with_children(&mut self, id: &D::Id, checked: bool, cx: &App) {
+        let delegate = &self.delegate;
+        set_checked(&mut self.checked, id, checked, &|parent| {
+            delegate.children(parent, cx)
+        });
+    }
+
+    fn update_check_states(&mut self, cx: &App) {
+        if !self.checkable {
+            return;
+        }
+
+        let delegate = &self.delegate;
+        self.check_states = check_states(&self.checked, &|parent| delegate.children(parent, cx));
+    }
+
+    fn on_action_select_prev(&mut self, _: &SelectPrev, _: &mut Window, cx: &mut Context<Self>) {
+        let row_ix = self.active_row().map_or(0, |ix| ix.saturating_sub(1));
+        self.select_row(row_ix, SelectMode::Single, cx);
+    }
+
+    fn on_action_select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let last_ix = self.rows.len().saturating_sub(1);
+        let row_ix = self.active_row().map_or(0, |ix| (ix + 1).min(last_ix));
+        self.select_row(row_ix, SelectMode::Single, cx);
+    }
+
+    fn on_action_select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        let row_ix = self.active_row().map_or(0, |ix| ix.saturating_sub(1));
+        self.select_row(row_ix, SelectMode::Range, cx);
+    }
+
+    fn on_action_select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        let last_ix = self.rows.len().saturating_sub(1);
+        let row_ix = self.active_row().map_or(0, |ix| (ix + 1).min(last_ix));
+        self.select_row(row_ix, SelectMode::Range, cx);
+    }
+
+    fn on_action_select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(0, SelectMode::Single, cx);
+    }
+
+    fn on_action_select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_row(self.rows.len().saturating_sub(1), SelectMode::Single, cx);
+    }
+
+    fn on_action_select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.multi_select {
+            return;
+        }
+
+        let ids = self
+            .rows
+            .iter()
+            .map(|row| row.id.clone())
+            .collect::<Vec<_>>();
+        self.set_selected_items(ids, cx);
+    }
+
+    fn on_action_expand(&mut self, _: &Expand, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(row_ix) = self.active_row() else {
+            return;
+        };
+        let row = &self.rows[row_ix];
+        if !row.expandable {
+            return;
+        }
+
+        if !row.expanded {
+            let id = row.id.clone();
+            self.set_expanded(&id, true, window, cx);
+        } else if self
+            .rows
+            .get(row_ix + 1)
+            .map_or(false, |next| next.parent == Some(row_ix))
+        {
+            self.select_row(row_ix + 1, SelectMode::Single, cx);
+        }
+    }
+
+    fn on_action_collapse(&mut self, _: &Collapse, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(row_ix) = self.active_row() else {
+            return;
+        };
+        let row = &self.rows[row_ix];
+
+        if row.expanded {
+            let id = row.id.clone();
+            self.set_expanded(&id, false, window, cx);
+        } else if let Some(parent_ix) = row.parent {
+            self.select_row(parent_ix, SelectMode::Single, cx);
+        }
+    }
+
+    fn on_action_confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(row_ix) = self.active_row() else {
+            return;
+        };
+        self.confirm_row(row_ix, window, cx);
+    }
+
+    fn on_action_toggle_check(&mut self, _: &ToggleCheck, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.checkable {
+            return;
+        }
+
+        let mut ids = self.selected_items();
+        if ids.is_empty() {
+            ids.extend(self.active.clone());
+        }
+        let checked = !ids
+            .iter()
+            .all(|id| self.check_state(id) == CheckState::Checked);
+        for id in &ids {
+            self.set_checked_with_children(id, checked, cx);
+        }
+
+        self.update_check_states(cx);
+        cx.emit(TreeEvent::CheckedChanged);
+        cx.notify();
+    }
+
+    /// Jump to the next item of which the label starts with the typed text.
+    fn on_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.control || keystroke.modifiers.platform || keystroke.modifiers.alt {
+            return;
+        }
+        let Some(text) = keystroke
+            .key_char
+            .as_ref()
+            .filter(|text| !text.trim().is_empty())
+        else {
+            return;
+        };
+
+        let now = Instant::now();
+        if self
+            .type_ahead_at
+            .map_or(true, |at| now.duration_since(at) > TYPE_AHEAD_TIMEOUT)
+        {
+            self.type_ahead.clear();
+        }
+        self.type_ahead_at = Some(now);
+        self.type_ahead.push_str(text);
+
+        // Start from the next item for the first char, to cycle the items with the same char.
+        let start = match self.active_row() {
+            Some(ix) if self.type_ahead.chars().count() == 1 => ix + 1,
+            Some(ix) => ix,
+            None => 0,
+        };
+        let labels = self
+            .rows
+            .iter()
+            .map(|row| self.delegate.label(&row.id, cx))
+            .collect::<Vec<_>>();
+        if let Some(row_ix) = type_ahead_match(&labels, &self.type_ahead, start) {
+            self.select_row(row_ix, SelectMode::Single, cx);
+        }
+        cx.stop_propagation();
+    }
+
+    fn confirm_row(&mut self, row_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(row) = self.rows.get(row_ix) else {
+            return;
+        };
+        let id = row.id.clone();
+        if row.expandable {
+            self.toggle_expanded(&id, window, cx);
+        }
+        cx.emit(TreeEvent::Confirm(id));
+    }
+
+    fn on_row_click(
+        &mut self,
+        row_ix: usize,
+        e: &ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_handle.focus(window);
+
+        let modifiers = e.modifiers();
+        let mode = if modifiers.shift {
+            SelectMode::Range
+        } else if modifiers.secondary() {
+            SelectMode::Toggle
+        } else {
+            SelectMode::Single
+        };
+        self.select_row(row_ix, mode, cx);
+
+        if e.click_count() == 2 {
+            self.confirm_row(row_ix, window, cx);
+        }
+    }
+
+    /// Returns the items to drag from the row, the selected items if the row is selected.
+    fn drag_items(&self, row_ix: usize, cx: &Context<Self>) -> DragTreeItems<D::Id> {
+        let id = &self.rows[row_ix].id;
+        let ids = if self.selected.contains(id) {
+            self.selected_items()
+        } else {
+            vec![id.clone()]
+        };
+        let label = match ids.len() {
+            1 => self.delegate.label(&ids[0], cx),
+            count => format!("{} items", count).into(),
+        };
+
+        DragTreeItems {
+            entity_id: cx.entity_id(),
+            ids,
+            label,
+        }
+    }
+
+    fn update_drop_target(
+        &mut self,
+        row_ix: usize,
+        drag: &DragTreeItems<D::Id>,
+        ratio: f32,
+        cx: &mut Context<Self>,
+    ) {
+        if drag.entity_id != cx.entity_id() {
+            return;
+        }
+        let Some(row) = self.rows.get(row_ix) else {
+            return;
+        };
+
+        let drop_target = if is_within(&self.rows, row_ix, &drag.ids) {
+            None
+        } else {
+            let can_inside = row.expandable
+                && self
+                    .delegate
+                    .can_drop(&drag.ids, &row.id, DropPosition::Inside, cx);
+            let position = DropPosition::from_ratio(ratio, can_inside);
+            (position == DropPosition::Inside
+                || self.delegate.can_drop(&drag.ids, &row.id, position, cx))
+            .then(|| (row.id.clone(), position))
+        };
+
+        if self.drop_target != drop_target {
+            self.drop_target = drop_target;
+            cx.notify();
+        }
+    }
+
+    fn on_drop(
+        &mut self,
+        drag: &DragTreeItems<D::Id>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((target, position)) = self.drop_target.take() else {
+            return;
+        };
+        if drag.entity_id != cx.entity_id() {
+            return;
+        }
+
+        self.delegate
+            .perform_drop(drag.ids.clone(), target.clone(), position, window, cx);
+        if position == DropPosition::Inside {
+            self.set_expanded(&target, true, window, cx);
+        }
+        self.refresh(cx);
+        cx.emit(TreeEvent::Drop {
+            ids: drag.ids.clone(),
+            target,
+            position,
+        });
+    }
+
+    fn render_row(
+        &self,
+        row_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let row = &self.rows[row_ix];
+        let id = row.id.clone();
+        let selected = self.selected.contains(&id);
+        let active = self.active.as_ref() == Some(&id) && self.focus_handle.is_focused(window);
+        let loading = self.loading.contains(&id);
+        let indent = self.indent * row.depth as f32;
+        let drop_position = self
+            .drop_target
+            .as_ref()
+            .filter(|(target, _)| *target == id)
+            .map(|(_, position)| *position);
+
+        h_flex()
+            .id(("tree-item", row_ix))
+            .relative()
+            .w_full()
+            .h(self.size.table_row_height())
+            .pl(indent + px(4.))
+            .pr_2()
+            .gap_1()
+            .input_text_size(self.size)
+            .text_color(cx.theme().foreground)
+            .map(|this| {
+                if selected {
+                    this.bg(cx.theme().list_active)
+                } else {
+                    this.hover(|this| this.bg(cx.theme().list_hover))
+                }
+            })
+            .child(
+                h_flex()
+                    .id("toggle")
+                    .size_4()
+                    .flex_shrink_0()
+                    .justify_center()
+                    .when(row.expandable, |this| {
+                        this.map(|this| {
+                            if loading {
+                                this.child(Indicator::new().xsmall())
+                            } else {
+                                this.child(
+                                    Icon::new(if row.expanded {
+                                        IconName::ChevronDown
+                                    } else {
+                                        IconName::ChevronRight
+                                    })
+                                    .xsmall()
+                                    .text_color(cx.theme().muted_foreground),
+                                )
+                            }
+                        })
+                        .on_click({
+                            let id = id.clone();
+                            cx.listener(move |this, _, window, cx| {
+                                cx.stop_propagation();
+                                this.toggle_expanded(&id, window, cx);
+                            })
+                        })
+                    }),
+            )
+            .when(self.checkable, |this| {
+                let state = self.check_state(&id);
+                this.child(
+                    Checkbox::new("check")
+                        .small()
+                        .checked(state == CheckState::Checked)
+                        .indeterminate(state == CheckState::Indeterminate)
+                        .on_click({
+                            let id = id.clone();
+                            cx.listener(move |this, checked: &bool, _, cx| {
+                                this.set_checked(&id, *checked, cx);
+                            })
+                        }),
+                )
+            })
+            .children(self.delegate.icon(&id, row.expanded, cx))
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .truncate()
+                    .child(self.delegate.render_item(&id, window, cx)),
+            )
+            .when(active, |this| {
+                this.child(
+                    div()
+                        .absolute()
+                        .top_0()
+                        .left_0()
+                        .size_full()
+                        .border_1()
+                        .border_color(cx.theme().list_active_border),
+                )
+            })
+            .when_some(drop_position, |this, position| {
+                let line = div()
+                    .absolute()
+                    .left(indent)
+                    .right_0()
+                    .h(px(2.))
+                    .bg(cx.theme().drag_border);
+
+                match position {
+                    DropPosition::Before => this.child(line.top_0()),
+                    DropPosition::After => this.child(line.bottom_0()),
+                    DropPosition::Inside => this.child(
+                        div()
+                            .absolute()
+                            .top_0()
+                            .left_0()
+                            .size_full()
+                            .bg(cx.theme().drop_target)
+                            .border_1()
+                            .border_color(cx.theme().drag_border),
+                    ),
+                }
+            })
+            .on_click(cx.listener(move |this, e: &ClickEvent, window, cx| {
+                this.on_row_click(row_ix, e, window, cx);
+            }))
+            .when(self.delegate.can_drag(&id, cx), |this| {
+                this.on_drag(self.drag_items(row_ix, cx), |drag, _, _, cx| {
+                    cx.stop_propagation();
+                    cx.new(|_| drag.clone())
+                })
+            })
+            .on_drag_move(cx.listener(
+                move |this, e: &DragMoveEvent<DragTreeItems<D::Id>>, _, cx| {
+                    if !e.bounds.contains(&e.event.position) {
+                        return;
+                    }
+
+                    let ratio = (e.event.position.y - e.bounds.top()) / e.bounds.size.height;
+                    let drag = e.drag(cx).clone();
+                    this.update_drop_target(row_ix, &drag, ratio, cx);
+                },
+            ))
+            .on_drop(
+                cx.listener(move |this, drag: &DragTreeItems<D::Id>, window, cx| {
+                    this.on_drop(drag, window, cx);
+                }),
+            )
+    }
+}
+
+impl<D> Focusable for TreeView<D>
+where
+    D: TreeDelegate,
+{
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl<D> EventEmitter<TreeEvent<D::Id>> for TreeView<D> where D: TreeDelegate {}
+
+impl<D> Render for TreeView<D>
+where
+    D: TreeDelegate,
+{
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // The drag has been dropped outside of the rows.
+        if self.drop_target.is_some() && !cx.has_active_drag() {
+            self.drop_target = None;
+        }
+
+        v_flex()
+            .id("tree-view")
+            .key_context("TreeView")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .relative()
+            .overflow_hidden()
+            .on_action(cx.listener(Self::on_action_select_prev))
+            .on_action(cx.listener(Self::on_action_select_next))
+            .on_action(cx.listener(Self::on_action_select_up))
+            .on_action(cx.listener(Self::on_action_select_down))
+            .on_action(cx.listener(Self::on_action_select_first))
+            .on_action(cx.listener(Self::on_action_select_last))
+            .on_action(cx.listener(Self::on_action_select_all))
+            .on_action(cx.listener(Self::on_action_expand))
+            .on_action(cx.listener(Self::on_action_collapse))
+            .on_action(cx.listener(Self::on_action_confirm))
+            .on_action(cx.listener(Self::on_action_toggle_check))
+            .on_key_down(cx.listener(Self::on_key_down))
+            .child(
+                v_virtual_list(
+                    cx.entity().clone(),
+                    "tree-rows",
+                    self.item_sizes.clone(),
+                    |tree, visible_range, window, cx| {
+                        visible_range
+                            .map(|row_ix| tree.render_row(row_ix, window, cx))
+                            .collect::<Vec<_>>()
+                    },
+                )
+                .track_scroll(&self.scroll_handle),
+            )
+            .child(Scrollbar::uniform_scroll(
+                &self.scroll_state,
+                &self.scroll_handle,
+            ))
+    }
+}
@@ -0,0 +1,476 @@
+//! Drives [`LspConnection`] and [`LspClient`] against a fake language server.
+//!
+//! This test has no harness: run with `--fake-server`, the binary is the server.
+
+use std::{
+    io::{BufRead, Read, Write},
+    time::Duration,
+};
+
+use gpui::{Entity, TestAppContext, VisualTestContext};
+use gpui_component::input::{
+    CodeActionProvider, CompletionProvider, InputState, LspClient, LspConnection, LspMessage,
+    SignatureHelpProvider,
+};
+use lsp_types::{
+    notification::{DidChangeTextDocument, DidOpenTextDocument, Exit, PublishDiagnostics},
+    request::{Completion, Initialize, Request, Shutdown},
+    CompletionContext, CompletionParams, CompletionResponse, CompletionTriggerKind,
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, InitializeParams, ParameterLabel,
+    Position, PositionEncodingKind, Range, SignatureHelpContext, SignatureHelpTriggerKind,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+    VersionedTextDocumentIdentifier,
+};
+use serde_json::{json, Value};
+
+/// Return the text of the document in the fake server.
+enum DocumentText {}
+
+impl Request for DocumentText {
+    type Params = ();
+    type Result = String;
+    const METHOD: &'static str = "fake/text";
+}
+
+fn main() {
+    if std::env::args().any(|arg| arg == "--fake-server") {
+        fake_server(std::env::args().any(|arg| arg == "--utf-16"));
+        return;
+    }
+
+    smol::block_on(test_connection());
+    test_client(&mut TestAppContext::single());
+    println!("lsp_client: ok");
+}
+
+async fn test_connection() {
+    let mut command = std::process::Command::new(std::env::current_exe().unwrap());
+    command.arg("--fake-server");
+    let (connection, messages, io) = LspConnection::spawn(command).unwrap();
+    let io = smol::spawn(io);
+
+    let result = connection
+        .request::<Initialize>(InitializeParams::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        result.capabilities.text_document_sync,
+        Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL
+        ))
+    );
+
+    let uri: Uri = "file:///main.rs".parse().unwrap();
+    connection
+        .notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "rust".into(),
+                0,
+                "let a = 1;\n// TODO\n".into(),
+            ),
+        })
+        .unwrap();
+
+    let diagnostics = next_diagnostics(&messages).await;
+    assert_eq!(diagnostics.version, Some(0));
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    assert_eq!(diagnostics.diagnostics[0].range.start, Position::new(1, 3));
+
+    // Positions are in characters.
+    connection
+        .notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 1),
+            content_changes: vec![
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(0, 4), Position::new(0, 5))),
+                    range_length: None,
+                    text: "中文".into(),
+                },
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(0, 6), Position::new(0, 6))),
+                    range_length: None,
+                    text: " // TODO".into(),
+                },
+            ],
+        })
+        .unwrap();
+
+    let diagnostics = next_diagnostics(&messages).await;
+    assert_eq!(diagnostics.version, Some(1));
+    assert_eq!(diagnostics.diagnostics.len(), 2);
+    assert_eq!(diagnostics.diagnostics[0].range.start, Position::new(0, 10));
+
+    let text = connection.request::<DocumentText>(()).await.unwrap();
+    assert_eq!(text, "let 中文 // TODO = 1;\n// TODO\n");
+
+    let completions = connection
+        .request::<Completion>(CompletionParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri),
+                Position::new(1, 0),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        })
+        .await
+        .unwrap();
+    let Some(CompletionResponse::Array(items)) = completions else {
+        panic!("expected completion items");
+    };
+    let labels = items
+        .iter()
+        .map(|item| item.label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, vec!["let", "中文", "TODO"]);
+
+    let err = connection
+        .request::<DocumentText>(())
+        .await
+        .expect_err("the text request fails without a document");
+    assert!(err.to_string().contains("no document"));
+
+    connection.request::<Shutdown>(()).await.unwrap();
+    connection.notify::<Exit>(()).unwrap();
+    io.await.unwrap();
+}
+
+/// The server counts in `utf-16`, so the client converts every position.
+fn test_client(cx: &mut TestAppContext) {
+    // The fake server is a real process.
+    cx.executor().allow_parking();
+    cx.update(gpui_component::init);
+
+    let mut command = std::process::Command::new(std::env::current_exe().unwrap());
+    command.args(["--fake-server", "--utf-16"]);
+    let start = cx.update(|cx| LspClient::start(command, None, cx));
+    let client = cx.executor().block(start).unwrap();
+    assert_eq!(client.position_encoding(), PositionEncodingKind::UTF16);
+
+    let (editor, cx) = cx.add_window_view(|window, cx| {
+        let mut state = InputState::new(window, cx).code_editor("rust");
+        state.set_value("let level = \"😀 TODO\";\n", window, cx);
+        state
+    });
+    let uri: Uri = "file:///main.rs".parse().unwrap();
+    cx.update(|window, cx| client.open(&editor, uri, "rust", window, cx))
+        .unwrap();
+    wait_for_diagnostics(cx, &editor, 1);
+
+    // Synced with a change starting after the emoji.
+    editor.update_in(cx, |state, window, cx| {
+        state.set_value("let level = \"😀😀 TODO TODO\";\n😀lev", window, cx);
+    });
+    wait_for_diagnostics(cx, &editor, 2);
+
+    // The server completes the word before the position.
+    let completions = editor.update_in(cx, |state, window, cx| {
+        let text = state.text().clone();
+        let context = CompletionContext {
+            trigger_kind: CompletionTriggerKind::INVOKED,
+            trigger_character: None,
+        };
+        client.completions(&text, text.len(), context, window, cx)
+    });
+    let completions = cx.executor().block(completions).unwrap();
+    let labels = completions
+        .into_iter()
+        .flat_map(|response| match response {
+            CompletionResponse::Array(items) => items,
+            CompletionResponse::List(list) => list.items,
+        })
+        .map(|item| item.label)
+        .collect::<Vec<_>>();
+    assert_eq!(labels, vec!["level"]);
+
+    // The code action replaces the diagnostics with a workspace edit.
+    let len = editor.read_with(cx, |state, _| state.text().len());
+    let actions = cx.update(|window, cx| client.code_actions(editor.clone(), 0..len, window, cx));
+    let mut actions = cx.executor().block(actions).unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].title, "Fix 2 TODO");
+
+    let perform = cx.update(|window, cx| {
+        client.perform_code_action(editor.clone(), actions.remove(0), true, window, cx)
+    });
+    cx.executor().block(perform).unwrap();
+    wait_for_diagnostics(cx, &editor, 0);
+
+    let text = editor.read_with(cx, |state, _| state.text().to_string());
+    assert_eq!(text, "let level = \"😀😀 DONE DONE\";\n😀lev");
+    let server_text = cx
+        .executor()
+        .block(client.connection().request::<DocumentText>(()))
+        .unwrap();
+    assert_eq!(server_text, text);
+
+    // The label offsets of the parameters are converted to bytes.
+    let help = editor.update_in(cx, |state, window, cx| {
+        let text = state.text().clone();
+        let context = SignatureHelpContext {
+            trigger_kind: SignatureHelpTriggerKind::INVOKED,
+            trigger_character: None,
+            is_retrigger: false,
+            active_signature_help: None,
+        };
+        client.signature_help(&text, 0, context, window, cx)
+    });
+    let help = cx.executor().block(help).unwrap().unwrap();
+    let labels = help.signatures[0]
+        .parameters
+        .iter()
+        .flatten()
+        .map(|parameter| parameter.label.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            ParameterLabel::LabelOffsets([8, 14]),
+            ParameterLabel::LabelOffsets([16, 22]),
+        ]
+    );
+
+    let shutdown = cx.update(|_, cx| client.shutdown(cx));
+    cx.executor().block(shutdown).unwrap();
+}
+
+/// Wait until the editor shows `count` diagnostics, published by the server.
+fn wait_for_diagnostics(cx: &mut VisualTestContext, editor: &Entity<InputState>, count: usize) {
+    for _ in 0..500 {
+        cx.run_until_parked();
+        let len = editor.read_with(cx, |state, _| {
+            state
+                .diagnostics()
+                .map_or(0, |diagnostics| diagnostics.len())
+        });
+        if len == count {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("expected {} diagnostics", count);
+}
+
+async fn next_diagnostics(
+    messages: &smol::channel::Receiver<LspMessage>,
+) -> lsp_types::PublishDiagnosticsParams {
+    loop {
+        let message = messages.recv().await.unwrap();
+        if let Some(params) = message.notification::<PublishDiagnostics>() {
+            return params;
+        }
+    }
+}
+
+/// A language server keeping one document in sync, which reports every `TODO`
+/// in it, completes the words starting with the one before the position, fixes the `TODO`s with a code action
+/// and has the signature help of one function.
+///
+/// The positions are in characters, or in `utf-16` code units with `--utf-16`.
+fn fake_server(utf16: bool) {
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    let mut text: Option<String> = None;
+    let mut text_requests = 0;
+
+    loop {
+        let Some(message) = read_message(&mut reader) else {
+            return;
+        };
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or_default();
+
+        match message["method"].as_str().unwrap_or_default() {
+            "initialize" => respond(
+                id,
+                json!({
+                    "capabilities": {
+                        "positionEncoding": if utf16 { "utf-16" } else { "utf-32" },
+                        "textDocumentSync": 2,
+                        "completionProvider": { "triggerCharacters": ["."] },
+                        "codeActionProvider": true,
+                        "signatureHelpProvider": { "triggerCharacters": ["("] },
+                    }
+                }),
+            ),
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                text = Some(document["text"].as_str().unwrap().to_string());
+                publish_diagnostics(document, text.as_deref().unwrap(), utf16);
+            }
+            "textDocument/didChange" => {
+                let document = text.as_mut().unwrap();
+                for change in params["contentChanges"].as_array().unwrap() {
+                    let range = change["range"].clone();
+                    let start = offset(document, &range["start"], utf16);
+                    let end = offset(document, &range["end"], utf16);
+                    document.replace_range(start..end, change["text"].as_str().unwrap());
+                }
+                publish_diagnostics(&params["textDocument"], document, utf16);
+            }
+            "textDocument/completion" => {
+                let text = text.as_deref().unwrap_or_default();
+                let is_word = |c: char| c.is_alphanumeric() || c == '_';
+                let position = offset(text, &params["position"], utf16);
+                let prefix = text[..position]
+                    .rsplit(|c: char| !is_word(c))
+                    .next()
+                    .unwrap_or_default();
+
+                let mut words: Vec<&str> = vec![];
+                for word in text.split(|c: char| !is_word(c)) {
+                    if word.chars().any(char::is_alphabetic)
+                        && word.starts_with(prefix)
+                        && word != prefix
+                        && !words.contains(&word)
+                    {
+                        words.push(word);
+                    }
+                }
+                let items = words
+                    .iter()
+                    .map(|word| json!({ "label": word }))
+                    .collect::<Vec<_>>();
+                respond(id, json!(items));
+            }
+            "textDocument/codeAction" => {
+                let diagnostics = params["context"]["diagnostics"].as_array().unwrap();
+                let edits = diagnostics
+                    .iter()
+                    .map(|diagnostic| json!({ "range": diagnostic["range"], "newText": "DONE" }))
+                    .collect::<Vec<_>>();
+                let uri = params["textDocument"]["uri"].as_str().unwrap();
+                respond(
+                    id,
+                    json!([{
+                        "title": format!("Fix {} TODO", edits.len()),
+                        "edit": { "changes": { uri: edits } },
+                    }]),
+                );
+            }
+            "textDocument/signatureHelp" => {
+                let label = "fn 😀(a: i32, b: i32)";
+                let parameters = ["a: i32", "b: i32"]
+                    .iter()
+                    .map(|parameter| {
+                        let start = column(&label[..label.find(parameter).unwrap()], utf16);
+                        json!({ "label": [start, start + column(parameter, utf16)] })
+                    })
+                    .collect::<Vec<_>>();
+                respond(
+                    id,
+                    json!({
+                        "signatures": [{ "label": label, "parameters": parameters }],
+                        "activeParameter": 1,
+                    }),
+                );
+            }
+            "fake/text" => {
+                text_requests += 1;
+                if text_requests > 1 {
+                    text = None;
+                }
+                match text.as_ref() {
+                    Some(text) => respond(id, json!(text)),
+                    None => write_message(&json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32602, "message": "no document" },
+                    })),
+                }
+            }
+            "shutdown" => respond(id, Value::Null),
+            "exit" => return,
+            _ => {}
+        }
+    }
+}
+
+fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    let mut content_len = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(len) = line.strip_prefix("Content-Length:") {
+            content_len = len.trim().parse().ok()?;
+        }
+    }
+
+    let mut buf = vec![0; content_len];
+    reader.read_exact(&mut buf).ok()?;
+    serde_json::from_slice(&buf).ok()
+}
+
+fn write_message(message: &Value) {
+    let body = message.to_string();
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    stdout.flush().unwrap();
+}
+
+fn respond(id: Option<Value>, result: Value) {
+    write_message(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+}
+
+fn publish_diagnostics(document: &Value, text: &str, utf16: bool) {
+    let diagnostics = text
+        .lines()
+        .enumerate()
+        .flat_map(|(line, content)| {
+            content.match_indices("TODO").map(move |(ix, _)| {
+                let character = column(&content[..ix], utf16);
+                json!({
+                    "range": {
+                        "start": { "line": line, "character": character },
+                        "end": { "line": line, "character": character + 4 },
+                    },
+                    "severity": 2,
+                    "message": "TODO",
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    write_message(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {
+            "uri": document["uri"],
+            "version": document["version"],
+            "diagnostics": diagnostics,
+        },
+    }));
+}
+
+/// The length of the text in chars, or in `utf-16` code units.
+fn column(text: &str, utf16: bool) -> usize {
+    if utf16 {
+        text.encode_utf16().count()
+    } else {
+        text.chars().count()
+    }
+}
+
+/// The byte offset of a position, with the character in chars or in `utf-16` code units.
+fn offset(text: &str, position: &Value, utf16: bool) -> usize {
+    let line = position["line"].as_u64().unwrap() as usize;
+    let character = position["character"].as_u64().unwrap() as usize;
+    let line_start = text
+        .split_inclusive('\n')
+        .take(line)
+        .map(str::len)
+        .sum::<usize>();
+    text[line_start..]
+        .char_indices()
+        .find(|(ix, _)| column(&text[line_start..line_start + ix], utf16) >= character)
+        .map_or(text.len(), |(ix, _)| line_start + ix)
+}